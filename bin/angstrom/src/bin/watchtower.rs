@@ -0,0 +1,11 @@
+// We use jemalloc for performance reasons
+#[cfg(all(feature = "jemalloc", unix))]
+#[global_allocator]
+static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
+
+fn main() {
+    if let Err(err) = angstrom::run_watchtower() {
+        eprintln!("Error: {err:?}");
+        std::process::exit(1);
+    }
+}
@@ -1,42 +1,343 @@
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf};
 
-use alloy_primitives::Address;
+use alloy::signers::local::PrivateKeySigner;
+use alloy_primitives::{hex, Address};
 use angstrom_metrics::initialize_prometheus_metrics;
-use angstrom_types::contract_bindings::angstrom::Angstrom::PoolKey;
+use angstrom_network::PropagationPolicy;
+use angstrom_notifier::NotifierConfig;
+use angstrom_types::{
+    contract_bindings::angstrom::Angstrom::PoolKey,
+    contract_payloads::rewards::SurplusDistribution,
+    orders::SortStrategy,
+    primitive::{
+        AngstromSigner, DeploymentConfig, DeploymentRegistry, Feature, FeatureFlags, PeerId,
+        RemoteSigner, TransferTax, ValidatorStake, DEFAULT_BLOCK_TIME_SECS
+    }
+};
+use consensus::SubmissionApprovalConfig;
 use eyre::Context;
+use k256::ecdsa::VerifyingKey;
+use matching_engine::solver::SolverKind;
 use serde::Deserialize;
 use url::Url;
 
 #[derive(Debug, Clone, Default, clap::Args)]
 pub struct AngstromConfig {
     #[clap(long)]
-    pub mev_guard:           bool,
+    pub mev_guard:                  bool,
+    /// file holding the key of the node, not needed when it is held by a
+    /// remote signer
+    #[clap(long, required_unless_present = "remote_signer_url")]
+    pub secret_key_location:        Option<PathBuf>,
+    /// Web3Signer compatible signer holding the key of the node, which then
+    /// signs the consensus messages and settlement transactions in its place
+    #[clap(long, requires = "remote_signer_public_key", conflicts_with = "secret_key_location")]
+    pub remote_signer_url:          Option<Url>,
+    /// hex encoded public key of the node's key on the remote signer
+    #[clap(long)]
+    pub remote_signer_public_key:   Option<String>,
+    /// file the consensus messages signed by this node are recorded in, used
+    /// to refuse signing conflicting messages after a restart.
+    /// Default: `signing_guard.jsonl` next to the secret key, or in the working
+    /// directory with a remote signer
+    #[clap(long)]
+    pub signing_guard_path:         Option<PathBuf>,
+    /// file the bundles submitted by this node are recorded in, used to refuse
+    /// submitting a second bundle for a height after a restart.
+    /// Default: `submission_ledger.jsonl` next to the secret key, or in the
+    /// working directory with a remote signer
+    #[clap(long)]
+    pub submission_ledger_path:     Option<PathBuf>,
+    /// file the pending orders are written to on shutdown and restored from on
+    /// the next start.
+    /// Default: `order_snapshot.json` next to the secret key, or in the
+    /// working directory with a remote signer
     #[clap(long)]
-    pub secret_key_location: PathBuf,
+    pub order_snapshot_path:        Option<PathBuf>,
+    /// file every order entering or leaving the pool is appended to. The books
+    /// of past blocks are rebuilt from it to serve `debug_getPoolSnapshot`,
+    /// which is only served when this is set
     #[clap(long)]
-    pub angstrom_addr:       Option<Address>,
+    pub order_archive_path:         Option<PathBuf>,
+    /// file the last block turned into eth events is stored in, the eth ExEx
+    /// resumes after it on the next start.
+    /// Default: `exex_cursor` next to the secret key, or in the working
+    /// directory with a remote signer
     #[clap(long)]
-    pub pool_manager_addr:   Option<Address>,
+    pub exex_cursor_path:           Option<PathBuf>,
     #[clap(long)]
-    pub node_config:         PathBuf,
+    pub angstrom_addr:              Option<Address>,
+    #[clap(long)]
+    pub pool_manager_addr:          Option<Address>,
+    #[clap(long)]
+    pub node_config:                PathBuf,
     /// enables the metrics
     #[clap(long, default_value = "false", global = true)]
-    pub metrics:             bool,
+    pub metrics:                    bool,
     /// spawns the prometheus metrics exporter at the specified port
     /// Default: 6969
     #[clap(long, default_value = "6969", global = true)]
-    pub metrics_port:        u16,
+    pub metrics_port:               u16,
     #[clap(short, long, default_value = "https://rpc.flashbots.net")]
-    pub mev_boost_endpoints: Vec<Url>
+    pub mev_boost_endpoints:        Vec<Url>,
+    /// keep the books of the pools between blocks and only update them with
+    /// the orders that changed, for pools with thousands of resting orders.
+    /// Same as `--enable-feature incremental-matching`
+    #[clap(long)]
+    pub incremental_matching:       bool,
+    /// run-time feature enabled on start, can be repeated. Features can be
+    /// switched later with `admin_setFeatureFlag`
+    #[clap(long = "enable-feature")]
+    pub enabled_features:           Vec<Feature>,
+    /// run-time feature disabled on start, can be repeated
+    #[clap(long = "disable-feature")]
+    pub disabled_features:          Vec<Feature>,
+    /// solver the books of the pools are solved with, `lp` is only available
+    /// with the `lp-solver` feature
+    #[clap(long, default_value_t = SolverKind::VolumeFill)]
+    pub solver:                     SolverKind,
+    /// basis points the surplus of a leader's proposal may fall short of the
+    /// one of our own solve before the proposal is rejected
+    #[clap(long, default_value_t = consensus::DEFAULT_MAX_SURPLUS_SHORTFALL_BPS)]
+    pub max_surplus_shortfall_bps:  u32,
+    /// basis points of the orders of a pre-proposal that may be unknown to us
+    /// before the pre-proposal is rejected
+    #[clap(long, default_value_t = consensus::DEFAULT_MAX_UNKNOWN_ORDERS_BPS)]
+    pub max_unknown_orders_bps:     u32,
+    /// basis points a pool's UCP may deviate from its AMM TWAP before matching
+    /// for the pool is paused
+    #[clap(long, default_value_t = consensus::DEFAULT_MAX_UCP_DEVIATION_BPS)]
+    pub max_ucp_deviation_bps:      u32,
+    /// consecutive blocks a pool may fail to settle in before matching for it
+    /// is paused
+    #[clap(long, default_value_t = consensus::DEFAULT_MAX_FAILED_SETTLEMENTS)]
+    pub max_failed_settlements:     u32,
+    /// simulate our bundles in every layout and submit the one that costs
+    /// the least gas
+    #[clap(long)]
+    pub golf_bundles:               bool,
+    /// send our solve time, book size and validation latency of every block
+    /// to the other validators
+    #[clap(long)]
+    pub share_telemetry:            bool,
+    /// peer id of a validator that has to approve our settlement transactions
+    /// before they are broadcast, can be repeated. Every validator should use
+    /// the same approvers
+    #[clap(long = "submission-approver")]
+    pub submission_approvers:       Vec<PeerId>,
+    /// approvers each settlement transaction needs
+    #[clap(
+        long = "submission-approval-threshold",
+        requires = "submission_approvers",
+        default_value_t = 1
+    )]
+    pub approval_threshold:         usize,
+    /// audit the leaders instead of taking part in consensus, raising alerts
+    /// on bad proposals and settlements. Validators only gossip rounds to each
+    /// other, so this needs the key of a validator of the deployment
+    #[clap(long)]
+    pub watchtower:                 bool,
+    /// url the watchtower POSTs its alerts to as json, can be repeated. Only
+    /// used with `--watchtower` or by `angstrom-watchtower`
+    #[clap(long = "watchtower-webhook")]
+    pub watchtower_webhooks:        Vec<Url>,
+    /// address a warm standby of this validator connects to. We stop taking
+    /// part in consensus while a standby follows us without acking
+    #[clap(long, conflicts_with_all = ["replica_of", "watchtower"])]
+    pub replica_listen:             Option<SocketAddr>,
+    /// run as the warm standby of the validator listening at this address,
+    /// taking over once it is gone. Needs the key of the validator
+    #[clap(long, conflicts_with = "watchtower")]
+    pub replica_of:                 Option<SocketAddr>,
+    /// url order events and consensus anomalies are POSTed to as json, can be
+    /// repeated
+    #[clap(long = "notify-webhook")]
+    pub notify_webhooks:            Vec<Url>,
+    /// file holding the secret the notifications are signed with
+    #[clap(long, requires = "notify_webhooks")]
+    pub notify_secret_location:     Option<PathBuf>,
+    /// attempts to deliver a notification before it is dropped
+    #[clap(long, default_value_t = angstrom_notifier::DEFAULT_MAX_ATTEMPTS)]
+    pub notify_max_attempts:        u32,
+    /// seconds orders stay in the books of the network, unless their deadline
+    /// comes first. Every node of the network should use the same value
+    #[clap(long)]
+    pub order_ttl_secs:             Option<u64>,
+    /// megabytes the order pool, validation caches and uniswap ticks may use
+    /// together before they start shedding load
+    #[clap(long)]
+    pub memory_budget_mb:           Option<usize>,
+    /// megabytes the order pool may use before it stops taking new orders
+    #[clap(long)]
+    pub order_pool_memory_mb:       Option<usize>,
+    /// megabytes each of the validation caches may use before it is dropped
+    #[clap(long)]
+    pub validation_cache_memory_mb: Option<usize>,
+    /// json rpc endpoint orders and bundles are validated against instead of
+    /// the node's database, e.g. an archive node run by a third party
+    #[clap(long)]
+    pub validation_rpc_url:         Option<Url>,
+    /// TOML config of who the orders are gossiped to, per order category. Every
+    /// order goes to every peer without it
+    #[clap(long)]
+    pub gossip_config:              Option<PathBuf>,
+    /// TOML config of the intake of orders relayed from other chains, which is
+    /// only served when this is set
+    #[clap(long)]
+    pub relay_config:               Option<PathBuf>,
+    /// TOML config of the FIX order entry gateway, which is only served when
+    /// this is set
+    #[cfg(feature = "fix-gateway")]
+    #[clap(long, requires = "fix_signing_key_location")]
+    pub fix_config:                 Option<PathBuf>,
+    /// file holding the key orders entered over FIX are signed with
+    #[cfg(feature = "fix-gateway")]
+    #[clap(long)]
+    pub fix_signing_key_location:   Option<PathBuf>
+}
+
+impl AngstromConfig {
+    /// `file_name` next to the secret key, or in the working directory when
+    /// the key is held by a remote signer.
+    pub fn default_path(&self, file_name: &str) -> PathBuf {
+        match &self.secret_key_location {
+            Some(location) => location.with_file_name(file_name),
+            None => PathBuf::from(file_name)
+        }
+    }
+
+    /// The run-time features of the node, `--incremental-matching` enables
+    /// [`Feature::IncrementalMatching`].
+    pub fn feature_flags(&self) -> eyre::Result<FeatureFlags> {
+        if let Some(feature) = self
+            .enabled_features
+            .iter()
+            .find(|feature| self.disabled_features.contains(feature))
+        {
+            eyre::bail!("feature {feature} is both enabled and disabled")
+        }
+
+        let incremental = self
+            .incremental_matching
+            .then_some(Feature::IncrementalMatching);
+        let enabled = self.enabled_features.iter().copied().chain(incremental);
+        let disabled = self.disabled_features.iter().copied();
+
+        Ok(FeatureFlags::new(
+            enabled
+                .map(|feature| (feature, true))
+                .chain(disabled.map(|feature| (feature, false)))
+        ))
+    }
+
+    /// Who approves the settlement transactions, none without a
+    /// `--submission-approver`.
+    pub fn submission_approval(&self) -> eyre::Result<Option<SubmissionApprovalConfig>> {
+        if self.submission_approvers.is_empty() {
+            return Ok(None)
+        }
+        if self.approval_threshold > self.submission_approvers.len() {
+            eyre::bail!(
+                "a threshold of {} can't be met by {} approvers",
+                self.approval_threshold,
+                self.submission_approvers.len()
+            )
+        }
+
+        Ok(Some(SubmissionApprovalConfig::new(
+            self.submission_approvers.iter().copied(),
+            self.approval_threshold
+        )))
+    }
+
+    /// Where order events and consensus anomalies are sent, none without a
+    /// `--notify-webhook`.
+    pub fn notifier_config(&self) -> eyre::Result<Option<NotifierConfig>> {
+        if self.notify_webhooks.is_empty() {
+            return Ok(None)
+        }
+
+        let mut config = NotifierConfig::new(self.notify_webhooks.clone())
+            .with_max_attempts(self.notify_max_attempts);
+        if let Some(path) = &self.notify_secret_location {
+            let secret = std::fs::read_to_string(path)
+                .wrap_err_with(|| format!("failed to read the notifier secret at {path:?}"))?;
+            config = config.with_secret(secret.trim().as_bytes().to_vec());
+        }
+
+        Ok(Some(config))
+    }
+
+    /// The key the node signs with, read from `--secret-key-location` or held
+    /// by the remote signer.
+    pub fn signer(&self) -> eyre::Result<AngstromSigner> {
+        if let Some(url) = &self.remote_signer_url {
+            let public_key = self
+                .remote_signer_public_key
+                .as_deref()
+                .ok_or_else(|| eyre::eyre!("--remote-signer-url requires a public key"))?;
+            let public_key = VerifyingKey::from_sec1_bytes(&hex::decode(public_key.trim())?)
+                .wrap_err("invalid remote signer public key")?;
+
+            return Ok(AngstromSigner::remote(RemoteSigner::new(url.clone(), public_key)?))
+        }
+
+        let sk_path = self
+            .secret_key_location
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("either a secret key or a remote signer is required"))?;
+        match sk_path.try_exists() {
+            Ok(true) => {
+                let contents = std::fs::read_to_string(sk_path)?;
+                Ok(AngstromSigner::new(contents.trim().parse::<PrivateKeySigner>()?))
+            }
+            _ => Err(eyre::eyre!("no secret_key was found at {:?}", sk_path))
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct NodeConfig {
-    pub secret_key:           String,
-    pub angstrom_address:     Address,
-    pub periphery_addr:       Address,
-    pub pool_manager_address: Address,
-    pub pools:                Vec<PoolKey>
+    pub secret_key: String,
+    /// JSON list of deployments to resolve the contract addresses from. When
+    /// unset, the deployments shipped with the node are used.
+    #[serde(default)]
+    pub deployments_file: Option<PathBuf>,
+    /// overrides the angstrom address of the resolved deployment
+    #[serde(default)]
+    pub angstrom_address: Option<Address>,
+    /// overrides the controller address of the resolved deployment
+    #[serde(default)]
+    pub periphery_addr: Option<Address>,
+    /// overrides the pool manager address of the resolved deployment
+    #[serde(default)]
+    pub pool_manager_address: Option<Address>,
+    /// overrides how the books of the resolved deployment are sorted. Every
+    /// validator of the deployment has to use the same strategy
+    #[serde(default)]
+    pub book_sort: Option<SortStrategy>,
+    /// overrides whether the resolved deployment simulates the pools of a
+    /// wide proposal on their own first. Every validator of the deployment
+    /// has to use the same setting
+    #[serde(default)]
+    pub parallel_bundle_simulation: Option<bool>,
+    /// overrides the fee-on-transfer tokens of the resolved deployment, as
+    /// `<token>:<bps>`. Every validator of the deployment has to use the same
+    /// list
+    #[serde(default)]
+    pub transfer_taxes: Option<Vec<TransferTax>>,
+    /// overrides the shares of the surplus of user orders the resolved
+    /// deployment charges for the LPs and the protocol. Every validator of the
+    /// deployment has to use the same shares
+    #[serde(default)]
+    pub surplus_distribution: Option<SurplusDistribution>,
+    /// overrides the block time of the resolved deployment's chain, in seconds
+    #[serde(default)]
+    pub block_time_secs: Option<u64>,
+    /// overrides the validator set of the resolved deployment
+    #[serde(default)]
+    pub validators: Option<Vec<ValidatorStake>>,
+    pub pools: Vec<PoolKey>
 }
 
 impl NodeConfig {
@@ -55,6 +356,99 @@ impl NodeConfig {
 
         Ok(node_config)
     }
+
+    /// The deployment this node runs against on `chain_id`, the chain reth
+    /// was started on with `--chain`. If all three addresses are set in the
+    /// config no registry is needed, otherwise the missing ones, and the chain
+    /// parameters not set in the config, are taken from the registry entry for
+    /// the chain.
+    pub fn deployment(&self, chain_id: u64) -> eyre::Result<DeploymentConfig> {
+        if let (Some(angstrom_address), Some(controller_address), Some(pool_manager_address)) =
+            (self.angstrom_address, self.periphery_addr, self.pool_manager_address)
+        {
+            return Ok(DeploymentConfig {
+                chain_id,
+                angstrom_address,
+                controller_address,
+                pool_manager_address,
+                book_sort: self.book_sort.unwrap_or(SortStrategy::ByPriceByVolume),
+                parallel_bundle_simulation: self.parallel_bundle_simulation.unwrap_or_default(),
+                transfer_taxes: self.transfer_taxes.clone().unwrap_or_default(),
+                surplus_distribution: self.surplus_distribution.unwrap_or_default(),
+                block_time_secs: self.block_time_secs.unwrap_or(DEFAULT_BLOCK_TIME_SECS),
+                validators: self.validators.clone().unwrap_or_default()
+            })
+        }
+
+        let registry = match &self.deployments_file {
+            Some(path) => DeploymentRegistry::load(path)
+                .wrap_err_with(|| format!("Could not load deployments from {:?}", path))?,
+            None => DeploymentRegistry::embedded()
+        };
+        let deployment = registry.get(chain_id)?;
+
+        Ok(DeploymentConfig {
+            chain_id,
+            angstrom_address: self.angstrom_address.unwrap_or(deployment.angstrom_address),
+            controller_address: self.periphery_addr.unwrap_or(deployment.controller_address),
+            pool_manager_address: self
+                .pool_manager_address
+                .unwrap_or(deployment.pool_manager_address),
+            book_sort: self.book_sort.unwrap_or(deployment.book_sort),
+            parallel_bundle_simulation: self
+                .parallel_bundle_simulation
+                .unwrap_or(deployment.parallel_bundle_simulation),
+            transfer_taxes: self
+                .transfer_taxes
+                .clone()
+                .unwrap_or(deployment.transfer_taxes),
+            surplus_distribution: self
+                .surplus_distribution
+                .unwrap_or(deployment.surplus_distribution),
+            block_time_secs: self.block_time_secs.unwrap_or(deployment.block_time_secs),
+            validators: self.validators.clone().unwrap_or(deployment.validators)
+        })
+    }
+}
+
+/// The config of the relayed order intake at `path`.
+pub fn load_relay_config(path: &std::path::Path) -> eyre::Result<order_pool::RelayConfig> {
+    let toml_content = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Could not read relay config {:?}", path))?;
+    let config = toml::from_str(&toml_content)
+        .wrap_err_with(|| format!("Could not deserialize relay config {:?}", path))?;
+
+    Ok(config)
+}
+
+/// The order gossip policy at `path`.
+pub fn load_gossip_config(path: &std::path::Path) -> eyre::Result<PropagationPolicy> {
+    let toml_content = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Could not read gossip config {:?}", path))?;
+    let config = toml::from_str(&toml_content)
+        .wrap_err_with(|| format!("Could not deserialize gossip config {:?}", path))?;
+
+    Ok(config)
+}
+
+/// The FIX gateway config at `path` along with the key its orders are signed
+/// with.
+#[cfg(feature = "fix-gateway")]
+pub fn load_fix_gateway_config(
+    path: &std::path::Path,
+    signing_key_location: &std::path::Path
+) -> eyre::Result<(angstrom_fix_gateway::FixGatewayConfig, alloy::signers::local::PrivateKeySigner)>
+{
+    let toml_content = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Could not read fix gateway config {:?}", path))?;
+    let config = toml::from_str(&toml_content)
+        .wrap_err_with(|| format!("Could not deserialize fix gateway config {:?}", path))?;
+
+    let signing_key = std::fs::read_to_string(signing_key_location)
+        .wrap_err_with(|| format!("Could not read fix signing key {:?}", signing_key_location))?;
+    let signer = signing_key.trim().parse()?;
+
+    Ok((config, signer))
 }
 
 pub async fn init_metrics(metrics_port: u16) {
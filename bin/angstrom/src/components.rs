@@ -1,34 +1,58 @@
 //! CLI definition and entrypoint to executable
 
-use std::{collections::HashSet, sync::Arc};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration
+};
 
 use alloy::{
     self,
     eips::{BlockId, BlockNumberOrTag},
+    primitives::{Address, BlockNumber},
     providers::{network::Ethereum, Provider, ProviderBuilder}
 };
-use alloy_chains::Chain;
 use angstrom_eth::{
+    backfill::filled_orders_in_range,
+    exex::{ChainUpdate, ExExCursor},
     handle::{Eth, EthCommand},
     manager::{EthDataCleanser, EthEvent}
 };
 use angstrom_network::{
     manager::StromConsensusEvent,
     pool_manager::{OrderCommand, PoolHandle},
-    NetworkBuilder as StromNetworkBuilder, NetworkOrderEvent, PoolManagerBuilder, StatusState,
+    NetworkBuilder as StromNetworkBuilder, NetworkOrderEvent, NetworkStatsHandle,
+    NetworkStatsRequest, PoolManagerBuilder, ResumptionCache, StatusState, StromNetworkHandle,
     VerificationSidecar
 };
+use angstrom_notifier::Notifier;
 use angstrom_types::{
     block_sync::{BlockSyncProducer, GlobalBlockSync},
     contract_bindings::controller_v_1::ControllerV1,
     contract_payloads::angstrom::{AngstromPoolConfigStore, UniswapAngstromRegistry},
     mev_boost::MevBoostProvider,
-    primitive::{AngstromSigner, PeerId, UniswapPoolRegistry},
+    orders::OrderOrigin,
+    primitive::{
+        AngstromSigner, ConfigUpdate, DeploymentConfig, FeatureFlags, OrderPoolNewOrderResult,
+        PeerId, TransferTaxes, UniswapPoolRegistry
+    },
     reth_db_wrapper::RethDbWrapper
 };
-use consensus::{AngstromValidator, ConsensusManager, ManagerNetworkDeps};
+use angstrom_utils::memory_budget::{MemoryBudget, MEMORY_CHECK_INTERVAL};
+use consensus::{
+    replica::{ReplicaConfig, ReplicaPrimary, ReplicaStandby},
+    AlertSink, AngstromValidator, CircuitBreakerConfig, ConsensusHandle, ConsensusManager,
+    ConsensusQueryHandle, ConsensusRequest, ContentRules, ManagerNetworkDeps, SigningGuard,
+    SubmissionLedger, SurplusPolicy, Watchtower
+};
+use futures::Stream;
 use matching_engine::{configure_uniswap_manager, manager::MatcherCommand, MatchingManager};
-use order_pool::{order_storage::OrderStorage, PoolConfig, PoolManagerUpdate};
+use order_pool::{
+    order_storage::OrderStorage, read_order_snapshot, OrderArchive, OrderPoolHandle,
+    OrderStorageView, PoolConfig, PoolManagerUpdate, ORDER_COMMAND_CHANNEL_SIZE,
+    ORDER_UPDATE_CHANNEL_SIZE
+};
 use reth::{
     api::NodeAddOns,
     builder::FullNodeComponents,
@@ -39,12 +63,17 @@ use reth::{
 };
 use reth_metrics::common::mpsc::{UnboundedMeteredReceiver, UnboundedMeteredSender};
 use reth_node_builder::{node::FullNodeTypes, rpc::RethRpcAddOns, FullNode, NodeTypes};
-use reth_provider::BlockReader;
+use reth_provider::{BlockHashReader, BlockReader};
 use tokio::sync::mpsc::{
     channel, unbounded_channel, Receiver, Sender, UnboundedReceiver, UnboundedSender
 };
+use tokio_stream::wrappers::BroadcastStream;
+use uniswap_v4::uniswap::{
+    pool_history::{serve_pool_history, PoolHistoryHandle, PoolHistoryRequest},
+    pool_manager::SyncedUniswapPools
+};
 use validation::{
-    common::TokenPriceGenerator,
+    common::{RemoteDb, SettlementCostEstimator, TokenPriceGenerator},
     init_validation,
     order::state::pools::AngstromPoolsTracker,
     validator::{ValidationClient, ValidationRequest}
@@ -54,19 +83,21 @@ use crate::{cli::NodeConfig, AngstromConfig};
 
 pub fn init_network_builder(
     secret_key: AngstromSigner,
+    chain_id: u64,
     eth_handle: UnboundedReceiver<EthEvent>
 ) -> eyre::Result<StromNetworkBuilder> {
     let public_key = secret_key.id();
 
-    let state = StatusState {
-        version:   0,
-        chain:     Chain::mainnet().id(),
-        peer:      public_key,
-        timestamp: 0
-    };
+    let state =
+        StatusState { version: 0, chain: chain_id, peer: public_key, timestamp: 0 };
 
-    let verification =
-        VerificationSidecar { status: state, has_sent: false, has_received: false, secret_key };
+    let verification = VerificationSidecar {
+        status: state,
+        has_sent: false,
+        has_received: false,
+        secret_key,
+        resumption: ResumptionCache::default()
+    };
 
     Ok(StromNetworkBuilder::new(verification, eth_handle))
 }
@@ -82,8 +113,8 @@ pub struct StromHandles {
     pub pool_tx: UnboundedMeteredSender<NetworkOrderEvent>,
     pub pool_rx: UnboundedMeteredReceiver<NetworkOrderEvent>,
 
-    pub orderpool_tx: UnboundedSender<DefaultOrderCommand>,
-    pub orderpool_rx: UnboundedReceiver<DefaultOrderCommand>,
+    pub orderpool_tx: Sender<DefaultOrderCommand>,
+    pub orderpool_rx: Receiver<DefaultOrderCommand>,
 
     pub validator_tx: UnboundedSender<ValidationRequest>,
     pub validator_rx: UnboundedReceiver<ValidationRequest>,
@@ -92,10 +123,27 @@ pub struct StromHandles {
     pub eth_handle_rx: Option<UnboundedReceiver<EthEvent>>,
 
     pub pool_manager_tx: tokio::sync::broadcast::Sender<PoolManagerUpdate>,
+    /// what pool handles read orders from, the order storage publishes to it
+    /// once it is created
+    pub order_view:      OrderStorageView,
 
     pub consensus_tx_op: UnboundedMeteredSender<StromConsensusEvent>,
     pub consensus_rx_op: UnboundedMeteredReceiver<StromConsensusEvent>,
 
+    pub consensus_query_tx: UnboundedSender<ConsensusRequest>,
+    pub consensus_query_rx: UnboundedReceiver<ConsensusRequest>,
+
+    pub pool_history_tx: UnboundedSender<PoolHistoryRequest>,
+    pub pool_history_rx: UnboundedReceiver<PoolHistoryRequest>,
+
+    pub network_stats_tx: UnboundedSender<NetworkStatsRequest>,
+    pub network_stats_rx: UnboundedReceiver<NetworkStatsRequest>,
+
+    /// fed by bundle validation, read by the searcher rpc
+    pub settlement_costs: SettlementCostEstimator,
+
+    pub config_tx: tokio::sync::broadcast::Sender<ConfigUpdate>,
+
     // only 1 set cur
     pub matching_tx: Sender<MatcherCommand>,
     pub matching_rx: Receiver<MatcherCommand>
@@ -105,21 +153,38 @@ impl StromHandles {
     pub fn get_pool_handle(&self) -> DefaultPoolHandle {
         PoolHandle {
             manager_tx:      self.orderpool_tx.clone(),
-            pool_manager_tx: self.pool_manager_tx.clone()
+            pool_manager_tx: self.pool_manager_tx.clone(),
+            view:            self.order_view.clone()
         }
     }
+
+    pub fn get_consensus_handle(&self) -> ConsensusQueryHandle {
+        ConsensusQueryHandle::new(self.consensus_query_tx.clone())
+    }
+
+    pub fn get_pool_history_handle(&self) -> PoolHistoryHandle {
+        PoolHistoryHandle::new(self.pool_history_tx.clone())
+    }
+
+    pub fn get_network_stats_handle(&self) -> NetworkStatsHandle {
+        NetworkStatsHandle::new(self.network_stats_tx.clone())
+    }
 }
 
 pub fn initialize_strom_handles() -> StromHandles {
     let (eth_tx, eth_rx) = channel(100);
     let (matching_tx, matching_rx) = channel(100);
-    let (pool_manager_tx, _) = tokio::sync::broadcast::channel(100);
+    let (pool_manager_tx, _) = tokio::sync::broadcast::channel(ORDER_UPDATE_CHANNEL_SIZE);
     let (pool_tx, pool_rx) = reth_metrics::common::mpsc::metered_unbounded_channel("orderpool");
-    let (orderpool_tx, orderpool_rx) = unbounded_channel();
+    let (orderpool_tx, orderpool_rx) = channel(ORDER_COMMAND_CHANNEL_SIZE);
     let (validator_tx, validator_rx) = unbounded_channel();
     let (eth_handle_tx, eth_handle_rx) = unbounded_channel();
     let (consensus_tx_op, consensus_rx_op) =
         reth_metrics::common::mpsc::metered_unbounded_channel("orderpool");
+    let (consensus_query_tx, consensus_query_rx) = unbounded_channel();
+    let (pool_history_tx, pool_history_rx) = unbounded_channel();
+    let (network_stats_tx, network_stats_rx) = unbounded_channel();
+    let (config_tx, _) = tokio::sync::broadcast::channel(16);
 
     StromHandles {
         eth_tx,
@@ -131,8 +196,17 @@ pub fn initialize_strom_handles() -> StromHandles {
         validator_tx,
         validator_rx,
         pool_manager_tx,
+        order_view: OrderStorageView::default(),
         consensus_tx_op,
         consensus_rx_op,
+        consensus_query_tx,
+        consensus_query_rx,
+        pool_history_tx,
+        pool_history_rx,
+        network_stats_tx,
+        network_stats_rx,
+        settlement_costs: SettlementCostEstimator::default(),
+        config_tx,
         matching_tx,
         matching_rx,
         eth_handle_tx: Some(eth_handle_tx),
@@ -140,11 +214,33 @@ pub fn initialize_strom_handles() -> StromHandles {
     }
 }
 
+/// The angstrom deployment of `chain_id`, with the addresses overridden on the
+/// command line.
+pub fn resolve_deployment(
+    config: &AngstromConfig,
+    chain_id: u64
+) -> eyre::Result<DeploymentConfig> {
+    let node_config = NodeConfig::load_from_config(Some(config.node_config.clone()))?;
+    let mut deployment = node_config.deployment(chain_id)?;
+    if let Some(angstrom_address) = config.angstrom_addr {
+        deployment.angstrom_address = angstrom_address;
+    }
+    if let Some(pool_manager_address) = config.pool_manager_addr {
+        deployment.pool_manager_address = pool_manager_address;
+    }
+
+    Ok(deployment)
+}
+
 pub async fn initialize_strom_components<Node, AddOns>(
     config: AngstromConfig,
+    deployment: DeploymentConfig,
     signer: AngstromSigner,
     mut handles: StromHandles,
     network_builder: StromNetworkBuilder,
+    chain_updates: impl Stream<Item = ChainUpdate> + Send + 'static,
+    exex_cursor: ExExCursor,
+    feature_flags: FeatureFlags,
     node: FullNode<Node, AddOns>,
     executor: &TaskExecutor
 ) where
@@ -157,7 +253,7 @@ pub async fn initialize_strom_components<Node, AddOns>(
     >,
     AddOns: NodeAddOns<Node> + RethRpcAddOns<Node>
 {
-    let node_config = NodeConfig::load_from_config(Some(config.node_config)).unwrap();
+    tracing::info!(?deployment, "resolved angstrom deployment");
     let node_address = signer.address();
 
     // NOTE:
@@ -194,7 +290,7 @@ pub async fn initialize_strom_components<Node, AddOns>(
 
     let pool_config_store = Arc::new(
         AngstromPoolConfigStore::load_from_chain(
-            node_config.angstrom_address,
+            deployment.angstrom_address,
             BlockId::Number(BlockNumberOrTag::Latest),
             &querying_provider
         )
@@ -206,7 +302,7 @@ pub async fn initialize_strom_components<Node, AddOns>(
     let uni_ang_registry =
         UniswapAngstromRegistry::new(uniswap_registry.clone(), pool_config_store.clone());
 
-    let periphery_c = ControllerV1::new(node_config.periphery_addr, querying_provider.clone());
+    let periphery_c = ControllerV1::new(deployment.controller_address, querying_provider.clone());
     let node_set = periphery_c
         .nodes()
         .call()
@@ -219,9 +315,10 @@ pub async fn initialize_strom_components<Node, AddOns>(
     // Build our PoolManager using the PoolConfig and OrderStorage we've already
     // created
     let eth_handle = EthDataCleanser::spawn(
-        node_config.angstrom_address,
-        node_config.periphery_addr,
-        node.provider.subscribe_to_canonical_state(),
+        deployment.angstrom_address,
+        deployment.controller_address,
+        chain_updates,
+        Some(exex_cursor),
         executor.clone(),
         handles.eth_tx,
         handles.eth_rx,
@@ -239,12 +336,17 @@ pub async fn initialize_strom_components<Node, AddOns>(
         uniswap_registry,
         block_id,
         global_block_sync.clone(),
-        node_config.pool_manager_address
+        deployment.pool_manager_address
     )
     .await;
 
     let uniswap_pools = uniswap_pool_manager.pools();
     executor.spawn(Box::pin(uniswap_pool_manager));
+    executor.spawn(Box::pin(serve_pool_history(
+        uniswap_pools.clone(),
+        querying_provider.clone(),
+        handles.pool_history_rx
+    )));
     let price_generator =
         TokenPriceGenerator::new(querying_provider.clone(), block_id, uniswap_pools.clone(), None)
             .await
@@ -252,33 +354,82 @@ pub async fn initialize_strom_components<Node, AddOns>(
 
     let block_height = node.provider.best_block_number().unwrap();
 
-    init_validation(
-        RethDbWrapper::new(node.provider.clone()),
-        block_height,
-        node_config.angstrom_address,
-        node_address,
-        // Because this is incapsulated under the orderpool syncer. this is the only case
-        // we can use the raw stream.
-        node.provider.canonical_state_stream(),
-        uniswap_pools.clone(),
-        price_generator,
-        pool_config_store.clone(),
-        handles.validator_rx
-    );
+    let megabytes = |mb: usize| mb << 20;
+    let memory_budget = MemoryBudget::new(config.memory_budget_mb.map(megabytes));
+    memory_budget.register("uniswap_ticks", Arc::new(uniswap_pools.clone()), None);
+
+    // Because this is incapsulated under the orderpool syncer. this is the only
+    // case we can use the raw stream.
+    let state_notifications = node.provider.canonical_state_stream();
+    let validation_cache_ceiling = config.validation_cache_memory_mb.map(megabytes);
+    if let Some(url) = config.validation_rpc_url.clone() {
+        tracing::info!(%url, "validating against a remote endpoint");
+        let remote = ProviderBuilder::new().on_http(url);
+        init_validation(
+            RemoteDb::new(Arc::new(remote)),
+            block_height,
+            deployment.angstrom_address,
+            node_address,
+            state_notifications,
+            uniswap_pools.clone(),
+            price_generator,
+            pool_config_store.clone(),
+            deployment.domain(),
+            handles.validator_rx,
+            memory_budget.clone(),
+            validation_cache_ceiling,
+            handles.settlement_costs.clone()
+        );
+    } else {
+        init_validation(
+            RethDbWrapper::new(node.provider.clone()),
+            block_height,
+            deployment.angstrom_address,
+            node_address,
+            state_notifications,
+            uniswap_pools.clone(),
+            price_generator,
+            pool_config_store.clone(),
+            deployment.domain(),
+            handles.validator_rx,
+            memory_budget.clone(),
+            validation_cache_ceiling,
+            handles.settlement_costs.clone()
+        );
+    }
 
     let validation_handle = ValidationClient(handles.validator_tx.clone());
 
+    let validator_set = network_builder.validator_set();
+    let gossip_policy = config
+        .gossip_config
+        .as_deref()
+        .map(crate::cli::load_gossip_config)
+        .transpose()
+        .expect("invalid gossip config")
+        .unwrap_or_default();
     let network_handle = network_builder
         .with_pool_manager(handles.pool_tx)
         .with_consensus_manager(handles.consensus_tx_op)
+        .with_stats_requests(handles.network_stats_rx)
         .build_handle(executor.clone(), node.provider.clone());
 
-    let pool_config = PoolConfig::default();
-    let order_storage = Arc::new(OrderStorage::new(&pool_config));
+    let pool_config = PoolConfig {
+        order_ttl: config.order_ttl_secs.map(Duration::from_secs),
+        ..Default::default()
+    };
+    let order_storage =
+        Arc::new(OrderStorage::new(&pool_config).with_view(handles.order_view.clone()));
+    memory_budget.register(
+        "order_pool",
+        order_storage.clone(),
+        config.order_pool_memory_mb.map(megabytes)
+    );
+    executor.spawn_critical("memory budget", Box::pin(memory_budget.run(MEMORY_CHECK_INTERVAL)));
     let angstrom_pool_tracker =
-        AngstromPoolsTracker::new(node_config.angstrom_address, pool_config_store.clone());
+        AngstromPoolsTracker::new(deployment.angstrom_address, pool_config_store.clone());
 
-    let _pool_handle = PoolManagerBuilder::new(
+    let pool_handle = PoolManagerBuilder::new(
         validation_handle.clone(),
         Some(order_storage.clone()),
         network_handle.clone(),
@@ -287,6 +438,10 @@ pub async fn initialize_strom_components<Node, AddOns>(
         global_block_sync.clone()
     )
     .with_config(pool_config)
+    .with_config_updates(handles.config_tx.subscribe())
+    .with_feature_flags(feature_flags.clone())
+    .with_propagation_policy(gossip_policy)
+    .with_validator_set(validator_set)
     .build_with_channels(
         executor.clone(),
         handles.orderpool_tx,
@@ -295,35 +450,315 @@ pub async fn initialize_strom_components<Node, AddOns>(
         handles.pool_manager_tx
     );
 
+    // archived before the snapshot is restored, so that the restored orders are too
+    if let Some(path) = &config.order_archive_path {
+        let archive = OrderArchive::open(path)
+            .expect("failed to open the order archive")
+            .with_source_tags(order_storage.source_tags());
+        executor
+            .spawn_critical("order archive", Box::pin(archive.run(pool_handle.subscribe_orders())));
+    }
+
+    let order_snapshot_path = config
+        .order_snapshot_path
+        .clone()
+        .unwrap_or_else(|| config.default_path("order_snapshot.json"));
+    restore_order_snapshot(
+        pool_handle.clone(),
+        &node.provider,
+        &uniswap_pools,
+        querying_provider.clone(),
+        deployment.angstrom_address,
+        block_height,
+        &order_snapshot_path
+    )
+    .await;
+
+    #[cfg(feature = "fix-gateway")]
+    if let Some(fix_config) = &config.fix_config {
+        let signing_key_location = config
+            .fix_signing_key_location
+            .as_ref()
+            .expect("--fix-config requires --fix-signing-key-location");
+        let (fix_config, fix_signer) =
+            crate::cli::load_fix_gateway_config(fix_config, signing_key_location)
+                .expect("failed to load the fix gateway config");
+        let gateway =
+            angstrom_fix_gateway::FixGateway::new(pool_handle.clone(), fix_signer, fix_config)
+                .with_domain(deployment.domain());
+
+        executor.spawn_critical("fix gateway", async move {
+            if let Err(e) = gateway.serve().await {
+                tracing::error!(%e, "fix gateway stopped");
+            }
+        });
+    }
+
     // TODO load the stakes from Eigen using node.provider
-    let validators = vec![
-        AngstromValidator::new(PeerId::default(), 100),
-        AngstromValidator::new(PeerId::default(), 200),
-        AngstromValidator::new(PeerId::default(), 300),
-    ];
+    let validators = if deployment.validators.is_empty() {
+        vec![
+            AngstromValidator::new(PeerId::default(), 100),
+            AngstromValidator::new(PeerId::default(), 200),
+            AngstromValidator::new(PeerId::default(), 300),
+        ]
+    } else {
+        deployment
+            .validators
+            .iter()
+            .map(|v| AngstromValidator::new(v.peer_id, v.voting_power))
+            .collect()
+    };
+
+    // every node seeds the leader schedule with the genesis hash so that they all
+    // agree on it
+    let leader_seed = node
+        .provider
+        .block_hash(0)
+        .unwrap()
+        .expect("no genesis block");
 
     // spinup matching engine
-    let matching_handle = MatchingManager::spawn(executor.clone(), validation_handle.clone());
+    let matching_handle = MatchingManager::spawn_with_config(
+        executor.clone(),
+        validation_handle.clone(),
+        deployment.parallel_bundle_simulation,
+        deployment.book_sort,
+        config.solver,
+        TransferTaxes::new(deployment.transfer_taxes.iter().copied()),
+        feature_flags
+    );
 
-    let manager = ConsensusManager::new(
-        ManagerNetworkDeps::new(
-            network_handle.clone(),
+    let anomalies = if config.watchtower {
+        let alerts = AlertSink::new(config.watchtower_webhooks.clone());
+        let anomalies = alerts.subscribe();
+        let watchtower = Watchtower::new(
             eth_handle.subscribe_cannon_state_notifications().await,
-            handles.consensus_rx_op
-        ),
-        signer,
-        validators,
-        order_storage.clone(),
-        block_height,
-        node_config.angstrom_address,
-        uni_ang_registry,
-        uniswap_pools.clone(),
-        mev_boost_provider,
-        matching_handle,
-        global_block_sync.clone()
+            handles.consensus_rx_op,
+            validators,
+            leader_seed,
+            block_height,
+            deployment.angstrom_address,
+            uni_ang_registry,
+            uniswap_pools.clone(),
+            matching_handle,
+            alerts
+        )
+        .with_surplus_policy(SurplusPolicy::new(config.max_surplus_shortfall_bps));
+
+        executor.spawn_critical("watchtower", Box::pin(watchtower));
+        anomalies
+    } else {
+        let signing_guard_path = config
+            .signing_guard_path
+            .clone()
+            .unwrap_or_else(|| config.default_path("signing_guard.jsonl"));
+        let mut signing_guard =
+            SigningGuard::open(&signing_guard_path).expect("failed to open the signing guard");
+
+        let submission_ledger_path = config
+            .submission_ledger_path
+            .clone()
+            .unwrap_or_else(|| config.default_path("submission_ledger.jsonl"));
+        let submission_ledger = SubmissionLedger::open(&submission_ledger_path)
+            .expect("failed to open the submission ledger");
+
+        let replica = if let Some(addr) = config.replica_listen {
+            let (signed_tx, signed_rx) = unbounded_channel();
+            signing_guard = signing_guard.with_replication(signed_tx);
+            let (primary, link) = ReplicaPrimary::new(
+                ReplicaConfig::default(),
+                signer.id(),
+                pool_handle.clone(),
+                ConsensusQueryHandle::new(handles.consensus_query_tx.clone()),
+                signing_guard.records(),
+                signed_rx
+            );
+            let listener = tokio::net::TcpListener::bind(addr)
+                .await
+                .expect("failed to listen for the standby");
+            executor.spawn_critical("replica primary", Box::pin(primary.run(listener)));
+            Some(link)
+        } else if let Some(addr) = config.replica_of {
+            let (standby, link) = ReplicaStandby::new(
+                ReplicaConfig::default(),
+                signer.id(),
+                addr,
+                pool_handle.clone()
+            );
+            executor.spawn_critical("replica standby", Box::pin(standby.run()));
+            Some(link)
+        } else {
+            None
+        };
+
+        let manager = ConsensusManager::new(
+            ManagerNetworkDeps::new(
+                network_handle.clone(),
+                eth_handle.subscribe_cannon_state_notifications().await,
+                handles.consensus_rx_op
+            ),
+            signer,
+            validators,
+            leader_seed,
+            order_storage.clone(),
+            block_height,
+            deployment.angstrom_address,
+            uni_ang_registry,
+            uniswap_pools.clone(),
+            mev_boost_provider,
+            matching_handle,
+            global_block_sync.clone()
+        )
+        .with_query_channel(handles.consensus_query_rx)
+        .with_signing_guard(signing_guard)
+        .with_submission_ledger(submission_ledger)
+        .with_surplus_policy(SurplusPolicy::new(config.max_surplus_shortfall_bps))
+        .with_content_rules(ContentRules::new(config.max_unknown_orders_bps))
+        .with_surplus_distribution(deployment.surplus_distribution)
+        .with_circuit_breakers(CircuitBreakerConfig {
+            max_ucp_deviation_bps: config.max_ucp_deviation_bps,
+            max_failed_settlements: config.max_failed_settlements,
+            ..Default::default()
+        })
+        .with_bundle_golfing(config.golf_bundles)
+        .with_telemetry(config.share_telemetry)
+        .with_block_time(deployment.block_time())
+        .with_config_updates(handles.config_tx.subscribe());
+        let manager = match config
+            .submission_approval()
+            .expect("invalid submission approval config")
+        {
+            Some(approval) => manager.with_submission_approval(approval),
+            None => manager
+        };
+        let manager = match replica {
+            Some(link) => manager.with_replica(link),
+            None => manager
+        };
+
+        let anomalies = manager.subscribe_anomalies();
+        executor.spawn_critical("consensus", Box::pin(manager));
+        anomalies
+    };
+
+    if let Some(notifier_config) = config.notifier_config().expect("invalid notifier config") {
+        let notifier = Notifier::new(notifier_config, pool_handle.subscribe_orders())
+            .with_anomalies(BroadcastStream::new(anomalies));
+        executor.spawn_critical("notifier", Box::pin(notifier));
+    }
+
+    let consensus_handle = ConsensusQueryHandle::new(handles.consensus_query_tx.clone());
+    executor.spawn_critical_with_graceful_shutdown_signal(
+        "angstrom shutdown",
+        move |shutdown| async move {
+            let _guard = shutdown.await;
+            shutdown_strom(pool_handle, consensus_handle, network_handle, order_snapshot_path)
+                .await;
+        }
     );
 
-    let _consensus_handle = executor.spawn_critical("consensus", Box::pin(manager));
     // ensure no more modules can be added to block sync.
     global_block_sync.finalize_modules();
 }
+
+/// Longest we wait for the current consensus round to finish on shutdown.
+const SHUTDOWN_ROUND_TIMEOUT: Duration = Duration::from_secs(12);
+
+/// Shuts the angstrom modules down in order. No new orders are taken in while
+/// the current round is wrapped up, the pending orders are written to disk once
+/// the round no longer touches them and peers are told that we are leaving
+/// last, so that a proposal we still send out reaches them.
+async fn shutdown_strom(
+    pool: PoolHandle,
+    consensus: ConsensusQueryHandle,
+    network: StromNetworkHandle,
+    order_snapshot_path: PathBuf
+) {
+    tracing::info!("shutting down angstrom");
+    pool.stop_intake().await;
+
+    if tokio::time::timeout(SHUTDOWN_ROUND_TIMEOUT, consensus.shutdown())
+        .await
+        .is_err()
+    {
+        tracing::warn!("consensus round didn't finish in time, abandoning it");
+    }
+
+    match pool.snapshot(order_snapshot_path).await {
+        Ok(count) => tracing::info!(count, "wrote pending orders to disk"),
+        Err(e) => tracing::error!(err=%e, "failed to write pending orders to disk")
+    }
+
+    let _ = network.shutdown().await;
+}
+
+/// Most blocks we read back through when restoring the order snapshot, about a
+/// day worth.
+const MAX_BACKFILL_BLOCKS: u64 = 7200;
+
+/// Puts the orders that were pending at the last shutdown back into the pool.
+///
+/// The blocks built while the node was down never reached the pool, so they
+/// are backfilled first: orders filled in them and flash orders for them are
+/// dropped, and the uniswap pools are loaded again at the current block. The
+/// remaining orders are revalidated against it. This runs before consensus is
+/// started, so we never take part in a round with orders that were settled in
+/// the gap. The snapshot is only deleted once all of its orders reached the
+/// pool.
+async fn restore_order_snapshot<P>(
+    pool: PoolHandle,
+    provider: &P,
+    uniswap_pools: &SyncedUniswapPools,
+    querying_provider: Arc<impl Provider>,
+    angstrom_address: Address,
+    current_block: BlockNumber,
+    path: &Path
+) where
+    P: BlockReader<Block = reth::primitives::Block, Receipt = reth::primitives::Receipt>
+{
+    let mut snapshot = match read_order_snapshot(path) {
+        Ok(snapshot) => snapshot,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) => {
+            tracing::warn!(err=%e, ?path, "failed to read order snapshot");
+            return
+        }
+    };
+
+    if let Some(missed) = snapshot.missed_blocks(current_block) {
+        // fills further back are still caught by the nonce checks of validation
+        let from = (*missed.start()).max(current_block.saturating_sub(MAX_BACKFILL_BLOCKS));
+        let missed = from..=current_block;
+        tracing::info!(from, to = current_block, "backfilling missed blocks");
+        let filled = filled_orders_in_range(provider, angstrom_address, missed)
+            .inspect_err(|e| tracing::warn!(err=%e, "failed to read the missed blocks"))
+            .unwrap_or_default();
+        let dropped = snapshot.prune(&filled, current_block);
+        tracing::info!(dropped, "dropped orders settled or expired while offline");
+
+        if let Err(e) = uniswap_pools
+            .reload_at(current_block, querying_provider)
+            .await
+        {
+            tracing::warn!(err=%e, "failed to reload the uniswap pools after the missed blocks");
+        }
+    }
+
+    let count = snapshot.orders.len();
+    let mut failed = 0;
+    for order in snapshot.orders {
+        if let OrderPoolNewOrderResult::Error(e) = pool.new_order(OrderOrigin::Local, order).await {
+            tracing::warn!(err=%e, "failed to restore an order from the last shutdown");
+            failed += 1;
+        }
+    }
+    if failed != 0 {
+        // kept for the next start, a later snapshot replaces it
+        tracing::warn!(failed, count, ?path, "keeping the order snapshot");
+        return
+    }
+
+    // the orders are in the pool from here on, a later snapshot replaces the file
+    let _ = std::fs::remove_file(path);
+    tracing::info!(count, "restored orders from the last shutdown");
+}
@@ -0,0 +1,256 @@
+//! Launches reth with the angstrom components installed on it.
+
+use std::sync::Arc;
+
+use angstrom_eth::exex::{ChainUpdate, ExExCursor};
+use angstrom_metrics::METRICS_ENABLED;
+use angstrom_network::{AngstromNetworkBuilder, NetworkBuilder as StromNetworkBuilder};
+use angstrom_rpc::{
+    api::{
+        AdminApiServer, ConsensusApiServer, DebugApiServer, NetworkApiServer, OrderApiServer,
+        RelayApiServer, SearcherApiServer
+    },
+    AdminApi, ConsensusApi, DebugApi, LogFilterHandle, NetworkApi, OrderApi, RelayApi, SearcherApi
+};
+use angstrom_types::primitive::AngstromSigner;
+use futures::TryStreamExt;
+use order_pool::CrossChainIntake;
+use reth::{
+    builder::{NodeBuilder, WithLaunchContext},
+    chainspec::ChainSpec,
+    primitives::EthPrimitives,
+    rpc::builder::RethRpcModule
+};
+use reth_db::DatabaseEnv;
+use reth_exex::{ExExContext, ExExEvent, ExExHead, ExExNotification};
+use reth_node_builder::{FullNodeComponents, NodeHandle, NodeTypes};
+use reth_node_ethereum::{node::EthereumAddOns, EthereumNode};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+use crate::{
+    cli::AngstromConfig,
+    components::{
+        init_network_builder, initialize_strom_components, initialize_strom_handles,
+        resolve_deployment, StromHandles
+    }
+};
+
+/// Id the eth event ExEx is installed under.
+pub const ETH_EXEX_ID: &str = "angstrom-eth";
+
+/// Installs the angstrom components on a reth node:
+///
+/// - the Strom subprotocol, through the network builder of the node
+/// - the eth event ExEx, which streams the blocks the node commits and reverts
+///   to the eth data cleanser
+/// - the angstrom rpc modules, next to reth's
+/// - the order pool, validation, matching and consensus, spawned on the node's
+///   task executor once it runs, so they shut down with it
+pub struct AngstromLauncher {
+    config:     AngstromConfig,
+    chain_id:   u64,
+    signer:     AngstromSigner,
+    handles:    StromHandles,
+    network:    StromNetworkBuilder,
+    log_filter: LogFilterHandle
+}
+
+impl AngstromLauncher {
+    /// `chain_id` is the chain reth runs on, picked with `--chain`. The
+    /// angstrom deployment and its parameters are resolved for it.
+    pub fn new(
+        config: AngstromConfig,
+        chain_id: u64,
+        log_filter: LogFilterHandle
+    ) -> eyre::Result<Self> {
+        let signer = config.signer()?;
+        let mut handles = initialize_strom_handles();
+        let network =
+            init_network_builder(signer.clone(), chain_id, handles.eth_handle_rx.take().unwrap())?;
+
+        Ok(Self { config, chain_id, signer, handles, network, log_filter })
+    }
+
+    pub async fn launch(
+        self,
+        builder: WithLaunchContext<NodeBuilder<Arc<DatabaseEnv>, ChainSpec>>
+    ) -> eyre::Result<()> {
+        let Self { config, chain_id, signer, handles, mut network, log_filter } = self;
+        let executor = builder.task_executor().clone();
+
+        if config.metrics {
+            executor.spawn_critical("metrics", crate::cli::init_metrics(config.metrics_port));
+            METRICS_ENABLED.set(true).unwrap();
+        } else {
+            METRICS_ENABLED.set(false).unwrap();
+        }
+
+        let protocol_handle = network.build_protocol_handler();
+        // unbounded, the cleanser only starts once the node runs and no update may be
+        // dropped until then
+        let (updates_tx, updates_rx) = unbounded_channel();
+        let cursor = ExExCursor::new(
+            config
+                .exex_cursor_path
+                .clone()
+                .unwrap_or_else(|| config.default_path("exex_cursor"))
+        );
+        let exex_cursor = cursor.clone();
+
+        // for rpc
+        let pool = handles.get_pool_handle();
+        let consensus = handles.get_consensus_handle();
+        let pool_history = handles.get_pool_history_handle();
+        let network_stats = handles.get_network_stats_handle();
+        let settlement_costs = handles.settlement_costs.clone();
+        let order_archive_path = config.order_archive_path.clone();
+        let config_tx = handles.config_tx.clone();
+        let profile_dir = config.default_path("heap_profiles");
+        let feature_flags = config.feature_flags()?;
+        let rpc_feature_flags = feature_flags.clone();
+        let relay_intake = config
+            .relay_config
+            .as_deref()
+            .map(crate::cli::load_relay_config)
+            .transpose()?
+            .map(|relay_config| Arc::new(CrossChainIntake::from_config(&relay_config)));
+        let deployment = resolve_deployment(&config, chain_id)?;
+        // validators only open strom sessions to each other, a watchtower with any
+        // other key would never see a round
+        if config.watchtower
+            && !deployment.validators.is_empty()
+            && !deployment
+                .validators
+                .iter()
+                .any(|validator| validator.peer_id == signer.id())
+        {
+            eyre::bail!(
+                "the watchtower has to run with the key of a validator of the deployment, {} \
+                 isn't one",
+                signer.id()
+            )
+        }
+        let angstrom_address = deployment.angstrom_address;
+        let rpc_executor = executor.clone();
+
+        let NodeHandle { node, node_exit_future } = builder
+            .with_types::<EthereumNode>()
+            .with_components(
+                EthereumNode::default()
+                    .components_builder()
+                    .network(AngstromNetworkBuilder::new(protocol_handle))
+            )
+            .with_add_ons::<EthereumAddOns<_>>(Default::default())
+            .install_exex(ETH_EXEX_ID, move |ctx| async move {
+                Ok(eth_exex(ctx, exex_cursor, updates_tx))
+            })
+            .extend_rpc_modules(move |rpc_context| {
+                let order_api =
+                    OrderApi::new(pool.clone(), rpc_executor.clone(), consensus.clone());
+                rpc_context.modules.merge_configured(order_api.into_rpc())?;
+
+                let searcher_api = SearcherApi::new(pool.clone(), consensus.clone(), rpc_executor)
+                    .with_settlement_costs(settlement_costs);
+                rpc_context
+                    .modules
+                    .merge_configured(searcher_api.into_rpc())?;
+
+                let consensus_api = ConsensusApi::new(consensus);
+                rpc_context
+                    .modules
+                    .merge_configured(consensus_api.into_rpc())?;
+
+                if let Some(intake) = relay_intake {
+                    let relay_api =
+                        RelayApi::new(intake).with_feature_flags(rpc_feature_flags.clone());
+                    rpc_context.modules.merge_configured(relay_api.into_rpc())?;
+                }
+
+                // lists peers and their traffic, served next to reth's net namespace
+                let network_api = NetworkApi::new(network_stats);
+                rpc_context
+                    .modules
+                    .merge_if_module_configured(RethRpcModule::Net, network_api.into_rpc())?;
+
+                // only served where reth's admin namespace is enabled
+                let admin_api = AdminApi::new(config_tx)
+                    .with_log_filter(log_filter)
+                    .with_profile_dir(profile_dir)
+                    .with_feature_flags(rpc_feature_flags);
+                rpc_context
+                    .modules
+                    .merge_if_module_configured(RethRpcModule::Admin, admin_api.into_rpc())?;
+
+                // replays bundles on historical state, served next to reth's debug namespace
+                let mut debug_api = DebugApi::new(rpc_context.provider().clone(), angstrom_address);
+                if let Some(path) = order_archive_path {
+                    debug_api = debug_api.with_pool_history(pool_history, path);
+                }
+                rpc_context
+                    .modules
+                    .merge_if_module_configured(RethRpcModule::Debug, debug_api.into_rpc())?;
+
+                Ok(())
+            })
+            .launch()
+            .await?;
+
+        initialize_strom_components(
+            config,
+            deployment,
+            signer,
+            handles,
+            network,
+            UnboundedReceiverStream::new(updates_rx),
+            cursor,
+            feature_flags,
+            node,
+            &executor
+        )
+        .await;
+
+        node_exit_future.await
+    }
+}
+
+/// Streams the chains the node commits and reverts to the eth data cleanser,
+/// and tells the node which blocks it is done with, so they can be pruned.
+///
+/// Resumes after the block in `cursor`, which the cleanser stores once it
+/// handled a block, so the blocks committed while the cleanser wasn't running
+/// are replayed to it.
+async fn eth_exex<Node>(
+    mut ctx: ExExContext<Node>,
+    cursor: ExExCursor,
+    updates: UnboundedSender<ChainUpdate>
+) -> eyre::Result<()>
+where
+    Node: FullNodeComponents<Types: NodeTypes<Primitives = EthPrimitives>>
+{
+    match cursor.load() {
+        Ok(Some(block)) => ctx.set_notifications_with_head(ExExHead { block }),
+        Ok(None) => {}
+        Err(e) => tracing::warn!(err=%e, "failed to read the exex cursor, following the tip")
+    }
+
+    while let Some(notification) = ctx.notifications.try_next().await? {
+        let update = match notification {
+            ExExNotification::ChainCommitted { new } => ChainUpdate::Commit(new),
+            ExExNotification::ChainReorged { old, new } => ChainUpdate::Reorg { old, new },
+            ExExNotification::ChainReverted { old } => ChainUpdate::Revert(old)
+        };
+
+        let finished = match &update {
+            ChainUpdate::Revert(_) => None,
+            update => Some(update.tip())
+        };
+        // the cleanser is only gone while shutting down
+        let _ = updates.send(update);
+        if let Some(tip) = finished {
+            ctx.events.send(ExExEvent::FinishedHeight(tip))?;
+        }
+    }
+
+    Ok(())
+}
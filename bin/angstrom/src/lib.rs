@@ -1,84 +1,73 @@
 //! Angstrom binary executable.
 //!
 //! ## Feature Flags
+//!
+//! - `fix-gateway`: serves FIX 4.4 order entry next to the rpc, configured
+//!   through `--fix-config`.
 
-use std::path::PathBuf;
-
-use alloy::signers::local::PrivateKeySigner;
-use angstrom_metrics::METRICS_ENABLED;
-use angstrom_network::AngstromNetworkBuilder;
-use angstrom_rpc::{api::OrderApiServer, OrderApi};
-use angstrom_types::primitive::AngstromSigner;
+use angstrom_rpc::LogFilterHandle;
 use clap::Parser;
 use cli::AngstromConfig;
+use launcher::AngstromLauncher;
 use reth::{chainspec::EthereumChainSpecParser, cli::Cli};
-use reth_node_builder::{Node, NodeHandle};
-use reth_node_ethereum::{node::EthereumAddOns, EthereumNode};
-use validation::validator::ValidationClient;
-
-use crate::components::{
-    init_network_builder, initialize_strom_components, initialize_strom_handles
-};
+use reth_tracing::{FileWorkerGuard, Layers};
+use tracing_subscriber::{reload, EnvFilter};
 
 pub mod cli;
 pub mod components;
+pub mod launcher;
+
+type AngstromCli = Cli<EthereumChainSpecParser, AngstromConfig>;
 
 /// Convenience function for parsing CLI options, set up logging and run the
 /// chosen command.
 #[inline]
 pub fn run() -> eyre::Result<()> {
-    Cli::<EthereumChainSpecParser, AngstromConfig>::parse().run(|builder, args| async move {
-        let executor = builder.task_executor().clone();
-
-        if args.metrics {
-            executor.spawn_critical("metrics", crate::cli::init_metrics(args.metrics_port));
-            METRICS_ENABLED.set(true).unwrap();
-        } else {
-            METRICS_ENABLED.set(false).unwrap();
-        }
-
-        let secret_key = get_secret_key(&args.secret_key_location)?;
+    let mut cli = AngstromCli::parse();
+    let (log_filter, _guard) = init_tracing(&mut cli)?;
 
-        let mut channels = initialize_strom_handles();
-        let mut network =
-            init_network_builder(secret_key.clone(), channels.eth_handle_rx.take().unwrap())?;
-        let protocol_handle = network.build_protocol_handler();
-
-        // for rpc
-        let pool = channels.get_pool_handle();
-        let executor_clone = executor.clone();
-        let validation_client = ValidationClient(channels.validator_tx.clone());
-        let NodeHandle { node, node_exit_future } = builder
-            .with_types::<EthereumNode>()
-            .with_components(
-                EthereumNode::default()
-                    .components_builder()
-                    .network(AngstromNetworkBuilder::new(protocol_handle))
-            )
-            .with_add_ons::<EthereumAddOns<_>>(Default::default())
-            .extend_rpc_modules(move |rpc_context| {
-                let order_api = OrderApi::new(pool.clone(), executor_clone, validation_client);
-                rpc_context.modules.merge_configured(order_api.into_rpc())?;
-
-                Ok(())
-            })
-            .launch()
-            .await?;
+    cli.run(|builder, args| async move {
+        let chain_id = builder.config().chain.chain().id();
+        AngstromLauncher::new(args, chain_id, log_filter)?
+            .launch(builder)
+            .await
+    })
+}
 
-        initialize_strom_components(args, secret_key, channels, network, node, &executor).await;
+/// Like [`run`], but audits the leaders as a watchtower instead of taking part
+/// in consensus. The node's key has to be the one of a validator.
+#[inline]
+pub fn run_watchtower() -> eyre::Result<()> {
+    let mut cli = AngstromCli::parse();
+    let (log_filter, _guard) = init_tracing(&mut cli)?;
 
-        node_exit_future.await
+    cli.run(|builder, mut args| async move {
+        args.watchtower = true;
+        let chain_id = builder.config().chain.chain().id();
+        AngstromLauncher::new(args, chain_id, log_filter)?
+            .launch(builder)
+            .await
     })
 }
 
-fn get_secret_key(sk_path: &PathBuf) -> eyre::Result<AngstromSigner> {
-    let exists = sk_path.try_exists();
+/// Installs reth's tracer as its `--log.*` flags configure it, with a log
+/// filter on top that can be swapped at runtime through `admin_setLogLevel`.
+/// The filter starts from `RUST_LOG`, and lets through what reth's filters do
+/// if it isn't set. Reth leaves the installed tracer in place when it runs the
+/// command.
+fn init_tracing(cli: &mut AngstromCli) -> eyre::Result<(LogFilterHandle, Option<FileWorkerGuard>)> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("trace"));
+    let (filter, handle) = reload::Layer::new(filter);
+    let mut layers = Layers::new();
+    layers.add_layer(filter);
 
-    match exists {
-        Ok(true) => {
-            let contents = std::fs::read_to_string(sk_path)?;
-            Ok(AngstromSigner::new(contents.as_str().parse::<PrivateKeySigner>()?))
-        }
-        _ => Err(eyre::eyre!("no secret_key was found at {:?}", sk_path))
+    // reth logs to a directory of the chain, which it only adds when it runs
+    let log_dir = cli.logs.log_file_directory.clone();
+    if let Some(chain_spec) = cli.command.chain_spec() {
+        cli.logs.log_file_directory = log_dir.join(chain_spec.chain.to_string());
     }
+    let guard = cli.logs.init_tracing_with_layers(layers);
+    cli.logs.log_file_directory = log_dir;
+
+    Ok((handle, guard?))
 }
@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use super::testnet::TestnetCli;
+
+#[derive(Parser, Clone, Debug)]
+pub struct LatencyBenchCli {
+    /// book orders submitted per pool each block. One scenario is run per
+    /// combination of book size and node count
+    #[clap(long, value_delimiter = ',', default_value = "10,50,100")]
+    pub book_sizes:         Vec<usize>,
+    /// amount of nodes in the testnet, needs to be at least 2
+    #[clap(long, value_delimiter = ',', default_value = "3")]
+    pub node_counts:        Vec<u64>,
+    /// the amount of blocks orders are submitted on in each scenario
+    #[clap(long, default_value = "5")]
+    pub blocks:             u64,
+    /// how long a scenario can run for. Orders that weren't pre-proposed by
+    /// then are counted as missed
+    #[clap(long, default_value = "120")]
+    pub timeout_secs:       u64,
+    /// where the json report is written to
+    #[clap(long, default_value = "latency_report.json")]
+    pub output:             PathBuf,
+    /// report of a previous run to compare against. The run fails if any
+    /// scenario regressed
+    #[clap(long)]
+    pub baseline:           Option<PathBuf>,
+    /// how much slower than the baseline a scenario can get, in percent
+    #[clap(long, default_value = "10")]
+    pub max_regression_pct: f64,
+    #[clap(flatten)]
+    pub testnet_config:     TestnetCli
+}
@@ -1,10 +1,12 @@
 pub mod devnet;
 pub mod e2e_orders;
+pub mod latency;
 pub mod testnet;
 use angstrom_metrics::{initialize_prometheus_metrics, METRICS_ENABLED};
 use clap::{ArgAction, Parser, Subcommand};
 use devnet::DevnetCli;
 use e2e_orders::End2EndOrdersCli;
+use latency::LatencyBenchCli;
 use reth_tasks::TaskExecutor;
 use testing_tools::types::config::{DevnetConfig, TestnetConfig};
 use testnet::TestnetCli;
@@ -13,7 +15,10 @@ use tracing_subscriber::{
     filter, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry
 };
 
-use crate::{run_devnet, run_testnet, simulations::e2e_orders::run_e2e_orders};
+use crate::{
+    run_devnet, run_testnet,
+    simulations::{e2e_orders::run_e2e_orders, latency::run_latency_bench}
+};
 
 #[derive(Parser)]
 pub struct AngstromTestnetCli {
@@ -70,7 +75,10 @@ pub enum TestnetSubcommmand {
     #[command(name = "devnet")]
     Devnet(DevnetCli),
     #[command(name = "e2e")]
-    End2EndOrders(End2EndOrdersCli)
+    End2EndOrders(End2EndOrdersCli),
+    /// measures the time from rpc order submission to pre-proposal inclusion
+    #[command(name = "latency")]
+    Latency(LatencyBenchCli)
 }
 
 impl TestnetSubcommmand {
@@ -78,7 +86,8 @@ impl TestnetSubcommmand {
         match self {
             TestnetSubcommmand::Testnet(testnet_cli) => run_testnet(executor, testnet_cli).await,
             TestnetSubcommmand::Devnet(devnet_cli) => run_devnet(executor, devnet_cli).await,
-            TestnetSubcommmand::End2EndOrders(e2e_cli) => run_e2e_orders(executor, e2e_cli).await
+            TestnetSubcommmand::End2EndOrders(e2e_cli) => run_e2e_orders(executor, e2e_cli).await,
+            TestnetSubcommmand::Latency(latency_cli) => run_latency_bench(latency_cli).await
         }
     }
 }
@@ -80,7 +80,7 @@ fn end_to_end_agent<'a>(
                                     .chain(vec![tob.into()])
                                     .collect::<Vec<AllOrders>>();
 
-                                 pending_orders.push(client.send_orders(all_orders));
+                                 pending_orders.push(client.send_orders(all_orders, None));
                             }
                         }
                         Some(resolved_order) = pending_orders.next() => {
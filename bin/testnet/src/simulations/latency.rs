@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use angstrom_network::manager::StromConsensusEvent;
+use reth_provider::test_utils::NoopProvider;
+use testing_tools::{
+    controllers::enviroments::AngstromTestnet,
+    latency::{latency_agent, LatencyRecorder, LatencyReport, LatencyScenario, ScenarioReport}
+};
+
+use crate::cli::latency::LatencyBenchCli;
+
+pub async fn run_latency_bench(cli: LatencyBenchCli) -> eyre::Result<()> {
+    let mut report = LatencyReport::default();
+
+    for &node_count in &cli.node_counts {
+        if node_count < 2 {
+            return Err(eyre::eyre!("latency scenarios need at least 2 nodes, got {node_count}"))
+        }
+
+        for &book_size in &cli.book_sizes {
+            let scenario = LatencyScenario { node_count, book_size };
+            tracing::info!(%scenario, "running latency scenario");
+
+            let scenario_report = run_scenario(&cli, scenario).await?;
+            tracing::info!(%scenario, stats = ?scenario_report.stats, "finished latency scenario");
+            report.scenarios.push(scenario_report);
+        }
+    }
+
+    report.write(&cli.output)?;
+    tracing::info!(output = ?cli.output, "wrote latency report");
+
+    let Some(baseline) = &cli.baseline else { return Ok(()) };
+    let regressions = report.regressions(&LatencyReport::load(baseline)?, cli.max_regression_pct);
+    if regressions.is_empty() {
+        return Ok(())
+    }
+
+    regressions
+        .iter()
+        .for_each(|regression| tracing::error!(%regression, "latency regression"));
+    Err(eyre::eyre!("{} latency regressions against {:?}", regressions.len(), baseline))
+}
+
+async fn run_scenario(
+    cli: &LatencyBenchCli,
+    scenario: LatencyScenario
+) -> eyre::Result<ScenarioReport> {
+    let mut testnet_cli = cli.testnet_config.clone();
+    testnet_cli.nodes_in_network = scenario.node_count;
+    let config = testnet_cli.make_config()?;
+
+    let recorder = LatencyRecorder::default();
+    let agents = vec![latency_agent(recorder.clone(), scenario.book_size, cli.blocks)];
+    let testnet = AngstromTestnet::spawn_testnet(NoopProvider::default(), config, agents).await?;
+
+    let tap = recorder.clone();
+    testnet.tap_consensus_events(move |_, event| {
+        if let StromConsensusEvent::PreProposal(_, pre_proposal) = event {
+            tap.record_pre_proposal(pre_proposal);
+        }
+    });
+
+    let timeout = Duration::from_secs(cli.timeout_secs);
+    let completed = testnet
+        .run_until(async {
+            let wait = async {
+                while !recorder.is_complete() {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                }
+            };
+            Ok(tokio::time::timeout(timeout, wait).await.is_ok())
+        })
+        .await?;
+
+    if !completed {
+        tracing::warn!(%scenario, "timed out waiting for orders to be pre-proposed");
+    }
+
+    Ok(recorder.report(scenario))
+}
@@ -1 +1,2 @@
 pub mod e2e_orders;
+pub mod latency;
@@ -0,0 +1,250 @@
+//! Anti-entropy for the order books of the network.
+//!
+//! Every [`DIGEST_INTERVAL`] a node sends its peers an [`OrderDigest`] of the
+//! orders in its book. A node that restarted, or missed the cancellation or
+//! fill of an order, would otherwise keep orders around that the rest of the
+//! network dropped long ago. From the digests of its peers it learns which of
+//! its orders all of its peers have dropped, which it drops too. Only the
+//! peers that know an order, because it was propagated to them or they sent
+//! it to us, have a say in that: an order the propagation policy keeps from
+//! them is in none of their digests.
+//!
+//! The expiries in the digests of peers are never taken. Nothing signed backs
+//! an expiry earlier than the deadline of the order or our own time to live,
+//! so a peer could otherwise have any order pruned across the network. Each
+//! node expires orders, and advertises their expiry, by its own clock.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant}
+};
+
+use alloy::primitives::B256;
+use angstrom_types::{
+    orders::{DigestEntry, OrderDigest},
+    primitive::PeerId
+};
+
+/// How often the digest of our book is sent to our peers.
+pub const DIGEST_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long an order has to be known, by us and by a peer, before the peer's
+/// digest is expected to hold it.
+const PROPAGATION_GRACE: Duration = Duration::from_secs(60);
+
+/// Digests older than this are from peers that went quiet, and no longer say
+/// anything about their book.
+const DIGEST_MAX_AGE: Duration = Duration::from_secs(3 * DIGEST_INTERVAL.as_secs());
+
+#[derive(Debug)]
+struct HeldOrder {
+    since:      Instant,
+    /// unix timestamp, in seconds
+    expires_at: u64
+}
+
+#[derive(Debug)]
+struct PeerDigest {
+    /// when the peer sent us its first digest
+    first_received: Instant,
+    received:       Instant,
+    orders:         HashSet<B256>
+}
+
+/// Tracks the orders in our book against the digests of our peers.
+#[derive(Debug, Default)]
+pub struct OrderAntiEntropy {
+    /// time orders live in the network's books, if they don't expire sooner
+    ttl:     Option<Duration>,
+    orders:  HashMap<B256, HeldOrder>,
+    digests: HashMap<PeerId, PeerDigest>
+}
+
+impl OrderAntiEntropy {
+    pub fn new(ttl: Option<Duration>) -> Self {
+        Self { ttl, ..Default::default() }
+    }
+
+    /// Catches up with the orders in the book, given with their deadlines.
+    /// `now` is the unix timestamp in seconds.
+    pub fn sync_book(&mut self, book: impl IntoIterator<Item = (B256, Option<u64>)>, now: u64) {
+        let ttl_expiry = self
+            .ttl
+            .map_or(u64::MAX, |ttl| now.saturating_add(ttl.as_secs()));
+        let mut held = std::mem::take(&mut self.orders);
+
+        self.orders = book
+            .into_iter()
+            .map(|(hash, deadline)| {
+                let order = held.remove(&hash).unwrap_or_else(|| HeldOrder {
+                    since:      Instant::now(),
+                    expires_at: deadline.unwrap_or(u64::MAX).min(ttl_expiry)
+                });
+                (hash, order)
+            })
+            .collect();
+    }
+
+    /// The digest of the book, as of the last [`Self::sync_book`].
+    pub fn digest(&self, block_number: u64) -> OrderDigest {
+        let mut orders = self
+            .orders
+            .iter()
+            .map(|(order_hash, order)| DigestEntry {
+                order_hash: *order_hash,
+                expires_at: order.expires_at
+            })
+            .collect::<Vec<_>>();
+        orders.sort_unstable_by_key(|entry| entry.order_hash);
+
+        OrderDigest { block_number, orders }
+    }
+
+    pub fn on_digest(&mut self, peer_id: PeerId, digest: OrderDigest) {
+        let now = Instant::now();
+        let first_received = self
+            .digests
+            .get(&peer_id)
+            .map_or(now, |digest| digest.first_received);
+        self.digests.insert(
+            peer_id,
+            PeerDigest {
+                first_received,
+                received: now,
+                orders: digest.orders.into_iter().map(|e| e.order_hash).collect()
+            }
+        );
+    }
+
+    pub fn remove_peer(&mut self, peer_id: &PeerId) {
+        self.digests.remove(peer_id);
+    }
+
+    /// Takes the orders that have to leave the book: the ones past their
+    /// expiry, and the ones that every peer with a recent digest that
+    /// `knows` the order has dropped although it had the time to learn about
+    /// them. `now` is the unix timestamp in seconds.
+    pub fn take_prunable(&mut self, now: u64, knows: impl Fn(&B256, &PeerId) -> bool) -> Vec<B256> {
+        let recent = self
+            .digests
+            .iter()
+            .filter(|(_, digest)| digest.received.elapsed() <= DIGEST_MAX_AGE)
+            .collect::<Vec<_>>();
+
+        let prunable = self
+            .orders
+            .iter()
+            .filter(|(hash, order)| {
+                let mut informed = recent
+                    .iter()
+                    .filter(|(peer_id, _)| knows(hash, peer_id))
+                    .peekable();
+                let dropped = informed.peek().is_some()
+                    && informed.all(|(_, digest)| {
+                        let known_since = order.since.max(digest.first_received);
+                        digest.received >= known_since + PROPAGATION_GRACE
+                            && !digest.orders.contains(*hash)
+                    });
+
+                order.expires_at <= now || dropped
+            })
+            .map(|(hash, _)| *hash)
+            .collect::<Vec<_>>();
+
+        for hash in &prunable {
+            self.orders.remove(hash);
+        }
+
+        prunable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(orders: &[(B256, u64)]) -> OrderDigest {
+        OrderDigest {
+            block_number: 1,
+            orders:       orders
+                .iter()
+                .map(|(order_hash, expires_at)| DigestEntry {
+                    order_hash: *order_hash,
+                    expires_at: *expires_at
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn orders_expire_by_our_own_clock() {
+        let order = B256::with_last_byte(1);
+        let mut anti_entropy = OrderAntiEntropy::new(Some(Duration::from_secs(100)));
+
+        anti_entropy.sync_book([(order, None)], 1_000);
+        assert_eq!(anti_entropy.digest(1).orders[0].expires_at, 1_100);
+
+        assert!(anti_entropy.take_prunable(1_099, |_, _| true).is_empty());
+        assert_eq!(anti_entropy.take_prunable(1_100, |_, _| true), vec![order]);
+        assert!(anti_entropy.digest(1).orders.is_empty());
+    }
+
+    #[test]
+    fn expiries_of_lying_peers_are_ignored() {
+        let order = B256::with_last_byte(1);
+        let mut anti_entropy = OrderAntiEntropy::new(Some(Duration::from_secs(100)));
+        anti_entropy.sync_book([(order, Some(1_050))], 1_000);
+
+        anti_entropy.on_digest(PeerId::random(), digest(&[(order, 0)]));
+
+        // neither pruned nor advertised with the peer's expiry
+        assert!(anti_entropy.take_prunable(1_000, |_, _| true).is_empty());
+        assert_eq!(anti_entropy.digest(1).orders[0].expires_at, 1_050);
+        assert_eq!(anti_entropy.take_prunable(1_050, |_, _| true), vec![order]);
+    }
+
+    #[test]
+    fn orders_are_only_dropped_once_peers_had_time_to_learn_them() {
+        let kept = B256::with_last_byte(1);
+        let dropped = B256::with_last_byte(2);
+        let peer = PeerId::random();
+        let mut anti_entropy = OrderAntiEntropy::default();
+
+        anti_entropy.sync_book([(kept, None), (dropped, Some(u64::MAX))], 0);
+        anti_entropy.on_digest(peer, digest(&[(kept, u64::MAX)]));
+        assert!(anti_entropy.take_prunable(0, |_, _| true).is_empty());
+
+        // the peer has been sending digests for longer than the grace period
+        let digest_state = anti_entropy.digests.get_mut(&peer).unwrap();
+        let received = digest_state.received;
+        digest_state.first_received = received - PROPAGATION_GRACE;
+        anti_entropy
+            .orders
+            .values_mut()
+            .for_each(|order| order.since = received - PROPAGATION_GRACE);
+
+        assert_eq!(anti_entropy.take_prunable(0, |_, _| true), vec![dropped]);
+        assert_eq!(anti_entropy.digest(1).orders.len(), 1);
+    }
+
+    #[test]
+    fn orders_kept_from_peers_are_not_dropped_for_missing_in_their_digests() {
+        let withheld = B256::with_last_byte(1);
+        let propagated = B256::with_last_byte(2);
+        let peer = PeerId::random();
+        let mut anti_entropy = OrderAntiEntropy::default();
+
+        anti_entropy.sync_book([(withheld, None), (propagated, None)], 0);
+        anti_entropy.on_digest(peer, digest(&[]));
+        let digest_state = anti_entropy.digests.get_mut(&peer).unwrap();
+        let received = digest_state.received;
+        digest_state.first_received = received - PROPAGATION_GRACE;
+        anti_entropy
+            .orders
+            .values_mut()
+            .for_each(|order| order.since = received - PROPAGATION_GRACE);
+
+        assert_eq!(anti_entropy.take_prunable(0, |hash, _| *hash == propagated), vec![propagated]);
+        assert_eq!(anti_entropy.digest(1).orders.len(), 1);
+    }
+}
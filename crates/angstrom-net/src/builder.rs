@@ -15,8 +15,8 @@ use tokio_util::sync::PollSender;
 
 use crate::{
     manager::StromConsensusEvent, state::StromState, types::status::StatusState, NetworkOrderEvent,
-    Status, StromNetworkHandle, StromNetworkManager, StromProtocolHandler, StromSessionManager,
-    StromSessionMessage, Swarm, VerificationSidecar
+    NetworkStatsRequest, Status, StromNetworkHandle, StromNetworkManager, StromProtocolHandler,
+    StromSessionManager, StromSessionMessage, Swarm, VerificationSidecar
 };
 
 pub struct NetworkBuilder {
@@ -24,6 +24,7 @@ pub struct NetworkBuilder {
     to_consensus_manager: Option<UnboundedMeteredSender<StromConsensusEvent>>,
     session_manager_rx:   Option<Receiver<StromSessionMessage>>,
     eth_handle:           UnboundedReceiver<EthEvent>,
+    stats_requests:       Option<UnboundedReceiver<NetworkStatsRequest>>,
 
     validator_set: Arc<RwLock<HashSet<Address>>>,
     verification:  VerificationSidecar
@@ -37,6 +38,7 @@ impl NetworkBuilder {
             to_consensus_manager: None,
             session_manager_rx: None,
             eth_handle,
+            stats_requests: None,
             validator_set: Default::default()
         }
     }
@@ -54,11 +56,24 @@ impl NetworkBuilder {
         self
     }
 
+    /// Where the network answers
+    /// [`NetworkStatsHandle`](crate::NetworkStatsHandle) requests from.
+    pub fn with_stats_requests(mut self, rx: UnboundedReceiver<NetworkStatsRequest>) -> Self {
+        self.stats_requests = Some(rx);
+        self
+    }
+
     pub fn with_validator_set(mut self, validator_set: Arc<RwLock<HashSet<Address>>>) -> Self {
         self.validator_set = validator_set;
         self
     }
 
+    /// The validators the network accepts sessions with, kept up to date as
+    /// they change.
+    pub fn validator_set(&self) -> Arc<RwLock<HashSet<Address>>> {
+        self.validator_set.clone()
+    }
+
     pub fn build_protocol_handler(&mut self) -> StromProtocolHandler {
         let (session_manager_tx, session_manager_rx) = tokio::sync::mpsc::channel(100);
         let protocol = StromProtocolHandler::new(
@@ -83,12 +98,15 @@ impl NetworkBuilder {
         let sessions = StromSessionManager::new(self.session_manager_rx.take().unwrap());
         let swarm = Swarm::new(sessions, state);
 
-        let network = StromNetworkManager::new(
+        let mut network = StromNetworkManager::new(
             swarm,
             self.eth_handle,
             self.to_pool_manager,
             self.to_consensus_manager
         );
+        if let Some(rx) = self.stats_requests.take() {
+            network = network.with_stats_requests(rx);
+        }
 
         let handle = network.get_handle();
         tp.spawn_critical("strom network", network.boxed());
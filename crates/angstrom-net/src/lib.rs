@@ -11,9 +11,18 @@ pub use manager::{StromNetworkEvent, StromNetworkManager};
 pub mod pool_manager;
 pub use pool_manager::PoolManagerBuilder;
 
+pub mod anti_entropy;
+pub use anti_entropy::*;
+
+pub mod propagation;
+pub use propagation::*;
+
 pub mod peers;
 pub use peers::*;
 
+pub mod peer_stats;
+pub use peer_stats::*;
+
 pub mod session;
 pub use session::*;
 
@@ -34,3 +43,6 @@ pub use swarm::*;
 
 pub mod eth_network_builder;
 pub use eth_network_builder::*;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod wire_tests;
@@ -8,7 +8,10 @@ use std::{
 use alloy::primitives::BlockNumber;
 use angstrom_eth::manager::EthEvent;
 use angstrom_types::{
-    consensus::{PreProposal, PreProposalAggregation, Proposal},
+    consensus::{
+        PreProposal, PreProposalAggregation, Proposal, SubmissionApproval,
+        SubmissionApprovalRequest, ValidatorTelemetry
+    },
     primitive::PeerId
 };
 use futures::StreamExt;
@@ -18,7 +21,10 @@ use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::error;
 
-use crate::{NetworkOrderEvent, StromMessage, StromNetworkHandleMsg, Swarm, SwarmEvent};
+use crate::{
+    NetworkOrderEvent, NetworkStats, NetworkStatsRequest, StromMessage, StromNetworkHandleMsg,
+    Swarm, SwarmEvent
+};
 #[allow(unused_imports)]
 use crate::{StromNetworkConfig, StromNetworkHandle, StromSessionManager};
 
@@ -36,7 +42,11 @@ pub struct StromNetworkManager<DB> {
     /// This is updated via internal events and shared via `Arc` with the
     /// [`NetworkHandle`] Updated by the `NetworkWorker` and loaded by the
     /// `NetworkService`.
-    num_active_peers: Arc<AtomicUsize>
+    num_active_peers: Arc<AtomicUsize>,
+    /// Per peer accounting, queried through
+    /// [`NetworkStatsHandle`](crate::NetworkStatsHandle)
+    stats:            NetworkStats,
+    stats_requests:   Option<UnboundedReceiverStream<NetworkStatsRequest>>
 }
 
 impl<DB: Unpin> StromNetworkManager<DB> {
@@ -60,10 +70,17 @@ impl<DB: Unpin> StromNetworkManager<DB> {
             from_handle_rx: rx.into(),
             to_pool_manager,
             to_consensus_manager,
-            event_listeners: Vec::new()
+            event_listeners: Vec::new(),
+            stats: NetworkStats::default(),
+            stats_requests: None
         }
     }
 
+    pub fn with_stats_requests(mut self, rx: UnboundedReceiver<NetworkStatsRequest>) -> Self {
+        self.stats_requests = Some(rx.into());
+        self
+    }
+
     pub fn install_consensus_manager(&mut self, tx: UnboundedMeteredSender<StromConsensusEvent>) {
         self.to_consensus_manager = Some(tx);
     }
@@ -116,8 +133,17 @@ impl<DB: Unpin> StromNetworkManager<DB> {
         match msg {
             StromNetworkHandleMsg::SubscribeEvents(tx) => self.event_listeners.push(tx),
             StromNetworkHandleMsg::SendStromMessage { peer_id, msg } => {
+                self.stats.on_sent(peer_id, msg.message_id());
                 self.swarm.sessions_mut().send_message(&peer_id, msg)
             }
+            StromNetworkHandleMsg::SendEncodedMessage { peer_ids, msg } => {
+                for peer_id in &peer_ids {
+                    self.stats.on_sent(*peer_id, msg.message_id);
+                }
+                self.swarm
+                    .sessions_mut()
+                    .send_encoded_message(&peer_ids, &msg)
+            }
             StromNetworkHandleMsg::Shutdown(tx) => {
                 // Disconnect all active connections
                 self.swarm
@@ -137,6 +163,7 @@ impl<DB: Unpin> StromNetworkManager<DB> {
                 .peers_mut()
                 .change_weight(peer_id, kind),
             StromNetworkHandleMsg::BroadcastStromMessage { msg } => {
+                self.stats.on_broadcast(msg.message_id());
                 self.swarm_mut().sessions_mut().broadcast_message(msg);
             }
             StromNetworkHandleMsg::DisconnectPeer(id, reason) => {
@@ -145,6 +172,18 @@ impl<DB: Unpin> StromNetworkManager<DB> {
         }
     }
 
+    fn on_stats_request(&self, request: NetworkStatsRequest) {
+        match request {
+            NetworkStatsRequest::Peers(tx) => {
+                let peers = self.swarm.state().peers();
+                let _ = tx.send(self.stats.peers(|peer_id| peers.reputation(peer_id)));
+            }
+            NetworkStatsRequest::Events(since, tx) => {
+                let _ = tx.send(self.stats.events_since(since));
+            }
+        }
+    }
+
     fn notify_listeners(&mut self, event: StromNetworkEvent) {
         self.event_listeners
             .retain(|tx| tx.send(event.clone()).is_ok());
@@ -175,6 +214,14 @@ impl<DB: Unpin> Future for StromNetworkManager<DB> {
                 _ => {}
             };
 
+            if let Some(Poll::Ready(Some(request))) = self
+                .stats_requests
+                .as_mut()
+                .map(|requests| requests.poll_next_unpin(cx))
+            {
+                self.on_stats_request(request);
+            }
+
             // make sure we add and remove validators properly
             if let Poll::Ready(Some(eth_event)) = self.eth_handle.poll_recv(cx) {
                 match eth_event {
@@ -189,6 +236,9 @@ impl<DB: Unpin> Future for StromNetworkManager<DB> {
             }
 
             if let Poll::Ready(Some(event)) = self.swarm.poll_next_unpin(cx) {
+                if let SwarmEvent::ValidMessage { peer_id, msg } = &event {
+                    self.stats.on_received(*peer_id, msg.message_id());
+                }
                 match event {
                     SwarmEvent::ValidMessage { peer_id, msg } => match msg {
                         StromMessage::PrePropose(p) => {
@@ -206,6 +256,22 @@ impl<DB: Unpin> Future for StromNetworkManager<DB> {
                                 let _ = tx.send(StromConsensusEvent::Proposal(peer_id, a));
                             });
                         }
+                        StromMessage::ValidatorTelemetry(t) => {
+                            self.to_consensus_manager.as_ref().inspect(|tx| {
+                                let _ = tx.send(StromConsensusEvent::Telemetry(peer_id, t));
+                            });
+                        }
+                        StromMessage::SubmissionApprovalRequest(request) => {
+                            self.to_consensus_manager.as_ref().inspect(|tx| {
+                                let _ =
+                                    tx.send(StromConsensusEvent::ApprovalRequest(peer_id, request));
+                            });
+                        }
+                        StromMessage::SubmissionApproval(approval) => {
+                            self.to_consensus_manager.as_ref().inspect(|tx| {
+                                let _ = tx.send(StromConsensusEvent::Approval(peer_id, approval));
+                            });
+                        }
                         StromMessage::PropagatePooledOrders(a) => {
                             self.to_pool_manager.as_ref().inspect(|tx| {
                                 let _ = tx
@@ -218,9 +284,48 @@ impl<DB: Unpin> Future for StromNetworkManager<DB> {
                                     tx.send(NetworkOrderEvent::CancelOrder { peer_id, request: a });
                             });
                         }
-                        StromMessage::Status(_) => {}
+                        StromMessage::SinceHashes(hashes) => {
+                            self.to_pool_manager.as_ref().inspect(|tx| {
+                                let _ = tx.send(NetworkOrderEvent::SinceHashes { peer_id, hashes });
+                            });
+                        }
+                        StromMessage::OrderSyncRequest(hashes) => {
+                            self.to_pool_manager.as_ref().inspect(|tx| {
+                                let _ = tx
+                                    .send(NetworkOrderEvent::OrderSyncRequest { peer_id, hashes });
+                            });
+                        }
+                        StromMessage::OrderAmendment(amendment) => {
+                            self.to_pool_manager.as_ref().inspect(|tx| {
+                                let _ =
+                                    tx.send(NetworkOrderEvent::AmendOrder { peer_id, amendment });
+                            });
+                        }
+                        StromMessage::OrderDigest(digest) => {
+                            self.to_pool_manager.as_ref().inspect(|tx| {
+                                let _ = tx.send(NetworkOrderEvent::OrderDigest { peer_id, digest });
+                            });
+                        }
+                        StromMessage::GetPooledOrders(hashes) => {
+                            self.to_pool_manager.as_ref().inspect(|tx| {
+                                let _ =
+                                    tx.send(NetworkOrderEvent::GetPooledOrders { peer_id, hashes });
+                            });
+                        }
+                        StromMessage::OrderCancelAll(request) => {
+                            self.to_pool_manager.as_ref().inspect(|tx| {
+                                let _ = tx
+                                    .send(NetworkOrderEvent::CancelAllOrders { peer_id, request });
+                            });
+                        }
+                        StromMessage::Status(_) | StromMessage::Resume(_) => {}
                     },
                     SwarmEvent::Disconnected { peer_id } => {
+                        self.stats.on_session_closed(peer_id);
+                        self.swarm
+                            .state_mut()
+                            .peers_mut()
+                            .on_session_closed(peer_id);
                         self.notify_listeners(StromNetworkEvent::SessionClosed {
                             peer_id,
                             reason: None
@@ -229,8 +334,21 @@ impl<DB: Unpin> Future for StromNetworkManager<DB> {
                     SwarmEvent::SessionEstablished { peer_id } => {
                         self.num_active_peers
                             .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                        self.stats.on_session_established(peer_id);
+                        self.swarm
+                            .state_mut()
+                            .peers_mut()
+                            .on_session_established(peer_id);
                         self.notify_listeners(StromNetworkEvent::SessionEstablished { peer_id })
                     }
+                    SwarmEvent::SessionVerified { peer_id, version } => {
+                        self.stats.on_session_verified(peer_id, version);
+                        self.notify_listeners(StromNetworkEvent::SessionVerified { peer_id })
+                    }
+                    SwarmEvent::SessionResumed { peer_id } => {
+                        self.stats.on_session_resumed(peer_id);
+                        self.notify_listeners(StromNetworkEvent::SessionResumed { peer_id })
+                    }
                 }
             }
         }
@@ -259,6 +377,18 @@ pub enum StromNetworkEvent {
         peer_id: PeerId /* #[cfg(feature = "testnet")]
                          * initial_state: Option<angstrom_types::testnet::InitialTestnetState> */
     },
+    /// The status handshake of an established session succeeded. Emitted after
+    /// [`StromNetworkEvent::SessionEstablished`].
+    SessionVerified {
+        /// The identifier of the peer that was verified.
+        peer_id: PeerId
+    },
+    /// A session that was closed within the resumption window has been
+    /// resumed. Emitted after [`StromNetworkEvent::SessionEstablished`].
+    SessionResumed {
+        /// The identifier of the peer whose session was resumed.
+        peer_id: PeerId
+    },
     /// Event emitted when a new peer is added
     PeerAdded(PeerId),
     /// Event emitted when a new peer is removed
@@ -269,7 +399,13 @@ pub enum StromNetworkEvent {
 pub enum StromConsensusEvent {
     PreProposal(PeerId, PreProposal),
     PreProposalAgg(PeerId, PreProposalAggregation),
-    Proposal(PeerId, Proposal)
+    Proposal(PeerId, Proposal),
+    /// how the peer did in a block, which isn't part of the rounds
+    Telemetry(PeerId, ValidatorTelemetry),
+    /// the round leader asking us to co-sign its settlement transaction
+    ApprovalRequest(PeerId, SubmissionApprovalRequest),
+    /// an approver co-signing our settlement transaction
+    Approval(PeerId, SubmissionApproval)
 }
 
 impl StromConsensusEvent {
@@ -277,7 +413,10 @@ impl StromConsensusEvent {
         match self {
             StromConsensusEvent::PreProposal(..) => "PreProposal",
             StromConsensusEvent::PreProposalAgg(..) => "PreProposalAggregation",
-            StromConsensusEvent::Proposal(..) => "Proposal"
+            StromConsensusEvent::Proposal(..) => "Proposal",
+            StromConsensusEvent::Telemetry(..) => "Telemetry",
+            StromConsensusEvent::ApprovalRequest(..) => "SubmissionApprovalRequest",
+            StromConsensusEvent::Approval(..) => "SubmissionApproval"
         }
     }
 
@@ -285,7 +424,10 @@ impl StromConsensusEvent {
         match self {
             StromConsensusEvent::PreProposal(peer_id, _)
             | StromConsensusEvent::Proposal(peer_id, _)
-            | StromConsensusEvent::PreProposalAgg(peer_id, _) => *peer_id
+            | StromConsensusEvent::PreProposalAgg(peer_id, _)
+            | StromConsensusEvent::Telemetry(peer_id, _)
+            | StromConsensusEvent::ApprovalRequest(peer_id, _)
+            | StromConsensusEvent::Approval(peer_id, _) => *peer_id
         }
     }
 
@@ -293,7 +435,12 @@ impl StromConsensusEvent {
         match self {
             StromConsensusEvent::PreProposal(_, pre_proposal) => pre_proposal.source,
             StromConsensusEvent::PreProposalAgg(_, pre_proposal) => pre_proposal.source,
-            StromConsensusEvent::Proposal(_, proposal) => proposal.source
+            StromConsensusEvent::Proposal(_, proposal) => proposal.source,
+            // telemetry isn't signed, it is from whoever sent it
+            StromConsensusEvent::Telemetry(peer_id, _) => *peer_id,
+            // requests are only taken from the leader's own session
+            StromConsensusEvent::ApprovalRequest(peer_id, _) => *peer_id,
+            StromConsensusEvent::Approval(_, approval) => approval.source
         }
     }
 
@@ -301,7 +448,10 @@ impl StromConsensusEvent {
         match self {
             StromConsensusEvent::PreProposal(_, PreProposal { block_height, .. }) => *block_height,
             StromConsensusEvent::PreProposalAgg(_, p) => p.block_height,
-            StromConsensusEvent::Proposal(_, Proposal { block_height, .. }) => *block_height
+            StromConsensusEvent::Proposal(_, Proposal { block_height, .. }) => *block_height,
+            StromConsensusEvent::Telemetry(_, telemetry) => telemetry.block_height,
+            StromConsensusEvent::ApprovalRequest(_, request) => request.block_height,
+            StromConsensusEvent::Approval(_, approval) => approval.block_height
         }
     }
 }
@@ -314,7 +464,14 @@ impl From<StromConsensusEvent> for StromMessage {
             }
             StromConsensusEvent::PreProposalAgg(_, agg) => StromMessage::PreProposeAgg(agg),
 
-            StromConsensusEvent::Proposal(_, proposal) => StromMessage::Propose(proposal)
+            StromConsensusEvent::Proposal(_, proposal) => StromMessage::Propose(proposal),
+            StromConsensusEvent::Telemetry(_, telemetry) => {
+                StromMessage::ValidatorTelemetry(telemetry)
+            }
+            StromConsensusEvent::ApprovalRequest(_, request) => {
+                StromMessage::SubmissionApprovalRequest(request)
+            }
+            StromConsensusEvent::Approval(_, approval) => StromMessage::SubmissionApproval(approval)
         }
     }
 }
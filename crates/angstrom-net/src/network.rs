@@ -1,7 +1,10 @@
 use std::sync::{atomic::AtomicUsize, Arc};
 
+use alloy::primitives::B256;
 use angstrom_types::{
-    orders::CancelOrderRequest, primitive::PeerId, sol_bindings::grouped_orders::AllOrders
+    orders::{CancelAllOrdersRequest, CancelOrderRequest, OrderAmendment, OrderDigest},
+    primitive::PeerId,
+    sol_bindings::grouped_orders::AllOrders
 };
 use reth_metrics::common::mpsc::UnboundedMeteredSender;
 use reth_network::DisconnectReason;
@@ -11,7 +14,7 @@ use tokio::sync::{
 };
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
-use crate::{ReputationChangeKind, StromMessage, StromNetworkEvent};
+use crate::{EncodedStromMessage, ReputationChangeKind, StromMessage, StromNetworkEvent};
 
 //TODO:
 // 1) Implement the order pool manager
@@ -41,6 +44,11 @@ impl StromNetworkHandle {
         self.send_to_network_manager(StromNetworkHandleMsg::SendStromMessage { peer_id, msg })
     }
 
+    /// Send a message that was encoded once to each of the peers
+    pub fn send_encoded_message(&self, peer_ids: Vec<PeerId>, msg: EncodedStromMessage) {
+        self.send_to_network_manager(StromNetworkHandleMsg::SendEncodedMessage { peer_ids, msg })
+    }
+
     /// Broadcast Strom message to all peers
     pub fn broadcast_message(&self, msg: StromMessage) {
         self.send_to_network_manager(StromNetworkHandleMsg::BroadcastStromMessage { msg })
@@ -92,7 +100,13 @@ struct StromNetworkInner {
 #[derive(Debug, Clone, PartialEq)]
 pub enum NetworkOrderEvent {
     IncomingOrders { peer_id: PeerId, orders: Vec<AllOrders> },
-    CancelOrder { peer_id: PeerId, request: CancelOrderRequest }
+    CancelOrder { peer_id: PeerId, request: CancelOrderRequest },
+    CancelAllOrders { peer_id: PeerId, request: CancelAllOrdersRequest },
+    AmendOrder { peer_id: PeerId, amendment: OrderAmendment },
+    SinceHashes { peer_id: PeerId, hashes: Vec<B256> },
+    OrderSyncRequest { peer_id: PeerId, hashes: Vec<B256> },
+    OrderDigest { peer_id: PeerId, digest: OrderDigest },
+    GetPooledOrders { peer_id: PeerId, hashes: Vec<B256> }
 }
 
 #[derive(Debug)]
@@ -109,6 +123,12 @@ pub enum StromNetworkHandleMsg {
         msg:     StromMessage
     },
 
+    /// Sends the same encoded message to each of the peers.
+    SendEncodedMessage {
+        peer_ids: Vec<PeerId>,
+        msg:      EncodedStromMessage
+    },
+
     /// Broadcasts the storm message to all peers
     BroadcastStromMessage {
         msg: StromMessage
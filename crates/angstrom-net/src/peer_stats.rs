@@ -0,0 +1,269 @@
+//! Accounting of the Strom sessions the network manager runs, for operators
+//! to look into who the node is connected to and what they exchange.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    time::{SystemTime, UNIX_EPOCH}
+};
+
+use angstrom_types::primitive::PeerId;
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{
+    mpsc::{UnboundedReceiver, UnboundedSender},
+    oneshot
+};
+
+use crate::{Reputation, StromMessageID};
+
+/// How many connect and disconnect events are kept around.
+pub const NETWORK_EVENT_LOG_SIZE: usize = 1024;
+
+/// A connected peer and the messages exchanged with it over its current
+/// session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerStats {
+    pub peer_id:          PeerId,
+    /// Strom version of the peer's status. None until the session is
+    /// verified
+    pub protocol_version: Option<u8>,
+    pub reputation:       Reputation,
+    /// unix timestamp in milliseconds
+    pub connected_at:     u64,
+    pub received:         BTreeMap<StromMessageID, u64>,
+    pub sent:             BTreeMap<StromMessageID, u64>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NetworkEventKind {
+    Connected,
+    /// the status handshake succeeded
+    Verified,
+    /// verified with a resumption token instead of a status handshake
+    Resumed,
+    Disconnected
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkEventRecord {
+    /// increases by one with every event, starting at 1
+    pub seq:       u64,
+    /// unix timestamp in milliseconds
+    pub timestamp: u64,
+    pub peer_id:   PeerId,
+    pub kind:      NetworkEventKind
+}
+
+/// Counts what is exchanged with every connected peer and logs the last
+/// [`NETWORK_EVENT_LOG_SIZE`] session events.
+#[derive(Debug, Default)]
+pub struct NetworkStats {
+    peers:             HashMap<PeerId, PeerStats>,
+    /// versions peers verified with, for the sessions resumed without a status
+    protocol_versions: HashMap<PeerId, u8>,
+    events:            VecDeque<NetworkEventRecord>,
+    last_seq:          u64
+}
+
+impl NetworkStats {
+    pub fn on_session_established(&mut self, peer_id: PeerId) {
+        self.peers.insert(
+            peer_id,
+            PeerStats {
+                peer_id,
+                protocol_version: None,
+                reputation: Reputation::default(),
+                connected_at: now_millis(),
+                received: BTreeMap::new(),
+                sent: BTreeMap::new()
+            }
+        );
+        self.log(peer_id, NetworkEventKind::Connected);
+    }
+
+    pub fn on_session_verified(&mut self, peer_id: PeerId, version: u8) {
+        self.protocol_versions.insert(peer_id, version);
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            peer.protocol_version = Some(version);
+        }
+        self.log(peer_id, NetworkEventKind::Verified);
+    }
+
+    pub fn on_session_resumed(&mut self, peer_id: PeerId) {
+        // the resumption token is derived from the statuses of the last session, so
+        // the version can't have changed
+        let version = self.protocol_versions.get(&peer_id).copied();
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            peer.protocol_version = version;
+        }
+        self.log(peer_id, NetworkEventKind::Resumed);
+    }
+
+    pub fn on_session_closed(&mut self, peer_id: PeerId) {
+        self.peers.remove(&peer_id);
+        self.log(peer_id, NetworkEventKind::Disconnected);
+    }
+
+    pub fn on_received(&mut self, peer_id: PeerId, id: StromMessageID) {
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            *peer.received.entry(id).or_default() += 1;
+        }
+    }
+
+    pub fn on_sent(&mut self, peer_id: PeerId, id: StromMessageID) {
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            *peer.sent.entry(id).or_default() += 1;
+        }
+    }
+
+    /// Counts a message sent to every connected peer.
+    pub fn on_broadcast(&mut self, id: StromMessageID) {
+        self.peers
+            .values_mut()
+            .for_each(|peer| *peer.sent.entry(id).or_default() += 1);
+    }
+
+    /// The connected peers, with their reputation as given by `reputation`.
+    pub fn peers(&self, reputation: impl Fn(&PeerId) -> Reputation) -> Vec<PeerStats> {
+        let mut peers = self
+            .peers
+            .values()
+            .map(|peer| PeerStats { reputation: reputation(&peer.peer_id), ..peer.clone() })
+            .collect::<Vec<_>>();
+        peers.sort_by_key(|peer| peer.connected_at);
+
+        peers
+    }
+
+    /// The logged events after the one numbered `since`, oldest first.
+    pub fn events_since(&self, since: u64) -> Vec<NetworkEventRecord> {
+        self.events
+            .iter()
+            .filter(|event| event.seq > since)
+            .copied()
+            .collect()
+    }
+
+    fn log(&mut self, peer_id: PeerId, kind: NetworkEventKind) {
+        self.last_seq += 1;
+        if self.events.len() == NETWORK_EVENT_LOG_SIZE {
+            self.events.pop_front();
+        }
+        self.events.push_back(NetworkEventRecord {
+            seq: self.last_seq,
+            timestamp: now_millis(),
+            peer_id,
+            kind
+        });
+    }
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[derive(Debug)]
+pub enum NetworkStatsRequest {
+    Peers(oneshot::Sender<Vec<PeerStats>>),
+    /// the events after the given sequence number
+    Events(u64, oneshot::Sender<Vec<NetworkEventRecord>>)
+}
+
+/// Queries the [`NetworkStats`] of the network manager. It can be handed out
+/// before the network is running, requests are answered once it is.
+#[derive(Debug, Clone)]
+pub struct NetworkStatsHandle {
+    tx: UnboundedSender<NetworkStatsRequest>
+}
+
+impl NetworkStatsHandle {
+    pub fn new(tx: UnboundedSender<NetworkStatsRequest>) -> Self {
+        Self { tx }
+    }
+
+    /// A handle along with the receiver to give the network manager.
+    pub fn channel() -> (Self, UnboundedReceiver<NetworkStatsRequest>) {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        (Self::new(tx), rx)
+    }
+
+    /// `None` if the network manager isn't running.
+    pub async fn peers(&self) -> Option<Vec<PeerStats>> {
+        let (tx, rx) = oneshot::channel();
+        self.tx.send(NetworkStatsRequest::Peers(tx)).ok()?;
+        rx.map(Result::ok).await
+    }
+
+    /// `None` if the network manager isn't running.
+    pub async fn events(&self, since: u64) -> Option<Vec<NetworkEventRecord>> {
+        let (tx, rx) = oneshot::channel();
+        self.tx.send(NetworkStatsRequest::Events(since, tx)).ok()?;
+        rx.map(Result::ok).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_messages_of_connected_peers() {
+        let mut stats = NetworkStats::default();
+        let (a, b) = (PeerId::random(), PeerId::random());
+
+        stats.on_session_established(a);
+        stats.on_session_verified(a, 0);
+        stats.on_received(a, StromMessageID::PrePropose);
+        stats.on_received(a, StromMessageID::PrePropose);
+        stats.on_sent(a, StromMessageID::Propose);
+        stats.on_broadcast(StromMessageID::OrderDigest);
+        // not connected, nothing to count
+        stats.on_received(b, StromMessageID::PrePropose);
+
+        let peers = stats.peers(|_| -5);
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].protocol_version, Some(0));
+        assert_eq!(peers[0].reputation, -5);
+        assert_eq!(peers[0].received[&StromMessageID::PrePropose], 2);
+        assert_eq!(peers[0].sent[&StromMessageID::Propose], 1);
+        assert_eq!(peers[0].sent[&StromMessageID::OrderDigest], 1);
+
+        stats.on_session_closed(a);
+        assert!(stats.peers(|_| 0).is_empty());
+
+        // a resumed session keeps the version, but its counters start over
+        stats.on_session_established(a);
+        stats.on_session_resumed(a);
+        let peers = stats.peers(|_| 0);
+        assert_eq!(peers[0].protocol_version, Some(0));
+        assert!(peers[0].received.is_empty());
+    }
+
+    #[test]
+    fn event_log_is_bounded() {
+        let mut stats = NetworkStats::default();
+        let peer = PeerId::random();
+
+        stats.on_session_established(peer);
+        stats.on_session_closed(peer);
+        let events = stats.events_since(0);
+        assert_eq!(
+            events.iter().map(|e| (e.seq, e.kind)).collect::<Vec<_>>(),
+            vec![(1, NetworkEventKind::Connected), (2, NetworkEventKind::Disconnected)]
+        );
+        assert_eq!(stats.events_since(1).len(), 1);
+
+        for _ in 0..NETWORK_EVENT_LOG_SIZE {
+            stats.on_session_established(peer);
+        }
+        let events = stats.events_since(0);
+        assert_eq!(events.len(), NETWORK_EVENT_LOG_SIZE);
+        assert_eq!(events[0].seq, 3);
+    }
+}
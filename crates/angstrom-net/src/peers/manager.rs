@@ -6,7 +6,9 @@ use reth_network_peers::PeerId;
 use tracing::trace;
 
 pub use super::reputation::ReputationChangeWeights;
-use super::reputation::{is_banned_reputation, ReputationChangeKind};
+use super::reputation::{
+    is_banned_reputation, Reputation, ReputationChangeKind, DEFAULT_REPUTATION
+};
 
 /// Maintains the state of _all_ the peers known to the network.
 ///
@@ -55,6 +57,30 @@ impl PeersManager {
             .push_back(PeerAction::PeerRemoved(peer_id));
     }
 
+    /// Starts tracking the peer of a new session, or marks a known one as
+    /// connected again.
+    pub fn on_session_established(&mut self, peer_id: PeerId) {
+        self.peers
+            .entry(peer_id)
+            .or_insert_with(|| Peer::new(PeerKind::Basic))
+            .connected = true;
+    }
+
+    /// The peer stays tracked, so it keeps its reputation when it reconnects.
+    pub fn on_session_closed(&mut self, peer_id: PeerId) {
+        if let Some(peer) = self.peers.get_mut(&peer_id) {
+            peer.connected = false;
+        }
+    }
+
+    /// The reputation of the peer, the default one if it isn't tracked.
+    pub fn reputation(&self, peer_id: &PeerId) -> Reputation {
+        self.peers
+            .get(peer_id)
+            .map(|peer| peer.reputation)
+            .unwrap_or(DEFAULT_REPUTATION)
+    }
+
     pub fn change_weight(&mut self, peer_id: PeerId, weight: ReputationChangeKind) {
         if let Some(outcome) = self
             .peers
@@ -150,10 +176,10 @@ enum ReputationChangeOutcome {
 // === impl Peer ===
 
 impl Peer {
-    // fn new(kind: PeerKind, trusted: bool, connected: bool) -> Self {
-    //     Peer { reputation: DEFAULT_REPUTATION, kind, trusted, connected }
-    // }
-    //
+    fn new(kind: PeerKind) -> Self {
+        Peer { reputation: DEFAULT_REPUTATION, kind, connected: false }
+    }
+
     // /// Resets the reputation of the peer to the default value. This always
     // /// returns [`ReputationChangeOutcome::None`].
     // fn reset_reputation(&mut self) -> ReputationChangeOutcome {
@@ -1,62 +1,239 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet, VecDeque},
     num::NonZeroUsize,
+    ops::Range,
+    path::PathBuf,
     pin::Pin,
     sync::Arc,
-    task::{Context, Poll, Waker}
+    task::{Context, Poll, Waker},
+    time::{Instant, SystemTime, UNIX_EPOCH}
 };
 
 use alloy::primitives::{Address, FixedBytes, B256};
 use angstrom_eth::manager::EthEvent;
+use angstrom_metrics::OrderChannelMetricsWrapper;
 use angstrom_types::{
     block_sync::BlockSyncConsumer,
-    orders::{CancelOrderRequest, OrderLocation, OrderOrigin, OrderStatus},
-    primitive::{NewInitializedPool, OrderPoolNewOrderResult, PeerId, PoolId},
+    orders::{
+        BookDelta, BookSnapshot, CancelAllOrdersRequest, CancelOrderRequest, OrderAmendment,
+        OrderDigest, OrderLocation, OrderOrigin, OrderStatesSnapshot, OrderStatus, SourceTag
+    },
+    primitive::{
+        ConfigUpdate, Feature, FeatureFlags, NewInitializedPool, OrderPoolNewOrderResult, PeerId,
+        PoolId
+    },
     sol_bindings::grouped_orders::AllOrders
 };
-use futures::{Future, FutureExt, StreamExt};
+use futures::{
+    future::{self, BoxFuture},
+    stream::FuturesUnordered,
+    Future, FutureExt, StreamExt
+};
 use order_pool::{
-    order_storage::OrderStorage, OrderIndexer, OrderPoolHandle, PoolConfig, PoolInnerEvent,
-    PoolManagerUpdate
+    order_storage::OrderStorage, write_order_snapshot, OrderIndexer, OrderPoolHandle,
+    OrderSnapshot, OrderStorageView, PoolConfig, PoolInnerEvent, PoolManagerUpdate,
+    ORDER_COMMAND_CHANNEL_SIZE, ORDER_UPDATE_CHANNEL_SIZE
 };
+use parking_lot::RwLock;
 use reth_metrics::common::mpsc::UnboundedMeteredReceiver;
 use reth_tasks::TaskSpawner;
-use tokio::sync::{
-    broadcast,
-    mpsc::{error::SendError, unbounded_channel, UnboundedReceiver, UnboundedSender}
+use tokio::{
+    sync::{
+        broadcast,
+        mpsc::{channel, error::TrySendError, Receiver, Sender}
+    },
+    time::{interval_at, Interval, MissedTickBehavior}
 };
-use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
-use validation::order::{
-    state::pools::AngstromPoolsTracker, OrderValidationResults, OrderValidatorHandle
+use tokio_stream::wrappers::{BroadcastStream, ReceiverStream, UnboundedReceiverStream};
+use validation::{
+    order::{state::pools::AngstromPoolsTracker, OrderValidationResults, OrderValidatorHandle},
+    ValidationError
 };
 
-use crate::{LruCache, NetworkOrderEvent, StromMessage, StromNetworkEvent, StromNetworkHandle};
+use crate::{
+    validator_address, EncodedStromMessage, LruCache, NetworkOrderEvent, OrderAntiEntropy,
+    PropagationPolicy, StromMessage, StromNetworkEvent, StromNetworkHandle, DIGEST_INTERVAL,
+    SESSION_RESUMPTION_WINDOW
+};
 
 const MODULE_NAME: &str = "Order Pool";
 
 /// Cache limit of transactions to keep track of for a single peer.
 const PEER_ORDER_CACHE_LIMIT: usize = 1024 * 10;
 
-/// Api to interact with [`PoolManager`] task.
+/// Amount of recently propagated orders we keep around to re-sync peers that
+/// resume their session.
+const RECENT_ORDER_LIMIT: usize = 1024 * 10;
+
+/// Amount of orders received from peers we remember as such, to tell them
+/// apart from the ones submitted to us when propagating them.
+const PEER_ORDER_LIMIT: usize = 1024 * 10;
+
+/// Most orders we send a peer in answer to a [`StromMessage::GetPooledOrders`].
+const MAX_REQUESTED_ORDERS: usize = 1024;
+
+/// Api to interact with [`PoolManager`] task. Changes to the orders go through
+/// the task, reads are served from the view of the order storage without it.
 #[derive(Debug, Clone)]
 pub struct PoolHandle {
-    pub manager_tx:      UnboundedSender<OrderCommand>,
-    pub pool_manager_tx: tokio::sync::broadcast::Sender<PoolManagerUpdate>
+    pub manager_tx:      Sender<OrderCommand>,
+    pub pool_manager_tx: tokio::sync::broadcast::Sender<PoolManagerUpdate>,
+    pub view:            OrderStorageView
+}
+
+/// Requests to the validator. They all go through the pool manager, so orders
+/// are only ever validated against the state the pool keeps for their user.
+#[derive(Debug)]
+pub enum ValidationCommand {
+    /// validates the order without adding it to the pool
+    ValidateOnly(AllOrders, tokio::sync::oneshot::Sender<OrderValidationResults>),
+    /// validates the order and adds it to the pool once it is valid,
+    /// attributed to the source it was submitted through if any
+    ValidateAndInsert(
+        OrderOrigin,
+        AllOrders,
+        Option<SourceTag>,
+        tokio::sync::oneshot::Sender<OrderValidationResults>
+    ),
+    /// validates the pending orders of the addresses again with the next
+    /// block, the ones no longer valid are dropped
+    Revalidate(Vec<Address>),
+    NextValidNonce(Address, tokio::sync::oneshot::Sender<u64>),
+    InvalidatedNonces(Address, Range<u64>, tokio::sync::oneshot::Sender<Vec<u64>>)
+}
+
+impl ValidationCommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ValidateOnly(..) => "validate_only",
+            Self::ValidateAndInsert(..) => "new_order",
+            Self::Revalidate(..) => "revalidate",
+            Self::NextValidNonce(..) => "next_valid_nonce",
+            Self::InvalidatedNonces(..) => "invalidated_nonces"
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum OrderCommand {
-    // new orders
-    NewOrder(OrderOrigin, AllOrders, tokio::sync::oneshot::Sender<OrderValidationResults>),
+    Validate(ValidationCommand),
     CancelOrder(CancelOrderRequest, tokio::sync::oneshot::Sender<bool>),
-    PendingOrders(Address, tokio::sync::oneshot::Sender<Vec<AllOrders>>),
-    OrdersByPool(FixedBytes<32>, OrderLocation, tokio::sync::oneshot::Sender<Vec<AllOrders>>),
-    OrderStatus(B256, tokio::sync::oneshot::Sender<Option<OrderStatus>>)
+    /// cancels all of the orders with a single command, e.g the orders of a
+    /// session that closed
+    CancelOrders(Vec<CancelOrderRequest>, tokio::sync::oneshot::Sender<Vec<bool>>),
+    /// cancels the orders of an account, with the hashes of the cancelled ones
+    CancelAllOrders(CancelAllOrdersRequest, tokio::sync::oneshot::Sender<Vec<B256>>),
+    AmendOrder(OrderAmendment, tokio::sync::oneshot::Sender<OrderValidationResults>),
+    OrderStates(tokio::sync::oneshot::Sender<OrderStatesSnapshot>),
+    SubscribeBook(tokio::sync::oneshot::Sender<broadcast::Receiver<BookDelta>>),
+    /// stop taking in new orders, used when the node is shutting down
+    StopIntake(tokio::sync::oneshot::Sender<()>),
+    /// write all pending orders to the given file
+    Snapshot(PathBuf, tokio::sync::oneshot::Sender<std::io::Result<usize>>),
+    /// remove orders that were dropped elsewhere, e.g by the primary of a
+    /// standby
+    DropOrders(Vec<B256>)
+}
+
+impl OrderCommand {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Validate(cmd) => cmd.name(),
+            Self::CancelOrder(..) => "cancel_order",
+            Self::CancelOrders(..) => "cancel_orders",
+            Self::CancelAllOrders(..) => "cancel_all_orders",
+            Self::AmendOrder(..) => "amend_order",
+            Self::OrderStates(..) => "order_states",
+            Self::SubscribeBook(..) => "subscribe_book",
+            Self::StopIntake(..) => "stop_intake",
+            Self::Snapshot(..) => "snapshot",
+            Self::DropOrders(..) => "drop_orders"
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum OrderCommandError {
+    #[error("order pool is overloaded, try again later")]
+    Overloaded,
+    #[error("order pool is shut down")]
+    Closed
 }
 
 impl PoolHandle {
-    fn send(&self, cmd: OrderCommand) -> Result<(), SendError<OrderCommand>> {
-        self.manager_tx.send(cmd)
+    /// Order submissions are never queued beyond
+    /// [`ORDER_COMMAND_CHANNEL_SIZE`]. When the pool manager falls that far
+    /// behind new ones are rejected, which drops their response channel.
+    fn send(&self, cmd: OrderCommand) -> Result<(), OrderCommandError> {
+        self.manager_tx.try_send(cmd).map_err(|e| match e {
+            TrySendError::Full(cmd) => {
+                tracing::warn!(command = cmd.name(), "order pool is overloaded, rejecting command");
+                OrderChannelMetricsWrapper::new().incr_rejected_commands(cmd.name());
+                OrderCommandError::Overloaded
+            }
+            TrySendError::Closed(_) => OrderCommandError::Closed
+        })
+    }
+
+    /// Every other command waits for room in the channel instead, so a flood
+    /// of submissions can't make the pool miss a shutdown, a cancellation or
+    /// a query.
+    fn send_control(
+        &self,
+        cmd: OrderCommand
+    ) -> impl Future<Output = Result<(), OrderCommandError>> + Send {
+        let manager_tx = self.manager_tx.clone();
+        async move {
+            manager_tx
+                .send(cmd)
+                .await
+                .map_err(|_| OrderCommandError::Closed)
+        }
+    }
+
+    /// Rejects all orders submitted after this resolves, both from rpc and
+    /// from peers.
+    pub fn stop_intake(&self) -> impl Future<Output = ()> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let sent = self.send_control(OrderCommand::StopIntake(tx));
+        async move {
+            if sent.await.is_ok() {
+                let _ = rx.await;
+            }
+        }
+    }
+
+    /// Writes the pending orders to `path`, returning how many were written.
+    pub fn snapshot(&self, path: PathBuf) -> impl Future<Output = std::io::Result<usize>> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let sent = self.send_control(OrderCommand::Snapshot(path, tx));
+        async move {
+            let gone = || std::io::Error::new(std::io::ErrorKind::Other, "pool manager is gone");
+            sent.await.map_err(|_| gone())?;
+            rx.await.unwrap_or_else(|_| Err(gone()))
+        }
+    }
+
+    /// Every pending order, e.g to bring a standby up to date.
+    pub fn all_orders(&self) -> impl Future<Output = Vec<AllOrders>> + Send {
+        future::ready(self.view.all_orders())
+    }
+
+    /// Removes the orders without them being filled or cancelled here.
+    pub fn drop_orders(
+        &self,
+        order_hashes: Vec<B256>
+    ) -> impl Future<Output = Result<(), OrderCommandError>> + Send {
+        self.send_control(OrderCommand::DropOrders(order_hashes))
+    }
+
+    /// Validates the pending orders of `addresses` again with the next block,
+    /// e.g. when their state changed in a way the block doesn't tell.
+    pub fn revalidate(
+        &self,
+        addresses: Vec<Address>
+    ) -> impl Future<Output = Result<(), OrderCommandError>> + Send {
+        self.send_control(OrderCommand::Validate(ValidationCommand::Revalidate(addresses)))
     }
 }
 
@@ -66,9 +243,75 @@ impl OrderPoolHandle for PoolHandle {
         origin: OrderOrigin,
         order: AllOrders
     ) -> impl Future<Output = OrderPoolNewOrderResult> + Send {
+        self.new_tagged_order(origin, order, None)
+    }
+
+    fn new_tagged_order(
+        &self,
+        origin: OrderOrigin,
+        order: AllOrders,
+        source_tag: Option<SourceTag>
+    ) -> impl Future<Output = OrderPoolNewOrderResult> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let sent = self.send(OrderCommand::Validate(ValidationCommand::ValidateAndInsert(
+            origin, order, source_tag, tx
+        )));
+
+        async move {
+            match sent {
+                Ok(()) => rx.await.into(),
+                Err(e) => OrderPoolNewOrderResult::Error(e.to_string())
+            }
+        }
+    }
+
+    fn amend_order(
+        &self,
+        amendment: OrderAmendment
+    ) -> impl Future<Output = OrderPoolNewOrderResult> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let sent = self.send(OrderCommand::AmendOrder(amendment, tx));
+
+        async move {
+            match sent {
+                Ok(()) => rx.await.into(),
+                Err(e) => OrderPoolNewOrderResult::Error(e.to_string())
+            }
+        }
+    }
+
+    fn simulate_order(
+        &self,
+        order: AllOrders
+    ) -> impl Future<Output = Option<OrderValidationResults>> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let _ = self.send(OrderCommand::Validate(ValidationCommand::ValidateOnly(order, tx)));
+        rx.map(Result::ok)
+    }
+
+    fn next_valid_nonce(&self, user: Address) -> impl Future<Output = Option<u64>> + Send {
         let (tx, rx) = tokio::sync::oneshot::channel();
-        let _ = self.send(OrderCommand::NewOrder(origin, order, tx));
-        rx.map(Into::into)
+        let sent =
+            self.send_control(OrderCommand::Validate(ValidationCommand::NextValidNonce(user, tx)));
+        async move {
+            sent.await.ok()?;
+            rx.await.ok()
+        }
+    }
+
+    fn invalidated_nonces(
+        &self,
+        user: Address,
+        range: Range<u64>
+    ) -> impl Future<Output = Option<Vec<u64>>> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let sent = self.send_control(OrderCommand::Validate(ValidationCommand::InvalidatedNonces(
+            user, range, tx
+        )));
+        async move {
+            sent.await.ok()?;
+            rx.await.ok()
+        }
     }
 
     fn subscribe_orders(&self) -> BroadcastStream<PoolManagerUpdate> {
@@ -80,37 +323,79 @@ impl OrderPoolHandle for PoolHandle {
         pool_id: FixedBytes<32>,
         location: OrderLocation
     ) -> impl Future<Output = Vec<AllOrders>> + Send {
-        let (tx, rx) = tokio::sync::oneshot::channel();
-
-        let _ = self
-            .manager_tx
-            .send(OrderCommand::OrdersByPool(pool_id, location, tx));
-
-        rx.map(|v| v.unwrap_or_default())
+        future::ready(self.view.orders_by_pool(&pool_id, location))
     }
 
     fn fetch_order_status(
         &self,
         order_hash: B256
     ) -> impl Future<Output = Option<OrderStatus>> + Send {
+        future::ready(self.view.order_status(&order_hash))
+    }
+
+    fn fetch_order_states(&self) -> impl Future<Output = OrderStatesSnapshot> + Send {
         let (tx, rx) = tokio::sync::oneshot::channel();
-        let _ = self
-            .manager_tx
-            .send(OrderCommand::OrderStatus(order_hash, tx));
+        let sent = self.send_control(OrderCommand::OrderStates(tx));
 
-        rx.map(|v| v.ok().flatten())
+        async move {
+            match sent.await {
+                Ok(()) => rx.await.unwrap_or_default(),
+                Err(_) => OrderStatesSnapshot::default()
+            }
+        }
     }
 
-    fn pending_orders(&self, sender: Address) -> impl Future<Output = Vec<AllOrders>> + Send {
+    fn subscribe_book(&self) -> impl Future<Output = Option<BroadcastStream<BookDelta>>> + Send {
         let (tx, rx) = tokio::sync::oneshot::channel();
-        let _ = self.send(OrderCommand::PendingOrders(sender, tx)).is_ok();
-        rx.map(|res| res.unwrap_or_default())
+        let sent = self.send_control(OrderCommand::SubscribeBook(tx));
+
+        async move {
+            sent.await.ok()?;
+            rx.await.ok().map(BroadcastStream::new)
+        }
+    }
+
+    fn fetch_book(&self, pool_id: PoolId) -> impl Future<Output = Option<BookSnapshot>> + Send {
+        future::ready(self.view.book(&pool_id))
+    }
+
+    fn pending_orders(&self, sender: Address) -> impl Future<Output = Vec<AllOrders>> + Send {
+        future::ready(self.view.pending_orders(sender))
     }
 
     fn cancel_order(&self, req: CancelOrderRequest) -> impl Future<Output = bool> + Send {
         let (tx, rx) = tokio::sync::oneshot::channel();
-        let _ = self.send(OrderCommand::CancelOrder(req, tx));
-        rx.map(|res| res.unwrap_or(false))
+        let sent = self.send_control(OrderCommand::CancelOrder(req, tx));
+        async move { sent.await.is_ok() && rx.await.unwrap_or(false) }
+    }
+
+    fn cancel_orders(
+        &self,
+        reqs: Vec<CancelOrderRequest>
+    ) -> impl Future<Output = Vec<bool>> + Send {
+        let count = reqs.len();
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let sent = self.send_control(OrderCommand::CancelOrders(reqs, tx));
+        async move {
+            match sent.await {
+                Ok(()) => rx.await.unwrap_or_else(|_| vec![false; count]),
+                Err(_) => vec![false; count]
+            }
+        }
+    }
+
+    fn cancel_all_orders(
+        &self,
+        req: CancelAllOrdersRequest
+    ) -> impl Future<Output = Vec<B256>> + Send {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let sent = self.send_control(OrderCommand::CancelAllOrders(req, tx));
+        async move {
+            match sent.await {
+                Ok(()) => rx.await.unwrap_or_default(),
+                Err(_) => vec![]
+            }
+        }
     }
 }
 
@@ -126,6 +411,10 @@ where
     strom_network_events: UnboundedReceiverStream<StromNetworkEvent>,
     eth_network_events:   UnboundedReceiverStream<EthEvent>,
     order_events:         UnboundedMeteredReceiver<NetworkOrderEvent>,
+    config_updates:       Option<BroadcastStream<ConfigUpdate>>,
+    feature_flags:        FeatureFlags,
+    propagation:          PropagationPolicy,
+    validator_set:        Arc<RwLock<HashSet<Address>>>,
     config:               PoolConfig
 }
 
@@ -150,6 +439,10 @@ where
             network_handle,
             validator,
             order_storage,
+            config_updates: None,
+            feature_flags: FeatureFlags::default(),
+            propagation: PropagationPolicy::default(),
+            validator_set: Default::default(),
             config: Default::default()
         }
     }
@@ -164,41 +457,52 @@ where
         self
     }
 
+    pub fn with_config_updates(mut self, updates: broadcast::Receiver<ConfigUpdate>) -> Self {
+        self.config_updates = Some(BroadcastStream::new(updates));
+        self
+    }
+
+    pub fn with_feature_flags(mut self, feature_flags: FeatureFlags) -> Self {
+        self.feature_flags = feature_flags;
+        self
+    }
+
+    /// Who the orders are gossiped to, every peer by default.
+    pub fn with_propagation_policy(mut self, propagation: PropagationPolicy) -> Self {
+        self.propagation = propagation;
+        self
+    }
+
+    /// The validators [`GossipTarget::Validators`](crate::GossipTarget)
+    /// restricts gossip to, see
+    /// [`NetworkBuilder::validator_set`](crate::NetworkBuilder).
+    pub fn with_validator_set(mut self, validator_set: Arc<RwLock<HashSet<Address>>>) -> Self {
+        self.validator_set = validator_set;
+        self
+    }
+
     pub fn build_with_channels<TP: TaskSpawner>(
         self,
         task_spawner: TP,
-        tx: UnboundedSender<OrderCommand>,
-        rx: UnboundedReceiver<OrderCommand>,
+        tx: Sender<OrderCommand>,
+        rx: Receiver<OrderCommand>,
         pool_storage: AngstromPoolsTracker,
         pool_manager_tx: tokio::sync::broadcast::Sender<PoolManagerUpdate>
     ) -> PoolHandle {
-        let rx = UnboundedReceiverStream::new(rx);
         let order_storage = self
             .order_storage
+            .clone()
             .unwrap_or_else(|| Arc::new(OrderStorage::new(&self.config)));
-        let handle =
-            PoolHandle { manager_tx: tx.clone(), pool_manager_tx: pool_manager_tx.clone() };
-        let inner = OrderIndexer::new(
-            self.validator.clone(),
-            order_storage.clone(),
-            0,
-            pool_manager_tx.clone(),
-            pool_storage
-        );
+        let handle = PoolHandle {
+            manager_tx:      tx.clone(),
+            pool_manager_tx: pool_manager_tx.clone(),
+            view:            order_storage.view()
+        };
         self.global_sync.register(MODULE_NAME);
 
         task_spawner.spawn_critical(
             "transaction manager",
-            Box::pin(PoolManager {
-                eth_network_events:   self.eth_network_events,
-                strom_network_events: self.strom_network_events,
-                order_events:         self.order_events,
-                peer_to_info:         HashMap::default(),
-                order_indexer:        inner,
-                network:              self.network_handle,
-                command_rx:           rx,
-                global_sync:          self.global_sync
-            })
+            Box::pin(self.into_manager(order_storage, rx, pool_storage, pool_manager_tx))
         );
 
         handle
@@ -209,38 +513,60 @@ where
         pool_storage: AngstromPoolsTracker,
         task_spawner: TP
     ) -> PoolHandle {
-        let (tx, rx) = unbounded_channel();
-        let rx = UnboundedReceiverStream::new(rx);
+        let (tx, rx) = channel(ORDER_COMMAND_CHANNEL_SIZE);
         let order_storage = self
             .order_storage
+            .clone()
             .unwrap_or_else(|| Arc::new(OrderStorage::new(&self.config)));
-        let (pool_manager_tx, _) = broadcast::channel(100);
-        let handle =
-            PoolHandle { manager_tx: tx.clone(), pool_manager_tx: pool_manager_tx.clone() };
-        let inner = OrderIndexer::new(
-            self.validator.clone(),
-            order_storage.clone(),
-            0,
-            pool_manager_tx.clone(),
-            pool_storage
-        );
+        let (pool_manager_tx, _) = broadcast::channel(ORDER_UPDATE_CHANNEL_SIZE);
+        let handle = PoolHandle {
+            manager_tx:      tx.clone(),
+            pool_manager_tx: pool_manager_tx.clone(),
+            view:            order_storage.view()
+        };
 
         task_spawner.spawn_critical(
             "transaction manager",
-            Box::pin(PoolManager {
-                eth_network_events:   self.eth_network_events,
-                strom_network_events: self.strom_network_events,
-                order_events:         self.order_events,
-                peer_to_info:         HashMap::default(),
-                order_indexer:        inner,
-                network:              self.network_handle,
-                command_rx:           rx,
-                global_sync:          self.global_sync
-            })
+            Box::pin(self.into_manager(order_storage, rx, pool_storage, pool_manager_tx))
         );
 
         handle
     }
+
+    /// The manager taking commands from `command_rx`, with its book in
+    /// `order_storage`.
+    fn into_manager(
+        self,
+        order_storage: Arc<OrderStorage>,
+        command_rx: Receiver<OrderCommand>,
+        pool_storage: AngstromPoolsTracker,
+        pool_manager_tx: broadcast::Sender<PoolManagerUpdate>
+    ) -> PoolManager<V, GlobalSync> {
+        let order_indexer =
+            OrderIndexer::new(self.validator, order_storage, 0, pool_manager_tx, pool_storage);
+
+        PoolManager {
+            eth_network_events: self.eth_network_events,
+            strom_network_events: self.strom_network_events,
+            order_events: self.order_events,
+            peer_to_info: HashMap::default(),
+            disconnected_peers: HashMap::default(),
+            recent_orders: VecDeque::default(),
+            order_indexer,
+            network: self.network_handle,
+            command_rx: ReceiverStream::new(command_rx),
+            validator_queries: FuturesUnordered::new(),
+            global_sync: self.global_sync,
+            config_updates: self.config_updates,
+            feature_flags: self.feature_flags,
+            anti_entropy: OrderAntiEntropy::new(self.config.order_ttl),
+            digest_interval: digest_interval(),
+            propagation: self.propagation,
+            validator_set: self.validator_set,
+            from_peers: LruCache::new(NonZeroUsize::new(PEER_ORDER_LIMIT).unwrap()),
+            intake: OrderIntake::default()
+        }
+    }
 }
 
 pub struct PoolManager<V, GlobalSync>
@@ -261,11 +587,47 @@ where
     /// have been filled  
     eth_network_events:   UnboundedReceiverStream<EthEvent>,
     /// receiver half of the commands to the pool manager
-    command_rx:           UnboundedReceiverStream<OrderCommand>,
+    command_rx:           ReceiverStream<OrderCommand>,
+    /// Requests to the validator that don't change the pool, they answer
+    /// their caller once done.
+    validator_queries:    FuturesUnordered<BoxFuture<'static, ()>>,
     /// Incoming events from the ProtocolManager.
     order_events:         UnboundedMeteredReceiver<NetworkOrderEvent>,
     /// All the connected peers.
-    peer_to_info:         HashMap<PeerId, StromPeer>
+    peer_to_info:         HashMap<PeerId, StromPeer>,
+    /// Peers whose session was closed recently, kept so a resumed session
+    /// doesn't have to warm up its order cache again.
+    disconnected_peers:   HashMap<PeerId, (StromPeer, Instant)>,
+    /// Orders we propagated recently along with when we did so.
+    recent_orders:        VecDeque<(Instant, AllOrders)>,
+    /// Live configuration changes made through the admin api.
+    config_updates:       Option<BroadcastStream<ConfigUpdate>>,
+    feature_flags:        FeatureFlags,
+    /// Our book against the digests of our peers.
+    anti_entropy:         OrderAntiEntropy,
+    /// Ticks when the digest of our book is due.
+    digest_interval:      Interval,
+    /// Who the orders are gossiped to.
+    propagation:          PropagationPolicy,
+    validator_set:        Arc<RwLock<HashSet<Address>>>,
+    /// Orders we got from peers rather than through the rpc.
+    from_peers:           LruCache<B256>,
+    /// whether new orders are taken in
+    intake:               OrderIntake
+}
+
+/// Intake can be paused and resumed through the admin api, but once the node
+/// starts shutting down it stays stopped.
+#[derive(Debug, Default, Clone, Copy)]
+struct OrderIntake {
+    paused:  bool,
+    stopped: bool
+}
+
+impl OrderIntake {
+    fn is_open(&self) -> bool {
+        !self.paused && !self.stopped
+    }
 }
 
 impl<V, GlobalSync> PoolManager<V, GlobalSync>
@@ -275,32 +637,133 @@ where
 {
     fn on_command(&mut self, cmd: OrderCommand) {
         match cmd {
-            OrderCommand::NewOrder(_, order, validation_response) => self
-                .order_indexer
-                .new_rpc_order(OrderOrigin::External, order, validation_response),
+            OrderCommand::Validate(cmd) => self.on_validation_command(cmd),
             OrderCommand::CancelOrder(req, receiver) => {
-                let res = self.order_indexer.cancel_order(&req);
-                if res {
-                    self.broadcast_cancel_to_peers(req);
-                }
+                let _ = receiver.send(self.cancel_order(req));
+            }
+            OrderCommand::CancelOrders(reqs, receiver) => {
+                let res = reqs
+                    .into_iter()
+                    .map(|req| self.cancel_order(req))
+                    .collect::<Vec<_>>();
+                tracing::debug!(
+                    cancelled = res.iter().filter(|cancelled| **cancelled).count(),
+                    requested = res.len(),
+                    "bulk cancelled orders"
+                );
                 let _ = receiver.send(res);
             }
-            OrderCommand::PendingOrders(from, receiver) => {
-                let res = self.order_indexer.pending_orders_for_address(from);
-                let _ = receiver.send(res.into_iter().map(|o| o.order).collect());
+            OrderCommand::CancelAllOrders(req, receiver) => {
+                let _ = receiver.send(self.cancel_all_orders(req, None).unwrap_or_default());
             }
-            OrderCommand::OrderStatus(order_hash, tx) => {
-                let res = self.order_indexer.order_status(order_hash);
+            OrderCommand::AmendOrder(amendment, validation_response) => {
+                if !self.intake.is_open() {
+                    let _ = validation_response.send(OrderValidationResults::Invalid(
+                        amendment.order.order_hash(),
+                        ValidationError::NotAcceptingOrders
+                    ));
+                    return
+                }
+                self.order_indexer
+                    .new_rpc_amendment(amendment, validation_response)
+            }
+            OrderCommand::OrderStates(tx) => {
+                let _ = tx.send(self.order_indexer.order_states());
+            }
+            OrderCommand::SubscribeBook(tx) => {
+                let _ = tx.send(self.order_indexer.subscribe_book());
+            }
+            OrderCommand::StopIntake(tx) => {
+                tracing::info!("no longer accepting new orders");
+                self.intake.stopped = true;
+                let _ = tx.send(());
+            }
+            OrderCommand::Snapshot(path, tx) => {
+                let snapshot = OrderSnapshot {
+                    block_number: self.order_indexer.block_number(),
+                    orders:       self.order_indexer.orders_not_in(&HashSet::new())
+                };
+                let res = write_order_snapshot(&path, &snapshot).map(|_| snapshot.orders.len());
                 let _ = tx.send(res);
             }
+            OrderCommand::DropOrders(order_hashes) => {
+                let dropped = self.order_indexer.prune_orders(&order_hashes);
+                tracing::debug!(dropped = dropped.len(), "dropped orders");
+            }
+        }
+    }
 
-            OrderCommand::OrdersByPool(pool_id, location, tx) => {
-                let res = self.order_indexer.orders_by_pool(pool_id, location);
-                let _ = tx.send(res);
+    fn on_validation_command(&mut self, cmd: ValidationCommand) {
+        let validator = self.order_indexer.validation_handle().clone();
+        match cmd {
+            ValidationCommand::ValidateOnly(order, tx) => {
+                self.validator_queries.push(Box::pin(async move {
+                    let _ = tx.send(validator.simulate_order(order).await);
+                }));
+            }
+            ValidationCommand::ValidateAndInsert(_, order, source_tag, validation_response) => {
+                if let Some(error) = self.intake_refusal(&order) {
+                    let _ = validation_response
+                        .send(OrderValidationResults::Invalid(order.order_hash(), error));
+                    return
+                }
+                match source_tag {
+                    Some(source_tag) => self.order_indexer.new_tagged_rpc_order(
+                        OrderOrigin::External,
+                        order,
+                        source_tag,
+                        validation_response
+                    ),
+                    None => self.order_indexer.new_rpc_order(
+                        OrderOrigin::External,
+                        order,
+                        validation_response
+                    )
+                }
+            }
+            ValidationCommand::Revalidate(addresses) => {
+                tracing::debug!(addresses = addresses.len(), "revalidating orders with next block");
+                self.order_indexer.revalidate(addresses);
+            }
+            ValidationCommand::NextValidNonce(user, tx) => {
+                self.validator_queries.push(Box::pin(async move {
+                    let _ = tx.send(validator.next_valid_nonce(user).await);
+                }));
+            }
+            ValidationCommand::InvalidatedNonces(user, range, tx) => {
+                self.validator_queries.push(Box::pin(async move {
+                    let _ = tx.send(validator.invalidated_nonces(user, range).await);
+                }));
             }
         }
     }
 
+    /// Why a new order is turned away before it is validated, if it is.
+    fn intake_refusal(&self, order: &AllOrders) -> Option<ValidationError> {
+        if !self.intake.is_open() {
+            return Some(ValidationError::NotAcceptingOrders)
+        }
+
+        (order.is_composable() && !self.feature_flags.is_enabled(Feature::ComposableOrders))
+            .then_some(ValidationError::FeatureDisabled(Feature::ComposableOrders))
+    }
+
+    fn on_config_update(&mut self, update: ConfigUpdate) {
+        match update {
+            ConfigUpdate::PoolLimits(limits) => {
+                tracing::info!(?limits, "updating order pool limits");
+                self.order_indexer.update_limits(limits);
+            }
+            ConfigUpdate::OrderIntake { paused } => {
+                tracing::info!(paused, "updating order intake");
+                self.intake.paused = paused;
+            }
+            ConfigUpdate::SubmissionTargets(_)
+            | ConfigUpdate::ResumePool(_)
+            | ConfigUpdate::ClearSubmission(_) => {}
+        }
+    }
+
     fn on_eth_event(&mut self, eth: EthEvent, waker: Waker) {
         match eth {
             EthEvent::NewBlockTransitions { block_number, filled_orders, address_changeset } => {
@@ -341,9 +804,13 @@ where
         match event {
             NetworkOrderEvent::IncomingOrders { peer_id, orders } => {
                 orders.into_iter().for_each(|order| {
+                    if self.intake_refusal(&order).is_some() {
+                        return
+                    }
                     self.peer_to_info
                         .get_mut(&peer_id)
                         .map(|peer| peer.orders.insert(order.order_hash()));
+                    self.from_peers.insert(order.order_hash());
 
                     self.order_indexer.new_network_order(
                         peer_id,
@@ -353,49 +820,220 @@ where
                 });
             }
             NetworkOrderEvent::CancelOrder { request, .. } => {
-                let res = self.order_indexer.cancel_order(&request);
-                if res {
-                    self.broadcast_cancel_to_peers(request);
+                self.cancel_order(request);
+            }
+            NetworkOrderEvent::CancelAllOrders { peer_id, request } => {
+                self.cancel_all_orders(request, Some(peer_id));
+            }
+            NetworkOrderEvent::AmendOrder { peer_id, amendment } => {
+                if !self.intake.is_open() {
+                    return
                 }
+                self.peer_to_info
+                    .get_mut(&peer_id)
+                    .map(|peer| peer.orders.insert(amendment.order.order_hash()));
+
+                self.order_indexer.new_network_amendment(peer_id, amendment);
+            }
+            NetworkOrderEvent::SinceHashes { peer_id, hashes } => {
+                self.on_since_hashes(peer_id, hashes);
             }
+            NetworkOrderEvent::OrderSyncRequest { peer_id, hashes } => {
+                self.on_order_sync_request(peer_id, hashes);
+            }
+            NetworkOrderEvent::OrderDigest { peer_id, digest } => {
+                self.on_order_digest(peer_id, digest);
+            }
+            NetworkOrderEvent::GetPooledOrders { peer_id, hashes } => {
+                self.on_get_pooled_orders(peer_id, hashes);
+            }
+        }
+    }
+
+    /// The peer told us what is in its book. The orders in it don't have to
+    /// be propagated to the peer anymore.
+    fn on_order_digest(&mut self, peer_id: PeerId, digest: OrderDigest) {
+        let Some(peer) = self.peer_to_info.get_mut(&peer_id) else { return };
+        digest.orders.iter().for_each(|entry| {
+            peer.orders.insert(entry.order_hash);
+        });
+
+        self.anti_entropy.on_digest(peer_id, digest);
+    }
+
+    /// Drops the orders that expired or that the rest of the network no longer
+    /// has, then sends the digest of what is left to our peers.
+    fn on_digest_tick(&mut self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        self.anti_entropy
+            .sync_book(self.order_indexer.order_deadlines(), now);
+
+        let peers = &self.peer_to_info;
+        let prunable = self.anti_entropy.take_prunable(now, |hash, peer_id| {
+            peers
+                .get(peer_id)
+                .is_some_and(|peer| peer.orders.contains(hash))
+        });
+        if !prunable.is_empty() {
+            let pruned = self.order_indexer.prune_orders(&prunable);
+            tracing::debug!(pruned = pruned.len(), "pruned orders the network dropped");
+        }
+
+        if self.peer_to_info.is_empty() {
+            return
+        }
+        let digest = self.anti_entropy.digest(self.order_indexer.block_number());
+        self.network
+            .broadcast_message(StromMessage::OrderDigest(digest));
+    }
+
+    /// A freshly verified peer sent us the contents of its book. Send over all
+    /// the orders it doesn't have yet so it converges before the next auction.
+    fn on_order_sync_request(&mut self, peer_id: PeerId, hashes: Vec<B256>) {
+        if !self.peer_to_info.contains_key(&peer_id) {
+            return
+        }
+        let known = hashes.into_iter().collect::<HashSet<_>>();
+        let missing = self
+            .order_indexer
+            .orders_not_in(&known)
+            .into_iter()
+            .filter(|order| self.propagates_to(order, &peer_id))
+            .collect::<Vec<_>>();
+
+        let Some(peer) = self.peer_to_info.get_mut(&peer_id) else { return };
+        known.iter().for_each(|hash| {
+            peer.orders.insert(*hash);
+        });
+        if missing.is_empty() {
+            return
+        }
+
+        tracing::debug!(?peer_id, missing = missing.len(), "syncing book with new peer");
+        missing.iter().for_each(|order| {
+            peer.orders.insert(order.order_hash());
+        });
+        self.network
+            .send_message(peer_id, StromMessage::PropagatePooledOrders(missing));
+    }
+
+    /// The peer is verifying a proposal with orders it doesn't have. Send over
+    /// the ones of them we have.
+    fn on_get_pooled_orders(&mut self, peer_id: PeerId, mut hashes: Vec<B256>) {
+        let Some(peer) = self.peer_to_info.get_mut(&peer_id) else { return };
+        hashes.truncate(MAX_REQUESTED_ORDERS);
+
+        let orders = self.order_indexer.orders_by_hash(&hashes);
+        if orders.is_empty() {
+            return
+        }
+
+        tracing::debug!(
+            ?peer_id,
+            requested = hashes.len(),
+            found = orders.len(),
+            "sending requested orders"
+        );
+        orders.iter().for_each(|order| {
+            peer.orders.insert(order.order_hash());
+        });
+        self.network
+            .send_message(peer_id, StromMessage::PropagatePooledOrders(orders));
+    }
+
+    /// The peer told us what it has seen while disconnected. Mark those orders
+    /// as known and send over whatever it missed.
+    fn on_since_hashes(&mut self, peer_id: PeerId, hashes: Vec<B256>) {
+        let Some(peer) = self.peer_to_info.get_mut(&peer_id) else { return };
+        hashes.into_iter().for_each(|hash| {
+            peer.orders.insert(hash);
+        });
+
+        let Some(since) = peer.disconnected_at.take() else { return };
+        let peer = &self.peer_to_info[&peer_id];
+        let missed = self
+            .recent_orders
+            .iter()
+            .filter(|(seen, order)| *seen >= since && !peer.orders.contains(&order.order_hash()))
+            .map(|(_, order)| order.clone())
+            .filter(|order| self.propagates_to(order, &peer_id))
+            .collect::<Vec<_>>();
+        let Some(peer) = self.peer_to_info.get_mut(&peer_id) else { return };
+
+        if missed.is_empty() {
+            return
+        }
+
+        tracing::debug!(?peer_id, missed = missed.len(), "re-syncing resumed peer");
+        missed.iter().for_each(|order| {
+            peer.orders.insert(order.order_hash());
+        });
+        self.network
+            .send_message(peer_id, StromMessage::PropagatePooledOrders(missed));
+    }
+
+    fn on_session_resumed(&mut self, peer_id: PeerId) {
+        let Some((mut peer, since)) = self
+            .disconnected_peers
+            .remove(&peer_id)
+            .filter(|(_, since)| since.elapsed() <= SESSION_RESUMPTION_WINDOW)
+        else {
+            return
+        };
+
+        let hashes = self
+            .recent_orders
+            .iter()
+            .filter(|(seen, _)| *seen >= since)
+            .map(|(_, order)| order.order_hash())
+            .collect::<Vec<_>>();
+
+        peer.disconnected_at = Some(since);
+        self.peer_to_info.insert(peer_id, peer);
+        self.network
+            .send_message(peer_id, StromMessage::SinceHashes(hashes));
+    }
+
+    fn on_session_closed(&mut self, peer_id: PeerId) {
+        let now = Instant::now();
+        self.disconnected_peers
+            .retain(|_, (_, since)| now.duration_since(*since) <= SESSION_RESUMPTION_WINDOW);
+
+        if let Some(peer) = self.peer_to_info.remove(&peer_id) {
+            self.disconnected_peers.insert(peer_id, (peer, now));
         }
+        self.anti_entropy.remove_peer(&peer_id);
     }
 
     fn on_network_event(&mut self, event: StromNetworkEvent) {
         match event {
             StromNetworkEvent::SessionEstablished { peer_id } => {
                 // insert a new peer into the peerset
-                self.peer_to_info.insert(
+                self.peer_to_info.insert(peer_id, StromPeer::new());
+            }
+            StromNetworkEvent::SessionVerified { peer_id } => {
+                // ask the peer for everything in its book that we don't have
+                self.network.send_message(
                     peer_id,
-                    StromPeer {
-                        orders:        LruCache::new(
-                            NonZeroUsize::new(PEER_ORDER_CACHE_LIMIT).unwrap()
-                        ),
-                        cancellations: LruCache::new(
-                            NonZeroUsize::new(PEER_ORDER_CACHE_LIMIT).unwrap()
-                        )
-                    }
+                    StromMessage::OrderSyncRequest(self.order_indexer.order_hashes())
                 );
             }
+            StromNetworkEvent::SessionResumed { peer_id } => {
+                self.on_session_resumed(peer_id);
+            }
             StromNetworkEvent::SessionClosed { peer_id, .. } => {
-                // remove the peer
-                self.peer_to_info.remove(&peer_id);
+                // keep the peer around in case it resumes the session
+                self.on_session_closed(peer_id);
             }
             StromNetworkEvent::PeerRemoved(peer_id) => {
                 self.peer_to_info.remove(&peer_id);
+                self.anti_entropy.remove_peer(&peer_id);
             }
             StromNetworkEvent::PeerAdded(peer_id) => {
-                self.peer_to_info.insert(
-                    peer_id,
-                    StromPeer {
-                        orders:        LruCache::new(
-                            NonZeroUsize::new(PEER_ORDER_CACHE_LIMIT).unwrap()
-                        ),
-                        cancellations: LruCache::new(
-                            NonZeroUsize::new(PEER_ORDER_CACHE_LIMIT).unwrap()
-                        )
-                    }
-                );
+                self.peer_to_info.insert(peer_id, StromPeer::new());
             }
         }
     }
@@ -405,6 +1043,10 @@ where
             .into_iter()
             .filter_map(|order| match order {
                 PoolInnerEvent::Propagation(order) => Some(order),
+                PoolInnerEvent::AmendmentPropagation(amendment) => {
+                    self.broadcast_amendment_to_peers(amendment);
+                    None
+                }
                 PoolInnerEvent::BadOrderMessages(o) => {
                     o.into_iter().for_each(|peer| {
                         self.network.peer_reputation_change(
@@ -426,6 +1068,43 @@ where
         self.broadcast_orders_to_peers(valid_orders);
     }
 
+    fn cancel_order(&mut self, req: CancelOrderRequest) -> bool {
+        let res = self.order_indexer.cancel_order(&req);
+        if res {
+            self.broadcast_cancel_to_peers(req);
+        }
+        res
+    }
+
+    /// Forwards the request to the peers but the one it came from, unless it is
+    /// stale. Peers only forward it once for the same reason, so it doesn't
+    /// loop.
+    fn cancel_all_orders(
+        &mut self,
+        req: CancelAllOrdersRequest,
+        from: Option<PeerId>
+    ) -> Option<Vec<B256>> {
+        let cancelled = self.order_indexer.cancel_all_orders(&req)?;
+        tracing::debug!(
+            user = ?req.user_address,
+            cancelled = cancelled.len(),
+            "cancelled all orders of account"
+        );
+
+        let peer_ids = self
+            .peer_to_info
+            .keys()
+            .filter(|peer_id| Some(**peer_id) != from)
+            .copied()
+            .collect::<Vec<_>>();
+        if !peer_ids.is_empty() {
+            let msg = EncodedStromMessage::new(&StromMessage::OrderCancelAll(req));
+            self.network.send_encoded_message(peer_ids, msg);
+        }
+
+        Some(cancelled)
+    }
+
     fn broadcast_cancel_to_peers(&mut self, cancel: CancelOrderRequest) {
         for (peer_id, info) in self.peer_to_info.iter_mut() {
             let order_hash = cancel.order_id;
@@ -438,22 +1117,116 @@ where
         }
     }
 
+    /// Peers that have seen the amended order, e.g. because they sent it to us,
+    /// don't need the amendment.
+    fn broadcast_amendment_to_peers(&mut self, amendment: OrderAmendment) {
+        let order = AllOrders::Standing(amendment.order.clone());
+        if self.recent_orders.len() == RECENT_ORDER_LIMIT {
+            self.recent_orders.pop_front();
+        }
+        self.recent_orders
+            .push_back((Instant::now(), order.clone()));
+
+        let order_hash = order.order_hash();
+        let peer_ids = self
+            .peer_to_info
+            .iter_mut()
+            .filter(|(_, info)| info.orders.insert(order_hash))
+            .map(|(peer_id, _)| *peer_id)
+            .collect::<Vec<_>>();
+        if !peer_ids.is_empty() {
+            let msg = EncodedStromMessage::new(&StromMessage::OrderAmendment(amendment));
+            self.network.send_encoded_message(peer_ids, msg);
+        }
+    }
+
+    /// Whether the propagation policy sends `order` to `peer_id`, out of the
+    /// peers we are connected to.
+    fn propagates_to(&self, order: &AllOrders, peer_id: &PeerId) -> bool {
+        let validators = self.validator_set.read();
+        self.propagation
+            .targets(
+                order,
+                self.from_peers.contains(&order.order_hash()),
+                self.peer_to_info.keys(),
+                |peer_id| validators.contains(&validator_address(peer_id))
+            )
+            .contains(peer_id)
+    }
+
+    /// Peers that are missing the same orders are sent a single message that
+    /// is only encoded once. Orders only go to the peers the propagation policy
+    /// picks for them.
     fn broadcast_orders_to_peers(&mut self, valid_orders: Vec<AllOrders>) {
+        let now = Instant::now();
         for order in valid_orders.iter() {
-            for (peer_id, info) in self.peer_to_info.iter_mut() {
-                let order_hash = order.order_hash();
-                if !info.orders.contains(&order_hash) {
-                    self.network.send_message(
-                        *peer_id,
-                        StromMessage::PropagatePooledOrders(vec![order.clone()])
-                    );
-                    info.orders.insert(order_hash);
-                }
+            if self.recent_orders.len() == RECENT_ORDER_LIMIT {
+                self.recent_orders.pop_front();
             }
+            self.recent_orders.push_back((now, order.clone()));
+        }
+
+        let hashes = valid_orders
+            .iter()
+            .map(AllOrders::order_hash)
+            .collect::<Vec<_>>();
+        let validators = self.validator_set.read();
+        let targets = valid_orders
+            .iter()
+            .zip(&hashes)
+            .map(|(order, hash)| {
+                self.propagation
+                    .targets(
+                        order,
+                        self.from_peers.contains(hash),
+                        self.peer_to_info.keys(),
+                        |peer_id| validators.contains(&validator_address(peer_id))
+                    )
+                    .into_iter()
+                    .collect::<HashSet<_>>()
+            })
+            .collect::<Vec<_>>();
+        drop(validators);
+
+        for (missing, peer_ids) in
+            peers_by_missing_orders(&mut self.peer_to_info, &hashes, |idx, peer_id| {
+                targets[idx].contains(peer_id)
+            })
+        {
+            let orders = missing
+                .into_iter()
+                .map(|idx| valid_orders[idx].clone())
+                .collect();
+            let msg = EncodedStromMessage::new(&StromMessage::PropagatePooledOrders(orders));
+            self.network.send_encoded_message(peer_ids, msg);
         }
     }
 }
 
+/// Groups the peers by the orders they haven't seen yet, given as indices into
+/// `hashes`, and marks those orders as seen. Only the orders `to_peer` allows
+/// for a peer count, peers missing none of them are left out.
+fn peers_by_missing_orders(
+    peers: &mut HashMap<PeerId, StromPeer>,
+    hashes: &[B256],
+    to_peer: impl Fn(usize, &PeerId) -> bool
+) -> HashMap<Vec<usize>, Vec<PeerId>> {
+    let mut groups: HashMap<Vec<usize>, Vec<PeerId>> = HashMap::new();
+    for (peer_id, info) in peers.iter_mut() {
+        let missing = hashes
+            .iter()
+            .enumerate()
+            .filter(|(idx, hash)| to_peer(*idx, peer_id) && info.orders.insert(**hash))
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            groups.entry(missing).or_default().push(*peer_id);
+        }
+    }
+
+    groups
+}
+
 impl<V, GlobalSync> Future for PoolManager<V, GlobalSync>
 where
     V: OrderValidatorHandle<Order = AllOrders> + Unpin,
@@ -471,6 +1244,18 @@ where
                 cx.waker().wake_by_ref();
                 break;
             }
+            // apply config changes before handling anything they affect
+            while let Some(Poll::Ready(Some(update))) = this
+                .config_updates
+                .as_mut()
+                .map(|updates| updates.poll_next_unpin(cx))
+            {
+                match update {
+                    Ok(update) => this.on_config_update(update),
+                    Err(e) => tracing::warn!(%e, "missed config updates")
+                }
+            }
+
             // pull all eth events
             while let Poll::Ready(Some(eth)) = this.eth_network_events.poll_next_unpin(cx) {
                 this.on_eth_event(eth, cx.waker().clone());
@@ -486,6 +1271,8 @@ where
                 this.on_pool_events(orders, || cx.waker().clone());
             }
 
+            while let Poll::Ready(Some(())) = this.validator_queries.poll_next_unpin(cx) {}
+
             // halt dealing with these till we have synced
             if this.global_sync.can_operate() {
                 // drain commands
@@ -499,6 +1286,10 @@ where
                     this.on_network_order_event(event);
                     cx.waker().wake_by_ref();
                 }
+
+                if this.digest_interval.poll_tick(cx).is_ready() {
+                    this.on_digest_tick();
+                }
             }
         }
 
@@ -506,6 +1297,12 @@ where
     }
 }
 
+fn digest_interval() -> Interval {
+    let mut interval = interval_at(tokio::time::Instant::now() + DIGEST_INTERVAL, DIGEST_INTERVAL);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval
+}
+
 /// All events related to orders emitted by the network.
 #[derive(Debug)]
 #[allow(missing_docs)]
@@ -520,6 +1317,180 @@ pub enum NetworkTransactionEvent {
 #[derive(Debug)]
 struct StromPeer {
     /// Keeps track of transactions that we know the peer has seen.
-    orders:        LruCache<B256>,
-    cancellations: LruCache<B256>
+    orders:          LruCache<B256>,
+    cancellations:   LruCache<B256>,
+    /// Set when the session was resumed, until the peer's
+    /// [`StromMessage::SinceHashes`] has been handled.
+    disconnected_at: Option<Instant>
+}
+
+impl StromPeer {
+    fn new() -> Self {
+        Self {
+            orders:          LruCache::new(NonZeroUsize::new(PEER_ORDER_CACHE_LIMIT).unwrap()),
+            cancellations:   LruCache::new(NonZeroUsize::new(PEER_ORDER_CACHE_LIMIT).unwrap()),
+            disconnected_at: None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::{
+        block_sync::GlobalBlockSync, contract_payloads::angstrom::AngstromPoolConfigStore,
+        sol_bindings::rpc_orders::TopOfBlockOrder
+    };
+    use reth_metrics::common::mpsc::metered_unbounded_channel;
+    use testing_tools::mocks::validator::MockValidator;
+    use tokio::sync::{mpsc::unbounded_channel, oneshot};
+
+    use super::*;
+
+    fn order() -> AllOrders {
+        AllOrders::TOB(TopOfBlockOrder::default())
+    }
+
+    #[test]
+    fn peers_missing_the_same_orders_share_a_message() {
+        let (a, b) = (B256::repeat_byte(1), B256::repeat_byte(2));
+        let (fresh, also_fresh, seen_a, seen_all) =
+            (PeerId::random(), PeerId::random(), PeerId::random(), PeerId::random());
+
+        let mut peers = [fresh, also_fresh, seen_a, seen_all]
+            .into_iter()
+            .map(|peer_id| (peer_id, StromPeer::new()))
+            .collect::<HashMap<_, _>>();
+        peers.get_mut(&seen_a).unwrap().orders.insert(a);
+        peers.get_mut(&seen_all).unwrap().orders.insert(a);
+        peers.get_mut(&seen_all).unwrap().orders.insert(b);
+
+        let mut groups = peers_by_missing_orders(&mut peers, &[a, b], |_, _| true);
+        groups.values_mut().for_each(|peer_ids| peer_ids.sort());
+        let mut both = vec![fresh, also_fresh];
+        both.sort();
+        assert_eq!(groups, HashMap::from([(vec![0, 1], both), (vec![1], vec![seen_a])]));
+
+        // everything was marked as seen
+        assert!(peers_by_missing_orders(&mut peers, &[a, b], |_, _| true).is_empty());
+    }
+
+    #[test]
+    fn orders_are_only_sent_to_the_peers_they_are_gossiped_to() {
+        let (a, b) = (B256::repeat_byte(1), B256::repeat_byte(2));
+        let (validator, other) = (PeerId::random(), PeerId::random());
+        let mut peers = [validator, other]
+            .into_iter()
+            .map(|peer_id| (peer_id, StromPeer::new()))
+            .collect::<HashMap<_, _>>();
+
+        // `a` only goes to the validator
+        let groups = peers_by_missing_orders(&mut peers, &[a, b], |idx, peer_id| {
+            idx == 1 || *peer_id == validator
+        });
+        assert_eq!(groups, HashMap::from([(vec![0, 1], vec![validator]), (vec![1], vec![other])]));
+        assert!(!peers[&other].orders.contains(&a));
+    }
+
+    #[tokio::test]
+    async fn rejects_commands_when_the_manager_is_backed_up() {
+        let (manager_tx, mut manager_rx) = channel(1);
+        let (pool_manager_tx, _) = broadcast::channel(1);
+        let handle = PoolHandle { manager_tx, pool_manager_tx, view: Default::default() };
+
+        // the first command fills the channel
+        let _pending = handle.new_order(OrderOrigin::External, order());
+        let rejected = handle.new_order(OrderOrigin::External, order()).await;
+        assert!(matches!(
+            rejected,
+            OrderPoolNewOrderResult::Error(e) if e == OrderCommandError::Overloaded.to_string()
+        ));
+
+        assert!(matches!(
+            manager_rx.recv().await,
+            Some(OrderCommand::Validate(ValidationCommand::ValidateAndInsert(..)))
+        ));
+        drop(manager_rx);
+        let closed = handle.new_order(OrderOrigin::External, order()).await;
+        assert!(matches!(
+            closed,
+            OrderPoolNewOrderResult::Error(e) if e == OrderCommandError::Closed.to_string()
+        ));
+    }
+
+    #[tokio::test]
+    async fn control_commands_wait_for_a_backed_up_manager() {
+        let (manager_tx, mut manager_rx) = channel(1);
+        let (pool_manager_tx, _) = broadcast::channel(1);
+        let handle = PoolHandle { manager_tx, pool_manager_tx, view: Default::default() };
+
+        let _pending = handle.new_order(OrderOrigin::External, order());
+        let stop = tokio::spawn(handle.stop_intake());
+
+        assert!(matches!(
+            manager_rx.recv().await,
+            Some(OrderCommand::Validate(ValidationCommand::ValidateAndInsert(..)))
+        ));
+        // queued once there is room instead of being dropped
+        let Some(OrderCommand::StopIntake(ack)) = manager_rx.recv().await else {
+            panic!("stop intake was dropped")
+        };
+        ack.send(()).unwrap();
+        stop.await.unwrap();
+    }
+
+    fn manager() -> PoolManager<MockValidator, GlobalBlockSync> {
+        let (network_tx, _) = metered_unbounded_channel("strom handle");
+        let network = StromNetworkHandle::new(Arc::default(), network_tx);
+        let (_, order_events) = metered_unbounded_channel("order events");
+        let (_, command_rx) = channel(1);
+        let pools =
+            AngstromPoolsTracker::new(Address::ZERO, Arc::new(AngstromPoolConfigStore::default()));
+
+        PoolManagerBuilder::new(
+            MockValidator::default(),
+            None,
+            network,
+            UnboundedReceiverStream::new(unbounded_channel().1),
+            order_events,
+            GlobalBlockSync::new(0)
+        )
+        .into_manager(
+            Arc::new(OrderStorage::new(&PoolConfig::default())),
+            command_rx,
+            pools,
+            broadcast::channel(1).0
+        )
+    }
+
+    /// Sends a new order through the manager, returning whether it was turned
+    /// away because no orders are taken in.
+    fn is_refused(manager: &mut PoolManager<MockValidator, GlobalBlockSync>) -> bool {
+        let (tx, mut rx) = oneshot::channel();
+        manager.on_command(OrderCommand::Validate(ValidationCommand::ValidateAndInsert(
+            OrderOrigin::External,
+            order(),
+            None,
+            tx
+        )));
+
+        matches!(
+            rx.try_recv(),
+            Ok(OrderValidationResults::Invalid(_, ValidationError::NotAcceptingOrders))
+        )
+    }
+
+    #[tokio::test]
+    async fn resuming_intake_does_not_undo_a_shutdown() {
+        let mut manager = manager();
+        manager.on_config_update(ConfigUpdate::OrderIntake { paused: true });
+        assert!(is_refused(&mut manager));
+        manager.on_config_update(ConfigUpdate::OrderIntake { paused: false });
+        assert!(!is_refused(&mut manager));
+
+        let (tx, rx) = oneshot::channel();
+        manager.on_command(OrderCommand::StopIntake(tx));
+        rx.await.unwrap();
+        manager.on_config_update(ConfigUpdate::OrderIntake { paused: false });
+        assert!(is_refused(&mut manager));
+    }
 }
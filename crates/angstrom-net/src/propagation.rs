@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{keccak256, Address, B256};
+use angstrom_types::{primitive::PeerId, sol_bindings::grouped_orders::AllOrders};
+use serde::{Deserialize, Serialize};
+
+/// The kinds of orders gossip can be configured for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderCategory {
+    /// top of block orders
+    Searcher,
+    Standing,
+    Flash
+}
+
+impl OrderCategory {
+    pub fn of(order: &AllOrders) -> Self {
+        match order {
+            AllOrders::TOB(_) => Self::Searcher,
+            AllOrders::Standing(_) => Self::Standing,
+            AllOrders::Flash(_) => Self::Flash
+        }
+    }
+}
+
+/// Which of our peers an order is gossiped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "to")]
+pub enum GossipTarget {
+    #[default]
+    All,
+    /// only the peers in the validator set. Strom sessions are only opened to
+    /// validators, so for now this is the same as [`Self::All`]
+    Validators,
+    /// `percent` of the peers, at least one. Which ones depends on the order,
+    /// so different orders take different paths through the network
+    Fanout { percent: u8 },
+    /// nobody, the order stays with us
+    None
+}
+
+/// How the orders of a category are gossiped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GossipRule {
+    #[serde(flatten)]
+    pub target:  GossipTarget,
+    /// whether the orders we got from peers are gossiped on, or only those
+    /// submitted to us. A fanout relies on the peers forwarding the orders to
+    /// reach the whole network
+    #[serde(default = "default_forward")]
+    pub forward: bool
+}
+
+fn default_forward() -> bool {
+    true
+}
+
+impl Default for GossipRule {
+    fn default() -> Self {
+        Self { target: GossipTarget::All, forward: true }
+    }
+}
+
+/// Who the orders we take in are propagated to. By default every order goes
+/// to every peer.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PropagationPolicy {
+    #[serde(default)]
+    pub default:    GossipRule,
+    /// rules of the categories that aren't gossiped the default way
+    #[serde(default)]
+    pub categories: HashMap<OrderCategory, GossipRule>
+}
+
+impl PropagationPolicy {
+    pub fn with_rule(mut self, category: OrderCategory, rule: GossipRule) -> Self {
+        self.categories.insert(category, rule);
+        self
+    }
+
+    pub fn rule(&self, category: OrderCategory) -> GossipRule {
+        self.categories
+            .get(&category)
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// The peers `order` is gossiped to, out of `peers`. `from_peer` is whether
+    /// a peer sent it to us.
+    pub fn targets<'a>(
+        &self,
+        order: &AllOrders,
+        from_peer: bool,
+        peers: impl Iterator<Item = &'a PeerId>,
+        is_validator: impl Fn(&PeerId) -> bool
+    ) -> Vec<PeerId> {
+        let rule = self.rule(OrderCategory::of(order));
+        if from_peer && !rule.forward {
+            return vec![]
+        }
+
+        match rule.target {
+            GossipTarget::All => peers.copied().collect(),
+            GossipTarget::Validators => peers.filter(|peer| is_validator(peer)).copied().collect(),
+            GossipTarget::Fanout { percent } => fanout(order.order_hash(), peers, percent),
+            GossipTarget::None => vec![]
+        }
+    }
+}
+
+/// The address a validator with `peer_id` is known by in the validator set.
+pub fn validator_address(peer_id: &PeerId) -> Address {
+    Address::from_slice(&keccak256(peer_id)[12..])
+}
+
+/// Picks `percent` of the peers, ranked by how close they are to the order.
+fn fanout<'a>(
+    order_hash: B256,
+    peers: impl Iterator<Item = &'a PeerId>,
+    percent: u8
+) -> Vec<PeerId> {
+    let mut ranked = peers
+        .map(|peer| (keccak256([order_hash.as_slice(), peer.as_slice()].concat()), *peer))
+        .collect::<Vec<_>>();
+    if ranked.is_empty() {
+        return vec![]
+    }
+    ranked.sort_unstable();
+
+    let picked = (ranked.len() * percent.min(100) as usize)
+        .div_ceil(100)
+        .max(1);
+    ranked
+        .into_iter()
+        .take(picked)
+        .map(|(_, peer)| peer)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::sol_bindings::rpc_orders::TopOfBlockOrder;
+
+    use super::*;
+
+    fn searcher_order(nonce: u8) -> AllOrders {
+        AllOrders::TOB(TopOfBlockOrder { quantity_in: nonce as u128, ..Default::default() })
+    }
+
+    #[test]
+    fn searcher_orders_can_be_kept_from_being_gossiped_on() {
+        let peers = [PeerId::random(), PeerId::random()];
+        let policy = PropagationPolicy::default().with_rule(
+            OrderCategory::Searcher,
+            GossipRule { target: GossipTarget::Validators, forward: false }
+        );
+
+        let ours = policy.targets(&searcher_order(0), false, peers.iter(), |p| *p == peers[0]);
+        assert_eq!(ours, vec![peers[0]]);
+        assert!(policy
+            .targets(&searcher_order(0), true, peers.iter(), |_| true)
+            .is_empty());
+    }
+
+    #[test]
+    fn fanout_picks_the_same_peers_for_an_order() {
+        let peers = (0..10).map(|_| PeerId::random()).collect::<Vec<_>>();
+        let policy = PropagationPolicy {
+            default:    GossipRule { target: GossipTarget::Fanout { percent: 25 }, forward: true },
+            categories: HashMap::new()
+        };
+
+        let picked = policy.targets(&searcher_order(1), true, peers.iter(), |_| false);
+        assert_eq!(picked.len(), 3);
+        assert_eq!(picked, policy.targets(&searcher_order(1), true, peers.iter(), |_| false));
+        assert_eq!(
+            policy
+                .targets(&searcher_order(1), true, peers[..1].iter(), |_| false)
+                .len(),
+            1
+        );
+    }
+}
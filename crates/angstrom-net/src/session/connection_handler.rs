@@ -19,11 +19,10 @@ use tokio::{
     sync::mpsc,
     time::{Duration, Instant}
 };
-use tokio_stream::wrappers::ReceiverStream;
 
 use crate::{
     errors::StromStreamError,
-    session::handle::StromSessionHandle,
+    session::handle::{SessionCommands, StromSessionHandle},
     types::message::{StromMessage, StromProtocolMessage},
     StromSession, VerificationSidecar
 };
@@ -53,6 +52,7 @@ pub struct StromConnectionHandler {
     pub to_session_manager: MeteredPollSender<StromSessionMessage>,
     pub protocol_breach_request_timeout: Duration,
     pub session_command_buffer: usize,
+    pub priority_command_buffer: usize,
     pub socket_addr: SocketAddr,
     pub side_car: VerificationSidecar,
     pub validator_set: HashSet<Address>
@@ -88,18 +88,20 @@ impl ConnectionHandler for StromConnectionHandler {
         }
 
         let (tx, rx) = mpsc::channel(self.session_command_buffer);
+        let (priority_tx, priority_rx) = mpsc::channel(self.priority_command_buffer);
 
         let handle = StromSessionHandle {
             direction,
             remote_id: peer_id,
             established: Instant::now(),
-            commands_to_session: tx
+            commands_to_session: tx,
+            priority_commands_to_session: priority_tx
         };
 
         PossibleStromSession::Session(StromSession::new(
             conn,
             peer_id,
-            ReceiverStream::new(rx),
+            SessionCommands::new(priority_rx, rx),
             self.to_session_manager,
             self.protocol_breach_request_timeout,
             self.side_car,
@@ -115,6 +117,21 @@ pub enum StromSessionMessage {
     /// Session was established.
     Established { handle: StromSessionHandle },
 
+    /// Session was verified with a full status handshake.
+    Verified {
+        /// The remote node's public key
+        peer_id: PeerId,
+        /// The protocol version of the remote's status
+        version: u8
+    },
+
+    /// Session was verified with a resumption token instead of a full status
+    /// handshake.
+    Resumed {
+        /// The remote node's public key
+        peer_id: PeerId
+    },
+
     /// Session was gracefully disconnected.
     Disconnected {
         /// The remote node's public key
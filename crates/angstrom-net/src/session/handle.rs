@@ -1,8 +1,22 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll}
+};
+
+use alloy::rlp::Bytes;
 use angstrom_types::primitive::PeerId;
+use futures::{Stream, StreamExt};
 use reth_network::Direction;
-use tokio::{sync::mpsc, time::Instant};
+use tokio::{
+    sync::mpsc::{self, error::TrySendError},
+    time::Instant
+};
+use tokio_stream::wrappers::ReceiverStream;
 
-use crate::{session::DisconnectReason, types::message::StromMessage};
+use crate::{
+    session::DisconnectReason,
+    types::message::{MessagePriority, StromMessage}
+};
 /// Commands that can be sent to the spawned session.
 //TODO: Create a subvariant of messages only for bidirectional messages received during an active
 // session
@@ -14,7 +28,9 @@ pub enum SessionCommand {
         reason: Option<DisconnectReason>
     },
     /// Sends a message to the peer
-    Message(StromMessage)
+    Message(StromMessage),
+    /// Sends an already encoded message to the peer
+    Encoded(Bytes)
 }
 
 /// An established session with a remote peer.
@@ -22,14 +38,17 @@ pub enum SessionCommand {
 #[allow(dead_code)]
 pub struct StromSessionHandle {
     /// The direction of the session
-    pub(crate) direction:           Direction,
+    pub(crate) direction:                    Direction,
     /// The identifier of the remote peer
-    pub(crate) remote_id:           PeerId,
+    pub(crate) remote_id:                    PeerId,
     /// The timestamp when the session has been established.
-    pub(crate) established:         Instant,
+    pub(crate) established:                  Instant,
     /// Sender half of the command channel used send commands _to_ the spawned
     /// session
-    pub(crate) commands_to_session: mpsc::Sender<SessionCommand>
+    pub(crate) commands_to_session:          mpsc::Sender<SessionCommand>,
+    /// Sender half of the channel for consensus messages, which the session
+    /// drains before the command channel
+    pub(crate) priority_commands_to_session: mpsc::Sender<SessionCommand>
 }
 
 impl StromSessionHandle {
@@ -42,4 +61,78 @@ impl StromSessionHandle {
             .clone()
             .try_send(SessionCommand::Disconnect { reason });
     }
+
+    /// Queues a message for the session on the channel of its priority.
+    pub(crate) fn send(&self, priority: MessagePriority, command: SessionCommand) {
+        let commands = match priority {
+            MessagePriority::Consensus => &self.priority_commands_to_session,
+            MessagePriority::Bulk => &self.commands_to_session
+        };
+
+        if let Err(TrySendError::Full(_)) = commands.try_send(command) {
+            tracing::debug!(
+                peer_id=?self.remote_id,
+                ?priority,
+                "session queue full, dropped message"
+            );
+        }
+    }
+}
+
+/// The commands a session receives, consensus messages first. A command
+/// channel that is full of orders never delays a pre-proposal or proposal.
+#[derive(Debug)]
+pub struct SessionCommands {
+    priority: ReceiverStream<SessionCommand>,
+    bulk:     ReceiverStream<SessionCommand>
+}
+
+impl SessionCommands {
+    pub fn new(
+        priority: mpsc::Receiver<SessionCommand>,
+        bulk: mpsc::Receiver<SessionCommand>
+    ) -> Self {
+        Self { priority: ReceiverStream::new(priority), bulk: ReceiverStream::new(bulk) }
+    }
+}
+
+impl Stream for SessionCommands {
+    type Item = SessionCommand;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let priority = self.priority.poll_next_unpin(cx);
+        if let Poll::Ready(Some(command)) = priority {
+            return Poll::Ready(Some(command))
+        }
+
+        match self.bulk.poll_next_unpin(cx) {
+            // both handles are dropped together, the session ends once both are drained
+            Poll::Ready(None) if priority.is_pending() => Poll::Pending,
+            poll => poll
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn consensus_messages_skip_the_queue() {
+        let (bulk_tx, bulk_rx) = mpsc::channel(4);
+        let (priority_tx, priority_rx) = mpsc::channel(4);
+        let mut commands = SessionCommands::new(priority_rx, bulk_rx);
+
+        bulk_tx
+            .try_send(SessionCommand::Encoded(Bytes::from_static(b"orders")))
+            .unwrap();
+        priority_tx
+            .try_send(SessionCommand::Message(StromMessage::SinceHashes(vec![])))
+            .unwrap();
+        drop((bulk_tx, priority_tx));
+
+        assert!(matches!(commands.next().await, Some(SessionCommand::Message(_))));
+        assert!(matches!(commands.next().await, Some(SessionCommand::Encoded(_))));
+        assert!(commands.next().await.is_none());
+    }
 }
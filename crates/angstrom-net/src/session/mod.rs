@@ -6,6 +6,9 @@ pub use handle::*;
 pub mod protocol_handler;
 pub use protocol_handler::*;
 
+pub mod resume;
+pub use resume::*;
+
 pub mod strom;
 use futures::Stream;
 pub use strom::*;
@@ -28,7 +31,7 @@ use reth_eth_wire::DisconnectReason;
 use reth_network::Direction;
 use tracing::warn;
 
-use crate::{errors::StromStreamError, StromMessage, StromProtocolMessage};
+use crate::{errors::StromStreamError, EncodedStromMessage, StromMessage, StromProtocolMessage};
 
 #[derive(Debug)]
 pub struct StromSessionManager {
@@ -49,20 +52,29 @@ impl StromSessionManager {
     /// Sends a message to the peer's session
     pub fn send_message(&mut self, peer_id: &PeerId, msg: StromMessage) {
         if let Some(session) = self.active_sessions.get_mut(peer_id) {
-            let _ = session
-                .commands_to_session
-                .try_send(SessionCommand::Message(msg));
+            session.send(msg.message_id().priority(), SessionCommand::Message(msg));
         }
     }
 
+    /// Encodes the message once and sends the same buffer to every session.
     pub fn broadcast_message(&mut self, msg: StromMessage) {
-        self.active_sessions.values_mut().for_each(|cmd| {
-            let _ = cmd
-                .commands_to_session
-                .try_send(SessionCommand::Message(msg.clone()));
+        let msg = EncodedStromMessage::new(&msg);
+        let priority = msg.message_id.priority();
+        self.active_sessions.values_mut().for_each(|session| {
+            session.send(priority, SessionCommand::Encoded(msg.bytes.clone()));
         })
     }
 
+    /// Sends the same encoded message to each of the peers.
+    pub fn send_encoded_message(&mut self, peer_ids: &[PeerId], msg: &EncodedStromMessage) {
+        peer_ids
+            .iter()
+            .filter_map(|peer_id| self.active_sessions.get(peer_id))
+            .for_each(|session| {
+                session.send(msg.message_id.priority(), SessionCommand::Encoded(msg.bytes.clone()));
+            })
+    }
+
     // Removes the Session handle if it exists.
     fn remove_session(&mut self, id: &PeerId) -> Option<StromSessionHandle> {
         let session = self.active_sessions.remove(id)?;
@@ -108,6 +120,12 @@ impl StromSessionManager {
 
                     Some(event)
                 }
+                StromSessionMessage::Verified { peer_id, version } => {
+                    Some(SessionEvent::SessionVerified { peer_id, version })
+                }
+                StromSessionMessage::Resumed { peer_id } => {
+                    Some(SessionEvent::SessionResumed { peer_id })
+                }
                 StromSessionMessage::ClosedOnConnectionError { peer_id, error } => {
                     Some(SessionEvent::OutgoingConnectionError { peer_id, error })
                 }
@@ -148,6 +166,19 @@ pub enum SessionEvent {
         /// before timing out the connection
         timeout:   Arc<AtomicU64>
     },
+    /// The status handshake of a newly established session succeeded.
+    SessionVerified {
+        /// The remote node's public key
+        peer_id: PeerId,
+        /// The protocol version of the remote's status
+        version: u8
+    },
+    /// A previously established session was resumed with a token instead of a
+    /// full status handshake.
+    SessionResumed {
+        /// The remote node's public key
+        peer_id: PeerId
+    },
     /// The peer was already connected with another session.
     AlreadyConnected {
         /// The remote node's public key
@@ -10,6 +10,9 @@ use tokio::time::Duration;
 use crate::{StromConnectionHandler, StromSessionMessage, VerificationSidecar};
 
 const SESSION_COMMAND_BUFFER: usize = 100;
+/// Consensus messages are few per round, but a full round of them has to fit
+/// while the session is busy writing orders.
+const PRIORITY_COMMAND_BUFFER: usize = 32;
 /// The protocol handler that is used to announce the strom capability upon
 /// successfully establishing a hello handshake on an incoming tcp connection.
 #[derive(Debug)]
@@ -33,6 +36,7 @@ impl ProtocolHandler for StromProtocolHandler {
             side_car: self.sidecar.clone(),
             protocol_breach_request_timeout: Duration::from_secs(15),
             session_command_buffer: SESSION_COMMAND_BUFFER,
+            priority_command_buffer: PRIORITY_COMMAND_BUFFER,
             socket_addr,
             validator_set: self.validators.read().clone()
         })
@@ -49,6 +53,7 @@ impl ProtocolHandler for StromProtocolHandler {
             to_session_manager: self.to_session_manager.clone(),
             protocol_breach_request_timeout: Duration::from_secs(15),
             session_command_buffer: SESSION_COMMAND_BUFFER,
+            priority_command_buffer: PRIORITY_COMMAND_BUFFER,
             socket_addr,
             side_car: self.sidecar.clone(),
             validator_set: self.validators.read().clone()
@@ -0,0 +1,143 @@
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+use alloy::primitives::B256;
+use angstrom_types::primitive::PeerId;
+use parking_lot::RwLock;
+use tokio::time::Duration;
+
+/// How long after a disconnect a peer is allowed to resume its session instead
+/// of going through the full status handshake again.
+pub const SESSION_RESUMPTION_WINDOW: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy)]
+struct ResumptionEntry {
+    token:           B256,
+    /// set once the session that produced the token has been closed.
+    disconnected_at: Option<Instant>
+}
+
+/// Tokens of the sessions we have fully verified, shared across all the
+/// connection handlers so that a reconnecting peer can be recognised.
+#[derive(Debug, Clone)]
+pub struct ResumptionCache {
+    window:  Duration,
+    entries: Arc<RwLock<HashMap<PeerId, ResumptionEntry>>>
+}
+
+impl Default for ResumptionCache {
+    fn default() -> Self {
+        Self::new(SESSION_RESUMPTION_WINDOW)
+    }
+}
+
+impl ResumptionCache {
+    pub fn new(window: Duration) -> Self {
+        Self { window, entries: Arc::new(RwLock::new(HashMap::default())) }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Stores the token of a session that just completed the full handshake.
+    pub fn insert(&self, peer_id: PeerId, token: B256) {
+        self.entries
+            .write()
+            .insert(peer_id, ResumptionEntry { token, disconnected_at: None });
+    }
+
+    /// Starts the resumption window for the given peer.
+    pub fn on_disconnect(&self, peer_id: PeerId) {
+        if let Some(entry) = self.entries.write().get_mut(&peer_id) {
+            entry.disconnected_at.get_or_insert_with(Instant::now);
+        }
+    }
+
+    /// Returns the token we can present to the peer, if its last session was
+    /// closed within the resumption window.
+    pub fn token_for(&self, peer_id: &PeerId) -> Option<B256> {
+        self.entries
+            .read()
+            .get(peer_id)
+            .filter(|entry| self.within_window(entry))
+            .map(|entry| entry.token)
+    }
+
+    /// Checks the token presented by the peer. On success the peer is
+    /// considered connected again, on failure the entry is dropped so that the
+    /// next connection attempt falls back to a full handshake.
+    pub fn verify(&self, peer_id: &PeerId, token: &B256) -> bool {
+        let mut entries = self.entries.write();
+        let Some(entry) = entries.get_mut(peer_id) else { return false };
+
+        if entry.token == *token && self.within_window(entry) {
+            entry.disconnected_at = None;
+            return true
+        }
+
+        entries.remove(peer_id);
+        false
+    }
+
+    pub fn remove(&self, peer_id: &PeerId) {
+        self.entries.write().remove(peer_id);
+    }
+
+    fn within_window(&self, entry: &ResumptionEntry) -> bool {
+        entry
+            .disconnected_at
+            .is_some_and(|at| at.elapsed() <= self.window)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_disconnected_sessions_can_resume() {
+        let cache = ResumptionCache::default();
+        let peer = PeerId::random();
+        let token = B256::random();
+
+        cache.insert(peer, token);
+        assert_eq!(cache.token_for(&peer), None);
+        assert!(!cache.verify(&peer, &token));
+
+        cache.insert(peer, token);
+        cache.on_disconnect(peer);
+        assert_eq!(cache.token_for(&peer), Some(token));
+        assert!(cache.verify(&peer, &token));
+
+        // the session is live again
+        assert_eq!(cache.token_for(&peer), None);
+    }
+
+    #[test]
+    fn wrong_token_drops_entry() {
+        let cache = ResumptionCache::default();
+        let peer = PeerId::random();
+        let token = B256::random();
+
+        cache.insert(peer, token);
+        cache.on_disconnect(peer);
+
+        assert!(!cache.verify(&peer, &B256::random()));
+        assert_eq!(cache.token_for(&peer), None);
+        assert!(!cache.verify(&peer, &token));
+    }
+
+    #[test]
+    fn expired_window_cannot_resume() {
+        let cache = ResumptionCache::new(Duration::ZERO);
+        let peer = PeerId::random();
+        let token = B256::random();
+
+        cache.insert(peer, token);
+        cache.on_disconnect(peer);
+        std::thread::sleep(Duration::from_millis(1));
+
+        assert_eq!(cache.token_for(&peer), None);
+        assert!(!cache.verify(&peer, &token));
+    }
+}
@@ -16,16 +16,18 @@ use futures::{
 use reth_eth_wire::multiplex::ProtocolConnection;
 use reth_metrics::common::mpsc::MeteredPollSender;
 use tokio::time::Duration;
-use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::sync::PollSender;
 
-use super::handle::SessionCommand;
+use super::{
+    handle::{SessionCommand, SessionCommands},
+    ResumptionCache
+};
 use crate::{
     types::{
         message::StromProtocolMessage,
         status::{Status, StatusState}
     },
-    StatusBuilder, StromMessage, StromSessionHandle, StromSessionMessage
+    ResumptionToken, StatusBuilder, StromMessage, StromSessionHandle, StromSessionMessage
 };
 
 const STATUS_TIMESTAMP_TIMEOUT_MS: u128 = 1500;
@@ -36,7 +38,9 @@ pub struct VerificationSidecar {
     pub secret_key:   AngstromSigner,
     pub status:       StatusState,
     pub has_sent:     bool,
-    pub has_received: bool
+    pub has_received: bool,
+    /// tokens of previous sessions, shared between all connections
+    pub resumption:   ResumptionCache
 }
 
 impl VerificationSidecar {
@@ -65,8 +69,8 @@ pub struct StromSession {
     pub(crate) conn:               ProtocolConnection,
     /// Identifier of the node we're connected to.
     pub(crate) remote_peer_id:     PeerId,
-    /// Incoming commands from the manager
-    pub(crate) commands_rx:        ReceiverStream<SessionCommand>,
+    /// Incoming commands from the manager, consensus messages first
+    pub(crate) commands_rx:        SessionCommands,
     /// Sink to send messages to the [`SessionManager`](super::SessionManager).
     pub(crate) to_session_manager: MeteredPollSender<StromSessionMessage>,
 
@@ -82,14 +86,20 @@ pub struct StromSession {
     /// has sent the handle to the receiver
     pending_handle: Option<StromSessionHandle>,
     /// buffer for pending messages
-    outbound_buffer: VecDeque<StromSessionMessage>
+    outbound_buffer: VecDeque<StromSessionMessage>,
+    /// the status we sent during the handshake, used to derive the
+    /// resumption token for this session.
+    local_status: Option<Status>,
+    /// true if we tried to resume a previous session instead of sending our
+    /// status
+    resuming: bool
 }
 
 impl StromSession {
     pub fn new(
         conn: ProtocolConnection,
         peer_id: PeerId,
-        commands_rx: ReceiverStream<SessionCommand>,
+        commands_rx: SessionCommands,
         to_session_manager: MeteredPollSender<StromSessionMessage>,
         protocol_breach_request_timeout: Duration,
         verification_sidecar: VerificationSidecar,
@@ -104,7 +114,9 @@ impl StromSession {
             protocol_breach_request_timeout,
             terminate_message: None,
             pending_handle: Some(handle),
-            outbound_buffer: VecDeque::default()
+            outbound_buffer: VecDeque::default(),
+            local_status: None,
+            resuming: false
         }
     }
 
@@ -114,6 +126,9 @@ impl StromSession {
             "got disconnect message, disconnecting from peer {:?}",
             self.remote_peer_id
         );
+        self.verification_sidecar
+            .resumption
+            .on_disconnect(self.remote_peer_id);
         let msg = StromSessionMessage::Disconnected { peer_id: self.remote_peer_id };
 
         self.terminate_message = Some((self.to_session_manager.inner().clone(), msg));
@@ -176,6 +191,13 @@ impl StromSession {
                             msg.encode(&mut buf);
                             Poll::Ready(Some(buf))
                         }
+                        // the multiplexer rewrites the message id in place, so the
+                        // buffer is only copied while other sessions still share it
+                        SessionCommand::Encoded(bytes) => Poll::Ready(Some(
+                            bytes
+                                .try_into_mut()
+                                .unwrap_or_else(|shared| BytesMut::from(&shared[..]))
+                        ))
                     }
                 )
             })
@@ -212,10 +234,21 @@ impl StromSession {
 
     fn poll_verification(&mut self, cx: &mut Context<'_>) -> Poll<Option<BytesMut>> {
         if !self.verification_sidecar.has_sent {
-            let msg = StromMessage::Status(
-                self.verification_sidecar
-                    .make_status_message(self.remote_peer_id)
-            );
+            // if we were recently connected to this peer, skip the full handshake.
+            let msg = if let Some(token) = self
+                .verification_sidecar
+                .resumption
+                .token_for(&self.remote_peer_id)
+            {
+                self.resuming = true;
+                StromMessage::Resume(ResumptionToken::new(token))
+            } else {
+                let status = self
+                    .verification_sidecar
+                    .make_status_message(self.remote_peer_id);
+                self.local_status = Some(status.clone());
+                StromMessage::Status(status)
+            };
             // mark our status as sent.
             self.verification_sidecar.has_sent = true;
 
@@ -237,13 +270,24 @@ impl StromSession {
                 msg.map(|bytes| {
                     let msg = StromProtocolMessage::decode_message(&mut bytes.deref());
 
-                    msg.map_or(false, |msg| {
-                        // first message has to be status
-                        if let StromMessage::Status(status) = msg.message {
+                    msg.map_or(false, |msg| match msg.message {
+                        // first message has to be status, or a resumption if we are resuming
+                        StromMessage::Status(status) if !self.resuming => {
                             tracing::debug!(?status, peer=?self.remote_peer_id, "decoded status message");
 
                             self.verify_incoming_status(status)
-                        } else {
+                        }
+                        StromMessage::Resume(token) if self.resuming => {
+                            tracing::debug!(peer=?self.remote_peer_id, "decoded resume message");
+
+                            self.verify_incoming_resumption(token)
+                        }
+                        _ => {
+                            // the peer doesn't agree on resuming, next attempt will do the full
+                            // handshake.
+                            self.verification_sidecar
+                                .resumption
+                                .remove(&self.remote_peer_id);
                             false
                         }
                     })
@@ -272,19 +316,58 @@ impl StromSession {
         }
     }
 
-    fn verify_incoming_status(&self, status: Status) -> bool {
+    fn verify_incoming_status(&mut self, status: Status) -> bool {
         let current_time = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_millis();
 
+        let token = self
+            .local_status
+            .as_ref()
+            .map(|local| ResumptionToken::derive(local, &status));
+
         let status_time = status.state.timestamp + STATUS_TIMESTAMP_TIMEOUT_MS;
         let verification = status.verify();
         if verification.is_err() {
             return false
         }
 
-        current_time <= status_time && verification.unwrap() == self.remote_peer_id
+        let verified = current_time <= status_time && verification.unwrap() == self.remote_peer_id;
+        if verified {
+            if let Some(token) = token {
+                self.verification_sidecar
+                    .resumption
+                    .insert(self.remote_peer_id, token);
+            }
+            self.outbound_buffer
+                .push_back(StromSessionMessage::Verified {
+                    peer_id: self.remote_peer_id,
+                    version: status.state.version
+                });
+        }
+
+        verified
+    }
+
+    fn verify_incoming_resumption(&mut self, token: ResumptionToken) -> bool {
+        let current_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+
+        let verified = current_time <= token.timestamp + STATUS_TIMESTAMP_TIMEOUT_MS
+            && self
+                .verification_sidecar
+                .resumption
+                .verify(&self.remote_peer_id, &token.token);
+
+        if verified {
+            self.outbound_buffer
+                .push_back(StromSessionMessage::Resumed { peer_id: self.remote_peer_id });
+        }
+
+        verified
     }
 }
 
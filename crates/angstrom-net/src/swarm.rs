@@ -63,6 +63,12 @@ impl<DB: Unpin> Swarm<DB> {
             SessionEvent::SessionEstablished { peer_id, .. } => {
                 Some(SwarmEvent::SessionEstablished { peer_id })
             }
+            SessionEvent::SessionVerified { peer_id, version } => {
+                Some(SwarmEvent::SessionVerified { peer_id, version })
+            }
+            SessionEvent::SessionResumed { peer_id } => {
+                Some(SwarmEvent::SessionResumed { peer_id })
+            }
             _ => None
         }
     }
@@ -95,6 +101,8 @@ impl<DB: Unpin> Stream for Swarm<DB> {
 
 pub enum SwarmEvent {
     SessionEstablished { peer_id: PeerId },
+    SessionVerified { peer_id: PeerId, version: u8 },
+    SessionResumed { peer_id: PeerId },
     ValidMessage { peer_id: PeerId, msg: StromMessage },
     Disconnected { peer_id: PeerId }
 }
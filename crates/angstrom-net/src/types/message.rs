@@ -1,10 +1,16 @@
 #![allow(missing_docs)]
 use std::{fmt::Debug, sync::Arc};
 
-use alloy::rlp::{Buf, BufMut, Decodable, Encodable};
+use alloy::{
+    primitives::B256,
+    rlp::{Buf, BufMut, Bytes, BytesMut, Decodable, Encodable}
+};
 use angstrom_types::{
-    consensus::{PreProposal, PreProposalAggregation, Proposal},
-    orders::CancelOrderRequest,
+    consensus::{
+        PreProposal, PreProposalAggregation, Proposal, SubmissionApproval,
+        SubmissionApprovalRequest, ValidatorTelemetry
+    },
+    orders::{CancelAllOrdersRequest, CancelOrderRequest, OrderAmendment, OrderDigest},
     sol_bindings::grouped_orders::AllOrders
 };
 use reth_eth_wire::{protocol::Protocol, Capability};
@@ -14,26 +20,69 @@ use serde::{Deserialize, Serialize};
 use crate::errors::StromStreamError;
 /// Result alias for result of a request.
 pub type RequestResult<T> = Result<T, RequestError>;
-use crate::Status;
+use crate::{ResumptionToken, Status};
 
 /// [`MAX_MESSAGE_SIZE`] is the maximum cap on the size of a protocol message.
 // https://github.com/ethereum/go-ethereum/blob/30602163d5d8321fbc68afdcbbaf2362b2641bde/eth/protocols/eth/protocol.go#L50
 pub const MAX_MESSAGE_SIZE: usize = 10 * 1024 * 1024;
 
 const STROM_CAPABILITY: Capability = Capability::new_static("strom", 1);
-const STROM_PROTOCOL: Protocol = Protocol::new(STROM_CAPABILITY, 5);
+const STROM_PROTOCOL: Protocol = Protocol::new(STROM_CAPABILITY, 16);
 /// Represents message IDs for eth protocol messages.
 #[repr(u8)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum StromMessageID {
-    Status            = 0,
+    Status             = 0,
     /// Consensus
-    PrePropose        = 1,
-    PreProposeAgg     = 2,
-    Propose           = 3,
+    PrePropose         = 1,
+    PreProposeAgg      = 2,
+    Propose            = 3,
     /// Propagation messages that broadcast new orders to all peers
     PropagatePooledOrders = 4,
-    OrderCancellation = 5
+    OrderCancellation  = 5,
+    /// Session resumption
+    Resume             = 6,
+    SinceHashes        = 7,
+    /// Book synchronization on connect
+    OrderSyncRequest   = 8,
+    OrderAmendment     = 9,
+    /// Anti-entropy of the books
+    OrderDigest        = 10,
+    /// Validator performance, optional
+    ValidatorTelemetry = 11,
+    /// Co-signing of the leader's settlement transaction, when required
+    SubmissionApprovalRequest = 12,
+    SubmissionApproval = 13,
+    /// Orders a proposal has that we don't, answered with
+    /// [`StromMessage::PropagatePooledOrders`]
+    GetPooledOrders    = 14,
+    /// Cancels all the orders of an account
+    OrderCancelAll     = 15
+}
+
+impl StromMessageID {
+    /// Which queue of a session the message is sent from.
+    pub const fn priority(&self) -> MessagePriority {
+        match self {
+            Self::PrePropose
+            | Self::PreProposeAgg
+            | Self::Propose
+            | Self::SubmissionApprovalRequest
+            | Self::SubmissionApproval
+            | Self::GetPooledOrders => MessagePriority::Consensus,
+            _ => MessagePriority::Bulk
+        }
+    }
+}
+
+/// Consensus messages are sent to a peer ahead of everything else it has
+/// queued, so a large batch of orders never holds up a round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MessagePriority {
+    /// Orders, cancellations and session bookkeeping
+    Bulk,
+    /// Pre-proposals, their aggregations and proposals
+    Consensus
 }
 
 impl Encodable for StromMessageID {
@@ -53,9 +102,19 @@ impl Decodable for StromMessageID {
             0 => StromMessageID::Status,
             1 => StromMessageID::PrePropose,
             2 => StromMessageID::PreProposeAgg,
-            3 => StromMessageID::PrePropose,
+            3 => StromMessageID::Propose,
             4 => StromMessageID::PropagatePooledOrders,
             5 => StromMessageID::OrderCancellation,
+            6 => StromMessageID::Resume,
+            7 => StromMessageID::SinceHashes,
+            8 => StromMessageID::OrderSyncRequest,
+            9 => StromMessageID::OrderAmendment,
+            10 => StromMessageID::OrderDigest,
+            11 => StromMessageID::ValidatorTelemetry,
+            12 => StromMessageID::SubmissionApprovalRequest,
+            13 => StromMessageID::SubmissionApproval,
+            14 => StromMessageID::GetPooledOrders,
+            15 => StromMessageID::OrderCancelAll,
             _ => return Err(alloy::rlp::Error::Custom("Invalid message ID"))
         };
         buf.advance(1);
@@ -74,7 +133,8 @@ impl StromProtocolMessage {
     pub fn decode_message(buf: &mut &[u8]) -> Result<Self, StromStreamError> {
         let message_id: StromMessageID = Decodable::decode(buf)?;
         let data: Vec<u8> = Decodable::decode(buf)?;
-        let message: StromMessage = bincode::deserialize(&data).unwrap();
+        let message: StromMessage =
+            bincode::deserialize(&data).map_err(|_| StromStreamError::InvalidMessageError)?;
 
         Ok(StromProtocolMessage { message_id, message })
     }
@@ -82,12 +142,39 @@ impl StromProtocolMessage {
 
 impl Encodable for StromProtocolMessage {
     fn encode(&self, out: &mut dyn BufMut) {
-        Encodable::encode(&self.message_id, out);
-        let buf = bincode::serialize(&self.message).unwrap();
+        Self::encode_message(self.message_id, &self.message, out);
+    }
+}
+
+impl StromProtocolMessage {
+    /// Encodes the message the same way as a [`StromProtocolMessage`] holding
+    /// it, without taking ownership of it.
+    fn encode_message(message_id: StromMessageID, message: &StromMessage, out: &mut dyn BufMut) {
+        Encodable::encode(&message_id, out);
+        let buf = bincode::serialize(message).unwrap();
         Encodable::encode(&buf, out);
     }
 }
 
+/// A [`StromMessage`] encoded once into a shared buffer, so it can be sent to
+/// any number of peers without being cloned or serialized again.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncodedStromMessage {
+    pub message_id: StromMessageID,
+    /// the encoded [`StromProtocolMessage`]
+    pub bytes:      Bytes
+}
+
+impl EncodedStromMessage {
+    pub fn new(message: &StromMessage) -> Self {
+        let message_id = message.message_id();
+        let mut buf = BytesMut::new();
+        StromProtocolMessage::encode_message(message_id, message, &mut buf);
+
+        Self { message_id, bytes: buf.freeze() }
+    }
+}
+
 impl StromProtocolMessage {
     /// Returns the protocol for the `Strom` protocol.
     pub const fn protocol() -> Protocol {
@@ -121,7 +208,45 @@ pub enum StromMessage {
 
     /// Propagation messages that broadcast new orders to all peers
     PropagatePooledOrders(Vec<AllOrders>),
-    OrderCancellation(CancelOrderRequest)
+    OrderCancellation(CancelOrderRequest),
+
+    /// Sent instead of [`StromMessage::Status`] when reconnecting to a peer
+    /// within the resumption window
+    Resume(ResumptionToken),
+    /// Hashes of the orders seen since the peer disconnected, sent after a
+    /// session has been resumed
+    SinceHashes(Vec<B256>),
+
+    /// Hashes of all the orders in our book, sent once a new session has been
+    /// verified. The peer answers with the orders we are missing.
+    OrderSyncRequest(Vec<B256>),
+
+    /// A new amount for a pending standing order
+    OrderAmendment(OrderAmendment),
+
+    /// The orders in our book, sent periodically so peers can drop the orders
+    /// the rest of the network no longer has
+    OrderDigest(OrderDigest),
+
+    /// How we did in the last block. Optional, validators that don't send it
+    /// just don't show up in the telemetry of the others
+    ValidatorTelemetry(ValidatorTelemetry),
+
+    /// The round leader asking the approvers to co-sign its settlement
+    /// transaction, sent to each of them directly
+    SubmissionApprovalRequest(SubmissionApprovalRequest),
+    /// An approver's answer to the round leader
+    SubmissionApproval(SubmissionApproval),
+
+    /// Asks for the orders with the given hashes while verifying a proposal
+    /// that has them. The peer sends back the ones it has as
+    /// [`StromMessage::PropagatePooledOrders`]
+    GetPooledOrders(Vec<B256>),
+
+    /// Cancels the orders of an account, and keeps the ones it covers that
+    /// are still being gossiped from coming back. Forwarded to the other peers
+    /// once
+    OrderCancelAll(CancelAllOrdersRequest)
 }
 impl StromMessage {
     /// Returns the message's ID.
@@ -132,7 +257,17 @@ impl StromMessage {
             StromMessage::PreProposeAgg(_) => StromMessageID::PreProposeAgg,
             StromMessage::Propose(_) => StromMessageID::Propose,
             StromMessage::PropagatePooledOrders(_) => StromMessageID::PropagatePooledOrders,
-            StromMessage::OrderCancellation(_) => StromMessageID::OrderCancellation
+            StromMessage::OrderCancellation(_) => StromMessageID::OrderCancellation,
+            StromMessage::Resume(_) => StromMessageID::Resume,
+            StromMessage::SinceHashes(_) => StromMessageID::SinceHashes,
+            StromMessage::OrderSyncRequest(_) => StromMessageID::OrderSyncRequest,
+            StromMessage::OrderAmendment(_) => StromMessageID::OrderAmendment,
+            StromMessage::OrderDigest(_) => StromMessageID::OrderDigest,
+            StromMessage::ValidatorTelemetry(_) => StromMessageID::ValidatorTelemetry,
+            StromMessage::SubmissionApprovalRequest(_) => StromMessageID::SubmissionApprovalRequest,
+            StromMessage::SubmissionApproval(_) => StromMessageID::SubmissionApproval,
+            StromMessage::GetPooledOrders(_) => StromMessageID::GetPooledOrders,
+            StromMessage::OrderCancelAll(_) => StromMessageID::OrderCancelAll
         }
     }
 }
@@ -10,3 +10,6 @@ pub mod broadcast;
 
 pub mod status;
 pub use status::*;
+
+pub mod resume;
+pub use resume::*;
@@ -0,0 +1,83 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy::primitives::{keccak256, B256};
+use serde::{Deserialize, Serialize};
+
+use crate::Status;
+
+/// Sent in place of a [`Status`] message by a peer that reconnects within the
+/// resumption window of a previous session. The token is derived from the
+/// status messages exchanged during the full handshake of that session, so
+/// only the two peers that took part in it are able to produce it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ResumptionToken {
+    /// token agreed upon in the original handshake
+    pub token:     B256,
+    /// The current timestamp. Used to make sure that the resumption message
+    /// will expire
+    pub timestamp: u128
+}
+
+impl ResumptionToken {
+    pub fn new(token: B256) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+
+        Self { token, timestamp }
+    }
+
+    /// Derives the session token from the two status messages exchanged in a
+    /// full handshake. The result does not depend on which side of the
+    /// connection computes it.
+    pub fn derive(local: &Status, remote: &Status) -> B256 {
+        let local = local.signature.as_bytes();
+        let remote = remote.signature.as_bytes();
+        let (first, second) = if local <= remote { (local, remote) } else { (remote, local) };
+
+        let mut buf = Vec::with_capacity(first.len() + second.len());
+        buf.extend_from_slice(&first);
+        buf.extend_from_slice(&second);
+
+        keccak256(buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::primitive::AngstromSigner;
+
+    use super::*;
+    use crate::{StatusBuilder, StatusState};
+
+    fn status(signer: &AngstromSigner, peer: &AngstromSigner) -> Status {
+        StatusBuilder::from(StatusState::new(peer.id())).build(signer)
+    }
+
+    #[test]
+    fn derived_token_is_symmetric() {
+        let a = AngstromSigner::random();
+        let b = AngstromSigner::random();
+
+        let a_status = status(&a, &b);
+        let b_status = status(&b, &a);
+
+        assert_eq!(
+            ResumptionToken::derive(&a_status, &b_status),
+            ResumptionToken::derive(&b_status, &a_status)
+        );
+    }
+
+    #[test]
+    fn derived_token_is_unique_per_handshake() {
+        let a = AngstromSigner::random();
+        let b = AngstromSigner::random();
+        let c = AngstromSigner::random();
+
+        assert_ne!(
+            ResumptionToken::derive(&status(&a, &b), &status(&b, &a)),
+            ResumptionToken::derive(&status(&a, &c), &status(&c, &a))
+        );
+    }
+}
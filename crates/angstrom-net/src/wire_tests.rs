@@ -0,0 +1,321 @@
+//! Golden encode/decode vectors for the strom wire protocol.
+//!
+//! Every [`StromMessage`] variant has a deterministic reference message. The
+//! encoded form of those messages is stored in
+//! `wire-vectors/strom-v<version>.json`, which other client implementations can
+//! use to check that they agree with us on the wire format. The tests in this
+//! module make sure a refactor of the message types doesn't silently change
+//! it.
+//!
+//! The vectors are checked in and the tests fail without them. Set
+//! `UPDATE_WIRE_VECTORS=1` when running the tests to regenerate them after an
+//! intended change of the format.
+
+use std::{collections::BTreeSet, path::PathBuf};
+
+use alloy::{
+    primitives::{hex, Address, PrimitiveSignature, B256, U256},
+    rlp::{BytesMut, Encodable},
+    signers::{local::PrivateKeySigner, SignerSync}
+};
+use angstrom_types::{
+    consensus::{
+        BookCommitment, PreProposal, PreProposalAggregation, Proposal, SubmissionApproval,
+        SubmissionApprovalRequest, ValidatorTelemetry
+    },
+    orders::{
+        CancelAllOrdersRequest, CancelOrderRequest, DigestEntry, OrderAmendment, OrderDigest,
+        PoolSolution
+    },
+    primitive::AngstromSigner,
+    sol_bindings::grouped_orders::{AllOrders, FlashVariants, StandingVariants}
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ResumptionToken, Status, StatusState, StromMessage, StromMessageID, StromProtocolMessage,
+    StromVersion
+};
+
+const REFERENCE_BLOCK: u64 = 1;
+const REFERENCE_TIMESTAMP: u128 = 1_700_000_000_000;
+
+/// A single encoded reference message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireVector {
+    /// name of the [`StromMessage`] variant
+    pub name:       String,
+    pub message_id: u8,
+    /// hex encoded [`StromProtocolMessage`]
+    pub encoded:    String
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WireVectorError {
+    #[error("no reference message for vector {0}")]
+    UnknownVector(String),
+    #[error("vector {0} is not valid hex")]
+    InvalidHex(String),
+    #[error("vector {0} failed to decode")]
+    Decode(String),
+    #[error("vector {0} decoded to a different message")]
+    MessageMismatch(String),
+    #[error("vector {0} has a different encoding than the reference message")]
+    EncodingMismatch(String),
+    #[error("reference message {0} has no vector")]
+    MissingVector(String)
+}
+
+/// Path of the vectors file for the given protocol version.
+pub fn vectors_path(version: StromVersion) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("wire-vectors")
+        .join(format!("strom-v{}.json", u8::from(version)))
+}
+
+/// The key all reference messages are signed with.
+pub fn reference_signer() -> AngstromSigner {
+    AngstromSigner::new(PrivateKeySigner::from_bytes(&B256::repeat_byte(0x11)).unwrap())
+}
+
+/// One deterministic message for every [`StromMessage`] variant.
+pub fn reference_messages() -> Vec<(&'static str, StromMessage)> {
+    let signer = reference_signer();
+
+    let state = StatusState {
+        version:   StromVersion::LATEST.into(),
+        chain:     1,
+        peer:      signer.id(),
+        timestamp: REFERENCE_TIMESTAMP
+    };
+    let status = Status { state, signature: signer.sign_hash_sync(&state.to_message()).unwrap() };
+
+    let pre_proposal =
+        PreProposal::generate_pre_proposal(REFERENCE_BLOCK, &signer, vec![], vec![], vec![]);
+    let aggregation = PreProposalAggregation::new(
+        REFERENCE_BLOCK,
+        &signer,
+        vec![pre_proposal.clone()],
+        &BTreeSet::from([signer.id()])
+    );
+    let proposal = Proposal::generate_proposal(
+        REFERENCE_BLOCK,
+        &signer,
+        vec![aggregation.clone()],
+        vec![PoolSolution::default()],
+        BookCommitment::default()
+    );
+
+    let orders = vec![
+        AllOrders::Standing(StandingVariants::Partial(Default::default())),
+        AllOrders::Standing(StandingVariants::Exact(Default::default())),
+        AllOrders::Flash(FlashVariants::Partial(Default::default())),
+        AllOrders::Flash(FlashVariants::Exact(Default::default())),
+        AllOrders::TOB(Default::default()),
+    ];
+
+    let cancellation = CancelOrderRequest {
+        signature:    PrimitiveSignature::new(U256::from(1), U256::from(2), false),
+        user_address: Address::repeat_byte(0x22),
+        order_id:     B256::repeat_byte(0x33)
+    };
+
+    let hashes = vec![B256::repeat_byte(0x44), B256::repeat_byte(0x55)];
+
+    vec![
+        ("Status", StromMessage::Status(status)),
+        ("PrePropose", StromMessage::PrePropose(pre_proposal)),
+        ("PreProposeAgg", StromMessage::PreProposeAgg(aggregation)),
+        ("Propose", StromMessage::Propose(proposal)),
+        ("PropagatePooledOrders", StromMessage::PropagatePooledOrders(orders)),
+        ("OrderCancellation", StromMessage::OrderCancellation(cancellation)),
+        (
+            "Resume",
+            StromMessage::Resume(ResumptionToken {
+                token:     B256::repeat_byte(0x66),
+                timestamp: REFERENCE_TIMESTAMP
+            })
+        ),
+        ("SinceHashes", StromMessage::SinceHashes(hashes.clone())),
+        ("OrderSyncRequest", StromMessage::OrderSyncRequest(hashes)),
+        (
+            "OrderAmendment",
+            StromMessage::OrderAmendment(OrderAmendment {
+                order_id: B256::repeat_byte(0x77),
+                order:    StandingVariants::Exact(Default::default())
+            })
+        ),
+        (
+            "OrderDigest",
+            StromMessage::OrderDigest(OrderDigest {
+                block_number: REFERENCE_BLOCK,
+                orders:       vec![DigestEntry {
+                    order_hash: B256::repeat_byte(0x88),
+                    expires_at: 1_700_000_000
+                }]
+            })
+        ),
+        (
+            "ValidatorTelemetry",
+            StromMessage::ValidatorTelemetry(ValidatorTelemetry {
+                block_height:          REFERENCE_BLOCK,
+                solve_time_ms:         Some(250),
+                book_size:             42,
+                validation_latency_ms: None
+            })
+        ),
+        (
+            "SubmissionApprovalRequest",
+            StromMessage::SubmissionApprovalRequest(SubmissionApprovalRequest {
+                block_height: REFERENCE_BLOCK,
+                raw_tx:       vec![0x02, 0x99].into()
+            })
+        ),
+        (
+            "SubmissionApproval",
+            StromMessage::SubmissionApproval(SubmissionApproval::new(
+                REFERENCE_BLOCK,
+                B256::repeat_byte(0x99),
+                &signer
+            ))
+        ),
+        ("GetPooledOrders", StromMessage::GetPooledOrders(vec![B256::repeat_byte(0xaa)])),
+        (
+            "OrderCancelAll",
+            StromMessage::OrderCancelAll(CancelAllOrdersRequest {
+                user_address: Address::repeat_byte(0x22),
+                pool_id:      Some(B256::repeat_byte(0xbb)),
+                nonce:        7,
+                signature:    PrimitiveSignature::new(U256::from(1), U256::from(2), false)
+            })
+        ),
+    ]
+}
+
+/// Encodes a message the same way a session puts it on the wire.
+pub fn encode_message(message: StromMessage) -> Vec<u8> {
+    let msg = StromProtocolMessage { message_id: message.message_id(), message };
+    let mut buf = BytesMut::new();
+    msg.encode(&mut buf);
+
+    buf.to_vec()
+}
+
+/// Builds the vectors for all the reference messages.
+pub fn generate_vectors() -> Vec<WireVector> {
+    reference_messages()
+        .into_iter()
+        .map(|(name, message)| WireVector {
+            name:       name.to_string(),
+            message_id: message.message_id() as u8,
+            encoded:    hex::encode_prefixed(encode_message(message))
+        })
+        .collect()
+}
+
+/// Checks a set of vectors against the reference messages. Every vector has
+/// to decode to its reference message and every reference message has to
+/// encode to its vector.
+pub fn check_vectors(vectors: &[WireVector]) -> Result<(), Vec<WireVectorError>> {
+    let references = reference_messages();
+    let mut errors = references
+        .iter()
+        .filter(|(name, _)| !vectors.iter().any(|v| v.name == *name))
+        .map(|(name, _)| WireVectorError::MissingVector(name.to_string()))
+        .collect::<Vec<_>>();
+
+    for vector in vectors {
+        let name = vector.name.clone();
+        let Some((_, reference)) = references.iter().find(|(n, _)| *n == name) else {
+            errors.push(WireVectorError::UnknownVector(name));
+            continue
+        };
+        let Ok(bytes) = hex::decode(&vector.encoded) else {
+            errors.push(WireVectorError::InvalidHex(name));
+            continue
+        };
+
+        match StromProtocolMessage::decode_message(&mut bytes.as_slice()) {
+            Ok(decoded)
+                if decoded.message_id as u8 == vector.message_id
+                    && decoded.message == *reference => {}
+            Ok(_) => errors.push(WireVectorError::MessageMismatch(name.clone())),
+            Err(_) => errors.push(WireVectorError::Decode(name.clone()))
+        }
+
+        if encode_message(reference.clone()) != bytes {
+            errors.push(WireVectorError::EncodingMismatch(name));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::rlp::Decodable;
+
+    use super::*;
+    use crate::EncodedStromMessage;
+
+    #[test]
+    fn reference_messages_cover_all_ids() {
+        let ids = reference_messages()
+            .into_iter()
+            .map(|(_, message)| message.message_id())
+            .collect::<Vec<_>>();
+
+        for id in 0..=u8::MAX {
+            if let Ok(decoded) = StromMessageID::decode(&mut [id].as_slice()) {
+                assert_eq!(decoded as u8, id, "message id {id} decodes to {decoded:?}");
+                assert!(ids.contains(&decoded), "no reference message for {decoded:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn reference_messages_round_trip() {
+        for (name, message) in reference_messages() {
+            let encoded = encode_message(message.clone());
+            let decoded = StromProtocolMessage::decode_message(&mut encoded.as_slice())
+                .unwrap_or_else(|_| panic!("{name} failed to decode"));
+
+            assert_eq!(decoded.message_id, message.message_id(), "{name}");
+            assert_eq!(decoded.message, message, "{name}");
+        }
+    }
+
+    #[test]
+    fn encoded_messages_match_session_encoding() {
+        for (name, message) in reference_messages() {
+            let encoded = EncodedStromMessage::new(&message);
+
+            assert_eq!(encoded.message_id, message.message_id(), "{name}");
+            assert_eq!(encoded.bytes.to_vec(), encode_message(message), "{name}");
+        }
+    }
+
+    #[test]
+    fn golden_vectors() {
+        let path = vectors_path(StromVersion::LATEST);
+
+        if std::env::var("UPDATE_WIRE_VECTORS").is_ok() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, serde_json::to_string_pretty(&generate_vectors()).unwrap())
+                .unwrap();
+        }
+
+        let vectors = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+            panic!("no wire vectors at {path:?} ({e}), generate them with UPDATE_WIRE_VECTORS=1")
+        });
+        let vectors: Vec<WireVector> = serde_json::from_str(&vectors).unwrap();
+
+        if let Err(errors) = check_vectors(&vectors) {
+            panic!("wire format changed, rerun with UPDATE_WIRE_VECTORS=1 if intended: {errors:#?}")
+        }
+    }
+}
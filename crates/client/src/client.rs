@@ -0,0 +1,243 @@
+use std::{collections::HashSet, future::Future, sync::Arc, time::Instant};
+
+use alloy::primitives::{Address, B256};
+use angstrom_rpc::{
+    api::{GasEstimateResponse, OrderApiClient, OrderSimulation, QuotingApiClient},
+    types::{
+        BookSubscriptionResult, GasEstimateFilter, GasQuote, OrderSubscriptionFilter,
+        OrderSubscriptionKind, OrderSubscriptionResult
+    }
+};
+use angstrom_types::{
+    orders::{OrderAmendment, OrderLocation, OrderStatus, SourceTag},
+    primitive::{OrderPoolNewOrderResult, PoolId},
+    sol_bindings::grouped_orders::AllOrders
+};
+use jsonrpsee::{
+    core::client::{ClientT, Subscription, SubscriptionClientT},
+    http_client::{HttpClient, HttpClientBuilder},
+    ws_client::{WsClient, WsClientBuilder}
+};
+
+use crate::{ClientError, ClientMiddleware, OrderSigner, RetryPolicy};
+
+/// Client for the rpc of an Angstrom node, over any jsonrpsee transport.
+/// Subscriptions need one that supports them, like [`WsClient`].
+#[derive(Clone)]
+pub struct AngstromClient<C> {
+    client:     C,
+    retry:      RetryPolicy,
+    middleware: Vec<Arc<dyn ClientMiddleware>>,
+    /// the dapp or frontend the orders sent are attributed to
+    source_tag: Option<SourceTag>
+}
+
+impl AngstromClient<HttpClient> {
+    pub fn http(url: impl AsRef<str>) -> Result<Self, ClientError> {
+        Ok(Self::new(HttpClientBuilder::default().build(url)?))
+    }
+}
+
+impl AngstromClient<WsClient> {
+    pub async fn ws(url: impl AsRef<str>) -> Result<Self, ClientError> {
+        Ok(Self::new(WsClientBuilder::default().build(url).await?))
+    }
+}
+
+impl<C> AngstromClient<C> {
+    pub fn new(client: C) -> Self {
+        Self { client, retry: RetryPolicy::default(), middleware: vec![], source_tag: None }
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Attributes the orders sent with the client to `source_tag`.
+    pub fn with_source_tag(mut self, source_tag: SourceTag) -> Self {
+        self.source_tag = Some(source_tag);
+        self
+    }
+
+    /// Middleware is called in the order it was added.
+    pub fn with_middleware(mut self, middleware: impl ClientMiddleware) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    pub fn inner(&self) -> &C {
+        &self.client
+    }
+}
+
+impl<C: ClientT + Send + Sync> AngstromClient<C> {
+    /// Runs `call`, again as long as it fails in transit and the retry policy
+    /// allows it.
+    async fn request<T, F, Fut>(&self, method: &'static str, call: F) -> Result<T, ClientError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, jsonrpsee::core::ClientError>>
+    {
+        self.middleware.iter().for_each(|m| m.on_request(method));
+        let start = Instant::now();
+
+        let mut attempt = 0;
+        let result = loop {
+            match call().await.map_err(ClientError::from) {
+                Err(e) if e.is_transient() && attempt < self.retry.max_retries => {
+                    attempt += 1;
+                    self.middleware
+                        .iter()
+                        .for_each(|m| m.on_retry(method, attempt, &e));
+                    tokio::time::sleep(self.retry.backoff(attempt - 1)).await;
+                }
+                result => break result
+            }
+        };
+
+        let elapsed = start.elapsed();
+        self.middleware
+            .iter()
+            .for_each(|m| m.on_response(method, elapsed, result.as_ref().err()));
+
+        result
+    }
+
+    pub async fn send_order(
+        &self,
+        order: AllOrders
+    ) -> Result<OrderPoolNewOrderResult, ClientError> {
+        self.request("angstrom_sendOrder", || {
+            self.client
+                .send_order(order.clone(), self.source_tag.clone(), None)
+        })
+        .await
+    }
+
+    pub async fn send_orders(
+        &self,
+        orders: Vec<AllOrders>
+    ) -> Result<Vec<OrderPoolNewOrderResult>, ClientError> {
+        self.request("angstrom_sendOrders", || {
+            self.client
+                .send_orders(orders.clone(), self.source_tag.clone())
+        })
+        .await
+    }
+
+    /// Signs `order` with `signer` and submits it.
+    pub async fn sign_and_send(
+        &self,
+        signer: &OrderSigner,
+        mut order: AllOrders
+    ) -> Result<OrderPoolNewOrderResult, ClientError> {
+        signer.sign_order(&mut order)?;
+        self.send_order(order).await
+    }
+
+    /// Cancels the order `order_hash` of `signer`. False if there was no such
+    /// pending order.
+    pub async fn cancel_order(
+        &self,
+        signer: &OrderSigner,
+        order_hash: B256
+    ) -> Result<bool, ClientError> {
+        let request = signer.cancel(order_hash)?;
+        self.request("angstrom_cancelOrder", || self.client.cancel_order(request.clone()))
+            .await
+    }
+
+    /// Cancels the pending orders of `signer`, in `pool_id` or in all pools,
+    /// returning the hashes of the cancelled ones. Standing orders signed
+    /// with the next valid nonce or later aren't affected.
+    pub async fn cancel_all_orders(
+        &self,
+        signer: &OrderSigner,
+        pool_id: Option<PoolId>
+    ) -> Result<Vec<B256>, ClientError> {
+        let nonce = self.next_nonce(signer.address()).await?;
+        let request = signer.cancel_all(pool_id, nonce)?;
+        self.request("angstrom_cancelAllOrders", || self.client.cancel_all_orders(request.clone()))
+            .await
+    }
+
+    pub async fn amend_order(
+        &self,
+        amendment: OrderAmendment
+    ) -> Result<OrderPoolNewOrderResult, ClientError> {
+        self.request("angstrom_amendOrder", || self.client.amend_order(amendment.clone()))
+            .await
+    }
+
+    pub async fn pending_orders(&self, from: Address) -> Result<Vec<AllOrders>, ClientError> {
+        self.request("angstrom_pendingOrder", || self.client.pending_order(from))
+            .await
+    }
+
+    pub async fn order_status(&self, order_hash: B256) -> Result<Option<OrderStatus>, ClientError> {
+        self.request("angstrom_orderStatus", || self.client.order_status(order_hash))
+            .await
+    }
+
+    /// The nonce for the next standing order of `address`.
+    pub async fn next_nonce(&self, address: Address) -> Result<u64, ClientError> {
+        self.request("angstrom_getNextValidNonce", || self.client.get_next_valid_nonce(address))
+            .await
+    }
+
+    pub async fn estimate_gas(&self, order: AllOrders) -> Result<GasEstimateResponse, ClientError> {
+        self.request("angstrom_estimateGas", || self.client.estimate_gas(order.clone()))
+            .await
+    }
+
+    /// What submitting `order` would do, without submitting it.
+    pub async fn simulate_order(&self, order: AllOrders) -> Result<OrderSimulation, ClientError> {
+        self.request("angstrom_simulateOrder", || self.client.simulate_order(order.clone()))
+            .await
+    }
+
+    /// The pending orders of one side of the book of `pool_id`.
+    pub async fn book(
+        &self,
+        pool_id: PoolId,
+        location: OrderLocation
+    ) -> Result<Vec<AllOrders>, ClientError> {
+        self.request("angstrom_ordersByPair", || self.client.orders_by_pool_id(pool_id, location))
+            .await
+    }
+}
+
+impl<C: SubscriptionClientT + Send + Sync> AngstromClient<C> {
+    pub async fn subscribe_orders(
+        &self,
+        kinds: HashSet<OrderSubscriptionKind>,
+        filters: HashSet<OrderSubscriptionFilter>
+    ) -> Result<Subscription<OrderSubscriptionResult>, ClientError> {
+        self.request("angstrom_subscribeOrders", || {
+            self.client.subscribe_orders(kinds.clone(), filters.clone())
+        })
+        .await
+    }
+
+    /// A snapshot of the book of `pool_id`, then the changes to it.
+    pub async fn subscribe_book(
+        &self,
+        pool_id: PoolId
+    ) -> Result<Subscription<BookSubscriptionResult>, ClientError> {
+        self.request("angstrom_subscribeBook", || self.client.subscribe_book(pool_id))
+            .await
+    }
+
+    /// Gas quotes of the pools matching `filters`, or of every pool for no
+    /// filter.
+    pub async fn subscribe_gas_quotes(
+        &self,
+        filters: HashSet<GasEstimateFilter>
+    ) -> Result<Subscription<GasQuote>, ClientError> {
+        self.request("quoting_subscribe_gas_estimates", || {
+            self.client.subscribe_gas_estimates(filters.clone())
+        })
+        .await
+    }
+}
@@ -0,0 +1,17 @@
+#[derive(Debug, thiserror::Error)]
+pub enum ClientError {
+    #[error(transparent)]
+    Rpc(#[from] jsonrpsee::core::ClientError),
+    #[error("failed to sign - {0}")]
+    Signing(#[from] alloy::signers::Error)
+}
+
+impl ClientError {
+    /// Whether the request never got an answer, sending it again may work.
+    /// Errors the node answered with are final.
+    pub fn is_transient(&self) -> bool {
+        use jsonrpsee::core::ClientError as Rpc;
+
+        matches!(self, Self::Rpc(Rpc::Transport(_) | Rpc::RestartNeeded(_) | Rpc::RequestTimeout))
+    }
+}
@@ -0,0 +1,18 @@
+//! Typed async client for the rpc of an Angstrom node.
+//!
+//! [`AngstromClient`] submits, cancels and amends orders, follows the book and
+//! the gas quotes of pools and reads the state of orders, over http or a
+//! websocket. Orders, cancellations and amendments are signed locally with an
+//! [`OrderSigner`]. Requests that fail in transit are retried as set by the
+//! [`RetryPolicy`], and every request goes through the [`ClientMiddleware`]s
+//! of the client.
+
+mod client;
+mod error;
+mod middleware;
+mod signing;
+
+pub use client::*;
+pub use error::*;
+pub use middleware::*;
+pub use signing::*;
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use crate::ClientError;
+
+/// How requests that fail in transit are retried. Requests the node answered,
+/// even with an error, never are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries:     u32,
+    /// the wait before the first retry, doubled for every one after it
+    pub initial_backoff: Duration,
+    pub max_backoff:     Duration
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries:     3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff:     Duration::from_secs(2)
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self { max_retries: 0, ..Default::default() }
+    }
+
+    /// How long to wait before the retry after `attempt` failed ones.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+/// Hooks into every request an [`AngstromClient`](crate::AngstromClient)
+/// makes, for logging, metrics or rate limiting. `method` is the name of the
+/// rpc method.
+pub trait ClientMiddleware: Send + Sync + 'static {
+    fn on_request(&self, _method: &'static str) {}
+
+    /// `attempt` failed with `error` and is retried.
+    fn on_retry(&self, _method: &'static str, _attempt: u32, _error: &ClientError) {}
+
+    /// The request is done, retries included.
+    fn on_response(&self, _method: &'static str, _elapsed: Duration, _error: Option<&ClientError>) {
+    }
+}
+
+/// Logs failed requests and slow ones.
+#[derive(Debug, Clone, Copy)]
+pub struct TracingMiddleware {
+    pub slow_request: Duration
+}
+
+impl Default for TracingMiddleware {
+    fn default() -> Self {
+        Self { slow_request: Duration::from_secs(1) }
+    }
+}
+
+impl ClientMiddleware for TracingMiddleware {
+    fn on_retry(&self, method: &'static str, attempt: u32, error: &ClientError) {
+        tracing::debug!(method, attempt, %error, "retrying angstrom request");
+    }
+
+    fn on_response(&self, method: &'static str, elapsed: Duration, error: Option<&ClientError>) {
+        if let Some(error) = error {
+            tracing::warn!(method, ?elapsed, %error, "angstrom request failed");
+        } else if elapsed > self.slow_request {
+            tracing::info!(method, ?elapsed, "slow angstrom request");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backs_off_exponentially_up_to_the_max() {
+        let policy = RetryPolicy {
+            max_retries:     10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff:     Duration::from_secs(1)
+        };
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(1), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(800));
+        assert_eq!(policy.backoff(4), Duration::from_secs(1));
+        assert_eq!(policy.backoff(40), Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,162 @@
+use alloy::{
+    primitives::{Address, B256},
+    signers::{local::PrivateKeySigner, SignerSync},
+    sol_types::Eip712Domain
+};
+use angstrom_types::{
+    orders::{CancelAllOrdersRequest, CancelOrderRequest, OrderAmendment},
+    primitive::{PoolId, ANGSTROM_DOMAIN},
+    sol_bindings::{
+        grouped_orders::{AllOrders, FlashVariants, StandingVariants},
+        rpc_orders::{
+            ExactFlashOrder, ExactStandingOrder, OmitOrderMeta, OrderMeta, PartialFlashOrder,
+            PartialStandingOrder, TopOfBlockOrder
+        },
+        RawPoolOrder
+    }
+};
+use pade::PadeEncode;
+
+use crate::ClientError;
+
+/// An order that is signed over its EIP-712 hash without its meta, which then
+/// carries the signature.
+pub trait SignableOrder: OmitOrderMeta {
+    fn set_meta(&mut self, meta: OrderMeta);
+}
+
+macro_rules! signable_orders {
+    ($($order:ty),*) => {
+        $(
+            impl SignableOrder for $order {
+                fn set_meta(&mut self, meta: OrderMeta) {
+                    self.meta = meta;
+                }
+            }
+        )*
+    };
+}
+
+signable_orders!(
+    ExactStandingOrder,
+    PartialStandingOrder,
+    ExactFlashOrder,
+    PartialFlashOrder,
+    TopOfBlockOrder
+);
+
+/// Signs the orders of a single account, along with their cancellations and
+/// amendments, without the key ever leaving the process.
+#[derive(Debug, Clone)]
+pub struct OrderSigner {
+    signer: PrivateKeySigner,
+    domain: Eip712Domain
+}
+
+impl OrderSigner {
+    /// Signs under the domain of the testnet deployment, see
+    /// [`Self::with_domain`].
+    pub fn new(signer: PrivateKeySigner) -> Self {
+        Self { signer, domain: ANGSTROM_DOMAIN }
+    }
+
+    /// The domain of the deployment the orders are for, see
+    /// [`angstrom_domain`](angstrom_types::primitive::angstrom_domain).
+    pub fn with_domain(mut self, domain: Eip712Domain) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    pub fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    pub fn sign<O: SignableOrder>(&self, order: &mut O) -> Result<(), ClientError> {
+        let hash = order.no_meta_eip712_signing_hash(&self.domain);
+        let signature = self.signer.sign_hash_sync(&hash)?;
+        order.set_meta(OrderMeta {
+            isEcdsa:   true,
+            from:      self.address(),
+            signature: signature.pade_encode().into()
+        });
+
+        Ok(())
+    }
+
+    pub fn sign_order(&self, order: &mut AllOrders) -> Result<(), ClientError> {
+        match order {
+            AllOrders::Standing(StandingVariants::Exact(o)) => self.sign(o),
+            AllOrders::Standing(StandingVariants::Partial(o)) => self.sign(o),
+            AllOrders::Flash(FlashVariants::Exact(o)) => self.sign(o),
+            AllOrders::Flash(FlashVariants::Partial(o)) => self.sign(o),
+            AllOrders::TOB(o) => self.sign(o)
+        }
+    }
+
+    /// Cancels our order `order_hash`.
+    pub fn cancel(&self, order_hash: B256) -> Result<CancelOrderRequest, ClientError> {
+        let hash = CancelOrderRequest::signing_hash(self.address(), order_hash);
+        let signature = self.signer.sign_hash_sync(&hash)?;
+
+        Ok(CancelOrderRequest { signature, user_address: self.address(), order_id: order_hash })
+    }
+
+    /// Cancels our pending orders, in `pool_id` or in all pools, with standing
+    /// orders below `nonce`.
+    pub fn cancel_all(
+        &self,
+        pool_id: Option<PoolId>,
+        nonce: u64
+    ) -> Result<CancelAllOrdersRequest, ClientError> {
+        let hash = CancelAllOrdersRequest::signing_hash(self.address(), pool_id, nonce);
+        let signature = self.signer.sign_hash_sync(&hash)?;
+
+        Ok(CancelAllOrdersRequest { user_address: self.address(), pool_id, nonce, signature })
+    }
+
+    /// Changes the amount of our pending standing order to `amount`.
+    pub fn amend(
+        &self,
+        order: &StandingVariants,
+        amount: u128
+    ) -> Result<OrderAmendment, ClientError> {
+        let mut amended = order.clone();
+        match &mut amended {
+            StandingVariants::Exact(o) => {
+                o.amount = amount;
+                self.sign(o)?;
+            }
+            StandingVariants::Partial(o) => {
+                o.max_amount_in = amount;
+                self.sign(o)?;
+            }
+        }
+
+        Ok(OrderAmendment { order_id: order.order_hash(), order: amended })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_orders_the_node_accepts() {
+        let signer = OrderSigner::new(PrivateKeySigner::random());
+
+        let mut order = AllOrders::Standing(StandingVariants::Exact(ExactStandingOrder {
+            amount: 100,
+            ..Default::default()
+        }));
+        signer.sign_order(&mut order).unwrap();
+        assert!(order.is_valid_signature(&ANGSTROM_DOMAIN));
+        assert_eq!(order.from(), signer.address());
+
+        let AllOrders::Standing(standing) = &order else { unreachable!() };
+        let amendment = signer.amend(standing, 200).unwrap();
+        assert!(amendment.amends(standing));
+        assert!(amendment.order.is_valid_signature(&ANGSTROM_DOMAIN));
+
+        assert!(signer.cancel(order.order_hash()).unwrap().is_valid());
+    }
+}
@@ -0,0 +1,169 @@
+//! Feedback on the top of block auction, so searchers can adjust their bids
+//! while the block is still open.
+
+use std::collections::HashMap;
+
+use alloy::primitives::{BlockNumber, B256, U256};
+use angstrom_types::{
+    consensus::{PreProposal, Proposal},
+    primitive::PoolId
+};
+use serde::{Deserialize, Serialize};
+
+/// Auction results kept for subscribers that fall behind.
+pub const AUCTION_RESULTS_CHANNEL_SIZE: usize = 16;
+
+/// The best top of block bid of a pool out of the pre-proposals seen this
+/// round. Pre-proposals are only built once the bid cutoff has passed, so
+/// there is none before.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProvisionalWinner {
+    pub height:     BlockNumber,
+    pub pool_id:    PoolId,
+    pub order_hash: B256,
+    pub tob_reward: U256
+}
+
+/// The outcome of the top of block auction of a pool, as settled by the
+/// proposal of a round.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuctionResult {
+    pub height:     BlockNumber,
+    pub pool_id:    PoolId,
+    /// hash of the winning order, none if no bid was included
+    pub winner:     Option<B256>,
+    pub tob_reward: U256
+}
+
+impl AuctionResult {
+    pub fn from_proposal(proposal: &Proposal) -> Vec<Self> {
+        proposal
+            .solutions
+            .iter()
+            .map(|solution| Self {
+                height:     proposal.block_height,
+                pool_id:    solution.id,
+                winner:     solution.searcher.as_ref().map(|order| order.order_id.hash),
+                tob_reward: solution
+                    .searcher
+                    .as_ref()
+                    .map(|order| order.tob_reward)
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+}
+
+/// Tracks the [`ProvisionalWinner`] of every pool during a round.
+#[derive(Debug, Default)]
+pub(crate) struct ProvisionalWinners(HashMap<PoolId, ProvisionalWinner>);
+
+impl ProvisionalWinners {
+    pub(crate) fn on_pre_proposal(&mut self, pre_proposal: &PreProposal) {
+        for order in &pre_proposal.searcher {
+            let bid = ProvisionalWinner {
+                height:     pre_proposal.block_height,
+                pool_id:    order.pool_id,
+                order_hash: order.order_id.hash,
+                tob_reward: order.tob_reward
+            };
+
+            self.0
+                .entry(order.pool_id)
+                .and_modify(|winner| {
+                    if bid.tob_reward > winner.tob_reward {
+                        *winner = bid.clone();
+                    }
+                })
+                .or_insert(bid);
+        }
+    }
+
+    /// The winners of all pools, ordered by pool.
+    pub(crate) fn winners(&self) -> Vec<ProvisionalWinner> {
+        let mut winners = self.0.values().cloned().collect::<Vec<_>>();
+        winners.sort_by_key(|winner| winner.pool_id);
+        winners
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::{
+        orders::PoolSolution,
+        sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder}
+    };
+
+    use super::*;
+
+    fn bid(pool_id: PoolId, hash: u8, tob_reward: u64) -> OrderWithStorageData<TopOfBlockOrder> {
+        let mut order = OrderWithStorageData {
+            pool_id,
+            tob_reward: U256::from(tob_reward),
+            ..Default::default()
+        };
+        order.order_id.hash = B256::repeat_byte(hash);
+        order
+    }
+
+    #[test]
+    fn highest_bid_per_pool_wins() {
+        let (a, b) = (PoolId::repeat_byte(1), PoolId::repeat_byte(2));
+        let mut winners = ProvisionalWinners::default();
+        assert!(winners.winners().is_empty());
+
+        winners.on_pre_proposal(&PreProposal {
+            block_height: 5,
+            searcher: vec![bid(b, 1, 10), bid(a, 2, 30)],
+            ..Default::default()
+        });
+        winners.on_pre_proposal(&PreProposal {
+            block_height: 5,
+            searcher: vec![bid(b, 3, 20), bid(a, 4, 30)],
+            ..Default::default()
+        });
+
+        let winners = winners.winners();
+        assert_eq!(
+            winners
+                .iter()
+                .map(|winner| (winner.pool_id, winner.order_hash))
+                .collect::<Vec<_>>(),
+            vec![(a, B256::repeat_byte(2)), (b, B256::repeat_byte(3))]
+        );
+        assert_eq!(winners[1].tob_reward, U256::from(20));
+    }
+
+    #[test]
+    fn results_follow_the_proposal_solutions() {
+        let (a, b) = (PoolId::repeat_byte(1), PoolId::repeat_byte(2));
+        let proposal = Proposal {
+            block_height: 7,
+            solutions: vec![
+                PoolSolution { id: a, searcher: Some(bid(a, 1, 40)), ..Default::default() },
+                PoolSolution { id: b, ..Default::default() },
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            AuctionResult::from_proposal(&proposal),
+            vec![
+                AuctionResult {
+                    height:     7,
+                    pool_id:    a,
+                    winner:     Some(B256::repeat_byte(1)),
+                    tob_reward: U256::from(40)
+                },
+                AuctionResult {
+                    height:     7,
+                    pool_id:    b,
+                    winner:     None,
+                    tob_reward: U256::ZERO
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::{HashMap, VecDeque};
+
+use alloy::primitives::U256;
+use angstrom_types::{matching::Ray, primitive::PoolId};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_MAX_UCP_DEVIATION_BPS: u32 = 500;
+pub const DEFAULT_MAX_FAILED_SETTLEMENTS: u32 = 3;
+pub const DEFAULT_TWAP_BLOCKS: usize = 30;
+
+/// When the breaker of a pool trips.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// largest deviation of a pool's UCP from its AMM TWAP, in basis points
+    pub max_ucp_deviation_bps:  u32,
+    /// number of blocks the AMM TWAP is taken over
+    pub twap_blocks:            usize,
+    /// consecutive blocks a pool may fail to settle in
+    pub max_failed_settlements: u32
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            max_ucp_deviation_bps:  DEFAULT_MAX_UCP_DEVIATION_BPS,
+            twap_blocks:            DEFAULT_TWAP_BLOCKS,
+            max_failed_settlements: DEFAULT_MAX_FAILED_SETTLEMENTS
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TripReason {
+    /// a UCP cleared too far from the AMM TWAP
+    UcpDeviation { ucp: Ray, twap: Ray },
+    /// the pool failed to settle this many blocks in a row
+    FailedSettlements(u32)
+}
+
+#[derive(Debug, Default)]
+struct PoolBreaker {
+    /// AMM prices at the start of the last blocks, oldest first
+    amm_prices:         VecDeque<Ray>,
+    failed_settlements: u32,
+    tripped:            Option<TripReason>
+}
+
+/// Pauses matching for a pool whose results look wrong, until an operator
+/// resumes it.
+///
+/// A settlement is only known to have failed when it was our own, other
+/// validators only propagate proposals that landed. Blocks we don't know the
+/// outcome of leave the count of failures as it is.
+#[derive(Debug, Default)]
+pub struct CircuitBreakers {
+    config: CircuitBreakerConfig,
+    pools:  HashMap<PoolId, PoolBreaker>
+}
+
+impl CircuitBreakers {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self { config, pools: HashMap::new() }
+    }
+
+    /// Records the AMM price of `pool` at the start of a block.
+    pub fn record_amm_price(&mut self, pool: PoolId, price: Ray) {
+        let prices = &mut self.pools.entry(pool).or_default().amm_prices;
+        prices.push_back(price);
+        while prices.len() > self.config.twap_blocks {
+            prices.pop_front();
+        }
+    }
+
+    /// Mean of the AMM prices of `pool` over the last blocks, each block
+    /// weighing the same.
+    pub fn twap(&self, pool: &PoolId) -> Option<Ray> {
+        let prices = &self.pools.get(pool)?.amm_prices;
+        if prices.is_empty() {
+            return None
+        }
+
+        let sum = prices
+            .iter()
+            .fold(U256::ZERO, |sum, price| sum.saturating_add(**price));
+        Some(Ray::from(sum / U256::from(prices.len())))
+    }
+
+    /// Trips the breaker of `pool` if `ucp` is too far off its TWAP.
+    pub fn check_ucp(&mut self, pool: PoolId, ucp: Ray) {
+        let Some(twap) = self.twap(&pool) else { return };
+        if twap.is_zero() {
+            return
+        }
+
+        let deviation_bps = ucp.abs_diff(*twap).saturating_mul(U256::from(10_000)) / *twap;
+        if deviation_bps > U256::from(self.config.max_ucp_deviation_bps) {
+            self.trip(pool, TripReason::UcpDeviation { ucp, twap });
+        }
+    }
+
+    /// Records whether the settlement of `pool` in a block landed.
+    pub fn on_settlement(&mut self, pool: PoolId, landed: bool) {
+        let breaker = self.pools.entry(pool).or_default();
+        if landed {
+            breaker.failed_settlements = 0;
+            return
+        }
+
+        breaker.failed_settlements += 1;
+        let failed = breaker.failed_settlements;
+        if failed >= self.config.max_failed_settlements {
+            self.trip(pool, TripReason::FailedSettlements(failed));
+        }
+    }
+
+    pub fn is_paused(&self, pool: &PoolId) -> bool {
+        self.pools
+            .get(pool)
+            .is_some_and(|breaker| breaker.tripped.is_some())
+    }
+
+    pub fn paused(&self) -> Vec<(PoolId, TripReason)> {
+        self.pools
+            .iter()
+            .filter_map(|(pool, breaker)| Some((*pool, breaker.tripped?)))
+            .collect()
+    }
+
+    /// Resumes matching for `pool`. Returns false if it wasn't paused.
+    pub fn resume(&mut self, pool: &PoolId) -> bool {
+        let Some(breaker) = self.pools.get_mut(pool) else { return false };
+        breaker.failed_settlements = 0;
+
+        breaker
+            .tripped
+            .take()
+            .inspect(|_| tracing::info!(?pool, "resumed matching for pool"))
+            .is_some()
+    }
+
+    fn trip(&mut self, pool: PoolId, reason: TripReason) {
+        let breaker = self.pools.entry(pool).or_default();
+        if breaker.tripped.is_none() {
+            tracing::error!(
+                ?pool,
+                ?reason,
+                "circuit breaker tripped, matching for the pool is paused until resumed"
+            );
+            breaker.tripped = Some(reason);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ray(price: u64) -> Ray {
+        Ray::from(U256::from(price))
+    }
+
+    #[test]
+    fn trips_on_ucp_far_from_twap() {
+        let mut breakers = CircuitBreakers::new(CircuitBreakerConfig {
+            max_ucp_deviation_bps:  500,
+            twap_blocks:            2,
+            max_failed_settlements: 3
+        });
+        let pool = PoolId::random();
+
+        breakers.record_amm_price(pool, ray(1_000));
+        breakers.record_amm_price(pool, ray(900));
+        breakers.record_amm_price(pool, ray(1_100));
+        assert_eq!(breakers.twap(&pool), Some(ray(1_000)));
+
+        breakers.check_ucp(pool, ray(1_050));
+        assert!(!breakers.is_paused(&pool));
+
+        breakers.check_ucp(pool, ray(1_051));
+        assert!(breakers.is_paused(&pool));
+        assert_eq!(
+            breakers.paused(),
+            vec![(pool, TripReason::UcpDeviation { ucp: ray(1_051), twap: ray(1_000) })]
+        );
+
+        assert!(breakers.resume(&pool));
+        assert!(!breakers.is_paused(&pool));
+        assert!(!breakers.resume(&pool));
+    }
+
+    #[test]
+    fn trips_on_consecutive_failed_settlements() {
+        let mut breakers = CircuitBreakers::new(CircuitBreakerConfig::default());
+        let pool = PoolId::random();
+
+        breakers.on_settlement(pool, false);
+        breakers.on_settlement(pool, false);
+        breakers.on_settlement(pool, true);
+        breakers.on_settlement(pool, false);
+        breakers.on_settlement(pool, false);
+        assert!(!breakers.is_paused(&pool));
+
+        breakers.on_settlement(pool, false);
+        assert_eq!(breakers.paused(), vec![(pool, TripReason::FailedSettlements(3))]);
+    }
+}
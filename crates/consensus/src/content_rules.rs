@@ -0,0 +1,110 @@
+use alloy::primitives::B256;
+use angstrom_types::consensus::PreProposal;
+
+/// Default share of a pre-proposal's orders we may never have seen, 5%.
+pub const DEFAULT_MAX_UNKNOWN_ORDERS_BPS: u32 = 500;
+
+/// Unknown orders every pre-proposal may have regardless of its size, for the
+/// ones whose gossip hasn't reached us yet.
+const MIN_UNKNOWN_ALLOWANCE: usize = 2;
+
+/// Rejects pre-proposals made up of orders that were never gossiped to us, so
+/// that a validator can't get orders only it knows of into the auction.
+///
+/// Of the orders of a pre-proposal, at most `max_unknown_bps` basis points, and
+/// never less than `min_unknown_allowance`, may be ones our order pool doesn't
+/// have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentRules {
+    pub max_unknown_bps:       u32,
+    pub min_unknown_allowance: usize
+}
+
+impl Default for ContentRules {
+    fn default() -> Self {
+        Self {
+            max_unknown_bps:       DEFAULT_MAX_UNKNOWN_ORDERS_BPS,
+            min_unknown_allowance: MIN_UNKNOWN_ALLOWANCE
+        }
+    }
+}
+
+impl ContentRules {
+    pub fn new(max_unknown_bps: u32) -> Self {
+        Self { max_unknown_bps, ..Default::default() }
+    }
+
+    /// Checks the orders of `pre_proposal` against the ones `is_known` to us.
+    /// Errors with the unknown ones when there are more than we tolerate.
+    pub fn check(
+        &self,
+        pre_proposal: &PreProposal,
+        is_known: impl Fn(&B256) -> bool
+    ) -> Result<(), Vec<B256>> {
+        let orders = pre_proposal
+            .limit
+            .iter()
+            .map(|order| order.order_id.hash)
+            .chain(
+                pre_proposal
+                    .searcher
+                    .iter()
+                    .map(|order| order.order_id.hash)
+            );
+
+        let mut total = 0;
+        let unknown = orders
+            .inspect(|_| total += 1)
+            .filter(|hash| !is_known(hash))
+            .collect::<Vec<_>>();
+
+        if unknown.len() <= self.allowance(total) {
+            Ok(())
+        } else {
+            Err(unknown)
+        }
+    }
+
+    fn allowance(&self, total: usize) -> usize {
+        let share = total.saturating_mul(self.max_unknown_bps as usize) / 10_000;
+        share.max(self.min_unknown_allowance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::sol_bindings::grouped_orders::OrderWithStorageData;
+
+    use super::*;
+
+    fn pre_proposal(orders: u8) -> PreProposal {
+        PreProposal {
+            limit: (0..orders)
+                .map(|i| {
+                    let mut order = OrderWithStorageData::default();
+                    order.order_id.hash = B256::repeat_byte(i);
+                    order
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tolerates_few_unknown_orders() {
+        let rules = ContentRules::new(1_000);
+        let pre_proposal = pre_proposal(40);
+
+        assert_eq!(rules.check(&pre_proposal, |_| true), Ok(()));
+        assert_eq!(rules.check(&pre_proposal, |hash| hash[0] >= 4), Ok(()));
+        assert_eq!(
+            rules.check(&pre_proposal, |hash| hash[0] >= 5),
+            Err((0..5).map(B256::repeat_byte).collect())
+        );
+
+        // small pre-proposals get the minimum allowance
+        let small = pre_proposal(3);
+        assert_eq!(rules.check(&small, |hash| hash[0] >= 2), Ok(()));
+        assert!(rules.check(&small, |_| false).is_err());
+    }
+}
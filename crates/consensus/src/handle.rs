@@ -0,0 +1,212 @@
+use std::future::Future;
+
+use alloy::primitives::{BlockNumber, B256, U256};
+use angstrom_types::{
+    contract_payloads::angstrom::FeeLedger,
+    mev_boost::SubmissionTargetStats,
+    primitive::{PeerId, PoolId},
+    sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData}
+};
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, mpsc::UnboundedSender, oneshot};
+
+use crate::{rounds::ConsensusPhase, AuctionResult, ProvisionalWinner, ValidatorTelemetrySummary};
+
+/// Snapshot of the consensus round the node is currently in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusRoundInfo {
+    pub height:                    BlockNumber,
+    pub phase:                     ConsensusPhase,
+    pub leader:                    PeerId,
+    pub is_leader:                 bool,
+    /// pre-proposals received from other validators this round
+    pub pre_proposals:             usize,
+    /// pre-proposal aggregations received from other validators this round
+    pub pre_proposal_aggregations: usize,
+    /// hash of the last proposal we have seen, along with its height
+    pub last_proposal:             Option<(BlockNumber, B256)>
+}
+
+/// The leader of a single height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderSlot {
+    pub height: BlockNumber,
+    pub leader: PeerId
+}
+
+/// How an order would be matched if it was in the book of its pool now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowMatch {
+    /// uniform clearing price of the pool, in ray
+    pub clearing_price:  U256,
+    /// how much of the order would be filled, zero if it wouldn't be
+    pub filled_quantity: u128,
+    /// how many orders are ahead of it on its side of the book. For a top of
+    /// block order, the bids of its pool that pay at least as much
+    pub queue_position:  usize
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ShadowMatchError {
+    #[error("pool {0} is paused")]
+    PoolPaused(PoolId),
+    #[error("pool {0} isn't tracked")]
+    UnknownPool(PoolId),
+    #[error("failed to match the pool - {0}")]
+    Matching(String)
+}
+
+#[derive(Debug)]
+pub enum ConsensusRequest {
+    RoundInfo(oneshot::Sender<ConsensusRoundInfo>),
+    /// leaders of `count` heights starting at the given one
+    LeaderSchedule(BlockNumber, u64, oneshot::Sender<Vec<LeaderSlot>>),
+    /// best top of block bid of every pool seen this round
+    ProvisionalWinners(oneshot::Sender<Vec<ProvisionalWinner>>),
+    /// what the bundles we landed saved for the protocol
+    FeeLedger(oneshot::Sender<FeeLedger>),
+    /// how the validators, us included, did over the last blocks
+    ValidatorTelemetry(oneshot::Sender<Vec<ValidatorTelemetrySummary>>),
+    /// how the endpoints we submit bundles to have been doing
+    SubmissionTargets(oneshot::Sender<Vec<SubmissionTargetStats>>),
+    /// the auction results of every proposal from now on
+    SubscribeAuctionResults(oneshot::Sender<broadcast::Receiver<Vec<AuctionResult>>>),
+    /// matches the book of the pool of a validated order with the order in it,
+    /// without adding it to the book
+    SimulateOrder(
+        Box<OrderWithStorageData<AllOrders>>,
+        oneshot::Sender<Result<ShadowMatch, ShadowMatchError>>
+    ),
+    /// stop participating in consensus. Answered once the current round is
+    /// finished or abdicated, after which the manager exits.
+    Shutdown(oneshot::Sender<()>)
+}
+
+/// Read access into the [`ConsensusManager`](crate::ConsensusManager).
+pub trait ConsensusHandle: Send + Sync + Clone + Unpin + 'static {
+    fn round_info(&self) -> impl Future<Output = Option<ConsensusRoundInfo>> + Send;
+
+    fn leader_schedule(
+        &self,
+        from: BlockNumber,
+        count: u64
+    ) -> impl Future<Output = Option<Vec<LeaderSlot>>> + Send;
+
+    fn get_leader(&self, height: BlockNumber) -> impl Future<Output = Option<PeerId>> + Send {
+        self.leader_schedule(height, 1)
+            .map(|slots| slots?.first().map(|slot| slot.leader))
+    }
+
+    fn provisional_winners(&self) -> impl Future<Output = Option<Vec<ProvisionalWinner>>> + Send;
+
+    fn subscribe_auction_results(
+        &self
+    ) -> impl Future<Output = Option<broadcast::Receiver<Vec<AuctionResult>>>> + Send;
+
+    fn fee_ledger(&self) -> impl Future<Output = Option<FeeLedger>> + Send;
+
+    fn validator_telemetry(
+        &self
+    ) -> impl Future<Output = Option<Vec<ValidatorTelemetrySummary>>> + Send;
+
+    fn submission_targets(&self)
+        -> impl Future<Output = Option<Vec<SubmissionTargetStats>>> + Send;
+
+    /// `None` if consensus isn't running.
+    fn simulate_order(
+        &self,
+        order: OrderWithStorageData<AllOrders>
+    ) -> impl Future<Output = Option<Result<ShadowMatch, ShadowMatchError>>> + Send;
+
+    fn shutdown(&self) -> impl Future<Output = ()> + Send;
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsensusQueryHandle {
+    pub sender: UnboundedSender<ConsensusRequest>
+}
+
+impl ConsensusQueryHandle {
+    pub fn new(sender: UnboundedSender<ConsensusRequest>) -> Self {
+        Self { sender }
+    }
+}
+
+impl ConsensusHandle for ConsensusQueryHandle {
+    fn round_info(&self) -> impl Future<Output = Option<ConsensusRoundInfo>> + Send {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(ConsensusRequest::RoundInfo(tx));
+        rx.map(Result::ok)
+    }
+
+    fn leader_schedule(
+        &self,
+        from: BlockNumber,
+        count: u64
+    ) -> impl Future<Output = Option<Vec<LeaderSlot>>> + Send {
+        let (tx, rx) = oneshot::channel();
+        let _ = self
+            .sender
+            .send(ConsensusRequest::LeaderSchedule(from, count, tx));
+        rx.map(Result::ok)
+    }
+
+    fn provisional_winners(&self) -> impl Future<Output = Option<Vec<ProvisionalWinner>>> + Send {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(ConsensusRequest::ProvisionalWinners(tx));
+        rx.map(Result::ok)
+    }
+
+    fn subscribe_auction_results(
+        &self
+    ) -> impl Future<Output = Option<broadcast::Receiver<Vec<AuctionResult>>>> + Send {
+        let (tx, rx) = oneshot::channel();
+        let _ = self
+            .sender
+            .send(ConsensusRequest::SubscribeAuctionResults(tx));
+        rx.map(Result::ok)
+    }
+
+    fn fee_ledger(&self) -> impl Future<Output = Option<FeeLedger>> + Send {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(ConsensusRequest::FeeLedger(tx));
+        rx.map(Result::ok)
+    }
+
+    fn validator_telemetry(
+        &self
+    ) -> impl Future<Output = Option<Vec<ValidatorTelemetrySummary>>> + Send {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(ConsensusRequest::ValidatorTelemetry(tx));
+        rx.map(Result::ok)
+    }
+
+    fn submission_targets(
+        &self
+    ) -> impl Future<Output = Option<Vec<SubmissionTargetStats>>> + Send {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(ConsensusRequest::SubmissionTargets(tx));
+        rx.map(Result::ok)
+    }
+
+    fn simulate_order(
+        &self,
+        order: OrderWithStorageData<AllOrders>
+    ) -> impl Future<Output = Option<Result<ShadowMatch, ShadowMatchError>>> + Send {
+        let (tx, rx) = oneshot::channel();
+        let _ = self
+            .sender
+            .send(ConsensusRequest::SimulateOrder(Box::new(order), tx));
+        rx.map(Result::ok)
+    }
+
+    fn shutdown(&self) -> impl Future<Output = ()> + Send {
+        let (tx, rx) = oneshot::channel();
+        let _ = self.sender.send(ConsensusRequest::Shutdown(tx));
+        rx.map(|_| ())
+    }
+}
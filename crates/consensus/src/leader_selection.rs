@@ -1,6 +1,6 @@
 use std::{cmp::Ordering, collections::HashSet};
 
-use alloy::primitives::BlockNumber;
+use alloy::primitives::{keccak256, BlockNumber, B256};
 use angstrom_types::primitive::PeerId;
 
 // https://github.com/tendermint/tendermint/pull/2785#discussion_r235038971
@@ -8,6 +8,10 @@ use angstrom_types::primitive::PeerId;
 const PENALTY_FACTOR: u64 = 1125;
 /// do the math with fixed here to avoid floats
 const ONE_E3: u64 = 1000;
+/// Number of blocks after which the priorities are reset to their seeded
+/// starting point. Anchoring the schedule to absolute block numbers means that
+/// a node derives the same leaders no matter at which height it started.
+pub const LEADER_EPOCH_LENGTH: u64 = 64;
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct AngstromValidator {
@@ -26,12 +30,15 @@ impl AngstromValidator {
     }
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct WeightedRoundRobin {
-    validators:                HashSet<AngstromValidator>,
+    validators: HashSet<AngstromValidator>,
     new_joiner_penalty_factor: u64,
-    block_number:              BlockNumber,
-    last_proposer:             Option<PeerId>
+    block_number: BlockNumber,
+    last_proposer: Option<PeerId>,
+    /// block hash all nodes agree on, used to shuffle the starting priorities
+    /// of every epoch
+    seed: B256
 }
 
 impl WeightedRoundRobin {
@@ -40,10 +47,69 @@ impl WeightedRoundRobin {
             validators: HashSet::from_iter(validators),
             new_joiner_penalty_factor: PENALTY_FACTOR,
             block_number,
-            last_proposer: None
+            last_proposer: None,
+            seed: B256::ZERO
         }
     }
 
+    pub fn with_seed(mut self, seed: B256) -> Self {
+        self.seed = seed;
+        self.last_proposer = None;
+        self
+    }
+
+    /// The leader for the given height. This only depends on the validator set,
+    /// the seed and the height, so every node derives the same schedule and it
+    /// can be looked up ahead of time.
+    pub fn get_leader(&self, block_number: BlockNumber) -> Option<PeerId> {
+        if self.validators.is_empty() {
+            return None
+        }
+
+        let mut round = self.seeded_for_epoch(block_number / LEADER_EPOCH_LENGTH);
+        let mut leader = None;
+        for _ in 0..=(block_number % LEADER_EPOCH_LENGTH) {
+            round.center_priorities();
+            round.scale_priorities();
+            leader = Some(round.proposer_selection());
+        }
+
+        leader
+    }
+
+    /// The leaders of `count` consecutive heights starting at `from`.
+    pub fn schedule(&self, from: BlockNumber, count: u64) -> Vec<(BlockNumber, PeerId)> {
+        (from..from.saturating_add(count))
+            .filter_map(|height| Some((height, self.get_leader(height)?)))
+            .collect()
+    }
+
+    /// Copy of the current state with the priorities moved to the starting
+    /// point of the given epoch. Each validator gets an offset below its own
+    /// voting power, which shuffles the order within the epoch without
+    /// changing the share of blocks it leads.
+    fn seeded_for_epoch(&self, epoch: u64) -> Self {
+        let mut round = self.clone();
+        round.validators = round
+            .validators
+            .drain()
+            .map(|mut validator| {
+                let mut buf = Vec::with_capacity(32 + 8 + 64);
+                buf.extend_from_slice(self.seed.as_slice());
+                buf.extend_from_slice(&epoch.to_be_bytes());
+                buf.extend_from_slice(validator.peer_id.as_slice());
+                let hash = keccak256(buf);
+
+                let offset = u64::from_be_bytes(hash[..8].try_into().unwrap())
+                    % validator.voting_power.max(1);
+                validator.priority += offset as i64;
+                validator
+            })
+            .collect();
+
+        round
+    }
+
     fn proposer_selection(&mut self) -> PeerId {
         let total_voting_power: u64 = self.validators.iter().map(|v| v.voting_power).sum();
 
@@ -129,35 +195,21 @@ impl WeightedRoundRobin {
     }
 
     pub fn choose_proposer(&mut self, block_number: BlockNumber) -> Option<PeerId> {
-        // 1. this is not ideal, since on multi-block reorgs the same proposer will be
-        //    chosen for the length of the reorg
-        // 2. reverting the block number (self.block_number = block_number) is also not
-        //    ideal, since nodes who were offline will not have seen the reorg, thus
-        //    would not have executed the extra rounds after this if statement
-        if block_number == self.block_number {
-            if self.last_proposer.is_none() {
-                self.last_proposer = Some(self.proposer_selection());
-            }
-
-            return self.last_proposer
+        // the leader doesn't depend on the heights we have seen before, so reorgs and
+        // nodes that were offline for a while still agree on it.
+        if block_number != self.block_number || self.last_proposer.is_none() {
+            self.last_proposer = self.get_leader(block_number);
+            self.block_number = block_number;
         }
 
-        let rounds_to_catchup = (block_number - self.block_number) as usize;
-        let mut leader = None;
-        for _ in 0..rounds_to_catchup {
-            self.center_priorities();
-            self.scale_priorities();
-            leader = Some(self.proposer_selection());
-            self.last_proposer = leader
-        }
-        self.block_number = block_number;
-        leader
+        self.last_proposer
     }
 
     #[allow(dead_code)]
     fn remove_validator(&mut self, peer_id: &PeerId) {
         let validator = AngstromValidator::new(*peer_id, 0);
         self.validators.remove(&validator);
+        self.last_proposer = None;
     }
 
     #[allow(dead_code)]
@@ -167,6 +219,7 @@ impl WeightedRoundRobin {
         new_validator.priority -=
             ((self.new_joiner_penalty_factor * total_voting_power) / ONE_E3) as i64;
         self.validators.insert(new_validator);
+        self.last_proposer = None;
     }
 }
 
@@ -444,6 +497,83 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_schedule_is_independent_of_start_height() {
+        let (_, validators) = create_test_validators();
+        let seed = B256::random();
+        let mut early = WeightedRoundRobin::new(validators.clone(), 0).with_seed(seed);
+        let mut late = WeightedRoundRobin::new(validators, 500).with_seed(seed);
+
+        for height in 500..700 {
+            assert_eq!(early.choose_proposer(height), late.choose_proposer(height));
+        }
+
+        // jumping around, e.g on a reorg, doesn't change the outcome
+        for height in [650, 520, 699, 500] {
+            assert_eq!(late.choose_proposer(height), early.get_leader(height));
+        }
+    }
+
+    #[test]
+    fn test_nodes_derive_identical_schedules() {
+        let (_, validators) = create_test_validators();
+        let seed = B256::random();
+
+        let schedules = (0..5)
+            .map(|i| {
+                // every node learns about the validators in a different order
+                let mut validators = validators.clone();
+                validators.rotate_left(i % validators.len());
+                WeightedRoundRobin::new(validators, i as u64 * 1000)
+                    .with_seed(seed)
+                    .schedule(10_000, 3 * LEADER_EPOCH_LENGTH)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(schedules[0].len(), 3 * LEADER_EPOCH_LENGTH as usize);
+        assert!(schedules.iter().all(|schedule| *schedule == schedules[0]));
+    }
+
+    #[test]
+    fn test_schedule_matches_choose_proposer() {
+        let (_, validators) = create_test_validators();
+        let mut algo = WeightedRoundRobin::new(validators, 0).with_seed(B256::random());
+        let schedule = algo.schedule(1, 100);
+
+        for (height, leader) in schedule {
+            assert_eq!(algo.choose_proposer(height), Some(leader));
+        }
+    }
+
+    #[test]
+    fn test_seed_shuffles_equal_stakes() {
+        let validators = (0..4)
+            .map(|_| AngstromValidator::new(PeerId::random(), 100))
+            .collect::<Vec<_>>();
+        let algo = WeightedRoundRobin::new(validators, 0);
+
+        let schedules = (0..10)
+            .map(|_| {
+                algo.clone()
+                    .with_seed(B256::random())
+                    .schedule(0, LEADER_EPOCH_LENGTH)
+            })
+            .collect::<Vec<_>>();
+
+        assert!(
+            schedules.iter().any(|schedule| *schedule != schedules[0]),
+            "the seed should decide the order of equally staked validators"
+        );
+    }
+
+    #[test]
+    fn test_empty_validator_set_has_no_leader() {
+        let mut algo = WeightedRoundRobin::new(vec![], 0);
+        assert_eq!(algo.get_leader(10), None);
+        assert_eq!(algo.choose_proposer(10), None);
+        assert!(algo.schedule(0, 10).is_empty());
+    }
+
     #[test]
     fn test_round_robin_simulation() {
         let peers = HashMap::from([
@@ -1,7 +1,28 @@
+mod auction;
+mod circuit_breaker;
+mod content_rules;
+mod handle;
 mod leader_selection;
 mod manager;
+mod signing_guard;
+mod submission_approval;
+mod submission_ledger;
+mod surplus_policy;
+mod telemetry;
+mod watchtower;
 
+pub use auction::*;
+pub use circuit_breaker::*;
+pub use content_rules::*;
+pub use handle::*;
 pub use manager::*;
+pub use signing_guard::*;
+pub use submission_approval::*;
+pub use submission_ledger::*;
+pub use surplus_policy::*;
+pub use telemetry::*;
+pub use watchtower::*;
+pub mod replica;
 pub mod rounds;
 
 use std::pin::Pin;
@@ -3,32 +3,42 @@ use std::{
     future::Future,
     pin::Pin,
     sync::Arc,
-    task::{Context, Poll, Waker}
+    task::{Context, Poll, Waker},
+    time::Duration
 };
 
 use alloy::{
     consensus::BlockHeader,
-    primitives::{Address, BlockNumber},
+    primitives::{Address, BlockNumber, B256},
     providers::Provider
 };
 use angstrom_metrics::ConsensusMetricsWrapper;
 use angstrom_network::{manager::StromConsensusEvent, StromMessage, StromNetworkHandle};
 use angstrom_types::{
-    block_sync::BlockSyncConsumer, contract_payloads::angstrom::UniswapAngstromRegistry,
-    mev_boost::MevBoostProvider, primitive::AngstromSigner
+    block_sync::BlockSyncConsumer,
+    consensus::{Proposal, SubmissionApprovalRequest},
+    contract_payloads::{angstrom::UniswapAngstromRegistry, rewards::SurplusDistribution},
+    mev_boost::MevBoostProvider,
+    primitive::{AngstromSigner, ConfigUpdate, PeerId}
 };
 use futures::StreamExt;
 use matching_engine::MatchingEngineHandle;
 use order_pool::order_storage::OrderStorage;
 use reth_metrics::common::mpsc::UnboundedMeteredReceiver;
 use reth_provider::{CanonStateNotification, CanonStateNotifications};
-use tokio_stream::wrappers::BroadcastStream;
+use tokio::sync::{broadcast, mpsc::UnboundedReceiver, oneshot, watch};
+use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
 use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
 
 use crate::{
+    auction::{AuctionResult, ProvisionalWinners, AUCTION_RESULTS_CHANNEL_SIZE},
+    handle::{ConsensusRequest, ConsensusRoundInfo, LeaderSlot},
     leader_selection::WeightedRoundRobin,
+    replica::ReplicaLink,
     rounds::{ConsensusMessage, RoundStateMachine, SharedRoundState},
-    AngstromValidator
+    AngstromValidator, CircuitBreakerConfig, ContentRules, SignedRecord, SigningGuard,
+    SubmissionApprovalConfig, SubmissionLedger, SurplusPolicy, TelemetryAggregator,
+    WatchtowerAlert
 };
 
 const MODULE_NAME: &str = "Consensus";
@@ -41,11 +51,51 @@ pub struct ConsensusManager<P, Matching, BlockSync> {
     strom_consensus_event:  UnboundedMeteredReceiver<StromConsensusEvent>,
     network:                StromNetworkHandle,
     block_sync:             BlockSync,
+    /// Requests for the state of consensus, e.g from rpc
+    queries:                Option<UnboundedReceiverStream<ConsensusRequest>>,
+    /// what we have seen during the current round
+    round_stats:            RoundStats,
+    /// set once we were asked to shut down, answered when we are done
+    shutdown:               Option<oneshot::Sender<()>>,
+    /// Live configuration changes made through the admin api
+    config_updates:         Option<BroadcastStream<ConfigUpdate>>,
+    /// Top of block auction outcome of every proposal we see
+    auction_results:        broadcast::Sender<Vec<AuctionResult>>,
+    /// how we and the validators that share their telemetry did
+    telemetry:              TelemetryAggregator,
+    /// whether our telemetry is sent to our peers
+    share_telemetry:        bool,
+    /// set when the node is the primary or the standby of a validator
+    replica:                Option<Replica>,
 
     /// Track broadcasted messages to avoid rebroadcasting
     broadcasted_messages: HashSet<StromConsensusEvent>
 }
 
+/// How the [`ReplicaLink`] of the node stands.
+struct Replica {
+    active:     watch::Receiver<bool>,
+    replicated: Option<UnboundedReceiverStream<SignedRecord>>,
+    /// whether we take part in the current round. We stop as soon as we are no
+    /// longer active, but only start with the next round
+    acting:     bool
+}
+
+impl Replica {
+    fn acting(&mut self) -> bool {
+        self.acting &= *self.active.borrow();
+        self.acting
+    }
+}
+
+#[derive(Debug, Default)]
+struct RoundStats {
+    pre_proposals:             usize,
+    pre_proposal_aggregations: usize,
+    last_proposal:             Option<(BlockNumber, B256)>,
+    provisional_winners:       ProvisionalWinners
+}
+
 impl<P, Matching, BlockSync> ConsensusManager<P, Matching, BlockSync>
 where
     P: Provider + 'static,
@@ -57,6 +107,7 @@ where
         netdeps: ManagerNetworkDeps,
         signer: AngstromSigner,
         validators: Vec<AngstromValidator>,
+        leader_seed: B256,
         order_storage: Arc<OrderStorage>,
         current_height: BlockNumber,
         angstrom_address: Address,
@@ -69,7 +120,8 @@ where
         let ManagerNetworkDeps { network, canonical_block_stream, strom_consensus_event } = netdeps;
         let wrapped_broadcast_stream = BroadcastStream::new(canonical_block_stream);
         tracing::info!(?validators, "setting up with validators");
-        let mut leader_selection = WeightedRoundRobin::new(validators.clone(), current_height);
+        let mut leader_selection =
+            WeightedRoundRobin::new(validators.clone(), current_height).with_seed(leader_seed);
         let leader = leader_selection.choose_proposer(current_height).unwrap();
         block_sync.register(MODULE_NAME);
 
@@ -93,10 +145,219 @@ where
             block_sync,
             network,
             canonical_block_stream: wrapped_broadcast_stream,
+            queries: None,
+            round_stats: RoundStats::default(),
+            shutdown: None,
+            config_updates: None,
+            auction_results: broadcast::channel(AUCTION_RESULTS_CHANNEL_SIZE).0,
+            telemetry: TelemetryAggregator::default(),
+            share_telemetry: false,
+            replica: None,
             broadcasted_messages: HashSet::new()
         }
     }
 
+    /// Installs the receiving end of a
+    /// [`ConsensusQueryHandle`](crate::ConsensusQueryHandle).
+    pub fn with_query_channel(mut self, rx: UnboundedReceiver<ConsensusRequest>) -> Self {
+        self.queries = Some(UnboundedReceiverStream::new(rx));
+        self
+    }
+
+    /// How much worse than the local solve a leader's proposal may be.
+    pub fn with_surplus_policy(mut self, surplus_policy: SurplusPolicy) -> Self {
+        self.consensus_round_state
+            .set_surplus_policy(surplus_policy);
+        self
+    }
+
+    /// How many orders we never saw a validator's pre-proposal may carry.
+    pub fn with_content_rules(mut self, content_rules: ContentRules) -> Self {
+        self.consensus_round_state.set_content_rules(content_rules);
+        self
+    }
+
+    /// Who the surplus of the user orders in the bundles we build and verify
+    /// goes to, the one of the deployment.
+    pub fn with_surplus_distribution(mut self, distribution: SurplusDistribution) -> Self {
+        self.consensus_round_state
+            .set_surplus_distribution(distribution);
+        self
+    }
+
+    /// When matching for a pool is paused automatically.
+    pub fn with_circuit_breakers(mut self, config: CircuitBreakerConfig) -> Self {
+        self.consensus_round_state.set_circuit_breakers(config);
+        self
+    }
+
+    /// Simulates the bundles we submit in every layout and submits the
+    /// cheapest, see [`matching_engine::golf`].
+    pub fn with_bundle_golfing(mut self, golf_bundles: bool) -> Self {
+        self.consensus_round_state.set_golf_bundles(golf_bundles);
+        self
+    }
+
+    /// Misbehavior of the leaders found when verifying their proposals, raised
+    /// as the same alerts the [`Watchtower`](crate::Watchtower) raises.
+    pub fn subscribe_anomalies(&self) -> broadcast::Receiver<WatchtowerAlert> {
+        self.consensus_round_state.subscribe_anomalies()
+    }
+
+    /// Times the rounds for a chain whose blocks are `block_time` apart
+    /// instead of mainnet's 12 seconds.
+    pub fn with_block_time(mut self, block_time: Duration) -> Self {
+        self.consensus_round_state.set_block_time(block_time);
+        self
+    }
+
+    /// Replaces the in memory signing guard, e.g with one backed by a file.
+    pub fn with_signing_guard(mut self, signing_guard: SigningGuard) -> Self {
+        self.consensus_round_state.set_signing_guard(signing_guard);
+        self
+    }
+
+    /// Replaces the in memory submission ledger, e.g with one backed by a file.
+    pub fn with_submission_ledger(mut self, submission_ledger: SubmissionLedger) -> Self {
+        self.consensus_round_state
+            .set_submission_ledger(submission_ledger);
+        self
+    }
+
+    /// Holds the settlement transactions back until `threshold` of the
+    /// approvers committed to them, and approves those of the other leaders
+    /// when we are one of the approvers.
+    pub fn with_submission_approval(mut self, config: SubmissionApprovalConfig) -> Self {
+        self.consensus_round_state.set_submission_approval(config);
+        self
+    }
+
+    /// Runs as the primary or the standby of a validator, only taking part in
+    /// consensus while the replica task says so. See [`crate::replica`].
+    pub fn with_replica(mut self, link: ReplicaLink) -> Self {
+        let acting = *link.active.borrow();
+        self.replica = Some(Replica {
+            active: link.active,
+            replicated: link.replicated.map(UnboundedReceiverStream::new),
+            acting
+        });
+        self
+    }
+
+    /// Sends our solve time, book size and validation latency to our peers
+    /// after every block.
+    pub fn with_telemetry(mut self, share_telemetry: bool) -> Self {
+        self.share_telemetry = share_telemetry;
+        self
+    }
+
+    pub fn with_config_updates(mut self, updates: broadcast::Receiver<ConfigUpdate>) -> Self {
+        self.config_updates = Some(BroadcastStream::new(updates));
+        self
+    }
+
+    fn on_config_update(&mut self, update: ConfigUpdate) {
+        match update {
+            ConfigUpdate::SubmissionTargets(urls) => {
+                tracing::info!(?urls, "updating submission targets");
+                self.consensus_round_state.set_submission_targets(&urls);
+            }
+            ConfigUpdate::ResumePool(pool) => {
+                if !self.consensus_round_state.resume_pool(&pool) {
+                    tracing::warn!(?pool, "asked to resume a pool that isn't paused");
+                }
+            }
+            ConfigUpdate::ClearSubmission(height) => {
+                match self.consensus_round_state.clear_submission(height) {
+                    Ok(Some(bundle_hash)) => {
+                        tracing::warn!(height, ?bundle_hash, "cleared submitted bundle")
+                    }
+                    Ok(None) => {
+                        tracing::warn!(height, "asked to clear a height we didn't submit for")
+                    }
+                    Err(e) => tracing::error!(err=%e, height, "failed to clear submission")
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_query(&mut self, request: ConsensusRequest) {
+        match request {
+            ConsensusRequest::RoundInfo(tx) => {
+                let shared = self.consensus_round_state.shared_state();
+                let _ = tx.send(ConsensusRoundInfo {
+                    height:                    self.current_height,
+                    phase:                     self.consensus_round_state.phase(),
+                    leader:                    shared.round_leader(),
+                    is_leader:                 shared.i_am_leader(),
+                    pre_proposals:             self.round_stats.pre_proposals,
+                    pre_proposal_aggregations: self.round_stats.pre_proposal_aggregations,
+                    last_proposal:             self.round_stats.last_proposal
+                });
+            }
+            ConsensusRequest::LeaderSchedule(from, count, tx) => {
+                let schedule = self
+                    .leader_selection
+                    .schedule(from, count)
+                    .into_iter()
+                    .map(|(height, leader)| LeaderSlot { height, leader })
+                    .collect();
+                let _ = tx.send(schedule);
+            }
+            ConsensusRequest::ProvisionalWinners(tx) => {
+                let _ = tx.send(self.round_stats.provisional_winners.winners());
+            }
+            ConsensusRequest::FeeLedger(tx) => {
+                let shared = self.consensus_round_state.shared_state();
+                let _ = tx.send(shared.fee_ledger().clone());
+            }
+            ConsensusRequest::ValidatorTelemetry(tx) => {
+                let _ = tx.send(self.telemetry.summaries());
+            }
+            ConsensusRequest::SubmissionTargets(tx) => {
+                let shared = self.consensus_round_state.shared_state();
+                let _ = tx.send(shared.submission_targets());
+            }
+            ConsensusRequest::SubscribeAuctionResults(tx) => {
+                let _ = tx.send(self.auction_results.subscribe());
+            }
+            ConsensusRequest::SimulateOrder(order, tx) => {
+                let shadow_match = self
+                    .consensus_round_state
+                    .shared_state()
+                    .shadow_match(*order);
+                tokio::spawn(async move {
+                    let _ = tx.send(shadow_match.await);
+                });
+            }
+            ConsensusRequest::Shutdown(tx) => {
+                tracing::info!(phase=?self.consensus_round_state.phase(), "shutting down consensus");
+                self.shutdown = Some(tx);
+            }
+        }
+    }
+
+    /// A round is only finished on shutdown if our bundle is already on its way
+    /// on chain, so that the proposal still reaches the other validators.
+    /// Otherwise the round is abdicated, nothing else is signed and the other
+    /// validators carry on without us.
+    fn poll_shutdown(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        if self.consensus_round_state.submission_in_flight() {
+            while let Poll::Ready(Some(msg)) = self.consensus_round_state.poll_next_unpin(cx) {
+                self.on_round_event(msg);
+            }
+        }
+        if self.consensus_round_state.submission_in_flight() {
+            return Poll::Pending
+        }
+
+        if let Some(tx) = self.shutdown.take() {
+            let _ = tx.send(());
+        }
+        Poll::Ready(())
+    }
+
     fn on_blockchain_state(&mut self, notification: CanonStateNotification, waker: Waker) {
         tracing::info!("got new block_chain state");
         let new_block = notification.tip();
@@ -107,15 +368,50 @@ where
             .unwrap();
         tracing::info!(?round_leader, "selected new round leader");
 
-        self.consensus_round_state
-            .reset_round(self.current_height, round_leader);
+        self.on_round_end();
+        if let Some(replica) = self.replica.as_mut() {
+            replica.acting = *replica.active.borrow();
+        }
+        self.consensus_round_state.reset_round(
+            self.current_height,
+            new_block.timestamp(),
+            round_leader
+        );
         self.broadcasted_messages.clear();
+        self.round_stats = RoundStats {
+            last_proposal: self.round_stats.last_proposal.take(),
+            ..Default::default()
+        };
 
         self.block_sync
             .sign_off_on_block(MODULE_NAME, self.current_height, Some(waker));
     }
 
+    /// Records how we did in the round that is ending, and shares it if we
+    /// were asked to.
+    fn on_round_end(&mut self) {
+        let telemetry = self.consensus_round_state.round_telemetry();
+        let us = self.consensus_round_state.shared_state().validator_id();
+        self.telemetry.record(us, telemetry);
+
+        if self.share_telemetry {
+            self.network
+                .broadcast_message(StromMessage::ValidatorTelemetry(telemetry));
+        }
+    }
+
     fn on_network_event(&mut self, event: StromConsensusEvent) {
+        // telemetry is about a block that is over, and is no part of the round
+        if let StromConsensusEvent::Telemetry(peer_id, telemetry) = event {
+            self.telemetry.record(peer_id, telemetry);
+            return
+        }
+        // answered here, the round only cares about the approvals of its own bundle
+        if let StromConsensusEvent::ApprovalRequest(peer_id, request) = event {
+            self.on_approval_request(peer_id, request);
+            return
+        }
+
         if self.current_height != event.block_height() {
             tracing::warn!(
                 event_block_height=%event.block_height(),
@@ -126,20 +422,85 @@ where
             return
         }
 
+        match &event {
+            StromConsensusEvent::PreProposal(_, pre_proposal) => {
+                self.round_stats.pre_proposals += 1;
+                self.round_stats
+                    .provisional_winners
+                    .on_pre_proposal(pre_proposal);
+            }
+            StromConsensusEvent::PreProposalAgg(..) => {
+                self.round_stats.pre_proposal_aggregations += 1
+            }
+            StromConsensusEvent::Proposal(_, proposal) => self.on_proposal(proposal),
+            // handled above, or only by the round
+            StromConsensusEvent::Telemetry(..)
+            | StromConsensusEvent::ApprovalRequest(..)
+            | StromConsensusEvent::Approval(..) => {}
+        }
+
         self.consensus_round_state.handle_message(event);
     }
 
+    fn on_approval_request(&mut self, peer_id: PeerId, request: SubmissionApprovalRequest) {
+        match self
+            .consensus_round_state
+            .approve_submission(peer_id, &request)
+        {
+            Ok(approval) => {
+                tracing::info!(%peer_id, tx_hash=?approval.tx_hash, "approved submission");
+                self.network
+                    .send_message(peer_id, StromMessage::SubmissionApproval(approval))
+            }
+            Err(e) => tracing::warn!(
+                %peer_id,
+                tx_hash=?request.tx_hash(),
+                err=%e,
+                "refused to approve submission"
+            )
+        }
+    }
+
+    /// Publishes the auction results of a proposal the first time we see it.
+    fn on_proposal(&mut self, proposal: &Proposal) {
+        let seen = Some((proposal.block_height, proposal.hash()));
+        if self.round_stats.last_proposal == seen {
+            return
+        }
+
+        self.round_stats.last_proposal = seen;
+        // only proposals that landed are propagated
+        self.consensus_round_state.on_proposal_landed(proposal);
+        // nobody listening is fine
+        let _ = self
+            .auction_results
+            .send(AuctionResult::from_proposal(proposal));
+    }
+
     fn on_round_event(&mut self, event: ConsensusMessage) {
         match event {
             ConsensusMessage::PropagateProposal(p) => {
+                self.on_proposal(&p);
                 self.network.broadcast_message(StromMessage::Propose(p))
             }
             ConsensusMessage::PropagatePreProposal(p) => {
+                self.round_stats.provisional_winners.on_pre_proposal(&p);
                 self.network.broadcast_message(StromMessage::PrePropose(p))
             }
             ConsensusMessage::PropagatePreProposalAgg(p) => self
                 .network
-                .broadcast_message(StromMessage::PreProposeAgg(p))
+                .broadcast_message(StromMessage::PreProposeAgg(p)),
+            ConsensusMessage::RequestSubmissionApproval { approvers, request } => {
+                for approver in approvers {
+                    self.network.send_message(
+                        approver,
+                        StromMessage::SubmissionApprovalRequest(request.clone())
+                    )
+                }
+            }
+            ConsensusMessage::RequestOrders { peer, hashes } => self
+                .network
+                .send_message(peer, StromMessage::GetPooledOrders(hashes))
         }
     }
 }
@@ -162,6 +523,45 @@ where
             };
         }
 
+        while let Some(Poll::Ready(Some(request))) = this
+            .queries
+            .as_mut()
+            .map(|queries| queries.poll_next_unpin(cx))
+        {
+            this.on_query(request);
+        }
+
+        while let Some(Poll::Ready(Some(update))) = this
+            .config_updates
+            .as_mut()
+            .map(|updates| updates.poll_next_unpin(cx))
+        {
+            match update {
+                Ok(update) => this.on_config_update(update),
+                Err(e) => tracing::warn!(%e, "missed config updates")
+            }
+        }
+
+        if this.shutdown.is_some() {
+            return this.poll_shutdown(cx)
+        }
+
+        if let Some(replica) = this.replica.as_mut() {
+            while let Some(Poll::Ready(Some(record))) = replica
+                .replicated
+                .as_mut()
+                .map(|replicated| replicated.poll_next_unpin(cx))
+            {
+                this.consensus_round_state.replicate_signature(record);
+            }
+
+            if !replica.acting() {
+                // the other replica of the validator takes part in the round
+                while let Poll::Ready(Some(_)) = this.strom_consensus_event.poll_next_unpin(cx) {}
+                return Poll::Pending
+            }
+        }
+
         if this.block_sync.can_operate() {
             while let Poll::Ready(Some(msg)) = this.strom_consensus_event.poll_next_unpin(cx) {
                 this.on_network_event(msg);
@@ -0,0 +1,214 @@
+//! Warm standby of a validator.
+//!
+//! A standby runs with the key of its primary but doesn't take part in
+//! consensus. The primary streams it the orders it holds and the consensus
+//! messages it signs over a dedicated tcp connection, so that the standby can
+//! take over within a block or two once the primary is gone.
+//!
+//! Both running at once would have the validator sign twice, so taking over is
+//! fenced on both sides:
+//! - the standby acks every heartbeat, and the primary only takes part in
+//!   consensus while it holds a lease renewed by those acks. A standby that
+//!   goes away closes the connection, which releases the primary from the
+//!   lease.
+//! - the standby only takes over once it hasn't heard from the primary for
+//!   longer than the lease, and a last handshake with the primary isn't
+//!   answered. An answer means the primary is alive and the standby carries on
+//!   following it.
+//! - once it took over, the standby tells the primary, which then never acts
+//!   again. It has to be restarted as the standby of the new primary.
+//!
+//! The consensus messages the primary signed are replayed into the signing
+//! guard of the standby, so that it can't sign anything conflicting for the
+//! heights the primary already took part in.
+
+mod primary;
+mod standby;
+
+use std::time::{Duration, Instant};
+
+use alloy::primitives::{BlockNumber, B256};
+use angstrom_types::{primitive::PeerId, sol_bindings::grouped_orders::AllOrders};
+pub use primary::*;
+use serde::{Deserialize, Serialize};
+pub use standby::*;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpStream
+    },
+    sync::{mpsc::UnboundedReceiver, watch}
+};
+
+use crate::SignedRecord;
+
+/// How the primary and its standby watch each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplicaConfig {
+    pub heartbeat_interval: Duration,
+    /// how long the primary keeps acting without acks from its standby
+    pub lease:              Duration,
+    /// how long the standby waits without heartbeats before trying to take
+    /// over. Longer than the lease, so that the primary stopped by then
+    pub failover_timeout:   Duration,
+    /// how long the primary has to answer the last handshake
+    pub handshake_timeout:  Duration
+}
+
+impl Default for ReplicaConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_secs(1),
+            lease:              Duration::from_secs(4),
+            failover_timeout:   Duration::from_secs(8),
+            handshake_timeout:  Duration::from_secs(2)
+        }
+    }
+}
+
+/// What the primary and the standby tell each other, one json object per line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ReplicaMessage {
+    /// The standby introduces itself. `take_over` is set when it lost the
+    /// primary and will take over unless it is answered.
+    Hello {
+        validator: PeerId,
+        take_over: Option<BlockNumber>
+    },
+    /// The primary answers, it is alive and the standby follows it again.
+    Welcome {
+        block_height: BlockNumber
+    },
+    Heartbeat {
+        block_height: BlockNumber
+    },
+    Ack {
+        block_height: BlockNumber
+    },
+    /// orders the primary took in
+    Orders {
+        orders: Vec<AllOrders>
+    },
+    /// orders the primary dropped without them being filled
+    DroppedOrders {
+        order_hashes: Vec<B256>
+    },
+    /// consensus messages the primary signed
+    Signed {
+        records: Vec<SignedRecord>
+    },
+    /// The standby took over, the primary must not act anymore.
+    Promoted {
+        block_height: BlockNumber
+    }
+}
+
+/// What a [`ConsensusManager`](crate::ConsensusManager) needs from the replica
+/// task of its node.
+#[derive(Debug)]
+pub struct ReplicaLink {
+    /// whether the node takes part in consensus
+    pub active:     watch::Receiver<bool>,
+    /// what the primary signed, for a standby
+    pub replicated: Option<UnboundedReceiver<SignedRecord>>
+}
+
+/// Whether the primary may act, given when it last heard from its standby.
+#[derive(Debug, Default)]
+pub struct Lease {
+    /// set while a standby follows us
+    acked:   Option<Instant>,
+    /// set once the standby took over
+    revoked: bool
+}
+
+impl Lease {
+    /// The standby acked a heartbeat, or just connected.
+    pub fn renew(&mut self, now: Instant) {
+        self.acked = Some(now);
+    }
+
+    /// The standby closed its connection, we no longer have one.
+    pub fn release(&mut self) {
+        self.acked = None;
+    }
+
+    /// The standby took over.
+    pub fn revoke(&mut self) {
+        self.revoked = true;
+    }
+
+    pub fn is_held(&self, now: Instant, lease: Duration) -> bool {
+        !self.revoked
+            && self
+                .acked
+                .map_or(true, |acked| now.saturating_duration_since(acked) < lease)
+    }
+}
+
+/// One end of the connection between the primary and the standby.
+#[derive(Debug)]
+pub(crate) struct ReplicaSession {
+    lines:  Lines<BufReader<OwnedReadHalf>>,
+    writer: OwnedWriteHalf
+}
+
+impl ReplicaSession {
+    pub(crate) fn new(stream: TcpStream) -> Self {
+        let (reader, writer) = stream.into_split();
+        Self { lines: BufReader::new(reader).lines(), writer }
+    }
+
+    pub(crate) async fn send(&mut self, message: &ReplicaMessage) -> std::io::Result<()> {
+        let mut line = serde_json::to_vec(message).expect("replica messages are serializable");
+        line.push(b'\n');
+        self.writer.write_all(&line).await
+    }
+
+    /// The next message, none once the other side closed the connection.
+    pub(crate) async fn recv(&mut self) -> std::io::Result<Option<ReplicaMessage>> {
+        let Some(line) = self.lines.next_line().await? else { return Ok(None) };
+        serde_json::from_str(&line)
+            .map(Some)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_lease_needs_acks_while_a_standby_follows() {
+        let lease = Duration::from_secs(4);
+        let start = Instant::now();
+        let mut state = Lease::default();
+        // nobody follows us yet
+        assert!(state.is_held(start + Duration::from_secs(60), lease));
+
+        state.renew(start);
+        assert!(state.is_held(start + Duration::from_secs(3), lease));
+        assert!(!state.is_held(start + Duration::from_secs(5), lease));
+
+        state.renew(start + Duration::from_secs(5));
+        assert!(state.is_held(start + Duration::from_secs(6), lease));
+
+        // the standby went away
+        state.release();
+        assert!(state.is_held(start + Duration::from_secs(60), lease));
+
+        state.revoke();
+        state.renew(start + Duration::from_secs(60));
+        assert!(!state.is_held(start + Duration::from_secs(60), lease));
+    }
+
+    #[test]
+    fn messages_are_tagged_by_type() {
+        let message = ReplicaMessage::Heartbeat { block_height: 7 };
+        let json = serde_json::to_string(&message).unwrap();
+        assert_eq!(json, r#"{"type":"heartbeat","blockHeight":7}"#);
+        assert_eq!(serde_json::from_str::<ReplicaMessage>(&json).unwrap(), message);
+    }
+}
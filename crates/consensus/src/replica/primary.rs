@@ -0,0 +1,214 @@
+use std::{collections::HashMap, time::Instant};
+
+use alloy::primitives::BlockNumber;
+use angstrom_network::pool_manager::PoolHandle;
+use angstrom_types::primitive::PeerId;
+use futures::StreamExt;
+use order_pool::{OrderPoolHandle, PoolManagerUpdate};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::{mpsc::UnboundedReceiver, watch}
+};
+
+use super::{Lease, ReplicaConfig, ReplicaLink, ReplicaMessage, ReplicaSession};
+use crate::{ConsensusHandle, SignedMessageKind, SignedRecord, SIGNING_GUARD_RETENTION};
+
+/// Streams the state of a validator to its standby, and keeps the validator
+/// from acting while it could have been taken over.
+pub struct ReplicaPrimary<Consensus> {
+    config:    ReplicaConfig,
+    validator: PeerId,
+    pool:      PoolHandle,
+    consensus: Consensus,
+    /// what the signing guard of our consensus records
+    signed:    UnboundedReceiver<SignedRecord>,
+    /// the records within the retention window, sent to a standby that
+    /// connects
+    records:   HashMap<(BlockNumber, SignedMessageKind), SignedRecord>,
+    lease:     Lease,
+    active:    watch::Sender<bool>
+}
+
+impl<Consensus: ConsensusHandle> ReplicaPrimary<Consensus> {
+    /// `records` are those already in the signing guard, `signed` receives
+    /// the ones it makes from now on, see
+    /// [`SigningGuard::with_replication`](crate::SigningGuard::with_replication).
+    pub fn new(
+        config: ReplicaConfig,
+        validator: PeerId,
+        pool: PoolHandle,
+        consensus: Consensus,
+        records: Vec<SignedRecord>,
+        signed: UnboundedReceiver<SignedRecord>
+    ) -> (Self, ReplicaLink) {
+        let (active, active_rx) = watch::channel(true);
+        let records = records
+            .into_iter()
+            .map(|record| ((record.height, record.kind), record))
+            .collect();
+
+        let this = Self {
+            config,
+            validator,
+            pool,
+            consensus,
+            signed,
+            records,
+            lease: Lease::default(),
+            active
+        };
+        (this, ReplicaLink { active: active_rx, replicated: None })
+    }
+
+    pub async fn run(mut self, listener: TcpListener) {
+        let mut updates = self.pool.subscribe_orders();
+        let mut heartbeat = tokio::time::interval(self.config.heartbeat_interval);
+        let mut session: Option<ReplicaSession> = None;
+
+        loop {
+            let outgoing = tokio::select! {
+                accepted = listener.accept() => {
+                    match accepted {
+                        Ok((stream, addr)) => match self.open_session(stream).await {
+                            Ok(new_session) => {
+                                tracing::info!(%addr, "standby connected");
+                                // a standby reconnecting replaces its old connection
+                                session = Some(new_session);
+                                self.lease.renew(Instant::now());
+                            }
+                            Err(e) => tracing::warn!(%addr, err=%e, "refused standby"),
+                        },
+                        Err(e) => tracing::warn!(err=%e, "failed to accept standby")
+                    }
+                    None
+                }
+                message = recv(&mut session) => {
+                    match message {
+                        Ok(Some(ReplicaMessage::Ack { .. })) => self.lease.renew(Instant::now()),
+                        Ok(Some(ReplicaMessage::Promoted { block_height })) => {
+                            self.demote(block_height)
+                        }
+                        Ok(Some(message)) => {
+                            tracing::warn!(?message, "unexpected message from standby")
+                        }
+                        Ok(None) => {
+                            tracing::warn!("standby closed the connection");
+                            session = None;
+                            self.lease.release();
+                        }
+                        Err(e) => {
+                            tracing::warn!(err=%e, "lost the standby");
+                            session = None;
+                        }
+                    }
+                    None
+                }
+                Some(update) = updates.next() => match update {
+                    Ok(update) => order_message(update),
+                    Err(e) => {
+                        tracing::warn!(err=%e, "standby missed order updates");
+                        None
+                    }
+                },
+                Some(record) = self.signed.recv() => {
+                    self.remember(record);
+                    Some(ReplicaMessage::Signed { records: vec![record] })
+                }
+                _ = heartbeat.tick() => {
+                    let held = self.lease.is_held(Instant::now(), self.config.lease);
+                    self.active.send_if_modified(|active| {
+                        if *active != held {
+                            tracing::warn!(held, "standby lease changed");
+                        }
+                        std::mem::replace(active, held) != held
+                    });
+                    Some(ReplicaMessage::Heartbeat { block_height: self.block_height().await })
+                }
+            };
+
+            let (Some(message), Some(current)) = (outgoing, session.as_mut()) else { continue };
+            if let Err(e) = current.send(&message).await {
+                // the lease runs out on its own if the standby is really gone
+                tracing::warn!(err=%e, "failed to stream to the standby");
+                session = None;
+            }
+        }
+    }
+
+    /// Takes the hello of a standby and brings it up to date. Answering a
+    /// standby that wants to take over tells it that we are alive.
+    async fn open_session(&mut self, stream: TcpStream) -> eyre::Result<ReplicaSession> {
+        let mut session = ReplicaSession::new(stream);
+        let hello = tokio::time::timeout(self.config.handshake_timeout, session.recv()).await??;
+        match hello {
+            Some(ReplicaMessage::Hello { validator, take_over }) => {
+                if validator != self.validator {
+                    eyre::bail!("standby of another validator {validator}")
+                }
+                if let Some(height) = take_over {
+                    tracing::warn!(height, "standby lost us, keeping it from taking over");
+                }
+            }
+            Some(ReplicaMessage::Promoted { block_height }) => {
+                self.demote(block_height);
+                eyre::bail!("standby already took over")
+            }
+            other => eyre::bail!("expected a hello, got {other:?}")
+        }
+
+        let block_height = self.block_height().await;
+        session
+            .send(&ReplicaMessage::Welcome { block_height })
+            .await?;
+        let orders = self.pool.all_orders().await;
+        session.send(&ReplicaMessage::Orders { orders }).await?;
+        let records = self.records.values().copied().collect();
+        session.send(&ReplicaMessage::Signed { records }).await?;
+
+        Ok(session)
+    }
+
+    fn demote(&mut self, block_height: BlockNumber) {
+        tracing::error!(block_height, "standby took over, no longer taking part in consensus");
+        self.lease.revoke();
+        self.active.send_replace(false);
+    }
+
+    fn remember(&mut self, record: SignedRecord) {
+        self.records.insert((record.height, record.kind), record);
+        let oldest = record.height.saturating_sub(SIGNING_GUARD_RETENTION);
+        self.records.retain(|(height, _), _| *height >= oldest);
+    }
+
+    async fn block_height(&self) -> BlockNumber {
+        self.consensus
+            .round_info()
+            .await
+            .map(|info| info.height)
+            .unwrap_or_default()
+    }
+}
+
+async fn recv(session: &mut Option<ReplicaSession>) -> std::io::Result<Option<ReplicaMessage>> {
+    match session {
+        Some(session) => session.recv().await,
+        None => std::future::pending().await
+    }
+}
+
+/// The standby follows the chain itself, it only needs to hear about the orders
+/// that come and go otherwise.
+fn order_message(update: PoolManagerUpdate) -> Option<ReplicaMessage> {
+    match update {
+        PoolManagerUpdate::NewOrder(order) | PoolManagerUpdate::UnfilledOrders(order) => {
+            Some(ReplicaMessage::Orders { orders: vec![order.order] })
+        }
+        PoolManagerUpdate::CancelledOrder { order_hash, .. } => {
+            Some(ReplicaMessage::DroppedOrders { order_hashes: vec![order_hash] })
+        }
+        PoolManagerUpdate::ExpiredOrder(order) => {
+            Some(ReplicaMessage::DroppedOrders { order_hashes: vec![order.order_id.hash] })
+        }
+        PoolManagerUpdate::FilledOrder(..) | PoolManagerUpdate::RejectedOrder { .. } => None
+    }
+}
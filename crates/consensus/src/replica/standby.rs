@@ -0,0 +1,179 @@
+use std::{net::SocketAddr, time::Instant};
+
+use alloy::primitives::BlockNumber;
+use angstrom_network::pool_manager::PoolHandle;
+use angstrom_types::{orders::OrderOrigin, primitive::PeerId};
+use order_pool::OrderPoolHandle;
+use tokio::{
+    net::TcpStream,
+    sync::{
+        mpsc::{unbounded_channel, UnboundedSender},
+        watch
+    }
+};
+
+use super::{ReplicaConfig, ReplicaLink, ReplicaMessage, ReplicaSession};
+use crate::SignedRecord;
+
+/// Follows the primary of a validator, and takes over its duties once the
+/// primary is gone.
+pub struct ReplicaStandby {
+    config:         ReplicaConfig,
+    validator:      PeerId,
+    primary:        SocketAddr,
+    pool:           PoolHandle,
+    /// into the signing guard of our consensus
+    replicated:     UnboundedSender<SignedRecord>,
+    active:         watch::Sender<bool>,
+    /// when the primary was last heard of and its height then, set once we
+    /// followed it
+    last_heartbeat: Option<(Instant, BlockNumber)>
+}
+
+impl ReplicaStandby {
+    pub fn new(
+        config: ReplicaConfig,
+        validator: PeerId,
+        primary: SocketAddr,
+        pool: PoolHandle
+    ) -> (Self, ReplicaLink) {
+        let (active, active_rx) = watch::channel(false);
+        let (replicated, replicated_rx) = unbounded_channel();
+
+        let this =
+            Self { config, validator, primary, pool, replicated, active, last_heartbeat: None };
+        (this, ReplicaLink { active: active_rx, replicated: Some(replicated_rx) })
+    }
+
+    pub async fn run(mut self) {
+        // connections the primary went silent on are kept open until we take over, the
+        // primary would take them closing for us going away
+        let mut silent = vec![];
+
+        loop {
+            let take_over = self.lost_primary().then(|| self.block_height());
+            match self.connect(take_over, &mut silent).await {
+                Ok(session) => {
+                    silent.clear();
+                    if let Some(session) = self.follow(session).await {
+                        tracing::warn!(primary=%self.primary, "primary went silent");
+                        silent.push(session);
+                    }
+                }
+                Err(e) if take_over.is_some() => {
+                    tracing::warn!(primary=%self.primary, err=%e, "primary is gone");
+                    return self.promote(silent).await
+                }
+                Err(e) => {
+                    tracing::warn!(primary=%self.primary, err=%e, "failed to reach the primary");
+                    tokio::time::sleep(self.config.heartbeat_interval).await;
+                }
+            }
+        }
+    }
+
+    fn lost_primary(&self) -> bool {
+        self.last_heartbeat
+            .is_some_and(|(at, _)| at.elapsed() >= self.config.failover_timeout)
+    }
+
+    fn block_height(&self) -> BlockNumber {
+        self.last_heartbeat
+            .map(|(_, height)| height)
+            .unwrap_or_default()
+    }
+
+    /// Says hello to the primary. A connection the primary doesn't answer on is
+    /// added to `silent`, it could still read it later on.
+    async fn connect(
+        &mut self,
+        take_over: Option<BlockNumber>,
+        silent: &mut Vec<ReplicaSession>
+    ) -> eyre::Result<ReplicaSession> {
+        let timeout = self.config.handshake_timeout;
+        let stream = tokio::time::timeout(timeout, TcpStream::connect(self.primary)).await??;
+        let mut session = ReplicaSession::new(stream);
+        session
+            .send(&ReplicaMessage::Hello { validator: self.validator, take_over })
+            .await?;
+
+        let block_height = match tokio::time::timeout(timeout, session.recv()).await {
+            Ok(Ok(Some(ReplicaMessage::Welcome { block_height }))) => block_height,
+            Ok(Ok(other)) => eyre::bail!("expected a welcome, got {other:?}"),
+            Ok(Err(e)) => return Err(e.into()),
+            Err(_) => {
+                silent.push(session);
+                eyre::bail!("the primary didn't answer")
+            }
+        };
+
+        if take_over.is_some() {
+            tracing::info!(primary=%self.primary, "primary is alive, not taking over");
+        }
+        self.last_heartbeat = Some((Instant::now(), block_height));
+
+        Ok(session)
+    }
+
+    /// Applies what the primary streams until it closes the connection, or
+    /// goes silent for too long, in which case the connection is handed back.
+    async fn follow(&mut self, mut session: ReplicaSession) -> Option<ReplicaSession> {
+        loop {
+            let silence = self
+                .last_heartbeat
+                .map(|(at, _)| self.config.failover_timeout.saturating_sub(at.elapsed()))
+                .unwrap_or(self.config.failover_timeout);
+
+            let message = match tokio::time::timeout(silence, session.recv()).await {
+                Ok(Ok(Some(message))) => message,
+                Ok(Ok(None)) => return None,
+                Ok(Err(e)) => {
+                    tracing::warn!(err=%e, "lost the primary");
+                    return None
+                }
+                Err(_) => return Some(session)
+            };
+
+            match message {
+                ReplicaMessage::Heartbeat { block_height } => {
+                    self.last_heartbeat = Some((Instant::now(), block_height));
+                    if session
+                        .send(&ReplicaMessage::Ack { block_height })
+                        .await
+                        .is_err()
+                    {
+                        return None
+                    }
+                }
+                ReplicaMessage::Orders { orders } => {
+                    futures::future::join_all(
+                        orders
+                            .into_iter()
+                            .map(|order| self.pool.new_order(OrderOrigin::Local, order))
+                    )
+                    .await;
+                }
+                ReplicaMessage::DroppedOrders { order_hashes } => {
+                    let _ = self.pool.drop_orders(order_hashes).await;
+                }
+                ReplicaMessage::Signed { records } => records.into_iter().for_each(|record| {
+                    let _ = self.replicated.send(record);
+                }),
+                message => tracing::warn!(?message, "unexpected message from the primary")
+            }
+        }
+    }
+
+    /// Takes over, telling the primary in case it is still around.
+    async fn promote(self, silent: Vec<ReplicaSession>) {
+        let block_height = self.block_height();
+        tracing::error!(primary=%self.primary, block_height, "taking over from the primary");
+        self.active.send_replace(true);
+
+        for mut session in silent {
+            let _ = session
+                .send(&ReplicaMessage::Promoted { block_height })
+                .await;
+        }
+    }
+}
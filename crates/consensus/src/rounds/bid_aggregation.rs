@@ -12,7 +12,8 @@ use matching_engine::MatchingEngineHandle;
 
 use super::{
     finalization::FinalizationState, pre_proposal::PreProposalState,
-    preproposal_wait_trigger::PreProposalWaitTrigger, ConsensusState, SharedRoundState
+    preproposal_wait_trigger::PreProposalWaitTrigger, ConsensusPhase, ConsensusState,
+    SharedRoundState
 };
 
 /// BidAggregationState
@@ -55,6 +56,10 @@ where
     P: Provider + 'static,
     Matching: MatchingEngineHandle
 {
+    fn phase(&self) -> ConsensusPhase {
+        ConsensusPhase::BidAggregation
+    }
+
     fn on_consensus_message(
         &mut self,
         handles: &mut SharedRoundState<P, Matching>,
@@ -82,6 +87,11 @@ where
                     self.waker.as_ref().inspect(|w| w.wake_by_ref());
                 }
             }
+            // telemetry and approval requests are taken care of by the manager, there
+            // is no submission to approve yet
+            StromConsensusEvent::Telemetry(..)
+            | StromConsensusEvent::ApprovalRequest(..)
+            | StromConsensusEvent::Approval(..) => {}
         }
     }
 
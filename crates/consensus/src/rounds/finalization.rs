@@ -1,16 +1,34 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     pin::Pin,
     task::{Context, Poll, Waker}
 };
 
-use alloy::providers::Provider;
+use alloy::{
+    primitives::{Address, BlockNumber, B256},
+    providers::Provider
+};
 use angstrom_network::manager::StromConsensusEvent;
-use angstrom_types::consensus::Proposal;
+use angstrom_types::{
+    consensus::{PreProposal, Proposal},
+    contract_payloads::angstrom::{AngstromBundle, BundleFees},
+    matching::uniswap::PoolSnapshot,
+    orders::{PoolSolution, SortStrategy},
+    primitive::{PeerId, PoolId}
+};
 use futures::{Future, FutureExt};
-use matching_engine::MatchingEngineHandle;
+use matching_engine::{
+    book::OrderBook,
+    build_book,
+    verification::{solution_surplus, verify_solution, Surplus},
+    MatchingEngineHandle
+};
 
-use super::{ConsensusState, SharedRoundState};
+use super::{
+    order_fetch::{OrderFetch, ORDER_FETCH_FALLBACK_PEERS, ORDER_FETCH_TIMEOUT},
+    ConsensusMessage, ConsensusPhase, ConsensusState, SharedRoundState
+};
+use crate::WatchtowerAlert;
 
 /// The finalization state.
 ///
@@ -19,8 +37,14 @@ use super::{ConsensusState, SharedRoundState};
 /// off) where we will wait for proposals to be propagated (consensus states you
 /// have a day max). in which they will be verified and the round will
 /// officially close.
+///
+/// The orders the proposal fills that we don't have are fetched and validated
+/// first, the proposal isn't verified without them.
 pub struct FinalizationState {
     verification_future: Pin<Box<dyn Future<Output = bool> + Send>>,
+    order_fetch:         Option<OrderFetch>,
+    height:              BlockNumber,
+    leader:              PeerId,
     completed:           bool
 }
 
@@ -40,10 +64,61 @@ impl FinalizationState {
             .into_iter()
             .collect::<HashSet<_>>();
 
+        // the books the proposal was solved from, to judge solutions that differ
+        // from ours. How they are sorted doesn't matter for that
+        let snapshot = handles.fetch_pool_snapshot();
+        let books = proposal_books(&proposal, &snapshot);
+        // the fees we charge, to recompute what the proposal's orders are charged
+        let fees = BundleFees::from_registry(
+            &handles.pool_registry,
+            snapshot.keys(),
+            handles.surplus_distribution
+        );
+        // the orders we take to be eligible, from the same pre-proposals
+        let book_commitment = handles.book_commitment(preproposal.clone());
+        let surplus_policy = handles.surplus_policy;
+        let anomalies = handles.anomalies.clone();
+        let (height, leader) = (proposal.block_height, proposal.source);
+
         let future = handles
             .matching_engine_output(preproposal)
             .map(move |output| {
-                let (solution, _) = output.unwrap();
+                let (solution, gas_details) = output.unwrap();
+
+                let disagreements = book_commitment.disagreements(&proposal.book_commitment);
+                if !disagreements.is_empty() {
+                    tracing::error!(
+                        pools=?disagreements,
+                        "Violation DETECTED. proposal was solved from other orders than eligible"
+                    );
+                    let _ = anomalies.send(WatchtowerAlert::BookMismatch {
+                        height: proposal.block_height,
+                        leader: proposal.source,
+                        pools:  disagreements
+                    });
+                    return false
+                }
+
+                if let Err(violation) = proposal.check_ucps() {
+                    tracing::error!(
+                        %violation,
+                        "Violation DETECTED. proposal ucp doesn't clear its fills"
+                    );
+                    return false
+                }
+
+                // the bundle the leader submits charges the orders the same surplus
+                // fees, which have to fund the rewards it pays out
+                if let Err(e) =
+                    AngstromBundle::from_proposal(&proposal, gas_details, &snapshot, &fees)
+                        .and_then(|bundle| Ok(bundle.verify_rewards()?))
+                {
+                    tracing::error!(
+                        err=%e,
+                        "Violation DETECTED. proposal's surplus charges don't fund its rewards"
+                    );
+                    return false
+                }
 
                 let mut proposal_solution = proposal.solutions.clone();
                 proposal_solution.sort();
@@ -51,14 +126,57 @@ impl FinalizationState {
                 let mut verification_solution = solution;
                 verification_solution.sort();
 
-                if !proposal_solution
-                    .into_iter()
-                    .zip(verification_solution)
-                    .all(|(p, v)| p == v)
+                if proposal_solution == verification_solution {
+                    return true
+                }
+
+                // a different solver can come to a different, still valid solution. It
+                // is taken as long as it isn't much worse for the users than ours
+                if let Some(report) = proposal_solution
+                    .iter()
+                    .map(|solution| match books.get(&solution.id) {
+                        Some(book) => verify_solution(book, solution),
+                        None => verify_solution(&OrderBook::default(), solution)
+                    })
+                    .find(|report| !report.is_valid())
                 {
                     tracing::error!(
+                        pool=?report.pool,
+                        violations=?report.violations,
                         "Violation DETECTED. in future this will be related to slashing"
                     );
+                    let _ = anomalies.send(WatchtowerAlert::InvalidSolution {
+                        height:     proposal.block_height,
+                        leader:     proposal.source,
+                        pool:       report.pool,
+                        violations: report.violations.iter().map(ToString::to_string).collect()
+                    });
+                    return false
+                }
+
+                // the leader leaves out the pools whose circuit breaker tripped on its
+                // side, the pools it did solve are held to ours
+                verification_solution.retain(|solution| {
+                    proposal_solution
+                        .iter()
+                        .any(|proposed| proposed.id == solution.id)
+                });
+                let proposed = total_surplus(&books, &proposal_solution);
+                let local = total_surplus(&books, &verification_solution);
+                if !surplus_policy.accepts(proposed, local) {
+                    tracing::error!(
+                        ?proposed,
+                        ?local,
+                        "Violation DETECTED. proposal falls short of the local surplus"
+                    );
+                    let _ = anomalies.send(WatchtowerAlert::SurplusShortfall {
+                        height:            proposal.block_height,
+                        leader:            proposal.source,
+                        proposed_vs_limit: proposed.vs_limit,
+                        proposed_vs_amm:   proposed.vs_amm,
+                        local_vs_limit:    local.vs_limit,
+                        local_vs_amm:      local.vs_amm
+                    });
                     return false
                 }
 
@@ -66,18 +184,78 @@ impl FinalizationState {
             })
             .boxed();
 
+        let missing = proposal_orders(&proposal)
+            .filter(|hash| !handles.order_storage.contains_order(hash))
+            .collect::<HashSet<_>>();
+        let order_fetch = (!missing.is_empty()).then(|| {
+            let us = handles.validator_id();
+            let fallbacks = handles
+                .validators
+                .iter()
+                .map(|validator| validator.peer_id)
+                .filter(|peer| *peer != us && *peer != leader)
+                .take(ORDER_FETCH_FALLBACK_PEERS);
+
+            OrderFetch::new(missing, std::iter::once(leader).chain(fallbacks), ORDER_FETCH_TIMEOUT)
+        });
+
         waker.wake_by_ref();
         tracing::info!("finalization");
 
-        Self { verification_future: future, completed: false }
+        Self { verification_future: future, order_fetch, height, leader, completed: false }
     }
 }
 
+/// Hashes of the orders the solutions of `proposal` fill.
+fn proposal_orders(proposal: &Proposal) -> impl Iterator<Item = B256> + '_ {
+    proposal.solutions.iter().flat_map(|solution| {
+        solution
+            .limit
+            .iter()
+            .map(|outcome| outcome.id.hash)
+            .chain(solution.searcher.iter().map(|order| order.order_id.hash))
+    })
+}
+
+/// The books of the pre-proposals `proposal` was solved from, one for every
+/// pool with a snapshot or orders.
+pub(crate) fn proposal_books(
+    proposal: &Proposal,
+    snapshots: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
+) -> HashMap<PoolId, OrderBook> {
+    let mut orders_by_pool = PreProposal::orders_by_pool_id(&proposal.flattened_pre_proposals());
+    snapshots.keys().for_each(|id| {
+        orders_by_pool.entry(*id).or_default();
+    });
+
+    orders_by_pool
+        .into_iter()
+        .map(|(id, orders)| {
+            let amm = snapshots.get(&id).map(|(.., snapshot, _)| snapshot.clone());
+            (id, build_book(id, amm, orders, SortStrategy::Unsorted))
+        })
+        .collect()
+}
+
+pub(crate) fn total_surplus(
+    books: &HashMap<PoolId, OrderBook>,
+    solutions: &[PoolSolution]
+) -> Surplus {
+    solutions
+        .iter()
+        .filter_map(|solution| Some(solution_surplus(books.get(&solution.id)?, solution)))
+        .fold(Surplus::default(), |total, surplus| total + surplus)
+}
+
 impl<P, Matching> ConsensusState<P, Matching> for FinalizationState
 where
     P: Provider + 'static,
     Matching: MatchingEngineHandle
 {
+    fn phase(&self) -> ConsensusPhase {
+        ConsensusPhase::Finalization
+    }
+
     fn on_consensus_message(
         &mut self,
         _: &mut SharedRoundState<P, Matching>,
@@ -89,13 +267,42 @@ where
 
     fn poll_transition(
         &mut self,
-        _: &mut SharedRoundState<P, Matching>,
+        handles: &mut SharedRoundState<P, Matching>,
         cx: &mut Context<'_>
     ) -> Poll<Option<Box<dyn ConsensusState<P, Matching>>>> {
         if self.completed {
             return Poll::Ready(None)
         }
 
+        if let Some(fetch) = self.order_fetch.as_mut() {
+            let order_storage = handles.order_storage.clone();
+            let fetched = fetch.poll_fetch(
+                cx,
+                |hash| order_storage.contains_order(hash),
+                |peer, hashes| {
+                    handles.propagate_message(ConsensusMessage::RequestOrders { peer, hashes })
+                }
+            );
+
+            match fetched {
+                Poll::Ready(Ok(())) => self.order_fetch = None,
+                Poll::Ready(Err(missing)) => {
+                    tracing::error!(
+                        missing = missing.len(),
+                        "Violation DETECTED. proposal fills orders we couldn't get or validate"
+                    );
+                    let _ = handles.anomalies.send(WatchtowerAlert::UnknownOrders {
+                        height: self.height,
+                        source: self.leader,
+                        orders: missing
+                    });
+                    self.completed = true;
+                    return Poll::Ready(None)
+                }
+                Poll::Pending => return Poll::Pending
+            }
+        }
+
         if let Poll::Ready(result) = self.verification_future.poll_unpin(cx) {
             tracing::info!(%result, "consensus result");
             self.completed = true;
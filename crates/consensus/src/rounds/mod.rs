@@ -1,25 +1,36 @@
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{BTreeSet, HashMap, HashSet, VecDeque},
     hash::Hash,
     pin::Pin,
-    sync::Arc,
-    task::{Context, Poll}
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant}
 };
 
 use alloy::{
-    primitives::{Address, BlockNumber, FixedBytes},
-    providers::Provider
+    primitives::{Address, BlockNumber, FixedBytes, B256},
+    providers::Provider,
+    transports::http::reqwest::Url
 };
 use angstrom_metrics::ConsensusMetricsWrapper;
 use angstrom_network::manager::StromConsensusEvent;
 use angstrom_types::{
-    consensus::{PreProposal, PreProposalAggregation, Proposal},
-    contract_payloads::angstrom::{BundleGasDetails, UniswapAngstromRegistry},
+    consensus::{
+        auction_cutoff, BookCommitment, PreProposal, PreProposalAggregation, Proposal,
+        SubmissionApproval, SubmissionApprovalRequest, ValidatorTelemetry
+    },
+    contract_payloads::{
+        angstrom::{BundleGasDetails, FeeLedger, UniswapAngstromRegistry},
+        rewards::SurplusDistribution
+    },
     matching::uniswap::PoolSnapshot,
-    mev_boost::MevBoostProvider,
-    orders::PoolSolution,
-    primitive::{AngstromSigner, PeerId},
-    sol_bindings::grouped_orders::OrderWithStorageData
+    mev_boost::{MevBoostProvider, SubmissionTargetStats},
+    orders::{median_validated_at, OrderSet, PoolSolution},
+    primitive::{AngstromSigner, PeerId, PoolId},
+    sol_bindings::{
+        grouped_orders::{GroupedVanillaOrder, OrderWithStorageData},
+        rpc_orders::TopOfBlockOrder
+    }
 };
 use bid_aggregation::BidAggregationState;
 use futures::{future::BoxFuture, FutureExt, Stream};
@@ -27,16 +38,27 @@ use itertools::Itertools;
 use matching_engine::MatchingEngineHandle;
 use order_pool::order_storage::OrderStorage;
 use preproposal_wait_trigger::{LastRoundInfo, PreProposalWaitTrigger};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
 use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
 
-use crate::AngstromValidator;
+use crate::{
+    check_submission, AngstromValidator, ApprovalRefusal, CircuitBreakerConfig, CircuitBreakers,
+    ContentRules, SignedMessageKind, SignedRecord, SigningGuard, SubmissionApprovalConfig,
+    SubmissionLedger, SubmissionLedgerError, SurplusPolicy, WatchtowerAlert,
+    ANOMALIES_CHANNEL_SIZE
+};
 
 mod bid_aggregation;
 mod finalization;
+mod order_fetch;
 mod pre_proposal;
 mod pre_proposal_aggregation;
 mod preproposal_wait_trigger;
 mod proposal;
+mod shadow_match;
+
+pub(crate) use finalization::{proposal_books, total_surplus};
 
 type PollTransition<P, Matching> = Poll<Option<Box<dyn ConsensusState<P, Matching>>>>;
 
@@ -62,6 +84,26 @@ where
     fn last_round_info(&mut self) -> Option<LastRoundInfo> {
         None
     }
+
+    /// the phase of the round this state represents
+    fn phase(&self) -> ConsensusPhase;
+
+    /// whether we sent a bundle on chain whose outcome we are still waiting
+    /// for. Such a round has to be finished instead of abandoned.
+    fn submission_in_flight(&self) -> bool {
+        false
+    }
+}
+
+/// The phases a consensus round goes through, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConsensusPhase {
+    BidAggregation,
+    PreProposal,
+    PreProposalAggregation,
+    Proposal,
+    Finalization
 }
 
 /// Holds and progresses the consensus state machine
@@ -91,7 +133,7 @@ where
         }
     }
 
-    pub fn reset_round(&mut self, new_block: u64, new_leader: PeerId) {
+    pub fn reset_round(&mut self, new_block: u64, new_timestamp: u64, new_leader: PeerId) {
         // grab the last round info if we were the leader.
         let info = self.current_state.last_round_info();
 
@@ -102,14 +144,153 @@ where
             self.consensus_wait_duration.reset_before_submission();
         }
 
+        // the round is over, its orders can leave the pool again
+        self.shared_state.order_storage.release_pins();
+
         self.shared_state.block_height = new_block;
+        self.shared_state.auction_cutoff = auction_cutoff(new_timestamp);
         self.shared_state.round_leader = new_leader;
+        self.shared_state.record_amm_prices();
 
         self.current_state = Box::new(BidAggregationState::new(
             self.consensus_wait_duration.update_for_new_round(info)
         ));
     }
 
+    pub fn phase(&self) -> ConsensusPhase {
+        self.current_state.phase()
+    }
+
+    pub fn submission_in_flight(&self) -> bool {
+        self.current_state.submission_in_flight()
+    }
+
+    pub fn set_signing_guard(&mut self, signing_guard: SigningGuard) {
+        self.shared_state.signing_guard = signing_guard;
+    }
+
+    pub fn set_submission_ledger(&mut self, submission_ledger: SubmissionLedger) {
+        self.shared_state.submission_ledger = Arc::new(Mutex::new(submission_ledger));
+    }
+
+    /// Allows a bundle to be submitted again for `height`, returning the one
+    /// that was recorded for it.
+    pub fn clear_submission(
+        &mut self,
+        height: BlockNumber
+    ) -> Result<Option<B256>, SubmissionLedgerError> {
+        self.shared_state
+            .submission_ledger
+            .lock()
+            .expect("poisoned")
+            .clear(height)
+    }
+
+    /// Records a message the primary of our validator signed, so that we never
+    /// sign a conflicting one.
+    pub fn replicate_signature(&mut self, record: SignedRecord) {
+        if let Err(e) =
+            self.shared_state
+                .signing_guard
+                .record(record.height, record.kind, record.hash)
+        {
+            tracing::error!(err=%e, "failed to replicate a signature of the primary");
+        }
+    }
+
+    pub fn set_surplus_policy(&mut self, surplus_policy: SurplusPolicy) {
+        self.shared_state.surplus_policy = surplus_policy;
+    }
+
+    pub fn set_content_rules(&mut self, content_rules: ContentRules) {
+        self.shared_state.content_rules = content_rules;
+    }
+
+    pub fn set_surplus_distribution(&mut self, distribution: SurplusDistribution) {
+        self.shared_state.surplus_distribution = distribution;
+    }
+
+    pub fn set_circuit_breakers(&mut self, config: CircuitBreakerConfig) {
+        self.shared_state.circuit_breakers = CircuitBreakers::new(config);
+    }
+
+    /// Restarts the current round with the pre-proposal timing of a chain
+    /// whose blocks are `block_time` apart.
+    pub fn set_block_time(&mut self, block_time: Duration) {
+        self.consensus_wait_duration = self
+            .consensus_wait_duration
+            .clone()
+            .with_block_time(block_time);
+        self.current_state = Box::new(BidAggregationState::new(
+            self.consensus_wait_duration.update_for_new_round(None)
+        ));
+    }
+
+    pub fn set_golf_bundles(&mut self, golf_bundles: bool) {
+        self.shared_state.golf_bundles = golf_bundles;
+    }
+
+    pub fn set_submission_approval(&mut self, config: SubmissionApprovalConfig) {
+        self.shared_state.submission_approval = Some(config);
+    }
+
+    /// Co-signs the settlement transaction of the round leader, see
+    /// [`crate::check_submission`].
+    pub fn approve_submission(
+        &mut self,
+        peer_id: PeerId,
+        request: &SubmissionApprovalRequest
+    ) -> Result<SubmissionApproval, ApprovalRefusal> {
+        self.shared_state.approve_submission(peer_id, request)
+    }
+
+    pub fn subscribe_anomalies(&self) -> broadcast::Receiver<WatchtowerAlert> {
+        self.shared_state.anomalies.subscribe()
+    }
+
+    /// Resumes matching for a pool whose circuit breaker tripped. Returns false
+    /// if it wasn't paused.
+    pub fn resume_pool(&mut self, pool: &PoolId) -> bool {
+        self.shared_state.circuit_breakers.resume(pool)
+    }
+
+    /// Feeds the outcome of a proposal that landed on chain to the circuit
+    /// breakers.
+    pub fn on_proposal_landed(&mut self, proposal: &Proposal) {
+        for solution in &proposal.solutions {
+            self.shared_state
+                .circuit_breakers
+                .check_ucp(solution.id, solution.ucp);
+            self.shared_state
+                .circuit_breakers
+                .on_settlement(solution.id, true);
+        }
+    }
+
+    /// Submissions already on their way keep going to the old endpoints.
+    pub fn set_submission_targets(&mut self, urls: &[Url]) {
+        self.shared_state.provider = Arc::new(self.shared_state.provider.with_urls(urls));
+    }
+
+    pub fn shared_state(&self) -> &SharedRoundState<P, Matching> {
+        &self.shared_state
+    }
+
+    /// How we did in the round that is ending. The solve time is only known
+    /// if we got to run the matching engine.
+    pub fn round_telemetry(&self) -> ValidatorTelemetry {
+        let shared = &self.shared_state;
+        let solve_time = shared.solve_time.lock().expect("poisoned").take();
+        let validation_latency = shared.order_storage.take_validation_latency();
+
+        ValidatorTelemetry {
+            block_height:          shared.block_height,
+            solve_time_ms:         solve_time.map(|time| time.as_millis() as u64),
+            book_size:             shared.order_storage.order_count() as u64,
+            validation_latency_ms: validation_latency.map(|time| time.as_millis() as u64)
+        }
+    }
+
     pub fn handle_message(&mut self, event: StromConsensusEvent) {
         self.current_state
             .on_consensus_message(&mut self.shared_state, event);
@@ -143,18 +324,39 @@ where
 }
 
 pub struct SharedRoundState<P, Matching> {
-    block_height:     BlockNumber,
-    angstrom_address: Address,
-    matching_engine:  Matching,
-    signer:           AngstromSigner,
-    round_leader:     PeerId,
-    validators:       Vec<AngstromValidator>,
-    order_storage:    Arc<OrderStorage>,
-    _metrics:         ConsensusMetricsWrapper,
-    pool_registry:    UniswapAngstromRegistry,
-    uniswap_pools:    SyncedUniswapPools,
-    provider:         Arc<MevBoostProvider<P>>,
-    messages:         VecDeque<ConsensusMessage>
+    block_height:         BlockNumber,
+    /// orders received after this time, in unix milliseconds, don't take part
+    /// in the auction of this round. Unknown until the first new block.
+    auction_cutoff:       u64,
+    angstrom_address:     Address,
+    matching_engine:      Matching,
+    signer:               AngstromSigner,
+    round_leader:         PeerId,
+    validators:           Vec<AngstromValidator>,
+    order_storage:        Arc<OrderStorage>,
+    metrics:              ConsensusMetricsWrapper,
+    pool_registry:        UniswapAngstromRegistry,
+    uniswap_pools:        SyncedUniswapPools,
+    provider:             Arc<MevBoostProvider<P>>,
+    messages:             VecDeque<ConsensusMessage>,
+    signing_guard:        SigningGuard,
+    /// the bundles we broadcast, shared with the submission in flight
+    submission_ledger:    Arc<Mutex<SubmissionLedger>>,
+    surplus_policy:       SurplusPolicy,
+    content_rules:        ContentRules,
+    /// who the surplus of the user orders in our bundles goes to
+    surplus_distribution: SurplusDistribution,
+    /// what the bundles we landed saved for the protocol
+    fee_ledger:           FeeLedger,
+    circuit_breakers:     CircuitBreakers,
+    /// lay our bundles out the cheapest way before submitting them
+    golf_bundles:         bool,
+    /// set when settlement transactions are only broadcast once approved
+    submission_approval:  Option<SubmissionApprovalConfig>,
+    /// misbehavior of the leaders we find when verifying their proposals
+    anomalies:            broadcast::Sender<WatchtowerAlert>,
+    /// how long the matching engine took this round
+    solve_time:           Arc<Mutex<Option<Duration>>>
 }
 
 // contains shared impls
@@ -179,6 +381,7 @@ where
     ) -> Self {
         Self {
             block_height,
+            auction_cutoff: u64::MAX,
             angstrom_address,
             round_leader,
             validators,
@@ -186,10 +389,21 @@ where
             pool_registry,
             uniswap_pools,
             signer,
-            _metrics: metrics,
+            metrics,
             matching_engine,
             messages: VecDeque::new(),
-            provider: Arc::new(provider)
+            provider: Arc::new(provider),
+            signing_guard: SigningGuard::in_memory(),
+            submission_ledger: Arc::new(Mutex::new(SubmissionLedger::in_memory())),
+            surplus_policy: SurplusPolicy::default(),
+            content_rules: ContentRules::default(),
+            surplus_distribution: SurplusDistribution::default(),
+            fee_ledger: FeeLedger::default(),
+            circuit_breakers: CircuitBreakers::default(),
+            golf_bundles: false,
+            submission_approval: None,
+            anomalies: broadcast::channel(ANOMALIES_CHANNEL_SIZE).0,
+            solve_time: Arc::default()
         }
     }
 
@@ -197,69 +411,201 @@ where
         self.messages.push_back(message);
     }
 
-    fn i_am_leader(&self) -> bool {
+    /// Has to be called before a message signed by us leaves the node. Returns
+    /// false if we already released a different message for this slot, in which
+    /// case the message must be dropped.
+    fn guard_signature(&mut self, kind: SignedMessageKind, hash: B256) -> bool {
+        self.signing_guard
+            .record(self.block_height, kind, hash)
+            .inspect_err(|e| tracing::error!(err=%e, ?kind, "refusing to release signed message"))
+            .is_ok()
+    }
+
+    fn approve_submission(
+        &mut self,
+        peer_id: PeerId,
+        request: &SubmissionApprovalRequest
+    ) -> Result<SubmissionApproval, ApprovalRefusal> {
+        if !self
+            .submission_approval
+            .as_ref()
+            .is_some_and(|config| config.is_approver(&self.signer.id()))
+        {
+            return Err(ApprovalRefusal::NotAnApprover)
+        }
+        if request.block_height != self.block_height {
+            return Err(ApprovalRefusal::WrongHeight {
+                requested: request.block_height,
+                current:   self.block_height
+            })
+        }
+        if peer_id != self.round_leader {
+            return Err(ApprovalRefusal::NotTheLeader(peer_id))
+        }
+
+        // the bundle lands in the block after the one the round is for
+        let tx_hash =
+            check_submission(request, self.angstrom_address, self.block_height + 1, |hash| {
+                self.order_storage.contains_order(hash)
+            })?;
+        if !self.guard_signature(SignedMessageKind::SubmissionApproval, tx_hash) {
+            return Err(ApprovalRefusal::AlreadyApproved)
+        }
+
+        Ok(SubmissionApproval::new(self.block_height, tx_hash, &self.signer))
+    }
+
+    pub(crate) fn i_am_leader(&self) -> bool {
         self.round_leader == self.signer.id()
     }
 
+    pub(crate) fn round_leader(&self) -> PeerId {
+        self.round_leader
+    }
+
+    pub(crate) fn validator_id(&self) -> PeerId {
+        self.signer.id()
+    }
+
+    pub(crate) fn fee_ledger(&self) -> &FeeLedger {
+        &self.fee_ledger
+    }
+
+    /// Records the fees of a bundle of ours that landed.
+    fn record_fees(&mut self, fees: &FeeLedger) {
+        self.fee_ledger.merge(fees);
+        for asset in fees.fees.keys() {
+            self.metrics
+                .set_protocol_fees(&asset.to_string(), self.fee_ledger.fee(asset));
+        }
+    }
+
+    /// How the endpoints we submit to have been doing.
+    pub(crate) fn submission_targets(&self) -> Vec<SubmissionTargetStats> {
+        self.provider.target_stats()
+    }
+
+    fn record_submission_targets(&self) {
+        for stats in self.submission_targets() {
+            self.metrics.set_submission_target(
+                &stats.target,
+                stats.success_rate,
+                stats.inclusion_rate,
+                stats.avg_latency_ms
+            );
+        }
+    }
+
     fn two_thirds_of_validation_set(&self) -> usize {
         (2 * self.validators.len()).div_ceil(3)
     }
 
+    /// The validator set in the order pre-proposal aggregations refer to it.
+    fn validator_ids(&self) -> BTreeSet<PeerId> {
+        self.validators.iter().map(|v| v.peer_id).collect()
+    }
+
     fn fetch_pool_snapshot(
         &self
     ) -> HashMap<FixedBytes<32>, (Address, Address, PoolSnapshot, u16)> {
-        self.uniswap_pools
-            .iter()
-            .map(|(key, pool)| {
-                tracing::info!(?key, "getting snapshot");
-                let (token_a, token_b, snapshot) =
-                    pool.read().unwrap().fetch_pool_snapshot().unwrap();
-                let entry = self.pool_registry.get_ang_entry(key).unwrap();
-
-                (*key, (token_a, token_b, snapshot, entry.store_index as u16))
-            })
-            .collect::<HashMap<_, _>>()
+        pool_snapshots(&self.uniswap_pools, &self.pool_registry)
     }
 
-    fn matching_engine_output(
+    /// Samples the AMM price of every pool for the TWAP the circuit breakers
+    /// compare UCPs to.
+    fn record_amm_prices(&mut self) {
+        for (key, pool) in self.uniswap_pools.iter() {
+            let Ok((_, _, snapshot)) = pool.read().unwrap().fetch_pool_snapshot() else { continue };
+            self.circuit_breakers
+                .record_amm_price(*key, snapshot.current_price().as_ray());
+        }
+    }
+
+    /// The orders of the pre-proposals that are eligible for the block: the
+    /// ones a quorum of them had by the auction cutoff. Every validator comes
+    /// to the same set, circuit breakers aren't taken into account.
+    fn eligible_orders(
         &self,
-        pre_proposal_aggregation: HashSet<PreProposalAggregation>
-    ) -> BoxFuture<'static, eyre::Result<(Vec<PoolSolution>, BundleGasDetails)>> {
-        // fetch
+        pre_proposal_aggregation: impl IntoIterator<Item = PreProposalAggregation>
+    ) -> OrderSet<GroupedVanillaOrder, TopOfBlockOrder> {
         let mut limit = Vec::new();
         let mut searcher = Vec::new();
 
         for pre_proposal_agg in pre_proposal_aggregation {
             pre_proposal_agg.pre_proposals.into_iter().for_each(|pre| {
-                limit.extend(pre.limit);
-                searcher.extend(pre.searcher);
+                let (pre_limit, pre_searcher) = pre.orders_received_by(self.auction_cutoff);
+                limit.extend(pre_limit);
+                searcher.extend(pre_searcher);
             });
         }
 
-        let limit = self.filter_quorum_orders(limit);
-        let searcher = self.filter_quorum_orders(searcher);
-        let pool_snapshots = self.fetch_pool_snapshot();
+        OrderSet {
+            limit:    self.filter_quorum_orders(limit),
+            searcher: self.filter_quorum_orders(searcher)
+        }
+    }
+
+    /// Commitment to the [`Self::eligible_orders`] of the pre-proposals.
+    fn book_commitment(
+        &self,
+        pre_proposal_aggregation: impl IntoIterator<Item = PreProposalAggregation>
+    ) -> BookCommitment {
+        let OrderSet { limit, searcher } = self.eligible_orders(pre_proposal_aggregation);
+        BookCommitment::from_orders(&limit, &searcher)
+    }
+
+    /// Solves the eligible orders of the pre-proposals, the way every validator
+    /// does to verify a proposal.
+    fn matching_engine_output(
+        &self,
+        pre_proposal_aggregation: HashSet<PreProposalAggregation>
+    ) -> BoxFuture<'static, eyre::Result<(Vec<PoolSolution>, BundleGasDetails)>> {
+        self.solve(self.eligible_orders(pre_proposal_aggregation), self.fetch_pool_snapshot())
+    }
+
+    /// Solves the eligible orders of the pre-proposals for our own proposal.
+    /// Pools whose circuit breaker tripped on our side are left out until
+    /// resumed, the pause is ours alone so it never applies to proposals we
+    /// verify.
+    fn proposal_matching_engine_output(
+        &self,
+        pre_proposal_aggregation: HashSet<PreProposalAggregation>
+    ) -> BoxFuture<'static, eyre::Result<(Vec<PoolSolution>, BundleGasDetails)>> {
+        let OrderSet { mut limit, mut searcher } = self.eligible_orders(pre_proposal_aggregation);
+        let mut pool_snapshots = self.fetch_pool_snapshot();
+
+        let breakers = &self.circuit_breakers;
+        limit.retain(|order| !breakers.is_paused(&order.pool_id));
+        searcher.retain(|order| !breakers.is_paused(&order.pool_id));
+        pool_snapshots.retain(|pool, _| !breakers.is_paused(pool));
+
+        self.solve(OrderSet { limit, searcher }, pool_snapshots)
+    }
+
+    fn solve(
+        &self,
+        orders: OrderSet<GroupedVanillaOrder, TopOfBlockOrder>,
+        pool_snapshots: HashMap<FixedBytes<32>, (Address, Address, PoolSnapshot, u16)>
+    ) -> BoxFuture<'static, eyre::Result<(Vec<PoolSolution>, BundleGasDetails)>> {
+        let OrderSet { limit, searcher } = orders;
 
         let matcher = self.matching_engine.clone();
+        let solve_time = self.solve_time.clone();
 
-        async move { matcher.solve_pools(limit, searcher, pool_snapshots).await }.boxed()
+        async move {
+            let start = Instant::now();
+            let solution = matcher.solve_pools(limit, searcher, pool_snapshots).await;
+            *solve_time.lock().expect("poisoned") = Some(start.elapsed());
+            solution
+        }
+        .boxed()
     }
 
     fn filter_quorum_orders<O: Hash + Eq + Clone>(
         &self,
         input: Vec<OrderWithStorageData<O>>
     ) -> Vec<OrderWithStorageData<O>> {
-        let two_thirds = self.two_thirds_of_validation_set();
-        input
-            .into_iter()
-            .fold(HashMap::new(), |mut acc, order| {
-                *acc.entry(order).or_insert(0) += 1;
-                acc
-            })
-            .into_iter()
-            .filter(|(_, count)| *count >= two_thirds)
-            .map(|(order, _)| order)
-            .collect()
+        filter_quorum_orders(input, self.two_thirds_of_validation_set())
     }
 
     fn handle_pre_proposal_aggregation(
@@ -268,11 +614,34 @@ where
         pre_proposal_agg: PreProposalAggregation,
         pre_proposal_agg_set: &mut HashSet<PreProposalAggregation>
     ) {
+        let validators = self.validator_ids();
+        let quorum = self.two_thirds_of_validation_set();
+        let order_storage = self.order_storage.clone();
+
         self.handle_proposal_verification(
             peer_id,
             pre_proposal_agg,
             pre_proposal_agg_set,
-            |proposal, block| proposal.is_valid(block)
+            |proposal, block| {
+                if !proposal.is_valid(block) {
+                    return false
+                }
+                // the aggregator only vouches for the set it signed, the pre-proposals
+                // in it have to add up to a quorum on their own
+                let is_quorum = proposal.is_quorum_of(&validators, quorum);
+                if !is_quorum {
+                    tracing::warn!(
+                        source=?proposal.source,
+                        contributors=proposal.contributors.len(),
+                        quorum,
+                        "pre-proposal aggregation doesn't have a quorum of validators"
+                    );
+                }
+                if is_quorum {
+                    pin_orders(&order_storage, &proposal.pre_proposals);
+                }
+                is_quorum
+            }
         )
     }
 
@@ -296,11 +665,40 @@ where
         pre_proposal: PreProposal,
         pre_proposal_set: &mut HashSet<PreProposal>
     ) {
+        let content_rules = self.content_rules;
+        let order_storage = self.order_storage.clone();
+        let anomalies = self.anomalies.clone();
+
         self.handle_proposal_verification(
             peer_id,
             pre_proposal,
             pre_proposal_set,
-            |proposal, block| proposal.is_valid(block)
+            |proposal, block| {
+                if !proposal.is_valid(block) {
+                    return false
+                }
+                // a signed pre-proposal full of orders only its source knows of
+                // is misbehavior, not a race with the gossip
+                let is_valid = content_rules
+                    .check(proposal, |hash| order_storage.contains_order(hash))
+                    .inspect_err(|unknown| {
+                        tracing::warn!(
+                            source=?proposal.source,
+                            unknown=unknown.len(),
+                            "pre-proposal has too many orders we don't know"
+                        );
+                        let _ = anomalies.send(WatchtowerAlert::UnknownOrders {
+                            height: *block,
+                            source: proposal.source,
+                            orders: unknown.clone()
+                        });
+                    })
+                    .is_ok();
+                if is_valid {
+                    pin_orders(&order_storage, std::slice::from_ref(proposal));
+                }
+                is_valid
+            }
         )
     }
 
@@ -334,13 +732,71 @@ where
     }
 }
 
+/// The current snapshot of every pool, with its tokens and its index in the
+/// pool config store.
+pub(crate) fn pool_snapshots(
+    uniswap_pools: &SyncedUniswapPools,
+    pool_registry: &UniswapAngstromRegistry
+) -> HashMap<PoolId, (Address, Address, PoolSnapshot, u16)> {
+    uniswap_pools
+        .iter()
+        .map(|(key, pool)| {
+            tracing::info!(?key, "getting snapshot");
+            let (token_a, token_b, snapshot) = pool.read().unwrap().fetch_pool_snapshot().unwrap();
+            let entry = pool_registry.get_ang_entry(key).unwrap();
+
+            (*key, (token_a, token_b, snapshot, entry.store_index as u16))
+        })
+        .collect::<HashMap<_, _>>()
+}
+
+/// Keeps the orders of the pre-proposals of the current round in the pool until
+/// the round completes, so churn in the pool can't fail the verification of
+/// the proposal built from them.
+pub(crate) fn pin_orders(order_storage: &OrderStorage, pre_proposals: &[PreProposal]) {
+    order_storage.pin_orders(pre_proposals.iter().flat_map(|pre| pre.order_hashes()));
+}
+
+/// Keeps the orders that at least `quorum` pre-proposals contain.
+pub(crate) fn filter_quorum_orders<O: Hash + Eq + Clone>(
+    input: Vec<OrderWithStorageData<O>>,
+    quorum: usize
+) -> Vec<OrderWithStorageData<O>> {
+    input
+        .into_iter()
+        .fold(HashMap::new(), |mut acc, mut order| {
+            // every node stamps the orders it validates with its own time, so
+            // copies are counted regardless of it and take the median stamp
+            let validated_at = std::mem::take(&mut order.priority_data.validated_at);
+            acc.entry(order).or_insert_with(Vec::new).push(validated_at);
+            acc
+        })
+        .into_iter()
+        .filter(|(_, stamps)| stamps.len() >= quorum)
+        .map(|(mut order, stamps)| {
+            order.priority_data.validated_at = median_validated_at(stamps);
+            order
+        })
+        .collect()
+}
+
 /// These messages will only be broadcasted to the peer network if our consensus
 /// contracts don't currently contain them.
 #[derive(Debug, Clone)]
 pub enum ConsensusMessage {
     PropagatePreProposal(PreProposal),
     PropagatePreProposalAgg(PreProposalAggregation),
-    PropagateProposal(Proposal)
+    PropagateProposal(Proposal),
+    /// sent to each of the approvers, not broadcast
+    RequestSubmissionApproval {
+        approvers: Vec<PeerId>,
+        request:   SubmissionApprovalRequest
+    },
+    /// orders of a proposal we don't have, asked for from `peer` only
+    RequestOrders {
+        peer:   PeerId,
+        hashes: Vec<B256>
+    }
 }
 
 impl From<PreProposal> for ConsensusMessage {
@@ -365,7 +821,7 @@ pub mod tests {
     };
 
     use alloy::{
-        primitives::Address,
+        primitives::{Address, B256},
         providers::{fillers::*, network::Ethereum, ProviderBuilder, RootProvider, *}
     };
     use angstrom_metrics::ConsensusMetricsWrapper;
@@ -373,7 +829,9 @@ pub mod tests {
     use angstrom_types::{
         contract_payloads::angstrom::{AngstromPoolConfigStore, UniswapAngstromRegistry},
         mev_boost::MevBoostProvider,
-        primitive::{AngstromSigner, PeerId, UniswapPoolRegistry}
+        orders::{OrderId, OrderPriorityData},
+        primitive::{AngstromSigner, PeerId, UniswapPoolRegistry},
+        sol_bindings::grouped_orders::OrderWithStorageData
     };
     use futures::{pin_mut, Stream};
     use order_pool::{order_storage::OrderStorage, PoolConfig};
@@ -387,7 +845,8 @@ pub mod tests {
     use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
 
     use super::{
-        pre_proposal::PreProposalState, ConsensusMessage, RoundStateMachine, SharedRoundState
+        filter_quorum_orders, pre_proposal::PreProposalState, ConsensusMessage, RoundStateMachine,
+        SharedRoundState
     };
     use crate::{
         rounds::{pre_proposal_aggregation::PreProposalAggregationState, ConsensusState},
@@ -582,11 +1041,19 @@ pub mod tests {
         let mut state_machine = setup_state_machine().await;
         let new_block = 2;
         let new_leader = PeerId::random();
+        let pinned = B256::repeat_byte(1);
+        state_machine
+            .shared_state
+            .order_storage
+            .pin_orders([pinned]);
 
         // Reset round with new block and leader
-        state_machine.reset_round(new_block, new_leader);
+        state_machine.reset_round(new_block, 1_700_000_000, new_leader);
 
+        // the pins of the last round are released
+        assert!(!state_machine.shared_state.order_storage.is_pinned(&pinned));
         assert_eq!(state_machine.shared_state.block_height, new_block);
+        assert_eq!(state_machine.shared_state.auction_cutoff, 1_700_000_011_200);
         assert_eq!(state_machine.shared_state.round_leader, new_leader);
 
         // Should be back in BidAggregationState
@@ -676,4 +1143,22 @@ pub mod tests {
         ));
         assert!(state_machine.shared_state.messages.is_empty());
     }
+
+    #[test]
+    fn quorum_orders_take_the_median_stamp() {
+        let stamped = |hash: u8, validated_at: u64| OrderWithStorageData::<()> {
+            priority_data: OrderPriorityData { validated_at, ..Default::default() },
+            order_id: OrderId { hash: B256::repeat_byte(hash), ..Default::default() },
+            ..Default::default()
+        };
+
+        // one validator back-dating the order doesn't move it up the book
+        let orders = filter_quorum_orders(
+            vec![stamped(1, 300), stamped(1, 0), stamped(1, 310), stamped(2, 100)],
+            2
+        );
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].order_id.hash, B256::repeat_byte(1));
+        assert_eq!(orders[0].priority_data.validated_at, 300);
+    }
 }
@@ -0,0 +1,137 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration
+};
+
+use alloy::primitives::B256;
+use angstrom_types::primitive::PeerId;
+use futures::FutureExt;
+use tokio::time::{interval, sleep, Interval, Sleep};
+
+/// How long a peer has to send us the orders we asked it for before we ask the
+/// next one.
+pub const ORDER_FETCH_TIMEOUT: Duration = Duration::from_millis(500);
+/// Validators other than the proposer we ask for the orders when it doesn't
+/// send them in time.
+pub const ORDER_FETCH_FALLBACK_PEERS: usize = 2;
+/// How often we check whether the fetched orders made it into the pool.
+const CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Gets the orders of a proposal that aren't in our pool into it, so the
+/// proposal is only accepted once they validated. They are asked for from one
+/// peer at a time, the proposer first. The peer sends them like any other
+/// orders, and they reach the pool once they validated.
+pub struct OrderFetch {
+    missing:        HashSet<B256>,
+    peers:          VecDeque<PeerId>,
+    timeout:        Duration,
+    /// when the peer we last asked runs out of time, none before the first
+    /// request
+    deadline:       Option<Pin<Box<Sleep>>>,
+    check_interval: Interval
+}
+
+impl OrderFetch {
+    pub fn new(
+        missing: HashSet<B256>,
+        peers: impl IntoIterator<Item = PeerId>,
+        timeout: Duration
+    ) -> Self {
+        Self {
+            missing,
+            peers: peers.into_iter().collect(),
+            timeout,
+            deadline: None,
+            check_interval: interval(CHECK_INTERVAL)
+        }
+    }
+
+    /// Ready once every missing order is `in_pool`, or with the ones that
+    /// still aren't after every peer was asked. `request` is called with each
+    /// peer to ask and the orders to ask it for.
+    pub fn poll_fetch(
+        &mut self,
+        cx: &mut Context<'_>,
+        in_pool: impl Fn(&B256) -> bool,
+        mut request: impl FnMut(PeerId, Vec<B256>)
+    ) -> Poll<Result<(), Vec<B256>>> {
+        while self.check_interval.poll_tick(cx).is_ready() {}
+
+        loop {
+            self.missing.retain(|hash| !in_pool(hash));
+            if self.missing.is_empty() {
+                return Poll::Ready(Ok(()))
+            }
+
+            if let Some(deadline) = self.deadline.as_mut() {
+                if deadline.poll_unpin(cx).is_pending() {
+                    return Poll::Pending
+                }
+            }
+
+            let Some(peer) = self.peers.pop_front() else {
+                return Poll::Ready(Err(self.missing.drain().collect()))
+            };
+            tracing::debug!(
+                ?peer,
+                missing = self.missing.len(),
+                "requesting orders of the proposal"
+            );
+            request(peer, self.missing.iter().copied().collect());
+            self.deadline = Some(Box::pin(sleep(self.timeout)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Mutex, time::Instant};
+
+    use futures::future::poll_fn;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn falls_back_to_the_next_peer() {
+        let (proposer, fallback) = (PeerId::random(), PeerId::random());
+        let missing = [B256::repeat_byte(1), B256::repeat_byte(2)];
+        let pool = Mutex::new(HashSet::from([missing[0]]));
+        let requests = Mutex::new(vec![]);
+
+        let mut fetch = OrderFetch::new(
+            missing.into_iter().collect(),
+            [proposer, fallback],
+            Duration::from_millis(50)
+        );
+
+        // the proposer never answers, the fallback does
+        let start = Instant::now();
+        let result = poll_fn(|cx| {
+            fetch.poll_fetch(
+                cx,
+                |hash| pool.lock().unwrap().contains(hash),
+                |peer, hashes| {
+                    requests.lock().unwrap().push((peer, hashes.clone()));
+                    if peer == fallback {
+                        pool.lock().unwrap().extend(hashes);
+                    }
+                }
+            )
+        })
+        .await;
+
+        assert_eq!(result, Ok(()));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(
+            *requests.lock().unwrap(),
+            vec![(proposer, vec![missing[1]]), (fallback, vec![missing[1]])]
+        );
+
+        let mut fetch =
+            OrderFetch::new(missing.into_iter().collect(), [proposer], Duration::from_millis(10));
+        let result = poll_fn(|cx| fetch.poll_fetch(cx, |_| false, |_, _| {})).await;
+        assert_eq!(result.unwrap_err().len(), 2);
+    }
+}
@@ -9,10 +9,13 @@ use angstrom_network::manager::StromConsensusEvent;
 use angstrom_types::consensus::{PreProposal, PreProposalAggregation, Proposal};
 use matching_engine::MatchingEngineHandle;
 
-use super::{ConsensusState, SharedRoundState};
-use crate::rounds::{
-    finalization::FinalizationState, pre_proposal_aggregation::PreProposalAggregationState,
-    ConsensusMessage
+use super::{pin_orders, ConsensusPhase, ConsensusState, SharedRoundState};
+use crate::{
+    rounds::{
+        finalization::FinalizationState, pre_proposal_aggregation::PreProposalAggregationState,
+        ConsensusMessage
+    },
+    SignedMessageKind
 };
 
 /// PreProposalState
@@ -44,13 +47,18 @@ impl PreProposalState {
         Matching: MatchingEngineHandle
     {
         // generate my pre_proposal
-        let my_preproposal =
-            PreProposal::new(block_height, &handles.signer, handles.order_storage.get_all_orders());
+        let orders = handles.order_storage.get_all_orders();
+        let receipts = handles.order_storage.receipts_for(&orders);
+        let my_preproposal = PreProposal::new(block_height, &handles.signer, orders, receipts);
 
-        // propagate my pre_proposal
-        handles.propagate_message(ConsensusMessage::PropagatePreProposal(my_preproposal.clone()));
+        if handles.guard_signature(SignedMessageKind::PreProposal, my_preproposal.hash()) {
+            pin_orders(&handles.order_storage, std::slice::from_ref(&my_preproposal));
+            // propagate my pre_proposal
+            handles
+                .propagate_message(ConsensusMessage::PropagatePreProposal(my_preproposal.clone()));
 
-        pre_proposals.insert(my_preproposal);
+            pre_proposals.insert(my_preproposal);
+        }
 
         // ensure we get polled to start the checks for when we have 2f +1 pre_proposals
         // collected
@@ -66,6 +74,10 @@ where
     P: Provider + 'static,
     Matching: MatchingEngineHandle
 {
+    fn phase(&self) -> ConsensusPhase {
+        ConsensusPhase::PreProposal
+    }
+
     fn on_consensus_message(
         &mut self,
         handles: &mut SharedRoundState<P, Matching>,
@@ -92,6 +104,9 @@ where
                     self.waker.wake_by_ref();
                 }
             }
+            StromConsensusEvent::Telemetry(..)
+            | StromConsensusEvent::ApprovalRequest(..)
+            | StromConsensusEvent::Approval(..) => {}
         }
     }
 
@@ -9,8 +9,11 @@ use angstrom_network::manager::StromConsensusEvent;
 use angstrom_types::consensus::{PreProposal, PreProposalAggregation, Proposal};
 use matching_engine::MatchingEngineHandle;
 
-use super::{ConsensusState, SharedRoundState};
-use crate::rounds::{finalization::FinalizationState, proposal::ProposalState};
+use super::{ConsensusPhase, ConsensusState, SharedRoundState};
+use crate::{
+    rounds::{finalization::FinalizationState, proposal::ProposalState},
+    SignedMessageKind
+};
 
 /// PreProposalAggregationState
 ///
@@ -45,13 +48,19 @@ impl PreProposalAggregationState {
         let my_preproposal_aggregation = PreProposalAggregation::new(
             handles.block_height,
             &handles.signer,
-            pre_proposals.into_iter().collect::<Vec<_>>()
+            pre_proposals.into_iter().collect::<Vec<_>>(),
+            &handles.validator_ids()
         );
 
-        // propagate my pre_proposal
-        handles.propagate_message(my_preproposal_aggregation.clone().into());
+        if handles.guard_signature(
+            SignedMessageKind::PreProposalAggregation,
+            my_preproposal_aggregation.hash()
+        ) {
+            // propagate my pre_proposal
+            handles.propagate_message(my_preproposal_aggregation.clone().into());
 
-        pre_proposals_aggregation.insert(my_preproposal_aggregation);
+            pre_proposals_aggregation.insert(my_preproposal_aggregation);
+        }
 
         // ensure we get polled to start the checks for when we have 2f +1 pre_proposals
         // collected
@@ -67,6 +76,10 @@ where
     P: Provider + 'static,
     Matching: MatchingEngineHandle
 {
+    fn phase(&self) -> ConsensusPhase {
+        ConsensusPhase::PreProposalAggregation
+    }
+
     fn on_consensus_message(
         &mut self,
         handles: &mut SharedRoundState<P, Matching>,
@@ -88,6 +101,9 @@ where
                     self.waker.wake_by_ref();
                 }
             }
+            StromConsensusEvent::Telemetry(..)
+            | StromConsensusEvent::ApprovalRequest(..)
+            | StromConsensusEvent::Approval(..) => {}
         }
     }
 
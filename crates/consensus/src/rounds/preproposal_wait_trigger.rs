@@ -9,8 +9,8 @@ use tokio::time::{interval, Interval};
 
 use crate::rounds::OrderStorage;
 
-/// How soon we send our pre-proposal
-const DEFAULT_DURATION: Duration = Duration::from_secs(9);
+/// How soon we send our pre-proposal, in quarters of the block time
+const DEFAULT_DURATION_QUARTERS: u32 = 3;
 /// The frequency we adjust our duration estimate. we have it super frequent
 /// because its very low overhead to check
 const CHECK_INTERVAL: Duration = Duration::from_millis(1);
@@ -18,7 +18,7 @@ const CHECK_INTERVAL: Duration = Duration::from_millis(1);
 const ORDER_SCALING: Duration = Duration::from_millis(10);
 /// How close we want to be to the creation of the ethereum block
 const TARGET_SUBMISSION_TIME_REM: Duration = Duration::from_millis(800);
-/// Eth block time
+/// Eth block time, unless the chain we run on is set up otherwise
 const ETH_BLOCK_TIME: Duration = Duration::from_secs(12);
 /// The amount of the difference we scale by to reach
 const SCALING_REM_ADJUSTMENT: u32 = 3;
@@ -32,6 +32,8 @@ const SCALING_REM_ADJUSTMENT: u32 = 3;
 pub struct PreProposalWaitTrigger {
     /// the base wait duration that we scale down based on orders.
     wait_duration:  Duration,
+    /// time between two blocks of the chain
+    block_time:     Duration,
     /// the start instant
     start_instant:  Instant,
     /// to track our scaling
@@ -44,6 +46,7 @@ impl Clone for PreProposalWaitTrigger {
     fn clone(&self) -> Self {
         Self {
             wait_duration:  self.wait_duration,
+            block_time:     self.block_time,
             start_instant:  Instant::now(),
             order_storage:  self.order_storage.clone(),
             check_interval: interval(CHECK_INTERVAL)
@@ -54,13 +57,22 @@ impl Clone for PreProposalWaitTrigger {
 impl PreProposalWaitTrigger {
     pub fn new(order_storage: Arc<OrderStorage>) -> Self {
         Self {
-            wait_duration: DEFAULT_DURATION,
+            wait_duration: ETH_BLOCK_TIME / 4 * DEFAULT_DURATION_QUARTERS,
+            block_time: ETH_BLOCK_TIME,
             order_storage,
             start_instant: Instant::now(),
             check_interval: interval(CHECK_INTERVAL)
         }
     }
 
+    /// Times the rounds against blocks that are `block_time` apart, starting
+    /// over from the default wait duration for it.
+    pub fn with_block_time(mut self, block_time: Duration) -> Self {
+        self.block_time = block_time;
+        self.wait_duration = block_time / 4 * DEFAULT_DURATION_QUARTERS;
+        self
+    }
+
     pub fn update_for_new_round(&mut self, info: Option<LastRoundInfo>) -> Self {
         if let Some(info) = info {
             self.update_wait_duration_base(info);
@@ -76,7 +88,7 @@ impl PreProposalWaitTrigger {
     }
 
     fn update_wait_duration_base(&mut self, info: LastRoundInfo) {
-        let base = ETH_BLOCK_TIME - TARGET_SUBMISSION_TIME_REM;
+        let base = self.block_time.saturating_sub(TARGET_SUBMISSION_TIME_REM);
 
         if info.time_to_complete < base && self.wait_duration < base {
             // if we overestimated the time, we will push our trigger back
@@ -1,29 +1,52 @@
 use std::{
     collections::HashSet,
+    sync::Mutex,
     task::{Context, Poll, Waker},
     time::{Duration, Instant}
 };
 
 use alloy::{
-    network::TransactionBuilder, providers::Provider, rpc::types::TransactionRequest,
+    eips::eip2718::Encodable2718,
+    network::TransactionBuilder,
+    primitives::{keccak256, BlockNumber, Bytes, TxHash, B256},
+    providers::Provider,
+    rpc::types::TransactionRequest,
     sol_types::SolCall
 };
+use angstrom_metrics::track_allocations;
 use angstrom_network::manager::StromConsensusEvent;
 use angstrom_types::{
-    consensus::{PreProposalAggregation, Proposal},
+    consensus::{PreProposalAggregation, Proposal, SubmissionApproval, SubmissionApprovalRequest},
     contract_bindings::angstrom::Angstrom,
-    contract_payloads::angstrom::{AngstromBundle, BundleGasDetails},
-    orders::PoolSolution
+    contract_payloads::angstrom::{AngstromBundle, BundleFees, BundleGasDetails, FeeLedger},
+    orders::PoolSolution,
+    primitive::AngstromSigner
 };
 use futures::{future::BoxFuture, FutureExt, StreamExt};
 use matching_engine::MatchingEngineHandle;
-use pade::PadeEncode;
+use tokio::sync::oneshot;
 
-use super::{ConsensusState, SharedRoundState};
-use crate::rounds::{preproposal_wait_trigger::LastRoundInfo, ConsensusMessage};
+use super::{ConsensusPhase, ConsensusState, SharedRoundState};
+use crate::{
+    rounds::{preproposal_wait_trigger::LastRoundInfo, ConsensusMessage},
+    ApprovalCollector, SignedMessageKind, SubmissionApprovalConfig, SubmissionLedger
+};
 
 type MatchingEngineFuture = BoxFuture<'static, eyre::Result<(Vec<PoolSolution>, BundleGasDetails)>>;
 
+/// Where our settlement transaction is at, when it has to be approved before
+/// it is broadcast.
+enum ApprovalStage {
+    /// the transaction is being built and signed
+    Signing {
+        config:   SubmissionApprovalConfig,
+        signed:   oneshot::Receiver<Bytes>,
+        approved: oneshot::Sender<()>
+    },
+    /// waiting for the approvers to commit to it
+    Collecting { collector: ApprovalCollector, approved: oneshot::Sender<()> }
+}
+
 /// Proposal State.
 ///
 /// We only transition to Proposal state if we are the leader.
@@ -34,8 +57,12 @@ type MatchingEngineFuture = BoxFuture<'static, eyre::Result<(Vec<PoolSolution>,
 pub struct ProposalState {
     matching_engine_future: Option<MatchingEngineFuture>,
     submission_future:      Option<BoxFuture<'static, bool>>,
+    /// set when the submission has to be approved first
+    approval:               Option<ApprovalStage>,
     pre_proposal_aggs:      Vec<PreProposalAggregation>,
     proposal:               Option<Proposal>,
+    /// fees saved by the bundle of the proposal, recorded once it lands
+    fees:                   Option<FeeLedger>,
     last_round_info:        Option<LastRoundInfo>,
     trigger_time:           Instant,
     waker:                  Waker
@@ -58,12 +85,14 @@ impl ProposalState {
 
         Self {
             matching_engine_future: Some(
-                handles.matching_engine_output(pre_proposal_aggregation.clone())
+                handles.proposal_matching_engine_output(pre_proposal_aggregation.clone())
             ),
             last_round_info: None,
             pre_proposal_aggs: pre_proposal_aggregation.into_iter().collect::<Vec<_>>(),
             submission_future: None,
+            approval: None,
             proposal: None,
+            fees: None,
             trigger_time,
             waker
         }
@@ -95,14 +124,24 @@ impl ProposalState {
             handles.block_height,
             &handles.signer,
             self.pre_proposal_aggs.clone(),
-            pool_solution
+            pool_solution,
+            handles.book_commitment(self.pre_proposal_aggs.clone())
         );
 
+        if !handles.guard_signature(SignedMessageKind::Proposal, proposal.hash()) {
+            return false
+        }
+
         self.proposal = Some(proposal.clone());
         let snapshot = handles.fetch_pool_snapshot();
+        let fees = BundleFees::from_registry(
+            &handles.pool_registry,
+            snapshot.keys(),
+            handles.surplus_distribution
+        );
 
-        let Ok(bundle) =
-            AngstromBundle::from_proposal(&proposal, gas_info, &snapshot).inspect_err(|e| {
+        let Ok(bundle) = AngstromBundle::from_proposal(&proposal, gas_info, &snapshot, &fees)
+            .inspect_err(|e| {
                 tracing::error!(err=%e,
                     "failed to encode angstrom bundle, THERE SHALL BE NO PROPOSAL THIS BLOCK :("
                 );
@@ -110,26 +149,78 @@ impl ProposalState {
         else {
             return false
         };
-
-        let encoded = Angstrom::executeCall::new((bundle.pade_encode().into(),)).abi_encode();
+        // the surplus charged to the orders has to fund what the bundle pays out
+        if let Err(e) = bundle.verify_rewards() {
+            tracing::error!(err=%e,
+                "invalid bundle rewards, THERE SHALL BE NO PROPOSAL THIS BLOCK :("
+            );
+            return false
+        }
+        // a different layout saves the same of every asset
+        self.fees = Some(FeeLedger::of_bundle(&bundle));
 
         let mut tx = TransactionRequest::default()
             .with_to(handles.angstrom_address)
-            .with_from(handles.signer.address())
-            .with_input(encoded);
+            .with_from(handles.signer.address());
 
         let provider = handles.provider.clone();
         let signer = handles.signer.clone();
+        let height = handles.block_height;
+        let ledger = handles.submission_ledger.clone();
+        let matching_engine = handles
+            .golf_bundles
+            .then(|| handles.matching_engine.clone());
+        let approval = handles.submission_approval.clone().map(|config| {
+            let (signed_tx, signed) = oneshot::channel();
+            let (approved_tx, approved) = oneshot::channel();
+            self.approval = Some(ApprovalStage::Signing { config, signed, approved: approved_tx });
+            (signed_tx, approved)
+        });
 
         let submission_future = async move {
+            let bundle = match matching_engine {
+                Some(matching_engine) => {
+                    let (bundle, report) = matching_engine.golf_bundle(bundle).await;
+                    tracing::info!(
+                        cheapest=?report.cheapest(),
+                        savings=?report.savings(),
+                        "picked bundle layout"
+                    );
+                    bundle
+                }
+                None => bundle
+            };
+            let encoded =
+                track_allocations("bundle_encoding", || bundle.pade_encode_for_submission());
+            let bundle_hash = keccak256(&encoded);
+            tx.set_input(Angstrom::executeCall::new((encoded.into(),)).abi_encode());
+
             tracing::info!("building bundle");
             provider
                 .populate_gas_nonce_chain_id(signer.address(), &mut tx)
                 .await;
 
-            let (hash, success) = provider.sign_and_send(signer, tx).await;
+            let (hash, receipt) = match approval {
+                Some((signed_tx, approved)) => {
+                    let Some((hash, encoded)) = sign(&signer, tx).await else { return false };
+                    if signed_tx.send(encoded.clone()).is_err() || approved.await.is_err() {
+                        tracing::warn!(?hash, "bundle wasn't approved, not submitting it");
+                        return false
+                    }
+                    if !record_submission(&ledger, height, bundle_hash) {
+                        return false
+                    }
+                    (hash, provider.send_signed(&encoded).await)
+                }
+                None => {
+                    if !record_submission(&ledger, height, bundle_hash) {
+                        return false
+                    }
+                    provider.sign_and_send(signer, tx).await
+                }
+            };
             tracing::info!("submitted bundle");
-            if !success {
+            if !receipt.accepted() {
                 return false
             }
 
@@ -143,11 +234,14 @@ impl ProposalState {
                 .next()
                 .await;
 
-            provider
+            let landed = provider
                 .get_transaction_by_hash(hash)
                 .await
                 .unwrap()
-                .is_some()
+                .is_some();
+            receipt.on_inclusion(landed);
+
+            landed
         }
         .boxed();
 
@@ -156,6 +250,96 @@ impl ProposalState {
 
         true
     }
+
+    /// Sends our signed transaction to the approvers once it is built, and lets
+    /// the submission go ahead once enough of them approved it.
+    fn poll_approval<P, Matching>(
+        &mut self,
+        handles: &mut SharedRoundState<P, Matching>,
+        cx: &mut Context<'_>
+    ) where
+        P: Provider + 'static,
+        Matching: MatchingEngineHandle
+    {
+        match self.approval.take() {
+            Some(ApprovalStage::Signing { config, mut signed, approved }) => {
+                match signed.poll_unpin(cx) {
+                    Poll::Ready(Ok(raw_tx)) => {
+                        let request = SubmissionApprovalRequest {
+                            block_height: handles.block_height,
+                            raw_tx
+                        };
+                        let tx_hash = request.tx_hash();
+                        let mut collector =
+                            ApprovalCollector::new(config, handles.block_height, tx_hash);
+
+                        // we built it, so we approve it if we are one of the approvers
+                        let us = handles.signer.id();
+                        if collector.approvers().any(|approver| *approver == us)
+                            && handles
+                                .guard_signature(SignedMessageKind::SubmissionApproval, tx_hash)
+                        {
+                            collector.add(&SubmissionApproval::new(
+                                handles.block_height,
+                                tx_hash,
+                                &handles.signer
+                            ));
+                        }
+
+                        tracing::info!(?tx_hash, "requesting approval of our bundle");
+                        let approvers = collector
+                            .approvers()
+                            .filter(|approver| **approver != us)
+                            .copied()
+                            .collect();
+                        handles.propagate_message(ConsensusMessage::RequestSubmissionApproval {
+                            approvers,
+                            request
+                        });
+                        cx.waker().wake_by_ref();
+                        self.approval = Some(ApprovalStage::Collecting { collector, approved });
+                    }
+                    // the submission gave up before it was signed
+                    Poll::Ready(Err(_)) => {}
+                    Poll::Pending => {
+                        self.approval = Some(ApprovalStage::Signing { config, signed, approved })
+                    }
+                }
+            }
+            Some(ApprovalStage::Collecting { collector, approved }) if collector.is_approved() => {
+                tracing::info!("bundle approved");
+                let _ = approved.send(());
+            }
+            stage => self.approval = stage
+        }
+    }
+}
+
+/// Has to be called right before the bundle is broadcast. Returns false if we
+/// already submitted one for `height`, in which case it must be dropped.
+fn record_submission(
+    ledger: &Mutex<SubmissionLedger>,
+    height: BlockNumber,
+    bundle_hash: B256
+) -> bool {
+    ledger
+        .lock()
+        .expect("poisoned")
+        .record(height, bundle_hash)
+        .inspect_err(|e| tracing::error!(err=%e, ?bundle_hash, "refusing to submit bundle"))
+        .is_ok()
+}
+
+/// Signs the settlement transaction without sending it, returning its hash and
+/// its eip-2718 encoding.
+async fn sign(signer: &AngstromSigner, tx: TransactionRequest) -> Option<(TxHash, Bytes)> {
+    let tx = tx
+        .build(signer)
+        .await
+        .inspect_err(|e| tracing::error!(err=%e, "failed to sign the bundle transaction"))
+        .ok()?;
+
+    Some((*tx.tx_hash(), tx.encoded_2718().into()))
 }
 
 impl<P, Matching> ConsensusState<P, Matching> for ProposalState
@@ -163,13 +347,31 @@ where
     P: Provider + 'static,
     Matching: MatchingEngineHandle
 {
+    fn phase(&self) -> ConsensusPhase {
+        ConsensusPhase::Proposal
+    }
+
+    fn submission_in_flight(&self) -> bool {
+        // a bundle waiting for approvals was never sent
+        self.submission_future.is_some() && self.approval.is_none()
+    }
+
     fn on_consensus_message(
         &mut self,
         _: &mut SharedRoundState<P, Matching>,
-        _: StromConsensusEvent
+        message: StromConsensusEvent
     ) {
-        // No messages at this point can effect the consensus round and thus are
-        // ignored.
+        // Apart from the approvals of our bundle, no messages at this point can
+        // effect the consensus round and thus are ignored.
+        if let (
+            StromConsensusEvent::Approval(_, approval),
+            Some(ApprovalStage::Collecting { collector, .. })
+        ) = (message, &mut self.approval)
+        {
+            if collector.add(&approval) {
+                self.waker.wake_by_ref();
+            }
+        }
     }
 
     fn poll_transition(
@@ -189,15 +391,25 @@ where
             }
         }
 
+        self.poll_approval(handles, cx);
+
         if let Some(mut b_fut) = self.submission_future.take() {
             match b_fut.poll_unpin(cx) {
                 Poll::Ready(transaction_landed) => {
+                    handles.record_submission_targets();
                     if transaction_landed {
+                        if let Some(fees) = self.fees.take() {
+                            handles.record_fees(&fees);
+                        }
                         let proposal = self.proposal.take().unwrap();
                         handles
                             .messages
                             .push_back(ConsensusMessage::PropagateProposal(proposal));
                         cx.waker().wake_by_ref();
+                    } else if let Some(proposal) = self.proposal.take() {
+                        for solution in &proposal.solutions {
+                            handles.circuit_breakers.on_settlement(solution.id, false);
+                        }
                     }
                     return Poll::Ready(None)
                 }
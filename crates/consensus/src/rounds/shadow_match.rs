@@ -0,0 +1,219 @@
+use alloy::providers::Provider;
+use angstrom_types::{
+    orders::{OrderId, PoolSolution},
+    sol_bindings::{
+        grouped_orders::{AllOrders, GroupedVanillaOrder, OrderWithStorageData},
+        rpc_orders::TopOfBlockOrder
+    }
+};
+use futures::{future::BoxFuture, FutureExt};
+use matching_engine::MatchingEngineHandle;
+
+use super::SharedRoundState;
+use crate::{ShadowMatch, ShadowMatchError};
+
+impl<P, Matching> SharedRoundState<P, Matching>
+where
+    P: Provider + 'static,
+    Matching: MatchingEngineHandle
+{
+    /// Matches the book of the pool of `order` as it is now, with `order` in
+    /// it. Neither the book nor the round are touched.
+    pub(crate) fn shadow_match(
+        &self,
+        order: OrderWithStorageData<AllOrders>
+    ) -> BoxFuture<'static, Result<ShadowMatch, ShadowMatchError>> {
+        let pool_id = order.pool_id;
+        let fail = |e| -> BoxFuture<'static, _> { futures::future::ready(Err(e)).boxed() };
+        if self.circuit_breakers.is_paused(&pool_id) {
+            return fail(ShadowMatchError::PoolPaused(pool_id))
+        }
+        let (Some(shard), Some(pool), Some(entry)) = (
+            self.order_storage.shard(&pool_id),
+            self.uniswap_pools.get(&pool_id),
+            self.pool_registry.get_ang_entry(&pool_id)
+        ) else {
+            return fail(ShadowMatchError::UnknownPool(pool_id))
+        };
+        let Ok((token_a, token_b, snapshot)) = pool.read().unwrap().fetch_pool_snapshot() else {
+            return fail(ShadowMatchError::UnknownPool(pool_id))
+        };
+        let pools = [(pool_id, (token_a, token_b, snapshot, entry.store_index as u16))].into();
+
+        let mut limit = shard
+            .limit_orders
+            .read()
+            .expect("poisoned")
+            .get_all_orders();
+        let searcher = shard
+            .searcher_orders
+            .read()
+            .expect("poisoned")
+            .get_all_orders();
+        let order_id = order.order_id;
+        let (shadow, queue_position) = shadow_book(order, &limit, &searcher);
+
+        let (searcher, max_q) = match shadow {
+            ShadowOrder::Limit(order) => {
+                let max_q = order.max_q();
+                limit.push(order);
+                (searcher.into_iter().max_by_key(|o| o.tob_reward), max_q)
+            }
+            // only the best bid of a pool is matched
+            ShadowOrder::TopOfBlock(order) => {
+                let best = searcher
+                    .into_iter()
+                    .chain(Some(order))
+                    .max_by_key(|o| o.tob_reward);
+                (best, 0)
+            }
+        };
+
+        let matcher = self.matching_engine.clone();
+        async move {
+            let (solutions, _) = matcher
+                .solve_pools(limit, searcher.into_iter().collect(), pools)
+                .await
+                .map_err(|e| ShadowMatchError::Matching(e.to_string()))?;
+            let solution = solutions
+                .into_iter()
+                .find(|solution| solution.id == pool_id)
+                .ok_or_else(|| ShadowMatchError::Matching("pool wasn't solved".to_string()))?;
+
+            Ok(shadow_outcome(&solution, &order_id, max_q, queue_position))
+        }
+        .boxed()
+    }
+}
+
+enum ShadowOrder {
+    Limit(OrderWithStorageData<GroupedVanillaOrder>),
+    TopOfBlock(OrderWithStorageData<TopOfBlockOrder>)
+}
+
+/// Converts `order` for the matching engine, along with how many orders of
+/// the book are ahead of it. Orders already in the book win ties.
+fn shadow_book(
+    order: OrderWithStorageData<AllOrders>,
+    limit: &[OrderWithStorageData<GroupedVanillaOrder>],
+    searcher: &[OrderWithStorageData<TopOfBlockOrder>]
+) -> (ShadowOrder, usize) {
+    if let AllOrders::TOB(_) = order.order {
+        let tob_reward = order.tob_reward;
+        let ahead = searcher
+            .iter()
+            .filter(|o| o.tob_reward >= tob_reward)
+            .count();
+        let mut order = order
+            .try_map_inner(|order| match order {
+                AllOrders::TOB(o) => Ok(o),
+                _ => unreachable!()
+            })
+            .unwrap();
+        // mapping doesn't carry the reward over
+        order.tob_reward = tob_reward;
+
+        return (ShadowOrder::TopOfBlock(order), ahead)
+    }
+
+    // both sides of the book are sorted with the best order first
+    let ahead = limit
+        .iter()
+        .filter(|o| o.is_bid == order.is_bid && o.priority_data <= order.priority_data)
+        .count();
+    let order = order
+        .try_map_inner(|order| match order {
+            AllOrders::Standing(o) => Ok(GroupedVanillaOrder::Standing(o)),
+            AllOrders::Flash(o) => Ok(GroupedVanillaOrder::KillOrFill(o)),
+            AllOrders::TOB(_) => unreachable!()
+        })
+        .unwrap();
+
+    (ShadowOrder::Limit(order), ahead)
+}
+
+/// What `solution` does for the order `id`. `max_q` is the quantity of a limit
+/// order, a top of block order is filled whole if it wins.
+fn shadow_outcome(
+    solution: &PoolSolution,
+    id: &OrderId,
+    max_q: u128,
+    queue_position: usize
+) -> ShadowMatch {
+    let filled_quantity = match &solution.searcher {
+        Some(tob) if tob.order_id == *id => tob.quantity_in,
+        _ => solution
+            .limit
+            .iter()
+            .find(|outcome| outcome.id == *id)
+            .map(|outcome| outcome.fill_amount(max_q))
+            .unwrap_or_default()
+    };
+
+    ShadowMatch { clearing_price: *solution.ucp, filled_quantity, queue_position }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+    use angstrom_types::{
+        orders::{OrderFillState, OrderOutcome, OrderPriorityData},
+        sol_bindings::{grouped_orders::StandingVariants, rpc_orders::ExactStandingOrder}
+    };
+
+    use super::*;
+
+    fn limit(is_bid: bool, price: u64, amount: u128) -> OrderWithStorageData<AllOrders> {
+        let order = ExactStandingOrder { amount, ..Default::default() };
+        OrderWithStorageData {
+            order: AllOrders::Standing(StandingVariants::Exact(order)),
+            is_bid,
+            priority_data: OrderPriorityData { price: U256::from(price), ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    fn vanilla(
+        order: OrderWithStorageData<AllOrders>
+    ) -> OrderWithStorageData<GroupedVanillaOrder> {
+        let ShadowOrder::Limit(order) = shadow_book(order, &[], &[]).0 else { unreachable!() };
+        order
+    }
+
+    #[test]
+    fn counts_the_orders_ahead_on_the_same_side() {
+        let book =
+            [limit(true, 1, 10), limit(true, 2, 10), limit(true, 3, 10), limit(false, 1, 10)]
+                .map(vanilla);
+
+        let (shadow, ahead) = shadow_book(limit(true, 2, 10), &book, &[]);
+        assert!(matches!(shadow, ShadowOrder::Limit(_)));
+        // resting orders at the same price are ahead
+        assert_eq!(ahead, 2);
+
+        let (_, ahead) = shadow_book(limit(false, 2, 10), &book, &[]);
+        assert_eq!(ahead, 1);
+    }
+
+    #[test]
+    fn reads_the_fill_of_the_order() {
+        let order = limit(true, 2, 10);
+        let solution = PoolSolution {
+            ucp: U256::from(7).into(),
+            limit: vec![OrderOutcome {
+                id:      order.order_id,
+                outcome: OrderFillState::PartialFill(4)
+            }],
+            ..Default::default()
+        };
+
+        let outcome = shadow_outcome(&solution, &order.order_id, 10, 3);
+        assert_eq!(
+            outcome,
+            ShadowMatch { clearing_price: U256::from(7), filled_quantity: 4, queue_position: 3 }
+        );
+
+        let unmatched = PoolSolution::default();
+        assert_eq!(shadow_outcome(&unmatched, &order.order_id, 10, 3).filled_quantity, 0);
+    }
+}
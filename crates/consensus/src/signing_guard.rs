@@ -0,0 +1,294 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf}
+};
+
+use alloy::primitives::{BlockNumber, B256};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
+
+/// How many heights behind the highest signed one we keep records for. We
+/// only ever sign for the current height, so anything older than this will
+/// never be asked for again.
+pub const SIGNING_GUARD_RETENTION: u64 = 256;
+
+/// The consensus messages this node signs, at most one of each per height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SignedMessageKind {
+    PreProposal,
+    PreProposalAggregation,
+    Proposal,
+    SubmissionApproval
+}
+
+/// A message we released, what the guard stores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedRecord {
+    pub height: BlockNumber,
+    pub kind:   SignedMessageKind,
+    pub hash:   B256
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SigningGuardError {
+    #[error("already signed {kind:?} {signed} for height {height}, refusing to sign {requested}")]
+    Conflict {
+        height:    BlockNumber,
+        kind:      SignedMessageKind,
+        signed:    B256,
+        requested: B256
+    },
+    #[error("height {height} is older than the signing guard history (latest {latest})")]
+    TooOld { height: BlockNumber, latest: BlockNumber },
+    #[error("signing guard store is corrupted at line {0}")]
+    Corrupted(usize),
+    #[error(transparent)]
+    Io(#[from] std::io::Error)
+}
+
+/// Remembers every consensus message this node has released and refuses to
+/// release a different one for the same slot. When backed by a file, every
+/// record is synced to disk before the message is allowed out, so a node that
+/// restarts, or is restored from a backup of its key, can't equivocate.
+#[derive(Debug, Default)]
+pub struct SigningGuard {
+    records:     HashMap<(BlockNumber, SignedMessageKind), B256>,
+    latest:      BlockNumber,
+    store:       Option<File>,
+    /// where new records are sent once they are durable, e.g to a standby
+    replication: Option<UnboundedSender<SignedRecord>>
+}
+
+impl SigningGuard {
+    /// A guard without a store. It only protects for the lifetime of the
+    /// process.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Loads the records at `path`, creating the file if it doesn't exist.
+    /// Records that fell out of the retention window are compacted away.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SigningGuardError> {
+        let path = path.as_ref();
+        let records = if path.exists() { Self::load(path)? } else { vec![] };
+
+        let mut guard = Self::default();
+        for record in records {
+            guard.latest = guard.latest.max(record.height);
+            guard
+                .records
+                .insert((record.height, record.kind), record.hash);
+        }
+        guard.prune();
+
+        // rewrite the retained records so the file doesn't grow forever. The new
+        // file is synced before it replaces the old one.
+        let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+        let mut file = File::create(&tmp)?;
+        for (&(height, kind), &hash) in &guard.records {
+            Self::write_record(&mut file, SignedRecord { height, kind, hash })?;
+        }
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+
+        guard.store = Some(OpenOptions::new().append(true).open(path)?);
+        Ok(guard)
+    }
+
+    /// Sends every record made from now on to `replication` once it is
+    /// durable.
+    pub fn with_replication(mut self, replication: UnboundedSender<SignedRecord>) -> Self {
+        self.replication = Some(replication);
+        self
+    }
+
+    /// The records within the retention window.
+    pub fn records(&self) -> Vec<SignedRecord> {
+        self.records
+            .iter()
+            .map(|(&(height, kind), &hash)| SignedRecord { height, kind, hash })
+            .collect()
+    }
+
+    fn load(path: &Path) -> Result<Vec<SignedRecord>, SigningGuardError> {
+        let lines = BufReader::new(File::open(path)?)
+            .lines()
+            .collect::<Result<Vec<_>, _>>()?;
+        let last = lines.len().saturating_sub(1);
+
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(i, line)| match serde_json::from_str(line) {
+                Ok(record) => Some(Ok(record)),
+                // a crash in the middle of a write leaves a partial last line. That record
+                // was never synced, so the message it guarded was never released.
+                Err(_) if i == last => {
+                    tracing::warn!(line = i + 1, "dropping partial signing guard record");
+                    None
+                }
+                Err(_) => Some(Err(SigningGuardError::Corrupted(i + 1)))
+            })
+            .collect()
+    }
+
+    fn write_record(file: &mut File, record: SignedRecord) -> Result<(), SigningGuardError> {
+        let mut line = serde_json::to_vec(&record).expect("record is always serializable");
+        line.push(b'\n');
+        file.write_all(&line)?;
+
+        Ok(())
+    }
+
+    /// Checks whether signing `hash` for the given slot is safe, without
+    /// recording it.
+    pub fn check(
+        &self,
+        height: BlockNumber,
+        kind: SignedMessageKind,
+        hash: B256
+    ) -> Result<(), SigningGuardError> {
+        if height + SIGNING_GUARD_RETENTION < self.latest {
+            return Err(SigningGuardError::TooOld { height, latest: self.latest })
+        }
+
+        match self.records.get(&(height, kind)) {
+            Some(&signed) if signed != hash => {
+                Err(SigningGuardError::Conflict { height, kind, signed, requested: hash })
+            }
+            _ => Ok(())
+        }
+    }
+
+    /// Records that we release `hash` for the given slot. Signing the exact
+    /// same message again is allowed, anything else for the slot is refused.
+    /// The record is durable once this returns.
+    pub fn record(
+        &mut self,
+        height: BlockNumber,
+        kind: SignedMessageKind,
+        hash: B256
+    ) -> Result<(), SigningGuardError> {
+        self.check(height, kind, hash)?;
+        if self.records.contains_key(&(height, kind)) {
+            return Ok(())
+        }
+
+        if let Some(store) = self.store.as_mut() {
+            Self::write_record(store, SignedRecord { height, kind, hash })?;
+            store.sync_data()?;
+        }
+
+        self.records.insert((height, kind), hash);
+        if height > self.latest {
+            self.latest = height;
+            self.prune();
+        }
+        if let Some(replication) = &self.replication {
+            let _ = replication.send(SignedRecord { height, kind, hash });
+        }
+
+        Ok(())
+    }
+
+    fn prune(&mut self) {
+        let oldest = self.latest.saturating_sub(SIGNING_GUARD_RETENTION);
+        self.records.retain(|(height, _), _| *height >= oldest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_conflicting_message() {
+        let mut guard = SigningGuard::in_memory();
+        let hash = B256::random();
+
+        guard
+            .record(10, SignedMessageKind::PreProposal, hash)
+            .unwrap();
+        // the same message can be signed again
+        guard
+            .record(10, SignedMessageKind::PreProposal, hash)
+            .unwrap();
+        // another kind or height is a different slot
+        guard
+            .record(10, SignedMessageKind::Proposal, B256::random())
+            .unwrap();
+        guard
+            .record(11, SignedMessageKind::PreProposal, B256::random())
+            .unwrap();
+
+        assert!(matches!(
+            guard.record(10, SignedMessageKind::PreProposal, B256::random()),
+            Err(SigningGuardError::Conflict { height: 10, .. })
+        ));
+    }
+
+    #[test]
+    fn records_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signing_guard.jsonl");
+        let hash = B256::random();
+
+        let mut guard = SigningGuard::open(&path).unwrap();
+        guard
+            .record(10, SignedMessageKind::PreProposalAggregation, hash)
+            .unwrap();
+        drop(guard);
+
+        let mut guard = SigningGuard::open(&path).unwrap();
+        assert!(guard
+            .record(10, SignedMessageKind::PreProposalAggregation, B256::random())
+            .is_err());
+        guard
+            .record(10, SignedMessageKind::PreProposalAggregation, hash)
+            .unwrap();
+    }
+
+    #[test]
+    fn partial_last_record_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("signing_guard.jsonl");
+        let hash = B256::random();
+
+        let mut guard = SigningGuard::open(&path).unwrap();
+        guard.record(5, SignedMessageKind::Proposal, hash).unwrap();
+        drop(guard);
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"height":6,"kind":"prop"#).unwrap();
+        drop(file);
+
+        let mut guard = SigningGuard::open(&path).unwrap();
+        assert!(guard
+            .record(5, SignedMessageKind::Proposal, B256::random())
+            .is_err());
+        guard
+            .record(6, SignedMessageKind::Proposal, B256::random())
+            .unwrap();
+    }
+
+    #[test]
+    fn old_heights_are_pruned_and_refused() {
+        let mut guard = SigningGuard::in_memory();
+        guard
+            .record(1, SignedMessageKind::PreProposal, B256::random())
+            .unwrap();
+        guard
+            .record(1 + SIGNING_GUARD_RETENTION + 1, SignedMessageKind::PreProposal, B256::random())
+            .unwrap();
+
+        assert_eq!(guard.records.len(), 1);
+        assert!(matches!(
+            guard.record(1, SignedMessageKind::PreProposal, B256::random()),
+            Err(SigningGuardError::TooOld { height: 1, .. })
+        ));
+    }
+}
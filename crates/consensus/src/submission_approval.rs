@@ -0,0 +1,204 @@
+//! k-of-n approval of the leader's settlement transaction.
+//!
+//! When approvals are required, the leader signs its settlement transaction
+//! but doesn't broadcast it. It sends the exact bytes to the approvers instead,
+//! and only broadcasts them once `threshold` of the approvers committed to
+//! them. An approver checks that the transaction settles a bundle at the
+//! Angstrom contract whose surplus charges fund its rewards and that only
+//! fills orders it holds, and approves at most one transaction per height. The
+//! leader key alone can then neither get contents the approvers didn't see
+//! submitted, nor two different transactions.
+//!
+//! The bundle isn't checked against the solutions of the round. The leader
+//! only broadcasts its proposal once the bundle landed, so the approvers don't
+//! have it yet, and a golfed bundle is laid out differently than the one built
+//! from the proposal anyway. Fills that differ from the proposal are caught
+//! by the validators verifying it afterwards.
+//!
+//! Every validator has to be configured with the same approvers.
+
+use std::collections::HashSet;
+
+use alloy::{
+    consensus::{Transaction, TxEnvelope},
+    eips::eip2718::Decodable2718,
+    primitives::{Address, BlockNumber, B256},
+    sol_types::SolCall
+};
+use angstrom_types::{
+    consensus::{SubmissionApproval, SubmissionApprovalRequest},
+    contract_bindings::angstrom::Angstrom,
+    contract_payloads::angstrom::AngstromBundle,
+    primitive::PeerId
+};
+
+/// Who approves the settlement transactions, and how many of them have to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmissionApprovalConfig {
+    pub approvers: HashSet<PeerId>,
+    pub threshold: usize
+}
+
+impl SubmissionApprovalConfig {
+    pub fn new(approvers: impl IntoIterator<Item = PeerId>, threshold: usize) -> Self {
+        // a threshold of zero would let the leader submit on its own
+        Self { approvers: approvers.into_iter().collect(), threshold: threshold.max(1) }
+    }
+
+    pub fn is_approver(&self, peer_id: &PeerId) -> bool {
+        self.approvers.contains(peer_id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ApprovalRefusal {
+    #[error("we don't approve submissions")]
+    NotAnApprover,
+    #[error("request for height {requested}, we are at {current}")]
+    WrongHeight { requested: BlockNumber, current: BlockNumber },
+    #[error("requested by {0}, who doesn't lead the round")]
+    NotTheLeader(PeerId),
+    #[error("the transaction doesn't decode")]
+    InvalidTransaction,
+    #[error("the transaction isn't sent to the angstrom contract")]
+    WrongTarget,
+    #[error("the transaction doesn't carry a bundle")]
+    NoBundle,
+    #[error("invalid bundle rewards - {0}")]
+    InvalidRewards(String),
+    #[error("the bundle settles order {0}, which we don't hold")]
+    UnknownOrder(B256),
+    #[error("we already approved another transaction for this height")]
+    AlreadyApproved
+}
+
+/// Checks the contents of a settlement transaction, returning the hash to
+/// approve. Only the target, the rewards and the orders of the bundle are
+/// checked, not its fills. `landing_block` is the block the bundle is for, `holds_order`
+/// whether we hold an order.
+pub fn check_submission(
+    request: &SubmissionApprovalRequest,
+    angstrom_address: Address,
+    landing_block: BlockNumber,
+    holds_order: impl Fn(&B256) -> bool
+) -> Result<B256, ApprovalRefusal> {
+    let tx = TxEnvelope::decode_2718(&mut request.raw_tx.as_ref())
+        .map_err(|_| ApprovalRefusal::InvalidTransaction)?;
+    if tx.to() != Some(angstrom_address) {
+        return Err(ApprovalRefusal::WrongTarget)
+    }
+
+    let encoded = Angstrom::executeCall::abi_decode(tx.input(), true)
+        .map_err(|_| ApprovalRefusal::NoBundle)?
+        .encoded;
+    let (_, bundle) = AngstromBundle::pade_decode_versioned(&mut encoded.as_ref())
+        .map_err(|_| ApprovalRefusal::NoBundle)?;
+    bundle
+        .verify_rewards()
+        .map_err(|e| ApprovalRefusal::InvalidRewards(e.to_string()))?;
+
+    if let Some(unknown) = bundle
+        .get_order_hashes(landing_block)
+        .find(|hash| !holds_order(hash))
+    {
+        return Err(ApprovalRefusal::UnknownOrder(unknown))
+    }
+
+    Ok(request.tx_hash())
+}
+
+/// The approvals of our settlement transaction gathered so far.
+#[derive(Debug, Clone)]
+pub struct ApprovalCollector {
+    block_height: BlockNumber,
+    tx_hash:      B256,
+    config:       SubmissionApprovalConfig,
+    approved_by:  HashSet<PeerId>
+}
+
+impl ApprovalCollector {
+    pub fn new(config: SubmissionApprovalConfig, block_height: BlockNumber, tx_hash: B256) -> Self {
+        Self { block_height, tx_hash, config, approved_by: HashSet::new() }
+    }
+
+    /// Counts the approval if it is a valid one of an approver, for our
+    /// transaction. Returns whether it was counted.
+    pub fn add(&mut self, approval: &SubmissionApproval) -> bool {
+        approval.block_height == self.block_height
+            && approval.tx_hash == self.tx_hash
+            && self.config.is_approver(&approval.source)
+            && approval.is_valid()
+            && self.approved_by.insert(approval.source)
+    }
+
+    pub fn is_approved(&self) -> bool {
+        self.approved_by.len() >= self.config.threshold
+    }
+
+    pub fn approvers(&self) -> impl Iterator<Item = &PeerId> {
+        self.config.approvers.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        consensus::{SignableTransaction, TxEip1559},
+        eips::eip2718::Encodable2718,
+        primitives::{Bytes, PrimitiveSignature, TxKind}
+    };
+    use angstrom_types::primitive::AngstromSigner;
+
+    use super::*;
+
+    fn request(to: Address, bundle: &AngstromBundle) -> SubmissionApprovalRequest {
+        let input = Angstrom::executeCall::new((bundle.pade_encode_for_submission().into(),))
+            .abi_encode()
+            .into();
+        let tx = TxEip1559 { to: TxKind::Call(to), input, ..Default::default() };
+        let tx = TxEnvelope::from(tx.into_signed(PrimitiveSignature::test_signature()));
+
+        SubmissionApprovalRequest { block_height: 10, raw_tx: tx.encoded_2718().into() }
+    }
+
+    #[test]
+    fn only_bundles_for_the_angstrom_contract_are_approved() {
+        let angstrom = Address::with_last_byte(1);
+        let bundle = AngstromBundle::new(vec![], vec![], vec![], vec![], vec![]);
+
+        let valid = request(angstrom, &bundle);
+        assert_eq!(check_submission(&valid, angstrom, 11, |_| false), Ok(valid.tx_hash()));
+
+        let elsewhere = request(Address::with_last_byte(2), &bundle);
+        assert_eq!(
+            check_submission(&elsewhere, angstrom, 11, |_| true),
+            Err(ApprovalRefusal::WrongTarget)
+        );
+
+        let garbage =
+            SubmissionApprovalRequest { block_height: 10, raw_tx: Bytes::from([1, 2]) };
+        assert_eq!(
+            check_submission(&garbage, angstrom, 11, |_| true),
+            Err(ApprovalRefusal::InvalidTransaction)
+        );
+    }
+
+    #[test]
+    fn approvals_are_counted_once_per_approver() {
+        let approvers = [AngstromSigner::random(), AngstromSigner::random()];
+        let outsider = AngstromSigner::random();
+        let config = SubmissionApprovalConfig::new(approvers.iter().map(AngstromSigner::id), 2);
+        let tx_hash = B256::repeat_byte(1);
+        let mut collector = ApprovalCollector::new(config, 10, tx_hash);
+
+        assert!(collector.add(&SubmissionApproval::new(10, tx_hash, &approvers[0])));
+        assert!(!collector.add(&SubmissionApproval::new(10, tx_hash, &approvers[0])));
+        assert!(!collector.add(&SubmissionApproval::new(10, tx_hash, &outsider)));
+        assert!(!collector.add(&SubmissionApproval::new(10, B256::ZERO, &approvers[1])));
+        assert!(!collector.add(&SubmissionApproval::new(11, tx_hash, &approvers[1])));
+        assert!(!collector.is_approved());
+
+        assert!(collector.add(&SubmissionApproval::new(10, tx_hash, &approvers[1])));
+        assert!(collector.is_approved());
+    }
+}
@@ -0,0 +1,284 @@
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf}
+};
+
+use alloy::primitives::{BlockNumber, B256};
+use serde::{Deserialize, Serialize};
+
+/// How many heights behind the highest submitted one we keep records for. We
+/// only ever submit for the current height.
+pub const SUBMISSION_LEDGER_RETENTION: u64 = 256;
+
+/// A bundle we broadcast, keyed by the height of the round it settles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmissionRecord {
+    pub height:      BlockNumber,
+    pub bundle_hash: B256
+}
+
+/// A line of the ledger file. Entries are replayed in order, so a height that
+/// was cleared can be submitted for again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "entry", rename_all = "camelCase")]
+enum LedgerEntry {
+    Submitted(SubmissionRecord),
+    Cleared { height: BlockNumber }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SubmissionLedgerError {
+    #[error(
+        "already submitted bundle {submitted} for height {height}, refusing to submit {requested}"
+    )]
+    AlreadySubmitted { height: BlockNumber, submitted: B256, requested: B256 },
+    #[error("height {height} is older than the submission ledger history (latest {latest})")]
+    TooOld { height: BlockNumber, latest: BlockNumber },
+    #[error("submission ledger store is corrupted at line {0}")]
+    Corrupted(usize),
+    #[error(transparent)]
+    Io(#[from] std::io::Error)
+}
+
+/// Remembers the bundles this node broadcast and refuses to broadcast a second
+/// one for a height, so a node that restarts in the middle of a round, or
+/// rebuilds its proposal, can't settle the same height twice. When backed by a
+/// file, every record is synced to disk before the bundle goes out.
+///
+/// A height can be released again with [`Self::clear`], for manual recovery
+/// when a recorded bundle is known to never have reached the chain.
+#[derive(Debug, Default)]
+pub struct SubmissionLedger {
+    records: HashMap<BlockNumber, B256>,
+    latest:  BlockNumber,
+    store:   Option<File>
+}
+
+impl SubmissionLedger {
+    /// A ledger without a store. It only protects for the lifetime of the
+    /// process.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Loads the ledger at `path`, creating the file if it doesn't exist.
+    /// Records that fell out of the retention window, and cleared ones, are
+    /// compacted away.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SubmissionLedgerError> {
+        let path = path.as_ref();
+        let entries = if path.exists() { Self::load(path)? } else { vec![] };
+
+        let mut ledger = Self::default();
+        for entry in entries {
+            match entry {
+                LedgerEntry::Submitted(record) => {
+                    ledger.latest = ledger.latest.max(record.height);
+                    ledger.records.insert(record.height, record.bundle_hash);
+                }
+                LedgerEntry::Cleared { height } => {
+                    ledger.records.remove(&height);
+                }
+            }
+        }
+        ledger.prune();
+
+        // the new file is synced before it replaces the old one
+        let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+        let mut file = File::create(&tmp)?;
+        for record in ledger.records() {
+            Self::write_entry(&mut file, LedgerEntry::Submitted(record))?;
+        }
+        file.sync_all()?;
+        std::fs::rename(&tmp, path)?;
+
+        ledger.store = Some(OpenOptions::new().append(true).open(path)?);
+        Ok(ledger)
+    }
+
+    /// The bundles submitted within the retention window.
+    pub fn records(&self) -> Vec<SubmissionRecord> {
+        self.records
+            .iter()
+            .map(|(&height, &bundle_hash)| SubmissionRecord { height, bundle_hash })
+            .collect()
+    }
+
+    pub fn get(&self, height: BlockNumber) -> Option<B256> {
+        self.records.get(&height).copied()
+    }
+
+    fn load(path: &Path) -> Result<Vec<LedgerEntry>, SubmissionLedgerError> {
+        let lines = BufReader::new(File::open(path)?)
+            .lines()
+            .collect::<Result<Vec<_>, _>>()?;
+        let last = lines.len().saturating_sub(1);
+
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(i, line)| match serde_json::from_str(line) {
+                Ok(entry) => Some(Ok(entry)),
+                // a partial last line was never synced, so the bundle it was
+                // for was never broadcast
+                Err(_) if i == last => {
+                    tracing::warn!(line = i + 1, "dropping partial submission ledger entry");
+                    None
+                }
+                Err(_) => Some(Err(SubmissionLedgerError::Corrupted(i + 1)))
+            })
+            .collect()
+    }
+
+    fn write_entry(file: &mut File, entry: LedgerEntry) -> Result<(), SubmissionLedgerError> {
+        let mut line = serde_json::to_vec(&entry).expect("entry is always serializable");
+        line.push(b'\n');
+        file.write_all(&line)?;
+
+        Ok(())
+    }
+
+    fn append(&mut self, entry: LedgerEntry) -> Result<(), SubmissionLedgerError> {
+        if let Some(store) = self.store.as_mut() {
+            Self::write_entry(store, entry)?;
+            store.sync_data()?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether a bundle can be submitted for `height`, without
+    /// recording it.
+    pub fn check(
+        &self,
+        height: BlockNumber,
+        bundle_hash: B256
+    ) -> Result<(), SubmissionLedgerError> {
+        if height + SUBMISSION_LEDGER_RETENTION < self.latest {
+            return Err(SubmissionLedgerError::TooOld { height, latest: self.latest })
+        }
+
+        match self.records.get(&height) {
+            Some(&submitted) => Err(SubmissionLedgerError::AlreadySubmitted {
+                height,
+                submitted,
+                requested: bundle_hash
+            }),
+            None => Ok(())
+        }
+    }
+
+    /// Records that we submit `bundle_hash` for `height`. Anything already
+    /// submitted for the height, even the same bundle, is refused. The record
+    /// is durable once this returns.
+    pub fn record(
+        &mut self,
+        height: BlockNumber,
+        bundle_hash: B256
+    ) -> Result<(), SubmissionLedgerError> {
+        self.check(height, bundle_hash)?;
+        self.append(LedgerEntry::Submitted(SubmissionRecord { height, bundle_hash }))?;
+
+        self.records.insert(height, bundle_hash);
+        if height > self.latest {
+            self.latest = height;
+            self.prune();
+        }
+
+        Ok(())
+    }
+
+    /// Forgets the bundle submitted for `height`, allowing another one to be
+    /// submitted for it. Returns the bundle that was recorded.
+    pub fn clear(&mut self, height: BlockNumber) -> Result<Option<B256>, SubmissionLedgerError> {
+        if !self.records.contains_key(&height) {
+            return Ok(None)
+        }
+        self.append(LedgerEntry::Cleared { height })?;
+
+        Ok(self.records.remove(&height))
+    }
+
+    fn prune(&mut self) {
+        let oldest = self.latest.saturating_sub(SUBMISSION_LEDGER_RETENTION);
+        self.records.retain(|height, _| *height >= oldest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_second_submission_for_a_height() {
+        let mut ledger = SubmissionLedger::in_memory();
+        let bundle = B256::random();
+
+        ledger.record(10, bundle).unwrap();
+        ledger.record(11, B256::random()).unwrap();
+
+        assert!(matches!(
+            ledger.record(10, B256::random()),
+            Err(SubmissionLedgerError::AlreadySubmitted { height: 10, submitted, .. })
+                if submitted == bundle
+        ));
+        assert!(ledger.record(10, bundle).is_err());
+    }
+
+    #[test]
+    fn records_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("submission_ledger.jsonl");
+        let bundle = B256::random();
+
+        let mut ledger = SubmissionLedger::open(&path).unwrap();
+        ledger.record(10, bundle).unwrap();
+        drop(ledger);
+
+        let mut ledger = SubmissionLedger::open(&path).unwrap();
+        assert_eq!(ledger.get(10), Some(bundle));
+        assert!(ledger.record(10, B256::random()).is_err());
+    }
+
+    #[test]
+    fn cleared_heights_can_be_submitted_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("submission_ledger.jsonl");
+        let bundle = B256::random();
+
+        let mut ledger = SubmissionLedger::open(&path).unwrap();
+        ledger.record(10, bundle).unwrap();
+        ledger.record(11, B256::random()).unwrap();
+        assert_eq!(ledger.clear(10).unwrap(), Some(bundle));
+        assert_eq!(ledger.clear(12).unwrap(), None);
+        drop(ledger);
+
+        // the clear is durable too
+        let mut ledger = SubmissionLedger::open(&path).unwrap();
+        assert_eq!(ledger.get(10), None);
+        assert!(ledger.get(11).is_some());
+        ledger.record(10, B256::random()).unwrap();
+    }
+
+    #[test]
+    fn partial_last_entry_is_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("submission_ledger.jsonl");
+
+        let mut ledger = SubmissionLedger::open(&path).unwrap();
+        ledger.record(5, B256::random()).unwrap();
+        drop(ledger);
+
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"entry":"submitted","height":6"#)
+            .unwrap();
+        drop(file);
+
+        let mut ledger = SubmissionLedger::open(&path).unwrap();
+        assert!(ledger.record(5, B256::random()).is_err());
+        ledger.record(6, B256::random()).unwrap();
+    }
+}
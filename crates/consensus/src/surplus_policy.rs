@@ -0,0 +1,67 @@
+use alloy::primitives::{I256, U256};
+use matching_engine::verification::Surplus;
+
+/// Default shortfall a proposal may have against the local solve, 1%.
+pub const DEFAULT_MAX_SURPLUS_SHORTFALL_BPS: u32 = 100;
+
+/// Rejects proposals whose solutions are valid but leave the users much worse
+/// off than our own solve of the same orders would, which protects against
+/// lazy or malicious leaders.
+///
+/// Both the surplus over the orders' limits and over filling them at the AMM
+/// price may fall short of the local solve by at most `max_shortfall_bps`
+/// basis points of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SurplusPolicy {
+    pub max_shortfall_bps: u32
+}
+
+impl Default for SurplusPolicy {
+    fn default() -> Self {
+        Self { max_shortfall_bps: DEFAULT_MAX_SURPLUS_SHORTFALL_BPS }
+    }
+}
+
+impl SurplusPolicy {
+    pub fn new(max_shortfall_bps: u32) -> Self {
+        Self { max_shortfall_bps }
+    }
+
+    pub fn accepts(&self, proposed: Surplus, local: Surplus) -> bool {
+        self.within(proposed.vs_limit, local.vs_limit) && self.within(proposed.vs_amm, local.vs_amm)
+    }
+
+    fn within(&self, proposed: I256, local: I256) -> bool {
+        let slack = local
+            .unsigned_abs()
+            .saturating_mul(U256::from(self.max_shortfall_bps))
+            / U256::from(10_000);
+        let slack = I256::try_from(slack).unwrap_or(I256::MAX);
+
+        proposed >= local.saturating_sub(slack)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn surplus(vs_limit: i64, vs_amm: i64) -> Surplus {
+        Surplus {
+            vs_limit: I256::try_from(vs_limit).unwrap(),
+            vs_amm:   I256::try_from(vs_amm).unwrap()
+        }
+    }
+
+    #[test]
+    fn rejects_proposals_short_of_the_local_surplus() {
+        let policy = SurplusPolicy::new(100);
+        let local = surplus(10_000, -1_000);
+
+        assert!(policy.accepts(local, local));
+        assert!(policy.accepts(surplus(9_900, -1_010), local));
+        assert!(policy.accepts(surplus(20_000, 0), local));
+        assert!(!policy.accepts(surplus(9_899, -1_000), local));
+        assert!(!policy.accepts(surplus(10_000, -1_011), local));
+    }
+}
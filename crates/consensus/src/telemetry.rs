@@ -0,0 +1,156 @@
+use std::collections::{HashMap, VecDeque};
+
+use alloy::primitives::BlockNumber;
+use angstrom_types::{consensus::ValidatorTelemetry, primitive::PeerId};
+use serde::{Deserialize, Serialize};
+
+/// Number of blocks of telemetry kept for every validator.
+pub const TELEMETRY_WINDOW: usize = 32;
+
+/// How many times the median of the validators a validator's solve time or
+/// validation latency has to be for it to be underperforming.
+const UNDERPERFORMING_FACTOR: u64 = 2;
+
+/// How a validator did over the last [`TELEMETRY_WINDOW`] blocks it reported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorTelemetrySummary {
+    pub validator:                  PeerId,
+    pub reports:                    usize,
+    pub last_height:                BlockNumber,
+    pub mean_solve_time_ms:         Option<u64>,
+    pub max_solve_time_ms:          Option<u64>,
+    pub mean_book_size:             u64,
+    pub mean_validation_latency_ms: Option<u64>,
+    /// solves or validates orders a lot slower than the other validators,
+    /// which is how a validator looks before it starts missing slots
+    pub underperforming:            bool
+}
+
+/// The telemetry the validators shared, ours included.
+#[derive(Debug, Default)]
+pub struct TelemetryAggregator {
+    reports: HashMap<PeerId, VecDeque<ValidatorTelemetry>>
+}
+
+impl TelemetryAggregator {
+    /// Reports that aren't newer than the last one of the validator are
+    /// dropped.
+    pub fn record(&mut self, validator: PeerId, telemetry: ValidatorTelemetry) {
+        let reports = self.reports.entry(validator).or_default();
+        if reports
+            .back()
+            .is_some_and(|last| last.block_height >= telemetry.block_height)
+        {
+            return
+        }
+
+        reports.push_back(telemetry);
+        if reports.len() > TELEMETRY_WINDOW {
+            reports.pop_front();
+        }
+    }
+
+    pub fn summaries(&self) -> Vec<ValidatorTelemetrySummary> {
+        let mut summaries = self
+            .reports
+            .iter()
+            .filter_map(|(validator, reports)| summarize(*validator, reports))
+            .collect::<Vec<_>>();
+
+        let solve_time = median(summaries.iter().filter_map(|s| s.mean_solve_time_ms));
+        let latency = median(
+            summaries
+                .iter()
+                .filter_map(|s| s.mean_validation_latency_ms)
+        );
+        let slower = |value: Option<u64>, median: Option<u64>| {
+            value
+                .zip(median)
+                .is_some_and(|(value, median)| value > median * UNDERPERFORMING_FACTOR)
+        };
+        for summary in &mut summaries {
+            summary.underperforming = slower(summary.mean_solve_time_ms, solve_time)
+                || slower(summary.mean_validation_latency_ms, latency);
+        }
+
+        summaries.sort_unstable_by_key(|summary| summary.validator);
+        summaries
+    }
+}
+
+fn summarize(
+    validator: PeerId,
+    reports: &VecDeque<ValidatorTelemetry>
+) -> Option<ValidatorTelemetrySummary> {
+    let last_height = reports.back()?.block_height;
+    let solve_times = reports.iter().filter_map(|r| r.solve_time_ms);
+
+    Some(ValidatorTelemetrySummary {
+        validator,
+        reports: reports.len(),
+        last_height,
+        mean_solve_time_ms: mean(solve_times.clone()),
+        max_solve_time_ms: solve_times.max(),
+        mean_book_size: mean(reports.iter().map(|r| r.book_size)).unwrap_or_default(),
+        mean_validation_latency_ms: mean(reports.iter().filter_map(|r| r.validation_latency_ms)),
+        underperforming: false
+    })
+}
+
+fn mean(values: impl Iterator<Item = u64>) -> Option<u64> {
+    let (sum, count) =
+        values.fold((0u64, 0u64), |(sum, count), value| (sum.saturating_add(value), count + 1));
+    (count != 0).then(|| sum / count)
+}
+
+/// The lower median, so that of two validators the slow one stands out.
+fn median(values: impl Iterator<Item = u64>) -> Option<u64> {
+    let mut values = values.collect::<Vec<_>>();
+    values.sort_unstable();
+    values.get(values.len().checked_sub(1)? / 2).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn telemetry(block_height: BlockNumber, solve_time_ms: u64) -> ValidatorTelemetry {
+        ValidatorTelemetry {
+            block_height,
+            solve_time_ms: Some(solve_time_ms),
+            book_size: 10,
+            validation_latency_ms: None
+        }
+    }
+
+    #[test]
+    fn slow_validators_are_flagged() {
+        let (fast, also_fast, slow) = (PeerId::random(), PeerId::random(), PeerId::random());
+        let mut aggregator = TelemetryAggregator::default();
+
+        for height in 1..=TELEMETRY_WINDOW as u64 + 2 {
+            aggregator.record(fast, telemetry(height, 100));
+            aggregator.record(also_fast, telemetry(height, 120));
+            aggregator.record(slow, telemetry(height, 500));
+        }
+        // stale or replayed reports don't count
+        aggregator.record(fast, telemetry(1, 10_000));
+
+        let summaries = aggregator.summaries();
+        let summary = |validator| {
+            summaries
+                .iter()
+                .find(|summary| summary.validator == validator)
+                .unwrap()
+        };
+
+        assert_eq!(summary(fast).reports, TELEMETRY_WINDOW);
+        assert_eq!(summary(fast).mean_solve_time_ms, Some(100));
+        assert_eq!(summary(fast).last_height, TELEMETRY_WINDOW as u64 + 2);
+        assert!(!summary(fast).underperforming);
+        assert!(!summary(also_fast).underperforming);
+        assert!(summary(slow).underperforming);
+        assert_eq!(summary(slow).mean_validation_latency_ms, None);
+    }
+}
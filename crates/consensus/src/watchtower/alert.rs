@@ -0,0 +1,162 @@
+use std::time::Duration;
+
+use alloy::{
+    primitives::{BlockNumber, B256, I256},
+    transports::http::reqwest::{header::CONTENT_TYPE, Client, Url}
+};
+use angstrom_metrics::WatchtowerMetricsWrapper;
+use angstrom_types::primitive::{PeerId, PoolId};
+use futures::{future::BoxFuture, FutureExt};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+/// Alerts kept for subscribers that fall behind.
+pub const ANOMALIES_CHANNEL_SIZE: usize = 64;
+
+/// How long a webhook has to take an alert.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Misbehavior of a round's leader the [`Watchtower`](super::Watchtower)
+/// found, or of another validator we caught during the round. `height` is the
+/// block the round was held on, its bundle lands in the next one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum WatchtowerAlert {
+    /// a solution of the proposal breaks constraints every valid one keeps
+    InvalidSolution {
+        height:     BlockNumber,
+        leader:     PeerId,
+        pool:       PoolId,
+        violations: Vec<String>
+    },
+    /// the proposal leaves the users worse off than the local solve of the
+    /// same orders, by more than the surplus policy allows
+    SurplusShortfall {
+        height:            BlockNumber,
+        leader:            PeerId,
+        proposed_vs_limit: I256,
+        proposed_vs_amm:   I256,
+        local_vs_limit:    I256,
+        local_vs_amm:      I256
+    },
+    /// orders a quorum of the pre-proposals we saw had in time, which the
+    /// proposal leaves out
+    CensoredOrders { height: BlockNumber, leader: PeerId, orders: Vec<B256> },
+    /// orders the proposal fills which the bundle that landed doesn't settle
+    SettlementMismatch { height: BlockNumber, leader: PeerId, orders: Vec<B256> },
+    /// no bundle landed for a round that had orders, `proposed` tells whether
+    /// the leader sent a proposal at all
+    MissedSubmission { height: BlockNumber, leader: Option<PeerId>, proposed: bool },
+    /// orders of a validator's pre-proposal that were never gossiped to us,
+    /// more of them than the content rules tolerate, or orders a proposal fills
+    /// that we couldn't fetch or that didn't validate
+    UnknownOrders { height: BlockNumber, source: PeerId, orders: Vec<B256> },
+    /// pools the proposal commits to other orders in than the ones eligible
+    /// by its pre-proposals
+    BookMismatch { height: BlockNumber, leader: PeerId, pools: Vec<PoolId> }
+}
+
+impl WatchtowerAlert {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::InvalidSolution { .. } => "invalidSolution",
+            Self::SurplusShortfall { .. } => "surplusShortfall",
+            Self::CensoredOrders { .. } => "censoredOrders",
+            Self::SettlementMismatch { .. } => "settlementMismatch",
+            Self::MissedSubmission { .. } => "missedSubmission",
+            Self::UnknownOrders { .. } => "unknownOrders",
+            Self::BookMismatch { .. } => "bookMismatch"
+        }
+    }
+
+    pub fn height(&self) -> BlockNumber {
+        match self {
+            Self::InvalidSolution { height, .. }
+            | Self::SurplusShortfall { height, .. }
+            | Self::CensoredOrders { height, .. }
+            | Self::SettlementMismatch { height, .. }
+            | Self::MissedSubmission { height, .. }
+            | Self::UnknownOrders { height, .. }
+            | Self::BookMismatch { height, .. } => *height
+        }
+    }
+}
+
+/// Where the watchtower raises its alerts: the log, the metrics, its
+/// subscribers and every webhook, which gets each alert POSTed as json.
+#[derive(Clone)]
+pub struct AlertSink {
+    webhooks:    Vec<Url>,
+    client:      Client,
+    metrics:     WatchtowerMetricsWrapper,
+    subscribers: broadcast::Sender<WatchtowerAlert>
+}
+
+impl AlertSink {
+    pub fn new(webhooks: Vec<Url>) -> Self {
+        Self {
+            webhooks,
+            client: Client::new(),
+            metrics: WatchtowerMetricsWrapper::new(),
+            subscribers: broadcast::channel(ANOMALIES_CHANNEL_SIZE).0
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<WatchtowerAlert> {
+        self.subscribers.subscribe()
+    }
+
+    pub(crate) fn set_block_height(&self, block_number: BlockNumber) {
+        self.metrics.set_block_height(block_number);
+    }
+
+    /// Logs and counts `alert`. The returned future delivers it to the
+    /// webhooks.
+    pub(crate) fn raise(&self, alert: WatchtowerAlert) -> BoxFuture<'static, ()> {
+        tracing::warn!(kind = alert.kind(), height = alert.height(), ?alert, "watchtower alert");
+        self.metrics.incr_alerts(alert.kind());
+        let _ = self.subscribers.send(alert.clone());
+
+        let body = serde_json::to_vec(&alert).expect("alerts serialize");
+        let deliveries = self
+            .webhooks
+            .iter()
+            .map(|url| {
+                self.client
+                    .post(url.clone())
+                    .header(CONTENT_TYPE, "application/json")
+                    .timeout(WEBHOOK_TIMEOUT)
+                    .body(body.clone())
+                    .send()
+                    .map(move |res| (url.clone(), res.and_then(|res| res.error_for_status())))
+            })
+            .collect::<Vec<_>>();
+
+        futures::future::join_all(deliveries)
+            .map(|results| {
+                for (url, res) in results {
+                    if let Err(e) = res {
+                        tracing::warn!(%url, %e, "failed to deliver a watchtower alert");
+                    }
+                }
+            })
+            .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alerts_are_tagged_with_their_kind() {
+        let alert =
+            WatchtowerAlert::MissedSubmission { height: 7, leader: None, proposed: false };
+
+        let json = serde_json::to_value(&alert).unwrap();
+        assert_eq!(json["kind"], alert.kind());
+        assert_eq!(json["height"], 7);
+        assert_eq!(json["proposed"], false);
+        assert_eq!(serde_json::from_value::<WatchtowerAlert>(json).unwrap(), alert);
+    }
+}
@@ -0,0 +1,476 @@
+//! Audits the leaders of the network without taking part in consensus.
+//!
+//! The watchtower follows the Strom messages of every round and the chain. It
+//! solves the orders of each proposal itself and checks the proposal against
+//! that, and checks the bundle that lands in the next block against the
+//! proposal. Whatever it finds is raised as a [`WatchtowerAlert`].
+//!
+//! Validators only open Strom sessions to other validators, so the watchtower
+//! has to run with the key of one to receive the rounds. It doesn't sign
+//! anything with it.
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    pin::Pin,
+    task::{Context, Poll}
+};
+
+use alloy::{
+    consensus::BlockHeader,
+    primitives::{Address, BlockNumber, B256}
+};
+use angstrom_eth::settlement::SettlementExtractor;
+use angstrom_network::manager::StromConsensusEvent;
+use angstrom_types::{
+    consensus::{auction_cutoff, PreProposal, Proposal},
+    contract_payloads::angstrom::UniswapAngstromRegistry,
+    orders::PoolSolution,
+    primitive::{PeerId, PoolId}
+};
+use futures::{future::BoxFuture, stream::FuturesUnordered, Future, FutureExt, StreamExt};
+use itertools::Itertools;
+use matching_engine::{book::OrderBook, verification::verify_solution, MatchingEngineHandle};
+use reth_metrics::common::mpsc::UnboundedMeteredReceiver;
+use reth_provider::{CanonStateNotification, CanonStateNotifications};
+use tokio_stream::wrappers::BroadcastStream;
+use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
+
+use crate::{
+    leader_selection::WeightedRoundRobin,
+    rounds::{filter_quorum_orders, pool_snapshots, proposal_books, total_surplus},
+    AngstromValidator, SurplusPolicy
+};
+
+mod alert;
+pub use alert::*;
+
+/// Rounds kept around, for the messages of peers that lag behind.
+const WATCHED_ROUNDS: u64 = 8;
+
+pub struct Watchtower<Matching> {
+    angstrom_address:       Address,
+    leader_selection:       WeightedRoundRobin,
+    /// pre-proposals an order needs to be in to be matched
+    quorum:                 usize,
+    pool_registry:          UniswapAngstromRegistry,
+    uniswap_pools:          SyncedUniswapPools,
+    matching_engine:        Matching,
+    surplus_policy:         SurplusPolicy,
+    canonical_block_stream: BroadcastStream<CanonStateNotification>,
+    strom_consensus_event:  UnboundedMeteredReceiver<StromConsensusEvent>,
+    rounds:                 BTreeMap<BlockNumber, WatchedRound>,
+    /// local solves of the proposals, resolving to what they found wrong
+    audits:                 FuturesUnordered<BoxFuture<'static, Vec<WatchtowerAlert>>>,
+    alerts:                 AlertSink,
+    deliveries:             FuturesUnordered<BoxFuture<'static, ()>>
+}
+
+impl<Matching: MatchingEngineHandle> Watchtower<Matching> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        canonical_block_stream: CanonStateNotifications,
+        strom_consensus_event: UnboundedMeteredReceiver<StromConsensusEvent>,
+        validators: Vec<AngstromValidator>,
+        leader_seed: B256,
+        current_height: BlockNumber,
+        angstrom_address: Address,
+        pool_registry: UniswapAngstromRegistry,
+        uniswap_pools: SyncedUniswapPools,
+        matching_engine: Matching,
+        alerts: AlertSink
+    ) -> Self {
+        let quorum = (2 * validators.len()).div_ceil(3);
+        let leader_selection =
+            WeightedRoundRobin::new(validators, current_height).with_seed(leader_seed);
+
+        Self {
+            angstrom_address,
+            leader_selection,
+            quorum,
+            pool_registry,
+            uniswap_pools,
+            matching_engine,
+            surplus_policy: SurplusPolicy::default(),
+            canonical_block_stream: BroadcastStream::new(canonical_block_stream),
+            strom_consensus_event,
+            rounds: BTreeMap::new(),
+            audits: FuturesUnordered::new(),
+            alerts,
+            deliveries: FuturesUnordered::new()
+        }
+    }
+
+    /// How much worse than the local solve a proposal may be before it is
+    /// raised.
+    pub fn with_surplus_policy(mut self, surplus_policy: SurplusPolicy) -> Self {
+        self.surplus_policy = surplus_policy;
+        self
+    }
+
+    fn raise(&mut self, alert: WatchtowerAlert) {
+        self.deliveries.push(self.alerts.raise(alert));
+    }
+
+    fn on_blockchain_state(&mut self, notification: CanonStateNotification) {
+        let tip = notification.tip();
+        let height = tip.number();
+        self.alerts.set_block_height(height);
+
+        // the bundle of the previous round lands in this block, one that reverted
+        // settled nothing
+        let receipts = notification
+            .committed()
+            .receipts_by_block_hash(tip.hash())
+            .unwrap_or_default();
+        let settled = SettlementExtractor::new(self.angstrom_address)
+            .settled_bundles(&tip.body().transactions, &receipts)
+            .into_iter()
+            .next()
+            .map(|(_, bundle)| bundle.get_order_hashes(height).collect::<HashSet<_>>());
+        if let Some(alert) = height.checked_sub(1).and_then(|previous| {
+            self.rounds
+                .get(&previous)?
+                .audit_settlement(previous, settled.as_ref())
+        }) {
+            self.raise(alert);
+        }
+
+        let round = self.rounds.entry(height).or_default();
+        round.leader = self.leader_selection.get_leader(height);
+        round.auction_cutoff = auction_cutoff(tip.timestamp());
+        self.rounds
+            .retain(|round_height, _| round_height + WATCHED_ROUNDS > height);
+    }
+
+    fn on_network_event(&mut self, event: StromConsensusEvent) {
+        let height = event.block_height();
+        let Some(newest) = self.rounds.keys().next_back() else { return };
+        // rounds that are too old were pruned, rounds ahead of our view of the chain
+        // are tracked until it catches up
+        if height + WATCHED_ROUNDS <= *newest {
+            return
+        }
+
+        match event {
+            StromConsensusEvent::PreProposal(_, pre_proposal) => {
+                self.on_pre_proposal(pre_proposal);
+            }
+            StromConsensusEvent::PreProposalAgg(_, aggregation) => {
+                aggregation
+                    .pre_proposals
+                    .into_iter()
+                    .for_each(|pre_proposal| self.on_pre_proposal(pre_proposal));
+            }
+            StromConsensusEvent::Proposal(_, proposal) => self.on_proposal(proposal),
+            // nothing to audit in how fast the validators are, approvals only
+            // gate what the leader broadcasts
+            StromConsensusEvent::Telemetry(..)
+            | StromConsensusEvent::ApprovalRequest(..)
+            | StromConsensusEvent::Approval(..) => {}
+        }
+    }
+
+    fn on_pre_proposal(&mut self, pre_proposal: PreProposal) {
+        let height = pre_proposal.block_height;
+        if !pre_proposal.is_valid(&height) {
+            return
+        }
+
+        self.rounds
+            .entry(height)
+            .or_default()
+            .pre_proposals
+            .entry(pre_proposal.source)
+            .or_insert(pre_proposal);
+    }
+
+    fn on_proposal(&mut self, proposal: Proposal) {
+        let height = proposal.block_height;
+        let round = self.rounds.entry(height).or_default();
+        if round.proposal.is_some() || !proposal.is_valid(&height) {
+            return
+        }
+        if round.leader.is_some_and(|leader| leader != proposal.source) {
+            tracing::warn!(
+                %height,
+                source=?proposal.source,
+                "proposal from a peer that isn't the leader"
+            );
+            return
+        }
+        round.proposal = Some(proposal.clone());
+        let censored = round.censored_orders(&proposal, self.quorum);
+        let auction_cutoff = round.auction_cutoff;
+
+        if !censored.is_empty() {
+            self.raise(WatchtowerAlert::CensoredOrders {
+                height,
+                leader: proposal.source,
+                orders: censored
+            });
+        }
+
+        // solve the orders of the proposal the way the validators do
+        let (mut limit, mut searcher) = (Vec::new(), Vec::new());
+        for pre_proposal in proposal.flattened_pre_proposals() {
+            let (pre_limit, pre_searcher) = pre_proposal.orders_received_by(auction_cutoff);
+            limit.extend(pre_limit);
+            searcher.extend(pre_searcher);
+        }
+        let limit = filter_quorum_orders(limit, self.quorum);
+        let searcher = filter_quorum_orders(searcher, self.quorum);
+        let snapshots = pool_snapshots(&self.uniswap_pools, &self.pool_registry);
+        let books = proposal_books(&proposal, &snapshots);
+
+        let matcher = self.matching_engine.clone();
+        let surplus_policy = self.surplus_policy;
+        self.audits.push(
+            async move {
+                match matcher.solve_pools(limit, searcher, snapshots).await {
+                    Ok((local, _)) => audit_solutions(&proposal, &books, &local, surplus_policy),
+                    Err(e) => {
+                        tracing::warn!(%height, %e, "failed to solve a proposal's orders");
+                        vec![]
+                    }
+                }
+            }
+            .boxed()
+        );
+    }
+}
+
+/// Checks the solutions of `proposal` against the books it was solved from
+/// and the `local` solve of the same orders.
+fn audit_solutions(
+    proposal: &Proposal,
+    books: &HashMap<PoolId, OrderBook>,
+    local: &[PoolSolution],
+    surplus_policy: SurplusPolicy
+) -> Vec<WatchtowerAlert> {
+    let (height, leader) = (proposal.block_height, proposal.source);
+    let invalid = proposal
+        .solutions
+        .iter()
+        .map(|solution| match books.get(&solution.id) {
+            Some(book) => verify_solution(book, solution),
+            None => verify_solution(&OrderBook::default(), solution)
+        })
+        .filter(|report| !report.is_valid())
+        .map(|report| WatchtowerAlert::InvalidSolution {
+            height,
+            leader,
+            pool: report.pool,
+            violations: report.violations.iter().map(ToString::to_string).collect()
+        })
+        .collect::<Vec<_>>();
+    if !invalid.is_empty() {
+        return invalid
+    }
+
+    let proposed = total_surplus(books, &proposal.solutions);
+    let local = total_surplus(books, local);
+    if surplus_policy.accepts(proposed, local) {
+        return vec![]
+    }
+
+    vec![WatchtowerAlert::SurplusShortfall {
+        height,
+        leader,
+        proposed_vs_limit: proposed.vs_limit,
+        proposed_vs_amm: proposed.vs_amm,
+        local_vs_limit: local.vs_limit,
+        local_vs_amm: local.vs_amm
+    }]
+}
+
+/// What the watchtower saw of a round.
+#[derive(Debug)]
+struct WatchedRound {
+    leader:         Option<PeerId>,
+    /// unknown until the block the round is held on arrives
+    auction_cutoff: u64,
+    pre_proposals:  HashMap<PeerId, PreProposal>,
+    proposal:       Option<Proposal>
+}
+
+impl Default for WatchedRound {
+    fn default() -> Self {
+        Self {
+            leader:         None,
+            auction_cutoff: u64::MAX,
+            pre_proposals:  HashMap::new(),
+            proposal:       None
+        }
+    }
+}
+
+impl WatchedRound {
+    /// The orders a quorum of the pre-proposals we saw had in time, which
+    /// don't reach it among the pre-proposals of `proposal`.
+    fn censored_orders(&self, proposal: &Proposal, quorum: usize) -> Vec<B256> {
+        let included = self.quorum_orders(proposal.flattened_pre_proposals().iter(), quorum);
+
+        self.quorum_orders(self.pre_proposals.values(), quorum)
+            .difference(&included)
+            .copied()
+            .sorted()
+            .collect()
+    }
+
+    fn quorum_orders<'a>(
+        &self,
+        pre_proposals: impl Iterator<Item = &'a PreProposal>,
+        quorum: usize
+    ) -> HashSet<B256> {
+        pre_proposals
+            .flat_map(|pre_proposal| {
+                pre_proposal
+                    .receipts
+                    .iter()
+                    .filter(|receipt| receipt.received_at <= self.auction_cutoff)
+                    .map(|receipt| receipt.order_hash)
+                    .unique()
+            })
+            .counts()
+            .into_iter()
+            .filter(|(_, count)| *count >= quorum)
+            .map(|(order, _)| order)
+            .collect()
+    }
+
+    /// Checks the bundle that settled the round, `settled` being the orders it
+    /// did, against the proposal.
+    fn audit_settlement(
+        &self,
+        height: BlockNumber,
+        settled: Option<&HashSet<B256>>
+    ) -> Option<WatchtowerAlert> {
+        let Some(proposal) = &self.proposal else {
+            // a round without orders has nothing to propose
+            let had_orders = self
+                .pre_proposals
+                .values()
+                .any(|pre_proposal| !pre_proposal.receipts.is_empty());
+            return (had_orders && settled.is_none()).then_some(WatchtowerAlert::MissedSubmission {
+                height,
+                leader: self.leader,
+                proposed: false
+            })
+        };
+
+        let filled = proposal
+            .solutions
+            .iter()
+            .flat_map(|solution| {
+                solution
+                    .limit
+                    .iter()
+                    .filter(|outcome| outcome.is_filled())
+                    .map(|outcome| outcome.id.hash)
+                    .chain(solution.searcher.iter().map(|order| order.order_id.hash))
+            })
+            .collect::<HashSet<_>>();
+        if filled.is_empty() {
+            return None
+        }
+
+        let Some(settled) = settled else {
+            return Some(WatchtowerAlert::MissedSubmission {
+                height,
+                leader: Some(proposal.source),
+                proposed: true
+            })
+        };
+
+        let unsettled = filled
+            .difference(settled)
+            .copied()
+            .sorted()
+            .collect::<Vec<_>>();
+        (!unsettled.is_empty()).then_some(WatchtowerAlert::SettlementMismatch {
+            height,
+            leader: proposal.source,
+            orders: unsettled
+        })
+    }
+}
+
+impl<Matching: MatchingEngineHandle> Future for Watchtower<Matching> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        while let Poll::Ready(Some(msg)) = this.canonical_block_stream.poll_next_unpin(cx) {
+            match msg {
+                Ok(notification) => this.on_blockchain_state(notification),
+                Err(e) => tracing::error!("Error receiving chain state notification: {}", e)
+            };
+        }
+
+        while let Poll::Ready(Some(msg)) = this.strom_consensus_event.poll_next_unpin(cx) {
+            this.on_network_event(msg);
+        }
+
+        while let Poll::Ready(Some(alerts)) = this.audits.poll_next_unpin(cx) {
+            alerts.into_iter().for_each(|alert| this.raise(alert));
+        }
+
+        while let Poll::Ready(Some(())) = this.deliveries.poll_next_unpin(cx) {}
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::consensus::{OrderReceipt, PreProposalAggregation};
+
+    use super::*;
+
+    fn pre_proposal(source: u8, orders: &[(u8, u64)]) -> PreProposal {
+        PreProposal {
+            block_height: 1,
+            source: PeerId::with_last_byte(source),
+            receipts: orders
+                .iter()
+                .map(|(order, received_at)| OrderReceipt {
+                    order_hash:  B256::with_last_byte(*order),
+                    received_at: *received_at
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn orders_left_out_of_the_proposal_are_censored() {
+        let seen = [
+            pre_proposal(1, &[(1, 10), (2, 10), (3, 10)]),
+            pre_proposal(2, &[(1, 10), (2, 10)]),
+            pre_proposal(3, &[(1, 10), (2, 30)])
+        ];
+        let round = WatchedRound {
+            auction_cutoff: 20,
+            pre_proposals: seen
+                .iter()
+                .map(|pre_proposal| (pre_proposal.source, pre_proposal.clone()))
+                .collect(),
+            ..Default::default()
+        };
+
+        // the leader leaves out the only pre-proposal with order 3, and one of the
+        // two that had order 2 in time
+        let proposal = Proposal {
+            block_height: 1,
+            source: PeerId::with_last_byte(3),
+            preproposals: vec![PreProposalAggregation {
+                pre_proposals: vec![seen[1].clone(), seen[2].clone()],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(round.censored_orders(&proposal, 1), vec![B256::with_last_byte(3)]);
+        assert_eq!(round.censored_orders(&proposal, 2), vec![B256::with_last_byte(2)]);
+    }
+}
@@ -0,0 +1,32 @@
+use std::{collections::HashSet, ops::RangeInclusive};
+
+use alloy::primitives::{Address, BlockNumber, B256};
+use reth_ethereum_primitives::{Block, Receipt};
+use reth_provider::{BlockReader, ProviderResult};
+
+use crate::settlement::SettlementExtractor;
+
+/// Hashes of the orders filled in a range of blocks, read from the node's
+/// database. Used to catch up on the blocks missed while the node was down, as
+/// those never went through the canonical state notifications.
+pub fn filled_orders_in_range<P>(
+    provider: &P,
+    angstrom_address: Address,
+    blocks: RangeInclusive<BlockNumber>
+) -> ProviderResult<HashSet<B256>>
+where
+    P: BlockReader<Block = Block, Receipt = Receipt>
+{
+    let extractor = SettlementExtractor::new(angstrom_address);
+    let mut filled = HashSet::new();
+    for block in provider.block_range(blocks)? {
+        let number = block.header.number;
+        let receipts = provider
+            .receipts_by_block(number.into())?
+            .unwrap_or_default();
+        let receipts = receipts.iter().collect::<Vec<_>>();
+        filled.extend(extractor.filled_orders(number, &block.body.transactions, &receipts));
+    }
+
+    Ok(filled)
+}
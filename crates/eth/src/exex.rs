@@ -0,0 +1,124 @@
+//! What the eth ExEx of the node streams into the [`EthDataCleanser`].
+//!
+//! [`EthDataCleanser`]: crate::manager::EthDataCleanser
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc
+};
+
+use alloy::{eips::BlockNumHash, primitives::BlockHash};
+use reth_provider::{CanonStateNotification, Chain};
+
+/// A change of the canonical chain.
+#[derive(Debug, Clone)]
+pub enum ChainUpdate {
+    Commit(Arc<Chain>),
+    Reorg {
+        old: Arc<Chain>,
+        new: Arc<Chain>
+    },
+    /// blocks unwound without a chain replacing them yet
+    Revert(Arc<Chain>)
+}
+
+impl ChainUpdate {
+    /// The canonical tip after the update, which is the parent of the first
+    /// reverted block on a revert.
+    pub fn tip(&self) -> BlockNumHash {
+        match self {
+            Self::Commit(new) | Self::Reorg { new, .. } => new.tip().num_hash(),
+            Self::Revert(old) => {
+                let first = old.first();
+                BlockNumHash::new(first.number - 1, first.parent_hash)
+            }
+        }
+    }
+
+    /// The update as the modules following canonical state notifications see
+    /// it. Reverts have none, the chain the node moves to next reaches them as
+    /// a reorg.
+    pub fn canon_state_notification(&self) -> Option<CanonStateNotification> {
+        match self {
+            Self::Commit(new) => Some(CanonStateNotification::Commit { new: new.clone() }),
+            Self::Reorg { old, new } => {
+                Some(CanonStateNotification::Reorg { old: old.clone(), new: new.clone() })
+            }
+            Self::Revert(_) => None
+        }
+    }
+}
+
+impl From<CanonStateNotification> for ChainUpdate {
+    fn from(notification: CanonStateNotification) -> Self {
+        match notification {
+            CanonStateNotification::Commit { new } => Self::Commit(new),
+            CanonStateNotification::Reorg { old, new } => Self::Reorg { old, new }
+        }
+    }
+}
+
+/// The last block the cleanser turned into events, kept on disk so the ExEx
+/// resumes after it on a restart and no block is handled twice.
+#[derive(Debug, Clone)]
+pub struct ExExCursor {
+    path: PathBuf
+}
+
+impl ExExCursor {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The stored block, if there is one.
+    pub fn load(&self) -> io::Result<Option<BlockNumHash>> {
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e)
+        };
+
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed exex cursor");
+        let (number, hash) = contents.trim().split_once(' ').ok_or_else(invalid)?;
+        let number = number.parse().map_err(|_| invalid())?;
+        let hash = hash.parse::<BlockHash>().map_err(|_| invalid())?;
+
+        Ok(Some(BlockNumHash::new(number, hash)))
+    }
+
+    /// Replaces the stored block. The file is swapped in whole, so a crash
+    /// leaves either the old or the new block.
+    pub fn store(&self, block: BlockNumHash) -> io::Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        std::fs::write(&tmp, format!("{} {}", block.number, block.hash))?;
+        std::fs::rename(tmp, &self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_round_trips() {
+        let dir = std::env::temp_dir().join(format!("exex-cursor-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let cursor = ExExCursor::new(dir.join("cursor"));
+
+        assert_eq!(cursor.load().unwrap(), None);
+
+        let block = BlockNumHash::new(100, BlockHash::random());
+        cursor.store(block).unwrap();
+        assert_eq!(cursor.load().unwrap(), Some(block));
+
+        std::fs::write(cursor.path(), "100").unwrap();
+        assert!(cursor.load().is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,2 +1,5 @@
+pub mod backfill;
+pub mod exex;
 pub mod handle;
 pub mod manager;
+pub mod settlement;
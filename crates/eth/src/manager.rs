@@ -1,45 +1,49 @@
 use std::{
     collections::HashSet,
     ops::RangeInclusive,
+    pin::Pin,
     sync::Arc,
     task::{Context, Poll}
 };
 
 use alloy::{
-    consensus::{BlockHeader, Transaction},
-    primitives::{aliases::I24, Address, BlockHash, BlockNumber, B256},
-    sol_types::SolEvent
+    consensus::BlockHeader,
+    eips::BlockNumHash,
+    primitives::{aliases::I24, Address, BlockHash, BlockNumber, B256}
 };
 use angstrom_types::{
-    block_sync::BlockSyncProducer,
-    contract_bindings::{
-        angstrom::Angstrom::PoolKey,
-        controller_v_1::ControllerV1::{NodeAdded, NodeRemoved, PoolConfigured, PoolRemoved}
-    },
-    contract_payloads::angstrom::{AngPoolConfigEntry, AngstromBundle, AngstromPoolConfigStore}
+    block_sync::{BlockSyncConsumer, BlockSyncProducer},
+    contract_bindings::angstrom::Angstrom::PoolKey,
+    contract_events::{AngstromEvent, AngstromEventDecoder},
+    contract_payloads::angstrom::{AngPoolConfigEntry, AngstromPoolConfigStore}
 };
 use futures::Future;
-use futures_util::{FutureExt, StreamExt};
-use itertools::Itertools;
-use pade::PadeDecode;
+use futures_util::{FutureExt, Stream, StreamExt};
 use reth_ethereum_primitives::{Block, Receipt, TransactionSigned};
 use reth_primitives_traits::RecoveredBlock;
-use reth_provider::{CanonStateNotification, CanonStateNotifications, Chain};
+use reth_provider::{CanonStateNotification, Chain};
 use reth_tasks::TaskSpawner;
 use tokio::sync::mpsc::{Receiver, Sender, UnboundedSender};
-use tokio_stream::wrappers::{BroadcastStream, ReceiverStream};
-
-use crate::handle::{EthCommand, EthHandle};
+use tokio_stream::wrappers::ReceiverStream;
 
-alloy::sol!(
-    event Transfer(address indexed _from, address indexed _to, uint256 _value);
-    event Approval(address indexed _owner, address indexed _spender, uint256 _value);
-);
+use crate::{
+    exex::{ChainUpdate, ExExCursor},
+    handle::{EthCommand, EthHandle},
+    settlement::{address_changeset, SettlementExtractor}
+};
 
 const MAX_REORG_DEPTH: u64 = 150;
 
-/// Listens for CanonStateNotifications and sends the appropriate updates to be
-/// executed by the order pool
+type ChainUpdates = Pin<Box<dyn Stream<Item = ChainUpdate> + Send>>;
+
+/// Listens for updates of the canonical chain and sends the appropriate
+/// updates to be executed by the order pool.
+///
+/// Every block is turned into events once: commits of blocks at or below the
+/// last one handled, which the ExEx replays after a restart, are skipped. A
+/// revert is held back until the chain replacing it arrives, and the two are
+/// handled as one reorg, so modules that follow canonical state notifications
+/// see the same chain.
 pub struct EthDataCleanser<Sync> {
     angstrom_address:  Address,
     periphery_address: Address,
@@ -49,8 +53,13 @@ pub struct EthDataCleanser<Sync> {
     event_listeners:   Vec<UnboundedSender<EthEvent>>,
     /// for rebroadcasting
     cannon_sender:     tokio::sync::broadcast::Sender<CanonStateNotification>,
-    /// Notifications for Canonical Block updates
-    canonical_updates: BroadcastStream<CanonStateNotification>,
+    /// updates of the canonical chain
+    chain_updates:     ChainUpdates,
+    /// blocks reverted without a chain replacing them yet
+    reverted:          Option<Arc<Chain>>,
+    /// the last block turned into events
+    last_block:        Option<BlockNumHash>,
+    cursor:            Option<ExExCursor>,
     angstrom_tokens:   HashSet<Address>,
     /// handles syncing of blocks.
     block_sync:        Sync,
@@ -62,12 +71,16 @@ pub struct EthDataCleanser<Sync> {
 
 impl<Sync> EthDataCleanser<Sync>
 where
-    Sync: BlockSyncProducer
+    Sync: BlockSyncProducer + BlockSyncConsumer
 {
+    /// Follows `chain_updates` from the block `sync` starts on, or the block
+    /// stored in `cursor` when that is later.
+    #[allow(clippy::too_many_arguments)]
     pub fn spawn<TP: TaskSpawner>(
         angstrom_address: Address,
         periphery_address: Address,
-        canonical_updates: CanonStateNotifications,
+        chain_updates: impl Stream<Item = ChainUpdate> + Send + 'static,
+        cursor: Option<ExExCursor>,
         tp: TP,
         tx: Sender<EthCommand>,
         rx: Receiver<EthCommand>,
@@ -80,10 +93,26 @@ where
         let stream = ReceiverStream::new(rx);
         let (cannon_tx, _) = tokio::sync::broadcast::channel(1000);
 
+        let start = sync.current_block_number();
+        let last_block = cursor
+            .as_ref()
+            .and_then(|cursor| {
+                cursor
+                    .load()
+                    .inspect_err(|e| tracing::warn!(err=%e, "failed to read the exex cursor"))
+                    .ok()
+                    .flatten()
+            })
+            .filter(|block| block.number > start)
+            .unwrap_or(BlockNumHash::new(start, BlockHash::ZERO));
+
         let mut this = Self {
             angstrom_address,
             periphery_address,
-            canonical_updates: BroadcastStream::new(canonical_updates),
+            chain_updates: Box::pin(chain_updates),
+            reverted: None,
+            last_block: Some(last_block),
+            cursor,
             commander: stream,
             angstrom_tokens,
             cannon_sender: cannon_tx,
@@ -126,12 +155,44 @@ where
         }
     }
 
-    fn on_canon_update(&mut self, canonical_updates: CanonStateNotification) {
-        match canonical_updates.clone() {
-            CanonStateNotification::Reorg { old, new } => self.handle_reorg(old, new),
-            CanonStateNotification::Commit { new } => self.handle_commit(new)
+    fn on_chain_update(&mut self, update: ChainUpdate) {
+        let update = match (update, self.reverted.take()) {
+            (ChainUpdate::Revert(old), reverted) => {
+                self.reverted = Some(earliest(reverted, old));
+                return
+            }
+            (ChainUpdate::Commit(new), Some(old)) => ChainUpdate::Reorg { old, new },
+            (ChainUpdate::Reorg { old, new }, reverted) => {
+                ChainUpdate::Reorg { old: earliest(reverted, old), new }
+            }
+            (update, None) => update
+        };
+
+        let tip = update.tip();
+        if matches!(update, ChainUpdate::Commit(_))
+            && self
+                .last_block
+                .is_some_and(|last| tip.number <= last.number)
+        {
+            tracing::debug!(?tip, "skipping block that was already handled");
+            return
+        }
+
+        match &update {
+            ChainUpdate::Commit(new) => self.handle_commit(new.clone()),
+            ChainUpdate::Reorg { old, new } => self.handle_reorg(old.clone(), new.clone()),
+            ChainUpdate::Revert(_) => unreachable!("reverts are held back")
+        }
+        if let Some(notification) = update.canon_state_notification() {
+            let _ = self.cannon_sender.send(notification);
+        }
+
+        self.last_block = Some(tip);
+        if let Some(cursor) = self.cursor.as_ref() {
+            if let Err(e) = cursor.store(tip) {
+                tracing::warn!(err=%e, "failed to store the exex cursor");
+            }
         }
-        let _ = self.cannon_sender.send(canonical_updates);
     }
 
     fn handle_reorg(&mut self, old: Arc<impl ChainExt>, new: Arc<impl ChainExt>) {
@@ -184,23 +245,22 @@ where
     /// looks at all periphery contrct events updating the internal state +
     /// sending out info.
     fn apply_periphery_logs(&mut self, chain: &impl ChainExt) {
-        let periphery_address = self.periphery_address;
-        chain
+        let decoder = AngstromEventDecoder::new(self.angstrom_address, self.periphery_address);
+        let receipts = chain
             .receipts_by_block_hash(chain.tip_hash())
-            .unwrap_or_default()
-            .into_iter()
-            .flat_map(|receipt| &receipt.logs)
-            .filter(|log| log.address == periphery_address)
-            .for_each(|log| {
-                if let Ok(remove_node) = NodeRemoved::decode_log(log, true) {
+            .unwrap_or_default();
+
+        for (_, event) in decoder.decode_receipts(receipts) {
+            match event {
+                AngstromEvent::NodeRemoved(remove_node) => {
                     self.node_set.remove(&remove_node.node);
                     self.send_events(EthEvent::RemovedNode(remove_node.node));
                 }
-                if let Ok(added_node) = NodeAdded::decode_log(log, true) {
+                AngstromEvent::NodeAdded(added_node) => {
                     self.node_set.insert(added_node.node);
                     self.send_events(EthEvent::AddedNode(added_node.node));
                 }
-                if let Ok(removed_pool) = PoolRemoved::decode_log(log, true) {
+                AngstromEvent::PoolRemoved(removed_pool) => {
                     self.pool_store
                         .remove_pair(removed_pool.asset0, removed_pool.asset1);
 
@@ -213,7 +273,7 @@ where
                     };
                     self.send_events(EthEvent::RemovedPool { pool: pool_key });
                 }
-                if let Ok(added_pool) = PoolConfigured::decode_log(log, true) {
+                AngstromEvent::PoolConfigured(added_pool) => {
                     let asset0 = added_pool.asset0;
                     let asset1 = added_pool.asset1;
                     let entry = AngPoolConfigEntry {
@@ -243,64 +303,54 @@ where
 
                     self.send_events(EthEvent::NewPool { pool: pool_key });
                 }
-            });
+                AngstromEvent::BundleSettled { .. } => {}
+            }
+        }
     }
 
-    fn fetch_filled_order<'a>(
-        &'a self,
-        chain: &'a impl ChainExt
-    ) -> impl Iterator<Item = B256> + 'a {
-        let tip_txs = chain.tip_transactions().cloned();
-
-        tip_txs
-            .filter(|tx| tx.to() == Some(self.angstrom_address))
-            .filter_map(|transaction| {
-                let mut input: &[u8] = transaction.input();
-                AngstromBundle::pade_decode(&mut input, None).ok()
-            })
-            .flat_map(move |bundle| {
-                bundle
-                    .get_order_hashes(chain.tip_number())
-                    .collect::<Vec<_>>()
+    /// The orders settled in the blocks of the chain, see
+    /// [`SettlementExtractor`].
+    fn fetch_filled_order(&self, chain: &impl ChainExt) -> impl Iterator<Item = B256> {
+        let extractor = SettlementExtractor::new(self.angstrom_address);
+        chain
+            .blocks_with_receipts()
+            .flat_map(|(number, transactions, receipts)| {
+                extractor.filled_orders(number, transactions, &receipts)
             })
+            .collect::<Vec<_>>()
+            .into_iter()
     }
 
     /// fetches all eoa addresses touched
     fn get_eoa(&self, chain: Arc<impl ChainExt>) -> Vec<Address> {
+        let mut eoas = HashSet::new();
         chain
-            .receipts_by_block_hash(chain.tip_hash())
-            .unwrap_or_default()
-            .into_iter()
-            .flat_map(|receipt| &receipt.logs)
-            .filter(|log| self.angstrom_tokens.contains(&log.address))
-            .flat_map(|logs| {
-                Transfer::decode_log(logs, true)
-                    .map(|log| log._from)
-                    .or_else(|_| Approval::decode_log(logs, true).map(|log| log._owner))
-            })
-            .unique()
+            .blocks_with_receipts()
+            .flat_map(|(_, _, receipts)| address_changeset(&receipts, &self.angstrom_tokens))
+            .filter(|address| eoas.insert(*address))
             .collect()
     }
 }
 
+/// Of two reverted chains, the one reaching further back.
+fn earliest(reverted: Option<Arc<Chain>>, old: Arc<Chain>) -> Arc<Chain> {
+    match reverted {
+        Some(reverted) if reverted.first().number <= old.first().number => reverted,
+        _ => old
+    }
+}
+
 impl<Sync> Future for EthDataCleanser<Sync>
 where
-    Sync: BlockSyncProducer
+    Sync: BlockSyncProducer + BlockSyncConsumer
 {
     type Output = ();
 
     fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         // poll all canonical updates
-        while let Poll::Ready(is_some) = self.canonical_updates.poll_next_unpin(cx).map(|res| {
-            res.transpose()
-                .ok()
-                .flatten()
-                .map(|update| self.on_canon_update(update))
-                .is_some()
-        }) {
-            if !is_some {
-                return Poll::Ready(())
-            }
+        while let Poll::Ready(update) = self.chain_updates.poll_next_unpin(cx) {
+            let Some(update) = update else { return Poll::Ready(()) };
+            self.on_chain_update(update);
         }
 
         while let Poll::Ready(Some(command)) = self.commander.poll_next_unpin(cx) {
@@ -340,8 +390,13 @@ pub trait ChainExt {
     fn tip_transactions(&self) -> impl Iterator<Item = &TransactionSigned> + '_;
     fn reorged_range(&self, new: impl ChainExt) -> Option<RangeInclusive<u64>>;
     fn blocks_iter(&self) -> impl Iterator<Item = &RecoveredBlock<Block>> + '_;
+    /// Every block of the chain with its transactions and their receipts.
+    fn blocks_with_receipts(&self) -> impl Iterator<Item = BlockWithReceipts<'_>> + '_;
 }
 
+/// A block number, the transactions of the block and their receipts.
+pub type BlockWithReceipts<'a> = (BlockNumber, Vec<&'a TransactionSigned>, Vec<&'a Receipt>);
+
 impl ChainExt for Chain {
     fn tip_number(&self) -> BlockNumber {
         self.tip().number
@@ -389,12 +444,23 @@ impl ChainExt for Chain {
     fn blocks_iter(&self) -> impl Iterator<Item = &RecoveredBlock<Block>> + '_ {
         self.blocks_iter()
     }
+
+    fn blocks_with_receipts(&self) -> impl Iterator<Item = BlockWithReceipts<'_>> + '_ {
+        self.blocks_iter().map(|block| {
+            (
+                block.number(),
+                block.body().transactions.iter().collect(),
+                self.receipts_by_block_hash(block.hash())
+                    .unwrap_or_default()
+            )
+        })
+    }
 }
 
 #[cfg(test)]
 pub mod test {
     use alloy::{
-        consensus::TxLegacy,
+        consensus::{Header, TxLegacy},
         hex,
         primitives::{aliases::U24, b256, Log, TxKind, U256},
         signers::Signature,
@@ -406,7 +472,7 @@ pub mod test {
             NodeAdded, NodeRemoved, PoolConfigured, PoolRemoved
         },
         contract_payloads::{
-            angstrom::{TopOfBlockOrder, UserOrder},
+            angstrom::{AngstromBundle, TopOfBlockOrder, UserOrder},
             Asset, Pair
         },
         orders::OrderOutcome,
@@ -415,9 +481,11 @@ pub mod test {
     };
     use pade::PadeEncode;
     use reth_primitives::LogData;
+    use reth_provider::ExecutionOutcome;
     use testing_tools::type_generator::orders::{ToBOrderBuilder, UserOrderBuilder};
 
     use super::*;
+    use crate::settlement::{Approval, Transfer};
 
     #[derive(Default)]
     pub struct MockChain<'a> {
@@ -451,13 +519,30 @@ pub mod test {
         fn blocks_iter(&self) -> impl Iterator<Item = &RecoveredBlock<Block>> + '_ {
             vec![].into_iter()
         }
+
+        fn blocks_with_receipts(&self) -> impl Iterator<Item = BlockWithReceipts<'_>> + '_ {
+            std::iter::once((
+                self.number,
+                self.transactions.iter().collect(),
+                self.receipts.clone()
+            ))
+        }
+    }
+
+    /// The receipt of a bundle transaction, which settled if it succeeded.
+    fn settlement_receipt(angstrom_address: Address, success: bool) -> Receipt {
+        let settled = LogData::new_unchecked(vec![], B256::repeat_byte(1).to_vec().into());
+        Receipt {
+            success,
+            logs: vec![Log { address: angstrom_address, data: settled }],
+            ..Default::default()
+        }
     }
 
     fn setup_non_subscription_eth_manager(
         angstrom_address: Option<Address>
     ) -> EthDataCleanser<GlobalBlockSync> {
         let (_command_tx, command_rx) = tokio::sync::mpsc::channel(3);
-        let (_cannon_tx, cannon_rx) = tokio::sync::broadcast::channel(3);
         let (tx, _) = tokio::sync::broadcast::channel(3);
         EthDataCleanser {
             commander:         ReceiverStream::new(command_rx),
@@ -466,7 +551,10 @@ pub mod test {
             node_set:          HashSet::default(),
             angstrom_address:  angstrom_address.unwrap_or_default(),
             periphery_address: Address::default(),
-            canonical_updates: BroadcastStream::new(cannon_rx),
+            chain_updates:     Box::pin(futures::stream::pending()),
+            reverted:          None,
+            last_block:        None,
+            cursor:            None,
             block_sync:        GlobalBlockSync::new(1),
             cannon_sender:     tx,
             pool_store:        Default::default()
@@ -532,12 +620,29 @@ pub mod test {
         };
 
         let mock_tx = TransactionSigned::new_unhashed(leg.into(), Signature::test_signature());
-        let mock_chain = MockChain { transactions: vec![mock_tx], ..Default::default() };
+        let settled = settlement_receipt(angstrom_address, true);
+        let mock_chain = MockChain {
+            transactions: vec![mock_tx.clone()],
+            receipts: vec![&settled],
+            ..Default::default()
+        };
         let filled_set = eth.fetch_filled_order(&mock_chain).collect::<HashSet<_>>();
 
         for order_hash in order_hashes {
             assert!(filled_set.contains(&order_hash));
         }
+
+        // a bundle that reverted, or didn't settle, fills nothing
+        let reverted = settlement_receipt(angstrom_address, false);
+        let unsettled = Receipt { success: true, ..Default::default() };
+        for receipt in [&reverted, &unsettled] {
+            let mock_chain = MockChain {
+                transactions: vec![mock_tx.clone()],
+                receipts: vec![receipt],
+                ..Default::default()
+            };
+            assert_eq!(eth.fetch_filled_order(&mock_chain).count(), 0);
+        }
     }
 
     #[test]
@@ -667,6 +772,45 @@ pub mod test {
         assert!(received_reorg, "Should have received ReorgedOrders event");
     }
 
+    fn chain(blocks: RangeInclusive<u64>) -> Arc<Chain> {
+        let blocks = blocks.map(|number| {
+            RecoveredBlock::new_unhashed(
+                Block {
+                    header: Header { number, ..Default::default() },
+                    body:   Default::default()
+                },
+                vec![]
+            )
+        });
+        Arc::new(Chain::new(blocks, ExecutionOutcome::default(), None))
+    }
+
+    #[test]
+    fn test_revert_and_commit_are_one_reorg() {
+        let mut eth = setup_non_subscription_eth_manager(None);
+        eth.last_block = Some(BlockNumHash::new(201, BlockHash::ZERO));
+        let mut notifications = eth.subscribe_cannon_notifications();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        eth.event_listeners.push(tx);
+
+        // replayed after a restart
+        eth.on_chain_update(ChainUpdate::Commit(chain(200..=200)));
+        assert!(rx.try_recv().is_err());
+
+        eth.on_chain_update(ChainUpdate::Revert(chain(201..=201)));
+        assert!(rx.try_recv().is_err());
+        assert!(notifications.try_recv().is_err());
+
+        eth.on_chain_update(ChainUpdate::Commit(chain(201..=202)));
+        assert!(matches!(notifications.try_recv(), Ok(CanonStateNotification::Reorg { .. })));
+        assert!(matches!(
+            rx.try_recv(),
+            Ok(EthEvent::NewBlockTransitions { block_number: 202, .. })
+        ));
+        assert!(matches!(rx.try_recv(), Ok(EthEvent::ReorgedOrders(..))));
+        assert_eq!(eth.last_block.map(|block| block.number), Some(202));
+    }
+
     #[test]
     fn test_handle_commit() {
         let ang_addr = Address::random();
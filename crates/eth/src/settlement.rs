@@ -0,0 +1,105 @@
+//! The orders a block filled, and the accounts it changed, read from the
+//! block's execution outcome.
+//!
+//! The Angstrom contract emits a single settlement log per bundle, which
+//! doesn't carry the orders, so they are decoded from the input of the
+//! transaction that emitted it. A bundle transaction that reverted, or that
+//! didn't get to settle, fills nothing, even though its input holds orders.
+//! The accounts changed are the senders of transfers and the owners of
+//! approvals of the tokens of the Angstrom pools.
+
+use std::collections::HashSet;
+
+use alloy::{
+    consensus::Transaction,
+    primitives::{Address, BlockNumber, B256},
+    sol_types::SolEvent
+};
+use angstrom_types::{
+    contract_events::{AngstromEvent, AngstromEventDecoder},
+    contract_payloads::angstrom::AngstromBundle
+};
+use itertools::Itertools;
+use reth_ethereum_primitives::{Receipt, TransactionSigned};
+
+alloy::sol!(
+    event Transfer(address indexed _from, address indexed _to, uint256 _value);
+    event Approval(address indexed _owner, address indexed _spender, uint256 _value);
+);
+
+/// Reads the orders settled in the blocks of one Angstrom deployment, see the
+/// [module docs](self).
+#[derive(Debug, Clone, Copy)]
+pub struct SettlementExtractor {
+    angstrom_address: Address
+}
+
+impl SettlementExtractor {
+    pub fn new(angstrom_address: Address) -> Self {
+        Self { angstrom_address }
+    }
+
+    /// The hashes of the orders settled by the bundles of a block, `receipts`
+    /// are the receipts of its `transactions`, in the same order.
+    pub fn filled_orders<'a>(
+        &self,
+        block_number: BlockNumber,
+        transactions: impl IntoIterator<Item = &'a TransactionSigned>,
+        receipts: &[&Receipt]
+    ) -> Vec<B256> {
+        self.settled_bundles(transactions, receipts)
+            .into_iter()
+            .flat_map(|(_, bundle)| bundle.get_order_hashes(block_number).collect::<Vec<_>>())
+            .collect()
+    }
+
+    /// The bundles settled in a block, along with the transactions that
+    /// settled them.
+    pub fn settled_bundles<'a>(
+        &self,
+        transactions: impl IntoIterator<Item = &'a TransactionSigned>,
+        receipts: &[&Receipt]
+    ) -> Vec<(&'a TransactionSigned, AngstromBundle)> {
+        transactions
+            .into_iter()
+            .zip(receipts)
+            .filter(|(tx, receipt)| {
+                tx.to() == Some(self.angstrom_address)
+                    && is_settlement(self.angstrom_address, receipt)
+            })
+            .filter_map(|(tx, _)| {
+                let mut input: &[u8] = tx.input();
+                AngstromBundle::pade_decode_versioned(&mut input)
+                    .map(|(_, bundle)| (tx, bundle))
+                    .ok()
+            })
+            .collect()
+    }
+}
+
+/// Whether the transaction of `receipt` settled an Angstrom bundle.
+pub fn is_settlement(angstrom_address: Address, receipt: &Receipt) -> bool {
+    receipt.success
+        && receipt.logs.iter().any(|log| {
+            log.address == angstrom_address
+                && matches!(
+                    AngstromEventDecoder::decode_settlement(log),
+                    Some(AngstromEvent::BundleSettled { .. })
+                )
+        })
+}
+
+/// The senders of transfers and the owners of approvals of `tokens`.
+pub fn address_changeset(receipts: &[&Receipt], tokens: &HashSet<Address>) -> Vec<Address> {
+    receipts
+        .iter()
+        .flat_map(|receipt| &receipt.logs)
+        .filter(|log| tokens.contains(&log.address))
+        .flat_map(|log| {
+            Transfer::decode_log(log, true)
+                .map(|log| log._from)
+                .or_else(|_| Approval::decode_log(log, true).map(|log| log._owner))
+        })
+        .unique()
+        .collect()
+}
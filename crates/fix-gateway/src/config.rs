@@ -0,0 +1,82 @@
+use std::net::SocketAddr;
+
+use alloy::primitives::Address;
+use serde::Deserialize;
+
+/// How long Day and GTC orders stay valid if the client doesn't cancel them.
+pub const DEFAULT_ORDER_TTL: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct FixGatewayConfig {
+    pub listen_addr:    SocketAddr,
+    /// the CompID of the gateway
+    pub sender_comp_id: String,
+    /// counterparties allowed to log on, none if empty
+    #[serde(default)]
+    pub counterparties: Vec<Counterparty>,
+    /// seconds Day and GTC orders are valid for
+    #[serde(default = "default_order_ttl")]
+    pub order_ttl:      u64,
+    /// the first nonce signed into orders. Standing orders of an address need
+    /// distinct nonces, so this has to be past the nonces used by earlier runs
+    #[serde(default)]
+    pub starting_nonce: u64,
+    pub instruments:    Vec<Instrument>
+}
+
+impl FixGatewayConfig {
+    pub fn instrument(&self, symbol: &str) -> Option<&Instrument> {
+        self.instruments
+            .iter()
+            .find(|instrument| instrument.symbol == symbol)
+    }
+}
+
+fn default_order_ttl() -> u64 {
+    DEFAULT_ORDER_TTL
+}
+
+/// A client allowed to log on. Anyone logged on enters orders signed with the
+/// gateway's key, so the Logon has to carry the counterparty's password in
+/// Password(554).
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+pub struct Counterparty {
+    pub comp_id:  String,
+    pub password: String
+}
+
+impl Counterparty {
+    /// Whether `password` is the counterparty's, compared in constant time.
+    pub fn authenticates(&self, password: &str) -> bool {
+        let (expected, given) = (self.password.as_bytes(), password.as_bytes());
+
+        expected.len() == given.len()
+            && expected
+                .iter()
+                .zip(given)
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+impl std::fmt::Debug for Counterparty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Counterparty")
+            .field("comp_id", &self.comp_id)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A pair that can be traded over FIX. Quantities are in units of `base` and
+/// prices in units of `quote` per unit of `base`, both as decimals.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Instrument {
+    pub symbol:         String,
+    pub base:           Address,
+    pub quote:          Address,
+    pub base_decimals:  u8,
+    pub quote_decimals: u8,
+    /// the most of asset0, in its smallest unit, an order pays for gas
+    #[serde(default)]
+    pub max_fee_asset0: u64
+}
@@ -0,0 +1,699 @@
+use std::{
+    collections::HashMap,
+    io,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc
+    },
+    time::{SystemTime, UNIX_EPOCH}
+};
+
+use alloy::{primitives::B256, sol_types::Eip712Domain};
+use angstrom_types::{
+    orders::OrderOrigin,
+    primitive::{OrderPoolNewOrderResult, ANGSTROM_DOMAIN},
+    sol_bindings::RawPoolOrder
+};
+use bytes::BytesMut;
+use futures::StreamExt;
+use order_pool::{OrderPoolHandle, PoolManagerUpdate};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpListener,
+    time::Instant
+};
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+
+use crate::{
+    config::FixGatewayConfig,
+    message::{msg_type, tags, FixError, FixMessage},
+    report::{cancel_reject, exec_type, rejected_report, TrackedOrder},
+    session::{Session, SessionError},
+    signer::OrderSigner,
+    translate::{sign_cancel, sign_order, NewOrderSingle, OrderRejection}
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum GatewayError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Message(#[from] FixError)
+}
+
+/// Takes orders over FIX and submits them to the order pool, signed by the
+/// gateway's [`OrderSigner`]. Every connection is a session of its own which
+/// gets reports on the orders it entered.
+pub struct FixGateway<Pool, S> {
+    pool:   Pool,
+    signer: Arc<S>,
+    config: Arc<FixGatewayConfig>,
+    domain: Eip712Domain,
+    nonces: Arc<AtomicU64>
+}
+
+impl<Pool: Clone, S> Clone for FixGateway<Pool, S> {
+    fn clone(&self) -> Self {
+        Self {
+            pool:   self.pool.clone(),
+            signer: self.signer.clone(),
+            config: self.config.clone(),
+            domain: self.domain.clone(),
+            nonces: self.nonces.clone()
+        }
+    }
+}
+
+impl<Pool, S> FixGateway<Pool, S>
+where
+    Pool: OrderPoolHandle,
+    S: OrderSigner
+{
+    pub fn new(pool: Pool, signer: S, config: FixGatewayConfig) -> Self {
+        let nonces = Arc::new(AtomicU64::new(config.starting_nonce));
+
+        Self {
+            pool,
+            signer: Arc::new(signer),
+            config: Arc::new(config),
+            domain: ANGSTROM_DOMAIN,
+            nonces
+        }
+    }
+
+    /// The domain orders are signed under, the testnet one by default.
+    pub fn with_domain(self, domain: Eip712Domain) -> Self {
+        Self { domain, ..self }
+    }
+
+    /// Accepts connections on the configured address until the listener
+    /// fails.
+    pub async fn serve(self) -> io::Result<()> {
+        let listener = TcpListener::bind(self.config.listen_addr).await?;
+        tracing::info!(addr = %self.config.listen_addr, "fix gateway listening");
+
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let _ = stream.set_nodelay(true);
+            let gateway = self.clone();
+
+            tokio::spawn(async move {
+                match gateway.run_session(stream).await {
+                    Ok(()) => tracing::debug!(%peer, "fix session closed"),
+                    Err(e) => tracing::warn!(%peer, %e, "fix session failed")
+                }
+            });
+        }
+    }
+
+    /// Runs a session on `stream` until either side logs out or the
+    /// connection drops.
+    pub async fn run_session<IO>(&self, stream: IO) -> Result<(), GatewayError>
+    where
+        IO: AsyncRead + AsyncWrite + Unpin
+    {
+        Connection {
+            gateway: self,
+            session: Session::new(
+                self.config.sender_comp_id.clone(),
+                self.config.counterparties.clone()
+            ),
+            stream,
+            buf: BytesMut::with_capacity(4096),
+            last_sent: Instant::now(),
+            orders: HashMap::new(),
+            by_hash: HashMap::new(),
+            exec_id_prefix: unix_now_millis(),
+            exec_ids: 0
+        }
+        .run()
+        .await
+    }
+}
+
+struct Connection<'a, Pool, S, IO> {
+    gateway:        &'a FixGateway<Pool, S>,
+    session:        Session,
+    stream:         IO,
+    buf:            BytesMut,
+    last_sent:      Instant,
+    /// the orders entered in this session by ClOrdID
+    orders:         HashMap<String, TrackedOrder>,
+    by_hash:        HashMap<B256, String>,
+    exec_id_prefix: u64,
+    exec_ids:       u64
+}
+
+impl<Pool, S, IO> Connection<'_, Pool, S, IO>
+where
+    Pool: OrderPoolHandle,
+    S: OrderSigner,
+    IO: AsyncRead + AsyncWrite + Unpin
+{
+    async fn run(mut self) -> Result<(), GatewayError> {
+        let mut updates = self.gateway.pool.subscribe_orders();
+        let mut last_received = Instant::now();
+
+        loop {
+            let interval = self.session.heartbeat_interval();
+            tokio::select! {
+                read = self.stream.read_buf(&mut self.buf) => {
+                    if read? == 0 {
+                        return Ok(())
+                    }
+                    last_received = Instant::now();
+
+                    while let Some(message) = FixMessage::decode(&mut self.buf)? {
+                        if !self.on_message(message).await? {
+                            return Ok(())
+                        }
+                    }
+                }
+                Some(update) = updates.next(), if self.session.is_logged_on() => {
+                    self.on_pool_update(update).await?;
+                }
+                _ = tokio::time::sleep_until(self.last_sent + interval) => {
+                    if !self.session.is_logged_on() {
+                        tracing::debug!("no logon received");
+                        return Ok(())
+                    }
+                    if last_received.elapsed() > interval * 2 {
+                        return self.logout("heartbeat timeout").await
+                    }
+                    self.send(FixMessage::new(msg_type::HEARTBEAT)).await?;
+                }
+            }
+        }
+    }
+
+    /// Returns false once the session is over.
+    async fn on_message(&mut self, message: FixMessage) -> Result<bool, GatewayError> {
+        let seq_num = message.parse::<u64>(tags::MSG_SEQ_NUM).ok().flatten();
+        let was_logged_on = self.session.is_logged_on();
+
+        if let Err(error) = self.session.on_incoming(&message) {
+            tracing::debug!(%error, "refused fix message");
+            if !was_logged_on {
+                return Ok(false)
+            }
+            if matches!(error, SessionError::Message(_)) {
+                self.send(Session::reject(seq_num, &error)).await?;
+                return Ok(true)
+            }
+            self.logout(&error.to_string()).await?;
+            return Ok(false)
+        }
+
+        match message.msg_type().unwrap_or_default() {
+            msg_type::LOGON if !was_logged_on => {
+                let counterparty = self.session.counterparty().unwrap_or_default();
+                tracing::info!(counterparty, "fix session logged on");
+                let logon = self.session.logon();
+                self.send(logon).await?;
+            }
+            msg_type::LOGON | msg_type::HEARTBEAT => {}
+            msg_type::TEST_REQUEST => {
+                let mut heartbeat = FixMessage::new(msg_type::HEARTBEAT);
+                if let Some(id) = message.get(tags::TEST_REQ_ID) {
+                    heartbeat.push(tags::TEST_REQ_ID, id);
+                }
+                self.send(heartbeat).await?;
+            }
+            msg_type::LOGOUT => {
+                self.logout("").await?;
+                return Ok(false)
+            }
+            msg_type::NEW_ORDER_SINGLE => self.on_new_order(&message, seq_num).await?,
+            msg_type::ORDER_CANCEL_REQUEST => self.on_cancel(&message, seq_num).await?,
+            other => {
+                let error = SessionError::UnsupportedMsgType(other.to_string());
+                self.send(Session::reject(seq_num, &error)).await?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    async fn on_new_order(
+        &mut self,
+        message: &FixMessage,
+        seq_num: Option<u64>
+    ) -> Result<(), GatewayError> {
+        let order = match NewOrderSingle::from_message(message) {
+            Ok(order) => order,
+            Err(error) => return self.send(Session::reject(seq_num, &error.into())).await
+        };
+
+        let report = match self.submit(&order).await {
+            Ok(tracked) => {
+                let report =
+                    tracked.report(&tracked.cl_ord_id, &self.next_exec_id(), exec_type::NEW);
+                self.by_hash
+                    .insert(tracked.order_hash, tracked.cl_ord_id.clone());
+                self.orders.insert(tracked.cl_ord_id.clone(), tracked);
+                report
+            }
+            Err(rejection) => {
+                tracing::debug!(cl_ord_id = order.cl_ord_id, %rejection, "rejected fix order");
+                rejected_report(&order, &self.next_exec_id(), &rejection)
+            }
+        };
+
+        self.send(report).await
+    }
+
+    async fn submit(&self, order: &NewOrderSingle) -> Result<TrackedOrder, OrderRejection> {
+        if self.orders.contains_key(&order.cl_ord_id) {
+            return Err(OrderRejection::DuplicateClOrdId)
+        }
+        let gateway = self.gateway;
+        let instrument = gateway
+            .config
+            .instrument(&order.symbol)
+            .ok_or_else(|| OrderRejection::UnknownSymbol(order.symbol.clone()))?;
+        let deadline = order.deadline(unix_now_millis() / 1000, gateway.config.order_ttl)?;
+
+        let nonce = gateway.nonces.fetch_add(1, Ordering::Relaxed);
+        let unsigned = instrument.standing_order(order, nonce, deadline)?;
+        let signed = sign_order(unsigned, gateway.signer.as_ref(), &gateway.domain).await?;
+        let order_hash = signed.order_hash();
+
+        match gateway.pool.new_order(OrderOrigin::External, signed).await {
+            OrderPoolNewOrderResult::Valid => Ok(TrackedOrder::new(order, order_hash)),
+            OrderPoolNewOrderResult::Invalid(e) => {
+                Err(OrderRejection::Pool(format!("order failed validation: {e}")))
+            }
+            OrderPoolNewOrderResult::TransitionedToBlock => {
+                Err(OrderRejection::Pool("a new block started during validation".to_string()))
+            }
+            OrderPoolNewOrderResult::Error(e) => Err(OrderRejection::Pool(e))
+        }
+    }
+
+    async fn on_cancel(
+        &mut self,
+        message: &FixMessage,
+        seq_num: Option<u64>
+    ) -> Result<(), GatewayError> {
+        let ids = message
+            .require(tags::CL_ORD_ID)
+            .and_then(|cl_ord_id| Ok((cl_ord_id, message.require(tags::ORIG_CL_ORD_ID)?)));
+        let (cl_ord_id, orig_cl_ord_id) = match ids {
+            Ok(ids) => ids,
+            Err(error) => return self.send(Session::reject(seq_num, &error.into())).await
+        };
+
+        let Some(order) = self.orders.get(orig_cl_ord_id).cloned() else {
+            let reject =
+                cancel_reject(cl_ord_id, orig_cl_ord_id, None, &OrderRejection::UnknownOrder);
+            return self.send(reject).await
+        };
+        let cancelled = if order.filled {
+            Err(OrderRejection::Filled)
+        } else {
+            self.cancel(order.order_hash).await
+        };
+
+        let response = match cancelled {
+            Ok(()) => {
+                self.untrack(&order.order_hash);
+                order.report(cl_ord_id, &self.next_exec_id(), exec_type::CANCELED)
+            }
+            Err(rejection) => cancel_reject(cl_ord_id, orig_cl_ord_id, Some(&order), &rejection)
+        };
+
+        self.send(response).await
+    }
+
+    async fn cancel(&self, order_hash: B256) -> Result<(), OrderRejection> {
+        let request = sign_cancel(order_hash, self.gateway.signer.as_ref()).await?;
+
+        self.gateway
+            .pool
+            .cancel_order(request)
+            .await
+            .then_some(())
+            .ok_or_else(|| OrderRejection::Pool("order is no longer in the pool".to_string()))
+    }
+
+    /// Reports fills, reorged fills, cancellations and expiries of the
+    /// session's orders.
+    async fn on_pool_update(
+        &mut self,
+        update: Result<PoolManagerUpdate, BroadcastStreamRecvError>
+    ) -> Result<(), GatewayError> {
+        let update = match update {
+            Ok(update) => update,
+            Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                tracing::warn!(missed, "fix session missed order updates");
+                return Ok(())
+            }
+        };
+        let (order_hash, exec_type) = match update {
+            PoolManagerUpdate::FilledOrder(_, order) => (order.order_id.hash, exec_type::TRADE),
+            PoolManagerUpdate::UnfilledOrders(order) => {
+                (order.order_id.hash, exec_type::TRADE_CANCEL)
+            }
+            PoolManagerUpdate::CancelledOrder { order_hash, .. } => {
+                (order_hash, exec_type::CANCELED)
+            }
+            PoolManagerUpdate::ExpiredOrder(order) => (order.order_id.hash, exec_type::EXPIRED),
+            PoolManagerUpdate::NewOrder(_) | PoolManagerUpdate::RejectedOrder { .. } => {
+                return Ok(())
+            }
+        };
+        let Some(order) = self
+            .by_hash
+            .get(&order_hash)
+            .and_then(|cl_ord_id| self.orders.get_mut(cl_ord_id))
+        else {
+            return Ok(())
+        };
+
+        match exec_type {
+            exec_type::TRADE if !order.filled => order.filled = true,
+            exec_type::TRADE_CANCEL if order.filled => order.filled = false,
+            exec_type::CANCELED | exec_type::EXPIRED if !order.filled => {}
+            _ => return Ok(())
+        }
+        let order = order.clone();
+        if exec_type == exec_type::CANCELED || exec_type == exec_type::EXPIRED {
+            self.untrack(&order_hash);
+        }
+
+        let report = order.report(&order.cl_ord_id, &self.next_exec_id(), exec_type);
+        self.send(report).await
+    }
+
+    fn untrack(&mut self, order_hash: &B256) {
+        if let Some(cl_ord_id) = self.by_hash.remove(order_hash) {
+            self.orders.remove(&cl_ord_id);
+        }
+    }
+
+    fn next_exec_id(&mut self) -> String {
+        self.exec_ids += 1;
+        format!("{}-{}", self.exec_id_prefix, self.exec_ids)
+    }
+
+    async fn send(&mut self, message: FixMessage) -> Result<(), GatewayError> {
+        let bytes = self.session.outgoing(message);
+        self.stream.write_all(&bytes).await?;
+        self.last_sent = Instant::now();
+
+        Ok(())
+    }
+
+    async fn logout(&mut self, text: &str) -> Result<(), GatewayError> {
+        let mut logout = FixMessage::new(msg_type::LOGOUT);
+        if !text.is_empty() {
+            logout.push(tags::TEXT, text);
+        }
+        self.send(logout).await?;
+        self.stream.flush().await?;
+
+        Ok(())
+    }
+}
+
+fn unix_now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        future::{self, Future},
+        ops::Range,
+        sync::Mutex
+    };
+
+    use alloy::{
+        primitives::{Address, FixedBytes},
+        signers::local::PrivateKeySigner
+    };
+    use angstrom_types::{
+        orders::{
+            BookDelta, BookSnapshot, CancelAllOrdersRequest, CancelOrderRequest, OrderAmendment,
+            OrderId, OrderLocation, OrderStatesSnapshot, OrderStatus
+        },
+        primitive::PoolId,
+        sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData, StandingVariants}
+    };
+    use tokio::{
+        io::{duplex, DuplexStream},
+        sync::broadcast
+    };
+    use tokio_stream::wrappers::BroadcastStream;
+    use validation::order::OrderValidationResults;
+
+    use super::*;
+    use crate::config::{Counterparty, Instrument};
+
+    #[derive(Clone)]
+    struct MockPool {
+        orders:  Arc<Mutex<Vec<AllOrders>>>,
+        updates: broadcast::Sender<PoolManagerUpdate>
+    }
+
+    impl OrderPoolHandle for MockPool {
+        fn new_order(
+            &self,
+            _: OrderOrigin,
+            order: AllOrders
+        ) -> impl Future<Output = OrderPoolNewOrderResult> + Send {
+            self.orders.lock().unwrap().push(order);
+            future::ready(OrderPoolNewOrderResult::Valid)
+        }
+
+        fn simulate_order(
+            &self,
+            _: AllOrders
+        ) -> impl Future<Output = Option<OrderValidationResults>> + Send {
+            future::ready(None)
+        }
+
+        fn next_valid_nonce(&self, _: Address) -> impl Future<Output = Option<u64>> + Send {
+            future::ready(None)
+        }
+
+        fn invalidated_nonces(
+            &self,
+            _: Address,
+            _: Range<u64>
+        ) -> impl Future<Output = Option<Vec<u64>>> + Send {
+            future::ready(None)
+        }
+
+        fn subscribe_orders(&self) -> BroadcastStream<PoolManagerUpdate> {
+            BroadcastStream::new(self.updates.subscribe())
+        }
+
+        fn pending_orders(&self, _: Address) -> impl Future<Output = Vec<AllOrders>> + Send {
+            future::ready(vec![])
+        }
+
+        fn cancel_order(&self, req: CancelOrderRequest) -> impl Future<Output = bool> + Send {
+            future::ready(req.is_valid())
+        }
+
+        fn cancel_all_orders(
+            &self,
+            _: CancelAllOrdersRequest
+        ) -> impl Future<Output = Vec<B256>> + Send {
+            future::ready(vec![])
+        }
+
+        fn amend_order(
+            &self,
+            _: OrderAmendment
+        ) -> impl Future<Output = OrderPoolNewOrderResult> + Send {
+            future::ready(OrderPoolNewOrderResult::Valid)
+        }
+
+        fn fetch_orders_from_pool(
+            &self,
+            _: FixedBytes<32>,
+            _: OrderLocation
+        ) -> impl Future<Output = Vec<AllOrders>> + Send {
+            future::ready(vec![])
+        }
+
+        fn fetch_order_status(&self, _: B256) -> impl Future<Output = Option<OrderStatus>> + Send {
+            future::ready(None)
+        }
+
+        fn fetch_order_states(&self) -> impl Future<Output = OrderStatesSnapshot> + Send {
+            future::ready(OrderStatesSnapshot::default())
+        }
+
+        fn subscribe_book(
+            &self
+        ) -> impl Future<Output = Option<BroadcastStream<BookDelta>>> + Send {
+            future::ready(None)
+        }
+
+        fn fetch_book(&self, _: PoolId) -> impl Future<Output = Option<BookSnapshot>> + Send {
+            future::ready(None)
+        }
+    }
+
+    struct Client {
+        stream:  DuplexStream,
+        buf:     BytesMut,
+        seq_num: u64
+    }
+
+    impl Client {
+        async fn send(&mut self, mut message: FixMessage) {
+            self.seq_num += 1;
+            message.set_header("CLIENT", "ANGSTROM", self.seq_num, "20240101-00:00:00.000");
+            self.stream.write_all(&message.encode()).await.unwrap();
+        }
+
+        async fn receive(&mut self) -> FixMessage {
+            loop {
+                if let Some(message) = FixMessage::decode(&mut self.buf).unwrap() {
+                    return message
+                }
+                assert_ne!(self.stream.read_buf(&mut self.buf).await.unwrap(), 0);
+            }
+        }
+    }
+
+    fn setup_gateway() -> (MockPool, Client) {
+        let pool =
+            MockPool { orders: Arc::new(Mutex::new(vec![])), updates: broadcast::channel(4).0 };
+        let config = FixGatewayConfig {
+            listen_addr:    "127.0.0.1:0".parse().unwrap(),
+            sender_comp_id: "ANGSTROM".to_string(),
+            counterparties: vec![Counterparty {
+                comp_id:  "CLIENT".to_string(),
+                password: "secret".to_string()
+            }],
+            order_ttl:      60,
+            starting_nonce: 5,
+            instruments:    vec![Instrument {
+                symbol:         "WETH/USDC".to_string(),
+                base:           Address::repeat_byte(2),
+                quote:          Address::repeat_byte(1),
+                base_decimals:  18,
+                quote_decimals: 6,
+                max_fee_asset0: 0
+            }]
+        };
+        let gateway = FixGateway::new(pool.clone(), PrivateKeySigner::random(), config);
+
+        let (client, server) = duplex(4096);
+        tokio::spawn(async move { gateway.run_session(server).await });
+
+        (pool, Client { stream: client, buf: BytesMut::new(), seq_num: 0 })
+    }
+
+    fn new_order_single(cl_ord_id: &str, symbol: &str) -> FixMessage {
+        FixMessage::new(msg_type::NEW_ORDER_SINGLE)
+            .with(tags::CL_ORD_ID, cl_ord_id)
+            .with(tags::SYMBOL, symbol)
+            .with(tags::SIDE, 2)
+            .with(tags::ORDER_QTY, "1.5")
+            .with(tags::ORD_TYPE, 2)
+            .with(tags::PRICE, "2500")
+    }
+
+    fn cancel_request(cl_ord_id: &str, orig_cl_ord_id: &str) -> FixMessage {
+        FixMessage::new(msg_type::ORDER_CANCEL_REQUEST)
+            .with(tags::CL_ORD_ID, cl_ord_id)
+            .with(tags::ORIG_CL_ORD_ID, orig_cl_ord_id)
+    }
+
+    async fn logon(client: &mut Client) {
+        client
+            .send(
+                FixMessage::new(msg_type::LOGON)
+                    .with(tags::HEART_BT_INT, 30)
+                    .with(tags::PASSWORD, "secret")
+            )
+            .await;
+        let logon = client.receive().await;
+        assert_eq!(logon.msg_type(), Some(msg_type::LOGON));
+        assert_eq!(logon.get(tags::TARGET_COMP_ID), Some("CLIENT"));
+    }
+
+    #[tokio::test]
+    async fn orders_are_submitted_and_fills_reported() {
+        let (pool, mut client) = setup_gateway();
+        logon(&mut client).await;
+
+        client.send(new_order_single("a", "WETH/USDC")).await;
+        let report = client.receive().await;
+        assert_eq!(report.msg_type(), Some(msg_type::EXECUTION_REPORT));
+        assert_eq!(report.get(tags::EXEC_TYPE), Some(exec_type::NEW));
+        assert_eq!(report.get(tags::LEAVES_QTY), Some("1.5"));
+
+        let order = pool.orders.lock().unwrap()[0].clone();
+        assert_eq!(report.get(tags::ORDER_ID), Some(order.order_hash().to_string().as_str()));
+        let AllOrders::Standing(StandingVariants::Partial(standing)) = &order else {
+            panic!("not a partial standing order")
+        };
+        assert_eq!(standing.nonce, 5);
+
+        let filled = OrderWithStorageData {
+            order_id: OrderId { hash: order.order_hash(), ..Default::default() },
+            order,
+            priority_data: Default::default(),
+            invalidates: vec![],
+            pool_id: PoolId::default(),
+            is_currently_valid: true,
+            is_bid: false,
+            is_valid: true,
+            valid_block: 1,
+            tob_reward: Default::default()
+        };
+        pool.updates
+            .send(PoolManagerUpdate::FilledOrder(1, filled))
+            .unwrap();
+        let report = client.receive().await;
+        assert_eq!(report.get(tags::EXEC_TYPE), Some(exec_type::TRADE));
+        assert_eq!(report.get(tags::CUM_QTY), Some("1.5"));
+        assert_eq!(report.get(tags::LAST_PX), Some("2500"));
+
+        client.send(cancel_request("b", "a")).await;
+        let reject = client.receive().await;
+        assert_eq!(reject.msg_type(), Some(msg_type::ORDER_CANCEL_REJECT));
+        assert_eq!(reject.get(tags::CXL_REJ_REASON), Some("0"));
+    }
+
+    #[tokio::test]
+    async fn orders_are_cancelled_and_rejected() {
+        let (_, mut client) = setup_gateway();
+        logon(&mut client).await;
+
+        client.send(new_order_single("a", "WBTC/USDC")).await;
+        let report = client.receive().await;
+        assert_eq!(report.get(tags::EXEC_TYPE), Some(exec_type::REJECTED));
+        assert_eq!(report.get(tags::ORD_REJ_REASON), Some("1"));
+
+        client.send(new_order_single("a", "WETH/USDC")).await;
+        assert_eq!(client.receive().await.get(tags::EXEC_TYPE), Some(exec_type::NEW));
+        client.send(new_order_single("a", "WETH/USDC")).await;
+        assert_eq!(client.receive().await.get(tags::ORD_REJ_REASON), Some("6"));
+
+        client.send(cancel_request("b", "a")).await;
+        let report = client.receive().await;
+        assert_eq!(report.get(tags::EXEC_TYPE), Some(exec_type::CANCELED));
+        assert_eq!(report.get(tags::ORIG_CL_ORD_ID), Some("a"));
+
+        client.send(cancel_request("c", "a")).await;
+        assert_eq!(client.receive().await.get(tags::CXL_REJ_REASON), Some("1"));
+
+        client
+            .send(FixMessage::new(msg_type::NEW_ORDER_SINGLE).with(tags::CL_ORD_ID, "d"))
+            .await;
+        let reject = client.receive().await;
+        assert_eq!(reject.msg_type(), Some(msg_type::REJECT));
+        assert_eq!(reject.get(tags::REF_SEQ_NUM), Some("7"));
+    }
+}
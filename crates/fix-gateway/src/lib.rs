@@ -0,0 +1,22 @@
+//! FIX 4.4 order entry in front of the order pool.
+//!
+//! NewOrderSingle limit orders are translated into standing orders, signed by
+//! the gateway's [`OrderSigner`] and submitted through an [`OrderPoolHandle`].
+//! OrderCancelRequests become signed cancellations. Fills, reorged fills and
+//! cancellations the pool reports are sent back as ExecutionReports to the
+//! session that entered the order.
+//!
+//! [`OrderPoolHandle`]: order_pool::OrderPoolHandle
+
+mod config;
+mod gateway;
+pub mod message;
+pub mod report;
+mod session;
+mod signer;
+pub mod translate;
+
+pub use config::*;
+pub use gateway::*;
+pub use session::*;
+pub use signer::*;
@@ -0,0 +1,306 @@
+use std::{fmt, str::FromStr};
+
+use bytes::{Buf, BytesMut};
+
+/// Field separator of the FIX tag=value encoding.
+pub const SOH: u8 = 0x01;
+pub const BEGIN_STRING: &str = "FIX.4.4";
+
+/// Messages with a larger body are refused rather than buffered.
+pub const MAX_BODY_LENGTH: usize = 64 * 1024;
+
+pub mod tags {
+    pub const AVG_PX: u32 = 6;
+    pub const BEGIN_STRING: u32 = 8;
+    pub const BODY_LENGTH: u32 = 9;
+    pub const CHECK_SUM: u32 = 10;
+    pub const CL_ORD_ID: u32 = 11;
+    pub const CUM_QTY: u32 = 14;
+    pub const EXEC_ID: u32 = 17;
+    pub const LAST_PX: u32 = 31;
+    pub const LAST_QTY: u32 = 32;
+    pub const MSG_SEQ_NUM: u32 = 34;
+    pub const MSG_TYPE: u32 = 35;
+    pub const ORDER_ID: u32 = 37;
+    pub const ORDER_QTY: u32 = 38;
+    pub const ORD_STATUS: u32 = 39;
+    pub const ORD_TYPE: u32 = 40;
+    pub const ORIG_CL_ORD_ID: u32 = 41;
+    pub const PRICE: u32 = 44;
+    pub const REF_SEQ_NUM: u32 = 45;
+    pub const SENDER_COMP_ID: u32 = 49;
+    pub const SENDING_TIME: u32 = 52;
+    pub const SIDE: u32 = 54;
+    pub const SYMBOL: u32 = 55;
+    pub const TARGET_COMP_ID: u32 = 56;
+    pub const TEXT: u32 = 58;
+    pub const TIME_IN_FORCE: u32 = 59;
+    pub const ENCRYPT_METHOD: u32 = 98;
+    pub const CXL_REJ_REASON: u32 = 102;
+    pub const ORD_REJ_REASON: u32 = 103;
+    pub const HEART_BT_INT: u32 = 108;
+    pub const MIN_QTY: u32 = 110;
+    pub const TEST_REQ_ID: u32 = 112;
+    pub const EXPIRE_TIME: u32 = 126;
+    pub const EXEC_TYPE: u32 = 150;
+    pub const LEAVES_QTY: u32 = 151;
+    pub const REF_TAG_ID: u32 = 371;
+    pub const SESSION_REJECT_REASON: u32 = 373;
+    pub const CXL_REJ_RESPONSE_TO: u32 = 434;
+    pub const PASSWORD: u32 = 554;
+}
+
+pub mod msg_type {
+    pub const HEARTBEAT: &str = "0";
+    pub const TEST_REQUEST: &str = "1";
+    pub const REJECT: &str = "3";
+    pub const LOGOUT: &str = "5";
+    pub const EXECUTION_REPORT: &str = "8";
+    pub const ORDER_CANCEL_REJECT: &str = "9";
+    pub const LOGON: &str = "A";
+    pub const NEW_ORDER_SINGLE: &str = "D";
+    pub const ORDER_CANCEL_REQUEST: &str = "F";
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum FixError {
+    #[error("message doesn't start with BeginString FIX.4.4")]
+    BeginString,
+    #[error("invalid BodyLength")]
+    BodyLength,
+    #[error("checksum {received:03} doesn't match the computed {computed:03}")]
+    CheckSum { received: u8, computed: u8 },
+    #[error("malformed field {0:?}")]
+    MalformedField(String),
+    #[error("required tag {0} is missing")]
+    MissingTag(u32),
+    #[error("value {1:?} of tag {0} is invalid")]
+    InvalidValue(u32, String)
+}
+
+impl FixError {
+    /// The tag the error is about, if it is about a single one.
+    pub fn tag(&self) -> Option<u32> {
+        match self {
+            Self::MissingTag(tag) | Self::InvalidValue(tag, _) => Some(*tag),
+            _ => None
+        }
+    }
+}
+
+/// A FIX message as the ordered list of its fields. BeginString, BodyLength
+/// and CheckSum are left out, they are derived when encoding.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixMessage {
+    fields: Vec<(u32, String)>
+}
+
+impl FixMessage {
+    pub fn new(msg_type: &str) -> Self {
+        Self { fields: vec![(tags::MSG_TYPE, msg_type.to_string())] }
+    }
+
+    pub fn with(mut self, tag: u32, value: impl fmt::Display) -> Self {
+        self.push(tag, value);
+        self
+    }
+
+    pub fn push(&mut self, tag: u32, value: impl fmt::Display) {
+        self.fields.push((tag, value.to_string()));
+    }
+
+    /// Puts the standard header fields behind the MsgType.
+    pub fn set_header(&mut self, sender: &str, target: &str, seq_num: u64, sending_time: &str) {
+        self.fields.retain(|(tag, _)| {
+            !matches!(
+                *tag,
+                tags::SENDER_COMP_ID
+                    | tags::TARGET_COMP_ID
+                    | tags::MSG_SEQ_NUM
+                    | tags::SENDING_TIME
+            )
+        });
+        let header = [
+            (tags::SENDER_COMP_ID, sender.to_string()),
+            (tags::TARGET_COMP_ID, target.to_string()),
+            (tags::MSG_SEQ_NUM, seq_num.to_string()),
+            (tags::SENDING_TIME, sending_time.to_string())
+        ];
+        let body = self.fields.split_off(self.fields.len().min(1));
+        self.fields.extend(header);
+        self.fields.extend(body);
+    }
+
+    pub fn msg_type(&self) -> Option<&str> {
+        self.get(tags::MSG_TYPE)
+    }
+
+    pub fn get(&self, tag: u32) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| *field == tag)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn require(&self, tag: u32) -> Result<&str, FixError> {
+        self.get(tag).ok_or(FixError::MissingTag(tag))
+    }
+
+    pub fn parse<T: FromStr>(&self, tag: u32) -> Result<Option<T>, FixError> {
+        self.get(tag)
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|_| FixError::InvalidValue(tag, value.to_string()))
+            })
+            .transpose()
+    }
+
+    pub fn parse_required<T: FromStr>(&self, tag: u32) -> Result<T, FixError> {
+        self.parse(tag)?.ok_or(FixError::MissingTag(tag))
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        for (tag, value) in &self.fields {
+            push_field(&mut body, *tag, value);
+        }
+
+        let mut message = Vec::with_capacity(body.len() + 32);
+        push_field(&mut message, tags::BEGIN_STRING, BEGIN_STRING);
+        push_field(&mut message, tags::BODY_LENGTH, body.len());
+        message.extend_from_slice(&body);
+
+        let check_sum = checksum(&message);
+        push_field(&mut message, tags::CHECK_SUM, format_args!("{check_sum:03}"));
+        message
+    }
+
+    /// Takes the first message off the front of `buf`. Returns none while the
+    /// message hasn't been received in full.
+    pub fn decode(buf: &mut BytesMut) -> Result<Option<Self>, FixError> {
+        let mut header = buf.split(|byte| *byte == SOH);
+        let (Some(begin_string), Some(body_length)) = (header.next(), header.next()) else {
+            if buf.len() > 32 {
+                return Err(FixError::BodyLength)
+            }
+            return Ok(None)
+        };
+        // a split only ends the field if the separator was received
+        let header_length = begin_string.len() + body_length.len() + 2;
+        if buf.len() < header_length {
+            return Ok(None)
+        }
+        if begin_string != format!("8={BEGIN_STRING}").as_bytes() {
+            return Err(FixError::BeginString)
+        }
+        let body_length = body_length
+            .strip_prefix(b"9=")
+            .and_then(|length| std::str::from_utf8(length).ok())
+            .and_then(|length| length.parse::<usize>().ok())
+            .filter(|length| *length <= MAX_BODY_LENGTH)
+            .ok_or(FixError::BodyLength)?;
+
+        // the trailer is always `10=nnn<SOH>`
+        let trailer_start = header_length + body_length;
+        let message_length = trailer_start + 7;
+        if buf.len() < message_length {
+            return Ok(None)
+        }
+
+        let message = buf.split_to(message_length);
+        let trailer = &message[trailer_start..];
+        if !trailer.starts_with(b"10=") || trailer[6] != SOH {
+            return Err(FixError::BodyLength)
+        }
+        let received = std::str::from_utf8(&trailer[3..6])
+            .ok()
+            .and_then(|sum| sum.parse::<u8>().ok())
+            .ok_or_else(|| {
+                FixError::InvalidValue(
+                    tags::CHECK_SUM,
+                    String::from_utf8_lossy(&trailer[3..6]).into_owned()
+                )
+            })?;
+        let computed = checksum(&message[..trailer_start]);
+        if received != computed {
+            return Err(FixError::CheckSum { received, computed })
+        }
+
+        let mut body = message;
+        body.advance(header_length);
+        body.truncate(body_length);
+
+        let fields = body
+            .split(|byte| *byte == SOH)
+            .filter(|field| !field.is_empty())
+            .map(parse_field)
+            .collect::<Result<Vec<_>, _>>()?;
+        if fields.first().map(|(tag, _)| *tag) != Some(tags::MSG_TYPE) {
+            return Err(FixError::MissingTag(tags::MSG_TYPE))
+        }
+
+        Ok(Some(Self { fields }))
+    }
+}
+
+fn push_field(buf: &mut Vec<u8>, tag: u32, value: impl fmt::Display) {
+    buf.extend_from_slice(format!("{tag}={value}").as_bytes());
+    buf.push(SOH);
+}
+
+fn parse_field(field: &[u8]) -> Result<(u32, String), FixError> {
+    let malformed = || FixError::MalformedField(String::from_utf8_lossy(field).into_owned());
+    let field = std::str::from_utf8(field).map_err(|_| malformed())?;
+    let (tag, value) = field.split_once('=').ok_or_else(malformed)?;
+
+    Ok((tag.parse().map_err(|_| malformed())?, value.to_string()))
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_round_trip() {
+        let mut message = FixMessage::new(msg_type::NEW_ORDER_SINGLE)
+            .with(tags::CL_ORD_ID, "order-1")
+            .with(tags::SIDE, 1);
+        message.set_header("CLIENT", "ANGSTROM", 7, "20240101-00:00:00.000");
+        let encoded = message.encode();
+        assert!(encoded.starts_with(b"8=FIX.4.4\x019="));
+
+        // arrives in two parts, followed by the start of the next message
+        let mut buf = BytesMut::from(&encoded[..20]);
+        assert_eq!(FixMessage::decode(&mut buf), Ok(None));
+        buf.extend_from_slice(&encoded[20..]);
+        buf.extend_from_slice(b"8=FIX");
+
+        let decoded = FixMessage::decode(&mut buf).unwrap().unwrap();
+        assert_eq!(decoded, message);
+        assert_eq!(decoded.msg_type(), Some(msg_type::NEW_ORDER_SINGLE));
+        assert_eq!(decoded.get(tags::MSG_SEQ_NUM), Some("7"));
+        assert_eq!(decoded.parse_required::<u8>(tags::SIDE), Ok(1));
+        assert_eq!(&buf[..], b"8=FIX");
+    }
+
+    #[test]
+    fn corrupted_messages_are_refused() {
+        let mut encoded = FixMessage::new(msg_type::HEARTBEAT).encode();
+        let body_start = encoded.iter().position(|byte| *byte == b'3').unwrap();
+        encoded[body_start + 3] = b'1';
+
+        assert!(matches!(
+            FixMessage::decode(&mut BytesMut::from(&encoded[..])),
+            Err(FixError::CheckSum { .. })
+        ));
+        assert_eq!(
+            FixMessage::decode(&mut BytesMut::from(&b"8=FIX.4.2\x019=5\x0135=0\x0110=000\x01"[..])),
+            Err(FixError::BeginString)
+        );
+    }
+}
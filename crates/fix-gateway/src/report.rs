@@ -0,0 +1,155 @@
+//! ExecutionReports and OrderCancelRejects sent back to the client.
+
+use alloy::primitives::B256;
+
+use crate::{
+    message::{msg_type, tags, FixMessage},
+    translate::{Decimal, NewOrderSingle, OrderRejection, Side}
+};
+
+pub mod exec_type {
+    pub const NEW: &str = "0";
+    pub const CANCELED: &str = "4";
+    pub const REJECTED: &str = "8";
+    pub const EXPIRED: &str = "C";
+    pub const TRADE: &str = "F";
+    pub const TRADE_CANCEL: &str = "H";
+}
+
+pub mod ord_status {
+    pub const NEW: &str = "0";
+    pub const FILLED: &str = "2";
+    pub const CANCELED: &str = "4";
+    pub const REJECTED: &str = "8";
+    pub const EXPIRED: &str = "C";
+}
+
+/// OrderID of reports on orders that never made it into the pool.
+const NO_ORDER_ID: &str = "NONE";
+
+/// An order entered through the session, kept to report on it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedOrder {
+    pub cl_ord_id:  String,
+    pub order_hash: B256,
+    pub symbol:     String,
+    pub side:       Side,
+    pub order_qty:  Decimal,
+    pub price:      Decimal,
+    pub filled:     bool
+}
+
+impl TrackedOrder {
+    pub fn new(order: &NewOrderSingle, order_hash: B256) -> Self {
+        Self {
+            cl_ord_id: order.cl_ord_id.clone(),
+            order_hash,
+            symbol: order.symbol.clone(),
+            side: order.side,
+            order_qty: order.order_qty,
+            price: order.price.unwrap_or_default(),
+            filled: false
+        }
+    }
+
+    pub fn ord_status(&self) -> &'static str {
+        if self.filled {
+            ord_status::FILLED
+        } else {
+            ord_status::NEW
+        }
+    }
+
+    /// The report of `exec_type` on the order. `cl_ord_id` is the one of the
+    /// request that caused the report, for a cancellation that differs from
+    /// the order's own.
+    ///
+    /// Orders are settled in full within a block, so a trade fills the whole
+    /// order. LastPx is the limit price, the uniform clearing price the order
+    /// settled at is never worse.
+    pub fn report(&self, cl_ord_id: &str, exec_id: &str, exec_type: &str) -> FixMessage {
+        let zero = Decimal::default();
+        let (ord_status, cum_qty, leaves_qty, avg_px) = match exec_type {
+            exec_type::TRADE => (ord_status::FILLED, self.order_qty, zero, self.price),
+            exec_type::CANCELED => (ord_status::CANCELED, zero, zero, zero),
+            exec_type::EXPIRED => (ord_status::EXPIRED, zero, zero, zero),
+            _ => (ord_status::NEW, zero, self.order_qty, zero)
+        };
+
+        let mut report = FixMessage::new(msg_type::EXECUTION_REPORT)
+            .with(tags::ORDER_ID, self.order_hash)
+            .with(tags::CL_ORD_ID, cl_ord_id);
+        if cl_ord_id != self.cl_ord_id {
+            report.push(tags::ORIG_CL_ORD_ID, &self.cl_ord_id);
+        }
+        report = report
+            .with(tags::EXEC_ID, exec_id)
+            .with(tags::EXEC_TYPE, exec_type)
+            .with(tags::ORD_STATUS, ord_status)
+            .with(tags::SYMBOL, &self.symbol)
+            .with(tags::SIDE, self.side.as_fix())
+            .with(tags::ORDER_QTY, self.order_qty)
+            .with(tags::PRICE, self.price);
+        if exec_type == exec_type::TRADE {
+            report = report
+                .with(tags::LAST_QTY, self.order_qty)
+                .with(tags::LAST_PX, self.price);
+        }
+
+        report
+            .with(tags::LEAVES_QTY, leaves_qty)
+            .with(tags::CUM_QTY, cum_qty)
+            .with(tags::AVG_PX, avg_px)
+    }
+}
+
+/// The report on an order that wasn't taken.
+pub fn rejected_report(
+    order: &NewOrderSingle,
+    exec_id: &str,
+    rejection: &OrderRejection
+) -> FixMessage {
+    let zero = Decimal::default();
+    let mut report = FixMessage::new(msg_type::EXECUTION_REPORT)
+        .with(tags::ORDER_ID, NO_ORDER_ID)
+        .with(tags::CL_ORD_ID, &order.cl_ord_id)
+        .with(tags::EXEC_ID, exec_id)
+        .with(tags::EXEC_TYPE, exec_type::REJECTED)
+        .with(tags::ORD_STATUS, ord_status::REJECTED)
+        .with(tags::ORD_REJ_REASON, rejection.ord_rej_reason())
+        .with(tags::SYMBOL, &order.symbol)
+        .with(tags::SIDE, order.side.as_fix())
+        .with(tags::ORDER_QTY, order.order_qty);
+    if let Some(price) = order.price {
+        report.push(tags::PRICE, price);
+    }
+
+    report
+        .with(tags::LEAVES_QTY, zero)
+        .with(tags::CUM_QTY, zero)
+        .with(tags::AVG_PX, zero)
+        .with(tags::TEXT, rejection)
+}
+
+/// Refuses the cancellation `cl_ord_id` of the order `orig_cl_ord_id`.
+pub fn cancel_reject(
+    cl_ord_id: &str,
+    orig_cl_ord_id: &str,
+    order: Option<&TrackedOrder>,
+    rejection: &OrderRejection
+) -> FixMessage {
+    let (order_id, ord_status) = match order {
+        Some(order) => (order.order_hash.to_string(), order.ord_status()),
+        None => (NO_ORDER_ID.to_string(), ord_status::REJECTED)
+    };
+
+    FixMessage::new(msg_type::ORDER_CANCEL_REJECT)
+        .with(tags::ORDER_ID, order_id)
+        .with(tags::CL_ORD_ID, cl_ord_id)
+        .with(tags::ORIG_CL_ORD_ID, orig_cl_ord_id)
+        .with(tags::ORD_STATUS, ord_status)
+        // a reject of an OrderCancelRequest, not of a replace
+        .with(tags::CXL_REJ_RESPONSE_TO, 1)
+        .with(tags::CXL_REJ_REASON, rejection.cxl_rej_reason())
+        .with(tags::TEXT, rejection)
+}
@@ -0,0 +1,325 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{
+    message::{msg_type, tags, FixError, FixMessage},
+    Counterparty
+};
+
+/// Heartbeat interval used if the counterparty's Logon doesn't set one.
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SessionError {
+    #[error("the first message must be a Logon, got MsgType {0:?}")]
+    NotLoggedOn(String),
+    #[error("unknown SenderCompID {0:?}")]
+    UnknownCounterparty(String),
+    #[error("wrong or missing Password for SenderCompID {0:?}")]
+    InvalidCredentials(String),
+    #[error("message is addressed to TargetCompID {0:?}")]
+    WrongTarget(String),
+    #[error("MsgSeqNum too low, expected {expected} but received {received}")]
+    SeqNumTooLow { expected: u64, received: u64 },
+    #[error("encrypted sessions are not supported")]
+    Encryption,
+    #[error("MsgType {0:?} is not supported")]
+    UnsupportedMsgType(String),
+    #[error(transparent)]
+    Message(#[from] FixError)
+}
+
+impl SessionError {
+    /// The tag the error is about, if it is about a single one.
+    pub fn tag(&self) -> Option<u32> {
+        match self {
+            Self::Message(error) => error.tag(),
+            _ => None
+        }
+    }
+}
+
+/// The state of the FIX session on one connection. Sequence numbers start at
+/// one for every connection, a session isn't resumed after a reconnect.
+#[derive(Debug)]
+pub struct Session {
+    sender_comp_id:     String,
+    target_comp_id:     Option<String>,
+    /// counterparties allowed to log on, none if empty
+    counterparties:     Vec<Counterparty>,
+    next_outgoing:      u64,
+    next_incoming:      u64,
+    heartbeat_interval: Duration
+}
+
+impl Session {
+    pub fn new(sender_comp_id: String, counterparties: Vec<Counterparty>) -> Self {
+        Self {
+            sender_comp_id,
+            target_comp_id: None,
+            counterparties,
+            next_outgoing: 1,
+            next_incoming: 1,
+            heartbeat_interval: DEFAULT_HEARTBEAT_INTERVAL
+        }
+    }
+
+    pub fn is_logged_on(&self) -> bool {
+        self.target_comp_id.is_some()
+    }
+
+    pub fn counterparty(&self) -> Option<&str> {
+        self.target_comp_id.as_deref()
+    }
+
+    pub fn heartbeat_interval(&self) -> Duration {
+        self.heartbeat_interval
+    }
+
+    /// Checks the header of a received message and logs the session on if it
+    /// is the Logon. A gap in the sequence numbers is tolerated since there
+    /// is nothing to resend, only messages replayed from before are refused.
+    pub fn on_incoming(&mut self, message: &FixMessage) -> Result<(), SessionError> {
+        let msg_type = message.require(tags::MSG_TYPE)?;
+        let sender = message.require(tags::SENDER_COMP_ID)?;
+        let target = message.require(tags::TARGET_COMP_ID)?;
+        let seq_num = message.parse_required::<u64>(tags::MSG_SEQ_NUM)?;
+        if target != self.sender_comp_id {
+            return Err(SessionError::WrongTarget(target.to_string()))
+        }
+        if seq_num < self.next_incoming {
+            return Err(SessionError::SeqNumTooLow {
+                expected: self.next_incoming,
+                received: seq_num
+            })
+        }
+
+        match &self.target_comp_id {
+            Some(counterparty) if counterparty != sender => {
+                return Err(SessionError::UnknownCounterparty(sender.to_string()))
+            }
+            Some(_) => {}
+            None if msg_type != msg_type::LOGON => {
+                return Err(SessionError::NotLoggedOn(msg_type.to_string()))
+            }
+            None => {
+                let Some(counterparty) = self
+                    .counterparties
+                    .iter()
+                    .find(|counterparty| counterparty.comp_id == sender)
+                else {
+                    return Err(SessionError::UnknownCounterparty(sender.to_string()))
+                };
+                if !counterparty.authenticates(message.get(tags::PASSWORD).unwrap_or_default()) {
+                    return Err(SessionError::InvalidCredentials(sender.to_string()))
+                }
+                if message.get(tags::ENCRYPT_METHOD).unwrap_or("0") != "0" {
+                    return Err(SessionError::Encryption)
+                }
+                if let Some(interval) = message.parse::<u64>(tags::HEART_BT_INT)? {
+                    self.heartbeat_interval = Duration::from_secs(interval.max(1));
+                }
+                self.target_comp_id = Some(sender.to_string());
+            }
+        }
+
+        self.next_incoming = seq_num + 1;
+        Ok(())
+    }
+
+    /// Stamps the header onto `message` and encodes it.
+    pub fn outgoing(&mut self, mut message: FixMessage) -> Vec<u8> {
+        let target = self.target_comp_id.as_deref().unwrap_or_default();
+        message.set_header(&self.sender_comp_id, target, self.next_outgoing, &sending_time());
+        self.next_outgoing += 1;
+
+        message.encode()
+    }
+
+    /// Answers to a Logon, mirroring the heartbeat interval.
+    pub fn logon(&self) -> FixMessage {
+        FixMessage::new(msg_type::LOGON)
+            .with(tags::ENCRYPT_METHOD, 0)
+            .with(tags::HEART_BT_INT, self.heartbeat_interval.as_secs())
+    }
+
+    /// A session level Reject of the message with `ref_seq_num`.
+    pub fn reject(ref_seq_num: Option<u64>, error: &SessionError) -> FixMessage {
+        let mut reject = FixMessage::new(msg_type::REJECT);
+        if let Some(ref_seq_num) = ref_seq_num {
+            reject.push(tags::REF_SEQ_NUM, ref_seq_num);
+        }
+        if let Some(tag) = error.tag() {
+            reject.push(tags::REF_TAG_ID, tag);
+        }
+        let reason = match error {
+            SessionError::Message(FixError::MissingTag(_)) => 1,
+            SessionError::Message(FixError::InvalidValue(..)) => 5,
+            SessionError::UnsupportedMsgType(_) => 11,
+            _ => 99
+        };
+
+        reject
+            .with(tags::SESSION_REJECT_REASON, reason)
+            .with(tags::TEXT, error)
+    }
+}
+
+/// The current UTC time in the `YYYYMMDD-HH:MM:SS.sss` format of SendingTime.
+pub fn sending_time() -> String {
+    format_utc_timestamp(SystemTime::now().duration_since(UNIX_EPOCH).unwrap())
+}
+
+pub fn format_utc_timestamp(since_epoch: Duration) -> String {
+    let secs = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let time_of_day = secs % 86_400;
+
+    format!(
+        "{year:04}{month:02}{day:02}-{:02}:{:02}:{:02}.{:03}",
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Parses a `YYYYMMDD-HH:MM:SS[.sss]` UTC timestamp into unix seconds.
+pub fn parse_utc_timestamp(timestamp: &str) -> Option<u64> {
+    let (date, time) = timestamp.split_once('-')?;
+    if date.len() != 8 || !date.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None
+    }
+    let year = date[..4].parse::<i64>().ok()?;
+    let month = date[4..6].parse::<u32>().ok()?;
+    let day = date[6..].parse::<u32>().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None
+    }
+
+    let time = time.split_once('.').map_or(time, |(time, _)| time);
+    let mut parts = time.split(':').map(|part| part.parse::<u64>().ok());
+    let (Some(Some(hour)), Some(Some(minute)), Some(Some(second)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None
+    };
+    if hour > 23 || minute > 59 || second > 60 {
+        return None
+    }
+
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    Some(days * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+// Howard Hinnant's algorithms for converting between days since the epoch and
+// proleptic Gregorian dates.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+
+    (yoe + era * 400 + i64::from(month <= 2), month, day)
+}
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(msg_type: &str, seq_num: u64) -> FixMessage {
+        FixMessage::new(msg_type)
+            .with(tags::SENDER_COMP_ID, "CLIENT")
+            .with(tags::TARGET_COMP_ID, "ANGSTROM")
+            .with(tags::MSG_SEQ_NUM, seq_num)
+    }
+
+    fn session(comp_ids: &[&str]) -> Session {
+        let counterparties = comp_ids
+            .iter()
+            .map(|comp_id| Counterparty {
+                comp_id:  comp_id.to_string(),
+                password: "secret".to_string()
+            })
+            .collect();
+
+        Session::new("ANGSTROM".to_string(), counterparties)
+    }
+
+    fn logon(password: &str) -> FixMessage {
+        message(msg_type::LOGON, 1).with(tags::PASSWORD, password)
+    }
+
+    #[test]
+    fn sessions_start_with_a_logon() {
+        let mut session = session(&["CLIENT"]);
+        assert_eq!(
+            session.on_incoming(&message(msg_type::HEARTBEAT, 1)),
+            Err(SessionError::NotLoggedOn(msg_type::HEARTBEAT.to_string()))
+        );
+
+        session
+            .on_incoming(&logon("secret").with(tags::HEART_BT_INT, 10))
+            .unwrap();
+        assert_eq!(session.counterparty(), Some("CLIENT"));
+        assert_eq!(session.heartbeat_interval(), Duration::from_secs(10));
+
+        session
+            .on_incoming(&message(msg_type::HEARTBEAT, 3))
+            .unwrap();
+        assert_eq!(
+            session.on_incoming(&message(msg_type::HEARTBEAT, 2)),
+            Err(SessionError::SeqNumTooLow { expected: 4, received: 2 })
+        );
+
+        let mut other = session(&["OTHER"]);
+        assert_eq!(
+            other.on_incoming(&logon("secret")),
+            Err(SessionError::UnknownCounterparty("CLIENT".to_string()))
+        );
+    }
+
+    #[test]
+    fn logons_need_the_counterparty_password() {
+        for password in ["wrong", "secret2", ""] {
+            assert_eq!(
+                session(&["CLIENT"]).on_incoming(&logon(password)),
+                Err(SessionError::InvalidCredentials("CLIENT".to_string()))
+            );
+        }
+        assert_eq!(
+            session(&["CLIENT"]).on_incoming(&message(msg_type::LOGON, 1)),
+            Err(SessionError::InvalidCredentials("CLIENT".to_string()))
+        );
+
+        // without any counterparties configured nobody logs on
+        assert_eq!(
+            session(&[]).on_incoming(&logon("secret")),
+            Err(SessionError::UnknownCounterparty("CLIENT".to_string()))
+        );
+    }
+
+    #[test]
+    fn timestamps_round_trip() {
+        let since_epoch = Duration::from_millis(1_709_251_199_250);
+        let timestamp = format_utc_timestamp(since_epoch);
+        assert_eq!(timestamp, "20240229-23:59:59.250");
+        assert_eq!(parse_utc_timestamp(&timestamp), Some(since_epoch.as_secs()));
+        assert_eq!(parse_utc_timestamp("20240229-23:59:59"), Some(since_epoch.as_secs()));
+        assert_eq!(parse_utc_timestamp("2024-02-29T23:59:59"), None);
+    }
+}
@@ -0,0 +1,33 @@
+use std::future::Future;
+
+use alloy::{
+    primitives::{Address, PrimitiveSignature, B256},
+    signers::Signer
+};
+
+/// The key the gateway signs orders and cancellations with. Orders entered
+/// over FIX are submitted as orders of this address.
+///
+/// Implemented for every alloy [`Signer`], so the key can be held locally, on
+/// a ledger or in a remote key manager.
+pub trait OrderSigner: Send + Sync + 'static {
+    fn address(&self) -> Address;
+
+    fn sign_hash(
+        &self,
+        hash: B256
+    ) -> impl Future<Output = alloy::signers::Result<PrimitiveSignature>> + Send;
+}
+
+impl<S: Signer + 'static> OrderSigner for S {
+    fn address(&self) -> Address {
+        Signer::address(self)
+    }
+
+    fn sign_hash(
+        &self,
+        hash: B256
+    ) -> impl Future<Output = alloy::signers::Result<PrimitiveSignature>> + Send {
+        async move { Signer::sign_hash(self, &hash).await }
+    }
+}
@@ -0,0 +1,508 @@
+//! Translation of FIX orders into signed Angstrom orders.
+
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use alloy::{
+    primitives::{aliases::U40, B256, U256},
+    sol_types::Eip712Domain
+};
+use angstrom_types::{
+    matching::Ray,
+    orders::CancelOrderRequest,
+    sol_bindings::{
+        grouped_orders::{AllOrders, StandingVariants},
+        rpc_orders::{ExactStandingOrder, OmitOrderMeta, OrderMeta, PartialStandingOrder}
+    }
+};
+use pade::PadeEncode;
+
+use crate::{
+    config::Instrument,
+    message::{tags, FixError, FixMessage},
+    session::parse_utc_timestamp,
+    signer::OrderSigner
+};
+
+/// Fraction digits beyond this are refused, they can't be meaningful for any
+/// token.
+const MAX_SCALE: u32 = 36;
+
+/// A non negative decimal as sent in FIX price and quantity fields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Decimal {
+    mantissa: U256,
+    scale:    u32
+}
+
+impl Decimal {
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    /// The value times `10^decimals`, none if that isn't a whole number.
+    pub fn scaled(&self, decimals: u8) -> Option<U256> {
+        let decimals = u32::from(decimals);
+        if self.scale <= decimals {
+            self.mantissa.checked_mul(pow10(decimals - self.scale)?)
+        } else {
+            let divisor = pow10(self.scale - decimals)?;
+            (self.mantissa % divisor)
+                .is_zero()
+                .then(|| self.mantissa / divisor)
+        }
+    }
+
+    fn cmp_value(&self, other: &Self) -> Option<Ordering> {
+        let scale = self.scale.max(other.scale);
+        let this = self.mantissa.checked_mul(pow10(scale - self.scale)?)?;
+        let other = other.mantissa.checked_mul(pow10(scale - other.scale)?)?;
+
+        Some(this.cmp(&other))
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        if (whole.is_empty() && fraction.is_empty())
+            || !whole
+                .bytes()
+                .chain(fraction.bytes())
+                .all(|byte| byte.is_ascii_digit())
+            || fraction.len() > MAX_SCALE as usize
+        {
+            return Err(())
+        }
+
+        let digits = format!("{whole}{fraction}");
+        let mantissa = U256::from_str_radix(&digits, 10).map_err(|_| ())?;
+        Ok(Self { mantissa, scale: fraction.len() as u32 })
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.to_string();
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return f.write_str(&digits)
+        }
+
+        let digits = format!("{digits:0>width$}", width = scale + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{whole}.{fraction}")
+    }
+}
+
+fn pow10(exp: u32) -> Option<U256> {
+    U256::from(10).checked_pow(U256::from(exp))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Buy,
+    Sell
+}
+
+impl Side {
+    pub fn as_fix(&self) -> u8 {
+        match self {
+            Self::Buy => 1,
+            Self::Sell => 2
+        }
+    }
+}
+
+/// Why an order or a cancellation isn't taken. These are reported in an
+/// ExecutionReport or OrderCancelReject rather than rejecting the message.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum OrderRejection {
+    #[error("unknown symbol {0:?}")]
+    UnknownSymbol(String),
+    #[error("only limit orders are supported")]
+    OrdType,
+    #[error("only Day, GTC and GTD orders are supported")]
+    TimeInForce,
+    #[error("ExpireTime is missing or has passed")]
+    ExpireTime,
+    #[error("quantity is zero or has more decimals than the instrument")]
+    Quantity,
+    #[error("MinQty exceeds OrderQty")]
+    MinQty,
+    #[error("price is zero or out of range")]
+    Price,
+    #[error("ClOrdID is already in use")]
+    DuplicateClOrdId,
+    #[error("unknown order")]
+    UnknownOrder,
+    #[error("order is already filled")]
+    Filled,
+    #[error("failed to sign: {0}")]
+    Signing(String),
+    #[error("rejected by the order pool: {0}")]
+    Pool(String)
+}
+
+impl OrderRejection {
+    /// The OrdRejReason of an ExecutionReport.
+    pub fn ord_rej_reason(&self) -> u32 {
+        match self {
+            Self::UnknownSymbol(_) => 1,
+            Self::UnknownOrder => 5,
+            Self::DuplicateClOrdId => 6,
+            Self::OrdType | Self::TimeInForce => 11,
+            Self::Quantity | Self::MinQty => 13,
+            _ => 99
+        }
+    }
+
+    /// The CxlRejReason of an OrderCancelReject.
+    pub fn cxl_rej_reason(&self) -> u32 {
+        match self {
+            Self::Filled => 0,
+            Self::UnknownOrder => 1,
+            Self::DuplicateClOrdId => 6,
+            _ => 99
+        }
+    }
+}
+
+/// The fields of a NewOrderSingle the gateway looks at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewOrderSingle {
+    pub cl_ord_id:     String,
+    pub symbol:        String,
+    pub side:          Side,
+    pub order_qty:     Decimal,
+    pub min_qty:       Option<Decimal>,
+    pub ord_type:      String,
+    pub price:         Option<Decimal>,
+    pub time_in_force: String,
+    /// unix seconds
+    pub expire_time:   Option<u64>
+}
+
+impl NewOrderSingle {
+    pub fn from_message(message: &FixMessage) -> Result<Self, FixError> {
+        let side = match message.require(tags::SIDE)? {
+            "1" => Side::Buy,
+            "2" => Side::Sell,
+            side => return Err(FixError::InvalidValue(tags::SIDE, side.to_string()))
+        };
+        let expire_time = message
+            .get(tags::EXPIRE_TIME)
+            .map(|time| {
+                parse_utc_timestamp(time)
+                    .ok_or_else(|| FixError::InvalidValue(tags::EXPIRE_TIME, time.to_string()))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            cl_ord_id: message.require(tags::CL_ORD_ID)?.to_string(),
+            symbol: message.require(tags::SYMBOL)?.to_string(),
+            side,
+            order_qty: message.parse_required(tags::ORDER_QTY)?,
+            min_qty: message.parse(tags::MIN_QTY)?,
+            ord_type: message.require(tags::ORD_TYPE)?.to_string(),
+            price: message.parse(tags::PRICE)?,
+            time_in_force: message.get(tags::TIME_IN_FORCE).unwrap_or("0").to_string(),
+            expire_time
+        })
+    }
+
+    /// The unix time the order expires at. Day and GTC orders live for
+    /// `order_ttl` seconds, GTD orders until their ExpireTime.
+    pub fn deadline(&self, now: u64, order_ttl: u64) -> Result<u64, OrderRejection> {
+        match self.time_in_force.as_str() {
+            "0" | "1" => Ok(now + order_ttl),
+            "6" => self
+                .expire_time
+                .filter(|expire_time| *expire_time > now)
+                .ok_or(OrderRejection::ExpireTime),
+            _ => Err(OrderRejection::TimeInForce)
+        }
+    }
+
+    /// A MinQty equal to the OrderQty makes the order all or none.
+    pub fn is_all_or_none(&self) -> bool {
+        self.min_qty
+            .is_some_and(|min_qty| min_qty.cmp_value(&self.order_qty) == Some(Ordering::Equal))
+    }
+}
+
+impl Instrument {
+    /// The unsigned standing order for `order`. Sells spend an exact amount of
+    /// base, all or none buys receive an exact amount of base and partially
+    /// fillable buys spend up to the quote needed at the limit price.
+    pub fn standing_order(
+        &self,
+        order: &NewOrderSingle,
+        nonce: u64,
+        deadline: u64
+    ) -> Result<StandingVariants, OrderRejection> {
+        if order.ord_type != "2" {
+            return Err(OrderRejection::OrdType)
+        }
+        let price = order
+            .price
+            .filter(|price| !price.is_zero())
+            .ok_or(OrderRejection::Price)?;
+        let min_price = self.min_price(order.side, price)?;
+
+        let quantity = self.base_amount(&order.order_qty)?;
+        let min_quantity = match &order.min_qty {
+            Some(min_qty) if min_qty.cmp_value(&order.order_qty) == Some(Ordering::Greater) => {
+                return Err(OrderRejection::MinQty)
+            }
+            Some(min_qty) if !min_qty.is_zero() => self.base_amount(min_qty)?,
+            _ => 0
+        };
+
+        let (asset_in, asset_out) = match order.side {
+            Side::Buy => (self.quote, self.base),
+            Side::Sell => (self.base, self.quote)
+        };
+        let deadline = U40::saturating_from(deadline);
+        let max_extra_fee_asset0 = u128::from(self.max_fee_asset0);
+
+        if order.is_all_or_none() {
+            return Ok(StandingVariants::Exact(ExactStandingOrder {
+                exact_in: order.side == Side::Sell,
+                amount: quantity,
+                max_extra_fee_asset0,
+                min_price: *min_price,
+                asset_in,
+                asset_out,
+                nonce,
+                deadline,
+                ..Default::default()
+            }))
+        }
+
+        // partial orders are always denominated in the asset going in
+        let (min_amount_in, max_amount_in) = match order.side {
+            Side::Sell => (min_quantity, quantity),
+            Side::Buy => (
+                min_price.inverse_quantity(min_quantity, false),
+                min_price.inverse_quantity(quantity, false)
+            )
+        };
+
+        Ok(StandingVariants::Partial(PartialStandingOrder {
+            min_amount_in,
+            max_amount_in,
+            max_extra_fee_asset0,
+            min_price: *min_price,
+            asset_in,
+            asset_out,
+            nonce,
+            deadline,
+            ..Default::default()
+        }))
+    }
+
+    fn base_amount(&self, quantity: &Decimal) -> Result<u128, OrderRejection> {
+        quantity
+            .scaled(self.base_decimals)
+            .filter(|amount| !amount.is_zero())
+            .and_then(|amount| u128::try_from(amount).ok())
+            .ok_or(OrderRejection::Quantity)
+    }
+
+    /// The least of the asset going out an order takes per unit of the asset
+    /// coming in, at a limit price in quote per base. Rounded up so the order
+    /// never trades past its limit.
+    pub fn min_price(&self, side: Side, price: Decimal) -> Result<Ray, OrderRejection> {
+        let (base, quote) = (u32::from(self.base_decimals), u32::from(self.quote_decimals));
+        let (numerator, denominator) = match side {
+            // quote out per base in
+            Side::Sell => (
+                pow10(27 + quote).and_then(|scale| price.mantissa.checked_mul(scale)),
+                pow10(price.scale + base)
+            ),
+            // base out per quote in
+            Side::Buy => (
+                pow10(27 + price.scale + base),
+                pow10(quote).and_then(|scale| price.mantissa.checked_mul(scale))
+            )
+        };
+
+        numerator
+            .zip(denominator)
+            .filter(|(_, denominator)| !denominator.is_zero())
+            .map(|(numerator, denominator)| numerator.div_ceil(denominator))
+            .filter(|min_price| !min_price.is_zero())
+            .map(Ray::from)
+            .ok_or(OrderRejection::Price)
+    }
+}
+
+/// Signs `order` and fills in its meta.
+pub async fn sign_order<S: OrderSigner>(
+    mut order: StandingVariants,
+    signer: &S,
+    domain: &Eip712Domain
+) -> Result<AllOrders, OrderRejection> {
+    let hash = match &order {
+        StandingVariants::Exact(order) => order.no_meta_eip712_signing_hash(domain),
+        StandingVariants::Partial(order) => order.no_meta_eip712_signing_hash(domain)
+    };
+    let signature = signer
+        .sign_hash(hash)
+        .await
+        .map_err(|e| OrderRejection::Signing(e.to_string()))?;
+    let meta = OrderMeta {
+        isEcdsa:   true,
+        from:      signer.address(),
+        signature: signature.pade_encode().into()
+    };
+
+    match &mut order {
+        StandingVariants::Exact(order) => order.meta = meta,
+        StandingVariants::Partial(order) => order.meta = meta
+    }
+
+    Ok(AllOrders::Standing(order))
+}
+
+/// A signed request to cancel the order with `order_hash`.
+pub async fn sign_cancel<S: OrderSigner>(
+    order_hash: B256,
+    signer: &S
+) -> Result<CancelOrderRequest, OrderRejection> {
+    let user_address = signer.address();
+    let signature = signer
+        .sign_hash(CancelOrderRequest::signing_hash(user_address, order_hash))
+        .await
+        .map_err(|e| OrderRejection::Signing(e.to_string()))?;
+
+    Ok(CancelOrderRequest { signature, user_address, order_id: order_hash })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{primitives::Address, signers::local::PrivateKeySigner};
+    use angstrom_types::{primitive::ANGSTROM_DOMAIN, sol_bindings::RawPoolOrder};
+
+    use super::*;
+    use crate::message::msg_type;
+
+    fn instrument() -> Instrument {
+        Instrument {
+            symbol:         "WETH/USDC".to_string(),
+            base:           Address::repeat_byte(2),
+            quote:          Address::repeat_byte(1),
+            base_decimals:  18,
+            quote_decimals: 6,
+            max_fee_asset0: 1_000
+        }
+    }
+
+    fn new_order_single(side: u8, qty: &str, min_qty: Option<&str>) -> FixMessage {
+        let mut message = FixMessage::new(msg_type::NEW_ORDER_SINGLE)
+            .with(tags::CL_ORD_ID, "order-1")
+            .with(tags::SYMBOL, "WETH/USDC")
+            .with(tags::SIDE, side)
+            .with(tags::ORDER_QTY, qty)
+            .with(tags::ORD_TYPE, 2)
+            .with(tags::PRICE, "2500.5");
+        if let Some(min_qty) = min_qty {
+            message.push(tags::MIN_QTY, min_qty);
+        }
+        message
+    }
+
+    #[test]
+    fn decimals_parse_and_scale() {
+        let qty = "1.25".parse::<Decimal>().unwrap();
+        assert_eq!(qty.to_string(), "1.25");
+        assert_eq!(qty.scaled(6), Some(U256::from(1_250_000)));
+        assert_eq!(qty.scaled(1), None);
+        assert_eq!("0.005".parse::<Decimal>().unwrap().to_string(), "0.005");
+        assert_eq!("12".parse::<Decimal>().unwrap().scaled(0), Some(U256::from(12)));
+        assert!("1.2.3".parse::<Decimal>().is_err());
+        assert!("-1".parse::<Decimal>().is_err());
+        assert!(".".parse::<Decimal>().is_err());
+    }
+
+    #[test]
+    fn sells_spend_base_at_the_limit_price() {
+        let message = new_order_single(2, "1.5", None);
+        let order = NewOrderSingle::from_message(&message).unwrap();
+        assert_eq!(order.deadline(100, 50), Ok(150));
+
+        let StandingVariants::Partial(partial) =
+            instrument().standing_order(&order, 7, 150).unwrap()
+        else {
+            panic!("order isn't all or none")
+        };
+        assert_eq!(partial.asset_in, instrument().base);
+        assert_eq!(partial.asset_out, instrument().quote);
+        assert_eq!(partial.max_amount_in, 1_500_000_000_000_000_000);
+        assert_eq!(partial.min_amount_in, 0);
+        assert_eq!(partial.nonce, 7);
+        // 2500.5 USDC per WETH is 2500.5 * 10^6 / 10^18 of the raw units
+        assert_eq!(partial.min_price, U256::from(2_500_500_000_000_000_000u64));
+    }
+
+    #[test]
+    fn all_or_none_buys_receive_exact_base() {
+        let message = new_order_single(1, "2", Some("2"));
+        let order = NewOrderSingle::from_message(&message).unwrap();
+
+        let StandingVariants::Exact(exact) = instrument().standing_order(&order, 0, 150).unwrap()
+        else {
+            panic!("order is all or none")
+        };
+        assert!(!exact.exact_in);
+        assert_eq!(exact.amount, 2_000_000_000_000_000_000);
+        assert_eq!(exact.asset_in, instrument().quote);
+        // paying 2500.5 USDC per WETH at most is receiving at least the inverse
+        let paid = Ray::from(exact.min_price).inverse_quantity(exact.amount, true);
+        assert!(paid <= 5_001_000_000);
+        assert!(paid >= 5_000_999_999);
+    }
+
+    #[test]
+    fn unsupported_orders_are_rejected() {
+        let instrument = instrument();
+        let order = NewOrderSingle::from_message(&new_order_single(2, "1", Some("1.5"))).unwrap();
+        assert_eq!(instrument.standing_order(&order, 0, 1), Err(OrderRejection::MinQty));
+
+        let order =
+            NewOrderSingle::from_message(&new_order_single(2, "0.0000000000000000001", None))
+                .unwrap();
+        assert_eq!(instrument.standing_order(&order, 0, 1), Err(OrderRejection::Quantity));
+
+        let mut order = NewOrderSingle::from_message(&new_order_single(2, "1", None)).unwrap();
+        order.ord_type = "1".to_string();
+        assert_eq!(instrument.standing_order(&order, 0, 1), Err(OrderRejection::OrdType));
+
+        let order = NewOrderSingle::from_message(
+            &new_order_single(2, "1", None).with(tags::TIME_IN_FORCE, 3)
+        )
+        .unwrap();
+        assert_eq!(order.deadline(0, 1), Err(OrderRejection::TimeInForce));
+
+        assert_eq!(
+            NewOrderSingle::from_message(&new_order_single(3, "1", None)),
+            Err(FixError::InvalidValue(tags::SIDE, "3".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn signed_orders_recover_to_the_signer() {
+        let signer = PrivateKeySigner::random();
+        let order = NewOrderSingle::from_message(&new_order_single(2, "1", None)).unwrap();
+        let order = instrument().standing_order(&order, 0, 150).unwrap();
+
+        let order = sign_order(order, &signer, &ANGSTROM_DOMAIN).await.unwrap();
+        assert_eq!(order.from(), signer.address());
+        assert!(order.is_valid_signature(&ANGSTROM_DOMAIN));
+
+        let cancel = sign_cancel(order.order_hash(), &signer).await.unwrap();
+        assert!(cancel.is_valid());
+    }
+}
@@ -1,4 +1,6 @@
 use alloy::primitives::FixedBytes;
+#[cfg(feature = "lp-solver")]
+use matching_engine::solver::{LpSolver, Solver, SolverConstraints};
 use matching_engine::strategy::{MatchingStrategy, SimpleCheckpointStrategy};
 use rand::{thread_rng, Rng};
 use testing_tools::type_generator::book::{generate_one_sided_book, generate_simple_cross_book};
@@ -31,3 +33,16 @@ fn one_sided_book<const N: usize>(bencher: divan::Bencher) {
         })
         .bench_refs(|book| SimpleCheckpointStrategy::run(book).map(|s| s.solution(None)));
 }
+
+/// The exact solver against the same books, it only takes small ones
+#[cfg(feature = "lp-solver")]
+#[divan::bench(consts = [1, 10, 50])]
+fn simple_cross_book_lp<const N: usize>(bencher: divan::Bencher) {
+    let solver = LpSolver::new().with_max_orders(usize::MAX);
+    bencher
+        .with_inputs(|| {
+            let pool_id = FixedBytes::<32>::random();
+            generate_simple_cross_book(pool_id, N, CENTER_PRICE)
+        })
+        .bench_refs(|book| solver.solve(book, &SolverConstraints::default()));
+}
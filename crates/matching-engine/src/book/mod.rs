@@ -1,8 +1,13 @@
 //! basic book impl so we can benchmark
+use std::cmp::Ordering;
+
 use angstrom_types::{
     matching::uniswap::PoolSnapshot,
     primitive::PoolId,
-    sol_bindings::grouped_orders::{GroupedVanillaOrder, OrderWithStorageData}
+    sol_bindings::{
+        grouped_orders::{GroupedVanillaOrder, OrderWithStorageData},
+        RawPoolOrder
+    }
 };
 use serde::{Deserialize, Serialize};
 
@@ -13,7 +18,7 @@ pub type BookOrder = OrderWithStorageData<GroupedVanillaOrder>;
 pub mod order;
 pub mod sort;
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct OrderBook {
     id:   PoolId,
     amm:  Option<PoolSnapshot>,
@@ -51,6 +56,37 @@ impl OrderBook {
     pub fn amm(&self) -> Option<&PoolSnapshot> {
         self.amm.as_ref()
     }
+
+    pub fn set_amm(&mut self, amm: Option<PoolSnapshot>) {
+        self.amm = amm;
+    }
+
+    pub fn retain_orders(&mut self, mut keep: impl FnMut(&BookOrder) -> bool) {
+        self.bids.retain(&mut keep);
+        self.asks.retain(keep);
+    }
+
+    /// Inserts `order` where [`crate::build_book`] with `sort` puts it, without
+    /// sorting the book again. Orders that compare equal keep the order they
+    /// were inserted in.
+    pub fn insert_order(&mut self, order: BookOrder, sort: SortStrategy) {
+        let is_bid = order.is_bid;
+        let side = if is_bid { &mut self.bids } else { &mut self.asks };
+        let at = side.partition_point(|o| book_cmp(sort, is_bid, o, &order) != Ordering::Greater);
+        side.insert(at, order);
+    }
+}
+
+/// The order of a side of the book: by `sort`, then by limit price, the best
+/// first.
+fn book_cmp(sort: SortStrategy, is_bid: bool, a: &BookOrder, b: &BookOrder) -> Ordering {
+    sort.compare(a, b).then_with(|| {
+        if is_bid {
+            b.limit_price().cmp(&a.limit_price())
+        } else {
+            a.limit_price().cmp(&b.limit_price())
+        }
+    })
 }
 
 #[cfg(test)]
@@ -2,9 +2,7 @@ use angstrom_types::{
     matching::{max_t1_for_t0, uniswap::Direction, CompositeOrder, Debt, DebtType},
     orders::{OrderFillState, OrderId, OrderPrice, OrderVolume},
     sol_bindings::{
-        grouped_orders::{
-            FlashVariants, GroupedVanillaOrder, OrderWithStorageData, StandingVariants
-        },
+        grouped_orders::{GroupedVanillaOrder, OrderWithStorageData},
         RawPoolOrder
     }
 };
@@ -130,17 +128,22 @@ impl<'a> OrderContainer<'a> {
     /// Is the underlying order a Partial Fill compatible order
     pub fn is_partial(&self) -> bool {
         match self {
-            Self::BookOrder { order, .. } => {
-                matches!(
-                    order.order,
-                    GroupedVanillaOrder::Standing(StandingVariants::Partial(_))
-                        | GroupedVanillaOrder::KillOrFill(FlashVariants::Partial(_))
-                )
-            }
+            Self::BookOrder { order, .. } => order.is_partial(),
             Self::Composite(_) => false
         }
     }
 
+    /// Whether the order can settle in `state`. A partially filled order has
+    /// to be partial and filled for at least its minimum
+    pub fn accepts_fill(&self, state: OrderFillState) -> bool {
+        match (self, state) {
+            (Self::BookOrder { order, .. }, OrderFillState::PartialFill(filled)) => {
+                order.accepts_partial_fill(filled)
+            }
+            _ => true
+        }
+    }
+
     /// If `true`, this is an inverse order that operates with T1 as a base
     /// quantity instead of T0.  That means this order will cause or react to
     /// debt
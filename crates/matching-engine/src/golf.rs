@@ -0,0 +1,117 @@
+//! Picks the cheapest way of laying out a solved block's bundle. Every
+//! [`BundleLayout`] settles the same fills, so the only thing that changes
+//! between them is the gas the bundle costs, which is simulated.
+use angstrom_types::contract_payloads::angstrom::{AngstromBundle, BundleLayout};
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use validation::bundle::BundleValidatorHandle;
+
+/// The simulated gas of a layout, or why its simulation failed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutGas {
+    pub layout: BundleLayout,
+    pub gas:    Result<u64, String>
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasReport {
+    pub layouts: Vec<LayoutGas>
+}
+
+impl GasReport {
+    /// The layout that simulated with the least gas. On a tie the one that
+    /// comes first in [`BundleLayout::all`] wins, so a bundle isn't changed
+    /// for nothing.
+    pub fn cheapest(&self) -> Option<(BundleLayout, u64)> {
+        self.layouts
+            .iter()
+            .filter_map(|entry| Some((entry.layout, *entry.gas.as_ref().ok()?)))
+            .min_by_key(|(_, gas)| *gas)
+    }
+
+    pub fn as_built(&self) -> Option<u64> {
+        self.layouts
+            .iter()
+            .find(|entry| entry.layout == BundleLayout::AS_BUILT)
+            .and_then(|entry| entry.gas.clone().ok())
+    }
+
+    /// Gas the cheapest layout saves over the bundle as it was built.
+    pub fn savings(&self) -> Option<u64> {
+        Some(self.as_built()?.saturating_sub(self.cheapest()?.1))
+    }
+}
+
+/// Simulates `bundle` in every layout.
+pub async fn gas_report<V: BundleValidatorHandle>(
+    validation: &V,
+    bundle: &AngstromBundle
+) -> GasReport {
+    let layouts = BundleLayout::all();
+    let simulations = layouts
+        .iter()
+        .map(|layout| validation.fetch_gas_for_bundle(bundle.with_layout(*layout)));
+
+    let layouts = layouts
+        .iter()
+        .zip(join_all(simulations).await)
+        .map(|(layout, res)| LayoutGas {
+            layout: *layout,
+            gas:    res
+                .map(|details| details.total_gas_cost_wei())
+                .map_err(|e| e.to_string())
+        })
+        .collect();
+
+    GasReport { layouts }
+}
+
+/// Lays `bundle` out in the cheapest of the layouts that simulated. Keeps it
+/// as it is when none did.
+pub async fn golf_bundle<V: BundleValidatorHandle>(
+    validation: &V,
+    bundle: AngstromBundle
+) -> (AngstromBundle, GasReport) {
+    let report = gas_report(validation, &bundle).await;
+    let bundle = match report.cheapest() {
+        Some((layout, _)) if layout != BundleLayout::AS_BUILT => bundle.with_layout(layout),
+        _ => bundle
+    };
+
+    (bundle, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(layout: BundleLayout, gas: Result<u64, &str>) -> LayoutGas {
+        LayoutGas { layout, gas: gas.map_err(str::to_string) }
+    }
+
+    #[test]
+    fn cheapest_skips_failed_layouts_and_prefers_as_built_on_ties() {
+        let layouts = BundleLayout::all();
+        let report = GasReport {
+            layouts: vec![
+                entry(layouts[0], Ok(100)),
+                entry(layouts[1], Err("reverted")),
+                entry(layouts[2], Ok(100)),
+            ]
+        };
+        assert_eq!(report.cheapest(), Some((BundleLayout::AS_BUILT, 100)));
+        assert_eq!(report.savings(), Some(0));
+
+        let report = GasReport {
+            layouts: vec![
+                entry(layouts[0], Ok(100)),
+                entry(layouts[1], Err("reverted")),
+                entry(layouts[2], Ok(90)),
+            ]
+        };
+        assert_eq!(report.cheapest(), Some((layouts[2], 90)));
+        assert_eq!(report.savings(), Some(10));
+    }
+}
@@ -0,0 +1,276 @@
+//! Incremental solving for pools with large books.
+//!
+//! Most of the orders resting in a large book are still there the next block.
+//! Instead of building and sorting the book of every pool from scratch, the
+//! [`IncrementalMatcher`] keeps the sorted book of each pool between blocks and
+//! only inserts the orders that were added and drops the ones that went away,
+//! along with the new AMM snapshot. A pool whose book and AMM didn't change at
+//! all reuses the solution of the last block.
+//!
+//! The book is built from scratch when there is nothing to build on, when the
+//! sort strategy gives no order to insert into, when the pool gained or lost
+//! its AMM, or when so much of the book changed that sorting it again is
+//! cheaper.
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc
+};
+
+use alloy_primitives::Address;
+use angstrom_metrics::track_allocations;
+use angstrom_types::{
+    matching::uniswap::PoolSnapshot,
+    orders::PoolSolution,
+    primitive::{PoolId, TransferTaxes},
+    sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder}
+};
+use tokio::task::JoinSet;
+
+use crate::{
+    book::{sort::SortStrategy, BookOrder, OrderBook},
+    build_book,
+    solver::{solve_after_tax, Solver, SolverConstraints}
+};
+
+/// How the book of a pool was brought up to date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookUpdate {
+    /// nothing changed since the last block
+    Unchanged,
+    /// the orders that changed were inserted and removed
+    Incremental { added: usize, removed: usize },
+    /// the book was built from scratch
+    Full
+}
+
+/// The book of a pool as of the last block, with its solution.
+#[derive(Debug, Default)]
+pub struct PoolSolveState {
+    book:   Option<OrderBook>,
+    orders: HashSet<BookOrder>,
+    /// the solution of the book as it is, along with the searcher order it
+    /// was solved with
+    solved: Option<(Option<OrderWithStorageData<TopOfBlockOrder>>, Option<PoolSolution>)>
+}
+
+impl PoolSolveState {
+    /// Brings the book up to date with `orders` and `amm`.
+    pub fn update(
+        &mut self,
+        id: PoolId,
+        orders: HashSet<BookOrder>,
+        amm: Option<PoolSnapshot>,
+        sort: SortStrategy
+    ) -> BookUpdate {
+        let Some(book) = self
+            .book
+            .as_mut()
+            .filter(|book| sort != SortStrategy::Unsorted && book.amm().is_some() == amm.is_some())
+        else {
+            return self.rebuild(id, orders, amm, sort)
+        };
+
+        let added = orders.difference(&self.orders).cloned().collect::<Vec<_>>();
+        let removed = self.orders.difference(&orders).count();
+        // inserting into a vec moves the orders behind, past a point sorting the
+        // book again is cheaper
+        if (added.len() + removed) * 2 > self.orders.len() {
+            return self.rebuild(id, orders, amm, sort)
+        }
+
+        if added.is_empty() && removed == 0 && book.amm() == amm.as_ref() {
+            return BookUpdate::Unchanged
+        }
+
+        if removed != 0 {
+            book.retain_orders(|order| orders.contains(order));
+        }
+        for order in &added {
+            book.insert_order(order.clone(), sort);
+        }
+        book.set_amm(amm);
+        self.orders = orders;
+        self.solved = None;
+
+        BookUpdate::Incremental { added: added.len(), removed }
+    }
+
+    fn rebuild(
+        &mut self,
+        id: PoolId,
+        orders: HashSet<BookOrder>,
+        amm: Option<PoolSnapshot>,
+        sort: SortStrategy
+    ) -> BookUpdate {
+        self.book = Some(build_book(id, amm, orders.clone(), sort));
+        self.orders = orders;
+        self.solved = None;
+
+        BookUpdate::Full
+    }
+
+    /// Solves the book as of the last [`Self::update`], or returns the last
+    /// solution if neither the book nor the searcher order changed since. The
+    /// transfer taxes are taken to be the same every time.
+    pub fn solve(
+        &mut self,
+        solver: &dyn Solver,
+        constraints: SolverConstraints
+    ) -> Option<PoolSolution> {
+        if let Some((solved_for, solution)) = &self.solved {
+            if *solved_for == constraints.searcher {
+                return solution.clone()
+            }
+        }
+
+        let book = self.book.as_ref()?;
+        let solution = Some(solve_after_tax(solver, book, &constraints).solution);
+        self.solved = Some((constraints.searcher, solution.clone()));
+
+        solution
+    }
+}
+
+/// Keeps the solve state of every pool between blocks, see the
+/// [module docs](self).
+#[derive(Debug)]
+pub struct IncrementalMatcher {
+    sort:           SortStrategy,
+    solver:         Arc<dyn Solver>,
+    transfer_taxes: TransferTaxes,
+    pools:          HashMap<PoolId, PoolSolveState>
+}
+
+impl IncrementalMatcher {
+    pub fn new(sort: SortStrategy, solver: Arc<dyn Solver>) -> Self {
+        Self { sort, solver, transfer_taxes: TransferTaxes::default(), pools: HashMap::new() }
+    }
+
+    pub fn with_transfer_taxes(mut self, transfer_taxes: TransferTaxes) -> Self {
+        self.transfer_taxes = transfer_taxes;
+        self
+    }
+
+    /// Solves the book of every pool with orders, each on a blocking task of
+    /// its own. Pools without orders this time lose their state.
+    pub async fn solve_pools(
+        &mut self,
+        limit: Vec<BookOrder>,
+        searcher: &HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>>,
+        pool_snapshots: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
+    ) -> Vec<PoolSolution> {
+        let orders = limit.into_iter().fold(
+            HashMap::<PoolId, HashSet<BookOrder>>::new(),
+            |mut acc, order| {
+                acc.entry(order.pool_id).or_default().insert(order);
+                acc
+            }
+        );
+
+        let mut states = std::mem::take(&mut self.pools);
+        let mut solves = JoinSet::new();
+        for (id, orders) in orders {
+            let mut state = states.remove(&id).unwrap_or_default();
+            let amm = pool_snapshots.get(&id).map(|pool| pool.2.clone());
+            let constraints = SolverConstraints {
+                searcher:       searcher.get(&id).cloned(),
+                transfer_taxes: self.transfer_taxes.clone()
+            };
+            let sort = self.sort;
+            let solver = self.solver.clone();
+
+            solves.spawn_blocking(move || {
+                track_allocations("matcher_solve", || {
+                    let update = state.update(id, orders, amm, sort);
+                    tracing::debug!(?id, ?update, "updated book");
+                    let solution = state.solve(solver.as_ref(), constraints);
+                    (id, state, solution)
+                })
+            });
+        }
+
+        let mut solutions = Vec::new();
+        while let Some(res) = solves.join_next().await {
+            // a pool whose solve panicked starts from scratch next time
+            let Ok((id, state, solution)) = res else { continue };
+            self.pools.insert(id, state);
+            solutions.extend(solution);
+        }
+
+        solutions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::U256;
+    use angstrom_types::sol_bindings::RawPoolOrder;
+    use testing_tools::type_generator::orders::{DistributionParameters, OrderDistributionBuilder};
+
+    use super::*;
+    use crate::{
+        solver::VolumeFillSolver,
+        strategy::{MatchingStrategy, SimpleCheckpointStrategy}
+    };
+
+    fn orders(pool_id: PoolId, is_bid: bool, count: usize) -> Vec<BookOrder> {
+        let (bids, asks) = DistributionParameters::crossed_at(100_000_000.0);
+        OrderDistributionBuilder::new()
+            .is_bid(is_bid)
+            .order_count(count)
+            .price_params(if is_bid { bids } else { asks })
+            .volume_params(DistributionParameters::fixed_at(100.0).0)
+            .pool_id(pool_id)
+            .build()
+            .unwrap()
+    }
+
+    /// Books are compared by the keys they are sorted by, orders with the same
+    /// keys can be in any order.
+    fn sort_keys(book: &OrderBook) -> Vec<(bool, U256)> {
+        book.bids()
+            .iter()
+            .chain(book.asks())
+            .map(|order| (order.is_bid, order.limit_price()))
+            .collect()
+    }
+
+    #[test]
+    fn incremental_book_matches_a_full_build() {
+        let pool_id = PoolId::random();
+        let sort = SortStrategy::ByPriceByVolume;
+        let mut book = orders(pool_id, true, 100);
+        book.extend(orders(pool_id, false, 100));
+
+        let mut state = PoolSolveState::default();
+        let update = state.update(pool_id, book.iter().cloned().collect(), None, sort);
+        assert_eq!(update, BookUpdate::Full);
+        let solution = state.solve(&VolumeFillSolver, SolverConstraints::default());
+
+        let update = state.update(pool_id, book.iter().cloned().collect(), None, sort);
+        assert_eq!(update, BookUpdate::Unchanged);
+        assert_eq!(state.solve(&VolumeFillSolver, SolverConstraints::default()), solution);
+
+        // a few orders are filled and a few new ones come in
+        book.drain(..5);
+        book.drain(95..100);
+        book.extend(orders(pool_id, true, 5));
+        book.extend(orders(pool_id, false, 5));
+        let update = state.update(pool_id, book.iter().cloned().collect(), None, sort);
+        assert_eq!(update, BookUpdate::Incremental { added: 10, removed: 10 });
+
+        let full = build_book(pool_id, None, book.iter().cloned().collect(), sort);
+        assert_eq!(sort_keys(state.book.as_ref().unwrap()), sort_keys(&full));
+        assert_eq!(
+            state
+                .solve(&VolumeFillSolver, SolverConstraints::default())
+                .map(|solution| solution.ucp),
+            SimpleCheckpointStrategy::run(&full).map(|s| s.solution(None).ucp)
+        );
+
+        // most of the book changed
+        let update =
+            state.update(pool_id, orders(pool_id, true, 10).into_iter().collect(), None, sort);
+        assert_eq!(update, BookUpdate::Full);
+    }
+}
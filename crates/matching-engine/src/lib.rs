@@ -7,27 +7,33 @@ use alloy::providers::Provider;
 use alloy_primitives::{Address, BlockNumber};
 use angstrom_types::{
     block_sync::BlockSyncConsumer,
-    contract_payloads::angstrom::BundleGasDetails,
+    contract_payloads::angstrom::{AngstromBundle, BundleGasDetails},
     matching::uniswap::PoolSnapshot,
     orders::PoolSolution,
-    primitive::{PoolId, UniswapPoolRegistry},
+    primitive::{PoolId, TokenPair, UniswapPoolRegistry},
     sol_bindings::{
         grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder, RawPoolOrder
     }
 };
-use book::{BookOrder, OrderBook};
+use book::{sort::SortStrategy, BookOrder, OrderBook};
 use futures_util::future::BoxFuture;
+use golf::GasReport;
 use reth_provider::CanonStateNotifications;
 use uniswap_v4::uniswap::{
     pool::EnhancedUniswapPool, pool_data_loader::DataLoader, pool_manager::UniswapPoolManager,
     pool_providers::canonical_state_adapter::CanonicalStateAdapter
 };
+use verification::VerificationReport;
 
 pub mod book;
+pub mod golf;
+pub mod incremental;
 pub mod manager;
 pub mod matcher;
 pub mod simulation;
+pub mod solver;
 pub mod strategy;
+pub mod verification;
 
 pub use manager::MatchingManager;
 
@@ -38,9 +44,34 @@ pub trait MatchingEngineHandle: Send + Sync + Clone + Unpin + 'static {
         searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>,
         pools: HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
     ) -> BoxFuture<eyre::Result<(Vec<PoolSolution>, BundleGasDetails)>>;
+
+    /// Checks a solution a peer proposed for `book`, see
+    /// [`verification::verify_solution`].
+    fn verify_solution(&self, book: &OrderBook, proposed: &PoolSolution) -> VerificationReport {
+        verification::verify_solution(book, proposed)
+    }
+
+    /// Lays `bundle` out the cheapest way, see [`golf::golf_bundle`]. Handles
+    /// that can't simulate return it as it is.
+    fn golf_bundle(&self, bundle: AngstromBundle) -> BoxFuture<(AngstromBundle, GasReport)> {
+        Box::pin(async move { (bundle, GasReport::default()) })
+    }
 }
 
-pub fn build_book(id: PoolId, amm: Option<PoolSnapshot>, orders: HashSet<BookOrder>) -> OrderBook {
+pub fn build_book(
+    id: PoolId,
+    amm: Option<PoolSnapshot>,
+    orders: HashSet<BookOrder>,
+    sort: SortStrategy
+) -> OrderBook {
+    debug_assert!(
+        orders.iter().all(|o| {
+            TokenPair::for_order(o.token_in(), o.token_out())
+                .map_or(true, |(_, is_bid)| is_bid == o.is_bid)
+        }),
+        "an order of pool {id:?} is on the wrong side of the book"
+    );
+
     let (mut bids, mut asks): (Vec<BookOrder>, Vec<BookOrder>) =
         orders.into_iter().partition(|o| o.is_bid);
 
@@ -48,7 +79,7 @@ pub fn build_book(id: PoolId, amm: Option<PoolSnapshot>, orders: HashSet<BookOrd
     bids.sort_by_key(|b| std::cmp::Reverse(b.limit_price()));
     asks.sort_by_key(|a| a.limit_price());
 
-    OrderBook::new(id, amm, bids, asks, Some(book::sort::SortStrategy::ByPriceByVolume))
+    OrderBook::new(id, amm, bids, asks, Some(sort))
 }
 
 pub async fn configure_uniswap_manager<BlockSync: BlockSyncConsumer>(
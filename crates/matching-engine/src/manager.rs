@@ -4,17 +4,18 @@ use std::{
     sync::Arc
 };
 
-use alloy_primitives::Address;
+use alloy_primitives::{Address, B256};
+use angstrom_metrics::track_allocations;
 use angstrom_types::{
     consensus::PreProposal,
     contract_payloads::angstrom::{AngstromBundle, BundleGasDetails},
     matching::{match_estimate_response::BundleEstimate, uniswap::PoolSnapshot},
-    orders::PoolSolution,
-    primitive::PoolId,
+    orders::{median_validated_at, PoolSolution},
+    primitive::{Feature, FeatureFlags, PoolId, TransferTaxes},
     sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder}
 };
-use futures::{stream::FuturesUnordered, Future};
-use futures_util::FutureExt;
+use futures::{future::join_all, stream::FuturesUnordered, Future};
+use futures_util::{future::BoxFuture, FutureExt};
 use reth_tasks::TaskSpawner;
 use tokio::{
     sync::{
@@ -27,9 +28,13 @@ use tracing::trace;
 use validation::bundle::BundleValidatorHandle;
 
 use crate::{
-    book::{BookOrder, OrderBook},
+    book::{sort::SortStrategy, BookOrder, OrderBook},
     build_book,
+    golf::{golf_bundle, GasReport},
+    incremental::IncrementalMatcher,
+    solver::{solve_after_tax, Solver, SolverConstraints, SolverKind},
     strategy::{MatchingStrategy, SimpleCheckpointStrategy},
+    verification::verify_transfer_taxes,
     MatchingEngineHandle
 };
 
@@ -45,7 +50,8 @@ pub enum MatcherCommand {
         searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>,
         pools:    HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>,
         tx:       oneshot::Sender<eyre::Result<BundleEstimate>>
-    }
+    },
+    GolfBundle(AngstromBundle, oneshot::Sender<(AngstromBundle, GasReport)>)
 }
 
 #[derive(Debug, Clone)]
@@ -70,45 +76,176 @@ impl MatchingEngineHandle for MatcherHandle {
         limit: Vec<BookOrder>,
         searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>,
         pools: HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
-    ) -> futures_util::future::BoxFuture<eyre::Result<(Vec<PoolSolution>, BundleGasDetails)>> {
+    ) -> BoxFuture<eyre::Result<(Vec<PoolSolution>, BundleGasDetails)>> {
         Box::pin(async move {
             let (tx, rx) = oneshot::channel();
             self.send_request(rx, MatcherCommand::BuildProposal(limit, searcher, pools, tx))
                 .await
         })
     }
+
+    fn golf_bundle(&self, bundle: AngstromBundle) -> BoxFuture<(AngstromBundle, GasReport)> {
+        Box::pin(async move {
+            let (tx, rx) = oneshot::channel();
+            self.send_request(rx, MatcherCommand::GolfBundle(bundle, tx))
+                .await
+        })
+    }
 }
 
+/// Proposals spanning at least this many pools are pre-screened pool by pool
+/// when parallel simulation is enabled.
+pub const PARALLEL_SIMULATION_MIN_POOLS: usize = 4;
+
 pub struct MatchingManager<TP: TaskSpawner, V> {
-    _futures:          FuturesUnordered<Pin<Box<dyn Future<Output = ()> + Sync + Send + 'static>>>,
-    validation_handle: V,
-    _tp:               Arc<TP>
+    _futures: FuturesUnordered<Pin<Box<dyn Future<Output = ()> + Sync + Send + 'static>>>,
+    validation_handle:   V,
+    tp:                  Arc<TP>,
+    /// simulate the part of the bundle of each pool on its own before the
+    /// full bundle. Changes which pools are solved, so it comes from the
+    /// deployment
+    parallel_simulation: bool,
+    /// how the books are sorted before matching
+    sort:                SortStrategy,
+    /// solves the book of every pool
+    solver:              Arc<dyn Solver>,
+    /// the fee-on-transfer tokens, orders that would get less than their limit
+    /// after the tax are left out of the solutions
+    transfer_taxes:      TransferTaxes,
+    /// keeps the books between blocks and only updates them with what
+    /// changed, instead of building them from scratch every time. Only set
+    /// while [`Feature::IncrementalMatching`] is enabled
+    incremental:         Option<IncrementalMatcher>,
+    feature_flags:       FeatureFlags
 }
 
 impl<TP: TaskSpawner + 'static, V: BundleValidatorHandle> MatchingManager<TP, V> {
     pub fn new(tp: TP, validation: V) -> Self {
         Self {
-            _futures:          FuturesUnordered::default(),
-            validation_handle: validation,
-            _tp:               tp.into()
+            _futures:            FuturesUnordered::default(),
+            validation_handle:   validation,
+            tp:                  tp.into(),
+            parallel_simulation: false,
+            sort:                SortStrategy::ByPriceByVolume,
+            solver:              SolverKind::default().solver(),
+            transfer_taxes:      TransferTaxes::default(),
+            incremental:         None,
+            feature_flags:       FeatureFlags::default()
         }
     }
 
+    pub fn with_parallel_simulation(mut self, parallel_simulation: bool) -> Self {
+        self.parallel_simulation = parallel_simulation;
+        self
+    }
+
+    pub fn with_sort_strategy(mut self, sort: SortStrategy) -> Self {
+        self.sort = sort;
+        // rebuilt with the new strategy on the next proposal
+        self.incremental = None;
+        self
+    }
+
+    pub fn with_solver(mut self, solver: Arc<dyn Solver>) -> Self {
+        self.solver = solver;
+        self.incremental = None;
+        self
+    }
+
+    pub fn with_transfer_taxes(mut self, transfer_taxes: TransferTaxes) -> Self {
+        self.transfer_taxes = transfer_taxes;
+        self.incremental = None;
+        self
+    }
+
+    /// With [`Feature::IncrementalMatching`] enabled the pools are solved
+    /// incrementally, see [`crate::incremental`]. Meant for pools with
+    /// thousands of resting orders.
+    pub fn with_feature_flags(mut self, feature_flags: FeatureFlags) -> Self {
+        self.feature_flags = feature_flags;
+        self
+    }
+
     pub fn spawn(tp: TP, validation: V) -> MatcherHandle {
+        Self::spawn_with_parallel_simulation(tp, validation, false)
+    }
+
+    /// Spawns the manager. With parallel simulation, the pools of a proposal
+    /// spanning at least [`PARALLEL_SIMULATION_MIN_POOLS`] are simulated on
+    /// their own before the full bundle.
+    pub fn spawn_with_parallel_simulation(
+        tp: TP,
+        validation: V,
+        parallel_simulation: bool
+    ) -> MatcherHandle {
+        Self::spawn_with_config(
+            tp,
+            validation,
+            parallel_simulation,
+            SortStrategy::ByPriceByVolume,
+            SolverKind::default(),
+            TransferTaxes::default(),
+            FeatureFlags::default()
+        )
+    }
+
+    /// Spawns the manager building its books with `sort`, which has to be the
+    /// strategy of the deployment for the solutions to match the ones of the
+    /// other validators, as does `parallel_simulation`. While
+    /// [`Feature::IncrementalMatching`] is enabled the books are kept
+    /// between blocks, see [`crate::incremental`]. The books are solved
+    /// with `solver`, which unlike the sort strategy can differ
+    /// between validators, see [`crate::solver`]. Orders that would get less
+    /// than their limit after the `transfer_taxes` of the deployment aren't
+    /// filled.
+    #[allow(clippy::too_many_arguments)]
+    pub fn spawn_with_config(
+        tp: TP,
+        validation: V,
+        parallel_simulation: bool,
+        sort: SortStrategy,
+        solver: SolverKind,
+        transfer_taxes: TransferTaxes,
+        feature_flags: FeatureFlags
+    ) -> MatcherHandle {
         let (tx, rx) = tokio::sync::mpsc::channel(100);
         let tp = Arc::new(tp);
 
-        let fut = manager_thread(rx, tp.clone(), validation).boxed();
+        let fut = manager_thread(
+            rx,
+            tp.clone(),
+            validation,
+            parallel_simulation,
+            sort,
+            solver.solver(),
+            transfer_taxes,
+            feature_flags
+        )
+        .boxed();
         tp.spawn_critical("matching_engine", fut);
 
         MatcherHandle { sender: tx }
     }
 
+    /// The orders of the pre-proposals by pool. Every node stamps the orders it
+    /// validates with its own time, so an order in several pre-proposals is
+    /// taken once, with the median of its timestamps.
     pub fn orders_by_pool_id(preproposals: &[PreProposal]) -> HashMap<PoolId, HashSet<BookOrder>> {
         preproposals
             .iter()
             .flat_map(|p| p.limit.iter())
-            .cloned()
+            .fold(HashMap::<B256, (BookOrder, Vec<u64>)>::new(), |mut acc, order| {
+                acc.entry(order.order_id.hash)
+                    .or_insert_with(|| (order.clone(), vec![]))
+                    .1
+                    .push(order.priority_data.validated_at);
+                acc
+            })
+            .into_values()
+            .map(|(mut order, stamps)| {
+                order.priority_data.validated_at = median_validated_at(stamps);
+                order
+            })
             .fold(HashMap::new(), |mut acc, order| {
                 acc.entry(order.pool_id).or_default().insert(order);
                 acc
@@ -117,7 +254,8 @@ impl<TP: TaskSpawner + 'static, V: BundleValidatorHandle> MatchingManager<TP, V>
 
     pub fn build_non_proposal_books(
         limit: Vec<BookOrder>,
-        pool_snapshots: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
+        pool_snapshots: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>,
+        sort: SortStrategy
     ) -> Vec<OrderBook> {
         let book_sources = Self::orders_sorted_by_pool_id(limit);
 
@@ -125,14 +263,15 @@ impl<TP: TaskSpawner + 'static, V: BundleValidatorHandle> MatchingManager<TP, V>
             .into_iter()
             .map(|(id, orders)| {
                 let amm = pool_snapshots.get(&id).map(|value| value.2.clone());
-                build_book(id, amm, orders)
+                build_book(id, amm, orders, sort)
             })
             .collect()
     }
 
     pub fn build_books(
         preproposals: &[PreProposal],
-        pool_snapshots: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
+        pool_snapshots: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>,
+        sort: SortStrategy
     ) -> Vec<OrderBook> {
         // Pull all the orders out of all the preproposals and build OrderPools out of
         // them.  This is ugly and inefficient right now
@@ -142,56 +281,210 @@ impl<TP: TaskSpawner + 'static, V: BundleValidatorHandle> MatchingManager<TP, V>
             .into_iter()
             .map(|(id, orders)| {
                 let amm = pool_snapshots.get(&id).map(|v| v.2.clone());
-                build_book(id, amm, orders)
+                build_book(id, amm, orders, sort)
             })
             .collect()
     }
 
     pub async fn build_proposal(
-        &self,
+        &mut self,
         limit: Vec<BookOrder>,
         searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>,
         pool_snapshots: HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
     ) -> eyre::Result<(Vec<PoolSolution>, BundleGasDetails)> {
         tracing::info!("starting to build proposal");
-        // Pull all the orders out of all the preproposals and build OrderPools out of
-        // them.  This is ugly and inefficient right now
-        let books = Self::build_non_proposal_books(limit.clone(), &pool_snapshots);
-
         let searcher_orders: HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>> =
             searcher.into_iter().fold(HashMap::new(), |mut acc, order| {
                 acc.entry(order.pool_id).or_insert(order);
                 acc
             });
 
+        // the flag can be switched between blocks, the kept books are dropped
+        // when it is turned off and built anew when it is turned back on
+        if !self.feature_flags.is_enabled(Feature::IncrementalMatching) {
+            self.incremental = None;
+        } else if self.incremental.is_none() {
+            self.incremental = Some(
+                IncrementalMatcher::new(self.sort, self.solver.clone())
+                    .with_transfer_taxes(self.transfer_taxes.clone())
+            );
+        }
+
+        let mut solutions = match self.incremental.as_mut() {
+            Some(incremental) => {
+                incremental
+                    .solve_pools(limit.clone(), &searcher_orders, &pool_snapshots)
+                    .await
+            }
+            None => {
+                self.solve_books(limit.clone(), &searcher_orders, &pool_snapshots)
+                    .await
+            }
+        };
+
+        if self.parallel_simulation && solutions.len() >= PARALLEL_SIMULATION_MIN_POOLS {
+            solutions = self
+                .prescreen_solutions(&limit, solutions, &pool_snapshots)
+                .await;
+        }
+
+        let solutions = self.check_transfer_taxes(&limit, solutions, &pool_snapshots);
+
+        // generate bundle without final gas known.
+        trace!("Building bundle for gas finalization");
+        let bundle =
+            AngstromBundle::for_gas_finalization(limit, solutions.clone(), &pool_snapshots)?;
+
+        println!("{:#?}", bundle);
+        let gas_response = self.validation_handle.fetch_gas_for_bundle(bundle).await?;
+
+        Ok((solutions, gas_response))
+    }
+
+    /// Builds the book of every pool from scratch and solves it.
+    async fn solve_books(
+        &self,
+        limit: Vec<BookOrder>,
+        searcher_orders: &HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>>,
+        pool_snapshots: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
+    ) -> Vec<PoolSolution> {
+        // Pull all the orders out of all the preproposals and build OrderPools out of
+        // them.  This is ugly and inefficient right now
+        let books = Self::build_non_proposal_books(limit, pool_snapshots, self.sort);
+
         let mut solution_set = JoinSet::new();
         books.into_iter().for_each(|b| {
-            let searcher = searcher_orders.get(&b.id()).cloned();
+            let constraints = SolverConstraints {
+                searcher:       searcher_orders.get(&b.id()).cloned(),
+                transfer_taxes: self.transfer_taxes.clone()
+            };
+            let solver = self.solver.clone();
             // Using spawn-blocking here is not BAD but it might be suboptimal as it allows
             // us to spawn many more tasks that the CPu has threads.  Better solution is a
             // dedicated threadpool and some suggest the `rayon` crate.  This is probably
             // not a problem while I'm testing, but leaving this note here as it may be
             // important for future efficiency gains
             solution_set.spawn_blocking(move || {
-                SimpleCheckpointStrategy::run(&b).map(|s| s.solution(searcher))
+                let output = track_allocations("matcher_solve", || {
+                    solve_after_tax(solver.as_ref(), &b, &constraints)
+                });
+                if !output.artifacts.verification.is_valid() {
+                    // peers will reject this solution, but proposing it shows where the
+                    // solver is wrong
+                    tracing::warn!(
+                        pool_id = ?b.id(),
+                        artifacts = ?output.artifacts,
+                        "solver produced a solution that fails verification"
+                    );
+                }
+                output.solution
             });
         });
         let mut solutions = Vec::new();
         while let Some(res) = solution_set.join_next().await {
-            if let Ok(Some(r)) = res {
+            if let Ok(r) = res {
                 solutions.push(r);
             }
         }
 
-        // generate bundle without final gas known.
-        trace!("Building bundle for gas finalization");
-        let bundle =
-            AngstromBundle::for_gas_finalization(limit, solutions.clone(), &pool_snapshots)?;
+        solutions
+    }
 
-        println!("{:#?}", bundle);
-        let gas_response = self.validation_handle.fetch_gas_for_bundle(bundle).await?;
+    /// Makes sure no order of the bundle gets less than its limit once the
+    /// transfer taxes are taken, as the simulation can't tell. The solver
+    /// already leaves such orders out, a pool whose solution still fills one
+    /// settles its AMM only.
+    fn check_transfer_taxes(
+        &self,
+        limit: &[BookOrder],
+        solutions: Vec<PoolSolution>,
+        pool_snapshots: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
+    ) -> Vec<PoolSolution> {
+        if self.transfer_taxes.is_empty() {
+            return solutions
+        }
 
-        Ok((solutions, gas_response))
+        let books = Self::build_non_proposal_books(limit.to_vec(), pool_snapshots, self.sort)
+            .into_iter()
+            .map(|book| (book.id(), book))
+            .collect::<HashMap<_, _>>();
+
+        solutions
+            .into_iter()
+            .map(|solution| {
+                let Some(book) = books.get(&solution.id) else { return solution };
+                let violations = verify_transfer_taxes(book, &solution, &self.transfer_taxes);
+                if violations.is_empty() {
+                    return solution
+                }
+
+                tracing::error!(
+                    pool_id = ?solution.id,
+                    ?violations,
+                    "solution fills orders below their limit after tax, settling the AMM only"
+                );
+                solution.amm_only()
+            })
+            .collect()
+    }
+
+    /// Simulates the part of the bundle of every pool on its own, all at once.
+    /// A pool whose part fails falls back to its [`PoolSolution::amm_only`]
+    /// solution if it has a searcher order to settle, and is dropped
+    /// otherwise. On a wide proposal this finds a failing pool much quicker
+    /// than simulating the whole bundle, which then only has to be done once
+    /// to get the gas of the bundle that is submitted.
+    async fn prescreen_solutions(
+        &self,
+        limit: &[BookOrder],
+        solutions: Vec<PoolSolution>,
+        pool_snapshots: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
+    ) -> Vec<PoolSolution> {
+        let checks = solutions.into_iter().map(|solution| async move {
+            let Err(e) = self.simulate_pool(limit, &solution, pool_snapshots).await else {
+                return Some(solution)
+            };
+            if solution.searcher.is_none() || solution.is_amm_only() {
+                tracing::warn!(err=%e, "dropping pool that failed simulation on its own");
+                return None
+            }
+
+            tracing::warn!(err=%e, "pool failed simulation on its own, settling the AMM only");
+            let fallback = solution.amm_only();
+            self.simulate_pool(limit, &fallback, pool_snapshots)
+                .await
+                .map(|_| fallback)
+                .inspect_err(
+                    |e| tracing::warn!(err=%e, "dropping pool whose AMM only solution failed")
+                )
+                .ok()
+        });
+
+        join_all(checks).await.into_iter().flatten().collect()
+    }
+
+    /// Simulates the part of the bundle of the pool of `solution`.
+    async fn simulate_pool(
+        &self,
+        limit: &[BookOrder],
+        solution: &PoolSolution,
+        pool_snapshots: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
+    ) -> eyre::Result<()> {
+        let pool_orders = limit
+            .iter()
+            .filter(|order| order.pool_id == solution.id)
+            .cloned()
+            .collect();
+        let bundle = AngstromBundle::for_gas_finalization(
+            pool_orders,
+            vec![solution.clone()],
+            pool_snapshots
+        )?;
+
+        self.validation_handle
+            .fetch_gas_for_bundle(bundle)
+            .await
+            .map(|_| ())
     }
 
     pub fn orders_sorted_by_pool_id(limit: Vec<BookOrder>) -> HashMap<PoolId, HashSet<BookOrder>> {
@@ -207,7 +500,7 @@ impl<TP: TaskSpawner + 'static, V: BundleValidatorHandle> MatchingManager<TP, V>
         searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>,
         pool_snapshots: HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
     ) -> eyre::Result<BundleEstimate> {
-        let books = Self::build_non_proposal_books(limit.clone(), &pool_snapshots);
+        let books = Self::build_non_proposal_books(limit.clone(), &pool_snapshots, self.sort);
 
         let searcher_orders: HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>> =
             searcher.into_iter().fold(HashMap::new(), |mut acc, order| {
@@ -246,10 +539,24 @@ impl<TP: TaskSpawner + 'static, V: BundleValidatorHandle> MatchingManager<TP, V>
 pub async fn manager_thread<TP: TaskSpawner + 'static, V: BundleValidatorHandle>(
     mut input: Receiver<MatcherCommand>,
     tp: Arc<TP>,
-    validation_handle: V
+    validation_handle: V,
+    parallel_simulation: bool,
+    sort: SortStrategy,
+    solver: Arc<dyn Solver>,
+    transfer_taxes: TransferTaxes,
+    feature_flags: FeatureFlags
 ) {
-    let manager =
-        MatchingManager { _futures: FuturesUnordered::default(), _tp: tp, validation_handle };
+    let mut manager = MatchingManager {
+        _futures: FuturesUnordered::default(),
+        tp,
+        validation_handle,
+        parallel_simulation,
+        sort,
+        solver,
+        transfer_taxes,
+        incremental: None,
+        feature_flags
+    };
 
     while let Some(c) = input.recv().await {
         match c {
@@ -260,6 +567,13 @@ pub async fn manager_thread<TP: TaskSpawner + 'static, V: BundleValidatorHandle>
             MatcherCommand::EstimateGasPerPool { .. } => {
                 todo!()
             }
+            MatcherCommand::GolfBundle(bundle, r) => {
+                // the simulations take a while, proposals aren't held up behind them
+                let validation = manager.validation_handle.clone();
+                manager.tp.spawn(Box::pin(async move {
+                    let _ = r.send(golf_bundle(&validation, bundle).await);
+                }));
+            }
         }
     }
 }
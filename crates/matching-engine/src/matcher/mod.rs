@@ -3,7 +3,7 @@ use angstrom_types::{
     matching::SqrtPriceX96,
     orders::{OrderPrice, OrderVolume}
 };
-pub use volume::VolumeFillMatcher;
+pub use volume::{VolumeFillMatchEndReason, VolumeFillMatcher};
 
 /// Preliminary implementation of a struct that captures all the information
 /// we'd want to get out of a finished match for us to use for heurestics and
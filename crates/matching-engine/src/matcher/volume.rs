@@ -3,9 +3,9 @@ use std::{
     cmp::{max, Ordering}
 };
 
-use alloy::primitives::U256;
 use angstrom_types::{
     matching::{
+        ucp,
         uniswap::{Direction, PoolPrice, PoolPriceVec},
         CompositeOrder, Debt, Ray
     },
@@ -101,22 +101,69 @@ impl<'a> VolumeFillMatcher<'a> {
         self.checkpoint.as_ref().map(|cp| *cp.clone())
     }
 
-    /// Restore our checkpoint into this VolumeFillBookSolver - not sure if we
-    /// ever want to do this but we can!
-    #[allow(dead_code)]
+    /// Restore our checkpoint into this VolumeFillBookSolver
     fn restore_checkpoint(&mut self) -> bool {
         let Some(checkpoint) = self.checkpoint.take() else {
             return false;
         };
-        let Self { bid_idx, bid_outcomes, ask_idx, ask_outcomes, amm_price, .. } = *checkpoint;
-        self.bid_idx = bid_idx;
-        self.bid_outcomes = bid_outcomes;
-        self.ask_idx = ask_idx;
-        self.ask_outcomes = ask_outcomes;
-        self.amm_price = amm_price;
+        *self = *checkpoint;
         true
     }
 
+    /// Drops a partial order that matching left filled for less than its
+    /// minimum. The book is rolled back to before the order was touched, so
+    /// the quantity matched against it is back in the book, and matching
+    /// continues without the order.
+    fn kill_below_minimum_fragment(&mut self) -> bool {
+        let Some(checkpoint) = self.checkpoint.as_deref() else { return false };
+        let bid = Self::below_minimum_fragment(
+            self.bid_idx.get(),
+            self.book.bids(),
+            &self.bid_outcomes,
+            &checkpoint.bid_outcomes
+        );
+        let ask = Self::below_minimum_fragment(
+            self.ask_idx.get(),
+            self.book.asks(),
+            &self.ask_outcomes,
+            &checkpoint.ask_outcomes
+        );
+        if bid.is_none() && ask.is_none() {
+            return false
+        }
+
+        self.restore_checkpoint();
+        if let Some(idx) = bid {
+            debug!(idx, "Dropping bid filled below its minimum");
+            self.bid_outcomes[idx] = OrderFillState::Killed;
+        }
+        if let Some(idx) = ask {
+            debug!(idx, "Dropping ask filled below its minimum");
+            self.ask_outcomes[idx] = OrderFillState::Killed;
+        }
+        self.save_checkpoint();
+
+        true
+    }
+
+    /// The index of the fragment if it's a partial order below its minimum
+    /// that was untouched at the checkpoint. One that was filled for enough by
+    /// then keeps that fill.
+    fn below_minimum_fragment(
+        idx: usize,
+        book: &[BookOrder],
+        outcomes: &[OrderFillState],
+        saved: &[OrderFillState]
+    ) -> Option<usize> {
+        let filled = outcomes.get(idx)?.partial_q()?;
+        let order = &book[idx];
+
+        (order.is_partial()
+            && !order.accepts_partial_fill(filled)
+            && saved[idx] == OrderFillState::Unfilled)
+            .then_some(idx)
+    }
+
     fn fill_amm(
         amm: &mut PoolPrice<'a>,
         results: &mut Solution,
@@ -156,6 +203,9 @@ impl<'a> VolumeFillMatcher<'a> {
         loop {
             if let Some(r) = self.single_match() {
                 tracing::debug!(?r);
+                if self.kill_below_minimum_fragment() {
+                    continue
+                }
                 return r
             }
             i += 1;
@@ -522,6 +572,10 @@ impl<'a> VolumeFillMatcher<'a> {
 
         debug!(debt = ?self.debt, "Current debt");
 
+        // The price this match sets, see the `ucp` module for the rules
+        let clearing =
+            ucp::clearing_price(ask.price().into(), bid.price().into(), bid_q.cmp(&ask_q));
+
         // Then we deal with fixing up our book orders
         match bid_q.cmp(&ask_q) {
             Ordering::Equal => {
@@ -530,10 +584,7 @@ impl<'a> VolumeFillMatcher<'a> {
 
                 // If we have a debt price, this is our current price, otherwise we get a price
                 // from our order outcomes
-                let new_price = self
-                    .debt
-                    .map(|d| d.price())
-                    .unwrap_or_else(|| (*(ask.price() + bid.price()) / U256::from(2)).into());
+                let new_price = self.debt.map(|d| d.price()).unwrap_or(clearing);
                 self.results.price = Some(new_price.into());
 
                 // Mark book orders as CompletelyFilled
@@ -551,7 +602,7 @@ impl<'a> VolumeFillMatcher<'a> {
             }
             Ordering::Greater => {
                 debug!("Greater than match");
-                self.results.price = Some(bid.price());
+                self.results.price = Some(clearing.into());
                 // Ask was completely filled, remainder bid
                 if ask.is_book() {
                     self.ask_outcomes[self.ask_idx.get()] = OrderFillState::CompleteFill
@@ -559,10 +610,11 @@ impl<'a> VolumeFillMatcher<'a> {
                 // Set our bid outcome to be partial
                 if bid.is_book() {
                     let partial_q = if bid.inverse_order() { t1_matched } else { matched };
-                    self.bid_outcomes[self.bid_idx.get()] =
-                        self.bid_outcomes[self.bid_idx.get()].partial_fill(partial_q);
-                    // A partial fill of a partial-safe order is checkpointable
-                    if bid.is_partial() {
+                    let state = self.bid_outcomes[self.bid_idx.get()].partial_fill(partial_q);
+                    self.bid_outcomes[self.bid_idx.get()] = state;
+                    // A partial fill of a partial-safe order is checkpointable once it
+                    // reaches the order's minimum
+                    if bid.accepts_fill(state) {
                         self.save_checkpoint();
                     }
                 } else {
@@ -572,7 +624,7 @@ impl<'a> VolumeFillMatcher<'a> {
             }
             Ordering::Less => {
                 debug!("Less than match");
-                self.results.price = Some(ask.price());
+                self.results.price = Some(clearing.into());
                 // Bid was completely filled, remainder ask
                 if bid.is_book() {
                     self.bid_outcomes[self.bid_idx.get()] = OrderFillState::CompleteFill
@@ -580,10 +632,11 @@ impl<'a> VolumeFillMatcher<'a> {
                 // Set our ask outcome to be partial
                 if ask.is_book() {
                     let partial_q = if ask.inverse_order() { t1_matched } else { matched };
-                    self.ask_outcomes[self.ask_idx.get()] =
-                        self.ask_outcomes[self.ask_idx.get()].partial_fill(partial_q);
-                    // A partial fill of a partial-safe order is checkpointable
-                    if ask.is_partial() {
+                    let state = self.ask_outcomes[self.ask_idx.get()].partial_fill(partial_q);
+                    self.ask_outcomes[self.ask_idx.get()] = state;
+                    // A partial fill of a partial-safe order is checkpointable once it
+                    // reaches the order's minimum
+                    if ask.accepts_fill(state) {
                         self.save_checkpoint();
                     }
                 } else {
@@ -877,6 +930,93 @@ mod tests {
         );
     }
 
+    #[test]
+    fn partial_fill_below_minimum_is_not_kept() {
+        let pool_id = PoolId::random();
+        let high_price = Ray::from(Uint::from(1_000_000_000_u128));
+        let low_price = Ray::from(Uint::from(1_000_u128));
+        let bid_order = UserOrderBuilder::new()
+            .exact()
+            .bid()
+            .amount(10)
+            .bid_min_price(high_price)
+            .with_storage()
+            .bid()
+            .build();
+        let ask_order = UserOrderBuilder::new()
+            .partial()
+            .ask()
+            .amount(100)
+            .min_amount(50)
+            .min_price(low_price)
+            .with_storage()
+            .ask()
+            .build();
+        let book = OrderBook::new(pool_id, None, vec![bid_order], vec![ask_order], None);
+        let mut matcher = VolumeFillMatcher::new(&book);
+        matcher.run_match();
+        let solution = matcher.from_checkpoint().unwrap().solution(None);
+        assert!(
+            solution.limit.iter().all(|outcome| !outcome.is_filled()),
+            "Ask was filled for less than its minimum"
+        );
+    }
+
+    #[test]
+    fn book_is_matched_again_without_an_order_below_its_minimum() {
+        let pool_id = PoolId::random();
+        let high_price = Ray::from(Uint::from(1_000_000_000_u128));
+        let low_price = Ray::from(Uint::from(1_000_u128));
+        let bid_order = UserOrderBuilder::new()
+            .exact()
+            .bid()
+            .amount(10)
+            .bid_min_price(high_price)
+            .with_storage()
+            .bid()
+            .build();
+        let partial_ask = UserOrderBuilder::new()
+            .partial()
+            .ask()
+            .amount(100)
+            .min_amount(50)
+            .min_price(low_price)
+            .with_storage()
+            .ask()
+            .build();
+        let exact_ask = UserOrderBuilder::new()
+            .exact()
+            .ask()
+            .amount(10)
+            .exact_in(true)
+            .min_price(Ray::from(Uint::from(2_000_u128)))
+            .with_storage()
+            .ask()
+            .build();
+        let book = OrderBook::new(
+            pool_id,
+            None,
+            vec![bid_order.clone()],
+            vec![partial_ask.clone(), exact_ask.clone()],
+            None
+        );
+        let mut matcher = VolumeFillMatcher::new(&book);
+        matcher.run_match();
+        let solution = matcher.from_checkpoint().unwrap().solution(None);
+
+        let outcome = |hash| {
+            solution
+                .limit
+                .iter()
+                .find(|outcome| outcome.id.hash == hash)
+                .unwrap()
+                .outcome
+        };
+        assert_eq!(outcome(partial_ask.order_id.hash), OrderFillState::Killed);
+        assert_eq!(outcome(exact_ask.order_id.hash), OrderFillState::CompleteFill);
+        assert_eq!(outcome(bid_order.order_id.hash), OrderFillState::CompleteFill);
+    }
+
     fn basic_order_book(
         is_bid: bool,
         count: usize,
@@ -45,10 +45,11 @@ pub fn order_distribution(
                 invalidates: vec![],
                 order,
                 priority_data: OrderPriorityData {
-                    price:     U256::from(p as u128),
-                    volume:    q as u128,
-                    gas:       U256::ZERO,
-                    gas_units: 0
+                    price:        U256::from(p as u128),
+                    volume:       q as u128,
+                    gas:          U256::ZERO,
+                    gas_units:    0,
+                    validated_at: 0
                 },
                 is_bid,
                 is_valid: true,
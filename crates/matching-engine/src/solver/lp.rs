@@ -0,0 +1,429 @@
+//! Solves a book exactly for the most surplus, as a benchmark for the
+//! [`VolumeFillSolver`] and for small books where every bit of surplus counts.
+//!
+//! The UCP makes the problem non-linear, so the book is solved once for each
+//! candidate UCP and the best of those solutions is taken. At a fixed UCP the
+//! orders that can be filled are known and the surplus of a fill is linear in
+//! its quantity, leaving a mixed integer program: a binary per order for
+//! whether it is filled, all or nothing for exact orders and above its minimum
+//! for partial ones, and the t0 bought by the orders and the AMM no more than
+//! the t0 they sell. The candidates are the limit prices of the orders and the
+//! points the AMM curve is approximated at, see
+//! [`LpSolver::with_amm_segments`].
+use std::collections::{BTreeSet, HashMap};
+
+use alloy_primitives::{B256, U256};
+use angstrom_types::{
+    matching::{
+        uniswap::{Direction, PoolSnapshot},
+        Ray, SqrtPriceX96
+    },
+    orders::{NetAmmOrder, OrderFillState, OrderOutcome, PoolSolution},
+    sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder}
+};
+use good_lp::{
+    constraint, solvers::highs::highs, variable, variables, Expression, ResolutionError, Solution,
+    SolverModel
+};
+
+use super::{Solver, SolverConstraints, SolverKind, SolverOutput, VolumeFillSolver};
+use crate::{
+    book::{BookOrder, OrderBook},
+    verification::{amm_t1, solution_surplus, t0_moved}
+};
+
+/// Largest book, bids and asks together, solved exactly by default.
+pub const DEFAULT_LP_MAX_ORDERS: usize = 64;
+/// Segments the AMM curve is approximated with by default.
+pub const DEFAULT_LP_AMM_SEGMENTS: usize = 16;
+
+/// Weight of the volume filled against the surplus, so of two solutions with
+/// the same surplus the one filling more is taken.
+const VOLUME_TIE_BREAK: f64 = 1e-9;
+
+/// Solves books of up to [`Self::with_max_orders`] orders for the most
+/// surplus, see the [module docs](self). Larger books, and books none of the
+/// candidate UCPs can be solved at, are solved by the [`VolumeFillSolver`]
+/// instead, which the artifacts of the output show.
+#[derive(Debug, Clone, Copy)]
+pub struct LpSolver {
+    max_orders:   usize,
+    amm_segments: usize
+}
+
+impl Default for LpSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LpSolver {
+    pub const fn new() -> Self {
+        Self { max_orders: DEFAULT_LP_MAX_ORDERS, amm_segments: DEFAULT_LP_AMM_SEGMENTS }
+    }
+
+    /// Every order adds a binary to the program of each candidate UCP, so the
+    /// time to solve a book grows quickly with its size.
+    pub fn with_max_orders(mut self, max_orders: usize) -> Self {
+        self.max_orders = max_orders;
+        self
+    }
+
+    /// The AMM is tried at this many evenly spaced prices between the lowest
+    /// and the highest limit of the book. A UCP between two of them where only
+    /// the AMM balances the book is missed.
+    pub fn with_amm_segments(mut self, amm_segments: usize) -> Self {
+        self.amm_segments = amm_segments.max(1);
+        self
+    }
+
+    fn candidate_prices(&self, book: &OrderBook) -> BTreeSet<Ray> {
+        let mut prices = book
+            .bids()
+            .iter()
+            .map(|order| order.price_for_book_side(true))
+            .chain(book.asks().iter().map(|order| order.price()))
+            .collect::<BTreeSet<_>>();
+
+        if let (Some(amm), Some(&low), Some(&high)) = (book.amm(), prices.first(), prices.last()) {
+            let amm_price = amm.current_price().as_ray();
+            let (low, high) = (low.min(amm_price), high.max(amm_price));
+            let step = (*high - *low) / U256::from(self.amm_segments);
+            prices.insert(amm_price);
+            prices.extend(
+                (1..self.amm_segments).map(|segment| Ray::from(*low + step * U256::from(segment)))
+            );
+        }
+
+        prices
+    }
+
+    /// The fills with the most surplus at `ucp`.
+    fn solve_at<'a>(&self, book: &'a OrderBook, ucp: Ray) -> Result<Fills<'a>, ResolutionError> {
+        let orders = book
+            .bids()
+            .iter()
+            .filter(|order| order.price_for_book_side(true) >= ucp)
+            .chain(book.asks().iter().filter(|order| order.price() <= ucp))
+            .map(|order| (order, t0_moved(order, order.max_q(), ucp)))
+            .filter(|(_, t0)| *t0 > 0)
+            .collect::<Vec<_>>();
+        let amm = book
+            .amm()
+            .and_then(|snapshot| AmmFill::up_to(snapshot, ucp));
+
+        // quantities are scaled down to at most 1, the solver works in floats
+        let scale = orders
+            .iter()
+            .map(|(_, t0)| *t0)
+            .chain(amm.as_ref().map(|amm| amm.capacity))
+            .max()
+            .unwrap_or(1) as f64;
+
+        let mut vars = variables!();
+        let mut constraints = Vec::new();
+        let (mut surplus, mut volume, mut balance) =
+            (Expression::from(0.), Expression::from(0.), Expression::from(0.));
+        let order_vars = orders
+            .iter()
+            .map(|(order, t0)| {
+                let fill = vars.add(variable().min(0).max(1));
+                let filled = vars.add(variable().binary());
+                let min_fill = if order.is_partial() {
+                    order.min_q() as f64 / order.max_q() as f64
+                } else {
+                    1.0
+                };
+                constraints.push(constraint!(fill <= filled));
+                constraints.push(constraint!(fill >= min_fill * filled));
+
+                let t0 = *t0 as f64 / scale;
+                let limit = order.price_for_book_side(order.is_bid).as_f64();
+                let (gain, sold) = if order.is_bid {
+                    (limit - ucp.as_f64(), -t0)
+                } else {
+                    (ucp.as_f64() - limit, t0)
+                };
+                surplus += (gain * t0) * fill;
+                volume += t0 * fill;
+                balance += sold * fill;
+
+                (fill, filled)
+            })
+            .collect::<Vec<_>>();
+        let amm_var = amm.as_ref().map(|amm| {
+            let t0 = vars.add(variable().min(0).max(amm.capacity as f64 / scale));
+            let sold = if amm.supplies() { 1.0 } else { -1.0 };
+            balance += sold * t0;
+            t0
+        });
+
+        let solution = vars
+            .maximise(surplus + VOLUME_TIE_BREAK * volume)
+            .using(highs)
+            .with_all(constraints)
+            .with(constraint!(balance >= 0))
+            .solve()?;
+
+        let orders = orders
+            .into_iter()
+            .zip(order_vars)
+            .map(|((order, _), (fill, filled))| {
+                let quantity = if solution.value(filled) < 0.5 {
+                    0
+                } else if solution.value(fill) >= 1.0 - 1e-9 {
+                    order.max_q()
+                } else {
+                    ((solution.value(fill) * order.max_q() as f64) as u128)
+                        .clamp(order.min_q(), order.max_q())
+                };
+                (order, quantity)
+            })
+            .collect();
+        let amm = amm.zip(amm_var).map(|(amm, t0)| AmmFill {
+            t0: ((solution.value(t0) * scale) as u128).min(amm.capacity),
+            ..amm
+        });
+
+        let mut fills = Fills { ucp, orders, amm };
+        fills.rebalance();
+        Ok(fills)
+    }
+}
+
+impl Solver for LpSolver {
+    fn kind(&self) -> SolverKind {
+        SolverKind::Lp
+    }
+
+    fn solve(&self, book: &OrderBook, constraints: &SolverConstraints) -> SolverOutput {
+        let orders = book.bids().len() + book.asks().len();
+        if orders == 0 || orders > self.max_orders {
+            return VolumeFillSolver.solve(book, constraints)
+        }
+
+        let best = self
+            .candidate_prices(book)
+            .into_iter()
+            .filter_map(|ucp| {
+                self.solve_at(book, ucp)
+                    .inspect_err(
+                        |err| tracing::debug!(pool_id = ?book.id(), ?ucp, %err, "no lp solution")
+                    )
+                    .ok()
+            })
+            .filter_map(|fills| fills.into_solution(book, constraints.searcher.clone()))
+            .map(|solution| (solution_surplus(book, &solution).vs_limit, solution))
+            // of solutions with the same surplus the one with the lowest ucp is kept
+            .reduce(|best, next| if next.0 > best.0 { next } else { best });
+
+        match best {
+            Some((_, solution)) => SolverOutput::new(self.kind(), book, solution, false),
+            None => {
+                tracing::warn!(pool_id = ?book.id(), "no lp solution, matching by volume");
+                VolumeFillSolver.solve(book, constraints)
+            }
+        }
+    }
+}
+
+/// How far the AMM is moved towards a UCP.
+#[derive(Debug, Clone, Copy)]
+struct AmmFill<'a> {
+    snapshot:  &'a PoolSnapshot,
+    direction: Direction,
+    /// t0 that moves the AMM all the way to the UCP
+    capacity:  u128,
+    t0:        u128
+}
+
+impl<'a> AmmFill<'a> {
+    fn up_to(snapshot: &'a PoolSnapshot, ucp: Ray) -> Option<Self> {
+        let start = snapshot.current_price();
+        let direction = match ucp.cmp(&start.as_ray()) {
+            std::cmp::Ordering::Greater => Direction::BuyingT0,
+            std::cmp::Ordering::Less => Direction::SellingT0,
+            std::cmp::Ordering::Equal => return None
+        };
+        let capacity = start.vec_to(SqrtPriceX96::from(ucp)).ok()?.d_t0;
+
+        (capacity > 0).then_some(Self { snapshot, direction, capacity, t0: 0 })
+    }
+
+    /// Whether the AMM sells t0 to the orders, rather than buys it from them.
+    fn supplies(&self) -> bool {
+        matches!(self.direction, Direction::BuyingT0)
+    }
+}
+
+/// The quantity every order the program could fill is filled for.
+struct Fills<'a> {
+    ucp:    Ray,
+    orders: Vec<(&'a BookOrder, u128)>,
+    amm:    Option<AmmFill<'a>>
+}
+
+impl Fills<'_> {
+    /// The t0 bought beyond what is sold, counted the way the bundle settles.
+    fn shortfall(&self) -> u128 {
+        let (mut supply, mut demand) = (0u128, 0u128);
+        for (order, quantity) in &self.orders {
+            let t0 = t0_moved(order, *quantity, self.ucp);
+            if order.is_bid {
+                demand = demand.saturating_add(t0);
+            } else {
+                supply = supply.saturating_add(t0);
+            }
+        }
+        if let Some(amm) = &self.amm {
+            if amm.supplies() {
+                supply = supply.saturating_add(amm.t0);
+            } else {
+                demand = demand.saturating_add(amm.t0);
+            }
+        }
+
+        demand.saturating_sub(supply)
+    }
+
+    /// The program balances the book in floats, rounding the fills can leave
+    /// them buying a few wei more than is sold. The AMM covers what it can,
+    /// the rest is taken off the bids with the lowest limits.
+    fn rebalance(&mut self) {
+        let ucp = self.ucp;
+        loop {
+            let shortfall = self.shortfall();
+            if shortfall == 0 {
+                return
+            }
+
+            if let Some(amm) = self.amm.as_mut() {
+                let room = if amm.supplies() { amm.capacity - amm.t0 } else { amm.t0 };
+                if room > 0 {
+                    let moved = room.min(shortfall);
+                    if amm.supplies() {
+                        amm.t0 += moved;
+                    } else {
+                        amm.t0 -= moved;
+                    }
+                    continue
+                }
+            }
+
+            let Some((order, quantity)) = self
+                .orders
+                .iter_mut()
+                .filter(|(order, quantity)| order.is_bid && *quantity > 0)
+                .min_by_key(|(order, _)| order.price_for_book_side(true))
+            else {
+                return
+            };
+            // a bid that is exact in is for t1
+            let cut = if order.exact_in() { ucp.quantity(shortfall, true) } else { shortfall };
+            let trimmed = quantity.saturating_sub(cut);
+            *quantity = if order.is_partial() && trimmed >= order.min_q() && trimmed < *quantity {
+                trimmed
+            } else {
+                0
+            };
+        }
+    }
+
+    fn into_solution(
+        self,
+        book: &OrderBook,
+        searcher: Option<OrderWithStorageData<TopOfBlockOrder>>
+    ) -> Option<PoolSolution> {
+        let quantities = self
+            .orders
+            .iter()
+            .map(|(order, quantity)| (order.order_id.hash, *quantity))
+            .collect::<HashMap<B256, u128>>();
+        let limit = book
+            .bids()
+            .iter()
+            .chain(book.asks())
+            .map(|order| {
+                let outcome = match quantities.get(&order.order_id.hash).copied().unwrap_or(0) {
+                    0 => OrderFillState::Unfilled,
+                    quantity if quantity >= order.max_q() => OrderFillState::CompleteFill,
+                    quantity => OrderFillState::PartialFill(quantity)
+                };
+                OrderOutcome { id: order.order_id, outcome }
+            })
+            .collect();
+
+        let amm_quantity = match self.amm.filter(|amm| amm.t0 > 0) {
+            Some(amm) => {
+                let t1 = amm_t1(amm.snapshot, amm.t0, amm.direction).ok()?;
+                Some(match amm.direction {
+                    Direction::BuyingT0 => NetAmmOrder::Sell(amm.t0, t1),
+                    Direction::SellingT0 => NetAmmOrder::Buy(amm.t0, t1)
+                })
+            }
+            None => None
+        };
+
+        Some(PoolSolution { id: book.id(), ucp: self.ucp, amm_quantity, searcher, limit })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::Uint;
+    use angstrom_types::primitive::PoolId;
+    use testing_tools::type_generator::orders::UserOrderBuilder;
+
+    use super::*;
+
+    fn crossed_book() -> OrderBook {
+        let bid = UserOrderBuilder::new()
+            .partial()
+            .bid()
+            .amount(100)
+            .min_price(Ray::from(Uint::from(1_000_000_000_u128)).inv_ray_round(true))
+            .with_storage()
+            .bid()
+            .build();
+        let ask = UserOrderBuilder::new()
+            .exact()
+            .ask()
+            .amount(10)
+            .exact_in(true)
+            .min_price(Ray::from(Uint::from(1_000_u128)))
+            .with_storage()
+            .ask()
+            .build();
+        OrderBook::new(PoolId::random(), None, vec![bid], vec![ask], None)
+    }
+
+    #[test]
+    fn finds_at_least_the_surplus_of_matching_by_volume() {
+        let book = crossed_book();
+        let constraints = SolverConstraints::default();
+
+        let exact = LpSolver::new().solve(&book, &constraints);
+        assert_eq!(exact.artifacts.solver, SolverKind::Lp);
+        assert!(exact.artifacts.verification.is_valid(), "{:?}", exact.artifacts);
+        assert!(exact
+            .solution
+            .limit
+            .iter()
+            .any(|outcome| outcome.is_filled()));
+
+        let by_volume = VolumeFillSolver.solve(&book, &constraints);
+        assert!(
+            solution_surplus(&book, &exact.solution).vs_limit
+                >= solution_surplus(&book, &by_volume.solution).vs_limit
+        );
+    }
+
+    #[test]
+    fn large_books_are_matched_by_volume() {
+        let book = crossed_book();
+        let output = LpSolver::new()
+            .with_max_orders(1)
+            .solve(&book, &SolverConstraints::default());
+        assert_eq!(output.artifacts.solver, SolverKind::VolumeFill);
+    }
+}
@@ -0,0 +1,298 @@
+//! Solvers turn the book of a pool into its solution. Which one a node solves
+//! its books with is up to its config: the solutions of a proposal are checked
+//! with [`verify_solution`] against the rules every solution has to follow, and
+//! only held to our own solve within the surplus shortfall we tolerate, so
+//! validators running different solvers still agree on proposals.
+use std::{collections::HashSet, fmt, str::FromStr, sync::Arc};
+
+use angstrom_types::{
+    orders::PoolSolution,
+    primitive::TransferTaxes,
+    sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder}
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    book::OrderBook,
+    matcher::VolumeFillMatcher,
+    strategy::{MatchingStrategy, SimpleCheckpointStrategy},
+    verification::{verify_solution, verify_transfer_taxes, SolutionViolation, VerificationReport}
+};
+
+#[cfg(feature = "lp-solver")]
+mod lp;
+#[cfg(feature = "lp-solver")]
+pub use lp::*;
+
+/// The solvers a node can be configured with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SolverKind {
+    /// [`VolumeFillSolver`]
+    #[default]
+    VolumeFill,
+    /// [`LpSolver`], only with the `lp-solver` feature
+    #[cfg(feature = "lp-solver")]
+    Lp
+}
+
+impl SolverKind {
+    pub const ALL: &'static [SolverKind] = &[
+        SolverKind::VolumeFill,
+        #[cfg(feature = "lp-solver")]
+        SolverKind::Lp
+    ];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            SolverKind::VolumeFill => "volume-fill",
+            #[cfg(feature = "lp-solver")]
+            SolverKind::Lp => "lp"
+        }
+    }
+
+    pub fn solver(self) -> Arc<dyn Solver> {
+        match self {
+            SolverKind::VolumeFill => Arc::new(VolumeFillSolver),
+            #[cfg(feature = "lp-solver")]
+            SolverKind::Lp => Arc::new(LpSolver::default())
+        }
+    }
+}
+
+impl fmt::Display for SolverKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown solver {0}")]
+pub struct UnknownSolver(pub String);
+
+impl FromStr for SolverKind {
+    type Err = UnknownSolver;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SolverKind::ALL
+            .iter()
+            .copied()
+            .find(|solver| solver.name() == s)
+            .ok_or_else(|| UnknownSolver(s.to_string()))
+    }
+}
+
+/// What the solution of a book has to respect besides the book itself.
+#[derive(Debug, Clone, Default)]
+pub struct SolverConstraints {
+    /// the top of block order of the pool, which swaps against the AMM before
+    /// the book is matched
+    pub searcher:       Option<OrderWithStorageData<TopOfBlockOrder>>,
+    /// the fee-on-transfer tokens, whose buyers get less than they are filled
+    /// for. See [`solve_after_tax`]
+    pub transfer_taxes: TransferTaxes
+}
+
+/// How a solver came to its solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolverArtifacts {
+    pub solver:       SolverKind,
+    /// whether the book couldn't be solved and the pool got its
+    /// [`amm_only_solution`]
+    pub fell_back:    bool,
+    /// the solution checked against the book, the way a peer verifies it
+    pub verification: VerificationReport
+}
+
+#[derive(Debug, Clone)]
+pub struct SolverOutput {
+    pub solution:  PoolSolution,
+    pub artifacts: SolverArtifacts
+}
+
+impl SolverOutput {
+    /// The output of `solver`, with `solution` checked against `book`.
+    pub fn new(
+        solver: SolverKind,
+        book: &OrderBook,
+        solution: PoolSolution,
+        fell_back: bool
+    ) -> Self {
+        let verification = verify_solution(book, &solution);
+        Self { solution, artifacts: SolverArtifacts { solver, fell_back, verification } }
+    }
+}
+
+pub trait Solver: fmt::Debug + Send + Sync + 'static {
+    fn kind(&self) -> SolverKind;
+
+    /// Solves `book` within `constraints`. A book that can't be solved gets
+    /// its [`amm_only_solution`] rather than none, so the pool still settles.
+    fn solve(&self, book: &OrderBook, constraints: &SolverConstraints) -> SolverOutput;
+}
+
+/// Solves `book` with `solver`, leaving out the orders that would get less than
+/// their limit once the transfer tax of the token they buy is taken, see
+/// [`verify_transfer_taxes`]. Each time the solution fills such orders, the
+/// book is solved again without them.
+pub fn solve_after_tax(
+    solver: &dyn Solver,
+    book: &OrderBook,
+    constraints: &SolverConstraints
+) -> SolverOutput {
+    let mut output = solver.solve(book, constraints);
+    let mut excluded = HashSet::new();
+
+    loop {
+        let below_limit =
+            verify_transfer_taxes(book, &output.solution, &constraints.transfer_taxes)
+                .into_iter()
+                .filter_map(|violation| match violation {
+                    SolutionViolation::TaxedBelowLimit(hash) => Some(hash),
+                    _ => None
+                })
+                .collect::<Vec<_>>();
+        // the filled orders change with every solve, but every one only leaves
+        // out more, so this ends with the book empty at the latest
+        if below_limit.is_empty() {
+            return output
+        }
+
+        tracing::debug!(
+            pool_id = ?book.id(),
+            orders = below_limit.len(),
+            "solving again without orders below their limit after tax"
+        );
+        excluded.extend(below_limit);
+        let mut untaxed = book.clone();
+        untaxed.retain_orders(|order| !excluded.contains(&order.order_id.hash));
+        output = solver.solve(&untaxed, constraints);
+    }
+}
+
+/// Matches the book with the [`VolumeFillMatcher`], rolled back to its last
+/// good checkpoint.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VolumeFillSolver;
+
+impl Solver for VolumeFillSolver {
+    fn kind(&self) -> SolverKind {
+        SolverKind::VolumeFill
+    }
+
+    fn solve(&self, book: &OrderBook, constraints: &SolverConstraints) -> SolverOutput {
+        let searcher = constraints.searcher.clone();
+        let (solution, fell_back) =
+            match SimpleCheckpointStrategy::try_solve(book, searcher.clone()) {
+                Some(solution) => (solution, false),
+                None => {
+                    tracing::warn!(pool_id = ?book.id(), "matching failed, settling the AMM only");
+                    (amm_only_solution(book, searcher), true)
+                }
+            };
+
+        SolverOutput::new(self.kind(), book, solution, fell_back)
+    }
+}
+
+/// The solution of the book that fills none of its orders. Only `searcher`
+/// swaps against the AMM, so the searcher bid and the LP rewards are still
+/// settled. The same for every validator with the same book.
+pub fn amm_only_solution(
+    book: &OrderBook,
+    searcher: Option<OrderWithStorageData<TopOfBlockOrder>>
+) -> PoolSolution {
+    VolumeFillMatcher::new(book).solution(searcher)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::Uint;
+    use angstrom_types::{
+        matching::Ray,
+        primitive::{PoolId, TransferTax}
+    };
+    use testing_tools::type_generator::orders::{
+        default_high_addr, default_low_addr, UserOrderBuilder
+    };
+
+    use super::*;
+    use crate::book::BookOrder;
+
+    fn crossing_orders() -> (BookOrder, BookOrder) {
+        let bid = UserOrderBuilder::new()
+            .partial()
+            .bid()
+            .amount(100)
+            .min_price(Ray::from(Uint::from(1_000_000_000_u128)).inv_ray_round(true))
+            .with_storage()
+            .bid()
+            .build();
+        let ask = UserOrderBuilder::new()
+            .exact()
+            .ask()
+            .amount(10)
+            .exact_in(true)
+            .min_price(Ray::from(Uint::from(1_000_u128)))
+            .with_storage()
+            .ask()
+            .build();
+
+        (bid, ask)
+    }
+
+    #[test]
+    fn amm_only_solution_fills_no_orders() {
+        let (bid, ask) = crossing_orders();
+        let searcher = Some(OrderWithStorageData::<TopOfBlockOrder>::default());
+        let book = OrderBook::new(PoolId::random(), None, vec![bid], vec![ask], None);
+
+        let constraints = SolverConstraints { searcher: searcher.clone(), ..Default::default() };
+        let output = VolumeFillSolver.solve(&book, &constraints);
+        assert!(!output.artifacts.fell_back);
+        assert_eq!(output.artifacts.solver, SolverKind::VolumeFill);
+        assert!(!output.solution.is_amm_only());
+
+        let fallback = amm_only_solution(&book, searcher);
+        assert!(fallback.is_amm_only());
+        assert_eq!(fallback.searcher, output.solution.searcher);
+        assert_eq!(fallback.limit.len(), 2);
+        assert_eq!(output.solution.amm_only(), fallback);
+    }
+
+    #[test]
+    fn orders_below_their_limit_after_tax_are_left_out() {
+        let (bid, ask) = crossing_orders();
+        let book = OrderBook::new(PoolId::random(), None, vec![bid], vec![ask], None);
+        // taxed so heavily that no ucp between the two limits works for both
+        let taxes = TransferTaxes::new([
+            TransferTax { token: *default_low_addr(), bps: 9_999 },
+            TransferTax { token: *default_high_addr(), bps: 9_999 }
+        ]);
+        let constraints = SolverConstraints { transfer_taxes: taxes.clone(), ..Default::default() };
+
+        let untaxed = VolumeFillSolver.solve(&book, &constraints);
+        assert!(!verify_transfer_taxes(&book, &untaxed.solution, &taxes).is_empty());
+
+        let output = solve_after_tax(&VolumeFillSolver, &book, &constraints);
+        assert!(verify_transfer_taxes(&book, &output.solution, &taxes).is_empty());
+        assert!(output
+            .solution
+            .limit
+            .iter()
+            .all(|outcome| !outcome.is_filled()));
+
+        // untaxed orders are solved as they are
+        let output = solve_after_tax(&VolumeFillSolver, &book, &SolverConstraints::default());
+        assert_eq!(output.solution, untaxed.solution);
+    }
+
+    #[test]
+    fn solvers_are_picked_by_name() {
+        for &solver in SolverKind::ALL {
+            assert_eq!(solver.name().parse(), Ok(solver));
+            assert_eq!(solver.solver().kind(), solver);
+        }
+        assert!("simplex".parse::<SolverKind>().is_err());
+    }
+}
@@ -7,7 +7,17 @@
 /// The intent is to implement several different strategies here and compare
 /// them via a suite of tests that will help us determine what the optimal
 /// matching strategy could be.
-use crate::{book::OrderBook, matcher::VolumeFillMatcher};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use angstrom_types::{
+    orders::PoolSolution,
+    sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder}
+};
+
+use crate::{
+    book::OrderBook,
+    matcher::{VolumeFillMatchEndReason, VolumeFillMatcher}
+};
 
 mod simplecheckpoint;
 pub use simplecheckpoint::SimpleCheckpointStrategy;
@@ -27,4 +37,23 @@ pub trait MatchingStrategy<'a> {
     /// not, do a "last mile" computation to get it there.  Will return
     /// `None` if the book is considered unsolveable.
     fn finalize(solver: VolumeFillMatcher) -> Option<VolumeFillMatcher>;
+
+    /// Runs the strategy against the book and takes the solution with
+    /// `searcher` as its top of block order. None when matching runs into an
+    /// error, panics or leaves the book unsolvable.
+    fn try_solve(
+        book: &'a OrderBook,
+        searcher: Option<OrderWithStorageData<TopOfBlockOrder>>
+    ) -> Option<PoolSolution> {
+        catch_unwind(AssertUnwindSafe(|| {
+            let mut solver = VolumeFillMatcher::new(book);
+            match solver.run_match() {
+                VolumeFillMatchEndReason::ErrorEncountered => None,
+                _ => Self::finalize(solver)
+            }
+        }))
+        .ok()
+        .flatten()
+        .map(|solver| solver.solution(searcher))
+    }
 }
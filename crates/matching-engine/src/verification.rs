@@ -0,0 +1,281 @@
+//! Checks a solution proposed by a peer against the book it was built from.
+//! Unlike comparing it to a local solve, this doesn't depend on the proposer
+//! running the exact same matcher, only on the solution respecting the
+//! constraints every valid solution has to.
+use std::{collections::HashMap, ops::Add};
+
+use alloy_primitives::{B256, I256, U256};
+use angstrom_types::{
+    matching::{
+        ucp::{self, UcpViolation},
+        uniswap::{Direction, PoolPriceVec, PoolSnapshot},
+        Ray
+    },
+    orders::{NetAmmOrder, OrderFillState, PoolSolution},
+    primitive::{PoolId, TransferTaxes, TRANSFER_TAX_BPS_SCALE},
+    sol_bindings::RawPoolOrder
+};
+
+use crate::book::{BookOrder, OrderBook};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SolutionViolation {
+    #[error("solution is for pool {0:?}, not the pool of the book")]
+    WrongPool(PoolId),
+    #[error(transparent)]
+    Ucp(#[from] UcpViolation),
+    #[error("order {0:?} is filled for more than its quantity")]
+    Overfilled(B256),
+    #[error("order {0:?} can't be partially filled")]
+    PartialFill(B256),
+    #[error("order {0:?} is filled for less than its minimum")]
+    BelowMinimumFill(B256),
+    #[error("orders and amm buy {demand} t0 but only {supply} is sold")]
+    Unbalanced { supply: u128, demand: u128 },
+    #[error("solution uses the amm of a pool that has none")]
+    NoAmm,
+    #[error("amm can't be moved by {0} t0")]
+    AmmUnreachable(u128),
+    #[error("amm moves {expected} t1 for its t0, solution claims {claimed}")]
+    AmmMismatch { expected: u128, claimed: u128 },
+    #[error("order {0:?} gets less than its limit once the transfer tax is taken")]
+    TaxedBelowLimit(B256)
+}
+
+/// Everything [`verify_solution`] found wrong with a solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationReport {
+    pub pool:       PoolId,
+    pub violations: Vec<SolutionViolation>
+}
+
+impl VerificationReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Checks that `proposed` could have come out of matching `book`:
+/// - no order is filled outside of its limit at the UCP, for more than its
+///   quantity, partially if it has to be filled in full, or for less than its
+///   minimum
+/// - the orders and the AMM buy no more t0 than they sell
+/// - the t1 the AMM moves is what the pool gives for its t0
+pub fn verify_solution(book: &OrderBook, proposed: &PoolSolution) -> VerificationReport {
+    let mut violations = Vec::new();
+    if proposed.id != book.id() {
+        violations.push(SolutionViolation::WrongPool(proposed.id));
+    }
+
+    let orders = book
+        .bids()
+        .iter()
+        .chain(book.asks())
+        .map(|order| (order.order_id.hash, order))
+        .collect::<HashMap<B256, &BookOrder>>();
+
+    if let Err(violation) =
+        ucp::check_fills(proposed.ucp, &proposed.limit, |hash| orders.get(hash).copied())
+    {
+        violations.push(violation.into());
+    }
+
+    let (mut supply, mut demand) = (0u128, 0u128);
+    for outcome in proposed.limit.iter().filter(|outcome| outcome.is_filled()) {
+        let Some(order) = orders.get(&outcome.id.hash) else { continue };
+
+        if let OrderFillState::PartialFill(quantity) = outcome.outcome {
+            if quantity > order.max_q() {
+                violations.push(SolutionViolation::Overfilled(outcome.id.hash));
+            }
+            if !order.is_partial() {
+                violations.push(SolutionViolation::PartialFill(outcome.id.hash));
+            } else if quantity < order.min_q() {
+                violations.push(SolutionViolation::BelowMinimumFill(outcome.id.hash));
+            }
+        }
+
+        let t0 = t0_moved(order, outcome.fill_amount(order.max_q()), proposed.ucp);
+        if order.is_bid {
+            demand = demand.saturating_add(t0);
+        } else {
+            supply = supply.saturating_add(t0);
+        }
+    }
+
+    if let Some(amm) = &proposed.amm_quantity {
+        match amm {
+            // see `NetAmmOrder::new`, a sell moves the amm as a buy of t0 and supplies it
+            NetAmmOrder::Sell(t0, _) => supply = supply.saturating_add(*t0),
+            NetAmmOrder::Buy(t0, _) => demand = demand.saturating_add(*t0)
+        }
+        // the matcher moves the amm in steps, each of which can round by a wei
+        let tolerance = proposed.limit.len() as u128 + 1;
+        if let Err(violation) = check_amm(book, amm, tolerance) {
+            violations.push(violation);
+        }
+    }
+
+    if demand > supply {
+        violations.push(SolutionViolation::Unbalanced { supply, demand });
+    }
+
+    VerificationReport { pool: proposed.id, violations }
+}
+
+/// Checks that the orders `solution` fills still get their limit once the
+/// transfer tax of the token they buy is taken: at the UCP net of the tax, and
+/// for exact out orders the whole quantity they asked for, which a taxed token
+/// never delivers.
+pub fn verify_transfer_taxes(
+    book: &OrderBook,
+    solution: &PoolSolution,
+    taxes: &TransferTaxes
+) -> Vec<SolutionViolation> {
+    if taxes.is_empty() {
+        return vec![]
+    }
+
+    solution
+        .limit
+        .iter()
+        .filter(|outcome| outcome.is_filled())
+        .filter_map(|outcome| {
+            let order = book
+                .bids()
+                .iter()
+                .chain(book.asks())
+                .find(|order| order.order_id.hash == outcome.id.hash)?;
+            let bps = taxes.bps(&order.token_out());
+            let below_limit =
+                bps != 0 && (!order.exact_in() || !clears_after_tax(order, solution.ucp, bps));
+
+            below_limit.then_some(SolutionViolation::TaxedBelowLimit(outcome.id.hash))
+        })
+        .collect()
+}
+
+/// Whether `order` is within its limit at `ucp` when `bps` of what it buys is
+/// taxed away on the way to it.
+fn clears_after_tax(order: &BookOrder, ucp: Ray, bps: u16) -> bool {
+    let limit = *order.price_for_book_side(order.is_bid);
+    let kept = U256::from(TRANSFER_TAX_BPS_SCALE - bps);
+    let scale = U256::from(TRANSFER_TAX_BPS_SCALE);
+
+    if order.is_bid {
+        // pays the ucp for t0 it only gets part of
+        ucp.saturating_mul(scale) <= limit.saturating_mul(kept)
+    } else {
+        // gets part of the t1 the ucp pays for its t0
+        ucp.saturating_mul(kept) >= limit.saturating_mul(scale)
+    }
+}
+
+/// How much better a solution's fills are than the orders asked for, in t1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Surplus {
+    /// gained over the limits of the filled orders
+    pub vs_limit: I256,
+    /// gained over filling the orders at the AMM's price before the block,
+    /// negative where the AMM alone would have done better
+    pub vs_amm:   I256
+}
+
+impl Add for Surplus {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            vs_limit: self.vs_limit.saturating_add(rhs.vs_limit),
+            vs_amm:   self.vs_amm.saturating_add(rhs.vs_amm)
+        }
+    }
+}
+
+/// The surplus the filled orders of `solution` get at its UCP. Orders that
+/// aren't in `book` are skipped.
+pub fn solution_surplus(book: &OrderBook, solution: &PoolSolution) -> Surplus {
+    let amm_price = book.amm().map(|amm| amm.current_price().as_ray());
+
+    solution
+        .limit
+        .iter()
+        .filter(|outcome| outcome.is_filled())
+        .filter_map(|outcome| {
+            let order = book
+                .bids()
+                .iter()
+                .chain(book.asks())
+                .find(|order| order.order_id.hash == outcome.id.hash)?;
+            let t0 = t0_moved(order, outcome.fill_amount(order.max_q()), solution.ucp);
+            let limit = order.price_for_book_side(order.is_bid);
+            // a bid gains from a ucp below its reference price, an ask from one above
+            let gain = |reference: Ray| {
+                if order.is_bid {
+                    price_gain(reference, solution.ucp, t0)
+                } else {
+                    price_gain(solution.ucp, reference, t0)
+                }
+            };
+
+            Some(Surplus {
+                vs_limit: gain(limit),
+                vs_amm:   amm_price.map(gain).unwrap_or_default()
+            })
+        })
+        .fold(Surplus::default(), Add::add)
+}
+
+/// The t1 gained on `t0` by trading at `better` instead of `worse`.
+fn price_gain(better: Ray, worse: Ray, t0: u128) -> I256 {
+    let gain = Ray::from(better.abs_diff(*worse)).mul_quantity(U256::from(t0));
+    let gain = I256::try_from(gain).unwrap_or(I256::MAX);
+
+    if better < worse {
+        -gain
+    } else {
+        gain
+    }
+}
+
+/// The t0 an order moves when filled for `quantity` at `ucp`, rounded the way
+/// the bundle settles it.
+pub(crate) fn t0_moved(order: &BookOrder, quantity: u128, ucp: Ray) -> u128 {
+    if order.is_bid() == order.exact_in() {
+        ucp.inverse_quantity(quantity, !order.is_bid())
+    } else {
+        quantity
+    }
+}
+
+fn check_amm(
+    book: &OrderBook,
+    amm: &NetAmmOrder,
+    tolerance: u128
+) -> Result<(), SolutionViolation> {
+    let snapshot = book.amm().ok_or(SolutionViolation::NoAmm)?;
+    let (t0, claimed, direction) = match amm {
+        NetAmmOrder::Sell(t0, t1) => (*t0, *t1, Direction::BuyingT0),
+        NetAmmOrder::Buy(t0, t1) => (*t0, *t1, Direction::SellingT0)
+    };
+
+    let expected =
+        amm_t1(snapshot, t0, direction).map_err(|_| SolutionViolation::AmmUnreachable(t0))?;
+
+    if expected.abs_diff(claimed) > tolerance {
+        return Err(SolutionViolation::AmmMismatch { expected, claimed })
+    }
+
+    Ok(())
+}
+
+/// The t1 the pool moves when `t0` is swapped through it in `direction`.
+pub(crate) fn amm_t1(
+    snapshot: &PoolSnapshot,
+    t0: u128,
+    direction: Direction
+) -> eyre::Result<u128> {
+    let start = snapshot.current_price();
+    let end = start.d_t0(t0, direction)?;
+    Ok(PoolPriceVec::from_price_range(start, end)?.d_t1)
+}
@@ -1,12 +1,14 @@
 use alloy::primitives::U256;
 use alloy_primitives::FixedBytes;
 use angstrom_types::{
-    matching::{uniswap::PoolSnapshot, Ray},
+    matching::{ucp::UcpViolation, uniswap::PoolSnapshot, Ray},
+    orders::OrderFillState,
     sol_bindings::grouped_orders::{GroupedVanillaOrder, OrderWithStorageData}
 };
 use matching_engine::{
     book::{BookOrder, OrderBook},
-    matcher::VolumeFillMatcher
+    matcher::VolumeFillMatcher,
+    verification::{verify_solution, SolutionViolation}
 };
 use testing_tools::type_generator::{
     amm::generate_single_position_amm_at_tick, orders::UserOrderBuilder
@@ -109,6 +111,41 @@ fn simple_book() {
     assert!(solution.limit.iter().all(|outcome| outcome.is_filled()), "All orders not filled");
 }
 
+#[test]
+fn verifies_solutions_against_the_book() {
+    let book = make_books(
+        vec![TestOrder { q: 100, p: raw_price(100) }],
+        vec![TestOrder { q: 100, p: raw_price(10) }],
+        None
+    );
+    let mut matcher = VolumeFillMatcher::new(&book);
+    matcher.run_match();
+    let solution = matcher.solution(None);
+    assert!(verify_solution(&book, &solution).is_valid());
+
+    // the ask can't be filled below its limit
+    let mut below_ask = solution.clone();
+    below_ask.ucp = raw_price(5);
+    let ask = book.asks()[0].order_id.hash;
+    assert_eq!(
+        verify_solution(&book, &below_ask).violations,
+        vec![SolutionViolation::Ucp(UcpViolation::AskBelowLimit(ask))]
+    );
+
+    // an exact order can't be partially filled, and the bid now buys more than
+    // the ask sells
+    let mut overfilled = solution;
+    let bid = book.bids()[0].order_id.hash;
+    overfilled
+        .limit
+        .iter_mut()
+        .filter(|outcome| outcome.id.hash == bid)
+        .for_each(|outcome| outcome.outcome = OrderFillState::PartialFill(200));
+    let violations = verify_solution(&book, &overfilled).violations;
+    assert!(violations.contains(&SolutionViolation::Overfilled(bid)));
+    assert!(violations.contains(&SolutionViolation::PartialFill(bid)));
+}
+
 #[test]
 fn unsolveable_book() {
     // Simple book where we can't fill anything because both orders don't have the
@@ -0,0 +1,245 @@
+//! Allocation profiling, compiled in with the `jemalloc` feature.
+//!
+//! The allocator stats say how much memory the node holds, not which part of it
+//! allocates. The code paths the performance work is about, matcher solves and
+//! bundle encoding, run in an [`AllocationScope`], which counts the bytes the
+//! thread allocated while it was open against the subsystem. The counts are
+//! exported as metrics and, along with the allocator stats, served by
+//! [`allocation_report`] to the admin rpc.
+//!
+//! Without the feature, scopes count nothing and there are no allocator stats.
+//! With `jemalloc-prof`, and jemalloc started with `prof:true`, the heap
+//! profile can be dumped with [`dump_heap_profile`].
+
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{Mutex, OnceLock}
+};
+
+use prometheus::IntCounterVec;
+use serde::{Deserialize, Serialize};
+
+use crate::METRICS_ENABLED;
+
+/// Bytes held by the allocator, see the jemalloc docs of `stats.*`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocatorStats {
+    pub allocated: u64,
+    pub active:    u64,
+    pub resident:  u64,
+    pub mapped:    u64,
+    pub metadata:  u64,
+    pub retained:  u64
+}
+
+/// What the scopes of a subsystem allocated since the node started.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubsystemAllocations {
+    pub subsystem: String,
+    pub scopes:    u64,
+    pub bytes:     u64,
+    /// most bytes allocated in a single scope
+    pub max_bytes: u64
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AllocationReport {
+    /// `None` when the node isn't built with jemalloc stats
+    pub allocator:  Option<AllocatorStats>,
+    pub subsystems: Vec<SubsystemAllocations>
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AllocationProfilingError {
+    #[error("heap profiling isn't enabled, build with `jemalloc-prof` and run with prof:true")]
+    ProfilingUnavailable,
+    #[error("failed to dump the heap profile: {0}")]
+    Dump(String)
+}
+
+#[derive(Clone)]
+struct AllocationMetrics {
+    // bytes allocated in the scopes of a subsystem
+    bytes:  IntCounterVec,
+    // scopes of a subsystem that were closed
+    scopes: IntCounterVec
+}
+
+impl Default for AllocationMetrics {
+    fn default() -> Self {
+        let bytes = prometheus::register_int_counter_vec!(
+            "allocation_bytes",
+            "bytes allocated in the scopes of a subsystem",
+            &["subsystem"]
+        )
+        .unwrap();
+
+        let scopes = prometheus::register_int_counter_vec!(
+            "allocation_scopes",
+            "scopes of a subsystem that were closed",
+            &["subsystem"]
+        )
+        .unwrap();
+
+        Self { bytes, scopes }
+    }
+}
+
+static METRICS: OnceLock<Option<AllocationMetrics>> = OnceLock::new();
+static SUBSYSTEMS: Mutex<BTreeMap<&'static str, SubsystemAllocations>> =
+    Mutex::new(BTreeMap::new());
+
+fn record(subsystem: &'static str, bytes: u64) {
+    let metrics = METRICS.get_or_init(|| {
+        METRICS_ENABLED
+            .get()
+            .copied()
+            .unwrap_or_default()
+            .then(AllocationMetrics::default)
+    });
+    if let Some(metrics) = metrics {
+        metrics.bytes.with_label_values(&[subsystem]).inc_by(bytes);
+        metrics.scopes.with_label_values(&[subsystem]).inc();
+    }
+
+    let mut subsystems = SUBSYSTEMS.lock().expect("poisoned");
+    let entry = subsystems
+        .entry(subsystem)
+        .or_insert_with(|| SubsystemAllocations {
+            subsystem: subsystem.to_string(),
+            ..Default::default()
+        });
+    entry.scopes += 1;
+    entry.bytes += bytes;
+    entry.max_bytes = entry.max_bytes.max(bytes);
+}
+
+/// Counts what the current thread allocates until it is dropped against
+/// `subsystem`. The scope has to be dropped on the thread it started on, so it
+/// can't be held across an await.
+#[must_use = "the scope counts until it is dropped"]
+pub struct AllocationScope {
+    subsystem: &'static str,
+    start:     Option<u64>
+}
+
+impl AllocationScope {
+    pub fn start(subsystem: &'static str) -> Self {
+        Self { subsystem, start: thread_allocated() }
+    }
+}
+
+impl Drop for AllocationScope {
+    fn drop(&mut self) {
+        if let Some((start, end)) = self.start.zip(thread_allocated()) {
+            record(self.subsystem, end.saturating_sub(start));
+        }
+    }
+}
+
+/// Runs `f` in an [`AllocationScope`] of `subsystem`.
+pub fn track_allocations<T>(subsystem: &'static str, f: impl FnOnce() -> T) -> T {
+    let _scope = AllocationScope::start(subsystem);
+    f()
+}
+
+/// The allocator stats as of now, and what the subsystems allocated so far.
+pub fn allocation_report() -> AllocationReport {
+    AllocationReport {
+        allocator:  allocator_stats(),
+        subsystems: SUBSYSTEMS
+            .lock()
+            .expect("poisoned")
+            .values()
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(all(feature = "jemalloc", unix))]
+fn thread_allocated() -> Option<u64> {
+    tikv_jemalloc_ctl::thread::allocatedp::read()
+        .ok()
+        .map(|allocated| allocated.get())
+}
+
+#[cfg(not(all(feature = "jemalloc", unix)))]
+fn thread_allocated() -> Option<u64> {
+    None
+}
+
+#[cfg(all(feature = "jemalloc", unix))]
+fn allocator_stats() -> Option<AllocatorStats> {
+    use tikv_jemalloc_ctl::{epoch, stats};
+
+    epoch::advance().ok()?;
+    Some(AllocatorStats {
+        allocated: stats::allocated::read().ok()? as u64,
+        active:    stats::active::read().ok()? as u64,
+        resident:  stats::resident::read().ok()? as u64,
+        mapped:    stats::mapped::read().ok()? as u64,
+        metadata:  stats::metadata::read().ok()? as u64,
+        retained:  stats::retained::read().ok()? as u64
+    })
+}
+
+#[cfg(not(all(feature = "jemalloc", unix)))]
+fn allocator_stats() -> Option<AllocatorStats> {
+    None
+}
+
+/// Writes the jemalloc heap profile to `path`, for `jeprof`.
+#[cfg(all(feature = "jemalloc-prof", unix))]
+pub fn dump_heap_profile(path: &Path) -> Result<(), AllocationProfilingError> {
+    use std::{ffi::CString, os::unix::ffi::OsStrExt};
+
+    // SAFETY: `opt.prof` is a bool
+    let enabled = unsafe { tikv_jemalloc_ctl::raw::read::<bool>(b"opt.prof\0") }.unwrap_or(false);
+    if !enabled {
+        return Err(AllocationProfilingError::ProfilingUnavailable)
+    }
+
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| AllocationProfilingError::Dump(e.to_string()))?;
+    // SAFETY: `prof.dump` takes a nul terminated path, which outlives the call
+    unsafe { tikv_jemalloc_ctl::raw::write(b"prof.dump\0", path.as_ptr()) }
+        .map_err(|e| AllocationProfilingError::Dump(e.to_string()))
+}
+
+/// Writes the jemalloc heap profile to `path`, for `jeprof`.
+#[cfg(not(all(feature = "jemalloc-prof", unix)))]
+pub fn dump_heap_profile(_path: &Path) -> Result<(), AllocationProfilingError> {
+    Err(AllocationProfilingError::ProfilingUnavailable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scopes_are_counted_per_subsystem() {
+        record("test_subsystem", 100);
+        record("test_subsystem", 300);
+        let value = track_allocations("test_scope", || vec![0u8; 1 << 20].len());
+        assert_eq!(value, 1 << 20);
+
+        let report = allocation_report();
+        let subsystem = report
+            .subsystems
+            .iter()
+            .find(|s| s.subsystem == "test_subsystem")
+            .unwrap();
+        assert_eq!((subsystem.scopes, subsystem.bytes, subsystem.max_bytes), (2, 400, 300));
+
+        // only counted when the allocator can tell what the thread allocated
+        let scope = report
+            .subsystems
+            .iter()
+            .find(|s| s.subsystem == "test_scope");
+        assert_eq!(scope.is_some(), report.allocator.is_some());
+    }
+}
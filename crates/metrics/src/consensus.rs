@@ -1,6 +1,6 @@
 use std::{collections::HashMap, time::Instant};
 
-use prometheus::{IntGauge, IntGaugeVec};
+use prometheus::{GaugeVec, IntGauge, IntGaugeVec};
 
 use crate::METRICS_ENABLED;
 
@@ -15,7 +15,15 @@ struct ConsensusMetrics {
     // time (ms) it takes proposal verification per block
     proposal_verification_time_per_block: IntGaugeVec,
     // map of block numbers to their consensus start times
-    block_consensus_start_times: HashMap<u64, Instant>
+    block_consensus_start_times: HashMap<u64, Instant>,
+    // protocol fees saved by the bundles we landed, per asset
+    protocol_fees: GaugeVec,
+    // share of the recent submissions each endpoint accepted
+    submission_target_success_rate: GaugeVec,
+    // share of the transactions each endpoint accepted that landed
+    submission_target_inclusion_rate: GaugeVec,
+    // average time (ms) each endpoint takes to answer a submission
+    submission_target_latency: IntGaugeVec
 }
 
 impl Default for ConsensusMetrics {
@@ -45,12 +53,44 @@ impl Default for ConsensusMetrics {
         )
         .unwrap();
 
+        let protocol_fees = prometheus::register_gauge_vec!(
+            "consensus_protocol_fees",
+            "protocol fees saved by the bundles we landed, per asset",
+            &["asset"]
+        )
+        .unwrap();
+
+        let submission_target_success_rate = prometheus::register_gauge_vec!(
+            "consensus_submission_target_success_rate",
+            "share of the recent submissions each endpoint accepted",
+            &["target"]
+        )
+        .unwrap();
+
+        let submission_target_inclusion_rate = prometheus::register_gauge_vec!(
+            "consensus_submission_target_inclusion_rate",
+            "share of the transactions each endpoint accepted that landed",
+            &["target"]
+        )
+        .unwrap();
+
+        let submission_target_latency = prometheus::register_int_gauge_vec!(
+            "consensus_submission_target_latency",
+            "average time (ms) each endpoint takes to answer a submission",
+            &["target"]
+        )
+        .unwrap();
+
         Self {
             block_height,
             proposal_build_time_per_block,
             completion_time_per_block,
             proposal_verification_time_per_block,
-            block_consensus_start_times: HashMap::default()
+            block_consensus_start_times: HashMap::default(),
+            protocol_fees,
+            submission_target_success_rate,
+            submission_target_inclusion_rate,
+            submission_target_latency
         }
     }
 }
@@ -77,6 +117,34 @@ impl ConsensusMetrics {
             .set(time as i64);
     }
 
+    pub fn set_protocol_fees(&self, asset: &str, fees: u128) {
+        self.protocol_fees
+            .get_metric_with_label_values(&[asset])
+            .unwrap()
+            .set(fees as f64);
+    }
+
+    pub fn set_submission_target(
+        &self,
+        target: &str,
+        success_rate: f64,
+        inclusion_rate: f64,
+        latency_ms: u64
+    ) {
+        self.submission_target_success_rate
+            .get_metric_with_label_values(&[target])
+            .unwrap()
+            .set(success_rate);
+        self.submission_target_inclusion_rate
+            .get_metric_with_label_values(&[target])
+            .unwrap()
+            .set(inclusion_rate);
+        self.submission_target_latency
+            .get_metric_with_label_values(&[target])
+            .unwrap()
+            .set(latency_ms as i64);
+    }
+
     pub fn set_block_height(&mut self, block_number: u64) {
         self.block_height.set(block_number as i64);
         self.block_consensus_start_times
@@ -135,6 +203,24 @@ impl ConsensusMetricsWrapper {
         }
     }
 
+    pub fn set_protocol_fees(&self, asset: &str, fees: u128) {
+        if let Some(this) = self.0.as_ref() {
+            this.set_protocol_fees(asset, fees)
+        }
+    }
+
+    pub fn set_submission_target(
+        &self,
+        target: &str,
+        success_rate: f64,
+        inclusion_rate: f64,
+        latency_ms: u64
+    ) {
+        if let Some(this) = self.0.as_ref() {
+            this.set_submission_target(target, success_rate, inclusion_rate, latency_ms)
+        }
+    }
+
     pub fn set_block_height(&mut self, block_number: u64) {
         if let Some(this) = self.0.as_mut() {
             this.set_block_height(block_number)
@@ -3,6 +3,9 @@ use std::sync::OnceLock;
 
 pub use exporter::*;
 
+mod allocations;
+pub use allocations::*;
+
 mod bundle_building;
 
 pub mod validation;
@@ -13,4 +16,7 @@ pub use order_pool::*;
 mod consensus;
 pub use consensus::*;
 
+mod watchtower;
+pub use watchtower::*;
+
 pub static METRICS_ENABLED: OnceLock<bool> = OnceLock::new();
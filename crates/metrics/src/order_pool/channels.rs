@@ -0,0 +1,97 @@
+use std::sync::OnceLock;
+
+use prometheus::{IntCounter, IntCounterVec};
+
+use crate::METRICS_ENABLED;
+
+#[derive(Clone)]
+struct OrderChannelMetrics {
+    // number of commands rejected because the pool manager was backed up
+    rejected_commands: IntCounterVec,
+    // number of order updates subscribers missed by falling behind
+    dropped_updates:   IntCounter,
+    // number of times a subscriber fell behind and was resynced
+    resyncs:           IntCounter
+}
+
+impl Default for OrderChannelMetrics {
+    fn default() -> Self {
+        let rejected_commands = prometheus::register_int_counter_vec!(
+            "order_pool_rejected_commands",
+            "number of commands rejected because the pool manager was backed up",
+            &["command"]
+        )
+        .unwrap();
+
+        let dropped_updates = prometheus::register_int_counter!(
+            "order_pool_dropped_updates",
+            "number of order updates subscribers missed by falling behind",
+        )
+        .unwrap();
+
+        let resyncs = prometheus::register_int_counter!(
+            "order_pool_subscriber_resyncs",
+            "number of times a subscriber fell behind and was resynced",
+        )
+        .unwrap();
+
+        Self { rejected_commands, dropped_updates, resyncs }
+    }
+}
+
+impl OrderChannelMetrics {
+    pub fn incr_rejected_commands(&self, command: &str) {
+        self.rejected_commands.with_label_values(&[command]).inc();
+    }
+
+    pub fn incr_dropped_updates(&self, count: u64) {
+        self.dropped_updates.inc_by(count);
+    }
+
+    pub fn incr_resyncs(&self) {
+        self.resyncs.inc();
+    }
+}
+
+/// Pool handles are created and cloned all over the node, so unlike the other
+/// metrics these are registered once and shared.
+static ORDER_CHANNEL_METRICS: OnceLock<OrderChannelMetrics> = OnceLock::new();
+
+#[derive(Clone)]
+pub struct OrderChannelMetricsWrapper(Option<&'static OrderChannelMetrics>);
+
+impl Default for OrderChannelMetricsWrapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderChannelMetricsWrapper {
+    pub fn new() -> Self {
+        Self(
+            METRICS_ENABLED
+                .get()
+                .copied()
+                .unwrap_or_default()
+                .then(|| ORDER_CHANNEL_METRICS.get_or_init(OrderChannelMetrics::default))
+        )
+    }
+
+    pub fn incr_rejected_commands(&self, command: &str) {
+        if let Some(this) = self.0 {
+            this.incr_rejected_commands(command)
+        }
+    }
+
+    pub fn incr_dropped_updates(&self, count: u64) {
+        if let Some(this) = self.0 {
+            this.incr_dropped_updates(count)
+        }
+    }
+
+    pub fn incr_resyncs(&self) {
+        if let Some(this) = self.0 {
+            this.incr_resyncs()
+        }
+    }
+}
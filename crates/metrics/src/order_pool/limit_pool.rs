@@ -1,3 +1,5 @@
+use std::sync::OnceLock;
+
 use angstrom_types::primitive::PoolId;
 use prometheus::{IntGauge, IntGaugeVec};
 
@@ -123,8 +125,12 @@ impl VanillaLimitOrderPoolMetrics {
     }
 }
 
+/// Every pool shard of the order storage holds its own limit pool, so the
+/// metrics are registered once and shared between them.
+static VANILLA_LIMIT_ORDER_POOL_METRICS: OnceLock<VanillaLimitOrderPoolMetrics> = OnceLock::new();
+
 #[derive(Clone)]
-pub struct VanillaLimitOrderPoolMetricsWrapper(Option<VanillaLimitOrderPoolMetrics>);
+pub struct VanillaLimitOrderPoolMetricsWrapper(Option<&'static VanillaLimitOrderPoolMetrics>);
 
 impl Default for VanillaLimitOrderPoolMetricsWrapper {
     fn default() -> Self {
@@ -134,13 +140,9 @@ impl Default for VanillaLimitOrderPoolMetricsWrapper {
 
 impl VanillaLimitOrderPoolMetricsWrapper {
     pub fn new() -> Self {
-        Self(
-            METRICS_ENABLED
-                .get()
-                .copied()
-                .unwrap_or_default()
-                .then(VanillaLimitOrderPoolMetrics::default)
-        )
+        Self(METRICS_ENABLED.get().copied().unwrap_or_default().then(|| {
+            VANILLA_LIMIT_ORDER_POOL_METRICS.get_or_init(VanillaLimitOrderPoolMetrics::default)
+        }))
     }
 
     pub fn incr_parked_orders(&self, pool_id: PoolId, count: usize) {
@@ -223,8 +225,11 @@ impl ComposableLimitOrderPoolMetrics {
     }
 }
 
+static COMPOSABLE_LIMIT_ORDER_POOL_METRICS: OnceLock<ComposableLimitOrderPoolMetrics> =
+    OnceLock::new();
+
 #[derive(Clone)]
-pub struct ComposableLimitOrderPoolMetricsWrapper(Option<ComposableLimitOrderPoolMetrics>);
+pub struct ComposableLimitOrderPoolMetricsWrapper(Option<&'static ComposableLimitOrderPoolMetrics>);
 
 impl Default for ComposableLimitOrderPoolMetricsWrapper {
     fn default() -> Self {
@@ -234,13 +239,10 @@ impl Default for ComposableLimitOrderPoolMetricsWrapper {
 
 impl ComposableLimitOrderPoolMetricsWrapper {
     pub fn new() -> Self {
-        Self(
-            METRICS_ENABLED
-                .get()
-                .copied()
-                .unwrap_or_default()
-                .then(ComposableLimitOrderPoolMetrics::default)
-        )
+        Self(METRICS_ENABLED.get().copied().unwrap_or_default().then(|| {
+            COMPOSABLE_LIMIT_ORDER_POOL_METRICS
+                .get_or_init(ComposableLimitOrderPoolMetrics::default)
+        }))
     }
 
     pub fn incr_all_orders(&self, pool_id: PoolId, count: usize) {
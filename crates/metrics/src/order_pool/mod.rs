@@ -9,3 +9,9 @@ pub use searcher_pool::*;
 
 mod finalization_pool;
 pub use finalization_pool::*;
+
+mod channels;
+pub use channels::*;
+
+mod source_tags;
+pub use source_tags::*;
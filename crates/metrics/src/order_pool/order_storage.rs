@@ -1,4 +1,4 @@
-use prometheus::IntGauge;
+use prometheus::{IntCounter, IntGauge};
 
 use crate::METRICS_ENABLED;
 
@@ -17,7 +17,10 @@ struct OrderStorageMetrics {
     // number of cancelled composable orders
     cancelled_composable_orders: IntGauge,
     // number of cancelled searcher orders
-    cancelled_searcher_orders:   IntGauge
+    cancelled_searcher_orders:   IntGauge,
+    // number of orders rejected because one with the same hash is stored or
+    // being validated
+    duplicate_orders:            IntCounter
 }
 
 impl Default for OrderStorageMetrics {
@@ -64,6 +67,12 @@ impl Default for OrderStorageMetrics {
         )
         .unwrap();
 
+        let duplicate_orders = prometheus::register_int_counter!(
+            "order_storage_duplicate_orders",
+            "number of orders rejected as duplicates of a stored or validating order",
+        )
+        .unwrap();
+
         Self {
             vanilla_limit_orders,
             searcher_orders,
@@ -71,7 +80,8 @@ impl Default for OrderStorageMetrics {
             composable_limit_orders,
             cancelled_vanilla_orders,
             cancelled_composable_orders,
-            cancelled_searcher_orders
+            cancelled_searcher_orders,
+            duplicate_orders
         }
     }
 }
@@ -120,6 +130,10 @@ impl OrderStorageMetrics {
     pub fn incr_cancelled_searcher_orders(&self, count: usize) {
         self.cancelled_searcher_orders.add(count as i64);
     }
+
+    pub fn incr_duplicate_orders(&self) {
+        self.duplicate_orders.inc();
+    }
 }
 
 #[derive(Clone)]
@@ -178,6 +192,12 @@ impl OrderStorageMetricsWrapper {
         }
     }
 
+    pub fn incr_duplicate_orders(&self) {
+        if let Some(this) = self.0.as_ref() {
+            this.incr_duplicate_orders()
+        }
+    }
+
     pub fn decr_composable_limit_orders(&self, count: usize) {
         if let Some(this) = self.0.as_ref() {
             this.decr_composable_limit_orders(count)
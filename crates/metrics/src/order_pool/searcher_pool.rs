@@ -1,3 +1,5 @@
+use std::sync::OnceLock;
+
 use angstrom_types::primitive::PoolId;
 use prometheus::{IntGauge, IntGaugeVec};
 
@@ -58,8 +60,11 @@ impl SearcherOrderPoolMetrics {
     }
 }
 
+/// Shared between the searcher pools of every pool shard of the order storage.
+static SEARCHER_ORDER_POOL_METRICS: OnceLock<SearcherOrderPoolMetrics> = OnceLock::new();
+
 #[derive(Clone)]
-pub struct SearcherOrderPoolMetricsWrapper(Option<SearcherOrderPoolMetrics>);
+pub struct SearcherOrderPoolMetricsWrapper(Option<&'static SearcherOrderPoolMetrics>);
 
 impl Default for SearcherOrderPoolMetricsWrapper {
     fn default() -> Self {
@@ -70,11 +75,9 @@ impl Default for SearcherOrderPoolMetricsWrapper {
 impl SearcherOrderPoolMetricsWrapper {
     pub fn new() -> Self {
         Self(
-            METRICS_ENABLED
-                .get()
-                .copied()
-                .unwrap_or_default()
-                .then(SearcherOrderPoolMetrics::default)
+            METRICS_ENABLED.get().copied().unwrap_or_default().then(|| {
+                SEARCHER_ORDER_POOL_METRICS.get_or_init(SearcherOrderPoolMetrics::default)
+            })
         )
     }
 
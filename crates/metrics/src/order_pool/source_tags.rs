@@ -0,0 +1,99 @@
+use std::sync::OnceLock;
+
+use prometheus::IntCounterVec;
+
+use crate::METRICS_ENABLED;
+
+#[derive(Clone)]
+struct SourceTagMetrics {
+    // number of orders submitted with each source tag
+    submitted: IntCounterVec,
+    // number of orders with each source tag that were added to the pool
+    accepted:  IntCounterVec,
+    // number of orders with each source tag that were filled
+    filled:    IntCounterVec
+}
+
+impl Default for SourceTagMetrics {
+    fn default() -> Self {
+        let submitted = prometheus::register_int_counter_vec!(
+            "order_pool_source_tag_submitted",
+            "number of orders submitted with each source tag",
+            &["source_tag"]
+        )
+        .unwrap();
+
+        let accepted = prometheus::register_int_counter_vec!(
+            "order_pool_source_tag_accepted",
+            "number of orders with each source tag that were added to the pool",
+            &["source_tag"]
+        )
+        .unwrap();
+
+        let filled = prometheus::register_int_counter_vec!(
+            "order_pool_source_tag_filled",
+            "number of orders with each source tag that were filled",
+            &["source_tag"]
+        )
+        .unwrap();
+
+        Self { submitted, accepted, filled }
+    }
+}
+
+impl SourceTagMetrics {
+    pub fn incr_submitted(&self, source_tag: &str) {
+        self.submitted.with_label_values(&[source_tag]).inc();
+    }
+
+    pub fn incr_accepted(&self, source_tag: &str) {
+        self.accepted.with_label_values(&[source_tag]).inc();
+    }
+
+    pub fn incr_filled(&self, source_tag: &str) {
+        self.filled.with_label_values(&[source_tag]).inc();
+    }
+}
+
+/// The source tags are shared by the pool and its storage, so like the channel
+/// metrics these are registered once.
+static SOURCE_TAG_METRICS: OnceLock<SourceTagMetrics> = OnceLock::new();
+
+#[derive(Clone)]
+pub struct SourceTagMetricsWrapper(Option<&'static SourceTagMetrics>);
+
+impl Default for SourceTagMetricsWrapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SourceTagMetricsWrapper {
+    pub fn new() -> Self {
+        Self(
+            METRICS_ENABLED
+                .get()
+                .copied()
+                .unwrap_or_default()
+                .then(|| SOURCE_TAG_METRICS.get_or_init(SourceTagMetrics::default))
+        )
+    }
+
+    pub fn incr_submitted(&self, source_tag: &str) {
+        if let Some(this) = self.0 {
+            this.incr_submitted(source_tag)
+        }
+    }
+
+    pub fn incr_accepted(&self, source_tag: &str) {
+        if let Some(this) = self.0 {
+            this.incr_accepted(source_tag)
+        }
+    }
+
+    pub fn incr_filled(&self, source_tag: &str) {
+        if let Some(this) = self.0 {
+            this.incr_filled(source_tag)
+        }
+    }
+}
@@ -1,9 +1,48 @@
-use std::{future::Future, pin::Pin, time::Instant};
-
-use prometheus::{Histogram, HistogramVec, IntGauge};
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Instant
+};
+
+use alloy_primitives::Address;
+use angstrom_types::{
+    orders::OrderOrigin,
+    primitive::{PoolId, ValidationError}
+};
+use prometheus::{Histogram, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec};
 
 use crate::METRICS_ENABLED;
 
+/// How many of the most rejected tokens are exported.
+const TOP_REJECTED_TOKENS: usize = 10;
+/// Orders can name any token, past this many tokens the least rejected half
+/// is forgotten.
+const MAX_COUNTED_TOKENS: usize = 1024;
+
+/// The stages an order goes through while it is validated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationStage {
+    /// checking the order is signed by its sender
+    Signature,
+    /// checking the order against the nonces, balances and approvals of its
+    /// user
+    State,
+    /// pricing the gas of the order and simulating top of block orders
+    Simulation
+}
+
+impl ValidationStage {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Signature => "signature",
+            Self::State => "state",
+            Self::Simulation => "sim"
+        }
+    }
+}
+
 #[derive(Clone)]
 struct ValidationMetricsInner {
     // generic
@@ -18,7 +57,14 @@ struct ValidationMetricsInner {
     // state
     loading_balances:           Histogram,
     loading_approvals:          Histogram,
-    applying_state_transitions: Histogram
+    applying_state_transitions: Histogram,
+    // rejections
+    rejections:                 IntCounterVec,
+    /// the pool of an order isn't known before its state is checked, so the
+    /// stages are only split by origin
+    stage_time:                 HistogramVec,
+    top_rejected_tokens:        IntGaugeVec,
+    rejected_token_counts:      Arc<Mutex<HashMap<Address, u64>>>
 }
 
 impl Default for ValidationMetricsInner {
@@ -85,10 +131,32 @@ impl Default for ValidationMetricsInner {
         let applying_state_transitions = prometheus::register_histogram!(
             "applying_state_transitions_time",
             "how long does it take to apply the new balances and check for expired orders.",
+            buckets.clone()
+        )
+        .unwrap();
+
+        let rejections = prometheus::register_int_counter_vec!(
+            "order_rejections",
+            "the amount of orders rejected, by reason, pool and origin",
+            &["reason", "pool", "origin"]
+        )
+        .unwrap();
+
+        let stage_time = prometheus::register_histogram_vec!(
+            "verification_stage_time",
+            "time spent in each stage of verifying an order",
+            &["stage", "origin"],
             buckets
         )
         .unwrap();
 
+        let top_rejected_tokens = prometheus::register_int_gauge_vec!(
+            "top_rejected_tokens",
+            "the tokens of the most rejected orders, with how many orders of them were rejected",
+            &["token"]
+        )
+        .unwrap();
+
         Self {
             pending_verification,
             verification_wait_time,
@@ -98,7 +166,11 @@ impl Default for ValidationMetricsInner {
             fetch_gas_for_user,
             loading_balances,
             loading_approvals,
-            applying_state_transitions
+            applying_state_transitions,
+            rejections,
+            stage_time,
+            top_rejected_tokens,
+            rejected_token_counts: Default::default()
         }
     }
 }
@@ -171,16 +243,75 @@ impl ValidationMetricsInner {
             .with_label_values(&[if is_searcher { "searcher" } else { "limit" }])
             .observe(elapsed);
     }
+
+    fn stage_time(&self, stage: ValidationStage, origin: &str, start: Instant) {
+        let elapsed = start.elapsed().as_nanos() as f64;
+        self.stage_time
+            .with_label_values(&[stage.label(), origin])
+            .observe(elapsed);
+    }
+
+    fn rejected(
+        &self,
+        error: &ValidationError,
+        pool: Option<PoolId>,
+        origin: &str,
+        tokens: &[Address]
+    ) {
+        let pool = pool.map(|pool| pool.to_string());
+        self.rejections
+            .with_label_values(&[error.reason(), pool.as_deref().unwrap_or("none"), origin])
+            .inc();
+
+        if tokens.is_empty() {
+            return
+        }
+        let top = {
+            let mut counts = self.rejected_token_counts.lock().unwrap();
+            tokens
+                .iter()
+                .for_each(|token| *counts.entry(*token).or_default() += 1);
+            top_rejected(&mut counts)
+        };
+        self.top_rejected_tokens.reset();
+        for (token, count) in top {
+            self.top_rejected_tokens
+                .with_label_values(&[&token.to_string()])
+                .set(count as i64);
+        }
+    }
+}
+
+/// The most rejected tokens, most rejected first. Forgets the least rejected
+/// half of `counts` once it holds too many tokens.
+fn top_rejected(counts: &mut HashMap<Address, u64>) -> Vec<(Address, u64)> {
+    let mut ranked = counts
+        .iter()
+        .map(|(token, count)| (*token, *count))
+        .collect::<Vec<_>>();
+    ranked.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    if ranked.len() > MAX_COUNTED_TOKENS {
+        ranked.truncate(MAX_COUNTED_TOKENS / 2);
+        *counts = ranked.iter().copied().collect();
+    }
+    ranked.truncate(TOP_REJECTED_TOKENS);
+
+    ranked
 }
 
 #[derive(Clone)]
-pub struct ValidationMetrics(Option<ValidationMetricsInner>);
+pub struct ValidationMetrics {
+    inner:  Option<ValidationMetricsInner>,
+    /// where the orders measured come from
+    origin: &'static str
+}
 
 macro_rules! delegate_metric {
     ($($name:ident),*) => {
         $(
             pub fn $name<T> (&self, f: impl FnOnce()->T ) -> T {
-                if let Some(inner) = self.0.as_ref() {
+                if let Some(inner) = self.inner.as_ref() {
                     let res = inner.$name(f);
 
                     return res
@@ -208,20 +339,38 @@ impl ValidationMetrics {
     );
 
     pub fn new() -> Self {
-        Self(
-            METRICS_ENABLED
+        Self {
+            inner:  METRICS_ENABLED
                 .get()
                 .copied()
                 .unwrap_or_default()
-                .then(ValidationMetricsInner::default)
-        )
+                .then(ValidationMetricsInner::default),
+            origin: "unknown"
+        }
+    }
+
+    /// Labels what is measured from now on with where the order came from.
+    pub fn with_origin(mut self, origin: OrderOrigin) -> Self {
+        self.origin = match origin {
+            OrderOrigin::Local => "local",
+            OrderOrigin::External => "external",
+            OrderOrigin::Private => "private"
+        };
+        self
+    }
+
+    /// Labels what is measured from now on as a simulation, which isn't
+    /// submitted from anywhere.
+    pub fn simulating(mut self) -> Self {
+        self.origin = "simulation";
+        self
     }
 
     pub async fn measure_wait_time<'a, T>(
         &self,
         f: impl FnOnce() -> Pin<Box<dyn Future<Output = T> + Send + Sync + 'a>>
     ) -> T {
-        if let Some(inner) = self.0.as_ref() {
+        if let Some(inner) = self.inner.as_ref() {
             return inner.handle_pending(f).await
         }
 
@@ -233,7 +382,7 @@ impl ValidationMetrics {
         T: FnOnce() -> F,
         F: Future<Output = ()>
     {
-        if let Some(inner) = self.0.as_ref() {
+        if let Some(inner) = self.inner.as_ref() {
             inner.new_order(is_searcher, f).await;
 
             return
@@ -243,10 +392,65 @@ impl ValidationMetrics {
     }
 
     pub fn fetch_gas_for_user<T>(&self, is_searcher: bool, f: impl FnOnce() -> T) -> T {
-        if let Some(inner) = self.0.as_ref() {
+        if let Some(inner) = self.inner.as_ref() {
             return inner.fetch_gas_for_user(is_searcher, f)
         }
 
         f()
     }
+
+    pub fn stage<T>(&self, stage: ValidationStage, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let r = f();
+        if let Some(inner) = self.inner.as_ref() {
+            inner.stage_time(stage, self.origin, start);
+        }
+
+        r
+    }
+
+    pub async fn stage_async<F: Future>(&self, stage: ValidationStage, f: F) -> F::Output {
+        let start = Instant::now();
+        let r = f.await;
+        if let Some(inner) = self.inner.as_ref() {
+            inner.stage_time(stage, self.origin, start);
+        }
+
+        r
+    }
+
+    /// Counts an order rejected with `error`. `pool` is the pool of the order
+    /// if it got far enough to be known, `tokens` the tokens it trades.
+    pub fn rejected(&self, error: &ValidationError, pool: Option<PoolId>, tokens: &[Address]) {
+        if let Some(inner) = self.inner.as_ref() {
+            inner.rejected(error, pool, self.origin, tokens);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_the_most_rejected_tokens() {
+        let mut counts = (1..=20)
+            .map(|i| (Address::repeat_byte(i), i as u64))
+            .collect::<HashMap<_, _>>();
+
+        let top = top_rejected(&mut counts);
+        assert_eq!(top.len(), TOP_REJECTED_TOKENS);
+        assert_eq!(top[0], (Address::repeat_byte(20), 20));
+        assert_eq!(top[9], (Address::repeat_byte(11), 11));
+        assert_eq!(counts.len(), 20);
+
+        let mut counts = (0..=MAX_COUNTED_TOKENS as u64)
+            .map(|i| (Address::with_last_byte(0).create(i), i))
+            .collect::<HashMap<_, _>>();
+        top_rejected(&mut counts);
+        assert_eq!(counts.len(), MAX_COUNTED_TOKENS / 2);
+        assert!(counts
+            .values()
+            .all(|count| *count > MAX_COUNTED_TOKENS as u64 / 2));
+    }
 }
@@ -0,0 +1,63 @@
+use prometheus::{IntCounterVec, IntGauge};
+
+use crate::METRICS_ENABLED;
+
+#[derive(Clone)]
+struct WatchtowerMetrics {
+    // latest block the watchtower audited
+    block_height: IntGauge,
+    // alerts raised about the leaders, by kind
+    alerts:       IntCounterVec
+}
+
+impl Default for WatchtowerMetrics {
+    fn default() -> Self {
+        let block_height = prometheus::register_int_gauge!(
+            "watchtower_block_height",
+            "latest block the watchtower audited"
+        )
+        .unwrap();
+
+        let alerts = prometheus::register_int_counter_vec!(
+            "watchtower_alerts",
+            "alerts raised about the leaders, by kind",
+            &["kind"]
+        )
+        .unwrap();
+
+        Self { block_height, alerts }
+    }
+}
+
+#[derive(Clone)]
+pub struct WatchtowerMetricsWrapper(Option<WatchtowerMetrics>);
+
+impl Default for WatchtowerMetricsWrapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WatchtowerMetricsWrapper {
+    pub fn new() -> Self {
+        Self(
+            METRICS_ENABLED
+                .get()
+                .copied()
+                .unwrap_or_default()
+                .then(WatchtowerMetrics::default)
+        )
+    }
+
+    pub fn set_block_height(&self, block_number: u64) {
+        if let Some(this) = self.0.as_ref() {
+            this.block_height.set(block_number as i64)
+        }
+    }
+
+    pub fn incr_alerts(&self, kind: &str) {
+        if let Some(this) = self.0.as_ref() {
+            this.alerts.with_label_values(&[kind]).inc()
+        }
+    }
+}
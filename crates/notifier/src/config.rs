@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use alloy::transports::http::reqwest::Url;
+
+/// Attempts to deliver a notification before it is dropped, by default.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// Wait before the first retry, by default. Every further retry waits twice as
+/// long as the one before.
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotifierConfig {
+    pub webhooks:      Vec<Url>,
+    /// key the notifications are signed with, they are sent unsigned without
+    pub secret:        Option<Vec<u8>>,
+    pub max_attempts:  u32,
+    pub retry_backoff: Duration
+}
+
+impl NotifierConfig {
+    pub fn new(webhooks: Vec<Url>) -> Self {
+        Self {
+            webhooks,
+            secret: None,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            retry_backoff: DEFAULT_RETRY_BACKOFF
+        }
+    }
+
+    pub fn with_secret(mut self, secret: Vec<u8>) -> Self {
+        self.secret = Some(secret);
+        self
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    pub fn with_retry_backoff(mut self, retry_backoff: Duration) -> Self {
+        self.retry_backoff = retry_backoff;
+        self
+    }
+}
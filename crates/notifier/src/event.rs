@@ -0,0 +1,125 @@
+use alloy::primitives::{Address, BlockNumber, B256};
+use angstrom_types::primitive::{PoolId, ValidationError};
+use consensus::WatchtowerAlert;
+use order_pool::PoolManagerUpdate;
+use serde::{Deserialize, Serialize};
+
+/// What a webhook is told about.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum NotifierEvent {
+    OrderAccepted {
+        order_hash: B256,
+        user:       Address,
+        pool_id:    PoolId
+    },
+    OrderRejected {
+        order_hash: B256,
+        error:      ValidationError
+    },
+    OrderFilled {
+        order_hash: B256,
+        user:       Address,
+        pool_id:    PoolId,
+        block:      BlockNumber
+    },
+    /// the block that filled the order was reorged out, it is pending again
+    OrderUnfilled {
+        order_hash: B256,
+        user:       Address,
+        pool_id:    PoolId
+    },
+    OrderCancelled {
+        order_hash: B256,
+        user:       Address,
+        pool_id:    PoolId
+    },
+    OrderExpired {
+        order_hash: B256,
+        user:       Address,
+        pool_id:    PoolId
+    },
+    /// a leader misbehaved, flattened into the event with its `kind`
+    ConsensusAnomaly(WatchtowerAlert)
+}
+
+impl From<PoolManagerUpdate> for NotifierEvent {
+    fn from(update: PoolManagerUpdate) -> Self {
+        match update {
+            PoolManagerUpdate::NewOrder(order) => Self::OrderAccepted {
+                order_hash: order.order_id.hash,
+                user:       order.from(),
+                pool_id:    order.pool_id
+            },
+            PoolManagerUpdate::RejectedOrder { order_hash, error } => {
+                Self::OrderRejected { order_hash, error }
+            }
+            PoolManagerUpdate::FilledOrder(block, order) => Self::OrderFilled {
+                order_hash: order.order_id.hash,
+                user: order.from(),
+                pool_id: order.pool_id,
+                block
+            },
+            PoolManagerUpdate::UnfilledOrders(order) => Self::OrderUnfilled {
+                order_hash: order.order_id.hash,
+                user:       order.from(),
+                pool_id:    order.pool_id
+            },
+            PoolManagerUpdate::CancelledOrder { user, pool_id, order_hash } => {
+                Self::OrderCancelled { order_hash, user, pool_id }
+            }
+            PoolManagerUpdate::ExpiredOrder(order) => Self::OrderExpired {
+                order_hash: order.order_id.hash,
+                user:       order.from(),
+                pool_id:    order.pool_id
+            }
+        }
+    }
+}
+
+impl From<WatchtowerAlert> for NotifierEvent {
+    fn from(alert: WatchtowerAlert) -> Self {
+        Self::ConsensusAnomaly(alert)
+    }
+}
+
+/// The body POSTed to the webhooks. Retries of a notification are sent with
+/// the same `id`, which counts up from 0 every time the node starts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    pub id:        u64,
+    /// unix time in milliseconds the event happened at
+    pub timestamp: u64,
+    #[serde(flatten)]
+    pub event:     NotifierEvent
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_flattened_into_the_notification() {
+        let notification = Notification {
+            id:        3,
+            timestamp: 1_700_000_000_000,
+            event:     PoolManagerUpdate::CancelledOrder {
+                user:       Address::with_last_byte(1),
+                pool_id:    PoolId::with_last_byte(2),
+                order_hash: B256::with_last_byte(3)
+            }
+            .into()
+        };
+
+        let json = serde_json::to_value(&notification).unwrap();
+        assert_eq!(json["event"], "orderCancelled");
+        assert_eq!(json["orderHash"], serde_json::to_value(B256::with_last_byte(3)).unwrap());
+        assert_eq!(serde_json::from_value::<Notification>(json).unwrap(), notification);
+
+        let alert =
+            WatchtowerAlert::MissedSubmission { height: 7, leader: None, proposed: true };
+        let json = serde_json::to_value(NotifierEvent::from(alert)).unwrap();
+        assert_eq!(json["event"], "consensusAnomaly");
+        assert_eq!(json["kind"], "missedSubmission");
+    }
+}
@@ -0,0 +1,18 @@
+//! Webhook callbacks for integrators without a streaming connection.
+//!
+//! The [`Notifier`] posts what happens to the orders of the pool, accepted,
+//! rejected, filled, unfilled by a reorg, cancelled or expired, and the
+//! misbehavior of leaders consensus finds, as a json [`Notification`] to every
+//! configured webhook. Deliveries that fail are retried with a backoff. When a
+//! secret is configured, the body is signed with HMAC-SHA256 in the
+//! [`SIGNATURE_HEADER`], so the receiver can check it came from the node.
+
+mod config;
+mod event;
+mod notifier;
+mod webhook;
+
+pub use config::*;
+pub use event::*;
+pub use notifier::*;
+pub use webhook::*;
@@ -0,0 +1,122 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::{SystemTime, UNIX_EPOCH}
+};
+
+use alloy::transports::http::reqwest::Client;
+use consensus::WatchtowerAlert;
+use futures::{future::BoxFuture, stream::FuturesUnordered, Future, FutureExt, StreamExt};
+use order_pool::PoolManagerUpdate;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+
+use crate::{sign, webhook::Delivery, Notification, NotifierConfig, NotifierEvent};
+
+/// Notifications on their way to the webhooks, including the ones waiting to
+/// be retried, before new ones are dropped.
+const MAX_PENDING_DELIVERIES: usize = 4096;
+
+/// Turns the updates of the order pool and the anomalies of consensus into
+/// [`Notification`]s and delivers them to the webhooks.
+pub struct Notifier {
+    config:     NotifierConfig,
+    client:     Client,
+    orders:     BroadcastStream<PoolManagerUpdate>,
+    anomalies:  Option<BroadcastStream<WatchtowerAlert>>,
+    next_id:    u64,
+    deliveries: FuturesUnordered<BoxFuture<'static, ()>>
+}
+
+impl Notifier {
+    pub fn new(config: NotifierConfig, orders: BroadcastStream<PoolManagerUpdate>) -> Self {
+        Self {
+            config,
+            client: Client::new(),
+            orders,
+            anomalies: None,
+            next_id: 0,
+            deliveries: FuturesUnordered::new()
+        }
+    }
+
+    /// Also notifies the misbehavior of leaders consensus finds.
+    pub fn with_anomalies(mut self, anomalies: BroadcastStream<WatchtowerAlert>) -> Self {
+        self.anomalies = Some(anomalies);
+        self
+    }
+
+    fn notify(&mut self, event: NotifierEvent) {
+        let notification = Notification { id: self.next_id, timestamp: unix_now_millis(), event };
+        self.next_id += 1;
+
+        let body = serde_json::to_vec(&notification).expect("notifications serialize");
+        let signature = self
+            .config
+            .secret
+            .as_ref()
+            .map(|secret| sign(secret, &body));
+
+        for url in &self.config.webhooks {
+            if self.deliveries.len() >= MAX_PENDING_DELIVERIES {
+                tracing::warn!(
+                    %url,
+                    id = notification.id,
+                    "too many pending notifications, dropping"
+                );
+                continue
+            }
+
+            let delivery = Delivery {
+                url:       url.clone(),
+                body:      body.clone(),
+                signature: signature.clone()
+            };
+            self.deliveries.push(
+                delivery
+                    .send(self.client.clone(), self.config.max_attempts, self.config.retry_backoff)
+                    .boxed()
+            );
+        }
+    }
+}
+
+impl Future for Notifier {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        while let Poll::Ready(Some(update)) = this.orders.poll_next_unpin(cx) {
+            match update {
+                Ok(update) => this.notify(update.into()),
+                Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                    tracing::warn!(missed, "notifier missed order updates")
+                }
+            }
+        }
+
+        while let Some(Poll::Ready(Some(alert))) = this
+            .anomalies
+            .as_mut()
+            .map(|anomalies| anomalies.poll_next_unpin(cx))
+        {
+            match alert {
+                Ok(alert) => this.notify(alert.into()),
+                Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                    tracing::warn!(missed, "notifier missed consensus anomalies")
+                }
+            }
+        }
+
+        while let Poll::Ready(Some(())) = this.deliveries.poll_next_unpin(cx) {}
+
+        Poll::Pending
+    }
+}
+
+fn unix_now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use alloy::{
+    hex,
+    transports::http::reqwest::{header::CONTENT_TYPE, Client, StatusCode, Url}
+};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Header holding the signature of the body, `sha256=` followed by the hex
+/// encoded HMAC-SHA256 of it under the notifier's secret.
+pub const SIGNATURE_HEADER: &str = "x-angstrom-signature";
+
+/// How long a webhook has to take a notification.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest wait between two attempts.
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+
+/// The value of the [`SIGNATURE_HEADER`] for `body`.
+pub fn sign(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("hmac takes keys of any size");
+    mac.update(body);
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Wait after the failed `attempt`, counted from 1.
+fn retry_delay(backoff: Duration, attempt: u32) -> Duration {
+    backoff
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_RETRY_BACKOFF)
+}
+
+/// Whether a webhook answering with `status` may take the notification later.
+fn is_retryable(status: StatusCode) -> bool {
+    status.is_server_error()
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::REQUEST_TIMEOUT
+}
+
+/// A signed notification on its way to a webhook.
+#[derive(Debug, Clone)]
+pub(crate) struct Delivery {
+    pub(crate) url:       Url,
+    pub(crate) body:      Vec<u8>,
+    pub(crate) signature: Option<String>
+}
+
+impl Delivery {
+    /// POSTs the notification until the webhook takes it, it refuses it for
+    /// good or `max_attempts` are used up.
+    pub(crate) async fn send(self, client: Client, max_attempts: u32, backoff: Duration) {
+        for attempt in 1..=max_attempts {
+            let mut request = client
+                .post(self.url.clone())
+                .header(CONTENT_TYPE, "application/json")
+                .timeout(WEBHOOK_TIMEOUT)
+                .body(self.body.clone());
+            if let Some(signature) = &self.signature {
+                request = request.header(SIGNATURE_HEADER, signature);
+            }
+
+            match request.send().await {
+                Ok(response) if response.status().is_success() => return,
+                Ok(response) if !is_retryable(response.status()) => {
+                    tracing::warn!(
+                        url=%self.url,
+                        status=%response.status(),
+                        "webhook refused notification"
+                    );
+                    return
+                }
+                Ok(response) => {
+                    tracing::debug!(
+                        url=%self.url,
+                        status=%response.status(),
+                        attempt,
+                        "webhook failed"
+                    );
+                }
+                Err(e) => tracing::debug!(url=%self.url, %e, attempt, "webhook unreachable")
+            }
+
+            if attempt < max_attempts {
+                tokio::time::sleep(retry_delay(backoff, attempt)).await;
+            }
+        }
+
+        tracing::warn!(
+            url=%self.url,
+            max_attempts,
+            "dropping notification the webhook didn't take"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_with_hmac_sha256() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign(b"Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_a_cap() {
+        let backoff = Duration::from_millis(500);
+        assert_eq!(retry_delay(backoff, 1), backoff);
+        assert_eq!(retry_delay(backoff, 3), Duration::from_secs(2));
+        assert_eq!(retry_delay(backoff, 40), MAX_RETRY_BACKOFF);
+    }
+}
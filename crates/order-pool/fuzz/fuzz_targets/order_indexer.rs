@@ -0,0 +1,451 @@
+//! Drives the [`OrderIndexer`] with random interleavings of new orders,
+//! cancellations, block transitions, reorgs and finalizations, checking its
+//! invariants after every step.
+//!
+//! ```sh
+//! cargo +nightly fuzz run order_indexer
+//! ```
+#![no_main]
+
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    ops::Range,
+    sync::{Arc, Mutex},
+    task::Context,
+    time::{SystemTime, UNIX_EPOCH}
+};
+
+use alloy::{
+    primitives::{keccak256, Address, B256, U256},
+    signers::{local::PrivateKeySigner, SignerSync},
+    sol_types::SolValue
+};
+use angstrom_types::{
+    contract_bindings::angstrom::Angstrom::PoolKey,
+    contract_payloads::angstrom::AngstromPoolConfigStore,
+    orders::{CancelOrderRequest, OrderId, OrderOrigin, OrderPriorityData},
+    primitive::{AngstromSigner, NewInitializedPool, PeerId, PoolId},
+    sol_bindings::{
+        grouped_orders::{AllOrders, OrderWithStorageData, StandingVariants},
+        RawPoolOrder
+    }
+};
+use arbitrary::Arbitrary;
+use futures_util::{task::noop_waker_ref, StreamExt};
+use libfuzzer_sys::fuzz_target;
+use order_pool::{order_storage::OrderStorage, OrderIndexer, PoolConfig};
+use testing_tools::type_generator::orders::{ToBOrderBuilder, UserOrderBuilder};
+use validation::{
+    order::{
+        state::pools::AngstromPoolsTracker, GasEstimationFuture, NonceFuture,
+        OrderValidationResults, OrderValidatorHandle, ValidationFuture
+    },
+    ValidationError
+};
+
+const USERS: u8 = 4;
+const POOLS: [(u8, u8); 2] = [(1, 2), (3, 4)];
+
+#[derive(Debug, Arbitrary)]
+enum OrderKind {
+    Standing,
+    Flash,
+    TopOfBlock
+}
+
+#[derive(Debug, Arbitrary)]
+enum Expiry {
+    Expired,
+    /// expires before the next block
+    NextBlock,
+    Later
+}
+
+#[derive(Debug, Arbitrary)]
+enum Action {
+    NewOrder {
+        user:      u8,
+        pool:      bool,
+        kind:      OrderKind,
+        expiry:    Expiry,
+        valid:     bool,
+        from_peer: bool
+    },
+    /// submits an order we have already seen again
+    Resubmit {
+        order: u8
+    },
+    Cancel {
+        order: u8
+    },
+    /// starts a new block that fills some of the indexed orders and changes
+    /// the state of some users. Only polls if the last block isn't done yet.
+    NewBlock {
+        filled:  Vec<u8>,
+        touched: Vec<u8>
+    },
+    /// unfills orders filled in blocks that aren't finalized
+    Reorg {
+        orders: Vec<u8>
+    },
+    Finalize {
+        depth: u8
+    },
+    Poll
+}
+
+/// Checks orders the way the real validator does for everything the indexer
+/// relies on: the order has to be for a known pool, not be expired, and not
+/// be marked invalid by the input. Orders are only checked once their future
+/// is first polled, like the real validator.
+#[derive(Debug, Clone, Default)]
+struct FuzzValidator(Arc<Mutex<ValidatorState>>);
+
+#[derive(Debug, Default)]
+struct ValidatorState {
+    block:   u64,
+    pools:   HashMap<(Address, Address), PoolId>,
+    invalid: HashSet<B256>
+}
+
+impl ValidatorState {
+    fn validate(&self, order: AllOrders) -> OrderValidationResults {
+        let hash = order.order_hash();
+        let expired = order
+            .deadline()
+            .is_some_and(|deadline| deadline <= U256::from(now()))
+            || order
+                .flash_block()
+                .is_some_and(|block| block != self.block + 1);
+
+        let Some(pool_id) = self
+            .pools
+            .get(&(order.token_in(), order.token_out()))
+            .copied()
+            .filter(|_| !expired && !self.invalid.contains(&hash))
+        else {
+            return OrderValidationResults::Invalid(hash, ValidationError::UnknownPool)
+        };
+
+        OrderValidationResults::Valid(OrderWithStorageData {
+            order_id: OrderId::from_all_orders(&order, pool_id),
+            is_bid: order.token_in() > order.token_out(),
+            // amounts are unique per order, so priorities never collide
+            priority_data: OrderPriorityData { volume: order.amount_in(), ..Default::default() },
+            order,
+            valid_block: self.block,
+            pool_id,
+            is_currently_valid: true,
+            is_valid: true,
+            invalidates: vec![],
+            tob_reward: U256::ZERO
+        })
+    }
+}
+
+impl OrderValidatorHandle for FuzzValidator {
+    type Order = AllOrders;
+
+    fn validate_order(&self, _: OrderOrigin, order: AllOrders) -> ValidationFuture {
+        let this = self.clone();
+        Box::pin(async move { this.0.lock().unwrap().validate(order) })
+    }
+
+    fn new_block(&self, block_number: u64, _: Vec<B256>, _: Vec<Address>) -> ValidationFuture {
+        self.0.lock().unwrap().block = block_number;
+        Box::pin(async move { OrderValidationResults::TransitionedToBlock })
+    }
+
+    fn validate_amendment(
+        &self,
+        origin: OrderOrigin,
+        order: StandingVariants,
+        _: B256
+    ) -> ValidationFuture {
+        self.validate_order(origin, AllOrders::Standing(order))
+    }
+
+    fn simulate_order(&self, order: AllOrders) -> ValidationFuture {
+        let this = self.clone();
+        Box::pin(async move { this.0.lock().unwrap().validate(order) })
+    }
+
+    fn estimate_gas(&self, _: AllOrders) -> GasEstimationFuture {
+        Box::pin(async move { Err(ValidationError::SimulationFailed("not supported".to_string())) })
+    }
+
+    fn next_valid_nonce(&self, _: Address) -> NonceFuture<u64> {
+        Box::pin(async move { 0 })
+    }
+
+    fn invalidated_nonces(&self, _: Address, _: Range<u64>) -> NonceFuture<Vec<u64>> {
+        Box::pin(async move { vec![] })
+    }
+}
+
+struct Harness {
+    indexer:             OrderIndexer<FuzzValidator>,
+    validator:           FuzzValidator,
+    users:               Vec<AngstromSigner>,
+    pools:               Vec<PoolKey>,
+    /// every order submitted so far
+    orders:              Vec<AllOrders>,
+    /// orders filled in blocks that aren't finalized
+    filled:              Vec<(u64, Vec<B256>)>,
+    block:               u64,
+    nonce:               u64,
+    /// a block was started and the expiry of the remaining orders hasn't been
+    /// checked yet
+    awaiting_transition: bool
+}
+
+impl Harness {
+    fn new() -> Self {
+        let validator = FuzzValidator::default();
+        let (tx, _) = tokio::sync::broadcast::channel(100);
+        let mut indexer = OrderIndexer::new(
+            validator.clone(),
+            Arc::new(OrderStorage::new(&PoolConfig::default())),
+            1,
+            tx,
+            AngstromPoolsTracker::new(Address::ZERO, Arc::new(AngstromPoolConfigStore::default()))
+        );
+        validator.0.lock().unwrap().block = 1;
+
+        let pools = POOLS
+            .iter()
+            .map(|&(currency0, currency1)| PoolKey {
+                currency0: Address::with_last_byte(currency0),
+                currency1: Address::with_last_byte(currency1),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        for key in &pools {
+            let id = PoolId::from(key.clone());
+            indexer.new_pool(NewInitializedPool {
+                currency_in: key.currency0,
+                currency_out: key.currency1,
+                id
+            });
+
+            let mut state = validator.0.lock().unwrap();
+            state.pools.insert((key.currency0, key.currency1), id);
+            state.pools.insert((key.currency1, key.currency0), id);
+        }
+
+        let users = (1..=USERS)
+            .map(|i| {
+                AngstromSigner::new(PrivateKeySigner::from_bytes(&B256::with_last_byte(i)).unwrap())
+            })
+            .collect();
+
+        Self {
+            indexer,
+            validator,
+            users,
+            pools,
+            orders: vec![],
+            filled: vec![],
+            block: 1,
+            nonce: 0,
+            awaiting_transition: false
+        }
+    }
+
+    fn apply(&mut self, action: &Action) {
+        match action {
+            Action::NewOrder { user, pool, kind, expiry, valid, from_peer } => {
+                let order = self.build_order(*user, *pool, kind, expiry);
+                if !valid {
+                    self.validator
+                        .0
+                        .lock()
+                        .unwrap()
+                        .invalid
+                        .insert(order.order_hash());
+                }
+                self.submit(order.clone(), *from_peer);
+                self.orders.push(order);
+            }
+            Action::Resubmit { order } => {
+                if let Some(order) = pick(&self.orders, *order).cloned() {
+                    self.submit(order, false);
+                }
+            }
+            Action::Cancel { order } => {
+                let Some(order) = pick(&self.orders, *order) else { return };
+                let hash = order.order_hash();
+                let user_address = order.from();
+                let signer = self
+                    .users
+                    .iter()
+                    .find(|user| user.address() == user_address)
+                    .unwrap();
+                let signature = signer
+                    .sign_hash_sync(&keccak256((user_address, hash).abi_encode()))
+                    .unwrap();
+
+                self.indexer.cancel_order(&CancelOrderRequest {
+                    signature,
+                    user_address,
+                    order_id: hash
+                });
+            }
+            Action::NewBlock { filled, touched } => {
+                if self.indexer.is_transitioning() {
+                    return self.poll()
+                }
+
+                let mut indexed = self.indexer.order_hashes();
+                indexed.sort();
+                let filled = filled
+                    .iter()
+                    .filter_map(|i| pick(&indexed, *i).copied())
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                let touched = touched
+                    .iter()
+                    .map(|i| self.users[(i % USERS) as usize].address())
+                    .collect();
+
+                self.block += 1;
+                self.indexer
+                    .start_new_block_processing(self.block, filled.clone(), touched);
+                self.awaiting_transition = true;
+                if !filled.is_empty() {
+                    self.filled.push((self.block, filled));
+                }
+            }
+            Action::Reorg { orders } => {
+                let unfinalized = self
+                    .filled
+                    .iter()
+                    .flat_map(|(_, hashes)| hashes.iter().copied())
+                    .collect::<Vec<_>>();
+                let reorged = orders
+                    .iter()
+                    .filter_map(|i| pick(&unfinalized, *i).copied())
+                    .collect();
+
+                self.indexer.reorg(reorged);
+            }
+            Action::Finalize { depth } => {
+                let Some(&(block, _)) = self.filled.first() else { return };
+                if block + (depth % 4) as u64 <= self.block {
+                    self.indexer.finalized_block(block);
+                    self.filled.remove(0);
+                }
+            }
+            Action::Poll => self.poll()
+        }
+    }
+
+    fn build_order(
+        &mut self,
+        user: u8,
+        pool: bool,
+        kind: &OrderKind,
+        expiry: &Expiry
+    ) -> AllOrders {
+        let signer = self.users[(user % USERS) as usize].clone();
+        let key = &self.pools[pool as usize];
+        // every order gets its own amount so that no two orders share a hash
+        self.nonce += 1;
+
+        let deadline = match expiry {
+            Expiry::Expired => now() - 10,
+            // anything expiring within a block time is dropped at the next block
+            Expiry::NextBlock => now() + 5,
+            Expiry::Later => now() + 3600
+        };
+        let flash_block = match expiry {
+            Expiry::Expired => self.block,
+            Expiry::NextBlock => self.block + 1,
+            Expiry::Later => self.block + 2
+        };
+
+        match kind {
+            OrderKind::Standing => UserOrderBuilder::new()
+                .standing()
+                .asset_in(key.currency0)
+                .asset_out(key.currency1)
+                .amount(1_000 + self.nonce as u128)
+                .nonce(self.nonce)
+                .deadline(U256::from(deadline))
+                .recipient(signer.address())
+                .signing_key(Some(signer))
+                .build()
+                .into(),
+            OrderKind::Flash => UserOrderBuilder::new()
+                .kill_or_fill()
+                .asset_in(key.currency1)
+                .asset_out(key.currency0)
+                .amount(1_000 + self.nonce as u128)
+                .block(flash_block)
+                .recipient(signer.address())
+                .signing_key(Some(signer))
+                .build()
+                .into(),
+            OrderKind::TopOfBlock => ToBOrderBuilder::new()
+                .asset_in(key.currency0)
+                .asset_out(key.currency1)
+                .quantity_in(1_000 + self.nonce as u128)
+                .quantity_out(1_000 + self.nonce as u128)
+                .valid_block(flash_block)
+                .recipient(signer.address())
+                .signing_key(Some(signer))
+                .build()
+                .into()
+        }
+    }
+
+    fn submit(&mut self, order: AllOrders, from_peer: bool) {
+        if from_peer {
+            self.indexer
+                .new_network_order(PeerId::default(), OrderOrigin::External, order);
+        } else {
+            let (tx, _) = tokio::sync::oneshot::channel();
+            self.indexer.new_rpc_order(OrderOrigin::Local, order, tx);
+        }
+    }
+
+    /// Lets the indexer make progress on the validations and block transition
+    /// in flight. Once a block transition completes, nothing that expired may
+    /// be left.
+    fn poll(&mut self) {
+        let _ = self
+            .indexer
+            .poll_next_unpin(&mut Context::from_waker(noop_waker_ref()));
+
+        if self.awaiting_transition && !self.indexer.is_transitioning() {
+            self.awaiting_transition = false;
+            self.indexer
+                .check_no_expired_orders()
+                .unwrap_or_else(|e| panic!("{e} after block {}", self.block));
+        }
+    }
+}
+
+fn pick<T>(items: &[T], index: u8) -> Option<&T> {
+    (!items.is_empty()).then(|| &items[index as usize % items.len()])
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fuzz_target!(|actions: Vec<Action>| {
+    let mut harness = Harness::new();
+
+    for action in &actions {
+        harness.apply(action);
+        harness
+            .indexer
+            .check_invariants()
+            .unwrap_or_else(|e| panic!("{e} after {action:?}"));
+    }
+});
@@ -0,0 +1,288 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    ops::RangeInclusive,
+    path::Path
+};
+
+use alloy::primitives::{BlockNumber, B256};
+use angstrom_types::{
+    orders::SourceTag,
+    primitive::PoolId,
+    sol_bindings::{grouped_orders::AllOrders, RawPoolOrder}
+};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio_stream::wrappers::BroadcastStream;
+
+use crate::{OrderSnapshot, PoolManagerUpdate, SourceTags};
+
+/// A change to the book, as the archive stores it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ArchivedOrderEvent {
+    /// the order entered the book of `pool_id` at `block`, submitted through
+    /// `source_tag` if it was tagged
+    Added {
+        block:      BlockNumber,
+        pool_id:    PoolId,
+        order:      AllOrders,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        source_tag: Option<SourceTag>
+    },
+    /// the order was cancelled or expired at `block`
+    Removed { block: BlockNumber, order_hash: B256 }
+}
+
+impl ArchivedOrderEvent {
+    pub fn block(&self) -> BlockNumber {
+        match self {
+            Self::Added { block, .. } | Self::Removed { block, .. } => *block
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum OrderArchiveError {
+    #[error("block {block} is before the archive, which starts at block {first}")]
+    NotArchived { block: BlockNumber, first: BlockNumber },
+    #[error("order archive is corrupted at line {0}")]
+    Corrupted(usize),
+    #[error("failed to read the orders filled on chain - {0}")]
+    Backfill(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error)
+}
+
+/// Appends every order that enters or leaves the pool to a file, so that the
+/// book can be rebuilt as of a past block with [`resting_orders`]. Fills
+/// aren't archived, they are read back from the settlement receipts, which
+/// also covers the blocks the node missed.
+#[derive(Debug)]
+pub struct OrderArchive {
+    store:       File,
+    /// the latest block we heard of, removals are archived at it
+    latest:      BlockNumber,
+    /// where the source tags of the added orders are looked up
+    source_tags: SourceTags
+}
+
+impl OrderArchive {
+    /// Opens the archive at `path`, creating it if it doesn't exist.
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let store = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { store, latest: 0, source_tags: SourceTags::default() })
+    }
+
+    /// Archives the orders along with their source tag, as found in
+    /// `source_tags`.
+    pub fn with_source_tags(mut self, source_tags: SourceTags) -> Self {
+        self.source_tags = source_tags;
+        self
+    }
+
+    /// Archives the updates of the pool until it shuts down.
+    pub async fn run(mut self, mut updates: BroadcastStream<PoolManagerUpdate>) {
+        while let Some(update) = updates.next().await {
+            match update {
+                Ok(update) => {
+                    if let Err(e) = self.record(update) {
+                        tracing::warn!(err=%e, "failed to archive an order update");
+                    }
+                }
+                Err(e) => tracing::warn!(err=%e, "order archive missed updates")
+            }
+        }
+    }
+
+    pub fn record(&mut self, update: PoolManagerUpdate) -> std::io::Result<()> {
+        let event = match update {
+            PoolManagerUpdate::NewOrder(order) | PoolManagerUpdate::UnfilledOrders(order) => {
+                self.latest = self.latest.max(order.valid_block);
+                ArchivedOrderEvent::Added {
+                    block:      order.valid_block,
+                    pool_id:    order.pool_id,
+                    source_tag: self.source_tags.get(&order.order_id.hash),
+                    order:      order.order
+                }
+            }
+            PoolManagerUpdate::FilledOrder(block, _) => {
+                self.latest = self.latest.max(block);
+                return Ok(())
+            }
+            PoolManagerUpdate::CancelledOrder { order_hash, .. } => {
+                ArchivedOrderEvent::Removed { block: self.latest, order_hash }
+            }
+            PoolManagerUpdate::ExpiredOrder(order) => ArchivedOrderEvent::Removed {
+                block:      self.latest,
+                order_hash: order.order_id.hash
+            },
+            PoolManagerUpdate::RejectedOrder { .. } => return Ok(())
+        };
+
+        serde_json::to_writer(&mut self.store, &event)?;
+        self.store.write_all(b"\n")
+    }
+}
+
+/// The orders resting in the book of `pool_id` once `block` was built, as
+/// archived at `path`. `filled` reads the hashes of the orders filled in a
+/// range of blocks from the chain.
+pub fn resting_orders<E: std::fmt::Display>(
+    path: impl AsRef<Path>,
+    pool_id: PoolId,
+    block: BlockNumber,
+    filled: impl FnOnce(RangeInclusive<BlockNumber>) -> Result<HashSet<B256>, E>
+) -> Result<OrderSnapshot, OrderArchiveError> {
+    let reader = BufReader::new(File::open(path)?);
+
+    let mut first = None;
+    let mut book = HashMap::new();
+    for (i, line) in reader.lines().enumerate() {
+        let event: ArchivedOrderEvent =
+            serde_json::from_str(&line?).map_err(|_| OrderArchiveError::Corrupted(i + 1))?;
+        first.get_or_insert(event.block());
+        if event.block() > block {
+            continue
+        }
+
+        match event {
+            ArchivedOrderEvent::Added { block, pool_id: id, order, .. } if id == pool_id => {
+                book.insert(order.order_hash(), (block, order));
+            }
+            ArchivedOrderEvent::Added { .. } => {}
+            ArchivedOrderEvent::Removed { order_hash, .. } => {
+                book.remove(&order_hash);
+            }
+        }
+    }
+
+    match first {
+        Some(first) if first <= block => {}
+        Some(first) => return Err(OrderArchiveError::NotArchived { block, first }),
+        None => return Ok(OrderSnapshot { block_number: block, orders: vec![] })
+    }
+
+    let oldest = book.values().map(|(added, _)| *added).min();
+    let filled = oldest
+        .map(|oldest| filled(oldest..=block))
+        .transpose()
+        .map_err(|e| OrderArchiveError::Backfill(e.to_string()))?
+        .unwrap_or_default();
+
+    let mut snapshot = OrderSnapshot {
+        block_number: block,
+        orders:       book.into_values().map(|(_, order)| order).collect()
+    };
+    snapshot.prune(&filled, block);
+
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use angstrom_types::sol_bindings::{
+        grouped_orders::{OrderWithStorageData, StandingVariants},
+        rpc_orders::ExactStandingOrder
+    };
+
+    use super::*;
+
+    fn standing(nonce: u64, pool_id: PoolId, valid_block: u64) -> OrderWithStorageData<AllOrders> {
+        let order = AllOrders::Standing(StandingVariants::Exact(ExactStandingOrder {
+            nonce,
+            ..Default::default()
+        }));
+        let mut order = OrderWithStorageData { order, pool_id, valid_block, ..Default::default() };
+        order.order_id.hash = order.order.order_hash();
+        order
+    }
+
+    #[test]
+    fn rebuilds_the_book_of_a_past_block() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("order_archive.jsonl");
+        let (pool, other_pool) = (PoolId::repeat_byte(1), PoolId::repeat_byte(2));
+        let orders = [standing(1, pool, 10), standing(2, pool, 11), standing(3, pool, 12)];
+
+        let mut archive = OrderArchive::open(&path).unwrap();
+        archive
+            .record(PoolManagerUpdate::NewOrder(orders[0].clone()))
+            .unwrap();
+        archive
+            .record(PoolManagerUpdate::NewOrder(standing(4, other_pool, 10)))
+            .unwrap();
+        archive
+            .record(PoolManagerUpdate::NewOrder(orders[1].clone()))
+            .unwrap();
+        archive
+            .record(PoolManagerUpdate::NewOrder(orders[2].clone()))
+            .unwrap();
+        archive
+            .record(PoolManagerUpdate::ExpiredOrder(orders[1].clone()))
+            .unwrap();
+
+        let hashes = |snapshot: OrderSnapshot| {
+            snapshot
+                .orders
+                .iter()
+                .map(|order| order.order_hash())
+                .collect::<HashSet<_>>()
+        };
+        let nothing_filled = |_| Ok::<_, Infallible>(HashSet::new());
+
+        let at_11 = resting_orders(&path, pool, 11, nothing_filled).unwrap();
+        assert_eq!(
+            hashes(at_11),
+            HashSet::from([orders[0].order.order_hash(), orders[1].order.order_hash()])
+        );
+
+        // the first order was filled on chain, the second expired at block 12
+        let filled = |range: RangeInclusive<u64>| {
+            assert_eq!(range, 10..=12);
+            Ok::<_, Infallible>(HashSet::from([orders[0].order.order_hash()]))
+        };
+        let at_12 = resting_orders(&path, pool, 12, filled).unwrap();
+        assert_eq!(hashes(at_12), HashSet::from([orders[2].order.order_hash()]));
+
+        assert!(matches!(
+            resting_orders(&path, pool, 9, nothing_filled),
+            Err(OrderArchiveError::NotArchived { block: 9, first: 10 })
+        ));
+    }
+
+    #[test]
+    fn archives_the_source_tag_of_orders() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("order_archive.jsonl");
+        let pool = PoolId::repeat_byte(1);
+        let (tagged, untagged) = (standing(1, pool, 10), standing(2, pool, 10));
+        let tag = SourceTag::new("dapp").unwrap();
+
+        let source_tags = SourceTags::default();
+        source_tags.insert(tagged.order_id.hash, tag.clone());
+        let mut archive = OrderArchive::open(&path)
+            .unwrap()
+            .with_source_tags(source_tags);
+        archive.record(PoolManagerUpdate::NewOrder(tagged)).unwrap();
+        archive
+            .record(PoolManagerUpdate::NewOrder(untagged))
+            .unwrap();
+
+        let events = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<ArchivedOrderEvent>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            &events[..],
+            [
+                ArchivedOrderEvent::Added { source_tag: Some(source_tag), .. },
+                ArchivedOrderEvent::Added { source_tag: None, .. }
+            ] if *source_tag == tag
+        ));
+    }
+}
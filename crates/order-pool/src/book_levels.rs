@@ -0,0 +1,161 @@
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use alloy::primitives::U256;
+use angstrom_types::{
+    orders::{BookDelta, BookLevel, BookLevelUpdate, BookSide, BookSnapshot},
+    primitive::PoolId
+};
+
+/// The pending vanilla orders of a pool aggregated by price. Every change is
+/// handed out as a [`BookDelta`] so subscribers can keep their own copy of the
+/// book without refetching it.
+#[derive(Debug)]
+pub struct BookLevels {
+    pool_id:  PoolId,
+    sequence: u64,
+    bids:     BTreeMap<U256, BookLevel>,
+    asks:     BTreeMap<U256, BookLevel>
+}
+
+impl BookLevels {
+    pub fn new(pool_id: PoolId) -> Self {
+        Self { pool_id, sequence: 0, bids: BTreeMap::new(), asks: BTreeMap::new() }
+    }
+
+    fn side_mut(&mut self, side: BookSide) -> &mut BTreeMap<U256, BookLevel> {
+        match side {
+            BookSide::Bid => &mut self.bids,
+            BookSide::Ask => &mut self.asks
+        }
+    }
+
+    pub fn add(&mut self, side: BookSide, price: U256, volume: u128) -> BookDelta {
+        let update = match self.side_mut(side).entry(price) {
+            Entry::Occupied(mut entry) => {
+                let level = entry.get_mut();
+                level.volume = level.volume.saturating_add(volume);
+                level.orders += 1;
+                BookLevelUpdate::Modify { side, level: *level }
+            }
+            Entry::Vacant(entry) => {
+                let level = entry.insert(BookLevel { price, volume, orders: 1 });
+                BookLevelUpdate::Add { side, level: *level }
+            }
+        };
+
+        self.next(update)
+    }
+
+    /// Returns none if there was no order at the price.
+    pub fn remove(&mut self, side: BookSide, price: U256, volume: u128) -> Option<BookDelta> {
+        let levels = self.side_mut(side);
+        let level = levels.get_mut(&price)?;
+        level.volume = level.volume.saturating_sub(volume);
+        level.orders -= 1;
+
+        let update = if level.orders == 0 {
+            levels.remove(&price);
+            BookLevelUpdate::Remove { side, price }
+        } else {
+            BookLevelUpdate::Modify { side, level: *level }
+        };
+
+        Some(self.next(update))
+    }
+
+    /// Empties the book, removing every level.
+    pub fn clear(&mut self) -> Vec<BookDelta> {
+        let bids = std::mem::take(&mut self.bids)
+            .into_keys()
+            .map(|price| BookLevelUpdate::Remove { side: BookSide::Bid, price });
+        let asks = std::mem::take(&mut self.asks)
+            .into_keys()
+            .map(|price| BookLevelUpdate::Remove { side: BookSide::Ask, price });
+
+        bids.chain(asks)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|update| self.next(update))
+            .collect()
+    }
+
+    pub fn snapshot(&self) -> BookSnapshot {
+        BookSnapshot {
+            pool_id:  self.pool_id,
+            sequence: self.sequence,
+            bids:     self.bids.values().rev().copied().collect(),
+            asks:     self.asks.values().copied().collect()
+        }
+    }
+
+    fn next(&mut self, update: BookLevelUpdate) -> BookDelta {
+        self.sequence += 1;
+        BookDelta { pool_id: self.pool_id, sequence: self.sequence, update }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_are_added_modified_and_removed() {
+        let pool_id = PoolId::repeat_byte(1);
+        let mut book = BookLevels::new(pool_id);
+        let (low, high) = (U256::from(10), U256::from(20));
+
+        let first = book.add(BookSide::Bid, low, 5);
+        assert_eq!(first.sequence, 1);
+        assert_eq!(
+            first.update,
+            BookLevelUpdate::Add {
+                side:  BookSide::Bid,
+                level: BookLevel { price: low, volume: 5, orders: 1 }
+            }
+        );
+        book.add(BookSide::Bid, high, 1);
+        book.add(BookSide::Ask, high, 2);
+        assert_eq!(
+            book.add(BookSide::Bid, low, 3).update,
+            BookLevelUpdate::Modify {
+                side:  BookSide::Bid,
+                level: BookLevel { price: low, volume: 8, orders: 2 }
+            }
+        );
+
+        let snapshot = book.snapshot();
+        assert_eq!(snapshot.sequence, 4);
+        assert_eq!(
+            snapshot
+                .bids
+                .iter()
+                .map(|level| level.price)
+                .collect::<Vec<_>>(),
+            vec![high, low]
+        );
+        assert_eq!(snapshot.asks.len(), 1);
+
+        assert!(book.remove(BookSide::Ask, low, 1).is_none());
+        assert_eq!(
+            book.remove(BookSide::Bid, low, 5).unwrap().update,
+            BookLevelUpdate::Modify {
+                side:  BookSide::Bid,
+                level: BookLevel { price: low, volume: 3, orders: 1 }
+            }
+        );
+        assert_eq!(
+            book.remove(BookSide::Bid, low, 3).unwrap().update,
+            BookLevelUpdate::Remove { side: BookSide::Bid, price: low }
+        );
+
+        let cleared = book.clear();
+        assert_eq!(
+            cleared
+                .iter()
+                .map(|delta| delta.sequence)
+                .collect::<Vec<_>>(),
+            vec![7, 8]
+        );
+        assert_eq!(book.snapshot(), BookSnapshot { pool_id, sequence: 8, ..Default::default() });
+    }
+}
@@ -24,7 +24,11 @@ impl SizeTracker {
         }
     }
 
-    #[allow(dead_code)]
+    /// Orders already tracked stay even if they are now over the limit.
+    pub fn set_max(&mut self, max: Option<usize>) {
+        self.max = max;
+    }
+
     pub fn remove_order(&mut self, size: usize) {
         self.current -= size;
     }
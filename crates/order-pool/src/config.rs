@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use angstrom_types::primitive::PoolId;
 
 /// Guarantees max orders per sender
@@ -15,12 +17,24 @@ pub const SEARCHER_SUBPOOL_MAX_ORDERS_DEFAULT: usize = 100;
 /// The default maximum allowed size of the searcher subpool.
 pub const SEARCHER_SUBPOOL_MAX_SIZE_MB_DEFAULT: usize = 5;
 
+/// Capacity of the command channel into the pool manager. Commands sent while
+/// it is full are rejected.
+pub const ORDER_COMMAND_CHANNEL_SIZE: usize = 4096;
+
+/// Capacity of the order update broadcast. Subscribers that fall further behind
+/// than this lose the oldest updates.
+pub const ORDER_UPDATE_CHANNEL_SIZE: usize = 1024;
+
+/// Capacity of the book delta broadcast. Subscribers that fall further behind
+/// than this have to start over from a snapshot of the book.
+pub const BOOK_UPDATE_CHANNEL_SIZE: usize = 1024;
+
 /// Configuration options for the Transaction pool.
 #[derive(Debug, Clone)]
 pub struct PoolConfig {
     /// pool ids
     pub ids:               Vec<PoolId>,
-    /// Max number of transaction in the pending sub-pool
+    /// Max number of transaction in the pending sub-pool of each pool
     pub lo_pending_limit:  LimitSubPoolLimit,
     /// Max number of transaction in the queued sub-pool
     pub lo_queued_limit:   LimitSubPoolLimit,
@@ -29,9 +43,13 @@ pub struct PoolConfig {
     /// Max number of transaction in the composable limit sub-pool
     pub cl_pending_limit:  LimitSubPoolLimit,
     /// Max number of transaction in the searcher & composable searcher sub-pool
+    /// of each pool
     pub s_pending_limit:   SearcherSubPoolLimit,
     /// Max number of executable transaction slots guaranteed per account
-    pub max_account_slots: usize
+    pub max_account_slots: usize,
+    /// How long orders stay in the books of the network, unless their deadline
+    /// comes first. Orders stay until their deadline when unset
+    pub order_ttl:         Option<Duration>
 }
 
 impl Default for PoolConfig {
@@ -43,7 +61,8 @@ impl Default for PoolConfig {
             lo_parked_limit:   Default::default(),
             cl_pending_limit:  Default::default(),
             s_pending_limit:   Default::default(),
-            max_account_slots: ORDER_POOL_MAX_ACCOUNT_SLOTS_PER_SENDER
+            max_account_slots: ORDER_POOL_MAX_ACCOUNT_SLOTS_PER_SENDER,
+            order_ttl:         None
         }
     }
 }
@@ -1,32 +1,61 @@
+mod archive;
+mod book_levels;
 mod common;
 mod config;
 mod finalization_pool;
 mod limit;
 mod order_indexer;
 pub mod order_storage;
+mod relay;
 
 mod searcher;
+mod snapshot;
+mod source_tags;
+mod storage_view;
 mod validator;
 
-use std::future::Future;
+use std::{future::Future, ops::Range};
 
 use alloy::primitives::{Address, FixedBytes, B256};
 use angstrom_types::{
-    orders::{CancelOrderRequest, OrderLocation, OrderOrigin, OrderStatus},
-    primitive::OrderPoolNewOrderResult,
+    orders::{
+        BookDelta, BookSnapshot, CancelAllOrdersRequest, CancelOrderRequest, OrderAmendment,
+        OrderLocation, OrderOrigin, OrderStatesSnapshot, OrderStatus, SourceTag
+    },
+    primitive::{OrderPoolNewOrderResult, PoolId, ValidationError},
     sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData}
 };
 pub use angstrom_utils::*;
-pub use config::PoolConfig;
+pub use archive::*;
+pub use book_levels::*;
+pub use config::{
+    PoolConfig, BOOK_UPDATE_CHANNEL_SIZE, ORDER_COMMAND_CHANNEL_SIZE, ORDER_UPDATE_CHANNEL_SIZE
+};
 pub use order_indexer::*;
+pub use relay::*;
+pub use snapshot::*;
+pub use source_tags::*;
+pub use storage_view::*;
 use tokio_stream::wrappers::BroadcastStream;
+use validation::order::OrderValidationResults;
 
 #[derive(Debug, Clone)]
 pub enum PoolManagerUpdate {
     NewOrder(OrderWithStorageData<AllOrders>),
     FilledOrder(u64, OrderWithStorageData<AllOrders>),
     UnfilledOrders(OrderWithStorageData<AllOrders>),
-    CancelledOrder { user: Address, pool_id: FixedBytes<32>, order_hash: B256 }
+    CancelledOrder {
+        user:       Address,
+        pool_id:    FixedBytes<32>,
+        order_hash: B256
+    },
+    /// an order that failed validation
+    RejectedOrder {
+        order_hash: B256,
+        error:      ValidationError
+    },
+    /// an order whose deadline or flash block passed before it was filled
+    ExpiredOrder(OrderWithStorageData<AllOrders>)
 }
 
 /// The OrderPool Trait is how other processes can interact with the orderpool
@@ -39,12 +68,66 @@ pub trait OrderPoolHandle: Send + Sync + Clone + Unpin + 'static {
         order: AllOrders
     ) -> impl Future<Output = OrderPoolNewOrderResult> + Send;
 
+    /// A new order submitted through the dapp or frontend `source_tag`, which
+    /// the order is attributed to while it is in the pool. Handles that don't
+    /// attribute orders drop the tag.
+    fn new_tagged_order(
+        &self,
+        origin: OrderOrigin,
+        order: AllOrders,
+        source_tag: Option<SourceTag>
+    ) -> impl Future<Output = OrderPoolNewOrderResult> + Send {
+        let _ = source_tag;
+        self.new_order(origin, order)
+    }
+
+    /// Replaces a pending standing order with the amended one, the result is
+    /// the validation of the amended order.
+    fn amend_order(
+        &self,
+        amendment: OrderAmendment
+    ) -> impl Future<Output = OrderPoolNewOrderResult> + Send;
+
+    /// Validates the order like a new one without adding it to the pool or
+    /// changing the pending state of its user. None if the pool can't be
+    /// reached.
+    fn simulate_order(
+        &self,
+        order: AllOrders
+    ) -> impl Future<Output = Option<OrderValidationResults>> + Send;
+
+    /// The lowest nonce a new order of `user` can use.
+    fn next_valid_nonce(&self, user: Address) -> impl Future<Output = Option<u64>> + Send;
+
+    /// The nonces in `range` that `user` used or invalidated on chain.
+    fn invalidated_nonces(
+        &self,
+        user: Address,
+        range: Range<u64>
+    ) -> impl Future<Output = Option<Vec<u64>>> + Send;
+
     fn subscribe_orders(&self) -> BroadcastStream<PoolManagerUpdate>;
 
     fn pending_orders(&self, sender: Address) -> impl Future<Output = Vec<AllOrders>> + Send;
 
     fn cancel_order(&self, req: CancelOrderRequest) -> impl Future<Output = bool> + Send;
 
+    /// Cancels the orders all at once, with whether each of them was
+    /// cancelled. Handles without a bulk path cancel them one by one.
+    fn cancel_orders(
+        &self,
+        reqs: Vec<CancelOrderRequest>
+    ) -> impl Future<Output = Vec<bool>> + Send {
+        futures_util::future::join_all(reqs.into_iter().map(|req| self.cancel_order(req)))
+    }
+
+    /// Cancels the pending orders `req` covers, returning their hashes.
+    /// Nothing is cancelled if the request is stale.
+    fn cancel_all_orders(
+        &self,
+        req: CancelAllOrdersRequest
+    ) -> impl Future<Output = Vec<B256>> + Send;
+
     fn fetch_orders_from_pool(
         &self,
         pool_id: FixedBytes<32>,
@@ -55,4 +138,14 @@ pub trait OrderPoolHandle: Send + Sync + Clone + Unpin + 'static {
         &self,
         order_hash: B256
     ) -> impl Future<Output = Option<OrderStatus>> + Send;
+
+    /// The state of every order in the pool, used to resync subscribers that
+    /// missed updates.
+    fn fetch_order_states(&self) -> impl Future<Output = OrderStatesSnapshot> + Send;
+
+    /// Changes to the books of all pools. None if the pool isn't running.
+    fn subscribe_book(&self) -> impl Future<Output = Option<BroadcastStream<BookDelta>>> + Send;
+
+    /// The current book of a pool, which book deltas continue from.
+    fn fetch_book(&self, pool_id: PoolId) -> impl Future<Output = Option<BookSnapshot>> + Send;
 }
@@ -61,4 +61,14 @@ impl ComposableLimitPool {
         let old_is_none = self.map.insert(pool.id, PendingPool::new()).is_none();
         assert!(old_is_none);
     }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn size(&self) -> usize {
+        self.map.values().map(|pool| pool.size()).sum()
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn order_hashes(&self) -> impl Iterator<Item = alloy::primitives::FixedBytes<32>> + '_ {
+        self.map.values().flat_map(|pool| pool.order_hashes())
+    }
 }
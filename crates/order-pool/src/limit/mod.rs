@@ -55,10 +55,30 @@ impl LimitOrderPool {
             })
     }
 
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.size.set_max(Some(max_size));
+    }
+
     pub fn remove_pool(&mut self, key: &PoolId) {
-        let _ = self.composable_orders.map.remove(key);
-        let _ = self.limit_orders.parked_orders.remove(key);
-        let _ = self.limit_orders.pending_orders.remove(key);
+        let removed = self
+            .composable_orders
+            .map
+            .remove(key)
+            .map(|pool| pool.size())
+            .unwrap_or_default()
+            + self
+                .limit_orders
+                .parked_orders
+                .remove(key)
+                .map(|pool| pool.size())
+                .unwrap_or_default()
+            + self
+                .limit_orders
+                .pending_orders
+                .remove(key)
+                .map(|pool| pool.size())
+                .unwrap_or_default();
+        self.size.remove_order(removed);
     }
 
     pub fn get_order_status(&self, order_hash: B256) -> Option<OrderStatus> {
@@ -74,7 +94,9 @@ impl LimitOrderPool {
             return Err(LimitPoolError::MaxSize)
         }
 
-        self.composable_orders.add_order(order)
+        self.composable_orders
+            .add_order(order)
+            .inspect_err(|_| self.size.remove_order(size))
     }
 
     pub fn add_vanilla_order(
@@ -86,12 +108,15 @@ impl LimitOrderPool {
             return Err(LimitPoolError::MaxSize)
         }
 
-        self.limit_orders.add_order(order)
+        self.limit_orders
+            .add_order(order)
+            .inspect_err(|_| self.size.remove_order(size))
     }
 
     pub fn remove_order(&mut self, id: &OrderId) -> Option<OrderWithStorageData<GroupedUserOrder>> {
         self.limit_orders
             .remove_order(id.pool_id, id.hash)
+            .inspect(|value| self.size.remove_order(value.size()))
             .and_then(|value| {
                 value
                     .try_map_inner(|this| Ok(GroupedUserOrder::Vanilla(this)))
@@ -100,6 +125,7 @@ impl LimitOrderPool {
             .or_else(|| {
                 self.composable_orders
                     .remove_order(id.pool_id, id.hash)
+                    .inspect(|value| self.size.remove_order(value.size()))
                     .and_then(|value| {
                         value
                             .try_map_inner(|this| Ok(GroupedUserOrder::Composable(this)))
@@ -125,6 +151,22 @@ impl LimitOrderPool {
             .unwrap_or_default()
     }
 
+    pub fn get_parked_orders(&self) -> Vec<OrderWithStorageData<GroupedVanillaOrder>> {
+        self.limit_orders
+            .parked_orders
+            .values()
+            .flat_map(|pool| pool.get_all_orders())
+            .collect()
+    }
+
+    pub fn get_composable_orders(&self) -> Vec<OrderWithStorageData<GroupedComposableOrder>> {
+        self.composable_orders
+            .map
+            .values()
+            .flat_map(|pool| pool.get_all_orders())
+            .collect()
+    }
+
     pub fn park_order(&mut self, id: &OrderId) {
         self.limit_orders.park_order(id);
     }
@@ -133,6 +175,25 @@ impl LimitOrderPool {
         self.limit_orders.new_pool(pool);
         self.composable_orders.new_pool(pool);
     }
+
+    /// The size the tracker accounts for.
+    pub fn tracked_size(&self) -> usize {
+        self.size.current
+    }
+
+    /// The size of the orders actually held.
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn held_size(&self) -> usize {
+        self.limit_orders.size() + self.composable_orders.size()
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn order_hashes(&self) -> Vec<B256> {
+        self.limit_orders
+            .order_hashes()
+            .chain(self.composable_orders.order_hashes())
+            .collect()
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -141,6 +202,8 @@ pub enum LimitPoolError {
     MaxSize,
     #[error("No pool was found for address: {0} ")]
     NoPool(PoolId),
+    #[error("an order with hash {0:?} is already stored")]
+    DuplicateOrder(B256),
     #[error(transparent)]
     Unknown(#[from] eyre::Error)
 }
@@ -28,4 +28,18 @@ impl ParkedPool {
     pub fn new_order(&mut self, order: OrderWithStorageData<GroupedVanillaOrder>) {
         self.0.insert(order.hash(), order);
     }
+
+    pub fn get_all_orders(&self) -> Vec<OrderWithStorageData<GroupedVanillaOrder>> {
+        self.0.values().cloned().collect()
+    }
+
+    /// Size of all the orders held.
+    pub fn size(&self) -> usize {
+        self.0.values().map(|order| order.size()).sum()
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn order_hashes(&self) -> impl Iterator<Item = FixedBytes<32>> + '_ {
+        self.0.keys().copied()
+    }
 }
@@ -55,4 +55,14 @@ impl<Order: Clone> PendingPool<Order> {
     pub fn get_all_orders(&self) -> Vec<OrderWithStorageData<Order>> {
         self.orders.values().cloned().collect()
     }
+
+    /// Size of all the orders held.
+    pub fn size(&self) -> usize {
+        self.orders.values().map(|order| order.size()).sum()
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn order_hashes(&self) -> impl Iterator<Item = FixedBytes<32>> + '_ {
+        self.orders.keys().copied()
+    }
 }
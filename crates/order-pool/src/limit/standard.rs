@@ -133,4 +133,25 @@ impl LimitPool {
 
         assert!(old_is_none);
     }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn size(&self) -> usize {
+        self.pending_orders
+            .values()
+            .map(|pool| pool.size())
+            .chain(self.parked_orders.values().map(|pool| pool.size()))
+            .sum()
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn order_hashes(&self) -> impl Iterator<Item = B256> + '_ {
+        self.pending_orders
+            .values()
+            .flat_map(|pool| pool.order_hashes())
+            .chain(
+                self.parked_orders
+                    .values()
+                    .flat_map(|pool| pool.order_hashes())
+            )
+    }
 }
@@ -3,25 +3,31 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
-    time::{Duration, SystemTime, UNIX_EPOCH}
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH}
 };
 
 use alloy::primitives::{Address, BlockNumber, FixedBytes, B256, U256};
 use angstrom_types::{
-    orders::{OrderId, OrderLocation, OrderOrigin, OrderSet, OrderStatus},
-    primitive::{NewInitializedPool, PeerId, PoolId},
+    orders::{
+        BookDelta, BookSnapshot, CancelAllOrdersRequest, OrderAmendment, OrderId, OrderLocation,
+        OrderOrigin, OrderSet, OrderState, OrderStatesSnapshot, OrderStatus, SourceTag
+    },
+    primitive::{NewInitializedPool, PeerId, PoolId, PoolLimits},
     sol_bindings::{
         grouped_orders::{AllOrders, OrderWithStorageData, *},
         rpc_orders::TopOfBlockOrder,
-        RawPoolOrder
+        RawPoolOrder, RespendAvoidanceMethod
     }
 };
 use futures_util::{Stream, StreamExt};
 use tokio::sync::oneshot::Sender;
 use tracing::{error, trace};
-use validation::order::{
-    state::{account::user::UserAddress, pools::AngstromPoolsTracker},
-    OrderValidationResults, OrderValidatorHandle
+use validation::{
+    order::{
+        state::{account::user::UserAddress, pools::AngstromPoolsTracker},
+        OrderValidationResults, OrderValidatorHandle
+    },
+    ValidationError
 };
 
 use crate::{
@@ -38,6 +44,9 @@ const SEEN_INVALID_ORDERS_CAPACITY: usize = 10000;
 /// represents the maximum number of blocks that we allow for new orders to not
 /// propagate (again mostly arbitrary)
 const MAX_NEW_ORDER_DELAY_PROPAGATION: u64 = 7000;
+/// mostly arbitrary. Once full, only the accounts that already cancelled can
+/// cancel again until tombstones expire
+const ACCOUNT_CANCELLATIONS_CAPACITY: usize = 100_000;
 
 struct CancelOrderRequest {
     /// The address of the entity requesting the cancellation.
@@ -46,6 +55,14 @@ struct CancelOrderRequest {
     pub valid_until: u64
 }
 
+/// Tombstone of the orders of an account that were cancelled at once.
+struct AccountCancellation {
+    /// standing orders with a lower nonce are cancelled
+    below_nonce: u64,
+    /// unix timestamp until which the tombstone is kept
+    valid_until: u64
+}
+
 pub struct OrderIndexer<V: OrderValidatorHandle> {
     /// order storage
     order_storage:          Arc<OrderStorage>,
@@ -59,10 +76,27 @@ pub struct OrderIndexer<V: OrderValidatorHandle> {
     order_hash_to_peer_id:  HashMap<B256, Vec<PeerId>>,
     /// Used to avoid unnecessary computation on order spam
     seen_invalid_orders:    HashSet<B256>,
+    /// Hashes of the orders being validated, so an order that arrives over
+    /// both the network and rpc is validated once, with when their validation
+    /// started
+    validating:             HashMap<B256, Instant>,
+    /// Amendments being validated, by the hash of the amended order
+    amendments:             HashMap<B256, OrderAmendment>,
+    /// Source tags of the orders being validated, handed to the storage once
+    /// the order is added
+    source_tags:            HashMap<B256, SourceTag>,
     /// Used to protect against late order propagation
     cancelled_orders:       HashMap<B256, CancelOrderRequest>,
+    /// Nonces below which the standing orders of an account, in a pool or in
+    /// all of them, were cancelled at once
+    account_cancellations:  HashMap<(Address, Option<PoolId>), AccountCancellation>,
+    /// Orders dropped from the network's books, with the time until which
+    /// they are refused if they arrive again
+    pruned_orders:          HashMap<B256, u64>,
     /// Order Validator
     validator:              OrderValidator<V>,
+    /// Addresses whose pending orders are validated again with the next block
+    revalidation:           HashSet<Address>,
     /// a mapping of tokens to pool_id
     pool_id_map:            AngstromPoolsTracker,
     /// List of subscribers for order validation result
@@ -86,46 +120,82 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
             order_hash_to_order_id: HashMap::new(),
             order_hash_to_peer_id: HashMap::new(),
             seen_invalid_orders: HashSet::with_capacity(SEEN_INVALID_ORDERS_CAPACITY),
+            validating: HashMap::new(),
+            amendments: HashMap::new(),
+            source_tags: HashMap::new(),
             pool_id_map: angstrom_pools,
             cancelled_orders: HashMap::new(),
+            account_cancellations: HashMap::new(),
+            pruned_orders: HashMap::new(),
             order_validation_subs: HashMap::new(),
             validator: OrderValidator::new(validator),
+            revalidation: HashSet::new(),
             orders_subscriber_tx
         }
     }
 
+    /// The handle to the validator the orders are validated with.
+    pub fn validation_handle(&self) -> &V {
+        self.validator.handle()
+    }
+
+    /// Validates the pending orders of `addresses` again once the next block
+    /// starts, along with the ones of the addresses the block changed. The
+    /// ones no longer valid are dropped then.
+    pub fn revalidate(&mut self, addresses: Vec<Address>) {
+        self.revalidation.extend(addresses);
+    }
+
     pub fn pending_orders_for_address(
         &self,
         address: Address
     ) -> Vec<OrderWithStorageData<AllOrders>> {
-        let mut orders = Vec::new();
-        if let Some(order_ids) = self.address_to_orders.get(&address) {
-            for order_id in order_ids {
-                let order = match order_id.location {
-                    angstrom_types::orders::OrderLocation::Limit => self
-                        .order_storage
-                        .limit_orders
-                        .lock()
-                        .expect("lock poisoned")
-                        .get_order(order_id)
-                        .and_then(|order| order.try_map_inner(|inner| Ok(inner.into())).ok()),
-                    angstrom_types::orders::OrderLocation::Searcher => self
-                        .order_storage
-                        .searcher_orders
-                        .lock()
-                        .expect("lock poisoned")
-                        .get_order(order_id.pool_id, order_id.hash)
-                        .and_then(|order| {
-                            order.try_map_inner(|inner| Ok(AllOrders::TOB(inner))).ok()
-                        })
-                };
+        self.address_to_orders
+            .get(&address)
+            .map(|order_ids| {
+                order_ids
+                    .iter()
+                    .filter_map(|order_id| self.order_by_id(order_id))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 
-                if let Some(order) = order {
-                    orders.push(order);
-                }
-            }
-        }
-        orders
+    fn order_by_id(&self, order_id: &OrderId) -> Option<OrderWithStorageData<AllOrders>> {
+        self.order_storage.get_order(order_id)
+    }
+
+    /// Hashes of all the orders currently indexed by the pool.
+    pub fn order_hashes(&self) -> Vec<B256> {
+        self.order_hash_to_order_id.keys().copied().collect()
+    }
+
+    /// Hashes of all the indexed orders along with their deadlines, in unix
+    /// seconds.
+    pub fn order_deadlines(&self) -> impl Iterator<Item = (B256, Option<u64>)> + '_ {
+        self.order_hash_to_order_id
+            .iter()
+            .map(|(hash, id)| (*hash, id.deadline.map(|deadline| deadline.saturating_to())))
+    }
+
+    /// Returns all the orders we hold that are not part of `known`.
+    pub fn orders_not_in(&self, known: &HashSet<B256>) -> Vec<AllOrders> {
+        self.order_hash_to_order_id
+            .iter()
+            .filter(|(hash, _)| !known.contains(*hash))
+            .filter_map(|(_, order_id)| self.order_by_id(order_id))
+            .map(|order| order.order)
+            .collect()
+    }
+
+    /// The ones of the orders `hashes` we have.
+    pub fn orders_by_hash(&self, hashes: &[B256]) -> Vec<AllOrders> {
+        hashes
+            .iter()
+            .filter_map(|hash| self.order_hash_to_order_id.get(hash))
+            .filter_map(|order_id| self.order_by_id(order_id))
+            .map(|order| order.order)
+            .collect()
     }
 
     pub fn orders_by_pool(
@@ -133,26 +203,32 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
         pool_id: FixedBytes<32>,
         order_location: OrderLocation
     ) -> Vec<AllOrders> {
-        match order_location {
-            OrderLocation::Limit => self
-                .order_storage
-                .limit_orders
-                .lock()
-                .expect("poisoned")
-                .get_all_orders_from_pool(pool_id),
-            OrderLocation::Searcher => self
-                .order_storage
-                .searcher_orders
-                .lock()
-                .expect("poisoned")
-                .get_all_orders_from_pool(pool_id)
-        }
+        self.order_storage.orders_by_pool(pool_id, order_location)
     }
 
     pub fn order_status(&self, order_hash: B256) -> Option<OrderStatus> {
         self.order_storage.fetch_status_of_order(order_hash)
     }
 
+    /// The state of every indexed order, ordered by hash.
+    pub fn order_states(&self) -> OrderStatesSnapshot {
+        let mut orders = self
+            .order_hash_to_order_id
+            .iter()
+            .filter_map(|(order_hash, order_id)| {
+                Some(OrderState {
+                    order_hash: *order_hash,
+                    user:       order_id.address,
+                    pool_id:    order_id.pool_id,
+                    status:     self.order_status(*order_hash)?
+                })
+            })
+            .collect::<Vec<_>>();
+        orders.sort_unstable_by_key(|state| state.order_hash);
+
+        OrderStatesSnapshot { block_number: self.block_number, orders }
+    }
+
     fn is_missing(&self, order_hash: &B256) -> bool {
         !self.order_hash_to_order_id.contains_key(order_hash)
     }
@@ -165,14 +241,37 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
         self.cancelled_orders.contains_key(order_hash)
     }
 
+    fn is_pruned(&self, order_hash: &B256) -> bool {
+        self.pruned_orders.contains_key(order_hash)
+    }
+
+    pub fn subscribe_book(&self) -> tokio::sync::broadcast::Receiver<BookDelta> {
+        self.order_storage.subscribe_book()
+    }
+
+    pub fn book_snapshot(&self, pool_id: &PoolId) -> Option<BookSnapshot> {
+        self.order_storage.book_snapshot(pool_id)
+    }
+
+    /// The last block the pool processed
+    pub fn block_number(&self) -> BlockNumber {
+        self.block_number
+    }
+
+    pub fn update_limits(&self, limits: PoolLimits) {
+        self.order_storage.update_limits(limits);
+    }
+
     pub fn remove_pool(&self, key: PoolId) {
         self.order_storage.remove_pool(key);
     }
 
+    /// Whether an order with the hash is stored, in any pool, or was found
+    /// invalid.
     fn is_duplicate(&self, order_hash: &B256) -> bool {
-        if self.order_hash_to_order_id.contains_key(order_hash) || self.is_seen_invalid(order_hash)
-        {
+        if self.order_storage.contains_order(order_hash) || self.is_seen_invalid(order_hash) {
             trace!(?order_hash, "got duplicate order");
+            self.order_storage.metrics.incr_duplicate_orders();
             return true
         }
 
@@ -188,10 +287,41 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
         self.new_order(None, origin, order, Some(validation_tx))
     }
 
+    /// A new rpc order submitted through the dapp or frontend `source_tag`,
+    /// which the order is attributed to once it is added to the pool.
+    pub fn new_tagged_rpc_order(
+        &mut self,
+        origin: OrderOrigin,
+        order: AllOrders,
+        source_tag: SourceTag,
+        validation_tx: tokio::sync::oneshot::Sender<OrderValidationResults>
+    ) {
+        let hash = order.order_hash();
+        self.order_storage.source_tags().submitted(&source_tag);
+        self.new_rpc_order(origin, order, validation_tx);
+
+        // orders refused before they are validated are never tagged
+        if self.validating.contains_key(&hash) {
+            self.source_tags.insert(hash, source_tag);
+        }
+    }
+
     pub fn new_network_order(&mut self, peer_id: PeerId, origin: OrderOrigin, order: AllOrders) {
         self.new_order(Some(peer_id), origin, order, None)
     }
 
+    pub fn new_rpc_amendment(
+        &mut self,
+        amendment: OrderAmendment,
+        validation_tx: tokio::sync::oneshot::Sender<OrderValidationResults>
+    ) {
+        self.amend_order(None, amendment, Some(validation_tx))
+    }
+
+    pub fn new_network_amendment(&mut self, peer_id: PeerId, amendment: OrderAmendment) {
+        self.amend_order(Some(peer_id), amendment, None)
+    }
+
     pub fn cancel_order(&mut self, request: &angstrom_types::orders::CancelOrderRequest) -> bool {
         // ensure validity
         if !request.is_valid() {
@@ -219,9 +349,9 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
 
             return true
         }
-        let id = self.order_hash_to_order_id.remove(&request.order_id);
+        let id = self.order_hash_to_order_id.get(&request.order_id).copied();
         if let Some(order) = id.and_then(|v| self.order_storage.cancel_order(&v)) {
-            self.order_hash_to_order_id.remove(&order.order_hash());
+            self.untrack_order(&request.order_id);
             self.order_hash_to_peer_id.remove(&order.order_hash());
             self.insert_cancel_request_with_deadline(
                 request.user_address,
@@ -240,6 +370,101 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
         false
     }
 
+    /// Cancels the pending orders `request` covers and keeps it as a tombstone
+    /// of the account, so covered orders arriving later are refused. The
+    /// tombstone is kept until the cancelled orders are past their deadline,
+    /// and at least as long as a late order can still be propagated. Returns
+    /// the hashes of the cancelled orders, None if the request is invalid, not
+    /// newer than the last one of the account, or there is no room left for
+    /// the tombstone of another account.
+    pub fn cancel_all_orders(&mut self, request: &CancelAllOrdersRequest) -> Option<Vec<B256>> {
+        if !request.is_valid() {
+            return None
+        }
+
+        let key = (request.user_address, request.pool_id);
+        if self
+            .account_cancellations
+            .get(&key)
+            .is_some_and(|tombstone| tombstone.below_nonce >= request.nonce)
+        {
+            return None
+        }
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        if self.account_cancellations.len() >= ACCOUNT_CANCELLATIONS_CAPACITY
+            && !self.account_cancellations.contains_key(&key)
+        {
+            self.account_cancellations
+                .retain(|_, tombstone| tombstone.valid_until >= now);
+            if self.account_cancellations.len() >= ACCOUNT_CANCELLATIONS_CAPACITY {
+                tracing::warn!(
+                    user = ?request.user_address,
+                    "no room left for account cancellations, refusing it"
+                );
+                return None
+            }
+        }
+
+        let covered = self
+            .address_to_orders
+            .get(&request.user_address)
+            .into_iter()
+            .flatten()
+            .filter(|id| request.covers(id))
+            .copied()
+            .collect::<Vec<_>>();
+
+        // a tombstone that is replaced still has to cover the orders it cancelled
+        let mut valid_until = self
+            .account_cancellations
+            .get(&key)
+            .map_or(0, |tombstone| tombstone.valid_until)
+            .max(now + MAX_NEW_ORDER_DELAY_PROPAGATION * ETH_BLOCK_TIME.as_secs());
+        let mut cancelled = vec![];
+        for id in covered {
+            let Some(order) = self.order_storage.cancel_order(&id) else { continue };
+            self.untrack_order(&id.hash);
+            self.order_hash_to_peer_id.remove(&id.hash);
+            self.insert_cancel_request_with_deadline(
+                request.user_address,
+                &id.hash,
+                order.deadline()
+            );
+            self.notify_order_subscribers(PoolManagerUpdate::CancelledOrder {
+                order_hash: id.hash,
+                user:       order.from(),
+                pool_id:    order.pool_id
+            });
+            cancelled.push(id.hash);
+            valid_until = valid_until.max(order.deadline().map_or(0, |d| d.saturating_to()));
+        }
+        self.account_cancellations
+            .insert(key, AccountCancellation { below_nonce: request.nonce, valid_until });
+
+        Some(cancelled)
+    }
+
+    /// Whether a standing order of `from` with the given nonce was cancelled
+    /// along with the other orders of the account, in `pool_id` or in all
+    /// pools.
+    fn is_account_cancelled(
+        &self,
+        from: Address,
+        pool_id: Option<PoolId>,
+        reuse_avoidance: RespendAvoidanceMethod
+    ) -> bool {
+        let RespendAvoidanceMethod::Nonce(nonce) = reuse_avoidance else { return false };
+
+        [None, pool_id]
+            .into_iter()
+            .filter_map(|pool| self.account_cancellations.get(&(from, pool)))
+            .any(|tombstone| nonce < tombstone.below_nonce)
+    }
+
     fn insert_cancel_request_with_deadline(
         &mut self,
         from: Address,
@@ -280,9 +505,37 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
                 .push(validation_tx);
         }
 
+        if self.is_pruned(&hash) {
+            trace!(?hash, "order was pruned from the book");
+            self.notify_validation_subscribers(
+                &hash,
+                OrderValidationResults::Invalid(hash, ValidationError::Expired)
+            );
+            return
+        }
+
+        // flash and top of block orders can only be settled in the block they are for
+        if order
+            .flash_block()
+            .is_some_and(|block| block <= self.block_number)
+        {
+            trace!(?hash, "order is for a block that passed");
+            self.notify_validation_subscribers(
+                &hash,
+                OrderValidationResults::Invalid(hash, ValidationError::Expired)
+            );
+            return
+        }
+
         let cancel_request = self.cancelled_orders.get(&hash);
-        let is_valid_cancel_request =
-            cancel_request.is_some() && cancel_request.unwrap().from == order.from();
+        let is_valid_cancel_request = (cancel_request.is_some()
+            && cancel_request.unwrap().from == order.from())
+            || self.is_account_cancelled(
+                order.from(),
+                self.pool_id_map
+                    .get_poolid(order.token_in(), order.token_out()),
+                order.respend_avoidance_strategy()
+            );
         // network spammers will get penalized only once
         if self.is_duplicate(&hash) || is_valid_cancel_request {
             if is_valid_cancel_request {
@@ -300,7 +553,12 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
                 }
                 self.order_storage.log_cancel_order(&order);
             }
-            self.notify_validation_subscribers(&hash, OrderValidationResults::Invalid(hash));
+            let error = if is_valid_cancel_request {
+                ValidationError::Cancelled
+            } else {
+                ValidationError::DuplicateOrder
+            };
+            self.notify_validation_subscribers(&hash, OrderValidationResults::Invalid(hash, error));
             return
         }
 
@@ -312,9 +570,106 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
                 .push(peer);
         }
 
+        // the same order arriving again while it is validated, e.g from a peer
+        // and over rpc, shares the result of the first validation
+        if self.validating.contains_key(&hash) {
+            trace!(?hash, "order is already being validated");
+            self.order_storage.metrics.incr_duplicate_orders();
+            return
+        }
+        self.validating.insert(hash, Instant::now());
+
         self.validator.validate_order(origin, order);
     }
 
+    /// Validates the amended order, which replaces the original once it is
+    /// valid. Peers can send amendments of orders we never saw, the amended
+    /// order is then just a new order.
+    fn amend_order(
+        &mut self,
+        peer_id: Option<PeerId>,
+        amendment: OrderAmendment,
+        validation_res_sub: Option<Sender<OrderValidationResults>>
+    ) {
+        let original = self
+            .order_hash_to_order_id
+            .get(&amendment.order_id)
+            .and_then(|id| self.order_by_id(id));
+        if original.is_none() && peer_id.is_some() {
+            self.new_order(
+                peer_id,
+                OrderOrigin::External,
+                AllOrders::Standing(amendment.order),
+                None
+            );
+            return
+        }
+
+        let hash = amendment.order.order_hash();
+        if let Some(validation_tx) = validation_res_sub {
+            self.order_validation_subs
+                .entry(hash)
+                .or_default()
+                .push(validation_tx);
+        }
+
+        let amends = original.is_some_and(|original| {
+            matches!(&original.order, AllOrders::Standing(order) if amendment.amends(order))
+        });
+        if !amends {
+            self.notify_validation_subscribers(
+                &hash,
+                OrderValidationResults::Invalid(hash, ValidationError::InvalidAmendment)
+            );
+            return
+        }
+
+        if self.is_duplicate(&hash) {
+            self.notify_validation_subscribers(
+                &hash,
+                OrderValidationResults::Invalid(hash, ValidationError::DuplicateOrder)
+            );
+            return
+        }
+
+        if let Some(peer) = peer_id {
+            self.order_hash_to_peer_id
+                .entry(hash)
+                .or_default()
+                .push(peer);
+        }
+
+        if self.validating.contains_key(&hash) {
+            trace!(?hash, "amendment is already being validated");
+            self.order_storage.metrics.incr_duplicate_orders();
+            return
+        }
+        self.validating.insert(hash, Instant::now());
+
+        let replaces = amendment.order_id;
+        let order = amendment.order.clone();
+        self.amendments.insert(hash, amendment);
+        self.validator
+            .validate_amendment(OrderOrigin::External, order, replaces);
+    }
+
+    /// Takes the original of a valid amendment out of the pool. None if it was
+    /// filled, cancelled or is settling in the meantime.
+    fn take_amended_order(&mut self, original: &B256) -> Option<OrderWithStorageData<AllOrders>> {
+        let id = *self.order_hash_to_order_id.get(original)?;
+        let order = self.order_storage.take_amended_order(&id)?;
+        self.untrack_order(original);
+        // a late copy of the original must not replace the amendment again
+        self.seen_invalid_orders.insert(*original);
+        self.notify_order_subscribers(PoolManagerUpdate::CancelledOrder {
+            order_hash: *original,
+            user:       order.from(),
+            pool_id:    order.pool_id
+        });
+
+        Some(order)
+    }
+
     /// used to remove orders that expire before the next ethereum block
     fn remove_expired_orders(&mut self, block_number: BlockNumber) -> Vec<B256> {
         self.block_number = block_number;
@@ -330,27 +685,75 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
                         .unwrap_or_default()
             })
             .map(|(k, _)| *k)
+            // pinned orders are swept once their round completes
+            .filter(|hash| !self.order_storage.is_pinned(hash))
             .collect::<Vec<_>>();
 
-        // TODO: notify rpc of dead orders
-        let _expired_orders = hashes
+        self.remove_orders(&hashes);
+
+        hashes
+    }
+
+    /// Drops orders that outlived their time to live or were dropped by the
+    /// rest of the network, and refuses them until their deadline if they
+    /// arrive again. Returns the hashes of the orders that were in the book.
+    /// Orders pinned by the current consensus round are kept.
+    pub fn prune_orders(&mut self, hashes: &[B256]) -> Vec<B256> {
+        // orders without a deadline are refused for as long as a late cancellation is
+        // kept around
+        let refused_until = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + MAX_NEW_ORDER_DELAY_PROPAGATION * ETH_BLOCK_TIME.as_secs();
+
+        let pruned = hashes
             .iter()
-            // remove hash from id
-            .map(|hash| self.order_hash_to_order_id.remove(hash).unwrap())
-            .inspect(|order_id| {
-                self.address_to_orders
-                    .values_mut()
-                    // remove from address to orders
-                    .for_each(|v| v.retain(|o| o != order_id));
+            .filter(|hash| !self.order_storage.is_pinned(hash))
+            .filter_map(|hash| {
+                let deadline = self.order_hash_to_order_id.get(hash)?.deadline;
+                Some((*hash, deadline.map_or(refused_until, |deadline| deadline.saturating_to())))
             })
+            .collect::<Vec<_>>();
+        self.pruned_orders.extend(pruned.iter().copied());
+
+        let hashes = pruned.into_iter().map(|(hash, _)| hash).collect::<Vec<_>>();
+        self.remove_orders(&hashes);
+
+        hashes
+    }
+
+    /// Removes the orders from the book and tells the subscribers they expired.
+    fn remove_orders(&mut self, hashes: &[B256]) {
+        let expired_ids = hashes
+            .iter()
+            .filter_map(|hash| self.untrack_order(hash))
+            .collect::<Vec<_>>();
+
+        let expired_orders = expired_ids
+            .into_iter()
             // remove from all underlying pools
             .filter_map(|id| match id.location {
                 OrderLocation::Searcher => self.order_storage.remove_searcher_order(&id),
                 OrderLocation::Limit => self.order_storage.remove_limit_order(&id)
             })
             .collect::<Vec<_>>();
+        for order in expired_orders {
+            self.notify_order_subscribers(PoolManagerUpdate::ExpiredOrder(order));
+        }
+    }
 
-        hashes
+    /// Stops indexing the order, returning its id if it was indexed.
+    fn untrack_order(&mut self, hash: &B256) -> Option<OrderId> {
+        let id = self.order_hash_to_order_id.remove(hash)?;
+        if let Some(ids) = self.address_to_orders.get_mut(&id.address) {
+            ids.retain(|o| o != &id);
+            if ids.is_empty() {
+                self.address_to_orders.remove(&id.address);
+            }
+        }
+
+        Some(id)
     }
 
     fn eoa_state_change(&mut self, eoas: &[Address]) {
@@ -358,6 +761,21 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
             .filter_map(|eoa| self.address_to_orders.remove(eoa))
             .for_each(|order_ids| {
                 order_ids.into_iter().for_each(|id| {
+                    // pinned orders stay in the book, they are revalidated with the next block
+                    if self.order_storage.is_pinned(&id.hash) {
+                        self.address_to_orders
+                            .entry(id.address)
+                            .or_default()
+                            .push(id);
+                        self.revalidation.insert(id.address);
+                        return
+                    }
+
+                    // the order is indexed again once it is revalidated
+                    self.order_hash_to_order_id.remove(&id.hash);
+                    if let Some(source_tag) = self.order_storage.source_tags().remove(&id.hash) {
+                        self.source_tags.insert(id.hash, source_tag);
+                    }
                     let Some(order) = (match id.location {
                         OrderLocation::Limit => self.order_storage.remove_limit_order(&id),
                         OrderLocation::Searcher => self.order_storage.remove_searcher_order(&id)
@@ -392,9 +810,14 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
             return
         }
 
-        let filled_orders = orders
+        let source_tags = self.order_storage.source_tags();
+        let filled_ids = orders
             .iter()
-            .filter_map(|hash| self.order_hash_to_order_id.remove(hash))
+            .inspect(|hash| source_tags.filled(hash))
+            .filter_map(|hash| self.untrack_order(hash))
+            .collect::<Vec<_>>();
+        let filled_orders = filled_ids
+            .into_iter()
             .filter_map(|order_id| match order_id.location {
                 OrderLocation::Limit => self.order_storage.remove_limit_order(&order_id),
                 OrderLocation::Searcher => self.order_storage.remove_searcher_order(&order_id)
@@ -421,19 +844,33 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
         self.order_storage.park_orders(order_info);
     }
 
+    fn finish_validating(&mut self, hash: &B256) {
+        if let Some(started) = self.validating.remove(hash) {
+            self.order_storage
+                .record_validation_latency(started.elapsed());
+        }
+    }
+
     fn handle_validated_order(
         &mut self,
         res: OrderValidationResults
     ) -> eyre::Result<PoolInnerEvent> {
         match res {
-            OrderValidationResults::Valid(valid) => {
+            OrderValidationResults::Valid(mut valid) => {
                 let hash = valid.order_hash();
+                self.finish_validating(&hash);
+                let amendment = self.amendments.remove(&hash);
+                let mut source_tag = self.source_tags.remove(&hash);
 
                 // what about the deadline?
                 if valid.valid_block != self.block_number {
+                    self.notify_order_subscribers(PoolManagerUpdate::RejectedOrder {
+                        order_hash: hash,
+                        error:      ValidationError::StaleBlock
+                    });
                     self.notify_validation_subscribers(
                         &hash,
-                        OrderValidationResults::Invalid(hash)
+                        OrderValidationResults::Invalid(hash, ValidationError::StaleBlock)
                     );
 
                     self.seen_invalid_orders.insert(hash);
@@ -441,6 +878,57 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
                     return Ok(PoolInnerEvent::BadOrderMessages(peers))
                 }
 
+                // the account cancelled its orders while this one was validated
+                if self.is_account_cancelled(
+                    valid.from(),
+                    Some(valid.pool_id),
+                    valid.order_id.reuse_avoidance
+                ) {
+                    self.order_hash_to_peer_id.remove(&hash);
+                    self.insert_cancel_request_with_deadline(valid.from(), &hash, valid.deadline());
+                    self.notify_validation_subscribers(
+                        &hash,
+                        OrderValidationResults::Invalid(hash, ValidationError::Cancelled)
+                    );
+                    return Ok(PoolInnerEvent::None)
+                }
+
+                // orders revalidated after a state change can still race a resubmission.
+                // Only the first result is indexed.
+                if self.order_storage.contains_order(&hash) {
+                    self.order_hash_to_peer_id.remove(&hash);
+                    self.notify_validation_subscribers(&hash, OrderValidationResults::Valid(valid));
+                    return Ok(PoolInnerEvent::None)
+                }
+
+                if let Some(amendment) = &amendment {
+                    // the amended order keeps the source of the original
+                    source_tag = source_tag
+                        .or_else(|| self.order_storage.source_tags().get(&amendment.order_id));
+                    let Some(original) = self.take_amended_order(&amendment.order_id) else {
+                        self.order_hash_to_peer_id.remove(&hash);
+                        self.notify_validation_subscribers(
+                            &hash,
+                            OrderValidationResults::Invalid(
+                                hash,
+                                ValidationError::InvalidAmendment
+                            )
+                        );
+                        return Ok(PoolInnerEvent::None)
+                    };
+
+                    // a smaller order keeps its place in the book
+                    if let AllOrders::Standing(order) = &original.order {
+                        if !amendment.is_increase(order) {
+                            valid.priority_data.validated_at = original.priority_data.validated_at;
+                        }
+                    }
+                }
+
+                // tagged before it is announced, so subscribers can look the tag up
+                if let Some(source_tag) = source_tag {
+                    self.order_storage.source_tags().insert(hash, source_tag);
+                }
                 self.notify_order_subscribers(PoolManagerUpdate::NewOrder(valid.clone()));
                 self.notify_validation_subscribers(
                     &hash,
@@ -452,12 +940,22 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
                 self.park_transactions(&valid.invalidates);
                 self.insert_order(valid)?;
 
-                Ok(PoolInnerEvent::Propagation(to_propagate))
+                Ok(match amendment {
+                    Some(amendment) => PoolInnerEvent::AmendmentPropagation(amendment),
+                    None => PoolInnerEvent::Propagation(to_propagate)
+                })
             }
-            OrderValidationResults::Invalid(bad_hash) => {
+            OrderValidationResults::Invalid(bad_hash, error) => {
+                self.finish_validating(&bad_hash);
+                self.amendments.remove(&bad_hash);
+                self.source_tags.remove(&bad_hash);
+                self.notify_order_subscribers(PoolManagerUpdate::RejectedOrder {
+                    order_hash: bad_hash,
+                    error:      error.clone()
+                });
                 self.notify_validation_subscribers(
                     &bad_hash,
-                    OrderValidationResults::Invalid(bad_hash)
+                    OrderValidationResults::Invalid(bad_hash, error)
                 );
                 self.seen_invalid_orders.insert(bad_hash);
                 let peers = self
@@ -536,9 +1034,12 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
         &mut self,
         block_number: BlockNumber,
         completed_orders: Vec<B256>,
-        address_changes: Vec<Address>
+        mut address_changes: Vec<Address>
     ) {
         tracing::info!(%block_number, "starting transition to new block processing");
+        self.revalidation
+            .retain(|address| !address_changes.contains(address));
+        address_changes.extend(self.revalidation.drain());
         self.validator
             .on_new_block(block_number, completed_orders, address_changes);
     }
@@ -562,6 +1063,9 @@ impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
             .as_secs();
         self.cancelled_orders
             .retain(|_, request| request.valid_until >= time_now);
+        self.pruned_orders.retain(|_, until| *until >= time_now);
+        self.account_cancellations
+            .retain(|_, tombstone| tombstone.valid_until >= time_now);
 
         self.validator.notify_validation_on_changes(
             block_number,
@@ -607,8 +1111,99 @@ where
     }
 }
 
+/// Hooks used by the fuzz targets to drive the indexer without a pool manager
+/// and to check its internal state between steps.
+#[cfg(any(test, feature = "fuzzing"))]
+impl<V: OrderValidatorHandle<Order = AllOrders>> OrderIndexer<V> {
+    /// Whether a block transition is still in progress. A new block can only
+    /// be started once the previous one is done.
+    pub fn is_transitioning(&self) -> bool {
+        !self.validator.is_regular_processing()
+    }
+
+    /// Checks the invariants that have to hold between any two events.
+    pub fn check_invariants(&self) -> Result<(), IndexerInvariantError> {
+        if let Some((hash, id)) = self
+            .order_hash_to_order_id
+            .iter()
+            .find(|(hash, id)| **hash != id.hash)
+        {
+            return Err(IndexerInvariantError::MismatchedId(*hash, id.hash))
+        }
+
+        let mut tracked = HashSet::new();
+        for id in self
+            .address_to_orders
+            .values()
+            .flatten()
+            .filter(|id| self.order_hash_to_order_id.contains_key(&id.hash))
+        {
+            if !tracked.insert(id.hash) {
+                return Err(IndexerInvariantError::DuplicateUserOrder(id.hash))
+            }
+        }
+
+        let mut stored = HashSet::new();
+        for hash in self.order_storage.order_hashes() {
+            if !stored.insert(hash) {
+                return Err(IndexerInvariantError::DuplicateStoredOrder(hash))
+            }
+            if !self.order_hash_to_order_id.contains_key(&hash) {
+                return Err(IndexerInvariantError::UnindexedOrder(hash))
+            }
+        }
+
+        for (pool, tracked, held) in self.order_storage.size_accounting() {
+            if tracked != held {
+                return Err(IndexerInvariantError::SizeMismatch { pool, tracked, held })
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that no expired order is still indexed. Only holds right after a
+    /// block transition completes.
+    pub fn check_no_expired_orders(&self) -> Result<(), IndexerInvariantError> {
+        let now = U256::from(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        );
+
+        self.order_hash_to_order_id
+            .values()
+            .find(|id| {
+                id.deadline.is_some_and(|deadline| deadline <= now)
+                    || id
+                        .flash_block
+                        .is_some_and(|block| block != self.block_number + 1)
+            })
+            .map_or(Ok(()), |id| Err(IndexerInvariantError::ExpiredOrder(id.hash)))
+    }
+}
+
+#[cfg(any(test, feature = "fuzzing"))]
+#[derive(Debug, thiserror::Error)]
+pub enum IndexerInvariantError {
+    #[error("order {0} is indexed under the id of order {1}")]
+    MismatchedId(B256, B256),
+    #[error("order {0} is tracked more than once for its user")]
+    DuplicateUserOrder(B256),
+    #[error("order {0} is stored more than once")]
+    DuplicateStoredOrder(B256),
+    #[error("order {0} is stored but not indexed")]
+    UnindexedOrder(B256),
+    #[error("order {0} is still active after it expired")]
+    ExpiredOrder(B256),
+    #[error("{pool} pool size tracker counts {tracked} but holds {held}")]
+    SizeMismatch { pool: &'static str, tracked: usize, held: usize }
+}
+
 pub enum PoolInnerEvent {
     Propagation(AllOrders),
+    AmendmentPropagation(OrderAmendment),
     BadOrderMessages(Vec<PeerId>),
     HasTransitionedToNewBlock(u64),
     None
@@ -635,7 +1230,7 @@ mod tests {
     use angstrom_types::{
         contract_bindings::angstrom::Angstrom::PoolKey,
         contract_payloads::angstrom::AngstromPoolConfigStore,
-        orders::OrderId,
+        orders::{OrderId, OrderPriorityData},
         primitive::AngstromSigner,
         sol_bindings::{grouped_orders::GroupedVanillaOrder, RespendAvoidanceMethod}
     };
@@ -790,7 +1385,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_block_transitions() {
+    async fn source_tags_follow_the_order() {
         let mut indexer = setup_test_indexer();
         let from = Address::random();
         let pool_key = PoolKey {
@@ -799,33 +1394,24 @@ mod tests {
             ..Default::default()
         };
         let pool_id = PoolId::from(pool_key.clone());
-        let validity = OrderValidity {
-            valid_until: Some(U256::from(
-                SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs()
-                    + 3600 // Valid for 1 hour
-            )),
-            flash_block: None,
-            is_standing: true
-        };
         indexer.new_pool(NewInitializedPool {
             currency_out: pool_key.currency0,
             currency_in:  pool_key.currency1,
             id:           pool_id
         });
+        let tag = SourceTag::new("dapp").unwrap();
+        let source_tags = indexer.order_storage.source_tags();
 
-        let order = create_test_order(from, pool_key.clone(), Some(validity), None);
+        let order = create_test_order(from, pool_key.clone(), None, None);
         let order_hash = order.order_hash();
-
-        // Submit and validate order
         let (tx, _) = tokio::sync::oneshot::channel();
-        indexer.new_rpc_order(OrderOrigin::Local, order.clone(), tx);
+        indexer.new_tagged_rpc_order(OrderOrigin::External, order.clone(), tag.clone(), tx);
+        // only tagged once it is added
+        assert!(source_tags.is_empty());
 
         indexer
             .handle_validated_order(OrderValidationResults::Valid(OrderWithStorageData {
-                order: order.clone(),
+                order,
                 order_id: OrderId {
                     address: from,
                     reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
@@ -837,12 +1423,85 @@ mod tests {
                 },
                 valid_block: 1,
                 pool_id,
-                is_bid: true,
                 is_currently_valid: true,
                 is_valid: true,
-                priority_data: Default::default(),
-                invalidates: vec![],
-                tob_reward: U256::ZERO
+                ..Default::default()
+            }))
+            .unwrap();
+        assert_eq!(source_tags.get(&order_hash), Some(tag.clone()));
+
+        indexer.filled_orders(1, &[order_hash]);
+        assert_eq!(source_tags.get(&order_hash), None);
+
+        // invalid orders are never tagged
+        let order = create_test_order(from, pool_key, None, None);
+        let invalid_hash = order.order_hash();
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_tagged_rpc_order(OrderOrigin::External, order, tag, tx);
+        indexer
+            .handle_validated_order(OrderValidationResults::Invalid(
+                invalid_hash,
+                ValidationError::DuplicateOrder
+            ))
+            .unwrap();
+        assert!(source_tags.is_empty());
+        assert!(indexer.source_tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_block_transitions() {
+        let mut indexer = setup_test_indexer();
+        let from = Address::random();
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key.clone());
+        let validity = OrderValidity {
+            valid_until: Some(U256::from(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()
+                    + 3600 // Valid for 1 hour
+            )),
+            flash_block: None,
+            is_standing: true
+        };
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+
+        let order = create_test_order(from, pool_key.clone(), Some(validity), None);
+        let order_hash = order.order_hash();
+
+        // Submit and validate order
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, order.clone(), tx);
+
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(OrderWithStorageData {
+                order: order.clone(),
+                order_id: OrderId {
+                    address: from,
+                    reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                    hash: order_hash,
+                    pool_id,
+                    location: OrderLocation::Limit,
+                    deadline: None,
+                    flash_block: None
+                },
+                valid_block: 1,
+                pool_id,
+                is_bid: true,
+                is_currently_valid: true,
+                is_valid: true,
+                priority_data: Default::default(),
+                invalidates: vec![],
+                tob_reward: U256::ZERO
             }))
             .unwrap();
 
@@ -945,7 +1604,10 @@ mod tests {
         indexer.new_rpc_order(OrderOrigin::Local, order.clone(), tx);
 
         indexer
-            .handle_validated_order(OrderValidationResults::Invalid(order_hash))
+            .handle_validated_order(OrderValidationResults::Invalid(
+                order_hash,
+                ValidationError::InvalidSignature
+            ))
             .unwrap();
 
         // Verify order was marked as invalid
@@ -953,7 +1615,10 @@ mod tests {
 
         // Verify validation result
         match rx.await {
-            Ok(OrderValidationResults::Invalid(hash)) => assert_eq!(hash, order_hash),
+            Ok(OrderValidationResults::Invalid(hash, error)) => {
+                assert_eq!(hash, order_hash);
+                assert_eq!(error, ValidationError::InvalidSignature);
+            }
             _ => panic!("Expected invalid order result")
         }
     }
@@ -1151,6 +1816,245 @@ mod tests {
         assert!(!indexer.order_hash_to_order_id.contains_key(&order_hash));
     }
 
+    #[tokio::test]
+    async fn test_cancel_all_orders() {
+        let mut indexer = setup_test_indexer();
+
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key.clone());
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let signer = AngstromSigner::random();
+        let from = signer.address();
+
+        let order = create_test_order(from, pool_key, None, Some(signer.clone()));
+        let order_hash = order.order_hash();
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(OrderWithStorageData {
+                order: order.clone(),
+                order_id: OrderId {
+                    address: from,
+                    reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                    hash: order_hash,
+                    pool_id,
+                    location: OrderLocation::Limit,
+                    deadline: None,
+                    flash_block: None
+                },
+                valid_block: 1,
+                pool_id,
+                is_bid: true,
+                is_currently_valid: true,
+                is_valid: true,
+                priority_data: Default::default(),
+                invalidates: vec![],
+                tob_reward: U256::ZERO
+            }))
+            .unwrap();
+
+        let hash = CancelAllOrdersRequest::signing_hash(from, None, 2);
+        let request = CancelAllOrdersRequest {
+            user_address: from,
+            pool_id:      None,
+            nonce:        2,
+            signature:    signer.sign_hash_sync(&hash).unwrap()
+        };
+
+        assert_eq!(indexer.cancel_all_orders(&request), Some(vec![order_hash]));
+        assert!(indexer.cancelled_orders.contains_key(&order_hash));
+        assert!(!indexer.address_to_orders.contains_key(&from));
+        // replaying it does nothing
+        assert_eq!(indexer.cancel_all_orders(&request), None);
+
+        // an order it covers that is still being gossiped is refused
+        let late_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let late = create_test_order(
+            from,
+            late_key,
+            Some(OrderValidity { is_standing: true, ..Default::default() }),
+            Some(signer)
+        );
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, late, tx);
+        assert!(matches!(
+            rx.await,
+            Ok(OrderValidationResults::Invalid(_, ValidationError::Cancelled))
+        ));
+    }
+
+    #[tokio::test]
+    async fn full_account_cancellations_refuse_new_accounts_until_they_expire() {
+        let mut indexer = setup_test_indexer();
+        let request = |signer: &AngstromSigner, nonce| {
+            let hash = CancelAllOrdersRequest::signing_hash(signer.address(), None, nonce);
+            CancelAllOrdersRequest {
+                user_address: signer.address(),
+                pool_id: None,
+                nonce,
+                signature: signer.sign_hash_sync(&hash).unwrap()
+            }
+        };
+        let (known, unknown) = (AngstromSigner::random(), AngstromSigner::random());
+        assert_eq!(indexer.cancel_all_orders(&request(&known, 1)), Some(vec![]));
+
+        let tombstones = (1..ACCOUNT_CANCELLATIONS_CAPACITY).map(|i| {
+            let account = Address::from_word(B256::from(U256::from(i)));
+            ((account, None), AccountCancellation { below_nonce: 1, valid_until: u64::MAX })
+        });
+        indexer.account_cancellations.extend(tombstones);
+
+        assert_eq!(indexer.cancel_all_orders(&request(&unknown, 1)), None);
+        assert_eq!(indexer.cancel_all_orders(&request(&known, 2)), Some(vec![]));
+        assert_eq!(indexer.account_cancellations.len(), ACCOUNT_CANCELLATIONS_CAPACITY);
+
+        // expired tombstones make room
+        indexer
+            .account_cancellations
+            .values_mut()
+            .for_each(|tombstone| tombstone.valid_until = 0);
+        assert_eq!(indexer.cancel_all_orders(&request(&unknown, 1)), Some(vec![]));
+        assert_eq!(indexer.account_cancellations.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn pruned_order_is_refused_when_it_comes_back() {
+        let mut indexer = setup_test_indexer();
+
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key.clone());
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let from = Address::random();
+        let order = create_test_order(from, pool_key, None, None);
+        let order_hash = order.order_hash();
+
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(OrderWithStorageData {
+                order: order.clone(),
+                order_id: OrderId {
+                    address: from,
+                    reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                    hash: order_hash,
+                    pool_id,
+                    location: OrderLocation::Limit,
+                    deadline: None,
+                    flash_block: None
+                },
+                valid_block: 1,
+                pool_id,
+                is_bid: true,
+                is_currently_valid: true,
+                is_valid: true,
+                priority_data: Default::default(),
+                invalidates: vec![],
+                tob_reward: U256::ZERO
+            }))
+            .unwrap();
+
+        assert_eq!(indexer.prune_orders(&[order_hash, B256::random()]), vec![order_hash]);
+        assert!(!indexer.order_hash_to_order_id.contains_key(&order_hash));
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, order, tx);
+        assert!(matches!(
+            rx.await,
+            Ok(OrderValidationResults::Invalid(_, ValidationError::Expired))
+        ));
+    }
+
+    #[tokio::test]
+    async fn pinned_orders_are_kept_until_released() {
+        let mut indexer = setup_test_indexer();
+
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key.clone());
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let from = Address::random();
+        let order = create_test_order(from, pool_key, None, None);
+        let order_hash = order.order_hash();
+
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(OrderWithStorageData {
+                order: order.clone(),
+                order_id: OrderId {
+                    address: from,
+                    reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                    hash: order_hash,
+                    pool_id,
+                    location: OrderLocation::Limit,
+                    deadline: None,
+                    flash_block: None
+                },
+                valid_block: 1,
+                pool_id,
+                is_bid: true,
+                is_currently_valid: true,
+                is_valid: true,
+                priority_data: Default::default(),
+                invalidates: vec![],
+                tob_reward: U256::ZERO
+            }))
+            .unwrap();
+
+        indexer.order_storage.pin_orders([order_hash]);
+        assert!(indexer.prune_orders(&[order_hash]).is_empty());
+        indexer.eoa_state_change(&[from]);
+        assert!(indexer.order_storage.contains_order(&order_hash));
+        assert!(indexer.address_to_orders.contains_key(&from));
+        assert!(indexer.revalidation.contains(&from));
+
+        indexer.order_storage.release_pins();
+        assert_eq!(indexer.prune_orders(&[order_hash]), vec![order_hash]);
+        assert!(!indexer.order_storage.contains_order(&order_hash));
+    }
+
+    #[tokio::test]
+    async fn test_orders_for_past_blocks_are_rejected() {
+        let mut indexer = setup_test_indexer();
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+
+        let validity = OrderValidity { flash_block: Some(1), ..Default::default() };
+        let order = create_test_order(Address::random(), pool_key, Some(validity), None);
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, order, tx);
+        assert!(matches!(
+            rx.await,
+            Ok(OrderValidationResults::Invalid(_, ValidationError::Expired))
+        ));
+        assert!(indexer.validating.is_empty());
+    }
+
     #[tokio::test]
     async fn test_duplicate_order_rejection() {
         let mut indexer = setup_test_indexer();
@@ -1204,8 +2108,300 @@ mod tests {
 
         // The duplicate order should be rejected
         match rx2.await {
-            Ok(OrderValidationResults::Invalid(hash)) => assert_eq!(hash, order_hash),
+            Ok(OrderValidationResults::Invalid(hash, error)) => {
+                assert_eq!(hash, order_hash);
+                assert_eq!(error, ValidationError::DuplicateOrder);
+            }
             _ => panic!("Expected invalid order result")
         }
     }
+
+    #[tokio::test]
+    async fn network_and_rpc_submissions_share_one_validation() {
+        let mut indexer = setup_test_indexer();
+        let from = Address::random();
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key.clone());
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let order = create_test_order(from, pool_key, None, None);
+        let order_hash = order.order_hash();
+
+        indexer.new_network_order(PeerId::random(), OrderOrigin::External, order.clone());
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, order.clone(), tx);
+        assert_eq!(indexer.validating.len(), 1);
+
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(OrderWithStorageData {
+                order,
+                order_id: OrderId {
+                    address: from,
+                    reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                    hash: order_hash,
+                    pool_id,
+                    location: OrderLocation::Limit,
+                    deadline: None,
+                    flash_block: None
+                },
+                valid_block: 1,
+                pool_id,
+                is_bid: true,
+                is_currently_valid: true,
+                is_valid: true,
+                priority_data: Default::default(),
+                invalidates: vec![],
+                tob_reward: U256::ZERO
+            }))
+            .unwrap();
+
+        // the rpc submission gets the result of the network one's validation
+        assert!(matches!(rx.await, Ok(OrderValidationResults::Valid(_))));
+        assert!(indexer.validating.is_empty());
+        assert!(indexer.order_storage.contains_order(&order_hash));
+    }
+
+    #[tokio::test]
+    async fn test_orders_not_in() {
+        let mut indexer = setup_test_indexer();
+        let s = AngstromSigner::random();
+        let from = s.address();
+
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key.clone());
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let validity = OrderValidity {
+            valid_until: Some(U256::from(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()
+                    + 3600
+            )),
+            flash_block: None,
+            is_standing: true
+        };
+        let order = create_test_order(from, pool_key, Some(validity), Some(s));
+        let order_hash = order.order_hash();
+
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_order(OrderOrigin::Local, order.clone(), tx);
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(OrderWithStorageData {
+                order: order.clone(),
+                order_id: OrderId {
+                    address: from,
+                    reuse_avoidance: RespendAvoidanceMethod::Nonce(1),
+                    hash: order_hash,
+                    pool_id,
+                    location: OrderLocation::Limit,
+                    deadline: None,
+                    flash_block: None
+                },
+                valid_block: 1,
+                pool_id,
+                is_bid: true,
+                is_currently_valid: true,
+                is_valid: true,
+                priority_data: Default::default(),
+                invalidates: vec![],
+                tob_reward: U256::ZERO
+            }))
+            .unwrap();
+
+        assert_eq!(indexer.order_hashes(), vec![order_hash]);
+        assert_eq!(indexer.orders_not_in(&HashSet::new()), vec![order]);
+        assert!(indexer
+            .orders_not_in(&HashSet::from([order_hash]))
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_order_validated_twice_is_indexed_once() {
+        let mut indexer = setup_test_indexer();
+        let s = AngstromSigner::random();
+        let from = s.address();
+
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key.clone());
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let order = create_test_order(from, pool_key, None, Some(s));
+        let order_hash = order.order_hash();
+        let valid = OrderWithStorageData {
+            order_id: OrderId::from_all_orders(&order, pool_id),
+            order,
+            valid_block: 1,
+            pool_id,
+            is_bid: true,
+            is_currently_valid: true,
+            is_valid: true,
+            priority_data: Default::default(),
+            invalidates: vec![],
+            tob_reward: U256::ZERO
+        };
+
+        // the same order submitted twice before its first validation completes
+        for _ in 0..2 {
+            indexer
+                .handle_validated_order(OrderValidationResults::Valid(valid.clone()))
+                .unwrap();
+            indexer.check_invariants().unwrap();
+        }
+        assert_eq!(indexer.pending_orders_for_address(from).len(), 1);
+        assert_eq!(
+            indexer.order_states().orders,
+            vec![OrderState {
+                order_hash,
+                user: from,
+                pool_id,
+                status: indexer.order_status(order_hash).unwrap()
+            }]
+        );
+
+        // filling releases everything it held
+        indexer.filled_orders(2, &[order_hash]);
+        indexer.check_invariants().unwrap();
+        assert!(indexer.order_hashes().is_empty());
+        assert!(!indexer.address_to_orders.contains_key(&from));
+        assert!(indexer
+            .order_storage
+            .size_accounting()
+            .iter()
+            .all(|(_, tracked, _)| *tracked == 0));
+    }
+
+    #[tokio::test]
+    async fn smaller_amendment_keeps_its_place_in_the_book() {
+        let mut indexer = setup_test_indexer();
+        let s = AngstromSigner::random();
+        let from = s.address();
+
+        let pool_key = PoolKey {
+            currency0: Address::random(),
+            currency1: Address::random(),
+            ..Default::default()
+        };
+        let pool_id = PoolId::from(pool_key.clone());
+        indexer.new_pool(NewInitializedPool {
+            currency_out: pool_key.currency0,
+            currency_in:  pool_key.currency1,
+            id:           pool_id
+        });
+        let validity = OrderValidity {
+            valid_until: Some(U256::from(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs()
+                    + 3600
+            )),
+            flash_block: None,
+            is_standing: true
+        };
+        let order = create_test_order(from, pool_key, Some(validity), Some(s));
+        let AllOrders::Standing(original) = order.clone() else { unreachable!() };
+        let valid = |order: AllOrders, validated_at: u64| OrderWithStorageData {
+            order_id: OrderId::from_all_orders(&order, pool_id),
+            order,
+            valid_block: 1,
+            pool_id,
+            is_bid: true,
+            is_currently_valid: true,
+            is_valid: true,
+            priority_data: OrderPriorityData { validated_at, ..Default::default() },
+            invalidates: vec![],
+            tob_reward: U256::ZERO
+        };
+        indexer
+            .handle_validated_order(OrderValidationResults::Valid(valid(order.clone(), 1)))
+            .unwrap();
+
+        let mut amended = original.clone();
+        match &mut amended {
+            StandingVariants::Exact(order) => order.amount -= 100,
+            StandingVariants::Partial(order) => order.max_amount_in -= 100
+        }
+        let amended_hash = amended.order_hash();
+        let (tx, _) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_amendment(
+            OrderAmendment { order_id: order.order_hash(), order: amended.clone() },
+            tx
+        );
+        assert!(indexer.amendments.contains_key(&amended_hash));
+
+        let event = indexer
+            .handle_validated_order(OrderValidationResults::Valid(valid(
+                AllOrders::Standing(amended),
+                5
+            )))
+            .unwrap();
+        assert!(matches!(event, PoolInnerEvent::AmendmentPropagation(_)));
+        indexer.check_invariants().unwrap();
+
+        assert_eq!(indexer.order_hashes(), vec![amended_hash]);
+        assert!(!indexer.order_storage.contains_order(&order.order_hash()));
+        let stored = indexer.pending_orders_for_address(from);
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].priority_data.validated_at, 1);
+    }
+
+    #[tokio::test]
+    async fn amendment_of_another_order_is_rejected() {
+        let mut indexer = setup_test_indexer();
+        let order = create_test_order(
+            Address::random(),
+            PoolKey::default(),
+            Some(OrderValidity { is_standing: true, ..Default::default() }),
+            None
+        );
+        let AllOrders::Standing(amended) = order else { unreachable!() };
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        indexer.new_rpc_amendment(OrderAmendment { order_id: B256::random(), order: amended }, tx);
+
+        assert!(matches!(
+            rx.await,
+            Ok(OrderValidationResults::Invalid(_, ValidationError::InvalidAmendment))
+        ));
+        assert!(indexer.validating.is_empty());
+    }
+
+    #[tokio::test]
+    async fn revalidation_waits_for_the_next_block() {
+        let mut indexer = setup_test_indexer();
+        let (changed, stale) = (Address::random(), Address::random());
+        indexer.revalidate(vec![stale, changed]);
+
+        indexer.start_new_block_processing(2, vec![], vec![changed]);
+        let OrderValidator::ClearingForNewBlock { revalidation_addresses, .. } = &indexer.validator
+        else {
+            panic!("expected a block transition")
+        };
+        assert_eq!(revalidation_addresses.len(), 2);
+        assert!(revalidation_addresses.contains(&stale));
+        assert!(indexer.revalidation.is_empty());
+    }
 }
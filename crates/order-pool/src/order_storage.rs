@@ -1,39 +1,150 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     default::Default,
     fmt::Debug,
-    sync::{Arc, Mutex},
-    time::Instant
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant}
 };
 
-use alloy::primitives::{BlockNumber, FixedBytes, B256};
+use alloy::primitives::{BlockNumber, FixedBytes, B256, U256};
 use angstrom_metrics::OrderStorageMetricsWrapper;
 use angstrom_types::{
-    orders::{OrderId, OrderLocation, OrderSet, OrderStatus},
-    primitive::{NewInitializedPool, PoolId},
+    consensus::OrderReceipt,
+    orders::{BookDelta, BookSide, BookSnapshot, OrderId, OrderLocation, OrderSet, OrderStatus},
+    primitive::{NewInitializedPool, PoolId, PoolLimits},
     sol_bindings::{
         grouped_orders::{AllOrders, GroupedUserOrder, GroupedVanillaOrder, OrderWithStorageData},
         rpc_orders::TopOfBlockOrder
     }
 };
+use angstrom_utils::memory_budget::MemoryConsumer;
+use tokio::sync::broadcast;
 
 use crate::{
+    book_levels::BookLevels,
     finalization_pool::FinalizationPool,
     limit::{LimitOrderPool, LimitPoolError},
     searcher::{SearcherPool, SearcherPoolError},
-    PoolConfig
+    OrderStorageView, PoolConfig, PoolView, SourceTags, BOOK_UPDATE_CHANNEL_SIZE
 };
 
+/// The orders of a single pool. Each shard has its own locks and size budget,
+/// so intake into one pool never waits on reads of another.
+pub struct PoolShard {
+    pub limit_orders:    RwLock<LimitOrderPool>,
+    pub searcher_orders: RwLock<SearcherPool>,
+    /// when the orders of the shard were added, by order hash
+    receipts:            Mutex<HashMap<B256, OrderReceipt>>,
+    book:                Mutex<BookLevels>,
+    /// held while a view of the shard is taken and published, so a view
+    /// taken before a change is never published over one taken after it
+    publishing:          Mutex<()>
+}
+
+impl PoolShard {
+    fn new(pool_id: PoolId, limits: &ShardLimits) -> Self {
+        Self {
+            limit_orders:    RwLock::new(LimitOrderPool::new(
+                &[pool_id],
+                Some(limits.limit_max_size)
+            )),
+            searcher_orders: RwLock::new(SearcherPool::new(
+                &[pool_id],
+                Some(limits.searcher_max_size)
+            )),
+            receipts:        Mutex::new(HashMap::new()),
+            book:            Mutex::new(BookLevels::new(pool_id)),
+            publishing:      Mutex::new(())
+        }
+    }
+
+    fn view(&self) -> PoolView {
+        let limit = self.limit_orders.read().expect("poisoned");
+        let searcher = self.searcher_orders.read().expect("poisoned");
+        PoolView::new(
+            limit.get_all_orders(),
+            limit.get_parked_orders(),
+            limit.get_composable_orders(),
+            searcher.get_all_orders(),
+            self.book.lock().expect("poisoned").snapshot()
+        )
+    }
+
+    /// Deltas are sent while the book is locked, so they go out in sequence.
+    fn add_to_book(&self, entry: Option<BookEntry>, updates: &broadcast::Sender<BookDelta>) {
+        let Some((side, price, volume)) = entry else { return };
+        let delta = self.book.lock().expect("poisoned").add(side, price, volume);
+        let _ = updates.send(delta);
+    }
+
+    fn remove_from_book(&self, entry: Option<BookEntry>, updates: &broadcast::Sender<BookDelta>) {
+        let Some((side, price, volume)) = entry else { return };
+        let mut book = self.book.lock().expect("poisoned");
+        if let Some(delta) = book.remove(side, price, volume) {
+            let _ = updates.send(delta);
+        }
+    }
+
+    fn stamp(&self, order_hash: B256) {
+        self.receipts
+            .lock()
+            .expect("poisoned")
+            .insert(order_hash, OrderReceipt::now(order_hash));
+    }
+
+    fn drop_receipt(&self, order_hash: &B256) {
+        self.receipts.lock().expect("poisoned").remove(order_hash);
+    }
+}
+
+/// Where an order sits in the book of its pool.
+type BookEntry = (BookSide, U256, u128);
+
+/// Only pending vanilla orders make up the book.
+fn book_entry(order: &OrderWithStorageData<GroupedUserOrder>) -> Option<BookEntry> {
+    (order.is_vanilla() && order.is_currently_valid).then(|| {
+        (BookSide::from_is_bid(order.is_bid), order.priority_data.price, order.priority_data.volume)
+    })
+}
+
+/// The size budgets given to each shard.
+#[derive(Debug, Clone, Copy)]
+struct ShardLimits {
+    limit_max_size:    usize,
+    searcher_max_size: usize
+}
+
 /// The Storage of all verified orders.
 #[derive(Clone)]
 pub struct OrderStorage {
-    pub limit_orders:                Arc<Mutex<LimitOrderPool>>,
-    pub searcher_orders:             Arc<Mutex<SearcherPool>>,
+    shards: Arc<RwLock<HashMap<PoolId, Arc<PoolShard>>>>,
+    shard_limits: Arc<Mutex<ShardLimits>>,
+    /// the id of every stored order by hash, across all pools and sub-pools,
+    /// so no hash is stored twice
+    order_index: Arc<Mutex<HashMap<B256, OrderId>>>,
+    /// the source tags of the stored orders that were submitted with one
+    source_tags: SourceTags,
     pub pending_finalization_orders: Arc<Mutex<FinalizationPool>>,
+    /// orders in the pre-proposals of the current consensus round. They can't
+    /// be cancelled, amended, expired or dropped until the round completes,
+    /// so the proposal built from them can still be verified
+    pinned_orders: Arc<Mutex<HashSet<B256>>>,
     /// we store filled order hashes until they are expired time wise to ensure
     /// we don't waste processing power in the validator.
-    pub filled_orders:               Arc<Mutex<HashMap<B256, Instant>>>,
-    pub metrics:                     OrderStorageMetricsWrapper
+    pub filled_orders: Arc<Mutex<HashMap<B256, Instant>>>,
+    /// changes to the books of all pools
+    book_updates: broadcast::Sender<BookDelta>,
+    /// how long the orders validated since the last block took
+    validation_latency: Arc<Mutex<ValidationLatency>>,
+    /// what readers see of the shards, republished after every change to one
+    view: OrderStorageView,
+    pub metrics: OrderStorageMetricsWrapper
+}
+
+#[derive(Debug, Default)]
+struct ValidationLatency {
+    total:  Duration,
+    orders: u32
 }
 
 impl Debug for OrderStorage {
@@ -43,29 +154,282 @@ impl Debug for OrderStorage {
     }
 }
 
+/// The pools can't drop the orders they hold to free memory, but stop taking
+/// new ones until they are back under budget.
+impl MemoryConsumer for OrderStorage {
+    fn memory_usage(&self) -> usize {
+        self.all_shards()
+            .iter()
+            .map(|shard| {
+                shard.limit_orders.read().expect("poisoned").tracked_size()
+                    + shard
+                        .searcher_orders
+                        .read()
+                        .expect("poisoned")
+                        .tracked_size()
+            })
+            .sum()
+    }
+
+    fn shed(&self, _: usize) -> usize {
+        self.pause_intake();
+        0
+    }
+
+    fn restore(&self) {
+        self.resume_intake();
+    }
+}
+
 impl OrderStorage {
+    /// The configured sub-pool sizes are the budget of every pool, not of the
+    /// storage as a whole.
     pub fn new(config: &PoolConfig) -> Self {
-        let limit_orders = Arc::new(Mutex::new(LimitOrderPool::new(
-            &config.ids,
-            Some(config.lo_pending_limit.max_size)
-        )));
-        let searcher_orders = Arc::new(Mutex::new(SearcherPool::new(
-            &config.ids,
-            Some(config.s_pending_limit.max_size)
-        )));
+        let shard_limits = ShardLimits {
+            limit_max_size:    config.lo_pending_limit.max_size,
+            searcher_max_size: config.s_pending_limit.max_size
+        };
+        let shards = config
+            .ids
+            .iter()
+            .map(|id| (*id, Arc::new(PoolShard::new(*id, &shard_limits))))
+            .collect();
         let pending_finalization_orders = Arc::new(Mutex::new(FinalizationPool::new()));
-        Self {
+        let storage = Self {
             filled_orders: Arc::new(Mutex::new(HashMap::default())),
-            limit_orders,
-            searcher_orders,
+            shards: Arc::new(RwLock::new(shards)),
+            shard_limits: Arc::new(Mutex::new(shard_limits)),
+            order_index: Arc::new(Mutex::new(HashMap::new())),
+            source_tags: SourceTags::default(),
             pending_finalization_orders,
+            pinned_orders: Arc::new(Mutex::new(HashSet::new())),
+            book_updates: broadcast::channel(BOOK_UPDATE_CHANNEL_SIZE).0,
+            validation_latency: Arc::new(Mutex::new(ValidationLatency::default())),
+            view: OrderStorageView::default(),
             metrics: OrderStorageMetricsWrapper::default()
+        };
+        storage.publish_all();
+
+        storage
+    }
+
+    /// Publishes to `view` instead, for when readers need a view before the
+    /// storage exists.
+    pub fn with_view(mut self, view: OrderStorageView) -> Self {
+        self.view = view;
+        self.publish_all();
+        self
+    }
+
+    /// Lock-free reads of the stored orders.
+    pub fn view(&self) -> OrderStorageView {
+        self.view.clone()
+    }
+
+    /// Publishes the current state of a shard, called after every change to
+    /// its orders. The write locks of the shard must be released.
+    fn publish(&self, pool_id: PoolId, shard: &PoolShard) {
+        let _publishing = shard.publishing.lock().expect("poisoned");
+        self.view.publish(pool_id, shard.view());
+    }
+
+    fn publish_all(&self) {
+        for (pool_id, shard) in self.shards.read().expect("poisoned").iter() {
+            self.publish(*pool_id, shard);
+        }
+    }
+
+    pub fn record_validation_latency(&self, latency: Duration) {
+        let mut validation_latency = self.validation_latency.lock().expect("poisoned");
+        validation_latency.total += latency;
+        validation_latency.orders += 1;
+    }
+
+    /// The mean time the orders took to validate since the last call, none if
+    /// no order was validated.
+    pub fn take_validation_latency(&self) -> Option<Duration> {
+        let latency = std::mem::take(&mut *self.validation_latency.lock().expect("poisoned"));
+        (latency.orders != 0).then(|| latency.total / latency.orders)
+    }
+
+    /// Number of orders stored, across all pools.
+    pub fn order_count(&self) -> usize {
+        self.order_index.lock().expect("poisoned").len()
+    }
+
+    /// Deltas of the books of all pools. Subscribers that fall behind should
+    /// start over from a [`BookSnapshot`].
+    pub fn subscribe_book(&self) -> broadcast::Receiver<BookDelta> {
+        self.book_updates.subscribe()
+    }
+
+    pub fn book_snapshot(&self, pool_id: &PoolId) -> Option<BookSnapshot> {
+        Some(
+            self.shard(pool_id)?
+                .book
+                .lock()
+                .expect("poisoned")
+                .snapshot()
+        )
+    }
+
+    /// The shard of the given pool. The shard map is only locked for the
+    /// lookup.
+    pub fn shard(&self, pool_id: &PoolId) -> Option<Arc<PoolShard>> {
+        self.shards.read().expect("poisoned").get(pool_id).cloned()
+    }
+
+    fn all_shards(&self) -> Vec<Arc<PoolShard>> {
+        self.shards
+            .read()
+            .expect("poisoned")
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// The id of the stored order with the given hash, in whichever pool it
+    /// is.
+    pub fn order_id(&self, order_hash: &B256) -> Option<OrderId> {
+        self.order_index
+            .lock()
+            .expect("poisoned")
+            .get(order_hash)
+            .copied()
+    }
+
+    pub fn contains_order(&self, order_hash: &B256) -> bool {
+        self.order_index
+            .lock()
+            .expect("poisoned")
+            .contains_key(order_hash)
+    }
+
+    /// Keeps the orders in the pool until [`Self::release_pins`], e.g. because
+    /// they are in a pre-proposal of the current round. Orders that aren't
+    /// stored yet are pinned once they are.
+    pub fn pin_orders(&self, order_hashes: impl IntoIterator<Item = B256>) {
+        self.pinned_orders
+            .lock()
+            .expect("poisoned")
+            .extend(order_hashes);
+    }
+
+    /// Releases the pins of the round that completed.
+    pub fn release_pins(&self) {
+        self.pinned_orders.lock().expect("poisoned").clear();
+    }
+
+    pub fn is_pinned(&self, order_hash: &B256) -> bool {
+        self.pinned_orders
+            .lock()
+            .expect("poisoned")
+            .contains(order_hash)
+    }
+
+    /// Claims the hash of an order that is about to be stored. Returns false,
+    /// and counts the duplicate, if an order with the hash is stored already.
+    fn index_order(&self, order_id: OrderId) -> bool {
+        let mut index = self.order_index.lock().expect("poisoned");
+        if index.contains_key(&order_id.hash) {
+            self.metrics.incr_duplicate_orders();
+            return false
+        }
+
+        index.insert(order_id.hash, order_id);
+        true
+    }
+
+    fn unindex_order(&self, order_hash: &B256) {
+        self.order_index
+            .lock()
+            .expect("poisoned")
+            .remove(order_hash);
+        self.source_tags.remove(order_hash);
+    }
+
+    /// The source tags of the stored orders, dropped along with their order.
+    pub fn source_tags(&self) -> SourceTags {
+        self.source_tags.clone()
+    }
+
+    pub fn pool_ids(&self) -> Vec<PoolId> {
+        self.shards
+            .read()
+            .expect("poisoned")
+            .keys()
+            .copied()
+            .collect()
+    }
+
+    pub fn update_limits(&self, limits: PoolLimits) {
+        let mut shard_limits = self.shard_limits.lock().expect("poisoned");
+        if let Some(max_size) = limits.limit_max_size {
+            shard_limits.limit_max_size = max_size;
+        }
+        if let Some(max_size) = limits.searcher_max_size {
+            shard_limits.searcher_max_size = max_size;
+        }
+
+        for shard in self.all_shards() {
+            if let Some(max_size) = limits.limit_max_size {
+                shard
+                    .limit_orders
+                    .write()
+                    .expect("poisoned")
+                    .set_max_size(max_size);
+            }
+            if let Some(max_size) = limits.searcher_max_size {
+                shard
+                    .searcher_orders
+                    .write()
+                    .expect("poisoned")
+                    .set_max_size(max_size);
+            }
+        }
+    }
+
+    /// Stops every pool from taking more orders than it holds now, the orders
+    /// already in stay. Undone by [`Self::resume_intake`].
+    pub fn pause_intake(&self) {
+        for shard in self.all_shards() {
+            let mut limit = shard.limit_orders.write().expect("poisoned");
+            let size = limit.tracked_size();
+            limit.set_max_size(size);
+
+            let mut searcher = shard.searcher_orders.write().expect("poisoned");
+            let size = searcher.tracked_size();
+            searcher.set_max_size(size);
+        }
+    }
+
+    /// Gives the pools their configured size budgets back.
+    pub fn resume_intake(&self) {
+        let limits = *self.shard_limits.lock().expect("poisoned");
+        for shard in self.all_shards() {
+            shard
+                .limit_orders
+                .write()
+                .expect("poisoned")
+                .set_max_size(limits.limit_max_size);
+            shard
+                .searcher_orders
+                .write()
+                .expect("poisoned")
+                .set_max_size(limits.searcher_max_size);
         }
     }
 
     pub fn remove_pool(&self, key: PoolId) {
-        self.searcher_orders.lock().unwrap().remove_pool(&key);
-        self.limit_orders.lock().unwrap().remove_pool(&key);
+        let Some(shard) = self.shards.write().expect("poisoned").remove(&key) else { return };
+        self.view.remove(&key);
+        self.order_index
+            .lock()
+            .expect("poisoned")
+            .retain(|_, id| id.pool_id != key);
+        for delta in shard.book.lock().expect("poisoned").clear() {
+            let _ = self.book_updates.send(delta);
+        }
     }
 
     pub fn fetch_status_of_order(&self, order: B256) -> Option<OrderStatus> {
@@ -83,19 +447,57 @@ impl OrderStorage {
             return Some(OrderStatus::Filled)
         }
 
-        if self
-            .searcher_orders
-            .lock()
-            .expect("poisoned")
-            .has_order(order)
-        {
-            return Some(OrderStatus::Pending)
+        let order_id = self.order_id(&order)?;
+        let shard = self.shard(&order_id.pool_id)?;
+        match order_id.location {
+            OrderLocation::Searcher => shard
+                .searcher_orders
+                .read()
+                .expect("poisoned")
+                .has_order(order)
+                .then_some(OrderStatus::Pending),
+            OrderLocation::Limit => shard
+                .limit_orders
+                .read()
+                .expect("poisoned")
+                .get_order_status(order)
         }
+    }
 
-        self.limit_orders
-            .lock()
-            .expect("poisoned")
-            .get_order_status(order)
+    pub fn get_order(&self, order_id: &OrderId) -> Option<OrderWithStorageData<AllOrders>> {
+        let shard = self.shard(&order_id.pool_id)?;
+
+        match order_id.location {
+            OrderLocation::Limit => shard
+                .limit_orders
+                .read()
+                .expect("lock poisoned")
+                .get_order(order_id)
+                .and_then(|order| order.try_map_inner(|inner| Ok(inner.into())).ok()),
+            OrderLocation::Searcher => shard
+                .searcher_orders
+                .read()
+                .expect("lock poisoned")
+                .get_order(order_id.pool_id, order_id.hash)
+                .and_then(|order| order.try_map_inner(|inner| Ok(AllOrders::TOB(inner))).ok())
+        }
+    }
+
+    pub fn orders_by_pool(&self, pool_id: PoolId, order_location: OrderLocation) -> Vec<AllOrders> {
+        let Some(shard) = self.shard(&pool_id) else { return vec![] };
+
+        match order_location {
+            OrderLocation::Limit => shard
+                .limit_orders
+                .read()
+                .expect("poisoned")
+                .get_all_orders_from_pool(pool_id),
+            OrderLocation::Searcher => shard
+                .searcher_orders
+                .read()
+                .expect("poisoned")
+                .get_all_orders_from_pool(pool_id)
+        }
     }
 
     // unfortunately, any other solution is just as ugly
@@ -109,22 +511,25 @@ impl OrderStorage {
     }
 
     pub fn cancel_order(&self, order_id: &OrderId) -> Option<OrderWithStorageData<AllOrders>> {
-        if self
-            .pending_finalization_orders
-            .lock()
-            .expect("poisoned")
-            .has_order(&order_id.hash)
+        if self.is_pinned(&order_id.hash)
+            || self
+                .pending_finalization_orders
+                .lock()
+                .expect("poisoned")
+                .has_order(&order_id.hash)
         {
             return None
         }
+        let shard = self.shard(&order_id.pool_id)?;
 
-        match order_id.location {
-            angstrom_types::orders::OrderLocation::Limit => self
+        let order = match order_id.location {
+            OrderLocation::Limit => shard
                 .limit_orders
-                .lock()
+                .write()
                 .expect("lock poisoned")
                 .remove_order(order_id)
                 .and_then(|order| {
+                    shard.remove_from_book(book_entry(&order), &self.book_updates);
                     match order.order {
                         GroupedUserOrder::Composable(_) => {
                             self.metrics.incr_cancelled_composable_orders()
@@ -133,9 +538,9 @@ impl OrderStorage {
                     }
                     order.try_map_inner(|inner| Ok(inner.into())).ok()
                 }),
-            angstrom_types::orders::OrderLocation::Searcher => self
+            OrderLocation::Searcher => shard
                 .searcher_orders
-                .lock()
+                .write()
                 .expect("lock poisoned")
                 .remove_order(order_id)
                 .map(|order| {
@@ -144,48 +549,76 @@ impl OrderStorage {
                         .try_map_inner(|inner| Ok(AllOrders::TOB(inner)))
                         .unwrap()
                 })
+        };
+        if order.is_some() {
+            shard.drop_receipt(&order_id.hash);
+            self.unindex_order(&order_id.hash);
+            self.publish(order_id.pool_id, &shard);
         }
+
+        order
     }
 
     /// moves all orders to the parked location if there not already.
     pub fn park_orders(&self, order_info: Vec<&OrderId>) {
-        // take lock here so we don't drop between iterations.
-        let mut limit_lock = self.limit_orders.lock().unwrap();
+        let mut by_pool: HashMap<PoolId, Vec<&OrderId>> = HashMap::new();
         order_info
             .into_iter()
             .for_each(|order| match order.location {
-                angstrom_types::orders::OrderLocation::Limit => {
-                    limit_lock.park_order(order);
-                }
-                angstrom_types::orders::OrderLocation::Searcher => {
+                OrderLocation::Limit => by_pool.entry(order.pool_id).or_default().push(order),
+                OrderLocation::Searcher => {
                     tracing::debug!("tried to park searcher order. this is not supported");
                 }
             });
+
+        for (pool_id, orders) in by_pool {
+            let Some(shard) = self.shard(&pool_id) else { continue };
+            // take lock here so we don't drop between iterations.
+            let mut limit_lock = shard.limit_orders.write().expect("poisoned");
+            orders.into_iter().for_each(|order| {
+                let entry = limit_lock
+                    .get_order(order)
+                    .and_then(|order| book_entry(&order));
+                limit_lock.park_order(order);
+                shard.remove_from_book(entry, &self.book_updates);
+            });
+            drop(limit_lock);
+            self.publish(pool_id, &shard);
+        }
     }
 
     pub fn top_tob_orders(&self) -> Vec<OrderWithStorageData<TopOfBlockOrder>> {
-        let mut top_orders = Vec::new();
-        let searcher_orders = self.searcher_orders.lock().expect("lock poisoned");
+        self.view.top_tob_orders()
+    }
 
-        for pool_id in searcher_orders.get_all_pool_ids() {
-            if let Some(top_order) = searcher_orders
-                .get_orders_for_pool(&pool_id)
-                .unwrap_or_else(|| panic!("pool {} does not exist", pool_id))
-                .iter()
-                .max_by_key(|order| order.tob_reward)
-                .cloned()
-            {
-                top_orders.push(top_order);
-            }
+    pub fn add_new_limit_order(
+        &self,
+        order: OrderWithStorageData<GroupedUserOrder>
+    ) -> Result<(), LimitPoolError> {
+        let pool_id = order.pool_id;
+        let shard = self
+            .shard(&pool_id)
+            .ok_or(LimitPoolError::NoPool(pool_id))?;
+        let order_hash = order.order_id.hash;
+        if !self.index_order(order.order_id) {
+            return Err(LimitPoolError::DuplicateOrder(order_hash))
         }
 
-        top_orders
+        self.insert_limit_order(&shard, order)
+            .inspect_err(|_| self.unindex_order(&order_hash))?;
+        shard.stamp(order_hash);
+        self.publish(pool_id, &shard);
+
+        Ok(())
     }
 
-    pub fn add_new_limit_order(
+    fn insert_limit_order(
         &self,
+        shard: &PoolShard,
         order: OrderWithStorageData<GroupedUserOrder>
     ) -> Result<(), LimitPoolError> {
+        let entry = book_entry(&order);
+
         if order.is_vanilla() {
             let mapped_order = order.try_map_inner(|this| {
                 let GroupedUserOrder::Vanilla(order) = this else {
@@ -194,10 +627,12 @@ impl OrderStorage {
                 Ok(order)
             })?;
 
-            self.limit_orders
-                .lock()
+            shard
+                .limit_orders
+                .write()
                 .expect("lock poisoned")
                 .add_vanilla_order(mapped_order)?;
+            shard.add_to_book(entry, &self.book_updates);
             self.metrics.incr_vanilla_limit_orders(1);
         } else {
             let mapped_order = order.try_map_inner(|this| {
@@ -207,8 +642,9 @@ impl OrderStorage {
                 Ok(order)
             })?;
 
-            self.limit_orders
-                .lock()
+            shard
+                .limit_orders
+                .write()
                 .expect("lock poisoned")
                 .add_composable_order(mapped_order)?;
             self.metrics.incr_composable_limit_orders(1);
@@ -221,10 +657,23 @@ impl OrderStorage {
         &self,
         order: OrderWithStorageData<TopOfBlockOrder>
     ) -> Result<(), SearcherPoolError> {
-        self.searcher_orders
-            .lock()
+        let pool_id = order.pool_id;
+        let shard = self
+            .shard(&pool_id)
+            .ok_or(SearcherPoolError::NoPool(pool_id))?;
+        let order_hash = order.order_id.hash;
+        if !self.index_order(order.order_id) {
+            return Err(SearcherPoolError::DuplicateOrder(order_hash))
+        }
+
+        shard
+            .searcher_orders
+            .write()
             .expect("lock poisoned")
-            .add_searcher_order(order)?;
+            .add_searcher_order(order)
+            .inspect_err(|_| self.unindex_order(&order_hash))?;
+        shard.stamp(order_hash);
+        self.publish(pool_id, &shard);
 
         self.metrics.incr_searcher_orders(1);
 
@@ -268,9 +717,13 @@ impl OrderStorage {
     }
 
     pub fn remove_searcher_order(&self, id: &OrderId) -> Option<OrderWithStorageData<AllOrders>> {
-        let order = self
+        let shard = self.shard(&id.pool_id)?;
+        shard.drop_receipt(&id.hash);
+        self.unindex_order(&id.hash);
+
+        let order = shard
             .searcher_orders
-            .lock()
+            .write()
             .expect("posioned")
             .remove_order(id)
             .map(|value| {
@@ -281,16 +734,42 @@ impl OrderStorage {
                     })
                     .unwrap()
             });
+        if order.is_some() {
+            self.publish(id.pool_id, &shard);
+        }
 
         order
     }
 
+    /// Takes out the limit order an amendment replaces. Orders in a proposed
+    /// bundle can't be amended until it lands or fails, nor pinned ones until
+    /// their round completes.
+    pub fn take_amended_order(&self, id: &OrderId) -> Option<OrderWithStorageData<AllOrders>> {
+        if self.is_pinned(&id.hash)
+            || self
+                .pending_finalization_orders
+                .lock()
+                .expect("poisoned")
+                .has_order(&id.hash)
+        {
+            return None
+        }
+
+        self.remove_limit_order(id)
+    }
+
     pub fn remove_limit_order(&self, id: &OrderId) -> Option<OrderWithStorageData<AllOrders>> {
-        self.limit_orders
-            .lock()
+        let shard = self.shard(&id.pool_id)?;
+        shard.drop_receipt(&id.hash);
+        self.unindex_order(&id.hash);
+
+        let order = shard
+            .limit_orders
+            .write()
             .expect("poisoned")
             .remove_order(id)
             .and_then(|order| {
+                shard.remove_from_book(book_entry(&order), &self.book_updates);
                 if order.is_vanilla() {
                     self.metrics.decr_vanilla_limit_orders(1);
                 } else if order.is_composable() {
@@ -298,21 +777,326 @@ impl OrderStorage {
                 }
 
                 order.try_map_inner(|inner| Ok(inner.into())).ok()
-            })
+            });
+        if order.is_some() {
+            self.publish(id.pool_id, &shard);
+        }
+
+        order
     }
 
+    /// The orders that are matched, read from the view so matching never
+    /// waits on intake.
     pub fn get_all_orders(&self) -> OrderSet<GroupedVanillaOrder, TopOfBlockOrder> {
-        let limit = self.limit_orders.lock().expect("poisoned").get_all_orders();
-        let searcher = self.top_tob_orders();
+        self.view.order_set()
+    }
 
-        OrderSet { limit, searcher }
+    /// The receipts of the given orders, stamped when they were added to the
+    /// storage.
+    pub fn receipts_for(
+        &self,
+        orders: &OrderSet<GroupedVanillaOrder, TopOfBlockOrder>
+    ) -> Vec<OrderReceipt> {
+        let shards = self.shards.read().expect("poisoned");
+        orders
+            .limit
+            .iter()
+            .map(|order| (order.pool_id, order.order_id.hash))
+            .chain(
+                orders
+                    .searcher
+                    .iter()
+                    .map(|order| (order.pool_id, order.order_id.hash))
+            )
+            .filter_map(|(pool_id, hash)| {
+                shards
+                    .get(&pool_id)?
+                    .receipts
+                    .lock()
+                    .expect("poisoned")
+                    .get(&hash)
+                    .copied()
+            })
+            .collect()
     }
 
     pub fn new_pool(&self, pool: NewInitializedPool) {
-        self.limit_orders.lock().expect("poisoned").new_pool(pool);
-        self.searcher_orders
-            .lock()
+        let shard_limits = self.shard_limits.lock().expect("poisoned");
+        let shard = self
+            .shards
+            .write()
             .expect("poisoned")
-            .new_pool(pool);
+            .entry(pool.id)
+            .or_insert_with(|| Arc::new(PoolShard::new(pool.id, &shard_limits)))
+            .clone();
+        self.publish(pool.id, &shard);
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn order_hashes(&self) -> Vec<B256> {
+        self.all_shards()
+            .into_iter()
+            .flat_map(|shard| {
+                let mut hashes = shard.limit_orders.read().expect("poisoned").order_hashes();
+                hashes.extend(
+                    shard
+                        .searcher_orders
+                        .read()
+                        .expect("poisoned")
+                        .order_hashes()
+                );
+                hashes
+            })
+            .collect()
+    }
+
+    /// The size each shard's trackers account for next to the size of the
+    /// orders they actually hold, as `(sub-pool, tracked, held)`.
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn size_accounting(&self) -> Vec<(&'static str, usize, usize)> {
+        self.all_shards()
+            .into_iter()
+            .flat_map(|shard| {
+                let limit = shard.limit_orders.read().expect("poisoned");
+                let searcher = shard.searcher_orders.read().expect("poisoned");
+                [
+                    ("limit", limit.tracked_size(), limit.held_size()),
+                    ("searcher", searcher.tracked_size(), searcher.held_size())
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::orders::BookLevelUpdate;
+
+    use super::*;
+
+    fn searcher_order(pool_id: PoolId, hash: u8) -> OrderWithStorageData<TopOfBlockOrder> {
+        let mut order = OrderWithStorageData { pool_id, ..Default::default() };
+        order.order_id.pool_id = pool_id;
+        order.order_id.hash = B256::repeat_byte(hash);
+        order.order_id.location = OrderLocation::Searcher;
+        order
+    }
+
+    #[test]
+    fn pools_have_their_own_size_budget() {
+        let (a, b) = (PoolId::repeat_byte(1), PoolId::repeat_byte(2));
+        let mut config = PoolConfig { ids: vec![a, b], ..Default::default() };
+        config.s_pending_limit.max_size = std::mem::size_of::<TopOfBlockOrder>();
+        let storage = OrderStorage::new(&config);
+
+        storage
+            .add_new_searcher_order(searcher_order(a, 1))
+            .unwrap();
+        assert!(matches!(
+            storage.add_new_searcher_order(searcher_order(a, 2)),
+            Err(SearcherPoolError::MaxSize)
+        ));
+        // a full pool doesn't take space from the others
+        storage
+            .add_new_searcher_order(searcher_order(b, 3))
+            .unwrap();
+        assert!(matches!(
+            storage.add_new_searcher_order(searcher_order(PoolId::repeat_byte(3), 4)),
+            Err(SearcherPoolError::NoPool(_))
+        ));
+        assert_eq!(storage.top_tob_orders().len(), 2);
+
+        storage.remove_pool(a);
+        assert_eq!(storage.pool_ids(), vec![b]);
+        assert!(storage
+            .orders_by_pool(a, OrderLocation::Searcher)
+            .is_empty());
+        assert_eq!(storage.fetch_status_of_order(B256::repeat_byte(3)), Some(OrderStatus::Pending));
+    }
+
+    #[test]
+    fn shedding_load_stops_intake_until_restored() {
+        let pool_id = PoolId::repeat_byte(1);
+        let storage = OrderStorage::new(&PoolConfig { ids: vec![pool_id], ..Default::default() });
+        storage
+            .add_new_searcher_order(searcher_order(pool_id, 1))
+            .unwrap();
+        assert_eq!(storage.memory_usage(), std::mem::size_of::<TopOfBlockOrder>());
+
+        assert_eq!(storage.shed(usize::MAX), 0);
+        assert!(matches!(
+            storage.add_new_searcher_order(searcher_order(pool_id, 2)),
+            Err(SearcherPoolError::MaxSize)
+        ));
+        assert_eq!(storage.top_tob_orders().len(), 1);
+
+        storage.restore();
+        storage
+            .add_new_searcher_order(searcher_order(pool_id, 2))
+            .unwrap();
+    }
+
+    #[test]
+    fn book_deltas_follow_pending_vanilla_orders() {
+        let pool_id = PoolId::repeat_byte(1);
+        let storage = OrderStorage::new(&PoolConfig { ids: vec![pool_id], ..Default::default() });
+        let mut updates = storage.subscribe_book();
+
+        let limit_order = |hash: u8, price: u64| {
+            let mut order = OrderWithStorageData::<GroupedVanillaOrder> {
+                pool_id,
+                is_bid: true,
+                is_currently_valid: true,
+                ..Default::default()
+            };
+            order.priority_data.price = U256::from(price);
+            order.priority_data.volume = 10;
+            order.order_id.pool_id = pool_id;
+            order.order_id.hash = B256::repeat_byte(hash);
+            order
+                .try_map_inner(|order| Ok(GroupedUserOrder::Vanilla(order)))
+                .unwrap()
+        };
+        let (a, b) = (limit_order(1, 100), limit_order(2, 200));
+        let (a_id, b_id) = (a.order_id, b.order_id);
+
+        storage.add_new_limit_order(a).unwrap();
+        storage.add_new_limit_order(b).unwrap();
+        assert!(matches!(
+            updates.try_recv().unwrap().update,
+            BookLevelUpdate::Add { side: BookSide::Bid, .. }
+        ));
+        assert_eq!(updates.try_recv().unwrap().sequence, 2);
+
+        let snapshot = storage.book_snapshot(&pool_id).unwrap();
+        assert_eq!(
+            snapshot
+                .bids
+                .iter()
+                .map(|level| level.price)
+                .collect::<Vec<_>>(),
+            vec![U256::from(200), U256::from(100)]
+        );
+
+        // parked and removed orders leave the book
+        storage.park_orders(vec![&a_id]);
+        storage.remove_limit_order(&b_id).unwrap();
+        assert_eq!(
+            updates.try_recv().unwrap().update,
+            BookLevelUpdate::Remove { side: BookSide::Bid, price: U256::from(100) }
+        );
+        assert_eq!(updates.try_recv().unwrap().sequence, 4);
+        assert!(updates.try_recv().is_err());
+        assert!(storage.book_snapshot(&pool_id).unwrap().bids.is_empty());
+    }
+
+    #[test]
+    fn order_hashes_are_unique_across_pools() {
+        let (a, b) = (PoolId::repeat_byte(1), PoolId::repeat_byte(2));
+        let storage = OrderStorage::new(&PoolConfig { ids: vec![a, b], ..Default::default() });
+
+        let order = searcher_order(a, 1);
+        let order_id = order.order_id;
+        storage.add_new_searcher_order(order).unwrap();
+        assert_eq!(storage.order_id(&B256::repeat_byte(1)), Some(order_id));
+
+        assert!(matches!(
+            storage.add_new_searcher_order(searcher_order(b, 1)),
+            Err(SearcherPoolError::DuplicateOrder(hash)) if hash == B256::repeat_byte(1)
+        ));
+        assert!(storage
+            .orders_by_pool(b, OrderLocation::Searcher)
+            .is_empty());
+
+        // the hash is free again once the order is gone
+        storage.remove_searcher_order(&order_id).unwrap();
+        assert!(!storage.contains_order(&B256::repeat_byte(1)));
+        storage
+            .add_new_searcher_order(searcher_order(b, 1))
+            .unwrap();
+        assert_eq!(storage.order_id(&B256::repeat_byte(1)).unwrap().pool_id, b);
+    }
+
+    #[test]
+    fn orders_are_stamped_until_removed() {
+        let pool_id = PoolId::repeat_byte(1);
+        let storage = OrderStorage::new(&PoolConfig { ids: vec![pool_id], ..Default::default() });
+
+        let order = searcher_order(pool_id, 1);
+        let order_id = order.order_id;
+        storage.add_new_searcher_order(order).unwrap();
+
+        let receipts = storage.receipts_for(&storage.get_all_orders());
+        assert_eq!(
+            receipts
+                .iter()
+                .map(|receipt| receipt.order_hash)
+                .collect::<Vec<_>>(),
+            vec![B256::repeat_byte(1)]
+        );
+        assert!(receipts[0].received_at <= OrderReceipt::now(B256::ZERO).received_at);
+
+        // reading the order leaves its receipt in place
+        assert!(storage.get_order(&order_id).is_some());
+        assert_eq!(storage.receipts_for(&storage.get_all_orders()), receipts);
+
+        storage.remove_searcher_order(&order_id).unwrap();
+        assert!(storage
+            .shard(&pool_id)
+            .unwrap()
+            .receipts
+            .lock()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn the_view_follows_every_change() {
+        let (a, b) = (PoolId::repeat_byte(1), PoolId::repeat_byte(2));
+        let view = OrderStorageView::default();
+        let storage = OrderStorage::new(&PoolConfig { ids: vec![a], ..Default::default() })
+            .with_view(view.clone());
+        assert!(view.pool(&a).is_some());
+
+        let order = searcher_order(a, 1);
+        let order_id = order.order_id;
+        storage.add_new_searcher_order(order).unwrap();
+        assert_eq!(view.order_status(&order_id.hash), Some(OrderStatus::Pending));
+        assert_eq!(view.orders_by_pool(&a, OrderLocation::Searcher).len(), 1);
+        assert_eq!(view.pending_orders(order_id.address).len(), 1);
+        assert_eq!(storage.get_all_orders().searcher.len(), 1);
+
+        // loaded views stay as they were
+        let loaded = view.pool(&a).unwrap();
+        storage.remove_searcher_order(&order_id).unwrap();
+        assert_eq!(loaded.searcher.len(), 1);
+        assert_eq!(view.order_status(&order_id.hash), None);
+
+        storage.new_pool(NewInitializedPool {
+            currency_in:  Default::default(),
+            currency_out: Default::default(),
+            id:           b
+        });
+        assert!(view.pool(&b).is_some());
+        storage.remove_pool(a);
+        assert!(view.pool(&a).is_none());
+    }
+
+    #[test]
+    fn pinned_orders_stay_until_released() {
+        let pool_id = PoolId::repeat_byte(1);
+        let storage = OrderStorage::new(&PoolConfig { ids: vec![pool_id], ..Default::default() });
+
+        let order = searcher_order(pool_id, 1);
+        let order_id = order.order_id;
+        storage.add_new_searcher_order(order).unwrap();
+        storage.pin_orders([order_id.hash]);
+
+        assert!(storage.cancel_order(&order_id).is_none());
+        assert!(storage.contains_order(&order_id.hash));
+
+        storage.release_pins();
+        assert!(!storage.is_pinned(&order_id.hash));
+        assert!(storage.cancel_order(&order_id).is_some());
     }
 }
@@ -0,0 +1,287 @@
+//! Intake of orders relayed from the Angstrom deployments of other chains.
+//!
+//! A relayed order is signed by its user for the deployment of its origin
+//! chain, which the node can't read. A [`RelayVerifier`] vouches for it
+//! instead: the [`AttestationSet`] takes the signatures of a threshold of known
+//! relayers, a light client of the origin chain would check the order against
+//! its state.
+//!
+//! Verified orders are kept in a [`RelayedOrderPool`] of their own, apart from
+//! the books of this chain. They are neither matched nor gossiped, and only
+//! held for cross-chain settlement to be tried on later.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH}
+};
+
+use alloy::primitives::{Address, B256, U256};
+use angstrom_types::{
+    orders::{OriginDomain, RelayedOrder},
+    sol_bindings::RawPoolOrder
+};
+use serde::Deserialize;
+
+/// Orders the relayed pool holds unless configured otherwise.
+pub const DEFAULT_MAX_RELAYED_ORDERS: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RelayError {
+    #[error("orders aren't relayed from chain {0}")]
+    UnknownDomain(u64),
+    #[error("the order isn't signed for its origin deployment")]
+    InvalidOrderSignature,
+    #[error("{have} of the {need} attestations needed")]
+    NotEnoughAttestations { have: usize, need: usize },
+    #[error("the order is past its deadline")]
+    Expired,
+    #[error("the order was already relayed")]
+    Duplicate,
+    #[error("the relayed order pool is full")]
+    PoolFull
+}
+
+/// Vouches for orders relayed from other chains.
+pub trait RelayVerifier: Send + Sync {
+    fn verify(&self, relayed: &RelayedOrder) -> Result<(), RelayError>;
+}
+
+/// Where relayed orders are taken from, and who vouches for them.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RelayConfig {
+    /// the origin deployments orders are taken from
+    pub domains:    Vec<OriginDomain>,
+    pub attesters:  Vec<Address>,
+    /// distinct attesters that have to sign an order
+    pub threshold:  usize,
+    #[serde(default = "default_max_orders")]
+    pub max_orders: usize
+}
+
+fn default_max_orders() -> usize {
+    DEFAULT_MAX_RELAYED_ORDERS
+}
+
+/// Takes orders that `threshold` of the known attesters signed for.
+#[derive(Debug, Clone)]
+pub struct AttestationSet {
+    domains:   HashSet<OriginDomain>,
+    attesters: HashSet<Address>,
+    threshold: usize
+}
+
+impl AttestationSet {
+    pub fn new(
+        domains: impl IntoIterator<Item = OriginDomain>,
+        attesters: impl IntoIterator<Item = Address>,
+        threshold: usize
+    ) -> Self {
+        Self {
+            domains:   domains.into_iter().collect(),
+            attesters: attesters.into_iter().collect(),
+            // an empty attestation set vouches for nothing
+            threshold: threshold.max(1)
+        }
+    }
+}
+
+impl From<&RelayConfig> for AttestationSet {
+    fn from(config: &RelayConfig) -> Self {
+        Self::new(
+            config.domains.iter().copied(),
+            config.attesters.iter().copied(),
+            config.threshold
+        )
+    }
+}
+
+impl RelayVerifier for AttestationSet {
+    fn verify(&self, relayed: &RelayedOrder) -> Result<(), RelayError> {
+        if !self.domains.contains(&relayed.origin) {
+            return Err(RelayError::UnknownDomain(relayed.origin.chain_id))
+        }
+        if !relayed.is_valid_order_signature() {
+            return Err(RelayError::InvalidOrderSignature)
+        }
+
+        let have = relayed
+            .attesters()
+            .filter(|attester| self.attesters.contains(attester))
+            .collect::<HashSet<_>>()
+            .len();
+        if have < self.threshold {
+            return Err(RelayError::NotEnoughAttestations { have, need: self.threshold })
+        }
+
+        Ok(())
+    }
+}
+
+/// The relayed orders, kept apart from the pools of this chain.
+#[derive(Debug)]
+pub struct RelayedOrderPool {
+    orders:   HashMap<B256, RelayedOrder>,
+    max_size: usize
+}
+
+impl RelayedOrderPool {
+    pub fn new(max_size: usize) -> Self {
+        Self { orders: HashMap::new(), max_size }
+    }
+
+    pub fn insert(&mut self, relayed: RelayedOrder) -> Result<B256, RelayError> {
+        let hash = relayed.order_hash();
+        if self.orders.contains_key(&hash) {
+            return Err(RelayError::Duplicate)
+        }
+        if self.orders.len() >= self.max_size {
+            return Err(RelayError::PoolFull)
+        }
+
+        self.orders.insert(hash, relayed);
+        Ok(hash)
+    }
+
+    pub fn remove(&mut self, order_hash: &B256) -> Option<RelayedOrder> {
+        self.orders.remove(order_hash)
+    }
+
+    /// The orders relayed from `chain_id`, or from every chain.
+    pub fn orders(&self, chain_id: Option<u64>) -> Vec<RelayedOrder> {
+        self.orders
+            .values()
+            .filter(|relayed| chain_id.map_or(true, |id| relayed.origin.chain_id == id))
+            .cloned()
+            .collect()
+    }
+
+    /// Drops the orders past their deadline, `now` is the unix timestamp in
+    /// seconds.
+    pub fn prune_expired(&mut self, now: u64) {
+        self.orders.retain(|_, relayed| !is_expired(relayed, now));
+    }
+
+    pub fn len(&self) -> usize {
+        self.orders.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.orders.is_empty()
+    }
+}
+
+fn is_expired(relayed: &RelayedOrder, now: u64) -> bool {
+    relayed
+        .order
+        .deadline()
+        .is_some_and(|deadline| deadline <= U256::from(now))
+}
+
+/// Verifies relayed orders and keeps the ones that pass, see the
+/// [module docs](self).
+pub struct CrossChainIntake {
+    verifier: Box<dyn RelayVerifier>,
+    pool:     Mutex<RelayedOrderPool>
+}
+
+impl CrossChainIntake {
+    pub fn new(verifier: impl RelayVerifier + 'static, max_orders: usize) -> Self {
+        Self {
+            verifier: Box::new(verifier),
+            pool:     Mutex::new(RelayedOrderPool::new(max_orders))
+        }
+    }
+
+    /// Verified with an [`AttestationSet`].
+    pub fn from_config(config: &RelayConfig) -> Self {
+        Self::new(AttestationSet::from(config), config.max_orders)
+    }
+
+    /// Returns the hash of the order on its origin chain.
+    pub fn submit(&self, relayed: RelayedOrder) -> Result<B256, RelayError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if is_expired(&relayed, now) {
+            return Err(RelayError::Expired)
+        }
+        self.verifier.verify(&relayed)?;
+
+        let mut pool = self.pool.lock().expect("poisoned");
+        pool.prune_expired(now);
+        let hash = pool.insert(relayed)?;
+        tracing::debug!(?hash, "took relayed order");
+
+        Ok(hash)
+    }
+
+    pub fn orders(&self, chain_id: Option<u64>) -> Vec<RelayedOrder> {
+        self.pool.lock().expect("poisoned").orders(chain_id)
+    }
+
+    pub fn remove(&self, order_hash: &B256) -> Option<RelayedOrder> {
+        self.pool.lock().expect("poisoned").remove(order_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::SignerSync;
+    use angstrom_types::{primitive::AngstromSigner, sol_bindings::grouped_orders::AllOrders};
+    use testing_tools::type_generator::orders::UserOrderBuilder;
+
+    use super::*;
+
+    fn relayed(origin: OriginDomain, attesters: &[&AngstromSigner]) -> RelayedOrder {
+        let order = UserOrderBuilder::new()
+            .standing()
+            .deadline(U256::from(u32::MAX))
+            .signing_key(Some(AngstromSigner::random()))
+            .domain(origin.eip712_domain())
+            .build();
+        let mut relayed = RelayedOrder {
+            origin,
+            source_block: 100,
+            order: AllOrders::from(order),
+            attestations: vec![]
+        };
+        let hash = relayed.attestation_hash();
+        relayed.attestations = attesters
+            .iter()
+            .map(|attester| attester.sign_hash_sync(&hash).unwrap())
+            .collect();
+
+        relayed
+    }
+
+    #[test]
+    fn orders_need_a_threshold_of_known_attesters() {
+        let origin =
+            OriginDomain { chain_id: 8453, angstrom_address: Address::with_last_byte(1) };
+        let (a, b, unknown) =
+            (AngstromSigner::random(), AngstromSigner::random(), AngstromSigner::random());
+        let intake = CrossChainIntake::new(
+            AttestationSet::new([origin], [a.address(), b.address()], 2),
+            DEFAULT_MAX_RELAYED_ORDERS
+        );
+
+        assert_eq!(
+            intake.submit(relayed(origin, &[&a, &a, &unknown])),
+            Err(RelayError::NotEnoughAttestations { have: 1, need: 2 })
+        );
+        let other_chain = OriginDomain { chain_id: 10, ..origin };
+        assert_eq!(
+            intake.submit(relayed(other_chain, &[&a, &b])),
+            Err(RelayError::UnknownDomain(10))
+        );
+
+        let order = relayed(origin, &[&a, &b]);
+        let hash = intake.submit(order.clone()).unwrap();
+        assert_eq!(hash, order.order_hash());
+        assert_eq!(intake.submit(order.clone()), Err(RelayError::Duplicate));
+        assert_eq!(intake.orders(Some(8453)), vec![order]);
+        assert!(intake.orders(Some(10)).is_empty());
+    }
+}
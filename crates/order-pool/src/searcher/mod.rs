@@ -76,10 +76,11 @@ impl SearcherPool {
         }
 
         let pool_id = order.pool_id;
-        self.searcher_orders
-            .get_mut(&pool_id)
-            .ok_or_else(|| SearcherPoolError::NoPool(pool_id))?
-            .add_order(order);
+        let Some(pool) = self.searcher_orders.get_mut(&pool_id) else {
+            self.size.remove_order(size);
+            return Err(SearcherPoolError::NoPool(pool_id))
+        };
+        pool.add_order(order);
 
         self.metrics.incr_all_orders(pool_id, 1);
 
@@ -90,6 +91,7 @@ impl SearcherPool {
         self.searcher_orders
             .get_mut(&id.pool_id)
             .and_then(|pool| pool.remove_order(id.hash))
+            .inspect(|order| self.size.remove_order(order.size()))
             .owned_map(|| self.metrics.decr_all_orders(id.pool_id, 1))
     }
 
@@ -121,8 +123,33 @@ impl SearcherPool {
         assert!(old_is_none);
     }
 
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.size.set_max(Some(max_size));
+    }
+
     pub fn remove_pool(&mut self, key: &PoolId) {
-        let _ = self.searcher_orders.remove(key);
+        if let Some(pool) = self.searcher_orders.remove(key) {
+            self.size.remove_order(pool.size());
+        }
+    }
+
+    /// The size the tracker accounts for.
+    pub fn tracked_size(&self) -> usize {
+        self.size.current
+    }
+
+    /// The size of the orders actually held.
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn held_size(&self) -> usize {
+        self.searcher_orders.values().map(|pool| pool.size()).sum()
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn order_hashes(&self) -> Vec<B256> {
+        self.searcher_orders
+            .values()
+            .flat_map(|pool| pool.order_hashes())
+            .collect()
     }
 }
 
@@ -132,6 +159,8 @@ pub enum SearcherPoolError {
     MaxSize,
     #[error("No pool was found for address: {0} ")]
     NoPool(PoolId),
+    #[error("an order with hash {0:?} is already stored")]
+    DuplicateOrder(B256),
     #[error(transparent)]
     Unknown(#[from] eyre::Error)
 }
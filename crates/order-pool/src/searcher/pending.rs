@@ -60,4 +60,14 @@ impl PendingPool {
         // TODO:  This should maybe only return the one best Searcher order we've seen?
         self.orders.values().cloned().collect()
     }
+
+    /// Size of all the orders held.
+    pub fn size(&self) -> usize {
+        self.orders.values().map(|order| order.size()).sum()
+    }
+
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn order_hashes(&self) -> impl Iterator<Item = FixedBytes<32>> + '_ {
+        self.orders.keys().copied()
+    }
 }
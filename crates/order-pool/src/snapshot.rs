@@ -0,0 +1,120 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufReader, BufWriter, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf}
+};
+
+use alloy::primitives::{BlockNumber, B256};
+use angstrom_types::sol_bindings::{grouped_orders::AllOrders, RawPoolOrder};
+use serde::{Deserialize, Serialize};
+
+/// The orders pending in the pool along with the block they were pending at.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderSnapshot {
+    /// last block the pool processed before the snapshot was taken
+    pub block_number: BlockNumber,
+    pub orders:       Vec<AllOrders>
+}
+
+impl OrderSnapshot {
+    /// Blocks that were built since the snapshot was taken, up to and
+    /// including `current_block`.
+    pub fn missed_blocks(&self, current_block: BlockNumber) -> Option<RangeInclusive<BlockNumber>> {
+        (self.block_number < current_block).then_some(self.block_number + 1..=current_block)
+    }
+
+    /// Drops the orders that were filled in the missed blocks along with flash
+    /// orders whose block has passed. Returns how many were dropped.
+    pub fn prune(&mut self, filled_orders: &HashSet<B256>, current_block: BlockNumber) -> usize {
+        let before = self.orders.len();
+        self.orders.retain(|order| {
+            !filled_orders.contains(&order.order_hash())
+                && !matches!(order.flash_block(), Some(block) if block <= current_block)
+        });
+
+        before - self.orders.len()
+    }
+}
+
+/// Writes the orders pending in the pool to `path`, so that they can be put
+/// back into the pool after a restart. The file is only replaced once the new
+/// snapshot is fully on disk.
+pub fn write_order_snapshot(
+    path: impl AsRef<Path>,
+    snapshot: &OrderSnapshot
+) -> std::io::Result<()> {
+    let path = path.as_ref();
+    let tmp = PathBuf::from(format!("{}.tmp", path.display()));
+
+    let mut writer = BufWriter::new(File::create(&tmp)?);
+    serde_json::to_writer(&mut writer, snapshot)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    std::fs::rename(&tmp, path)
+}
+
+/// Reads a snapshot written with [`write_order_snapshot`]. The orders still
+/// have to be revalidated, as the state they were validated against is gone.
+pub fn read_order_snapshot(path: impl AsRef<Path>) -> std::io::Result<OrderSnapshot> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(serde_json::from_reader(reader)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::sol_bindings::{
+        grouped_orders::{FlashVariants, StandingVariants},
+        rpc_orders::{ExactFlashOrder, ExactStandingOrder}
+    };
+
+    use super::*;
+
+    #[test]
+    fn snapshot_round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("orders.json");
+        let snapshot = OrderSnapshot {
+            block_number: 10,
+            orders:       vec![
+                AllOrders::Standing(StandingVariants::Exact(Default::default())),
+                AllOrders::Flash(FlashVariants::Partial(Default::default())),
+                AllOrders::TOB(Default::default()),
+            ]
+        };
+
+        write_order_snapshot(&path, &snapshot).unwrap();
+        assert_eq!(read_order_snapshot(&path).unwrap(), snapshot);
+
+        // a second snapshot replaces the first one
+        let empty = OrderSnapshot { block_number: 11, orders: vec![] };
+        write_order_snapshot(&path, &empty).unwrap();
+        assert_eq!(read_order_snapshot(&path).unwrap(), empty);
+    }
+
+    #[test]
+    fn prunes_filled_and_expired_orders() {
+        let standing = |nonce| {
+            AllOrders::Standing(StandingVariants::Exact(ExactStandingOrder {
+                nonce,
+                ..Default::default()
+            }))
+        };
+        let flash = |valid_for_block| {
+            AllOrders::Flash(FlashVariants::Exact(ExactFlashOrder {
+                valid_for_block,
+                ..Default::default()
+            }))
+        };
+        let orders = vec![standing(1), standing(2), flash(12), flash(13)];
+
+        let mut snapshot = OrderSnapshot { block_number: 10, orders: orders.clone() };
+        assert_eq!(snapshot.missed_blocks(12), Some(11..=12));
+        assert_eq!(snapshot.missed_blocks(10), None);
+
+        let filled = HashSet::from([orders[0].order_hash()]);
+        assert_eq!(snapshot.prune(&filled, 12), 2);
+        assert_eq!(snapshot.orders, vec![orders[1].clone(), orders[3].clone()]);
+    }
+}
@@ -0,0 +1,63 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex}
+};
+
+use alloy::primitives::B256;
+use angstrom_metrics::SourceTagMetricsWrapper;
+use angstrom_types::orders::SourceTag;
+
+/// The source tags of the orders in the pool, by order hash. A tag is kept for
+/// as long as its order is stored, and counted as it is submitted, added to
+/// the pool and filled.
+#[derive(Clone, Default)]
+pub struct SourceTags {
+    tags:    Arc<Mutex<HashMap<B256, SourceTag>>>,
+    metrics: SourceTagMetricsWrapper
+}
+
+impl std::fmt::Debug for SourceTags {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SourceTags")
+            .field("tags", &self.tags)
+            .finish()
+    }
+}
+
+impl SourceTags {
+    pub fn get(&self, order_hash: &B256) -> Option<SourceTag> {
+        self.tags.lock().expect("poisoned").get(order_hash).cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tags.lock().expect("poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn submitted(&self, source_tag: &SourceTag) {
+        self.metrics.incr_submitted(source_tag.as_str());
+    }
+
+    /// Tags an order that is added to the pool.
+    pub(crate) fn insert(&self, order_hash: B256, source_tag: SourceTag) {
+        self.metrics.incr_accepted(source_tag.as_str());
+        self.tags
+            .lock()
+            .expect("poisoned")
+            .insert(order_hash, source_tag);
+    }
+
+    pub(crate) fn remove(&self, order_hash: &B256) -> Option<SourceTag> {
+        self.tags.lock().expect("poisoned").remove(order_hash)
+    }
+
+    /// Counts the fill of an order, before it leaves the pool.
+    pub(crate) fn filled(&self, order_hash: &B256) {
+        if let Some(source_tag) = self.get(order_hash) {
+            self.metrics.incr_filled(source_tag.as_str());
+        }
+    }
+}
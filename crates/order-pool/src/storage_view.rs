@@ -0,0 +1,201 @@
+use std::{collections::HashMap, sync::Arc};
+
+use alloy::primitives::{Address, B256};
+use angstrom_types::{
+    orders::{BookSnapshot, OrderLocation, OrderSet, OrderStatus},
+    primitive::PoolId,
+    sol_bindings::{
+        grouped_orders::{
+            AllOrders, GroupedComposableOrder, GroupedVanillaOrder, OrderWithStorageData
+        },
+        rpc_orders::TopOfBlockOrder
+    }
+};
+use arc_swap::ArcSwap;
+
+/// The orders of a single pool as of the last change to them. A published view
+/// never changes, so readers can hold on to it for as long as they like.
+#[derive(Debug, Clone)]
+pub struct PoolView {
+    /// pending vanilla limit orders, the ones that are matched
+    pub limit:      Vec<OrderWithStorageData<GroupedVanillaOrder>>,
+    /// limit orders waiting on the balance or approval of their user
+    pub parked:     Vec<OrderWithStorageData<GroupedVanillaOrder>>,
+    pub composable: Vec<OrderWithStorageData<GroupedComposableOrder>>,
+    pub searcher:   Vec<OrderWithStorageData<TopOfBlockOrder>>,
+    pub book:       BookSnapshot,
+    statuses:       HashMap<B256, OrderStatus>
+}
+
+impl PoolView {
+    pub fn new(
+        limit: Vec<OrderWithStorageData<GroupedVanillaOrder>>,
+        parked: Vec<OrderWithStorageData<GroupedVanillaOrder>>,
+        composable: Vec<OrderWithStorageData<GroupedComposableOrder>>,
+        searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>,
+        book: BookSnapshot
+    ) -> Self {
+        let statuses = limit
+            .iter()
+            .map(|order| (order.order_id.hash, OrderStatus::Pending))
+            .chain(
+                searcher
+                    .iter()
+                    .map(|order| (order.order_id.hash, OrderStatus::Pending))
+            )
+            .chain(
+                parked
+                    .iter()
+                    .map(|order| (order.order_id.hash, OrderStatus::Blocked))
+            )
+            .collect();
+
+        Self { limit, parked, composable, searcher, book, statuses }
+    }
+
+    /// The status of a pending or parked order of the pool, composable orders
+    /// have none.
+    pub fn order_status(&self, order_hash: &B256) -> Option<OrderStatus> {
+        self.statuses.get(order_hash).copied()
+    }
+
+    /// The pending orders of one sub-pool.
+    pub fn orders_at(&self, location: OrderLocation) -> Vec<AllOrders> {
+        match location {
+            OrderLocation::Limit => self
+                .limit
+                .iter()
+                .map(|order| order.order.clone().into())
+                .collect(),
+            OrderLocation::Searcher => self
+                .searcher
+                .iter()
+                .map(|order| order.order.clone().into())
+                .collect()
+        }
+    }
+
+    /// Every order of the pool, parked and composable ones included.
+    pub fn orders(&self) -> impl Iterator<Item = OrderWithStorageData<AllOrders>> + '_ {
+        fn erased<O: Into<AllOrders> + Clone>(
+            order: &OrderWithStorageData<O>
+        ) -> Option<OrderWithStorageData<AllOrders>> {
+            order.clone().try_map_inner(|inner| Ok(inner.into())).ok()
+        }
+
+        self.limit
+            .iter()
+            .chain(&self.parked)
+            .filter_map(erased)
+            .chain(self.composable.iter().filter_map(erased))
+            .chain(self.searcher.iter().filter_map(erased))
+    }
+
+    /// The searcher order with the highest reward, the only one that is
+    /// matched.
+    pub fn top_tob(&self) -> Option<&OrderWithStorageData<TopOfBlockOrder>> {
+        self.searcher.iter().max_by_key(|order| order.tob_reward)
+    }
+}
+
+/// Reads of the [`OrderStorage`](crate::order_storage::OrderStorage) that never
+/// lock or go through the pool manager. The storage publishes a new
+/// [`PoolView`] of a pool after every change to its orders, readers load
+/// whichever is current. Changes to the orders still go through the pool
+/// manager.
+#[derive(Debug, Clone, Default)]
+pub struct OrderStorageView {
+    pools: Arc<ArcSwap<HashMap<PoolId, Arc<PoolView>>>>
+}
+
+impl OrderStorageView {
+    pub fn pool(&self, pool_id: &PoolId) -> Option<Arc<PoolView>> {
+        self.pools.load().get(pool_id).cloned()
+    }
+
+    /// The views of all pools, as of the same moment.
+    pub fn pools(&self) -> Arc<HashMap<PoolId, Arc<PoolView>>> {
+        self.pools.load_full()
+    }
+
+    pub(crate) fn publish(&self, pool_id: PoolId, view: PoolView) {
+        let view = Arc::new(view);
+        self.pools.rcu(|pools| {
+            let mut pools = HashMap::clone(pools);
+            pools.insert(pool_id, view.clone());
+            pools
+        });
+    }
+
+    pub(crate) fn remove(&self, pool_id: &PoolId) {
+        self.pools.rcu(|pools| {
+            let mut pools = HashMap::clone(pools);
+            pools.remove(pool_id);
+            pools
+        });
+    }
+
+    pub fn orders_by_pool(&self, pool_id: &PoolId, location: OrderLocation) -> Vec<AllOrders> {
+        self.pool(pool_id)
+            .map(|pool| pool.orders_at(location))
+            .unwrap_or_default()
+    }
+
+    pub fn order_status(&self, order_hash: &B256) -> Option<OrderStatus> {
+        self.pools
+            .load()
+            .values()
+            .find_map(|pool| pool.order_status(order_hash))
+    }
+
+    /// Every order of `address`, in any pool.
+    pub fn pending_orders(&self, address: Address) -> Vec<AllOrders> {
+        self.pools
+            .load()
+            .values()
+            .flat_map(|pool| {
+                pool.orders()
+                    .filter(|order| order.order_id.address == address)
+                    .map(|order| order.order)
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    pub fn all_orders(&self) -> Vec<AllOrders> {
+        self.pools
+            .load()
+            .values()
+            .flat_map(|pool| pool.orders().map(|order| order.order).collect::<Vec<_>>())
+            .collect()
+    }
+
+    /// The top searcher order of every pool.
+    pub fn top_tob_orders(&self) -> Vec<OrderWithStorageData<TopOfBlockOrder>> {
+        self.pools
+            .load()
+            .values()
+            .filter_map(|pool| pool.top_tob().cloned())
+            .collect()
+    }
+
+    /// The orders that are matched: the pending vanilla limit orders of every
+    /// pool along with its top searcher order.
+    pub fn order_set(&self) -> OrderSet<GroupedVanillaOrder, TopOfBlockOrder> {
+        let pools = self.pools.load();
+        OrderSet {
+            limit:    pools
+                .values()
+                .flat_map(|pool| pool.limit.iter().cloned())
+                .collect(),
+            searcher: pools
+                .values()
+                .filter_map(|pool| pool.top_tob().cloned())
+                .collect()
+        }
+    }
+
+    pub fn book(&self, pool_id: &PoolId) -> Option<BookSnapshot> {
+        self.pool(pool_id).map(|pool| pool.book.clone())
+    }
+}
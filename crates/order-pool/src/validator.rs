@@ -5,13 +5,20 @@ use std::{
 };
 
 use alloy::primitives::{Address, B256};
-use angstrom_types::{orders::OrderOrigin, sol_bindings::grouped_orders::AllOrders};
+use angstrom_types::{
+    orders::OrderOrigin,
+    sol_bindings::grouped_orders::{AllOrders, StandingVariants}
+};
 use futures_util::{stream::FuturesUnordered, Future, FutureExt, Stream, StreamExt};
 use tracing::info;
 use validation::order::{OrderValidationResults, OrderValidatorHandle};
 
 type ValidationFuture = Pin<Box<dyn Future<Output = OrderValidationResults> + Send + Sync>>;
 
+/// An order held back while the pool moves to a new block, along with the
+/// order it amends, if any.
+type PendingValidation = (OrderOrigin, AllOrders, Option<B256>);
+
 pub enum OrderValidator<V: OrderValidatorHandle> {
     /// Waits for all current processing to be completed. This allows us
     /// to have all orders for the previous block be indexed properly so that
@@ -21,7 +28,7 @@ pub enum OrderValidator<V: OrderValidatorHandle> {
     ClearingForNewBlock {
         validator:              V,
         block_number:           u64,
-        waiting_for_new_block:  VecDeque<(OrderOrigin, AllOrders)>,
+        waiting_for_new_block:  VecDeque<PendingValidation>,
         /// all order hashes that have been filled or expired.
         completed_orders:       Vec<B256>,
         /// all addresses that we need to invalidate the cache for balances /
@@ -32,7 +39,7 @@ pub enum OrderValidator<V: OrderValidatorHandle> {
     /// waits for storage to go through and purge all invalided orders.
     WaitingForStorageCleanup {
         validator:             V,
-        waiting_for_new_block: VecDeque<(OrderOrigin, AllOrders)>
+        waiting_for_new_block: VecDeque<PendingValidation>
     },
     /// The inform state is telling the validation client to
     /// progress a block and the cache segments it should remove + pending order
@@ -40,7 +47,7 @@ pub enum OrderValidator<V: OrderValidatorHandle> {
     /// the order validator has the correct state and thus can progress.
     InformState {
         validator:             V,
-        waiting_for_new_block: VecDeque<(OrderOrigin, AllOrders)>,
+        waiting_for_new_block: VecDeque<PendingValidation>,
         future:                ValidationFuture
     },
     RegularProcessing {
@@ -57,6 +64,15 @@ where
         Self::RegularProcessing { validator, remaining_futures: FuturesUnordered::new() }
     }
 
+    pub fn handle(&self) -> &V {
+        match self {
+            Self::ClearingForNewBlock { validator, .. }
+            | Self::WaitingForStorageCleanup { validator, .. }
+            | Self::InformState { validator, .. }
+            | Self::RegularProcessing { validator, .. } => validator
+        }
+    }
+
     pub fn on_new_block(
         &mut self,
         block_number: u64,
@@ -115,20 +131,40 @@ where
     }
 
     pub fn validate_order(&mut self, origin: OrderOrigin, order: AllOrders) {
+        self.validate(origin, order, None)
+    }
+
+    /// Validates `order` in place of the pending order `replaces`.
+    pub fn validate_amendment(
+        &mut self,
+        origin: OrderOrigin,
+        order: StandingVariants,
+        replaces: B256
+    ) {
+        self.validate(origin, AllOrders::Standing(order), Some(replaces))
+    }
+
+    fn validate(&mut self, origin: OrderOrigin, order: AllOrders, replaces: Option<B256>) {
         match self {
             Self::RegularProcessing { remaining_futures, validator } => {
                 let val = validator.clone();
-                remaining_futures
-                    .push(Box::pin(async move { val.validate_order(origin, order).await }))
+                remaining_futures.push(Box::pin(async move {
+                    match (order, replaces) {
+                        (AllOrders::Standing(order), Some(replaces)) => {
+                            val.validate_amendment(origin, order, replaces).await
+                        }
+                        (order, _) => val.validate_order(origin, order).await
+                    }
+                }))
             }
             Self::WaitingForStorageCleanup { waiting_for_new_block, .. } => {
-                waiting_for_new_block.push_back((origin, order));
+                waiting_for_new_block.push_back((origin, order, replaces));
             }
             Self::ClearingForNewBlock { waiting_for_new_block, .. } => {
-                waiting_for_new_block.push_back((origin, order));
+                waiting_for_new_block.push_back((origin, order, replaces));
             }
             Self::InformState { waiting_for_new_block, .. } => {
-                waiting_for_new_block.push_back((origin, order));
+                waiting_for_new_block.push_back((origin, order, replaces));
             }
         }
     }
@@ -137,9 +173,14 @@ where
         matches!(self, Self::ClearingForNewBlock { .. } | Self::InformState { .. })
     }
 
+    #[cfg(any(test, feature = "fuzzing"))]
+    pub fn is_regular_processing(&self) -> bool {
+        matches!(self, Self::RegularProcessing { .. })
+    }
+
     fn handle_inform(
         validator: &mut V,
-        waiting_for_new_block: &mut VecDeque<(OrderOrigin, AllOrders)>,
+        waiting_for_new_block: &mut VecDeque<PendingValidation>,
         future: &mut ValidationFuture,
         cx: &mut Context<'_>
     ) -> Option<Self> {
@@ -150,9 +191,11 @@ where
                 validator:         validator_clone,
                 remaining_futures: FuturesUnordered::default()
             };
-            waiting_for_new_block.drain(..).for_each(|(origin, order)| {
-                this.validate_order(origin, order);
-            });
+            waiting_for_new_block
+                .drain(..)
+                .for_each(|(origin, order, replaces)| {
+                    this.validate(origin, order, replaces);
+                });
 
             return Some(this)
         }
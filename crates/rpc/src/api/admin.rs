@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use angstrom_metrics::AllocationReport;
+use angstrom_types::primitive::{Feature, PoolId, PoolLimits};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "admin"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "admin"))]
+#[async_trait::async_trait]
+pub trait AdminApi {
+    /// Replaces the log filter, using the same directives as `RUST_LOG`. It
+    /// applies on top of the filters of the `--log.*` flags
+    #[method(name = "setLogLevel")]
+    async fn set_log_level(&self, directives: String) -> RpcResult<()>;
+
+    /// Changes the maximum size of the order sub-pools
+    #[method(name = "updatePoolLimits")]
+    async fn update_pool_limits(&self, limits: PoolLimits) -> RpcResult<()>;
+
+    /// Replaces the endpoints bundles are submitted to
+    #[method(name = "updateSubmissionTargets")]
+    async fn update_submission_targets(&self, urls: Vec<String>) -> RpcResult<()>;
+
+    /// Stops, or resumes, accepting new orders from users and peers
+    #[method(name = "pauseOrderIntake")]
+    async fn pause_order_intake(&self, paused: bool) -> RpcResult<()>;
+
+    /// Resumes matching for a pool that was paused by its circuit breaker
+    #[method(name = "resumePool")]
+    async fn resume_pool(&self, pool: PoolId) -> RpcResult<()>;
+
+    /// Forgets the bundle this node submitted for a block height, so that it
+    /// can submit one for it again. Only for recovery, when the recorded bundle
+    /// is known to never have reached the chain
+    #[method(name = "clearSubmission")]
+    async fn clear_submission(&self, height: u64) -> RpcResult<()>;
+
+    /// The allocator stats and what the matcher and bundle encoding allocated
+    #[method(name = "allocationStats")]
+    async fn allocation_stats(&self) -> RpcResult<AllocationReport>;
+
+    /// Dumps the jemalloc heap profile, returns the file it was written to
+    #[method(name = "dumpHeapProfile")]
+    async fn dump_heap_profile(&self) -> RpcResult<String>;
+
+    /// Whether each of the run-time features is enabled
+    #[method(name = "featureFlags")]
+    async fn feature_flags(&self) -> RpcResult<BTreeMap<Feature, bool>>;
+
+    /// Enables, or disables, a run-time feature, returns whether it was
+    /// enabled before
+    #[method(name = "setFeatureFlag")]
+    async fn set_feature_flag(&self, feature: Feature, enabled: bool) -> RpcResult<bool>;
+}
@@ -0,0 +1,37 @@
+use alloy_primitives::BlockNumber;
+use angstrom_types::{
+    contract_payloads::angstrom::FeeLedger, mev_boost::SubmissionTargetStats, primitive::PeerId
+};
+use consensus::{ConsensusRoundInfo, LeaderSlot, ValidatorTelemetrySummary};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "consensus"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "consensus"))]
+#[async_trait::async_trait]
+pub trait ConsensusApi {
+    /// Height, phase, leader and message counts of the current consensus round
+    #[method(name = "roundInfo")]
+    async fn round_info(&self) -> RpcResult<ConsensusRoundInfo>;
+
+    /// The leader of the given height
+    #[method(name = "getLeader")]
+    async fn get_leader(&self, height: BlockNumber) -> RpcResult<PeerId>;
+
+    /// The leaders of `count` consecutive heights starting at `from`
+    #[method(name = "leaderSchedule")]
+    async fn leader_schedule(&self, from: BlockNumber, count: u64) -> RpcResult<Vec<LeaderSlot>>;
+
+    /// Protocol fees saved by the bundles this node landed, by asset
+    #[method(name = "feeLedger")]
+    async fn fee_ledger(&self) -> RpcResult<FeeLedger>;
+
+    /// Solve time, book size and validation latency of the validators that
+    /// share their telemetry, this node included, over the last blocks
+    #[method(name = "validatorTelemetry")]
+    async fn validator_telemetry(&self) -> RpcResult<Vec<ValidatorTelemetrySummary>>;
+
+    /// Success rate, latency and inclusion rate of each endpoint bundles are
+    /// submitted to. Unhealthy ones are submitted to last
+    #[method(name = "submissionTargets")]
+    async fn submission_targets(&self) -> RpcResult<Vec<SubmissionTargetStats>>;
+}
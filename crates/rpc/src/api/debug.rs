@@ -0,0 +1,21 @@
+use angstrom_types::{contract_payloads::angstrom::BundleTrace, primitive::PoolId};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+use crate::types::HistoricalPoolSnapshot;
+
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "debug"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "debug"))]
+#[async_trait::async_trait]
+pub trait DebugApi {
+    /// The bundle settled in the given block, order by order, along with the
+    /// outcome of simulating it again. `None` if no bundle was settled in it
+    #[method(name = "getBundleTrace")]
+    async fn bundle_trace(&self, block: u64) -> RpcResult<Option<BundleTrace>>;
+
+    /// The uniswap pool and the orders resting in its book once the given
+    /// block was built, for backtesting against. Only served by nodes that
+    /// archive their orders
+    #[method(name = "getPoolSnapshot")]
+    async fn pool_snapshot(&self, pool_id: PoolId, block: u64)
+        -> RpcResult<HistoricalPoolSnapshot>;
+}
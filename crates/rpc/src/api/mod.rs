@@ -1,5 +1,17 @@
+mod admin;
+mod consensus;
+mod debug;
+mod network;
 mod orders;
 mod quoting;
+mod relay;
+mod searcher;
 
+pub use admin::*;
+pub use consensus::*;
+pub use debug::*;
+pub use network::*;
 pub use orders::*;
 pub use quoting::*;
+pub use relay::*;
+pub use searcher::*;
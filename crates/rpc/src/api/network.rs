@@ -0,0 +1,17 @@
+use angstrom_network::{NetworkEventRecord, PeerStats};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "network"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "network"))]
+#[async_trait::async_trait]
+pub trait NetworkApi {
+    /// The connected Strom peers, with their protocol version, reputation and
+    /// the messages exchanged with them by type
+    #[method(name = "peers")]
+    async fn peers(&self) -> RpcResult<Vec<PeerStats>>;
+
+    /// The logged connects and disconnects after the event numbered `since`.
+    /// Pass the last `seq` seen to only get the new ones
+    #[method(name = "events")]
+    async fn events(&self, since: u64) -> RpcResult<Vec<NetworkEventRecord>>;
+}
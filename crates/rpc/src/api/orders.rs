@@ -1,11 +1,15 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, ops::Range};
 
 use alloy_primitives::{Address, B256, U256};
 use angstrom_types::{
-    orders::{CancelOrderRequest, OrderLocation, OrderStatus},
+    orders::{
+        CancelAllOrdersRequest, CancelOrderRequest, OrderAmendment, OrderLocation, OrderStatus,
+        SourceTag
+    },
     primitive::{OrderPoolNewOrderResult, PoolId},
     sol_bindings::grouped_orders::AllOrders
 };
+use consensus::ShadowMatch;
 use futures::StreamExt;
 use jsonrpsee::{
     core::{RpcResult, Serialize},
@@ -13,7 +17,7 @@ use jsonrpsee::{
 };
 use serde::Deserialize;
 
-use crate::types::{OrderSubscriptionFilter, OrderSubscriptionKind};
+use crate::types::{OrderSubscriptionFilter, OrderSubscriptionKind, SessionOrder};
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct GasEstimateResponse {
@@ -21,13 +25,34 @@ pub struct GasEstimateResponse {
     pub gas:       U256
 }
 
+/// What submitting an order would do, without it being submitted.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OrderSimulation {
+    pub gas_units:          u64,
+    /// the gas the order would be charged, in token0 of its pool
+    pub gas:                U256,
+    /// whether its user has the balance and approval for it now, the order is
+    /// kept until they do otherwise
+    pub is_currently_valid: bool,
+    /// how it would be matched against the current book, `None` on nodes that
+    /// don't take part in consensus
+    pub outcome:            Option<ShadowMatch>
+}
+
 #[cfg_attr(not(feature = "client"), rpc(server, namespace = "angstrom"))]
 #[cfg_attr(feature = "client", rpc(server, client, namespace = "angstrom"))]
 #[async_trait::async_trait]
 pub trait OrderApi {
-    /// Submit any type of order
+    /// Submit any type of order. `source_tag` attributes the order to the dapp
+    /// or frontend it was submitted through. With `session` the order is
+    /// cancelled once that session closes.
     #[method(name = "sendOrder")]
-    async fn send_order(&self, order: AllOrders) -> RpcResult<OrderPoolNewOrderResult>;
+    async fn send_order(
+        &self,
+        order: AllOrders,
+        source_tag: Option<SourceTag>,
+        session: Option<SessionOrder>
+    ) -> RpcResult<OrderPoolNewOrderResult>;
 
     #[method(name = "pendingOrder")]
     async fn pending_order(&self, from: Address) -> RpcResult<Vec<AllOrders>>;
@@ -35,12 +60,42 @@ pub trait OrderApi {
     #[method(name = "cancelOrder")]
     async fn cancel_order(&self, request: CancelOrderRequest) -> RpcResult<bool>;
 
+    /// Cancel all pending orders of an account, or only the ones in a pool,
+    /// returning the hashes of the cancelled orders. Standing orders signed
+    /// with a nonce from the request's on aren't affected.
+    #[method(name = "cancelAllOrders")]
+    async fn cancel_all_orders(&self, request: CancelAllOrdersRequest) -> RpcResult<Vec<B256>>;
+
+    /// Change the amount of a pending standing order. The amended order keeps
+    /// its place in the book unless the amount is raised.
+    #[method(name = "amendOrder")]
+    async fn amend_order(&self, amendment: OrderAmendment) -> RpcResult<OrderPoolNewOrderResult>;
+
     #[method(name = "estimateGas")]
     async fn estimate_gas(&self, order: AllOrders) -> RpcResult<GasEstimateResponse>;
 
+    /// Validates the order and matches it against the current book of its
+    /// pool without submitting it. Nothing changes for the pending orders of
+    /// its user.
+    #[method(name = "simulateOrder")]
+    async fn simulate_order(&self, order: AllOrders) -> RpcResult<OrderSimulation>;
+
     #[method(name = "orderStatus")]
     async fn order_status(&self, order_hash: B256) -> RpcResult<Option<OrderStatus>>;
 
+    /// The lowest nonce that is neither used on chain nor by a pending order
+    /// of `address`, for the next standing order it signs
+    #[method(name = "getNextValidNonce")]
+    async fn get_next_valid_nonce(&self, address: Address) -> RpcResult<u64>;
+
+    /// The nonces in `range` that `address` used or invalidated on chain
+    #[method(name = "getInvalidatedNonces")]
+    async fn get_invalidated_nonces(
+        &self,
+        address: Address,
+        range: Range<u64>
+    ) -> RpcResult<Vec<u64>>;
+
     #[method(name = "ordersByPair")]
     async fn orders_by_pool_id(
         &self,
@@ -59,11 +114,36 @@ pub trait OrderApi {
         filters: HashSet<OrderSubscriptionFilter>
     ) -> jsonrpsee::core::SubscriptionResult;
 
+    /// Opens an order session, announcing its id. The session orders
+    /// submitted to it are cancelled once the subscription ends, e.g. because
+    /// the connection dropped.
+    #[subscription(
+        name = "subscribeSession",
+        unsubscribe = "unsubscribeSession",
+        item = u64
+    )]
+    async fn subscribe_session(&self) -> jsonrpsee::core::SubscriptionResult;
+
+    /// Level changes to the book of a pool, starting with a snapshot of it
+    #[subscription(
+        name = "subscribeBook",
+        unsubscribe = "unsubscribeBook",
+        item = crate::types::subscriptions::BookSubscriptionResult
+    )]
+    async fn subscribe_book(&self, pool_id: PoolId) -> jsonrpsee::core::SubscriptionResult;
+
     // MULTI CALL
     #[method(name = "sendOrders")]
-    async fn send_orders(&self, orders: Vec<AllOrders>) -> RpcResult<Vec<OrderPoolNewOrderResult>> {
+    async fn send_orders(
+        &self,
+        orders: Vec<AllOrders>,
+        source_tag: Option<SourceTag>
+    ) -> RpcResult<Vec<OrderPoolNewOrderResult>> {
         futures::stream::iter(orders.into_iter())
-            .map(|order| async { self.send_order(order).await })
+            .map(|order| {
+                let source_tag = source_tag.clone();
+                async { self.send_order(order, source_tag, None).await }
+            })
             .buffered(3)
             .collect::<Vec<_>>()
             .await
@@ -11,7 +11,7 @@ pub trait QuotingApi {
     #[subscription(
         name = "subscribe_gas_estimates", 
         unsubscribe = "unsubscribe_gas_estimates",
-        item = crate::types::quoting::GasEstimateUpdate
+        item = crate::types::quoting::GasQuote
     )]
     async fn subscribe_gas_estimates(
         &self,
@@ -0,0 +1,17 @@
+use alloy_primitives::B256;
+use angstrom_types::orders::RelayedOrder;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "relay"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "relay"))]
+#[async_trait::async_trait]
+pub trait RelayApi {
+    /// Submit an order relayed from the deployment of another chain, returns
+    /// its hash there
+    #[method(name = "sendOrder")]
+    async fn send_order(&self, relayed: RelayedOrder) -> RpcResult<B256>;
+
+    /// The relayed orders held, of the given origin chain or of all of them
+    #[method(name = "orders")]
+    async fn orders(&self, chain_id: Option<u64>) -> RpcResult<Vec<RelayedOrder>>;
+}
@@ -0,0 +1,45 @@
+use std::collections::HashSet;
+
+use angstrom_types::{
+    primitive::{OrderPoolNewOrderResult, PoolId},
+    sol_bindings::rpc_orders::TopOfBlockOrder
+};
+use consensus::ProvisionalWinner;
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use validation::common::SettlementCostEstimate;
+
+#[cfg_attr(not(feature = "client"), rpc(server, namespace = "searcher"))]
+#[cfg_attr(feature = "client", rpc(server, client, namespace = "searcher"))]
+#[async_trait::async_trait]
+pub trait SearcherApi {
+    /// Submit a top of block order
+    #[method(name = "sendTobOrder")]
+    async fn send_tob_order(&self, order: TopOfBlockOrder) -> RpcResult<OrderPoolNewOrderResult>;
+
+    /// The best bid for the pool out of the pre-proposals seen this round.
+    /// None until the bid cutoff has passed.
+    #[method(name = "provisionalWinner")]
+    async fn provisional_winner(&self, pool_id: PoolId) -> RpcResult<Option<ProvisionalWinner>>;
+
+    /// The best bid of every pool out of the pre-proposals seen this round
+    #[method(name = "provisionalWinners")]
+    async fn provisional_winners(&self) -> RpcResult<Vec<ProvisionalWinner>>;
+
+    /// What settling the next bundle is expected to cost, from the gas recent
+    /// bundles took and the next block's base fee. None until a bundle has
+    /// been simulated.
+    #[method(name = "estimatedSettlementCost")]
+    async fn estimated_settlement_cost(&self) -> RpcResult<Option<SettlementCostEstimate>>;
+
+    /// Auction results of the given pools, or all of them if empty, as each
+    /// proposal comes in
+    #[subscription(
+        name = "subscribeAuctionResults",
+        unsubscribe = "unsubscribeAuctionResults",
+        item = consensus::AuctionResult
+    )]
+    async fn subscribe_auction_results(
+        &self,
+        pools: HashSet<PoolId>
+    ) -> jsonrpsee::core::SubscriptionResult;
+}
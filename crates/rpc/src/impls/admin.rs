@@ -0,0 +1,260 @@
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH}
+};
+
+use angstrom_metrics::{AllocationProfilingError, AllocationReport};
+use angstrom_types::primitive::{ConfigUpdate, Feature, FeatureFlags, PoolId, PoolLimits};
+use jsonrpsee::core::RpcResult;
+use tokio::sync::broadcast;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+use url::Url;
+
+use crate::{api::AdminApiServer, invalid_params_rpc_err, rpc_err};
+
+/// Handle to the reloadable log filter installed by the node.
+pub type LogFilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Changes the configuration of the running node. Every change is broadcast to
+/// the managers, which pick up the ones that concern them.
+pub struct AdminApi {
+    config_updates: broadcast::Sender<ConfigUpdate>,
+    log_filter:     Option<LogFilterHandle>,
+    /// where heap profiles are dumped to
+    profile_dir:    Option<PathBuf>,
+    feature_flags:  Option<FeatureFlags>
+}
+
+impl AdminApi {
+    pub fn new(config_updates: broadcast::Sender<ConfigUpdate>) -> Self {
+        Self { config_updates, log_filter: None, profile_dir: None, feature_flags: None }
+    }
+
+    /// The flags the subsystems of the node consult, which are switched in
+    /// place rather than broadcast.
+    pub fn with_feature_flags(mut self, feature_flags: FeatureFlags) -> Self {
+        self.feature_flags = Some(feature_flags);
+        self
+    }
+
+    pub fn with_profile_dir(mut self, profile_dir: PathBuf) -> Self {
+        self.profile_dir = Some(profile_dir);
+        self
+    }
+
+    pub fn with_log_filter(mut self, log_filter: LogFilterHandle) -> Self {
+        self.log_filter = Some(log_filter);
+        self
+    }
+
+    fn flags(&self) -> Result<&FeatureFlags, AdminApiError> {
+        self.feature_flags
+            .as_ref()
+            .ok_or(AdminApiError::FeatureFlagsUnavailable)
+    }
+
+    fn broadcast(&self, update: ConfigUpdate) -> Result<(), AdminApiError> {
+        self.config_updates
+            .send(update)
+            .map(|_| ())
+            .map_err(|_| AdminApiError::NoSubscribers)
+    }
+}
+
+#[async_trait::async_trait]
+impl AdminApiServer for AdminApi {
+    async fn set_log_level(&self, directives: String) -> RpcResult<()> {
+        let log_filter = self
+            .log_filter
+            .as_ref()
+            .ok_or(AdminApiError::LogFilterUnavailable)?;
+        let filter = EnvFilter::try_new(&directives)
+            .map_err(|e| AdminApiError::InvalidLogFilter(e.to_string()))?;
+        log_filter
+            .reload(filter)
+            .map_err(|_| AdminApiError::LogFilterUnavailable)?;
+
+        tracing::info!(%directives, "updated log filter");
+        Ok(())
+    }
+
+    async fn update_pool_limits(&self, limits: PoolLimits) -> RpcResult<()> {
+        Ok(self.broadcast(ConfigUpdate::PoolLimits(limits))?)
+    }
+
+    async fn update_submission_targets(&self, urls: Vec<String>) -> RpcResult<()> {
+        if urls.is_empty() {
+            return Err(AdminApiError::NoSubmissionTargets.into())
+        }
+        let urls = urls
+            .iter()
+            .map(|url| Url::parse(url).map_err(|_| AdminApiError::InvalidUrl(url.clone())))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(self.broadcast(ConfigUpdate::SubmissionTargets(urls))?)
+    }
+
+    async fn pause_order_intake(&self, paused: bool) -> RpcResult<()> {
+        Ok(self.broadcast(ConfigUpdate::OrderIntake { paused })?)
+    }
+
+    async fn resume_pool(&self, pool: PoolId) -> RpcResult<()> {
+        Ok(self.broadcast(ConfigUpdate::ResumePool(pool))?)
+    }
+
+    async fn clear_submission(&self, height: u64) -> RpcResult<()> {
+        Ok(self.broadcast(ConfigUpdate::ClearSubmission(height))?)
+    }
+
+    async fn allocation_stats(&self) -> RpcResult<AllocationReport> {
+        Ok(angstrom_metrics::allocation_report())
+    }
+
+    async fn dump_heap_profile(&self) -> RpcResult<String> {
+        let dir = self
+            .profile_dir
+            .as_ref()
+            .ok_or(AdminApiError::Profiling(AllocationProfilingError::ProfilingUnavailable))?;
+        std::fs::create_dir_all(dir)
+            .map_err(|e| AllocationProfilingError::Dump(e.to_string()))
+            .map_err(AdminApiError::Profiling)?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = dir.join(format!("heap-{now}.prof"));
+        angstrom_metrics::dump_heap_profile(&path).map_err(AdminApiError::Profiling)?;
+
+        tracing::info!(path=%path.display(), "dumped heap profile");
+        Ok(path.display().to_string())
+    }
+
+    async fn feature_flags(&self) -> RpcResult<BTreeMap<Feature, bool>> {
+        Ok(self.flags()?.snapshot())
+    }
+
+    async fn set_feature_flag(&self, feature: Feature, enabled: bool) -> RpcResult<bool> {
+        let was_enabled = self.flags()?.set(feature, enabled);
+
+        tracing::info!(%feature, enabled, "updated feature flag");
+        Ok(was_enabled)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AdminApiError {
+    #[error("the log filter of this node can't be changed")]
+    LogFilterUnavailable,
+    #[error("invalid log filter: {0}")]
+    InvalidLogFilter(String),
+    #[error("invalid url: {0}")]
+    InvalidUrl(String),
+    #[error("at least one submission target is required")]
+    NoSubmissionTargets,
+    #[error("no manager is listening for config updates")]
+    NoSubscribers,
+    #[error(transparent)]
+    Profiling(#[from] AllocationProfilingError),
+    #[error("the feature flags of this node can't be changed")]
+    FeatureFlagsUnavailable
+}
+
+impl From<AdminApiError> for jsonrpsee::types::ErrorObjectOwned {
+    fn from(error: AdminApiError) -> Self {
+        match error {
+            AdminApiError::LogFilterUnavailable
+            | AdminApiError::NoSubscribers
+            | AdminApiError::Profiling(_)
+            | AdminApiError::FeatureFlagsUnavailable => {
+                rpc_err(jsonrpsee::types::error::INTERNAL_ERROR_CODE, error.to_string(), None)
+            }
+            AdminApiError::InvalidLogFilter(_)
+            | AdminApiError::InvalidUrl(_)
+            | AdminApiError::NoSubmissionTargets => invalid_params_rpc_err(error.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_updates_are_broadcast() {
+        let (tx, mut rx) = broadcast::channel(10);
+        let api = AdminApi::new(tx);
+
+        let limits = PoolLimits { limit_max_size: Some(1024), searcher_max_size: None };
+        api.update_pool_limits(limits).await.unwrap();
+        api.pause_order_intake(true).await.unwrap();
+        api.update_submission_targets(vec!["http://localhost:8545".to_string()])
+            .await
+            .unwrap();
+        api.resume_pool(PoolId::repeat_byte(1)).await.unwrap();
+        api.clear_submission(100).await.unwrap();
+
+        assert_eq!(rx.recv().await.unwrap(), ConfigUpdate::PoolLimits(limits));
+        assert_eq!(rx.recv().await.unwrap(), ConfigUpdate::OrderIntake { paused: true });
+        assert_eq!(
+            rx.recv().await.unwrap(),
+            ConfigUpdate::SubmissionTargets(vec![Url::parse("http://localhost:8545").unwrap()])
+        );
+        assert_eq!(rx.recv().await.unwrap(), ConfigUpdate::ResumePool(PoolId::repeat_byte(1)));
+        assert_eq!(rx.recv().await.unwrap(), ConfigUpdate::ClearSubmission(100));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_updates_are_rejected() {
+        let (tx, mut rx) = broadcast::channel(10);
+        let api = AdminApi::new(tx);
+
+        assert!(api.update_submission_targets(vec![]).await.is_err());
+        assert!(api
+            .update_submission_targets(vec!["not a url".to_string()])
+            .await
+            .is_err());
+        assert!(api.set_log_level("info".to_string()).await.is_err());
+        assert!(rx.try_recv().is_err());
+
+        drop(rx);
+        assert!(api.pause_order_intake(false).await.is_err());
+        // no profile dir, or no heap profiling in this build
+        assert!(api.dump_heap_profile().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_feature_flags_are_switched_in_place() {
+        let (tx, _rx) = broadcast::channel(10);
+        assert!(AdminApi::new(tx.clone()).feature_flags().await.is_err());
+
+        let flags = FeatureFlags::default();
+        let api = AdminApi::new(tx).with_feature_flags(flags.clone());
+        assert!(!api
+            .set_feature_flag(Feature::IncrementalMatching, true)
+            .await
+            .unwrap());
+        assert!(flags.is_enabled(Feature::IncrementalMatching));
+        assert_eq!(api.feature_flags().await.unwrap(), flags.snapshot());
+    }
+
+    #[tokio::test]
+    async fn test_log_filter_reload() {
+        let (filter, handle) = reload::Layer::<_, Registry>::new(EnvFilter::new("info"));
+        // the handle only works while the layer is alive
+        let _subscriber = Registry::default().with(filter);
+        let (tx, _rx) = broadcast::channel(10);
+        let api = AdminApi::new(tx).with_log_filter(handle);
+
+        api.set_log_level("debug,consensus=trace".to_string())
+            .await
+            .unwrap();
+        assert!(api
+            .set_log_level("consensus=loud".to_string())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use alloy_primitives::BlockNumber;
+use angstrom_types::{
+    contract_payloads::angstrom::FeeLedger, mev_boost::SubmissionTargetStats, primitive::PeerId
+};
+use consensus::{ConsensusHandle, ConsensusRoundInfo, LeaderSlot, ValidatorTelemetrySummary};
+use jsonrpsee::core::RpcResult;
+
+use crate::{api::ConsensusApiServer, invalid_params_rpc_err, rpc_err};
+
+/// The most heights a single `consensus_leaderSchedule` call can ask for.
+pub const MAX_LEADER_LOOKAHEAD: u64 = 256;
+
+pub struct ConsensusApi<Consensus> {
+    consensus: Consensus
+}
+
+impl<Consensus> ConsensusApi<Consensus> {
+    pub fn new(consensus: Consensus) -> Self {
+        Self { consensus }
+    }
+}
+
+#[async_trait::async_trait]
+impl<Consensus> ConsensusApiServer for ConsensusApi<Consensus>
+where
+    Consensus: ConsensusHandle
+{
+    async fn round_info(&self) -> RpcResult<ConsensusRoundInfo> {
+        Ok(self
+            .consensus
+            .round_info()
+            .await
+            .ok_or(ConsensusApiError::Unavailable)?)
+    }
+
+    async fn get_leader(&self, height: BlockNumber) -> RpcResult<PeerId> {
+        Ok(self
+            .consensus
+            .get_leader(height)
+            .await
+            .ok_or(ConsensusApiError::Unavailable)?)
+    }
+
+    async fn leader_schedule(&self, from: BlockNumber, count: u64) -> RpcResult<Vec<LeaderSlot>> {
+        if count > MAX_LEADER_LOOKAHEAD {
+            return Err(ConsensusApiError::LookaheadTooLarge(count).into())
+        }
+
+        Ok(self
+            .consensus
+            .leader_schedule(from, count)
+            .await
+            .ok_or(ConsensusApiError::Unavailable)?)
+    }
+
+    async fn fee_ledger(&self) -> RpcResult<FeeLedger> {
+        Ok(self
+            .consensus
+            .fee_ledger()
+            .await
+            .ok_or(ConsensusApiError::Unavailable)?)
+    }
+
+    async fn validator_telemetry(&self) -> RpcResult<Vec<ValidatorTelemetrySummary>> {
+        Ok(self
+            .consensus
+            .validator_telemetry()
+            .await
+            .ok_or(ConsensusApiError::Unavailable)?)
+    }
+
+    async fn submission_targets(&self) -> RpcResult<Vec<SubmissionTargetStats>> {
+        Ok(self
+            .consensus
+            .submission_targets()
+            .await
+            .ok_or(ConsensusApiError::Unavailable)?)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConsensusApiError {
+    #[error("consensus manager is not running")]
+    Unavailable,
+    #[error("requested {0} heights, at most {MAX_LEADER_LOOKAHEAD} are allowed")]
+    LookaheadTooLarge(u64)
+}
+
+impl From<ConsensusApiError> for jsonrpsee::types::ErrorObjectOwned {
+    fn from(error: ConsensusApiError) -> Self {
+        match error {
+            ConsensusApiError::Unavailable => {
+                rpc_err(jsonrpsee::types::error::INTERNAL_ERROR_CODE, error.to_string(), None)
+            }
+            ConsensusApiError::LookaheadTooLarge(_) => invalid_params_rpc_err(error.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future;
+
+    use alloy_primitives::B256;
+    use angstrom_types::{
+        primitive::PeerId,
+        sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData}
+    };
+    use consensus::{
+        rounds::ConsensusPhase, AuctionResult, ProvisionalWinner, ShadowMatch, ShadowMatchError
+    };
+    use tokio::sync::broadcast;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct MockConsensus(Option<ConsensusRoundInfo>);
+
+    impl ConsensusHandle for MockConsensus {
+        fn round_info(
+            &self
+        ) -> impl std::future::Future<Output = Option<ConsensusRoundInfo>> + Send {
+            future::ready(self.0.clone())
+        }
+
+        fn leader_schedule(
+            &self,
+            from: BlockNumber,
+            count: u64
+        ) -> impl std::future::Future<Output = Option<Vec<LeaderSlot>>> + Send {
+            // the leader of the current round leads every height
+            future::ready(self.0.as_ref().map(|info| {
+                (from..from + count)
+                    .map(|height| LeaderSlot { height, leader: info.leader })
+                    .collect()
+            }))
+        }
+
+        fn provisional_winners(
+            &self
+        ) -> impl std::future::Future<Output = Option<Vec<ProvisionalWinner>>> + Send {
+            future::ready(self.0.as_ref().map(|_| vec![]))
+        }
+
+        fn subscribe_auction_results(
+            &self
+        ) -> impl std::future::Future<Output = Option<broadcast::Receiver<Vec<AuctionResult>>>> + Send
+        {
+            future::ready(None)
+        }
+
+        fn fee_ledger(&self) -> impl std::future::Future<Output = Option<FeeLedger>> + Send {
+            future::ready(self.0.as_ref().map(|_| FeeLedger::default()))
+        }
+
+        fn validator_telemetry(
+            &self
+        ) -> impl std::future::Future<Output = Option<Vec<ValidatorTelemetrySummary>>> + Send
+        {
+            future::ready(self.0.as_ref().map(|_| vec![]))
+        }
+
+        fn submission_targets(
+            &self
+        ) -> impl std::future::Future<Output = Option<Vec<SubmissionTargetStats>>> + Send {
+            future::ready(self.0.as_ref().map(|info| {
+                vec![SubmissionTargetStats {
+                    target:         "https://relay.example/".to_string(),
+                    submissions:    info.height,
+                    accepted:       info.height,
+                    included:       0,
+                    success_rate:   1.0,
+                    inclusion_rate: 0.0,
+                    avg_latency_ms: 20,
+                    healthy:        true
+                }]
+            }))
+        }
+
+        fn simulate_order(
+            &self,
+            _: OrderWithStorageData<AllOrders>
+        ) -> impl std::future::Future<Output = Option<Result<ShadowMatch, ShadowMatchError>>> + Send
+        {
+            future::ready(None)
+        }
+
+        fn shutdown(&self) -> impl std::future::Future<Output = ()> + Send {
+            future::ready(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_round_info() {
+        let info = ConsensusRoundInfo {
+            height:                    10,
+            phase:                     ConsensusPhase::PreProposal,
+            leader:                    PeerId::random(),
+            is_leader:                 false,
+            pre_proposals:             2,
+            pre_proposal_aggregations: 0,
+            last_proposal:             Some((9, B256::random()))
+        };
+
+        let api = ConsensusApi::new(MockConsensus(Some(info.clone())));
+        assert_eq!(api.round_info().await.unwrap(), info);
+
+        let api = ConsensusApi::new(MockConsensus(None));
+        assert!(api.round_info().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_submission_targets() {
+        let info = ConsensusRoundInfo {
+            height:                    10,
+            phase:                     ConsensusPhase::Finalization,
+            leader:                    PeerId::random(),
+            is_leader:                 true,
+            pre_proposals:             0,
+            pre_proposal_aggregations: 0,
+            last_proposal:             None
+        };
+
+        let api = ConsensusApi::new(MockConsensus(Some(info)));
+        let targets = api.submission_targets().await.unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].submissions, 10);
+
+        let api = ConsensusApi::new(MockConsensus(None));
+        assert!(api.submission_targets().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_leader_lookahead() {
+        let leader = PeerId::random();
+        let info = ConsensusRoundInfo {
+            height: 10,
+            phase: ConsensusPhase::BidAggregation,
+            leader,
+            is_leader: false,
+            pre_proposals: 0,
+            pre_proposal_aggregations: 0,
+            last_proposal: None
+        };
+
+        let api = ConsensusApi::new(MockConsensus(Some(info)));
+        assert_eq!(api.get_leader(12).await.unwrap(), leader);
+
+        let schedule = api.leader_schedule(11, 3).await.unwrap();
+        assert_eq!(schedule.iter().map(|slot| slot.height).collect::<Vec<_>>(), vec![11, 12, 13]);
+        assert!(api
+            .leader_schedule(11, MAX_LEADER_LOOKAHEAD + 1)
+            .await
+            .is_err());
+
+        let api = ConsensusApi::new(MockConsensus(None));
+        assert!(api.get_leader(12).await.is_err());
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::PathBuf;
+
+use alloy_consensus::Transaction;
+use alloy_primitives::Address;
+use angstrom_eth::{backfill::filled_orders_in_range, settlement::SettlementExtractor};
+use angstrom_types::{contract_payloads::angstrom::BundleTrace, primitive::PoolId};
+use jsonrpsee::core::RpcResult;
+use order_pool::{resting_orders, OrderArchiveError};
+use reth_ethereum_primitives::{Block, Receipt};
+use reth_primitives_traits::SignedTransaction;
+use reth_provider::{BlockReader, ProviderError, StateProviderFactory};
+use uniswap_v4::uniswap::pool_history::{PoolHistoryError, PoolHistoryHandle};
+use validation::bundle::{replay_bundle_tx, SettledBundleTx};
+
+use crate::{api::DebugApiServer, rpc_err, types::HistoricalPoolSnapshot};
+
+/// Traces the bundles settled on chain, for looking into fills users didn't
+/// expect. Reads the blocks, receipts and historical state of the node.
+pub struct DebugApi<Provider> {
+    provider:         Provider,
+    angstrom_address: Address,
+    /// the pools at past blocks and the order archive, set when pool snapshots
+    /// are served
+    pool_history:     Option<(PoolHistoryHandle, PathBuf)>
+}
+
+impl<Provider> DebugApi<Provider> {
+    pub fn new(provider: Provider, angstrom_address: Address) -> Self {
+        Self { provider, angstrom_address, pool_history: None }
+    }
+
+    /// Serves the pools as they were at past blocks, along with their books
+    /// rebuilt from the order archive at `order_archive`.
+    pub fn with_pool_history(mut self, pools: PoolHistoryHandle, order_archive: PathBuf) -> Self {
+        self.pool_history = Some((pools, order_archive));
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl<Provider> DebugApiServer for DebugApi<Provider>
+where
+    Provider:
+        BlockReader<Block = Block, Receipt = Receipt> + StateProviderFactory + Clone + 'static
+{
+    async fn bundle_trace(&self, block: u64) -> RpcResult<Option<BundleTrace>> {
+        let provider = self.provider.clone();
+        let angstrom_address = self.angstrom_address;
+
+        let trace =
+            tokio::task::spawn_blocking(move || trace_block(&provider, angstrom_address, block))
+                .await
+                .map_err(|e| DebugApiError::Internal(e.to_string()))??;
+        Ok(trace)
+    }
+
+    async fn pool_snapshot(
+        &self,
+        pool_id: PoolId,
+        block: u64
+    ) -> RpcResult<HistoricalPoolSnapshot> {
+        let Some((pools, order_archive)) = self.pool_history.clone() else {
+            return Err(DebugApiError::NoPoolHistory.into())
+        };
+        let (token0, token1, amm) = pools
+            .pool_snapshot(pool_id, block)
+            .await
+            .map_err(DebugApiError::from)?;
+
+        let provider = self.provider.clone();
+        let angstrom_address = self.angstrom_address;
+        let book = tokio::task::spawn_blocking(move || {
+            resting_orders(order_archive, pool_id, block, |blocks| {
+                filled_orders_in_range(&provider, angstrom_address, blocks)
+            })
+        })
+        .await
+        .map_err(|e| DebugApiError::Internal(e.to_string()))?
+        .map_err(DebugApiError::from)?;
+
+        Ok(HistoricalPoolSnapshot {
+            pool_id,
+            block_number: block,
+            token0,
+            token1,
+            amm,
+            orders: book.orders
+        })
+    }
+}
+
+fn trace_block<Provider>(
+    provider: &Provider,
+    angstrom_address: Address,
+    number: u64
+) -> Result<Option<BundleTrace>, DebugApiError>
+where
+    Provider: BlockReader<Block = Block, Receipt = Receipt> + StateProviderFactory
+{
+    let block = provider
+        .block_by_number(number)?
+        .ok_or(DebugApiError::UnknownBlock(number))?;
+    let receipts = provider
+        .receipts_by_block(number.into())?
+        .unwrap_or_default();
+    let receipts = receipts.iter().collect::<Vec<_>>();
+
+    let transactions = &block.body.transactions;
+    let Some((tx, bundle)) = SettlementExtractor::new(angstrom_address)
+        .settled_bundles(transactions, &receipts)
+        .into_iter()
+        .next()
+    else {
+        return Ok(None)
+    };
+    let index = transactions
+        .iter()
+        .position(|other| std::ptr::eq(other, tx))
+        .expect("settled by a transaction of the block");
+
+    let settled = SettledBundleTx {
+        sender: tx
+            .recover_signer()
+            .map_err(|e| DebugApiError::Internal(e.to_string()))?,
+        angstrom_address,
+        gas_limit: tx.gas_limit(),
+        input: tx.input().clone()
+    };
+    let parent_state = provider.history_by_block_number(number.saturating_sub(1))?;
+    let simulation = replay_bundle_tx(parent_state, &block.header, &settled)
+        .map_err(|e| DebugApiError::Internal(e.to_string()))?;
+
+    // receipts carry the gas used up to and including their transaction
+    let cumulative_gas = |index: usize| receipts.get(index).map_or(0, |r| r.cumulative_gas_used);
+    let gas_used = cumulative_gas(index) - index.checked_sub(1).map_or(0, cumulative_gas);
+
+    Ok(Some(BundleTrace {
+        block_number: number,
+        tx_hash: *tx.tx_hash(),
+        gas_used,
+        simulation,
+        orders: bundle.order_traces(number),
+        amm_swaps: bundle.amm_swap_traces()
+    }))
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DebugApiError {
+    #[error("block {0} is unknown to this node")]
+    UnknownBlock(u64),
+    #[error(transparent)]
+    Provider(#[from] ProviderError),
+    #[error("this node doesn't archive its orders")]
+    NoPoolHistory,
+    #[error(transparent)]
+    PoolHistory(#[from] PoolHistoryError),
+    #[error(transparent)]
+    OrderArchive(#[from] OrderArchiveError),
+    #[error("{0}")]
+    Internal(String)
+}
+
+impl From<DebugApiError> for jsonrpsee::types::ErrorObjectOwned {
+    fn from(error: DebugApiError) -> Self {
+        match error {
+            DebugApiError::UnknownBlock(_)
+            | DebugApiError::NoPoolHistory
+            | DebugApiError::PoolHistory(PoolHistoryError::UnknownPool(_))
+            | DebugApiError::OrderArchive(OrderArchiveError::NotArchived { .. }) => {
+                crate::invalid_params_rpc_err(error.to_string())
+            }
+            DebugApiError::Provider(_)
+            | DebugApiError::PoolHistory(_)
+            | DebugApiError::OrderArchive(_)
+            | DebugApiError::Internal(_) => {
+                rpc_err(jsonrpsee::types::error::INTERNAL_ERROR_CODE, error.to_string(), None)
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use angstrom_network::{NetworkEventRecord, NetworkStatsHandle, PeerStats};
+use jsonrpsee::core::RpcResult;
+
+use crate::{api::NetworkApiServer, rpc_err};
+
+pub struct NetworkApi {
+    stats: NetworkStatsHandle
+}
+
+impl NetworkApi {
+    pub fn new(stats: NetworkStatsHandle) -> Self {
+        Self { stats }
+    }
+}
+
+#[async_trait::async_trait]
+impl NetworkApiServer for NetworkApi {
+    async fn peers(&self) -> RpcResult<Vec<PeerStats>> {
+        Ok(self
+            .stats
+            .peers()
+            .await
+            .ok_or(NetworkApiError::Unavailable)?)
+    }
+
+    async fn events(&self, since: u64) -> RpcResult<Vec<NetworkEventRecord>> {
+        Ok(self
+            .stats
+            .events(since)
+            .await
+            .ok_or(NetworkApiError::Unavailable)?)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NetworkApiError {
+    #[error("network manager is not running")]
+    Unavailable
+}
+
+impl From<NetworkApiError> for jsonrpsee::types::ErrorObjectOwned {
+    fn from(error: NetworkApiError) -> Self {
+        match error {
+            NetworkApiError::Unavailable => {
+                rpc_err(jsonrpsee::types::error::INTERNAL_ERROR_CODE, error.to_string(), None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use angstrom_network::{NetworkEventKind, NetworkStatsRequest, StromMessageID};
+    use angstrom_types::primitive::PeerId;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn serves_the_network_stats() {
+        let (handle, mut requests) = NetworkStatsHandle::channel();
+        let api = NetworkApi::new(handle);
+        let peer_id = PeerId::random();
+        let peer = PeerStats {
+            peer_id,
+            protocol_version: Some(0),
+            reputation: 0,
+            connected_at: 1,
+            received: BTreeMap::from([(StromMessageID::PrePropose, 3)]),
+            sent: BTreeMap::new()
+        };
+        let event =
+            NetworkEventRecord { seq: 4, timestamp: 1, peer_id, kind: NetworkEventKind::Connected };
+
+        let served = peer.clone();
+        tokio::spawn(async move {
+            while let Some(request) = requests.recv().await {
+                match request {
+                    NetworkStatsRequest::Peers(tx) => {
+                        let _ = tx.send(vec![served.clone()]);
+                    }
+                    NetworkStatsRequest::Events(since, tx) => {
+                        let _ = tx.send(if since < event.seq { vec![event] } else { vec![] });
+                    }
+                }
+            }
+        });
+
+        assert_eq!(api.peers().await.unwrap(), vec![peer]);
+        assert_eq!(api.events(3).await.unwrap(), vec![event]);
+        assert!(api.events(4).await.unwrap().is_empty());
+
+        // counters are keyed by message name
+        let json = serde_json::to_value(api.peers().await.unwrap()).unwrap();
+        assert_eq!(json[0]["received"]["PrePropose"], 3);
+    }
+
+    #[tokio::test]
+    async fn fails_when_the_network_is_down() {
+        let (handle, requests) = NetworkStatsHandle::channel();
+        drop(requests);
+
+        assert!(NetworkApi::new(handle).peers().await.is_err());
+    }
+}
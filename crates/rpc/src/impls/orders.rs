@@ -1,44 +1,143 @@
-use std::collections::HashSet;
+use std::{collections::HashSet, ops::Range};
 
 use alloy_primitives::{Address, B256};
+use angstrom_metrics::OrderChannelMetricsWrapper;
 use angstrom_types::{
-    orders::{CancelOrderRequest, OrderLocation, OrderOrigin, OrderStatus},
+    orders::{
+        CancelAllOrdersRequest, CancelOrderRequest, OrderAmendment, OrderLocation, OrderOrigin,
+        OrderState, OrderStatus, SourceTag
+    },
     primitive::{OrderPoolNewOrderResult, PoolId},
-    sol_bindings::grouped_orders::AllOrders
+    sol_bindings::{
+        grouped_orders::{AllOrders, OrderWithStorageData},
+        RawPoolOrder
+    }
 };
+use consensus::{ConsensusHandle, ShadowMatchError};
 use futures::StreamExt;
-use jsonrpsee::{core::RpcResult, PendingSubscriptionSink, SubscriptionMessage};
+use jsonrpsee::{core::RpcResult, PendingSubscriptionSink, SubscriptionMessage, SubscriptionSink};
 use order_pool::{OrderPoolHandle, PoolManagerUpdate};
 use reth_tasks::TaskSpawner;
-use validation::order::OrderValidatorHandle;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use validation::{order::OrderValidationResults, ValidationError};
 
 use crate::{
-    api::{GasEstimateResponse, OrderApiServer},
-    types::{OrderSubscriptionFilter, OrderSubscriptionKind, OrderSubscriptionResult},
-    OrderApiError::GasEstimationError
+    api::{GasEstimateResponse, OrderApiServer, OrderSimulation},
+    impls::{OrderSessions, SessionError},
+    types::{
+        BookSubscriptionResult, OrderSubscriptionFilter, OrderSubscriptionKind,
+        OrderSubscriptionResult, SessionOrder
+    }
 };
 
-pub struct OrderApi<OrderPool, Spawner, Validator> {
+/// Orders are only validated through the pool, which keeps the pending state
+/// of their users.
+pub struct OrderApi<OrderPool, Spawner, Consensus> {
     pool:         OrderPool,
     task_spawner: Spawner,
-    validator:    Validator
+    /// matches simulated orders against the current book
+    consensus:    Consensus,
+    sessions:     OrderSessions
 }
 
-impl<OrderPool, Spawner, Validator> OrderApi<OrderPool, Spawner, Validator> {
-    pub fn new(pool: OrderPool, task_spawner: Spawner, validator: Validator) -> Self {
-        Self { pool, task_spawner, validator }
+impl<OrderPool, Spawner, Consensus> OrderApi<OrderPool, Spawner, Consensus> {
+    pub fn new(pool: OrderPool, task_spawner: Spawner, consensus: Consensus) -> Self {
+        Self { pool, task_spawner, consensus, sessions: OrderSessions::default() }
+    }
+
+    /// The cancellation a session order is signed with, if the session is
+    /// open and the signature is its signer's.
+    fn session_cancel(
+        &self,
+        order: &AllOrders,
+        session: SessionOrder
+    ) -> Result<CancelOrderRequest, OrderApiError> {
+        if !self.sessions.is_open(session.session_id) {
+            return Err(SessionError::Unknown(session.session_id).into())
+        }
+        let cancel = CancelOrderRequest {
+            signature:    session.cancel_signature,
+            user_address: order.from(),
+            order_id:     order.order_hash()
+        };
+        if !cancel.is_valid() {
+            return Err(OrderApiError::InvalidSignature)
+        }
+
+        Ok(cancel)
+    }
+
+    /// Validates the order without adding it to the pool.
+    async fn simulate(
+        &self,
+        order: AllOrders
+    ) -> Result<OrderWithStorageData<AllOrders>, OrderApiError>
+    where
+        OrderPool: OrderPoolHandle
+    {
+        match self.pool.simulate_order(order).await {
+            Some(OrderValidationResults::Valid(order)) => Ok(order),
+            Some(OrderValidationResults::Invalid(_, e)) => Err(OrderApiError::Validation(e)),
+            Some(OrderValidationResults::TransitionedToBlock) => {
+                Err(OrderApiError::Validation(ValidationError::TransitionedToBlock))
+            }
+            None => Err(OrderApiError::PoolUnavailable)
+        }
     }
 }
 
 #[async_trait::async_trait]
-impl<OrderPool, Spawner, Validator> OrderApiServer for OrderApi<OrderPool, Spawner, Validator>
+impl<OrderPool, Spawner, Consensus> OrderApiServer for OrderApi<OrderPool, Spawner, Consensus>
 where
     OrderPool: OrderPoolHandle,
     Spawner: TaskSpawner + 'static,
-    Validator: OrderValidatorHandle
+    Consensus: ConsensusHandle
 {
-    async fn send_order(&self, order: AllOrders) -> RpcResult<OrderPoolNewOrderResult> {
-        Ok(self.pool.new_order(OrderOrigin::External, order).await)
+    async fn send_order(
+        &self,
+        order: AllOrders,
+        source_tag: Option<SourceTag>,
+        session: Option<SessionOrder>
+    ) -> RpcResult<OrderPoolNewOrderResult> {
+        let session = match session {
+            Some(session) => Some((session.session_id, self.session_cancel(&order, session)?)),
+            None => None
+        };
+
+        let res = match self
+            .pool
+            .new_tagged_order(OrderOrigin::External, order, source_tag)
+            .await
+        {
+            OrderPoolNewOrderResult::Invalid(e) => return Err(OrderApiError::Validation(e).into()),
+            res => res
+        };
+
+        if let Some((session_id, cancel)) = session.filter(|_| res.is_valid()) {
+            // the session closed or filled up while the order was validated
+            if let Err(e) = self.sessions.add(session_id, cancel.clone()) {
+                self.pool.cancel_order(cancel).await;
+                return Err(OrderApiError::Session(e).into())
+            }
+        }
+
+        Ok(res)
+    }
+
+    async fn send_orders(
+        &self,
+        orders: Vec<AllOrders>,
+        source_tag: Option<SourceTag>
+    ) -> RpcResult<Vec<OrderPoolNewOrderResult>> {
+        // an invalid order doesn't fail the batch, its result carries the error
+        Ok(futures::stream::iter(orders)
+            .map(|order| {
+                self.pool
+                    .new_tagged_order(OrderOrigin::External, order, source_tag.clone())
+            })
+            .buffered(3)
+            .collect()
+            .await)
     }
 
     async fn pending_order(&self, from: Address) -> RpcResult<Vec<AllOrders>> {
@@ -49,13 +148,70 @@ where
         Ok(self.pool.cancel_order(request).await)
     }
 
+    async fn cancel_all_orders(&self, request: CancelAllOrdersRequest) -> RpcResult<Vec<B256>> {
+        if !request.is_valid() {
+            return Err(OrderApiError::InvalidSignature.into())
+        }
+
+        Ok(self.pool.cancel_all_orders(request).await)
+    }
+
+    async fn amend_order(&self, amendment: OrderAmendment) -> RpcResult<OrderPoolNewOrderResult> {
+        match self.pool.amend_order(amendment).await {
+            OrderPoolNewOrderResult::Invalid(e) => Err(OrderApiError::Validation(e).into()),
+            res => Ok(res)
+        }
+    }
+
     async fn estimate_gas(&self, order: AllOrders) -> RpcResult<GasEstimateResponse> {
-        let (gas_limit, gas) = self
-            .validator
-            .estimate_gas(order)
+        let order = self.simulate(order).await?;
+        Ok(GasEstimateResponse {
+            gas:       order.priority_data.gas,
+            gas_units: order.priority_data.gas_units
+        })
+    }
+
+    async fn simulate_order(&self, order: AllOrders) -> RpcResult<OrderSimulation> {
+        let order = self.simulate(order).await?;
+
+        let simulation = OrderSimulation {
+            gas_units:          order.priority_data.gas_units,
+            gas:                order.priority_data.gas,
+            is_currently_valid: order.is_currently_valid,
+            outcome:            None
+        };
+        match self.consensus.simulate_order(order).await {
+            Some(outcome) => Ok(OrderSimulation {
+                outcome: Some(outcome.map_err(OrderApiError::ShadowMatch)?),
+                ..simulation
+            }),
+            // nodes that don't take part in consensus only validate
+            None => Ok(simulation)
+        }
+    }
+
+    async fn get_next_valid_nonce(&self, address: Address) -> RpcResult<u64> {
+        Ok(self
+            .pool
+            .next_valid_nonce(address)
             .await
-            .map_err(GasEstimationError)?;
-        Ok(GasEstimateResponse { gas, gas_units: gas_limit })
+            .ok_or(OrderApiError::PoolUnavailable)?)
+    }
+
+    async fn get_invalidated_nonces(
+        &self,
+        address: Address,
+        range: Range<u64>
+    ) -> RpcResult<Vec<u64>> {
+        if range.end.saturating_sub(range.start) > MAX_NONCE_RANGE {
+            return Err(OrderApiError::NonceRangeTooLarge(range).into())
+        }
+
+        Ok(self
+            .pool
+            .invalidated_nonces(address, range)
+            .await
+            .ok_or(OrderApiError::PoolUnavailable)?)
     }
 
     async fn order_status(&self, order_hash: B256) -> RpcResult<Option<OrderStatus>> {
@@ -77,17 +233,37 @@ where
         filter: HashSet<OrderSubscriptionFilter>
     ) -> jsonrpsee::core::SubscriptionResult {
         let sink = pending.accept().await?;
+        let pool = self.pool.clone();
+        let resync_filter = filter.clone();
         let mut subscription = self
             .pool
             .subscribe_orders()
             .map(move |update| update.map(|value| value.filter_out_order(&kind, &filter)));
 
         self.task_spawner.spawn(Box::pin(async move {
-            while let Some(Ok(order)) = subscription.next().await {
+            while let Some(update) = subscription.next().await {
                 if sink.is_closed() {
                     break
                 }
 
+                // a slow subscriber loses the oldest updates instead of its subscription, and
+                // gets the current state of the pool to make up for them
+                let order = match update {
+                    Ok(order) => order,
+                    Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                        tracing::warn!(missed, "order subscriber fell behind, resyncing");
+                        let metrics = OrderChannelMetricsWrapper::new();
+                        metrics.incr_dropped_updates(missed);
+                        metrics.incr_resyncs();
+
+                        let mut snapshot = pool.fetch_order_states().await;
+                        snapshot
+                            .orders
+                            .retain(|state| matches_filter(&resync_filter, state));
+                        Some(OrderSubscriptionResult::Resync(snapshot))
+                    }
+                };
+
                 if let Some(result) = order {
                     match SubscriptionMessage::from_json(&result) {
                         Ok(message) => {
@@ -105,6 +281,88 @@ where
 
         Ok(())
     }
+
+    async fn subscribe_session(
+        &self,
+        pending: PendingSubscriptionSink
+    ) -> jsonrpsee::core::SubscriptionResult {
+        let sink = pending.accept().await?;
+        let session_id = self.sessions.open();
+        let sessions = self.sessions.clone();
+        let pool = self.pool.clone();
+
+        self.task_spawner.spawn(Box::pin(async move {
+            // the session lasts as long as the subscription, however it ends
+            if let Ok(message) = SubscriptionMessage::from_json(&session_id) {
+                if sink.send(message).await.is_ok() {
+                    sink.closed().await;
+                }
+            }
+
+            let orders = sessions.close(session_id);
+            if !orders.is_empty() {
+                let cancelled = pool
+                    .cancel_orders(orders)
+                    .await
+                    .into_iter()
+                    .filter(|cancelled| *cancelled)
+                    .count();
+                tracing::debug!(session_id, cancelled, "session closed, cancelled its orders");
+            }
+        }));
+
+        Ok(())
+    }
+
+    async fn subscribe_book(
+        &self,
+        pending: PendingSubscriptionSink,
+        pool_id: PoolId
+    ) -> jsonrpsee::core::SubscriptionResult {
+        // subscribe before taking the snapshot so no delta falls in between
+        let updates = self.pool.subscribe_book().await;
+        let snapshot = self.pool.fetch_book(pool_id).await;
+        let (Some(mut updates), Some(snapshot)) = (updates, snapshot) else {
+            pending.reject(OrderApiError::UnknownPool(pool_id)).await;
+            return Ok(())
+        };
+        let sink = pending.accept().await?;
+        let pool = self.pool.clone();
+
+        self.task_spawner.spawn(Box::pin(async move {
+            let mut sequence = snapshot.sequence;
+            if !send_book_result(&sink, BookSubscriptionResult::Snapshot(snapshot)).await {
+                return
+            }
+
+            while let Some(update) = updates.next().await {
+                if sink.is_closed() {
+                    break
+                }
+
+                let result = match update {
+                    Ok(delta) if delta.pool_id == pool_id && delta.sequence > sequence => {
+                        sequence = delta.sequence;
+                        BookSubscriptionResult::Delta(delta)
+                    }
+                    Ok(_) => continue,
+                    // deltas can't be skipped, so a slow subscriber starts over
+                    Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                        tracing::warn!(missed, "book subscriber fell behind, resyncing");
+                        let Some(snapshot) = pool.fetch_book(pool_id).await else { break };
+                        sequence = snapshot.sequence;
+                        BookSubscriptionResult::Snapshot(snapshot)
+                    }
+                };
+
+                if !send_book_result(&sink, result).await {
+                    break
+                }
+            }
+        }));
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -113,8 +371,18 @@ pub enum OrderApiError {
     InvalidSignature,
     #[error("failed to recover signer from signature")]
     SignatureRecoveryError,
-    #[error("failed to estimate gas: {0}")]
-    GasEstimationError(String)
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    #[error("no pool with id {0}")]
+    UnknownPool(PoolId),
+    #[error("nonce range {0:?} spans more than {MAX_NONCE_RANGE} nonces")]
+    NonceRangeTooLarge(Range<u64>),
+    #[error(transparent)]
+    ShadowMatch(#[from] ShadowMatchError),
+    #[error("order pool is unavailable")]
+    PoolUnavailable,
+    #[error(transparent)]
+    Session(#[from] SessionError)
 }
 
 impl From<OrderApiError> for jsonrpsee::types::ErrorObjectOwned {
@@ -122,11 +390,68 @@ impl From<OrderApiError> for jsonrpsee::types::ErrorObjectOwned {
         match error {
             OrderApiError::InvalidSignature => invalid_params_rpc_err(error.to_string()),
             OrderApiError::SignatureRecoveryError => invalid_params_rpc_err(error.to_string()),
-            OrderApiError::GasEstimationError(e) => invalid_params_rpc_err(e)
+            OrderApiError::Validation(e) => validation_rpc_err(&e),
+            OrderApiError::UnknownPool(_) | OrderApiError::Session(_) => {
+                invalid_params_rpc_err(error.to_string())
+            }
+            OrderApiError::NonceRangeTooLarge(_) => {
+                rpc_err(LIMIT_EXCEEDED_CODE, error.to_string(), None)
+            }
+            OrderApiError::ShadowMatch(ShadowMatchError::PoolPaused(_))
+            | OrderApiError::PoolUnavailable => {
+                rpc_err(RESOURCE_UNAVAILABLE_CODE, error.to_string(), None)
+            }
+            OrderApiError::ShadowMatch(ShadowMatchError::UnknownPool(_)) => {
+                invalid_params_rpc_err(error.to_string())
+            }
+            OrderApiError::ShadowMatch(ShadowMatchError::Matching(_)) => {
+                rpc_err(jsonrpsee::types::error::INTERNAL_ERROR_CODE, error.to_string(), None)
+            }
         }
     }
 }
 
+/// Most nonces `angstrom_getInvalidatedNonces` reads in one call, 16 bitmap
+/// words.
+pub const MAX_NONCE_RANGE: u64 = 4096;
+
+/// Code of an order whose simulation reverted, the same `eth_call` uses.
+pub const EXECUTION_REVERTED_CODE: i32 = 3;
+// codes of EIP-1474
+pub const RESOURCE_UNAVAILABLE_CODE: i32 = -32002;
+pub const ORDER_REJECTED_CODE: i32 = -32003;
+pub const LIMIT_EXCEEDED_CODE: i32 = -32005;
+
+/// Orders that can be resubmitted as they are fail with
+/// [`RESOURCE_UNAVAILABLE_CODE`] or [`LIMIT_EXCEEDED_CODE`], orders that will
+/// never validate with [`ORDER_REJECTED_CODE`].
+pub fn validation_rpc_err(error: &ValidationError) -> jsonrpsee::types::ErrorObjectOwned {
+    let code = match error {
+        ValidationError::Db(_) => jsonrpsee::types::error::INTERNAL_ERROR_CODE,
+        ValidationError::SimulationReverted(_) => EXECUTION_REVERTED_CODE,
+        ValidationError::ThreadpoolSaturated => LIMIT_EXCEEDED_CODE,
+        ValidationError::StaleBlock
+        | ValidationError::TransitionedToBlock
+        | ValidationError::NotAcceptingOrders
+        | ValidationError::FeatureDisabled(_) => RESOURCE_UNAVAILABLE_CODE,
+        ValidationError::SimulationFailed(_)
+        | ValidationError::MissingPrice { .. }
+        | ValidationError::InvalidSignature
+        | ValidationError::NativeEth
+        | ValidationError::UnknownPool
+        | ValidationError::DuplicateNonce
+        | ValidationError::DuplicateBlock
+        | ValidationError::Cancelled
+        | ValidationError::DuplicateOrder
+        | ValidationError::InvalidBlock(_)
+        | ValidationError::TopOfBlock(_)
+        | ValidationError::InvalidAmendment
+        | ValidationError::Expired => ORDER_REJECTED_CODE
+    };
+
+    rpc_err(code, error.to_string(), None)
+}
+
 pub fn invalid_params_rpc_err(msg: impl Into<String>) -> jsonrpsee::types::ErrorObjectOwned {
     rpc_err(jsonrpsee::types::error::INVALID_PARAMS_CODE, msg, None)
 }
@@ -146,6 +471,23 @@ pub fn rpc_err(
     )
 }
 
+/// Returns false once the subscriber is gone.
+async fn send_book_result(sink: &SubscriptionSink, result: BookSubscriptionResult) -> bool {
+    match SubscriptionMessage::from_json(&result) {
+        Ok(message) => sink.send(message).await.is_ok(),
+        Err(e) => {
+            tracing::error!("Failed to serialize subscription message: {:?}", e);
+            true
+        }
+    }
+}
+
+fn matches_filter(filter: &HashSet<OrderSubscriptionFilter>, state: &OrderState) -> bool {
+    filter.contains(&OrderSubscriptionFilter::ByPair(state.pool_id))
+        || filter.contains(&OrderSubscriptionFilter::ByAddress(state.user))
+        || filter.contains(&OrderSubscriptionFilter::None)
+}
+
 trait OrderFilterMatching {
     fn filter_out_order(
         self,
@@ -202,18 +544,28 @@ impl OrderFilterMatching for PoolManagerUpdate {
 mod tests {
     use std::{future, future::Future};
 
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
     use alloy_primitives::{Address, B256, U256};
-    use angstrom_network::pool_manager::OrderCommand;
+    use angstrom_network::pool_manager::{OrderCommand, ValidationCommand};
     use angstrom_types::{
-        orders::{OrderOrigin, OrderStatus},
-        sol_bindings::grouped_orders::{AllOrders, FlashVariants, StandingVariants}
+        orders::{
+            BookDelta, BookLevelUpdate, BookSide, BookSnapshot, OrderOrigin, OrderPriorityData,
+            OrderStatesSnapshot, OrderStatus
+        },
+        sol_bindings::{
+            grouped_orders::{AllOrders, FlashVariants, StandingVariants},
+            rpc_orders::PartialStandingOrder
+        }
     };
-    use futures::FutureExt;
+    use consensus::{ConsensusQueryHandle, ConsensusRequest, ShadowMatch};
+    use jsonrpsee::rpc_params;
     use order_pool::PoolManagerUpdate;
     use reth_tasks::TokioTaskExecutor;
-    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+    use tokio::sync::{
+        broadcast,
+        mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender}
+    };
     use tokio_stream::wrappers::BroadcastStream;
-    use validation::order::{GasEstimationFuture, ValidationFuture};
 
     use super::*;
 
@@ -237,7 +589,7 @@ mod tests {
         // Test standing order
         let standing_order = create_standing_order();
         assert!(api
-            .send_order(standing_order)
+            .send_order(standing_order, None, None)
             .await
             .expect("to not throw error")
             .is_valid());
@@ -245,7 +597,7 @@ mod tests {
         // Test flash order
         let flash_order = create_flash_order();
         assert!(api
-            .send_order(flash_order)
+            .send_order(flash_order, None, None)
             .await
             .expect("to not throw error")
             .is_valid());
@@ -253,34 +605,259 @@ mod tests {
         // Test TOB order
         let tob_order = create_tob_order();
         assert!(api
-            .send_order(tob_order)
+            .send_order(tob_order, None, None)
             .await
             .expect("to not throw error")
             .is_valid());
     }
 
-    fn setup_order_api(
-    ) -> (OrderApiTestHandle, OrderApi<MockOrderPoolHandle, TokioTaskExecutor, MockValidator>) {
+    #[tokio::test]
+    async fn orders_keep_their_source_tag() {
+        let (mut handle, api) = setup_order_api();
+        let tag = SourceTag::new("dapp").unwrap();
+
+        api.send_orders(vec![create_standing_order(), create_tob_order()], Some(tag.clone()))
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            assert!(matches!(
+                handle._from_api.recv().await,
+                Some(OrderCommand::Validate(ValidationCommand::ValidateAndInsert(
+                    _, _, Some(source_tag), _
+                ))) if source_tag == tag
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn session_orders_are_cancelled_with_their_session() {
+        let (mut handle, api) = setup_order_api();
+        let signer = PrivateKeySigner::random();
+        let mut order = PartialStandingOrder::default();
+        order.meta.from = signer.address();
+        let order = AllOrders::Standing(StandingVariants::Partial(order));
+        let cancel_hash = CancelOrderRequest::signing_hash(signer.address(), order.order_hash());
+        let session = |session_id| SessionOrder {
+            session_id,
+            cancel_signature: signer.sign_hash_sync(&cancel_hash).unwrap()
+        };
+
+        let rpc = api.into_rpc();
+        let mut subscription = rpc
+            .subscribe_unbounded("angstrom_subscribeSession", rpc_params![])
+            .await
+            .unwrap();
+        let (session_id, _) = subscription.next::<u64>().await.unwrap().unwrap();
+
+        let send = |session: SessionOrder| {
+            rpc.call::<_, OrderPoolNewOrderResult>(
+                "angstrom_sendOrder",
+                rpc_params![order.clone(), None::<SourceTag>, session]
+            )
+        };
+        assert!(send(session(session_id + 1)).await.is_err());
+        let wrong_signer = SessionOrder {
+            cancel_signature: PrivateKeySigner::random()
+                .sign_hash_sync(&cancel_hash)
+                .unwrap(),
+            ..session(session_id)
+        };
+        assert!(send(wrong_signer).await.is_err());
+        assert!(send(session(session_id)).await.unwrap().is_valid());
+        assert!(matches!(handle._from_api.recv().await, Some(OrderCommand::Validate(_))));
+
+        // the connection dropping ends the subscription
+        drop(subscription);
+        assert!(matches!(
+            handle._from_api.recv().await,
+            Some(OrderCommand::CancelOrder(cancel, _))
+                if cancel.order_id == order.order_hash() && cancel.is_valid()
+        ));
+    }
+
+    #[tokio::test]
+    async fn cancel_all_orders_checks_the_signature() {
+        let (mut handle, api) = setup_order_api();
+        let signer = PrivateKeySigner::random();
+        let hash = CancelAllOrdersRequest::signing_hash(signer.address(), None, 3);
+        let request = CancelAllOrdersRequest {
+            user_address: signer.address(),
+            pool_id:      None,
+            nonce:        3,
+            signature:    signer.sign_hash_sync(&hash).unwrap()
+        };
+
+        let other_pool =
+            CancelAllOrdersRequest { pool_id: Some(B256::repeat_byte(1)), ..request.clone() };
+        assert!(api.cancel_all_orders(other_pool).await.is_err());
+
+        api.cancel_all_orders(request.clone()).await.unwrap();
+        assert!(matches!(
+            handle._from_api.recv().await,
+            Some(OrderCommand::CancelAllOrders(sent, _)) if sent == request
+        ));
+    }
+
+    #[test]
+    fn validation_errors_map_to_rpc_codes() {
+        let code = |error: ValidationError| validation_rpc_err(&error).code();
+
+        assert_eq!(
+            code(ValidationError::SimulationReverted("STF".to_string())),
+            EXECUTION_REVERTED_CODE
+        );
+        assert_eq!(code(ValidationError::ThreadpoolSaturated), LIMIT_EXCEEDED_CODE);
+        assert_eq!(code(ValidationError::StaleBlock), RESOURCE_UNAVAILABLE_CODE);
+        assert_eq!(code(ValidationError::InvalidSignature), ORDER_REJECTED_CODE);
+        assert_eq!(
+            code(ValidationError::Db("missing trie node".to_string())),
+            jsonrpsee::types::error::INTERNAL_ERROR_CODE
+        );
+    }
+
+    #[test]
+    fn native_eth_orders_are_told_to_use_weth() {
+        let e = validation_rpc_err(&ValidationError::NativeEth);
+
+        assert_eq!(e.code(), ORDER_REJECTED_CODE);
+        assert_eq!(e.message(), "order uses native ETH, which can't be settled. WETH is required");
+    }
+
+    #[tokio::test]
+    async fn nonce_helpers() {
+        let (_handle, api) = setup_order_api();
+
+        assert_eq!(api.get_next_valid_nonce(Address::ZERO).await.unwrap(), 3);
+        assert_eq!(
+            api.get_invalidated_nonces(Address::ZERO, 5..10)
+                .await
+                .unwrap(),
+            vec![6, 8]
+        );
+
+        let e = api
+            .get_invalidated_nonces(Address::ZERO, 0..MAX_NONCE_RANGE + 1)
+            .await
+            .unwrap_err();
+        assert_eq!(e.code(), LIMIT_EXCEEDED_CODE);
+    }
+
+    const BOOK_POOL: PoolId = PoolId::repeat_byte(1);
+
+    fn book_snapshot() -> BookSnapshot {
+        BookSnapshot { pool_id: BOOK_POOL, sequence: 1, ..Default::default() }
+    }
+
+    fn book_delta(pool_id: PoolId, sequence: u64) -> BookDelta {
+        BookDelta {
+            pool_id,
+            sequence,
+            update: BookLevelUpdate::Remove { side: BookSide::Ask, price: U256::from(1) }
+        }
+    }
+
+    #[tokio::test]
+    async fn book_subscription_starts_from_a_snapshot() {
+        let (_handle, api) = setup_order_api();
+        let updates = api.pool.book_updates.clone();
+        let rpc = api.into_rpc();
+
+        assert!(rpc
+            .subscribe_unbounded("angstrom_subscribeBook", [PoolId::repeat_byte(2)])
+            .await
+            .is_err());
+
+        let mut subscription = rpc
+            .subscribe_unbounded("angstrom_subscribeBook", [BOOK_POOL])
+            .await
+            .unwrap();
+        let (first, _) = subscription
+            .next::<BookSubscriptionResult>()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first, BookSubscriptionResult::Snapshot(book_snapshot()));
+
+        // the first delta is already part of the snapshot, the second one is of
+        // another pool
+        updates.send(book_delta(BOOK_POOL, 1)).unwrap();
+        updates.send(book_delta(PoolId::repeat_byte(2), 2)).unwrap();
+        updates.send(book_delta(BOOK_POOL, 2)).unwrap();
+
+        let (next, _) = subscription
+            .next::<BookSubscriptionResult>()
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(next, BookSubscriptionResult::Delta(book_delta(BOOK_POOL, 2)));
+    }
+
+    #[tokio::test]
+    async fn simulate_order_matches_without_submitting() {
+        let (mut handle, api) = setup_order_api();
+        let shadow_match =
+            ShadowMatch { clearing_price: U256::from(7), filled_quantity: 4, queue_position: 2 };
+        let consensus = tokio::spawn(async move {
+            let Some(ConsensusRequest::SimulateOrder(order, tx)) = handle.consensus.recv().await
+            else {
+                panic!("expected a simulation")
+            };
+            assert!(matches!(order.order, AllOrders::Standing(_)));
+            tx.send(Ok(shadow_match)).unwrap();
+            handle
+        });
+
+        let simulation = api.simulate_order(create_standing_order()).await.unwrap();
+        assert_eq!(
+            simulation,
+            OrderSimulation {
+                gas_units:          21_000,
+                gas:                U256::from(250_000u64),
+                is_currently_valid: true,
+                outcome:            Some(shadow_match)
+            }
+        );
+
+        // nothing was sent to the pool
+        let mut handle = consensus.await.unwrap();
+        assert!(handle._from_api.try_recv().is_err());
+
+        // without consensus the order is only validated
+        drop(handle.consensus);
+        let simulation = api.simulate_order(create_standing_order()).await.unwrap();
+        assert_eq!(simulation.outcome, None);
+    }
+
+    type MockOrderApi = OrderApi<MockOrderPoolHandle, TokioTaskExecutor, ConsensusQueryHandle>;
+
+    fn setup_order_api() -> (OrderApiTestHandle, MockOrderApi) {
         let (to_pool, pool_rx) = unbounded_channel();
+        let (to_consensus, consensus_rx) = unbounded_channel();
         let pool_handle = MockOrderPoolHandle::new(to_pool);
         let task_executor = TokioTaskExecutor::default();
-        let api = OrderApi::new(pool_handle.clone(), task_executor, MockValidator);
-        let handle = OrderApiTestHandle { _from_api: pool_rx };
+        let api = OrderApi::new(
+            pool_handle.clone(),
+            task_executor,
+            ConsensusQueryHandle::new(to_consensus)
+        );
+        let handle = OrderApiTestHandle { _from_api: pool_rx, consensus: consensus_rx };
         (handle, api)
     }
 
     struct OrderApiTestHandle {
-        _from_api: UnboundedReceiver<OrderCommand>
+        _from_api: UnboundedReceiver<OrderCommand>,
+        consensus: UnboundedReceiver<ConsensusRequest>
     }
 
     #[derive(Clone)]
     struct MockOrderPoolHandle {
-        sender: UnboundedSender<OrderCommand>
+        sender:       UnboundedSender<OrderCommand>,
+        book_updates: broadcast::Sender<BookDelta>
     }
 
     impl MockOrderPoolHandle {
         fn new(sender: UnboundedSender<OrderCommand>) -> Self {
-            Self { sender }
+            Self { sender, book_updates: broadcast::channel(8).0 }
         }
     }
 
@@ -297,15 +874,56 @@ mod tests {
             &self,
             origin: OrderOrigin,
             order: AllOrders
+        ) -> impl Future<Output = OrderPoolNewOrderResult> + Send {
+            self.new_tagged_order(origin, order, None)
+        }
+
+        fn new_tagged_order(
+            &self,
+            origin: OrderOrigin,
+            order: AllOrders,
+            source_tag: Option<SourceTag>
         ) -> impl Future<Output = OrderPoolNewOrderResult> + Send {
             let (tx, _) = tokio::sync::oneshot::channel();
             let _ = self
                 .sender
-                .send(OrderCommand::NewOrder(origin, order, tx))
+                .send(OrderCommand::Validate(ValidationCommand::ValidateAndInsert(
+                    origin, order, source_tag, tx
+                )))
                 .is_ok();
             future::ready(OrderPoolNewOrderResult::Valid)
         }
 
+        fn simulate_order(
+            &self,
+            order: AllOrders
+        ) -> impl Future<Output = Option<OrderValidationResults>> + Send {
+            let order = OrderWithStorageData {
+                order,
+                priority_data: OrderPriorityData {
+                    gas: U256::from(250_000u64),
+                    gas_units: 21_000,
+                    ..Default::default()
+                },
+                is_currently_valid: true,
+                ..Default::default()
+            };
+            future::ready(Some(OrderValidationResults::Valid(order)))
+        }
+
+        fn next_valid_nonce(&self, _: Address) -> impl Future<Output = Option<u64>> + Send {
+            future::ready(Some(3))
+        }
+
+        // every even nonce is used
+        fn invalidated_nonces(
+            &self,
+            _: Address,
+            range: Range<u64>
+        ) -> impl Future<Output = Option<Vec<u64>>> + Send {
+            future::ready(Some(range.filter(|nonce| nonce % 2 == 0).collect()))
+        }
+
         fn subscribe_orders(&self) -> BroadcastStream<PoolManagerUpdate> {
             unimplemented!("Not needed for this test")
         }
@@ -316,41 +934,47 @@ mod tests {
             future::ready(true)
         }
 
-        fn pending_orders(&self, address: Address) -> impl Future<Output = Vec<AllOrders>> + Send {
-            let (tx, rx) = tokio::sync::oneshot::channel();
+        fn cancel_all_orders(
+            &self,
+            req: CancelAllOrdersRequest
+        ) -> impl Future<Output = Vec<B256>> + Send {
+            let (tx, _) = tokio::sync::oneshot::channel();
+            let _ = self.sender.send(OrderCommand::CancelAllOrders(req, tx));
+            future::ready(vec![])
+        }
+
+        fn amend_order(
+            &self,
+            amendment: OrderAmendment
+        ) -> impl Future<Output = OrderPoolNewOrderResult> + Send {
+            let (tx, _) = tokio::sync::oneshot::channel();
             let _ = self
                 .sender
-                .send(OrderCommand::PendingOrders(address, tx))
+                .send(OrderCommand::AmendOrder(amendment, tx))
                 .is_ok();
-            rx.map(|res| res.unwrap_or_default())
+            future::ready(OrderPoolNewOrderResult::Valid)
+        }
+
+        fn pending_orders(&self, _: Address) -> impl Future<Output = Vec<AllOrders>> + Send {
+            future::ready(vec![])
         }
 
         fn fetch_order_status(&self, _: B256) -> impl Future<Output = Option<OrderStatus>> + Send {
             future::ready(None)
         }
-    }
-
-    #[derive(Debug, Clone)]
-    struct MockValidator;
 
-    impl OrderValidatorHandle for MockValidator {
-        type Order = AllOrders;
-
-        fn validate_order(&self, _origin: OrderOrigin, _order: Self::Order) -> ValidationFuture {
-            unimplemented!("order validation is complicated")
+        fn fetch_order_states(&self) -> impl Future<Output = OrderStatesSnapshot> + Send {
+            future::ready(OrderStatesSnapshot::default())
         }
 
-        fn new_block(
-            &self,
-            _block_number: u64,
-            _completed_orders: Vec<B256>,
-            _addresses: Vec<Address>
-        ) -> ValidationFuture {
-            unimplemented!("no new block")
+        fn subscribe_book(
+            &self
+        ) -> impl Future<Output = Option<BroadcastStream<BookDelta>>> + Send {
+            future::ready(Some(BroadcastStream::new(self.book_updates.subscribe())))
         }
 
-        fn estimate_gas(&self, _order: AllOrders) -> GasEstimationFuture {
-            Box::pin(future::ready(Ok((21_000u64, U256::from(250_000u64)))))
+        fn fetch_book(&self, pool_id: PoolId) -> impl Future<Output = Option<BookSnapshot>> + Send {
+            future::ready((pool_id == BOOK_POOL).then(book_snapshot))
         }
     }
 }
@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use alloy_primitives::B256;
+use angstrom_types::{
+    orders::RelayedOrder,
+    primitive::{Feature, FeatureFlags, ValidationError}
+};
+use jsonrpsee::core::RpcResult;
+use order_pool::CrossChainIntake;
+
+use crate::{api::RelayApiServer, invalid_params_rpc_err, validation_rpc_err};
+
+/// Takes orders relayed from other chains into their own pool, they aren't
+/// matched on this one. New orders are only taken while
+/// [`Feature::RelayIntake`] is enabled.
+pub struct RelayApi {
+    intake:        Arc<CrossChainIntake>,
+    feature_flags: FeatureFlags
+}
+
+impl RelayApi {
+    pub fn new(intake: Arc<CrossChainIntake>) -> Self {
+        Self { intake, feature_flags: FeatureFlags::default() }
+    }
+
+    pub fn with_feature_flags(mut self, feature_flags: FeatureFlags) -> Self {
+        self.feature_flags = feature_flags;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl RelayApiServer for RelayApi {
+    async fn send_order(&self, relayed: RelayedOrder) -> RpcResult<B256> {
+        if !self.feature_flags.is_enabled(Feature::RelayIntake) {
+            return Err(validation_rpc_err(&ValidationError::FeatureDisabled(Feature::RelayIntake)))
+        }
+
+        self.intake
+            .submit(relayed)
+            .map_err(|e| invalid_params_rpc_err(e.to_string()))
+    }
+
+    async fn orders(&self, chain_id: Option<u64>) -> RpcResult<Vec<RelayedOrder>> {
+        Ok(self.intake.orders(chain_id))
+    }
+}
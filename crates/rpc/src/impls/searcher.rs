@@ -0,0 +1,291 @@
+use std::collections::HashSet;
+
+use angstrom_types::{
+    orders::OrderOrigin,
+    primitive::{OrderPoolNewOrderResult, PoolId},
+    sol_bindings::{grouped_orders::AllOrders, rpc_orders::TopOfBlockOrder}
+};
+use consensus::{ConsensusHandle, ProvisionalWinner};
+use futures::StreamExt;
+use jsonrpsee::{core::RpcResult, PendingSubscriptionSink, SubscriptionMessage};
+use order_pool::OrderPoolHandle;
+use reth_tasks::TaskSpawner;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use validation::common::{SettlementCostEstimate, SettlementCostEstimator};
+
+use crate::{api::SearcherApiServer, rpc_err};
+
+pub struct SearcherApi<OrderPool, Consensus, Spawner> {
+    pool:             OrderPool,
+    consensus:        Consensus,
+    task_spawner:     Spawner,
+    settlement_costs: SettlementCostEstimator
+}
+
+impl<OrderPool, Consensus, Spawner> SearcherApi<OrderPool, Consensus, Spawner> {
+    pub fn new(pool: OrderPool, consensus: Consensus, task_spawner: Spawner) -> Self {
+        Self { pool, consensus, task_spawner, settlement_costs: SettlementCostEstimator::default() }
+    }
+
+    /// Serves the settlement cost forecast of bundle validation.
+    pub fn with_settlement_costs(mut self, settlement_costs: SettlementCostEstimator) -> Self {
+        self.settlement_costs = settlement_costs;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl<OrderPool, Consensus, Spawner> SearcherApiServer for SearcherApi<OrderPool, Consensus, Spawner>
+where
+    OrderPool: OrderPoolHandle,
+    Consensus: ConsensusHandle,
+    Spawner: TaskSpawner + 'static
+{
+    async fn send_tob_order(&self, order: TopOfBlockOrder) -> RpcResult<OrderPoolNewOrderResult> {
+        Ok(self
+            .pool
+            .new_order(OrderOrigin::External, AllOrders::TOB(order))
+            .await)
+    }
+
+    async fn provisional_winner(&self, pool_id: PoolId) -> RpcResult<Option<ProvisionalWinner>> {
+        Ok(self
+            .provisional_winners()
+            .await?
+            .into_iter()
+            .find(|winner| winner.pool_id == pool_id))
+    }
+
+    async fn provisional_winners(&self) -> RpcResult<Vec<ProvisionalWinner>> {
+        Ok(self
+            .consensus
+            .provisional_winners()
+            .await
+            .ok_or(SearcherApiError::ConsensusUnavailable)?)
+    }
+
+    async fn estimated_settlement_cost(&self) -> RpcResult<Option<SettlementCostEstimate>> {
+        Ok(self.settlement_costs.estimate())
+    }
+
+    async fn subscribe_auction_results(
+        &self,
+        pending: PendingSubscriptionSink,
+        pools: HashSet<PoolId>
+    ) -> jsonrpsee::core::SubscriptionResult {
+        let Some(results) = self.consensus.subscribe_auction_results().await else {
+            pending.reject(SearcherApiError::ConsensusUnavailable).await;
+            return Ok(())
+        };
+        let sink = pending.accept().await?;
+        let mut results = BroadcastStream::new(results);
+
+        self.task_spawner.spawn(Box::pin(async move {
+            while let Some(update) = results.next().await {
+                if sink.is_closed() {
+                    break
+                }
+
+                let results = match update {
+                    Ok(results) => results,
+                    Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                        tracing::warn!(missed, "auction result subscriber fell behind");
+                        continue
+                    }
+                };
+
+                for result in results
+                    .into_iter()
+                    .filter(|result| pools.is_empty() || pools.contains(&result.pool_id))
+                {
+                    match SubscriptionMessage::from_json(&result) {
+                        Ok(message) => {
+                            if sink.send(message).await.is_err() {
+                                return
+                            }
+                        }
+                        Err(e) => {
+                            tracing::error!("Failed to serialize subscription message: {:?}", e);
+                        }
+                    }
+                }
+            }
+        }));
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SearcherApiError {
+    #[error("consensus manager is not running")]
+    ConsensusUnavailable
+}
+
+impl From<SearcherApiError> for jsonrpsee::types::ErrorObjectOwned {
+    fn from(error: SearcherApiError) -> Self {
+        match error {
+            SearcherApiError::ConsensusUnavailable => {
+                rpc_err(jsonrpsee::types::error::INTERNAL_ERROR_CODE, error.to_string(), None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::{self, Future};
+
+    use alloy_consensus::Header;
+    use alloy_primitives::{BlockNumber, B256, U256};
+    use angstrom_network::pool_manager::PoolHandle;
+    use angstrom_types::{
+        contract_payloads::angstrom::FeeLedger,
+        mev_boost::SubmissionTargetStats,
+        sol_bindings::grouped_orders::{AllOrders, OrderWithStorageData}
+    };
+    use consensus::{
+        AuctionResult, ConsensusRoundInfo, LeaderSlot, ShadowMatch, ShadowMatchError,
+        ValidatorTelemetrySummary
+    };
+    use reth_tasks::TokioTaskExecutor;
+    use tokio::sync::{broadcast, mpsc};
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct MockConsensus {
+        winners: Option<Vec<ProvisionalWinner>>,
+        results: broadcast::Sender<Vec<AuctionResult>>
+    }
+
+    impl ConsensusHandle for MockConsensus {
+        fn round_info(&self) -> impl Future<Output = Option<ConsensusRoundInfo>> + Send {
+            future::ready(None)
+        }
+
+        fn leader_schedule(
+            &self,
+            _: BlockNumber,
+            _: u64
+        ) -> impl Future<Output = Option<Vec<LeaderSlot>>> + Send {
+            future::ready(None)
+        }
+
+        fn provisional_winners(
+            &self
+        ) -> impl Future<Output = Option<Vec<ProvisionalWinner>>> + Send {
+            future::ready(self.winners.clone())
+        }
+
+        fn subscribe_auction_results(
+            &self
+        ) -> impl Future<Output = Option<broadcast::Receiver<Vec<AuctionResult>>>> + Send {
+            future::ready(Some(self.results.subscribe()))
+        }
+
+        fn fee_ledger(&self) -> impl Future<Output = Option<FeeLedger>> + Send {
+            future::ready(None)
+        }
+
+        fn validator_telemetry(
+            &self
+        ) -> impl Future<Output = Option<Vec<ValidatorTelemetrySummary>>> + Send {
+            future::ready(None)
+        }
+
+        fn submission_targets(
+            &self
+        ) -> impl Future<Output = Option<Vec<SubmissionTargetStats>>> + Send {
+            future::ready(None)
+        }
+
+        fn simulate_order(
+            &self,
+            _: OrderWithStorageData<AllOrders>
+        ) -> impl Future<Output = Option<Result<ShadowMatch, ShadowMatchError>>> + Send {
+            future::ready(None)
+        }
+
+        fn shutdown(&self) -> impl Future<Output = ()> + Send {
+            future::ready(())
+        }
+    }
+
+    fn setup_searcher_api(
+        winners: Option<Vec<ProvisionalWinner>>
+    ) -> (
+        SearcherApi<PoolHandle, MockConsensus, TokioTaskExecutor>,
+        broadcast::Sender<Vec<AuctionResult>>
+    ) {
+        let (manager_tx, _) = mpsc::channel(1);
+        let (pool_manager_tx, _) = broadcast::channel(1);
+        let pool = PoolHandle { manager_tx, pool_manager_tx };
+        let (results, _) = broadcast::channel(4);
+        let consensus = MockConsensus { winners, results: results.clone() };
+
+        (SearcherApi::new(pool, consensus, TokioTaskExecutor::default()), results)
+    }
+
+    fn result(pool_id: PoolId) -> AuctionResult {
+        AuctionResult {
+            height: 3,
+            pool_id,
+            winner: Some(B256::random()),
+            tob_reward: U256::from(1)
+        }
+    }
+
+    #[tokio::test]
+    async fn provisional_winner_of_a_pool() {
+        let (a, b) = (PoolId::repeat_byte(1), PoolId::repeat_byte(2));
+        let winner = ProvisionalWinner {
+            height:     3,
+            pool_id:    a,
+            order_hash: B256::random(),
+            tob_reward: U256::from(10)
+        };
+
+        let (api, _) = setup_searcher_api(Some(vec![winner.clone()]));
+        assert_eq!(api.provisional_winner(a).await.unwrap(), Some(winner));
+        assert_eq!(api.provisional_winner(b).await.unwrap(), None);
+
+        let (api, _) = setup_searcher_api(None);
+        assert!(api.provisional_winner(a).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn auction_results_are_filtered_by_pool() {
+        let (a, b) = (PoolId::repeat_byte(1), PoolId::repeat_byte(2));
+        let (api, results) = setup_searcher_api(Some(vec![]));
+
+        let mut subscription = api
+            .into_rpc()
+            .subscribe_unbounded("searcher_subscribeAuctionResults", [vec![b]])
+            .await
+            .unwrap();
+        results.send(vec![result(a), result(b)]).unwrap();
+
+        let (received, _) = subscription.next::<AuctionResult>().await.unwrap().unwrap();
+        assert_eq!(received.pool_id, b);
+    }
+
+    #[tokio::test]
+    async fn estimated_settlement_cost_follows_validation() {
+        let (api, _) = setup_searcher_api(Some(vec![]));
+        let settlement_costs = SettlementCostEstimator::default();
+        let api = api.with_settlement_costs(settlement_costs.clone());
+        assert_eq!(api.estimated_settlement_cost().await.unwrap(), None);
+
+        settlement_costs.on_new_block(&Header {
+            number: 7,
+            base_fee_per_gas: Some(10),
+            ..Default::default()
+        });
+        settlement_costs.record(7, 200_000);
+
+        let estimate = api.estimated_settlement_cost().await.unwrap().unwrap();
+        assert_eq!((estimate.block, estimate.gas), (8, 200_000));
+        assert_eq!(estimate.cost, 2_000_000);
+    }
+}
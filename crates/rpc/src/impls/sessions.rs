@@ -0,0 +1,95 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc
+    }
+};
+
+use angstrom_types::orders::CancelOrderRequest;
+use parking_lot::Mutex;
+
+/// Most orders a session can hold, so a session that is never closed can't
+/// grow without bound.
+pub const MAX_SESSION_ORDERS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum SessionError {
+    #[error("no open session {0}")]
+    Unknown(u64),
+    #[error("session {0} already holds {MAX_SESSION_ORDERS} orders")]
+    Full(u64)
+}
+
+/// The open order sessions and the cancellations of the orders submitted to
+/// them. A session lives as long as the subscription that opened it, its
+/// orders are cancelled once it closes.
+#[derive(Debug, Clone, Default)]
+pub struct OrderSessions {
+    sessions: Arc<Mutex<HashMap<u64, Vec<CancelOrderRequest>>>>,
+    last_id:  Arc<AtomicU64>
+}
+
+impl OrderSessions {
+    pub fn open(&self) -> u64 {
+        let id = self.last_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.sessions.lock().insert(id, vec![]);
+        id
+    }
+
+    /// Adds an order to the session, to be cancelled with `cancel` once the
+    /// session closes.
+    pub fn add(&self, session_id: u64, cancel: CancelOrderRequest) -> Result<(), SessionError> {
+        let mut sessions = self.sessions.lock();
+        let orders = sessions
+            .get_mut(&session_id)
+            .ok_or(SessionError::Unknown(session_id))?;
+        if orders.len() == MAX_SESSION_ORDERS {
+            return Err(SessionError::Full(session_id))
+        }
+        orders.push(cancel);
+
+        Ok(())
+    }
+
+    pub fn is_open(&self, session_id: u64) -> bool {
+        self.sessions.lock().contains_key(&session_id)
+    }
+
+    /// Closes the session, returning the cancellations of its orders.
+    pub fn close(&self, session_id: u64) -> Vec<CancelOrderRequest> {
+        self.sessions.lock().remove(&session_id).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::{Address, PrimitiveSignature, B256, U256};
+
+    use super::*;
+
+    fn cancel() -> CancelOrderRequest {
+        CancelOrderRequest {
+            signature:    PrimitiveSignature::new(U256::from(1), U256::from(1), false),
+            user_address: Address::ZERO,
+            order_id:     B256::random()
+        }
+    }
+
+    #[test]
+    fn closing_a_session_hands_out_its_orders() {
+        let sessions = OrderSessions::default();
+        let (a, b) = (sessions.open(), sessions.open());
+        assert_ne!(a, b);
+
+        let order = cancel();
+        sessions.add(a, order.clone()).unwrap();
+        sessions.add(b, cancel()).unwrap();
+
+        assert_eq!(sessions.close(a), vec![order]);
+        assert!(!sessions.is_open(a));
+        assert_eq!(sessions.add(a, cancel()), Err(SessionError::Unknown(a)));
+        assert!(sessions.close(a).is_empty());
+        assert!(sessions.is_open(b));
+    }
+}
@@ -0,0 +1,18 @@
+use alloy_primitives::{Address, BlockNumber};
+use angstrom_types::{
+    matching::uniswap::PoolSnapshot, primitive::PoolId, sol_bindings::grouped_orders::AllOrders
+};
+use serde::{Deserialize, Serialize};
+
+/// A pool as it was once a block was built.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoricalPoolSnapshot {
+    pub pool_id:      PoolId,
+    pub block_number: BlockNumber,
+    pub token0:       Address,
+    pub token1:       Address,
+    pub amm:          PoolSnapshot,
+    /// the orders resting in the book, to be matched in the next block
+    pub orders:       Vec<AllOrders>
+}
@@ -1,5 +1,9 @@
+pub mod history;
 pub mod quoting;
+pub mod session;
 pub mod subscriptions;
 
+pub use history::*;
 pub use quoting::*;
+pub use session::*;
 pub use subscriptions::*;
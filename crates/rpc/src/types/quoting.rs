@@ -1,13 +1,45 @@
 use alloy_primitives::{FixedBytes, U256};
+use angstrom_types::{matching::uniswap::QuoteRejection, primitive::TokenMetadata};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct GasEstimateUpdate {
-    timestamp:        u128,
-    pair:             FixedBytes<32>,
-    estimate_wei:     u64,
-    old_estimate_erc: U256,
-    new_estimate_erc: U256
+    timestamp:          u128,
+    pair:               FixedBytes<32>,
+    estimate_wei:       u64,
+    old_estimate_erc:   U256,
+    new_estimate_erc:   U256,
+    /// the new estimate in whole tokens
+    new_estimate_units: f64
+}
+
+impl GasEstimateUpdate {
+    pub fn new(
+        timestamp: u128,
+        pair: FixedBytes<32>,
+        estimate_wei: u64,
+        old_estimate_erc: U256,
+        new_estimate_erc: U256,
+        token: &TokenMetadata
+    ) -> Self {
+        Self {
+            timestamp,
+            pair,
+            estimate_wei,
+            old_estimate_erc,
+            new_estimate_erc,
+            new_estimate_units: token.to_units(new_estimate_erc)
+        }
+    }
+}
+
+/// An item of the gas estimate subscription. A pair whose AMM snapshot or
+/// price is stale isn't priced, the reason is sent instead.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum GasQuote {
+    Priced(GasEstimateUpdate),
+    Rejected { timestamp: u128, pair: FixedBytes<32>, reason: QuoteRejection }
 }
 
 #[derive(
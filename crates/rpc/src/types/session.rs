@@ -0,0 +1,18 @@
+use alloy_primitives::PrimitiveSignature;
+use serde::{Deserialize, Serialize};
+
+/// Submits an order as a session order, which is cancelled once the session
+/// it is submitted to closes, e.g. because the websocket connection that
+/// opened it dropped.
+///
+/// The cancellation is signed along with the order, so it reaches the peers
+/// the order was gossiped to like any other cancellation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionOrder {
+    /// announced by `angstrom_subscribeSession`
+    pub session_id:       u64,
+    /// signature of the order's signer over the `CancelOrderRequest` of the
+    /// order
+    pub cancel_signature: PrimitiveSignature
+}
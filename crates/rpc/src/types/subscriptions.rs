@@ -1,7 +1,11 @@
 use std::sync::Arc;
 
 use alloy_primitives::{Address, FixedBytes, B256};
-use angstrom_types::{consensus::*, sol_bindings::grouped_orders::AllOrders};
+use angstrom_types::{
+    consensus::*,
+    orders::{BookDelta, BookSnapshot, OrderStatesSnapshot},
+    sol_bindings::grouped_orders::AllOrders
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
@@ -62,5 +66,19 @@ pub enum OrderSubscriptionResult {
     NewOrder(AllOrders),
     FilledOrder(u64, AllOrders),
     UnfilledOrder(AllOrders),
-    CancelledOrder(B256)
+    CancelledOrder(B256),
+    /// The subscriber fell behind and missed updates. Carries the current state
+    /// of the orders matching its filter to rebuild from. Updates that follow
+    /// can overlap with what the snapshot already reflects.
+    Resync(OrderStatesSnapshot)
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+#[serde(rename_all = "camelCase")]
+pub enum BookSubscriptionResult {
+    /// The whole book. Sent first, and again whenever the subscriber fell
+    /// behind. The deltas that follow continue from its sequence.
+    Snapshot(BookSnapshot),
+    Delta(BookDelta)
 }
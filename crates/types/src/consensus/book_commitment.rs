@@ -0,0 +1,109 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use alloy::primitives::{keccak256, B256};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    primitive::PoolId,
+    sol_bindings::{
+        grouped_orders::{GroupedVanillaOrder, OrderWithStorageData},
+        rpc_orders::TopOfBlockOrder
+    }
+};
+
+/// Canonical commitment to the orders a validator deems eligible for a block.
+/// Every pool with orders commits to the sorted hashes of its orders, so two
+/// validators with the same orders come to the same commitment no matter the
+/// order they have them in, and the pools they disagree on can be told apart.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BookCommitment {
+    /// the commitment of every pool with orders, sorted by pool id
+    pub pools: Vec<(PoolId, B256)>
+}
+
+impl BookCommitment {
+    /// The commitment to the orders with the given pool ids and hashes.
+    pub fn new(orders: impl IntoIterator<Item = (PoolId, B256)>) -> Self {
+        let pools = orders
+            .into_iter()
+            .fold(BTreeMap::<PoolId, BTreeSet<B256>>::new(), |mut pools, (pool, hash)| {
+                pools.entry(pool).or_default().insert(hash);
+                pools
+            })
+            .into_iter()
+            .map(|(pool, hashes)| {
+                let mut buf = pool.to_vec();
+                hashes.iter().for_each(|hash| buf.extend(hash));
+                (pool, keccak256(buf))
+            })
+            .collect();
+
+        Self { pools }
+    }
+
+    pub fn from_orders(
+        limit: &[OrderWithStorageData<GroupedVanillaOrder>],
+        searcher: &[OrderWithStorageData<TopOfBlockOrder>]
+    ) -> Self {
+        Self::new(
+            limit
+                .iter()
+                .map(|order| (order.pool_id, order.order_id.hash))
+                .chain(
+                    searcher
+                        .iter()
+                        .map(|order| (order.pool_id, order.order_id.hash))
+                )
+        )
+    }
+
+    /// Hash of the commitments of all the pools.
+    pub fn hash(&self) -> B256 {
+        keccak256(
+            self.pools
+                .iter()
+                .flat_map(|(pool, commitment)| pool.iter().chain(commitment.iter()))
+                .copied()
+                .collect::<Vec<_>>()
+        )
+    }
+
+    /// The pools whose orders differ between the two commitments, a pool that
+    /// only one of them has included.
+    pub fn disagreements(&self, other: &Self) -> Vec<PoolId> {
+        let ours = self.pools.iter().copied().collect::<BTreeMap<_, _>>();
+        let theirs = other.pools.iter().copied().collect::<BTreeMap<_, _>>();
+
+        ours.keys()
+            .chain(theirs.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter(|pool| ours.get(pool) != theirs.get(pool))
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commitment_is_canonical() {
+        let (a, b) = (PoolId::repeat_byte(1), PoolId::repeat_byte(2));
+        let order = B256::repeat_byte;
+
+        let commitment = BookCommitment::new([(a, order(1)), (b, order(2)), (a, order(3))]);
+        let reordered = BookCommitment::new([(b, order(2)), (a, order(3)), (a, order(1))]);
+        assert_eq!(commitment, reordered);
+        assert_eq!(commitment.hash(), reordered.hash());
+        assert!(commitment.disagreements(&reordered).is_empty());
+
+        let other = BookCommitment::new([(a, order(1)), (b, order(2))]);
+        assert_ne!(commitment.hash(), other.hash());
+        assert_eq!(commitment.disagreements(&other), vec![a]);
+
+        let no_b = BookCommitment::new([(a, order(1)), (a, order(3))]);
+        assert_eq!(commitment.disagreements(&no_b), vec![b]);
+    }
+}
@@ -1,9 +1,17 @@
+pub mod book_commitment;
 pub mod evidence;
+pub mod order_receipt;
 pub mod pre_prepose;
 pub mod pre_propose_agg;
 pub mod proposal;
+pub mod submission_approval;
+pub mod telemetry;
 
+pub use book_commitment::*;
 pub use evidence::*;
+pub use order_receipt::*;
 pub use pre_prepose::*;
 pub use pre_propose_agg::*;
 pub use proposal::*;
+pub use submission_approval::*;
+pub use telemetry::*;
@@ -0,0 +1,38 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy::primitives::B256;
+use serde::{Deserialize, Serialize};
+
+/// How long after the parent block orders are still taken into the auction of
+/// the next block.
+pub const AUCTION_CUTOFF: Duration = Duration::from_millis(11_200);
+
+/// When a node accepted an order into its pool. Receipts are part of the
+/// signed payload of the node's pre-proposal, so every validator can check
+/// which orders made the cutoff of a block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OrderReceipt {
+    pub order_hash:  B256,
+    /// unix time in milliseconds
+    pub received_at: u64
+}
+
+impl OrderReceipt {
+    pub fn now(order_hash: B256) -> Self {
+        let received_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        Self { order_hash, received_at }
+    }
+}
+
+/// The last time, in unix milliseconds, an order can be received at to take
+/// part in the auction of the block after the one with `parent_timestamp`.
+/// It only depends on the chain, so all validators agree on it.
+pub fn auction_cutoff(parent_timestamp: u64) -> u64 {
+    parent_timestamp
+        .saturating_mul(1000)
+        .saturating_add(AUCTION_CUTOFF.as_millis() as u64)
+}
@@ -4,7 +4,7 @@ use std::{
 };
 
 use alloy::{
-    primitives::{keccak256, BlockNumber},
+    primitives::{keccak256, BlockNumber, B256},
     signers::{Signature, SignerSync}
 };
 use alloy_primitives::U256;
@@ -12,6 +12,7 @@ use bytes::Bytes;
 use reth_network_peers::PeerId;
 use serde::{Deserialize, Serialize};
 
+use super::{BookCommitment, OrderReceipt};
 use crate::{
     orders::OrderSet,
     primitive::{AngstromSigner, PoolId},
@@ -23,35 +24,43 @@ use crate::{
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
 pub struct PreProposal {
-    pub block_height: BlockNumber,
-    pub source:       PeerId,
+    pub block_height:    BlockNumber,
+    pub source:          PeerId,
     // TODO: this really should be HashMap<PoolId, GroupedVanillaOrder>
-    pub limit:        Vec<OrderWithStorageData<GroupedVanillaOrder>>,
+    pub limit:           Vec<OrderWithStorageData<GroupedVanillaOrder>>,
     // TODO: this really should be another type with HashMap<PoolId, {order, tob_reward}>
-    pub searcher:     Vec<OrderWithStorageData<TopOfBlockOrder>>,
-    /// The signature is over the ethereum height as well as the limit and
-    /// searcher sets
-    pub signature:    Signature
+    pub searcher:        Vec<OrderWithStorageData<TopOfBlockOrder>>,
+    /// when the source received the orders above
+    pub receipts:        Vec<OrderReceipt>,
+    /// commitment to the limit and searcher orders above
+    pub book_commitment: BookCommitment,
+    /// The signature is over the ethereum height, the limit and searcher sets,
+    /// the receipts as well as the book commitment
+    pub signature:       Signature
 }
 
 impl Default for PreProposal {
     fn default() -> Self {
         Self {
-            signature:    Signature::new(U256::ZERO, U256::ZERO, false),
-            block_height: Default::default(),
-            source:       Default::default(),
-            limit:        Default::default(),
-            searcher:     Default::default()
+            signature:       Signature::new(U256::ZERO, U256::ZERO, false),
+            block_height:    Default::default(),
+            source:          Default::default(),
+            limit:           Default::default(),
+            searcher:        Default::default(),
+            receipts:        Default::default(),
+            book_commitment: Default::default()
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PreProposalContent {
-    pub block_height: BlockNumber,
-    pub source:       PeerId,
-    pub limit:        Vec<OrderWithStorageData<GroupedVanillaOrder>>,
-    pub searcher:     Vec<OrderWithStorageData<TopOfBlockOrder>>
+    pub block_height:    BlockNumber,
+    pub source:          PeerId,
+    pub limit:           Vec<OrderWithStorageData<GroupedVanillaOrder>>,
+    pub searcher:        Vec<OrderWithStorageData<TopOfBlockOrder>>,
+    pub receipts:        Vec<OrderReceipt>,
+    pub book_commitment: BookCommitment
 }
 
 // the reason for the manual implementation is because EcDSA signatures are not
@@ -64,16 +73,20 @@ impl std::hash::Hash for PreProposalContent {
         self.source.hash(state);
         self.limit.hash(state);
         self.searcher.hash(state);
+        self.receipts.hash(state);
+        self.book_commitment.hash(state);
     }
 }
 
 impl PreProposal {
     pub fn content(&self) -> PreProposalContent {
         PreProposalContent {
-            block_height: self.block_height,
-            source:       self.source,
-            limit:        self.limit.clone(),
-            searcher:     self.searcher.clone()
+            block_height:    self.block_height,
+            source:          self.source,
+            limit:           self.limit.clone(),
+            searcher:        self.searcher.clone(),
+            receipts:        self.receipts.clone(),
+            book_commitment: self.book_commitment.clone()
         }
     }
 }
@@ -88,51 +101,126 @@ impl PreProposal {
         ethereum_height: BlockNumber,
         sk: &AngstromSigner,
         limit: Vec<OrderWithStorageData<GroupedVanillaOrder>>,
-        searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>
+        searcher: Vec<OrderWithStorageData<TopOfBlockOrder>>,
+        receipts: Vec<OrderReceipt>
     ) -> Self {
-        let payload = Self::serialize_payload(&ethereum_height, &limit, &searcher);
+        let book_commitment = BookCommitment::from_orders(&limit, &searcher);
+        let payload = Self::serialize_payload(
+            &ethereum_height,
+            &limit,
+            &searcher,
+            &receipts,
+            &book_commitment
+        );
         let signature = Self::sign_payload(sk, payload);
 
-        Self { limit, source: sk.id(), searcher, block_height: ethereum_height, signature }
+        Self {
+            limit,
+            source: sk.id(),
+            searcher,
+            receipts,
+            book_commitment,
+            block_height: ethereum_height,
+            signature
+        }
     }
 
     pub fn new(
         ethereum_height: u64,
         sk: &AngstromSigner,
-        orders: OrderSet<GroupedVanillaOrder, TopOfBlockOrder>
+        orders: OrderSet<GroupedVanillaOrder, TopOfBlockOrder>,
+        receipts: Vec<OrderReceipt>
     ) -> Self {
         let OrderSet { limit, searcher } = orders;
         let limit_orders = limit.len();
         let searcher_orders = searcher.len();
         tracing::info!(%limit_orders,%searcher_orders, %ethereum_height,"building my pre_proposal");
-        Self::generate_pre_proposal(ethereum_height, sk, limit, searcher)
+        Self::generate_pre_proposal(ethereum_height, sk, limit, searcher, receipts)
     }
 
-    /// ensures block height is correct as-well as validates the signature.
+    /// ensures block height is correct as-well as validates the signature and
+    /// that the book commitment is the one of the orders.
     pub fn is_valid(&self, block_height: &BlockNumber) -> bool {
-        let hash = keccak256(self.payload());
+        let hash = self.hash();
         let Ok(source) = self.signature.recover_from_prehash(&hash) else {
             return false;
         };
         let source = AngstromSigner::public_key_to_peer_id(&source);
 
-        source == self.source && &self.block_height == block_height
+        source == self.source
+            && &self.block_height == block_height
+            && self.book_commitment == BookCommitment::from_orders(&self.limit, &self.searcher)
+    }
+
+    /// hash of the signed payload, identifies the pre_proposal.
+    pub fn hash(&self) -> B256 {
+        keccak256(self.payload())
+    }
+
+    /// Hashes of the limit and searcher orders.
+    pub fn order_hashes(&self) -> impl Iterator<Item = B256> + '_ {
+        self.limit
+            .iter()
+            .map(|order| order.order_id.hash)
+            .chain(self.searcher.iter().map(|order| order.order_id.hash))
     }
 
     fn serialize_payload(
         block_height: &BlockNumber,
         limit: &Vec<OrderWithStorageData<GroupedVanillaOrder>>,
-        searcher: &Vec<OrderWithStorageData<TopOfBlockOrder>>
+        searcher: &Vec<OrderWithStorageData<TopOfBlockOrder>>,
+        receipts: &Vec<OrderReceipt>,
+        book_commitment: &BookCommitment
     ) -> Vec<u8> {
         let mut buf = Vec::new();
         buf.extend(bincode::serialize(block_height).unwrap());
         buf.extend(bincode::serialize(limit).unwrap());
         buf.extend(bincode::serialize(searcher).unwrap());
+        buf.extend(bincode::serialize(receipts).unwrap());
+        buf.extend(bincode::serialize(book_commitment).unwrap());
         buf
     }
 
     fn payload(&self) -> Bytes {
-        Bytes::from(Self::serialize_payload(&self.block_height, &self.limit, &self.searcher))
+        Bytes::from(Self::serialize_payload(
+            &self.block_height,
+            &self.limit,
+            &self.searcher,
+            &self.receipts,
+            &self.book_commitment
+        ))
+    }
+
+    /// The orders the source received by `cutoff`, in unix milliseconds.
+    /// Orders without a receipt are treated as received too late.
+    #[allow(clippy::type_complexity)]
+    pub fn orders_received_by(
+        self,
+        cutoff: u64
+    ) -> (Vec<OrderWithStorageData<GroupedVanillaOrder>>, Vec<OrderWithStorageData<TopOfBlockOrder>>)
+    {
+        let in_time = self
+            .receipts
+            .iter()
+            .filter(|receipt| receipt.received_at <= cutoff)
+            .map(|receipt| receipt.order_hash)
+            .collect::<HashSet<_>>();
+
+        let (limit, late_limit): (Vec<_>, Vec<_>) = self
+            .limit
+            .into_iter()
+            .partition(|order| in_time.contains(&order.order_id.hash));
+        let (searcher, late_searcher): (Vec<_>, Vec<_>) = self
+            .searcher
+            .into_iter()
+            .partition(|order| in_time.contains(&order.order_id.hash));
+
+        let late = late_limit.len() + late_searcher.len();
+        if late != 0 {
+            tracing::debug!(source=?self.source, late, "dropping orders received after the cutoff");
+        }
+
+        (limit, searcher)
     }
 
     pub fn orders_by_pool_id(
@@ -152,8 +240,13 @@ impl PreProposal {
 #[cfg(test)]
 mod tests {
 
+    use alloy::primitives::B256;
+
     use super::PreProposal;
-    use crate::primitive::AngstromSigner;
+    use crate::{
+        consensus::OrderReceipt, primitive::AngstromSigner,
+        sol_bindings::grouped_orders::OrderWithStorageData
+    };
 
     #[test]
     fn can_be_constructed() {
@@ -161,7 +254,7 @@ mod tests {
         let limit = vec![];
         let searcher = vec![];
         let sk = AngstromSigner::random();
-        PreProposal::generate_pre_proposal(ethereum_height, &sk, limit, searcher);
+        PreProposal::generate_pre_proposal(ethereum_height, &sk, limit, searcher, vec![]);
     }
 
     #[test]
@@ -171,8 +264,72 @@ mod tests {
         let searcher = vec![];
         // Generate crypto stuff
         let sk = AngstromSigner::random();
-        let preproposal = PreProposal::generate_pre_proposal(ethereum_height, &sk, limit, searcher);
+        let preproposal =
+            PreProposal::generate_pre_proposal(ethereum_height, &sk, limit, searcher, vec![]);
 
         assert!(preproposal.is_valid(&ethereum_height), "Unable to validate self");
     }
+
+    #[test]
+    fn orders_received_after_the_cutoff_are_dropped() {
+        let order = |hash: u8| {
+            let mut order = OrderWithStorageData::default();
+            order.order_id.hash = B256::repeat_byte(hash);
+            order
+        };
+        let receipt = |hash: u8, received_at: u64| OrderReceipt {
+            order_hash: B256::repeat_byte(hash),
+            received_at
+        };
+
+        let sk = AngstromSigner::random();
+        let mut preproposal = PreProposal::generate_pre_proposal(
+            100,
+            &sk,
+            vec![order(1), order(2), order(3)],
+            vec![order(4), order(5)],
+            vec![receipt(1, 1_000), receipt(2, 1_001), receipt(4, 999), receipt(5, 2_000)]
+        );
+        assert!(preproposal.is_valid(&100));
+
+        let (limit, searcher) = preproposal.clone().orders_received_by(1_000);
+        assert_eq!(
+            limit
+                .iter()
+                .map(|order| order.order_id.hash)
+                .collect::<Vec<_>>(),
+            vec![B256::repeat_byte(1)]
+        );
+        assert_eq!(
+            searcher
+                .iter()
+                .map(|order| order.order_id.hash)
+                .collect::<Vec<_>>(),
+            vec![B256::repeat_byte(4)]
+        );
+
+        // receipts can't be moved forward without the source's key
+        preproposal.receipts[1].received_at = 1_000;
+        assert!(!preproposal.is_valid(&100));
+    }
+
+    #[test]
+    fn book_commitment_has_to_match_the_orders() {
+        let mut order = OrderWithStorageData::default();
+        order.order_id.hash = B256::repeat_byte(1);
+
+        let sk = AngstromSigner::random();
+        let preproposal = PreProposal::generate_pre_proposal(100, &sk, vec![order], vec![], vec![]);
+        assert!(preproposal.is_valid(&100));
+
+        // a commitment that doesn't match can't be signed into a pre-proposal
+        let mut tampered = preproposal.clone();
+        tampered.limit.clear();
+        assert!(!tampered.is_valid(&100));
+
+        let forged = PreProposal::generate_pre_proposal(100, &sk, vec![], vec![], vec![]);
+        let mut forged = PreProposal { book_commitment: preproposal.book_commitment, ..forged };
+        forged.signature = PreProposal::sign_payload(&sk, forged.payload().to_vec());
+        assert!(!forged.is_valid(&100));
+    }
 }
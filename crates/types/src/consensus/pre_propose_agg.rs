@@ -1,5 +1,7 @@
+use std::collections::BTreeSet;
+
 use alloy::{
-    primitives::{keccak256, BlockNumber, U256},
+    primitives::{keccak256, BlockNumber, B256, U256},
     signers::{Signature, SignerSync}
 };
 use bytes::Bytes;
@@ -8,11 +10,58 @@ use serde::{Deserialize, Serialize};
 
 use crate::{consensus::PreProposal, primitive::AngstromSigner};
 
+/// A subset of the validators, by their position in the set ordered by peer id
+/// so that every validator agrees on the positions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct ValidatorBitmap(Vec<u8>);
+
+impl ValidatorBitmap {
+    /// The `members` that are part of `validators`.
+    pub fn new(validators: &BTreeSet<PeerId>, members: impl IntoIterator<Item = PeerId>) -> Self {
+        let mut bits = vec![0u8; validators.len().div_ceil(8)];
+        members
+            .into_iter()
+            .filter_map(|member| validators.iter().position(|v| *v == member))
+            .for_each(|idx| bits[idx / 8] |= 1 << (idx % 8));
+
+        Self(bits)
+    }
+
+    pub fn contains(&self, idx: usize) -> bool {
+        self.0
+            .get(idx / 8)
+            .is_some_and(|byte| byte & (1 << (idx % 8)) != 0)
+    }
+
+    /// Number of set bits, including any beyond the validator set.
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|byte| byte.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn members<'a>(
+        &'a self,
+        validators: &'a BTreeSet<PeerId>
+    ) -> impl Iterator<Item = PeerId> + 'a {
+        validators
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| self.contains(*idx))
+            .map(|(_, validator)| *validator)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct PreProposalAggregation {
     pub block_height:  BlockNumber,
     pub source:        PeerId,
     pub pre_proposals: Vec<PreProposal>,
+    /// the validators whose pre-proposals were aggregated, each of them
+    /// signed its own
+    pub contributors:  ValidatorBitmap,
     pub signature:     Signature
 }
 
@@ -22,6 +71,7 @@ impl Default for PreProposalAggregation {
             block_height:  Default::default(),
             source:        Default::default(),
             pre_proposals: Default::default(),
+            contributors:  Default::default(),
             signature:     Signature::new(U256::ZERO, U256::ZERO, false)
         }
     }
@@ -31,11 +81,14 @@ impl PreProposalAggregation {
     pub fn new(
         block_height: BlockNumber,
         sk: &AngstromSigner,
-        pre_proposals: Vec<PreProposal>
+        pre_proposals: Vec<PreProposal>,
+        validators: &BTreeSet<PeerId>
     ) -> Self {
-        let payload = Self::serialize_payload(&block_height, &pre_proposals);
+        let contributors =
+            ValidatorBitmap::new(validators, pre_proposals.iter().map(|pre| pre.source));
+        let payload = Self::serialize_payload(&block_height, &contributors, &pre_proposals);
         let signature = Self::sign_payload(sk, payload);
-        Self { block_height, source: sk.id(), pre_proposals, signature }
+        Self { block_height, source: sk.id(), pre_proposals, contributors, signature }
     }
 
     fn sign_payload(sk: &AngstromSigner, payload: Vec<u8>) -> Signature {
@@ -44,15 +97,29 @@ impl PreProposalAggregation {
         sk.sign_hash_sync(&hash).unwrap()
     }
 
-    fn serialize_payload(block_height: &BlockNumber, pre_proposals: &[PreProposal]) -> Vec<u8> {
+    fn serialize_payload(
+        block_height: &BlockNumber,
+        contributors: &ValidatorBitmap,
+        pre_proposals: &[PreProposal]
+    ) -> Vec<u8> {
         let mut buf = Vec::new();
         buf.extend(bincode::serialize(block_height).unwrap());
+        buf.extend(bincode::serialize(contributors).unwrap());
         buf.extend(bincode::serialize(pre_proposals).unwrap());
         buf
     }
 
     fn payload(&self) -> Bytes {
-        Bytes::from(Self::serialize_payload(&self.block_height, &self.pre_proposals))
+        Bytes::from(Self::serialize_payload(
+            &self.block_height,
+            &self.contributors,
+            &self.pre_proposals
+        ))
+    }
+
+    /// hash of the signed payload, identifies the aggregation.
+    pub fn hash(&self) -> B256 {
+        keccak256(self.payload())
     }
 
     pub fn is_valid(&self, block_height: &BlockNumber) -> bool {
@@ -63,7 +130,7 @@ impl PreProposalAggregation {
         {
             return false
         }
-        let hash = keccak256(self.payload());
+        let hash = self.hash();
         let Ok(source) = self.signature.recover_from_prehash(&hash) else {
             return false;
         };
@@ -71,4 +138,72 @@ impl PreProposalAggregation {
 
         source == self.source
     }
+
+    /// Whether the aggregation holds exactly one pre-proposal of each of the
+    /// `contributors`, and they are at least `quorum` of the `validators`.
+    /// The pre-proposals' signatures are checked by [`Self::is_valid`].
+    pub fn is_quorum_of(&self, validators: &BTreeSet<PeerId>, quorum: usize) -> bool {
+        let contributors = self
+            .contributors
+            .members(validators)
+            .collect::<BTreeSet<_>>();
+        let sources = self
+            .pre_proposals
+            .iter()
+            .map(|pre| pre.source)
+            .collect::<BTreeSet<_>>();
+
+        // bits beyond the validator set or two pre-proposals of one validator
+        contributors.len() == self.contributors.len()
+            && sources.len() == self.pre_proposals.len()
+            && contributors == sources
+            && contributors.len() >= quorum
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregation_needs_a_quorum_of_contributors() {
+        let signers = (0..4).map(|_| AngstromSigner::random()).collect::<Vec<_>>();
+        let validators = signers.iter().map(|sk| sk.id()).collect::<BTreeSet<_>>();
+        let pre_proposals = signers
+            .iter()
+            .map(|sk| PreProposal::generate_pre_proposal(1, sk, vec![], vec![], vec![]))
+            .collect::<Vec<_>>();
+
+        let agg =
+            PreProposalAggregation::new(1, &signers[0], pre_proposals[..3].to_vec(), &validators);
+        assert!(agg.is_valid(&1));
+        assert_eq!(agg.contributors.len(), 3);
+        assert!(agg.is_quorum_of(&validators, 3));
+        assert!(!agg.is_quorum_of(&validators, 4));
+
+        // the bitmap has to name the validators whose pre-proposals it holds
+        let mut forged = agg.clone();
+        forged.contributors = ValidatorBitmap::new(&validators, validators.iter().copied());
+        assert!(!forged.is_quorum_of(&validators, 3));
+        assert!(!forged.is_valid(&1));
+
+        // a validator counts once
+        let duplicated =
+            vec![pre_proposals[0].clone(), pre_proposals[0].clone(), pre_proposals[1].clone()];
+        let agg = PreProposalAggregation::new(1, &signers[0], duplicated, &validators);
+        assert!(!agg.is_quorum_of(&validators, 2));
+
+        // as do only validators of the set
+        let outsider = AngstromSigner::random();
+        let mut with_outsider = pre_proposals[..2].to_vec();
+        with_outsider.push(PreProposal::generate_pre_proposal(
+            1,
+            &outsider,
+            vec![],
+            vec![],
+            vec![]
+        ));
+        let agg = PreProposalAggregation::new(1, &signers[0], with_outsider, &validators);
+        assert!(!agg.is_quorum_of(&validators, 2));
+    }
 }
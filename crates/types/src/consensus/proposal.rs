@@ -1,5 +1,7 @@
+use std::collections::HashMap;
+
 use alloy::{
-    primitives::{BlockNumber, U256},
+    primitives::{BlockNumber, B256, U256},
     signers::{Signature, SignerSync}
 };
 use alloy_primitives::keccak256;
@@ -7,34 +9,40 @@ use bytes::Bytes;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
-use super::{PreProposal, PreProposalAggregation};
+use super::{BookCommitment, PreProposal, PreProposalAggregation};
 use crate::{
+    matching::ucp::{self, UcpViolation},
     orders::PoolSolution,
-    primitive::{AngstromSigner, PeerId}
+    primitive::{AngstromSigner, PeerId},
+    sol_bindings::grouped_orders::{GroupedVanillaOrder, OrderWithStorageData}
 };
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Proposal {
     // Might not be necessary as this is encoded in all the proposals anyways
-    pub block_height: BlockNumber,
-    pub source:       PeerId,
+    pub block_height:    BlockNumber,
+    pub source:          PeerId,
     /// PreProposals sorted by source
-    pub preproposals: Vec<PreProposalAggregation>,
+    pub preproposals:    Vec<PreProposalAggregation>,
     /// PoolSolutions sorted by PoolId
-    pub solutions:    Vec<PoolSolution>,
+    pub solutions:       Vec<PoolSolution>,
+    /// commitment to the orders of the pre-proposals that were eligible for
+    /// the block, the ones the solutions were solved from
+    pub book_commitment: BookCommitment,
     /// This signature is over (etheruem_block | hash(vanilla_bundle) |
     /// hash(order_buffer) | hash(lower_bound))
-    pub signature:    Signature
+    pub signature:       Signature
 }
 
 impl Default for Proposal {
     fn default() -> Self {
         Self {
-            block_height: Default::default(),
-            source:       Default::default(),
-            preproposals: Default::default(),
-            solutions:    Default::default(),
-            signature:    Signature::new(U256::ZERO, U256::ZERO, false)
+            block_height:    Default::default(),
+            source:          Default::default(),
+            preproposals:    Default::default(),
+            solutions:       Default::default(),
+            book_commitment: Default::default(),
+            signature:       Signature::new(U256::ZERO, U256::ZERO, false)
         }
     }
 }
@@ -44,27 +52,24 @@ impl Proposal {
         ethereum_height: BlockNumber,
         sk: &AngstromSigner,
         preproposals: Vec<PreProposalAggregation>,
-        mut solutions: Vec<PoolSolution>
+        mut solutions: Vec<PoolSolution>,
+        book_commitment: BookCommitment
     ) -> Self {
         // Sort our solutions
         solutions.sort_by_key(|sol| sol.id);
 
-        // Build our hash and sign
-        let mut buf = Vec::new();
-        buf.extend(bincode::serialize(&ethereum_height).unwrap());
-        buf.extend(&sk.id());
-        buf.extend(bincode::serialize(&preproposals).unwrap());
-        buf.extend(bincode::serialize(&solutions).unwrap());
-        let hash = keccak256(buf);
-        let sig = sk.sign_hash_sync(&hash).unwrap();
-
-        Self {
+        let mut proposal = Self {
             block_height: ethereum_height,
             source: sk.id(),
             preproposals,
             solutions,
-            signature: sig
-        }
+            book_commitment,
+            ..Default::default()
+        };
+        // Build our hash and sign
+        proposal.signature = sk.sign_hash_sync(&proposal.hash()).unwrap();
+
+        proposal
     }
 
     pub fn preproposals(&self) -> &Vec<PreProposalAggregation> {
@@ -81,7 +86,7 @@ impl Proposal {
             return false
         }
         // Then our own signature has to be valid
-        let hash = keccak256(self.payload());
+        let hash = self.hash();
         let Ok(source) = self.signature.recover_from_prehash(&hash) else {
             return false;
         };
@@ -90,12 +95,33 @@ impl Proposal {
         source == self.source
     }
 
+    /// Checks that no solution fills one of the orders of the pre-proposals
+    /// outside of its limit at the UCP it claims.
+    pub fn check_ucps(&self) -> Result<(), UcpViolation> {
+        let pre_proposals = self.flattened_pre_proposals();
+        let orders = pre_proposals
+            .iter()
+            .flat_map(|pre_proposal| pre_proposal.limit.iter())
+            .map(|order| (order.order_id.hash, order))
+            .collect::<HashMap<B256, &OrderWithStorageData<GroupedVanillaOrder>>>();
+
+        self.solutions.iter().try_for_each(|solution| {
+            ucp::check_fills(solution.ucp, &solution.limit, |hash| orders.get(hash).copied())
+        })
+    }
+
+    /// hash of the signed payload, identifies the proposal.
+    pub fn hash(&self) -> B256 {
+        keccak256(self.payload())
+    }
+
     fn payload(&self) -> Bytes {
         let mut buf = vec![];
         buf.extend(bincode::serialize(&self.block_height).unwrap());
         buf.extend(*self.source);
         buf.extend(bincode::serialize(&self.preproposals).unwrap());
         buf.extend(bincode::serialize(&self.solutions).unwrap());
+        buf.extend(bincode::serialize(&self.book_commitment).unwrap());
 
         Bytes::from_iter(buf)
     }
@@ -112,7 +138,7 @@ impl Proposal {
 #[cfg(test)]
 mod tests {
     use super::Proposal;
-    use crate::primitive::AngstromSigner;
+    use crate::{consensus::BookCommitment, primitive::AngstromSigner};
 
     #[test]
     fn can_be_constructed() {
@@ -120,7 +146,13 @@ mod tests {
         let preproposals = vec![];
         let solutions = vec![];
         let sk = AngstromSigner::random();
-        Proposal::generate_proposal(ethereum_height, &sk, preproposals, solutions);
+        Proposal::generate_proposal(
+            ethereum_height,
+            &sk,
+            preproposals,
+            solutions,
+            BookCommitment::default()
+        );
     }
 
     #[test]
@@ -130,7 +162,13 @@ mod tests {
         let solutions = vec![];
         // Generate crypto stuff
         let sk = AngstromSigner::random();
-        let proposal = Proposal::generate_proposal(ethereum_height, &sk, preproposals, solutions);
+        let proposal = Proposal::generate_proposal(
+            ethereum_height,
+            &sk,
+            preproposals,
+            solutions,
+            BookCommitment::default()
+        );
 
         assert!(proposal.is_valid(&ethereum_height), "Unable to validate self");
     }
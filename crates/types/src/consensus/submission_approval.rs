@@ -0,0 +1,76 @@
+use alloy::{
+    primitives::{keccak256, BlockNumber, Bytes, B256},
+    signers::{Signature, SignerSync},
+    sol_types::SolValue
+};
+use reth_network_peers::PeerId;
+use serde::{Deserialize, Serialize};
+
+use crate::primitive::AngstromSigner;
+
+/// The leader's signed settlement transaction, sent to the approvers before it
+/// is broadcast.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SubmissionApprovalRequest {
+    pub block_height: BlockNumber,
+    /// eip-2718 encoding of the transaction, as it will be broadcast
+    pub raw_tx:       Bytes
+}
+
+impl SubmissionApprovalRequest {
+    /// Hash of the transaction, what the approvals commit to.
+    pub fn tx_hash(&self) -> B256 {
+        keccak256(&self.raw_tx)
+    }
+}
+
+/// An approver's commitment to the exact bytes of a settlement transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SubmissionApproval {
+    pub block_height: BlockNumber,
+    pub tx_hash:      B256,
+    pub source:       PeerId,
+    /// over [`Self::digest`]
+    pub signature:    Signature
+}
+
+impl SubmissionApproval {
+    pub fn new(block_height: BlockNumber, tx_hash: B256, signer: &AngstromSigner) -> Self {
+        let signature = signer
+            .sign_hash_sync(&Self::digest(block_height, tx_hash))
+            .unwrap();
+
+        Self { block_height, tx_hash, source: signer.id(), signature }
+    }
+
+    pub fn digest(block_height: BlockNumber, tx_hash: B256) -> B256 {
+        keccak256((block_height, tx_hash).abi_encode())
+    }
+
+    /// Whether the approval was signed by its source.
+    pub fn is_valid(&self) -> bool {
+        self.signature
+            .recover_from_prehash(&Self::digest(self.block_height, self.tx_hash))
+            .is_ok_and(|key| AngstromSigner::public_key_to_peer_id(&key) == self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn approvals_commit_to_the_height_and_the_tx() {
+        let signer = AngstromSigner::random();
+        let request =
+            SubmissionApprovalRequest { block_height: 7, raw_tx: Bytes::from([1, 2, 3]) };
+        let approval = SubmissionApproval::new(7, request.tx_hash(), &signer);
+        assert!(approval.is_valid());
+        assert_eq!(approval.source, signer.id());
+
+        let other_tx = SubmissionApproval { tx_hash: keccak256([4]), ..approval.clone() };
+        assert!(!other_tx.is_valid());
+        let other_height = SubmissionApproval { block_height: 8, ..approval };
+        assert!(!other_height.is_valid());
+    }
+}
@@ -0,0 +1,18 @@
+use alloy::primitives::BlockNumber;
+use serde::{Deserialize, Serialize};
+
+/// How a validator did in a block, shared with the other validators so that
+/// one that falls behind is spotted before it misses its slots. Sending it is
+/// optional.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorTelemetry {
+    pub block_height:          BlockNumber,
+    /// time it took to solve the auction, to build a proposal or to verify
+    /// the leader's, in milliseconds. Unset when the round ended before
+    pub solve_time_ms:         Option<u64>,
+    /// orders in the validator's book at the end of the block
+    pub book_size:             u64,
+    /// mean time the orders of the block took to validate, in milliseconds
+    pub validation_latency_ms: Option<u64>
+}
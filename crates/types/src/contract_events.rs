@@ -0,0 +1,167 @@
+//! Typed decoding of the logs emitted by the Angstrom contracts.
+//!
+//! The Angstrom contract itself only emits a single anonymous log per bundle,
+//! committing to the fees it collected. Pool and node configuration is emitted
+//! by the controller. Orders settled in a bundle don't emit logs, they are read
+//! from the bundle in the transaction input instead.
+
+use alloy::{
+    primitives::{Address, BlockNumber, Log, B256},
+    sol_types::SolEvent
+};
+use reth_ethereum_primitives::Receipt;
+use reth_provider::ExecutionOutcome;
+
+use crate::contract_bindings::controller_v_1::ControllerV1::{
+    NodeAdded, NodeRemoved, PoolConfigured, PoolRemoved
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AngstromEvent {
+    /// A bundle was settled. Carries the hash of the fee summary of every
+    /// asset in the bundle.
+    BundleSettled {
+        fee_summary_hash: B256
+    },
+    PoolConfigured(PoolConfigured),
+    PoolRemoved(PoolRemoved),
+    NodeAdded(NodeAdded),
+    NodeRemoved(NodeRemoved)
+}
+
+/// An event along with where it was emitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedEvent {
+    pub block_number: BlockNumber,
+    /// index of the log in its block
+    pub log_index:    usize,
+    pub event:        AngstromEvent
+}
+
+/// Decodes the events of one deployment of the Angstrom and controller
+/// contracts. Logs of any other contract are skipped.
+#[derive(Debug, Clone, Copy)]
+pub struct AngstromEventDecoder {
+    angstrom_address:   Address,
+    controller_address: Address
+}
+
+impl AngstromEventDecoder {
+    pub fn new(angstrom_address: Address, controller_address: Address) -> Self {
+        Self { angstrom_address, controller_address }
+    }
+
+    pub fn decode_log(&self, log: &Log) -> Option<AngstromEvent> {
+        if log.address == self.angstrom_address {
+            return Self::decode_settlement(log)
+        }
+        if log.address != self.controller_address {
+            return None
+        }
+
+        match log.topics().first().copied()? {
+            PoolConfigured::SIGNATURE_HASH => PoolConfigured::decode_log(log, true)
+                .ok()
+                .map(|l| AngstromEvent::PoolConfigured(l.data)),
+            PoolRemoved::SIGNATURE_HASH => PoolRemoved::decode_log(log, true)
+                .ok()
+                .map(|l| AngstromEvent::PoolRemoved(l.data)),
+            NodeAdded::SIGNATURE_HASH => NodeAdded::decode_log(log, true)
+                .ok()
+                .map(|l| AngstromEvent::NodeAdded(l.data)),
+            NodeRemoved::SIGNATURE_HASH => NodeRemoved::decode_log(log, true)
+                .ok()
+                .map(|l| AngstromEvent::NodeRemoved(l.data)),
+            _ => None
+        }
+    }
+
+    /// The settlement log is a `log0` of the fee summary hash. Doesn't check
+    /// that it was emitted by the Angstrom contract.
+    pub fn decode_settlement(log: &Log) -> Option<AngstromEvent> {
+        (log.topics().is_empty() && log.data.data.len() == 32).then(|| {
+            AngstromEvent::BundleSettled { fee_summary_hash: B256::from_slice(&log.data.data) }
+        })
+    }
+
+    /// Decodes the events in a block's logs, along with the index of their
+    /// log.
+    pub fn decode_logs<'a>(
+        &'a self,
+        logs: impl IntoIterator<Item = &'a Log> + 'a
+    ) -> impl Iterator<Item = (usize, AngstromEvent)> + 'a {
+        logs.into_iter()
+            .enumerate()
+            .filter_map(move |(log_index, log)| Some((log_index, self.decode_log(log)?)))
+    }
+
+    /// Decodes the events of the receipts of a single block, in order.
+    pub fn decode_receipts<'a>(
+        &'a self,
+        receipts: impl IntoIterator<Item = &'a Receipt> + 'a
+    ) -> impl Iterator<Item = (usize, AngstromEvent)> + 'a {
+        self.decode_logs(receipts.into_iter().flat_map(|receipt| &receipt.logs))
+    }
+
+    /// Decodes the events of every block in the outcome, in order.
+    pub fn decode_outcome<'a>(
+        &'a self,
+        outcome: &'a ExecutionOutcome
+    ) -> impl Iterator<Item = DecodedEvent> + 'a {
+        outcome.range().flat_map(move |block_number| {
+            outcome
+                .logs(block_number)
+                .into_iter()
+                .flat_map(move |logs| self.decode_logs(logs))
+                .map(move |(log_index, event)| DecodedEvent { block_number, log_index, event })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{aliases::U24, LogData};
+
+    use super::*;
+
+    #[test]
+    fn decodes_only_known_contracts() {
+        let angstrom = Address::repeat_byte(1);
+        let controller = Address::repeat_byte(2);
+        let decoder = AngstromEventDecoder::new(angstrom, controller);
+
+        let node_added = NodeAdded { node: Address::repeat_byte(3) };
+        let pool_configured = PoolConfigured {
+            asset0:      Address::repeat_byte(4),
+            asset1:      Address::repeat_byte(5),
+            tickSpacing: 60,
+            bundleFee:   U24::from(100),
+            unlockedFee: U24::from(200)
+        };
+        let fee_summary_hash = B256::repeat_byte(6);
+        let settled = LogData::new_unchecked(vec![], fee_summary_hash.to_vec().into());
+
+        let receipt = Receipt {
+            logs: vec![
+                Log { address: controller, data: node_added.encode_log_data() },
+                // same event from another contract
+                Log { address: Address::repeat_byte(7), data: node_added.encode_log_data() },
+                Log { address: controller, data: pool_configured.encode_log_data() },
+                Log { address: angstrom, data: settled },
+            ],
+            ..Default::default()
+        };
+
+        let events = decoder
+            .decode_receipts([&Receipt::default(), &receipt])
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            vec![
+                (0, AngstromEvent::NodeAdded(node_added)),
+                (2, AngstromEvent::PoolConfigured(pool_configured)),
+                (3, AngstromEvent::BundleSettled { fee_summary_hash }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use alloy::primitives::{Address, U256};
+use tracing::warn;
+
+/// A user's funds in a token at the start of the block.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UserBalance {
+    pub balance:  U256,
+    /// what the user approved angstrom to pull
+    pub approval: U256,
+    /// the balance the user holds in angstrom
+    pub angstrom: U256
+}
+
+/// Start of block funds keyed by user and token.
+pub type UserBalances = HashMap<(Address, Address), UserBalance>;
+
+/// The funds a user order moves when it is settled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementLeg {
+    pub user:         Address,
+    pub recipient:    Address,
+    pub use_internal: bool,
+    pub asset_in:     Address,
+    pub asset_out:    Address,
+    pub quantity_in:  U256,
+    pub quantity_out: U256
+}
+
+/// Tracks the funds of a bundle's users while its user orders settle one after
+/// the other. The contract pays an order out before it pulls the input of the
+/// next one, so a user's order can be funded by what an earlier order of the
+/// same bundle paid them.
+///
+/// Funds of users without a known start balance aren't tracked, their orders
+/// are taken to be funded as they were when validated.
+#[derive(Debug, Default)]
+pub struct BalanceDeltas {
+    balances: UserBalances
+}
+
+impl BalanceDeltas {
+    pub fn new(start: UserBalances) -> Self {
+        Self { balances: start }
+    }
+
+    pub fn can_settle(&self, leg: &SettlementLeg) -> bool {
+        let Some(funds) = self.balances.get(&(leg.user, leg.asset_in)) else { return true };
+
+        if leg.use_internal {
+            funds.angstrom >= leg.quantity_in
+        } else {
+            funds.balance >= leg.quantity_in && funds.approval >= leg.quantity_in
+        }
+    }
+
+    pub fn settle(&mut self, leg: &SettlementLeg) {
+        if let Some(funds) = self.balances.get_mut(&(leg.user, leg.asset_in)) {
+            if leg.use_internal {
+                funds.angstrom = funds.angstrom.saturating_sub(leg.quantity_in);
+            } else {
+                funds.balance = funds.balance.saturating_sub(leg.quantity_in);
+                funds.approval = funds.approval.saturating_sub(leg.quantity_in);
+            }
+        }
+
+        if let Some(funds) = self.balances.get_mut(&(leg.recipient, leg.asset_out)) {
+            if leg.use_internal {
+                funds.angstrom = funds.angstrom.saturating_add(leg.quantity_out);
+            } else {
+                funds.balance = funds.balance.saturating_add(leg.quantity_out);
+            }
+        }
+    }
+
+    /// The order to settle `legs` in, as indices into it. Greedily takes the
+    /// first leg that is funded at that point, so the given order is kept
+    /// wherever no user runs short. Legs that are never funded come last, in
+    /// the given order.
+    pub fn settlement_order(mut self, legs: &[SettlementLeg]) -> Vec<usize> {
+        let mut pending = (0..legs.len()).collect::<Vec<_>>();
+        let mut order = Vec::with_capacity(legs.len());
+
+        while let Some(pos) = pending.iter().position(|i| self.can_settle(&legs[*i])) {
+            let i = pending.remove(pos);
+            self.settle(&legs[i]);
+            order.push(i);
+        }
+
+        if !pending.is_empty() {
+            warn!(
+                unfunded = pending.len(),
+                "bundle has user orders that can't be funded in any order"
+            );
+        }
+        order.extend(pending);
+
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::address;
+
+    use super::*;
+
+    const USER: Address = address!("1111111111111111111111111111111111111111");
+    const T0: Address = address!("2222222222222222222222222222222222222222");
+    const T1: Address = address!("3333333333333333333333333333333333333333");
+
+    fn leg(
+        asset_in: Address,
+        asset_out: Address,
+        quantity_in: u64,
+        quantity_out: u64
+    ) -> SettlementLeg {
+        SettlementLeg {
+            user: USER,
+            recipient: USER,
+            use_internal: false,
+            asset_in,
+            asset_out,
+            quantity_in: U256::from(quantity_in),
+            quantity_out: U256::from(quantity_out)
+        }
+    }
+
+    fn funds(balance: u64) -> UserBalance {
+        UserBalance { balance: U256::from(balance), approval: U256::MAX, angstrom: U256::ZERO }
+    }
+
+    #[test]
+    fn proceeds_fund_later_orders() {
+        // selling t1 needs the t1 bought by the second order
+        let legs = vec![leg(T1, T0, 150, 100), leg(T0, T1, 100, 200)];
+        let start = UserBalances::from([((USER, T0), funds(100)), ((USER, T1), funds(0))]);
+
+        assert_eq!(BalanceDeltas::new(start).settlement_order(&legs), vec![1, 0]);
+    }
+
+    #[test]
+    fn order_is_kept_when_funded() {
+        let legs = vec![leg(T1, T0, 150, 100), leg(T0, T1, 100, 200)];
+        let start = UserBalances::from([((USER, T0), funds(100)), ((USER, T1), funds(150))]);
+        assert_eq!(BalanceDeltas::new(start).settlement_order(&legs), vec![0, 1]);
+
+        // unknown users are taken to be funded
+        assert_eq!(BalanceDeltas::default().settlement_order(&legs), vec![0, 1]);
+    }
+
+    #[test]
+    fn unfunded_orders_come_last() {
+        let legs = vec![leg(T1, T0, 500, 100), leg(T0, T1, 100, 200)];
+        let start = UserBalances::from([((USER, T0), funds(100)), ((USER, T1), funds(0))]);
+
+        assert_eq!(BalanceDeltas::new(start).settlement_order(&legs), vec![1, 0]);
+    }
+
+    #[test]
+    fn proceeds_to_other_recipients_dont_count() {
+        let mut buy = leg(T0, T1, 100, 200);
+        buy.recipient = T0;
+        let sell = leg(T1, T0, 150, 100);
+        let start = UserBalances::from([((USER, T0), funds(100)), ((USER, T1), funds(0))]);
+
+        let mut deltas = BalanceDeltas::new(start);
+        assert!(deltas.can_settle(&buy));
+        deltas.settle(&buy);
+        assert!(!deltas.can_settle(&sell));
+    }
+}
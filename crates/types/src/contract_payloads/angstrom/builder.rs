@@ -0,0 +1,231 @@
+use std::{collections::HashSet, marker::PhantomData};
+
+use alloy::primitives::Address;
+
+use super::{AngstromBundle, TopOfBlockOrder, UserOrder};
+use crate::contract_payloads::{rewards::PoolUpdate, Asset, Pair};
+
+/// The sections of a bundle, in the order [`BundleBuilder`] takes them.
+pub mod stage {
+    pub struct Assets;
+    pub struct Pairs;
+    pub struct PoolUpdates;
+    pub struct TopOfBlockOrders;
+    pub struct UserOrders;
+    pub struct Complete;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BundleBuildError {
+    #[error("asset {0} is registered twice")]
+    DuplicateAsset(Address),
+    #[error("pair {pair} refers to asset {asset}, only {assets} assets are registered")]
+    UnknownAsset { pair: usize, asset: u16, assets: usize },
+    #[error("pair {0} trades an asset against itself")]
+    SameAsset(usize),
+    #[error("{section} {index} refers to pair {pair}, only {pairs} pairs are registered")]
+    UnknownPair { section: &'static str, index: usize, pair: u16, pairs: usize }
+}
+
+/// Builds an [`AngstromBundle`] one section at a time, in the order the
+/// sections refer to each other: assets, the pairs of those assets, then the
+/// pool updates, top of block orders and user orders of those pairs. Taking
+/// the sections in any other order doesn't compile, and a section referring to
+/// an asset or a pair that isn't registered fails when it is added rather than
+/// when the bundle is simulated.
+pub struct BundleBuilder<Stage> {
+    bundle: AngstromBundle,
+    _stage: PhantomData<Stage>
+}
+
+impl AngstromBundle {
+    pub fn builder() -> BundleBuilder<stage::Assets> {
+        BundleBuilder {
+            bundle: Self::new(vec![], vec![], vec![], vec![], vec![]),
+            _stage: PhantomData
+        }
+    }
+}
+
+impl<Stage> BundleBuilder<Stage> {
+    fn advance<Next>(self) -> BundleBuilder<Next> {
+        BundleBuilder { bundle: self.bundle, _stage: PhantomData }
+    }
+
+    fn check_pairs(
+        &self,
+        section: &'static str,
+        pairs: impl Iterator<Item = u16>
+    ) -> Result<(), BundleBuildError> {
+        let registered = self.bundle.pairs.len();
+        pairs.enumerate().try_for_each(|(index, pair)| {
+            if pair as usize >= registered {
+                return Err(BundleBuildError::UnknownPair {
+                    section,
+                    index,
+                    pair,
+                    pairs: registered
+                })
+            }
+            Ok(())
+        })
+    }
+}
+
+impl BundleBuilder<stage::Assets> {
+    pub fn assets(
+        mut self,
+        assets: Vec<Asset>
+    ) -> Result<BundleBuilder<stage::Pairs>, BundleBuildError> {
+        let mut seen = HashSet::new();
+        if let Some(asset) = assets.iter().find(|asset| !seen.insert(asset.addr)) {
+            return Err(BundleBuildError::DuplicateAsset(asset.addr))
+        }
+
+        self.bundle.assets = assets;
+        Ok(self.advance())
+    }
+}
+
+impl BundleBuilder<stage::Pairs> {
+    pub fn pairs(
+        mut self,
+        pairs: Vec<Pair>
+    ) -> Result<BundleBuilder<stage::PoolUpdates>, BundleBuildError> {
+        let assets = self.bundle.assets.len();
+        for (index, pair) in pairs.iter().enumerate() {
+            if let Some(asset) = [pair.index0, pair.index1]
+                .into_iter()
+                .find(|asset| *asset as usize >= assets)
+            {
+                return Err(BundleBuildError::UnknownAsset { pair: index, asset, assets })
+            }
+            if pair.index0 == pair.index1 {
+                return Err(BundleBuildError::SameAsset(index))
+            }
+        }
+
+        self.bundle.pairs = pairs;
+        Ok(self.advance())
+    }
+}
+
+impl BundleBuilder<stage::PoolUpdates> {
+    pub fn pool_updates(
+        mut self,
+        pool_updates: Vec<PoolUpdate>
+    ) -> Result<BundleBuilder<stage::TopOfBlockOrders>, BundleBuildError> {
+        self.check_pairs("pool update", pool_updates.iter().map(|update| update.pair_index))?;
+
+        self.bundle.pool_updates = pool_updates;
+        Ok(self.advance())
+    }
+}
+
+impl BundleBuilder<stage::TopOfBlockOrders> {
+    pub fn top_of_block_orders(
+        mut self,
+        orders: Vec<TopOfBlockOrder>
+    ) -> Result<BundleBuilder<stage::UserOrders>, BundleBuildError> {
+        self.check_pairs("top of block order", orders.iter().map(|order| order.pairs_index))?;
+
+        self.bundle.top_of_block_orders = orders;
+        Ok(self.advance())
+    }
+}
+
+impl BundleBuilder<stage::UserOrders> {
+    pub fn user_orders(
+        mut self,
+        orders: Vec<UserOrder>
+    ) -> Result<BundleBuilder<stage::Complete>, BundleBuildError> {
+        self.check_pairs("user order", orders.iter().map(|order| order.pair_index))?;
+
+        self.bundle.user_orders = orders;
+        Ok(self.advance())
+    }
+}
+
+impl BundleBuilder<stage::Complete> {
+    pub fn build(self) -> AngstromBundle {
+        self.bundle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_payloads::rewards::RewardsUpdate;
+
+    fn asset(byte: u8) -> Asset {
+        Asset { addr: Address::repeat_byte(byte), ..Default::default() }
+    }
+
+    fn pair(index0: u16, index1: u16) -> Pair {
+        Pair { index0, index1, ..Default::default() }
+    }
+
+    #[test]
+    fn builds_the_sections_in_order() {
+        let bundle = AngstromBundle::builder()
+            .assets(vec![asset(1), asset(2)])
+            .unwrap()
+            .pairs(vec![pair(0, 1)])
+            .unwrap()
+            .pool_updates(vec![PoolUpdate {
+                zero_for_one:     false,
+                pair_index:       0,
+                swap_in_quantity: 0,
+                rewards_update:   RewardsUpdate::CurrentOnly { amount: 0 }
+            }])
+            .unwrap()
+            .top_of_block_orders(vec![TopOfBlockOrder::default()])
+            .unwrap()
+            .user_orders(vec![])
+            .unwrap()
+            .build();
+
+        assert_eq!(bundle.assets.len(), 2);
+        assert_eq!(bundle.pairs.len(), 1);
+        assert_eq!(bundle.pool_updates.len(), 1);
+        assert_eq!(bundle.top_of_block_orders.len(), 1);
+    }
+
+    #[test]
+    fn sections_only_refer_to_what_is_registered() {
+        assert_eq!(
+            AngstromBundle::builder()
+                .assets(vec![asset(1), asset(1)])
+                .err(),
+            Some(BundleBuildError::DuplicateAsset(Address::repeat_byte(1)))
+        );
+
+        let pairs = || {
+            AngstromBundle::builder()
+                .assets(vec![asset(1), asset(2)])
+                .unwrap()
+        };
+        assert_eq!(
+            pairs().pairs(vec![pair(0, 1), pair(1, 2)]).err(),
+            Some(BundleBuildError::UnknownAsset { pair: 1, asset: 2, assets: 2 })
+        );
+        assert_eq!(pairs().pairs(vec![pair(1, 1)]).err(), Some(BundleBuildError::SameAsset(0)));
+
+        let tob = TopOfBlockOrder { pairs_index: 1, ..Default::default() };
+        assert_eq!(
+            pairs()
+                .pairs(vec![pair(0, 1)])
+                .unwrap()
+                .pool_updates(vec![])
+                .unwrap()
+                .top_of_block_orders(vec![tob])
+                .err(),
+            Some(BundleBuildError::UnknownPair {
+                section: "top of block order",
+                index:   0,
+                pair:    1,
+                pairs:   1
+            })
+        );
+    }
+}
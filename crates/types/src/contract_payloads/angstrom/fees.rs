@@ -0,0 +1,151 @@
+use std::collections::{BTreeMap, HashMap};
+
+use alloy::primitives::{Address, U256};
+use serde::{Deserialize, Serialize};
+
+use super::{AngstromBundle, UniswapAngstromRegistry};
+use crate::{contract_payloads::rewards::SurplusDistribution, matching::Ray, primitive::PoolId};
+
+/// Denominator of the pool fees in the config store
+pub const FEE_DENOMINATOR_E6: u32 = 1_000_000;
+
+/// The fees a bundle charges the user orders it settles.
+///
+/// Each pool has a protocol fee, in millionths, by which the contract scales
+/// down the price its user orders get. What that keeps from the orders is
+/// saved for the protocol. On top of that, the orders are charged the shares of
+/// their surplus the [`SurplusDistribution`] gives to the LPs and the protocol.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BundleFees {
+    pool_fees_e6: HashMap<PoolId, u32>,
+    surplus:      SurplusDistribution
+}
+
+impl BundleFees {
+    pub fn new(surplus: SurplusDistribution) -> Self {
+        Self { surplus, ..Default::default() }
+    }
+
+    /// The fees of `pools`, as set in the pool config store.
+    pub fn from_registry<'a>(
+        registry: &UniswapAngstromRegistry,
+        pools: impl IntoIterator<Item = &'a PoolId>,
+        surplus: SurplusDistribution
+    ) -> Self {
+        let pool_fees_e6 = pools
+            .into_iter()
+            .filter_map(|pool| Some((*pool, registry.get_ang_entry(pool)?.fee_in_e6)))
+            .collect();
+
+        Self { pool_fees_e6, surplus }
+    }
+
+    pub fn with_pool_fee(mut self, pool: PoolId, fee_e6: u32) -> Self {
+        self.pool_fees_e6.insert(pool, fee_e6);
+        self
+    }
+
+    pub fn surplus(&self) -> SurplusDistribution {
+        self.surplus
+    }
+
+    /// The protocol fee of `pool`, in millionths. Pools that aren't in the
+    /// config store pay none.
+    pub fn pool_fee_e6(&self, pool: &PoolId) -> u32 {
+        self.pool_fees_e6
+            .get(pool)
+            .copied()
+            .unwrap_or_default()
+            .min(FEE_DENOMINATOR_E6)
+    }
+
+    /// The price, out per in, that user orders of `pool` swapping at
+    /// `price_1over0` get once its fee is taken, computed like
+    /// `Pair.getSwapInfo`.
+    pub fn price_out_vs_in(&self, pool: &PoolId, price_1over0: Ray, zero_for_one: bool) -> Ray {
+        let price = if zero_for_one {
+            *price_1over0
+        } else {
+            // the contract inverts without any rounding mode, which floors
+            *price_1over0.inv_ray()
+        };
+        let one_minus_fee = U256::from(FEE_DENOMINATOR_E6 - self.pool_fee_e6(pool));
+        Ray::from(price * one_minus_fee / U256::from(FEE_DENOMINATOR_E6))
+    }
+}
+
+/// What bundles saved for the protocol, by asset, which are the protocol fees
+/// of their pools and the protocol's share of the surplus of their orders.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeLedger {
+    /// bundles recorded in the ledger
+    pub bundles: u64,
+    pub fees:    BTreeMap<Address, u128>
+}
+
+impl FeeLedger {
+    /// The fees saved by `bundle`.
+    pub fn of_bundle(bundle: &AngstromBundle) -> Self {
+        let mut ledger = Self::default();
+        ledger.record(bundle);
+        ledger
+    }
+
+    pub fn record(&mut self, bundle: &AngstromBundle) {
+        self.bundles += 1;
+        for asset in bundle.assets.iter().filter(|asset| asset.save != 0) {
+            *self.fees.entry(asset.addr).or_default() += asset.save;
+        }
+    }
+
+    pub fn merge(&mut self, other: &Self) {
+        self.bundles += other.bundles;
+        for (asset, fee) in &other.fees {
+            *self.fees.entry(*asset).or_default() += fee;
+        }
+    }
+
+    pub fn fee(&self, asset: &Address) -> u128 {
+        self.fees.get(asset).copied().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_payloads::Asset;
+
+    #[test]
+    fn pool_fee_scales_down_the_price() {
+        let pool = PoolId::with_last_byte(1);
+        let fees = BundleFees::default().with_pool_fee(pool, 3_000);
+        let two = Ray::from(U256::from(2) * U256::from(10).pow(U256::from(27)));
+
+        let price = fees.price_out_vs_in(&pool, two, true);
+        assert_eq!(price.quantity(1_000_000, false), 1_994_000);
+        let price = fees.price_out_vs_in(&pool, two, false);
+        assert_eq!(price.quantity(1_000_000, false), 498_500);
+        // pools that aren't in the config store pay none
+        let price = fees.price_out_vs_in(&PoolId::ZERO, two, true);
+        assert_eq!(price.quantity(1_000_000, false), 2_000_000);
+    }
+
+    #[test]
+    fn ledger_accumulates_saved_assets() {
+        let asset = |last_byte, save| Asset {
+            addr: Address::with_last_byte(last_byte),
+            save,
+            ..Default::default()
+        };
+        let bundle =
+            AngstromBundle::new(vec![asset(1, 10), asset(2, 0)], vec![], vec![], vec![], vec![]);
+
+        let mut ledger = FeeLedger::of_bundle(&bundle);
+        ledger.merge(&FeeLedger::of_bundle(&bundle));
+
+        assert_eq!(ledger.bundles, 2);
+        assert_eq!(ledger.fee(&Address::with_last_byte(1)), 20);
+        assert!(!ledger.fees.contains_key(&Address::with_last_byte(2)));
+    }
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use super::{AngstromBundle, OrderQuantities};
+
+/// In which order the user orders of a bundle settle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UserOrderLayout {
+    /// as the bundle was built, which settles orders funded by the proceeds of
+    /// other orders after those
+    AsBuilt,
+    /// grouped by pair, so the contract loads every pair once
+    ByPair,
+    /// exact orders first and partial fills after them
+    PartialFillsLast
+}
+
+/// A way of laying out the same settlement in a bundle. Every layout fills the
+/// same orders for the same amounts, only the encoding and the order of
+/// execution differ, which changes the gas the bundle costs. The assets and
+/// pairs always stay in the order the contract requires, ascending.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleLayout {
+    pub user_orders: UserOrderLayout
+}
+
+impl BundleLayout {
+    /// The layout of a bundle as it is built.
+    pub const AS_BUILT: Self = Self { user_orders: UserOrderLayout::AsBuilt };
+
+    /// Every layout, starting with [`Self::AS_BUILT`].
+    pub fn all() -> Vec<Self> {
+        [UserOrderLayout::AsBuilt, UserOrderLayout::ByPair, UserOrderLayout::PartialFillsLast]
+            .into_iter()
+            .map(|user_orders| Self { user_orders })
+            .collect()
+    }
+}
+
+impl AngstromBundle {
+    /// The bundle laid out with `layout`. Layouts that don't apply leave the
+    /// bundle as it is, none of them check whether the contract accepts the
+    /// result, which only a simulation can tell.
+    pub fn with_layout(&self, layout: BundleLayout) -> Self {
+        let mut bundle = self.clone();
+
+        match layout.user_orders {
+            UserOrderLayout::AsBuilt => {}
+            UserOrderLayout::ByPair => bundle.user_orders.sort_by_key(|order| order.pair_index),
+            UserOrderLayout::PartialFillsLast => bundle.user_orders.sort_by_key(|order| {
+                matches!(order.order_quantities, OrderQuantities::Partial { .. })
+            })
+        }
+
+        bundle
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeSet, HashMap, HashSet},
     hash::Hash,
     ops::Deref,
     sync::Arc
@@ -20,16 +20,16 @@ use tracing::{debug, trace, warn};
 
 use super::{
     asset::builder::{AssetBuilder, AssetBuilderStage},
-    rewards::PoolUpdate,
+    rewards::{PoolRewards, PoolUpdate, SurplusCharge},
     tob::ToBOutcome,
     Asset, Pair, CONFIG_STORE_SLOT, POOL_CONFIG_STORE_ENTRY_SIZE
 };
 use crate::{
     consensus::{PreProposal, Proposal},
     contract_bindings::angstrom::Angstrom::PoolKey,
-    matching::{uniswap::PoolSnapshot, Ray},
+    matching::{surplus::OrderSurplus, uniswap::PoolSnapshot, Ray},
     orders::{OrderFillState, OrderOutcome, PoolSolution},
-    primitive::{PoolId, UniswapPoolRegistry},
+    primitive::{PoolId, TokenPair, UniswapPoolRegistry},
     sol_bindings::{
         grouped_orders::{GroupedVanillaOrder, OrderWithStorageData},
         rpc_orders::TopOfBlockOrder as RpcTopOfBlockOrder,
@@ -38,12 +38,24 @@ use crate::{
     testnet::TestnetStateOverrides
 };
 
+mod balances;
+mod builder;
+mod fees;
+mod layout;
 mod order;
 mod tob;
+mod trace;
+mod version;
+pub use balances::*;
+pub use builder::*;
+pub use fees::*;
+pub use layout::*;
 pub use order::{OrderQuantities, StandingValidation, UserOrder};
 pub use tob::*;
+pub use trace::*;
+pub use version::*;
 
-#[derive(Debug, PadeEncode, PadeDecode)]
+#[derive(Debug, Clone, PadeEncode, PadeDecode)]
 pub struct AngstromBundle {
     pub assets:              Vec<Asset>,
     pub pairs:               Vec<Pair>,
@@ -174,16 +186,7 @@ impl AngstromBundle {
 
         // Get the information for the pool or skip this solution if we can't find a
         // pool for it
-        let (t0, t1) = {
-            let token_in = user_order.token_in();
-            let token_out = user_order.token_out();
-
-            if token_in < token_out {
-                (token_in, token_out)
-            } else {
-                (token_out, token_in)
-            }
-        };
+        let (t0, t1) = TokenPair::new(user_order.token_in(), user_order.token_out()).tokens();
         // Make sure the involved assets are in our assets array and we have the
         // appropriate asset index for them
         let t0_idx = asset_builder.add_or_get_asset(t0) as u16;
@@ -215,13 +218,13 @@ impl AngstromBundle {
         // Get our list of user orders, if we have any
         top_of_block_orders.push(TopOfBlockOrder::of_max_gas(user_order, 0));
 
-        Ok(Self::new(
-            asset_builder.get_asset_array(),
-            pairs,
-            pool_updates,
-            top_of_block_orders,
-            user_orders
-        ))
+        Ok(Self::builder()
+            .assets(asset_builder.get_asset_array())?
+            .pairs(pairs)?
+            .pool_updates(pool_updates)?
+            .top_of_block_orders(top_of_block_orders)?
+            .user_orders(user_orders)?
+            .build())
     }
 
     pub fn build_dummy_for_user_gas(
@@ -240,15 +243,7 @@ impl AngstromBundle {
         {
             // Get the information for the pool or skip this solution if we can't find a
             // pool for it
-            let (t0, t1) = {
-                let token_in = user_order.token_in();
-                let token_out = user_order.token_out();
-                if token_in < token_out {
-                    (token_in, token_out)
-                } else {
-                    (token_out, token_in)
-                }
-            };
+            let (t0, t1) = TokenPair::new(user_order.token_in(), user_order.token_out()).tokens();
             // Make sure the involved assets are in our assets array and we have the
             // appropriate asset index for them
             let t0_idx = asset_builder.add_or_get_asset(t0) as u16;
@@ -279,13 +274,13 @@ impl AngstromBundle {
             ));
         }
 
-        Ok(Self::new(
-            asset_builder.get_asset_array(),
-            pairs,
-            pool_updates,
-            top_of_block_orders,
-            user_orders
-        ))
+        Ok(Self::builder()
+            .assets(asset_builder.get_asset_array())?
+            .pairs(pairs)?
+            .pool_updates(pool_updates)?
+            .top_of_block_orders(top_of_block_orders)?
+            .user_orders(user_orders)?
+            .build())
     }
 
     // builds a bundle where orders are set to max allocated gas to ensure a fully
@@ -327,7 +322,7 @@ impl AngstromBundle {
         // right order
 
         // Walk through our solutions to add them to the structure
-        for solution in solutions.iter() {
+        for solution in Self::canonical_solutions(&solutions, pools, &mut asset_builder) {
             println!("Processing solution");
             // Get the information for the pool or skip this solution if we can't find a
             // pool for it
@@ -356,13 +351,13 @@ impl AngstromBundle {
                 None
             )?;
         }
-        Ok(Self::new(
-            asset_builder.get_asset_array(),
-            pairs,
-            pool_updates,
-            top_of_block_orders,
-            user_orders
-        ))
+        Ok(Self::builder()
+            .assets(asset_builder.get_asset_array())?
+            .pairs(pairs)?
+            .pool_updates(pool_updates)?
+            .top_of_block_orders(top_of_block_orders)?
+            .user_orders(user_orders)?
+            .build())
     }
 
     fn fetch_total_orders_and_gas_delegated_to_orders(
@@ -376,21 +371,15 @@ impl AngstromBundle {
             .iter()
             .map(|s| (s, orders_by_pool.get(&s.id).cloned()))
             .filter_map(|(solution, order_list)| {
-                let mut order_list = order_list?.into_iter().collect::<Vec<_>>();
-                // Sort the user order list so we can properly associate it with our
-                // OrderOutcomes.  First bids by price then asks by price.
-                order_list.sort_by(|a, b| match (a.is_bid, b.is_bid) {
-                    (true, true) => b.priority_data.cmp(&a.priority_data),
-                    (false, false) => a.priority_data.cmp(&b.priority_data),
-                    (..) => b.is_bid.cmp(&a.is_bid)
-                });
+                let order_list = order_list?;
+                let orders_by_hash = Self::orders_by_hash(order_list.iter());
                 let mut cnt = 0;
                 let mut total_gas = 0;
-                for (_, order) in solution
+                for order in solution
                     .limit
                     .iter()
-                    .zip(order_list.iter())
-                    .filter(|(outcome, _)| outcome.is_filled())
+                    .filter(|outcome| outcome.is_filled())
+                    .filter_map(|outcome| orders_by_hash.get(&outcome.id.hash))
                 {
                     cnt += 1;
                     total_gas += order.priority_data.gas_units;
@@ -410,6 +399,42 @@ impl AngstromBundle {
             })
     }
 
+    /// The solutions in the order of their pairs, with the assets of their
+    /// pools added to `asset_builder` in address order up front. The pair
+    /// indices then match the sorted asset array and strictly increase, as the
+    /// contract requires, and the bundle doesn't depend on the order the
+    /// solutions were found in.
+    fn canonical_solutions<'a>(
+        solutions: &'a [PoolSolution],
+        pools: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>,
+        asset_builder: &mut AssetBuilder
+    ) -> Vec<&'a PoolSolution> {
+        let assets = solutions
+            .iter()
+            .filter_map(|solution| pools.get(&solution.id))
+            .flat_map(|(t0, t1, ..)| [*t0, *t1])
+            .collect::<BTreeSet<_>>();
+        for asset in assets {
+            asset_builder.add_or_get_asset(asset);
+        }
+
+        let mut solutions = solutions.iter().collect::<Vec<_>>();
+        solutions.sort_by_cached_key(|solution| {
+            let pair = pools.get(&solution.id).map(|(t0, t1, ..)| {
+                (asset_builder.add_or_get_asset(*t0), asset_builder.add_or_get_asset(*t1))
+            });
+            (pair, solution.id)
+        });
+
+        solutions
+    }
+
+    fn orders_by_hash<'a>(
+        orders: impl Iterator<Item = &'a OrderWithStorageData<GroupedVanillaOrder>>
+    ) -> HashMap<B256, &'a OrderWithStorageData<GroupedVanillaOrder>> {
+        orders.map(|order| (order.order_id.hash, order)).collect()
+    }
+
     pub fn process_solution(
         pairs: &mut Vec<Pair>,
         asset_builder: &mut AssetBuilder,
@@ -426,6 +451,47 @@ impl AngstromBundle {
         t1: Address,
         store_index: u16,
         shared_gas: Option<U256>
+    ) -> eyre::Result<()> {
+        Self::process_solution_with_legs(
+            pairs,
+            asset_builder,
+            user_orders,
+            None,
+            orders_by_pool,
+            top_of_block_orders,
+            pool_updates,
+            solution,
+            snapshot,
+            t0,
+            t1,
+            store_index,
+            shared_gas,
+            &BundleFees::default()
+        )
+    }
+
+    /// [`Self::process_solution`] that also records the funds each user order
+    /// moves into `legs`, in the same order as `user_orders`, and charges the
+    /// orders `fees`.
+    #[allow(clippy::too_many_arguments)]
+    fn process_solution_with_legs(
+        pairs: &mut Vec<Pair>,
+        asset_builder: &mut AssetBuilder,
+        user_orders: &mut Vec<UserOrder>,
+        mut legs: Option<&mut Vec<SettlementLeg>>,
+        orders_by_pool: &HashMap<
+            FixedBytes<32>,
+            HashSet<OrderWithStorageData<GroupedVanillaOrder>>
+        >,
+        top_of_block_orders: &mut Vec<TopOfBlockOrder>,
+        pool_updates: &mut Vec<PoolUpdate>,
+        solution: &PoolSolution,
+        snapshot: &PoolSnapshot,
+        t0: Address,
+        t1: Address,
+        store_index: u16,
+        shared_gas: Option<U256>,
+        fees: &BundleFees
     ) -> eyre::Result<()> {
         // Dump the solution
         let json = serde_json::to_string(&(
@@ -518,16 +584,6 @@ impl AngstromBundle {
             quantity_in,
             quantity_out
         );
-        // Account for our reward
-        asset_builder.allocate(AssetBuilderStage::Reward, t0, tob_outcome.total_reward.to());
-        let rewards_update = tob_outcome.to_rewards_update();
-        // Push the pool update
-        pool_updates.push(PoolUpdate {
-            zero_for_one,
-            pair_index: pair_idx as u16,
-            swap_in_quantity: quantity_in,
-            rewards_update
-        });
 
         // Add the ToB order to our tob order list - This is currently converting
         // between two ToB order formats
@@ -550,30 +606,29 @@ impl AngstromBundle {
             top_of_block_orders.push(contract_tob);
         }
 
-        // Get our list of user orders, if we have any
-        let mut order_list: Vec<&OrderWithStorageData<GroupedVanillaOrder>> = orders_by_pool
+        // Get our user orders, if we have any, so we can associate them with our
+        // OrderOutcomes. The outcomes follow the order of the book, which depends on
+        // the sort strategy, so they are matched up by hash and added in hash order
+        let orders_by_hash = orders_by_pool
             .get(&solution.id)
-            .map(|order_set| order_set.iter().collect())
+            .map(|order_set| Self::orders_by_hash(order_set.iter()))
             .unwrap_or_default();
-        // Sort the user order list so we can properly associate it with our
-        // OrderOutcomes.  First bids by price then asks by price.
-        order_list.sort_by(|a, b| match (a.is_bid, b.is_bid) {
-            (true, true) => a.priority_data.cmp(&b.priority_data),
-            (false, false) => a.priority_data.cmp(&b.priority_data),
-            (..) => b.is_bid.cmp(&a.is_bid)
-        });
         // Loop through our filled user orders, do accounting, and add them to our user
         // order list
         let ray_ucp = Ray::from(ucp);
-        for (outcome, order) in solution
+        let mut surplus_charged = SurplusCharge::default();
+        let mut filled = solution
             .limit
             .iter()
-            .zip(order_list.iter())
-            .filter(|(outcome, _)| outcome.is_filled())
-        {
+            .filter(|outcome| outcome.is_filled())
+            .collect::<Vec<_>>();
+        filled.sort_by_key(|outcome| outcome.id.hash);
+        for outcome in filled {
+            let order = orders_by_hash.get(&outcome.id.hash).ok_or_else(|| {
+                eyre::eyre!("outcome for order {:?} that isn't in the book", outcome.id.hash)
+            })?;
             // Calculate our final amounts based on whether the order is in T0 or T1 context
             let inverse_order = order.is_bid() == order.exact_in();
-            assert_eq!(outcome.id.hash, order.order_id.hash, "Order and outcome mismatched");
             let (t0_moving, t1_moving) = if inverse_order {
                 let t1_moving = outcome.fill_amount(order.max_q());
                 let t0_moving = ray_ucp.inverse_quantity(t1_moving, !order.is_bid());
@@ -584,12 +639,50 @@ impl AngstromBundle {
                 (t0_moving, t1_moving)
             };
 
+            let mut user_order = if let Some(g) = shared_gas {
+                UserOrder::from_internal_order(order, outcome, g, pair_idx as u16)?
+            } else {
+                UserOrder::from_internal_order_max_gas(order, outcome, pair_idx as u16)
+            };
+            // the contract reverts the whole bundle on a dust fill
+            if !user_order.order_quantities.is_settleable() {
+                eyre::bail!("order {:?} is filled below its minimum", outcome.id.hash)
+            }
+            // charged on top of the order's gas, in as far as its fee allows
+            let surplus = OrderSurplus::of(order, outcome, ray_ucp);
+            let charge = fees.surplus().charge(
+                surplus.t0,
+                user_order
+                    .max_extra_fee_asset0
+                    .saturating_sub(user_order.extra_fee_asset0)
+            );
+            user_order.extra_fee_asset0 += charge.total();
+            surplus_charged += charge;
+
             let (quantity_in, quantity_out) =
                 if order.is_bid { (t1_moving, t0_moving) } else { (t0_moving, t1_moving) };
+            let (asset_in, asset_out) = if order.is_bid { (t1, t0) } else { (t0, t1) };
+            // the contract settles the order at the pair price less the pool's protocol
+            // fee, what that keeps from the order is saved for the protocol
+            let price = fees.price_out_vs_in(&solution.id, ray_ucp, !order.is_bid);
+            let (quantity_in, quantity_out, (fee_asset, protocol_fee)) = if order.exact_in() {
+                let net_out = U256::from(price.quantity(quantity_in.saturating_to(), false));
+                (quantity_in, net_out, (asset_out, quantity_out.saturating_sub(net_out)))
+            } else {
+                let net_in = U256::from(price.inverse_quantity(quantity_out.saturating_to(), true));
+                (net_in, quantity_out, (asset_in, net_in.saturating_sub(quantity_in)))
+            };
+            // the surplus charge is paid in T0, out of what a bid gets or on top of
+            // what an ask pays
+            let charged = U256::from(charge.total());
+            let (quantity_in, quantity_out) = if order.is_bid {
+                (quantity_in, quantity_out.saturating_sub(charged))
+            } else {
+                (quantity_in + charged, quantity_out)
+            };
 
             trace!(quantity_in = ?quantity_in, quantity_out = ?quantity_out, is_bid = order.is_bid, exact_in = order.exact_in(), "Processing user order");
             // Account for our user order
-            let (asset_in, asset_out) = if order.is_bid { (t1, t0) } else { (t0, t1) };
             asset_builder.external_swap(
                 AssetBuilderStage::UserOrder,
                 asset_in,
@@ -597,26 +690,50 @@ impl AngstromBundle {
                 quantity_in.to(),
                 quantity_out.to()
             );
-            let user_order = if let Some(g) = shared_gas {
-                UserOrder::from_internal_order(order, outcome, g, pair_idx as u16)?
-            } else {
-                UserOrder::from_internal_order_max_gas(order, outcome, pair_idx as u16)
-            };
+            asset_builder.save(AssetBuilderStage::UserOrder, fee_asset, protocol_fee.to());
+            if let Some(legs) = legs.as_deref_mut() {
+                legs.push(SettlementLeg {
+                    user: order.from(),
+                    recipient: user_order.recipient.unwrap_or(order.from()),
+                    use_internal: user_order.use_internal,
+                    asset_in,
+                    asset_out,
+                    quantity_in,
+                    quantity_out
+                });
+            }
             user_orders.push(user_order);
         }
+
+        // Account for our reward, the donations we encode have to add up to what we
+        // allocate for them
+        let mut rewards = PoolRewards::from_tob_outcome(&tob_outcome);
+        rewards.donate_surplus(surplus_charged.lps);
+        asset_builder.allocate(AssetBuilderStage::Reward, t0, rewards.total());
+        asset_builder.save(AssetBuilderStage::Reward, t0, surplus_charged.protocol);
+        // Push the pool update
+        pool_updates.push(PoolUpdate {
+            zero_for_one,
+            pair_index: pair_idx as u16,
+            swap_in_quantity: quantity_in,
+            rewards_update: rewards.to_rewards_update()
+        });
         Ok(())
     }
 
+    /// The bundle settling `proposal`, which charges its user orders `fees`.
     pub fn from_proposal(
         proposal: &Proposal,
         gas_details: BundleGasDetails,
-        pools: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>
+        pools: &HashMap<PoolId, (Address, Address, PoolSnapshot, u16)>,
+        fees: &BundleFees
     ) -> eyre::Result<Self> {
         trace!("Starting from_proposal");
         let mut top_of_block_orders = Vec::new();
         let mut pool_updates = Vec::new();
         let mut pairs = Vec::new();
         let mut user_orders = Vec::new();
+        let mut legs = Vec::new();
         let mut asset_builder = AssetBuilder::new();
 
         // Break out our input orders into lists of orders by pool
@@ -631,7 +748,7 @@ impl AngstromBundle {
 
         // this should never underflow. if it does. means that there is underlying
         // problem with the gas delegation module
-        assert!(
+        eyre::ensure!(
             gas_details.total_gas_cost_wei > total_gas,
             "Total gas cost '{}' greater than total gas '{}'",
             gas_details.total_gas_cost_wei,
@@ -644,7 +761,7 @@ impl AngstromBundle {
 
         // fetch gas used
         // Walk through our solutions to add them to the structure
-        for solution in proposal.solutions.iter() {
+        for solution in Self::canonical_solutions(&proposal.solutions, pools, &mut asset_builder) {
             // Get the information for the pool or skip this solution if we can't find a
             // pool for it
             let Some((t0, t1, snapshot, store_index)) = pools.get(&solution.id) else {
@@ -670,10 +787,11 @@ impl AngstromBundle {
             );
 
             // Call our processing function with a fixed amount of shared gas
-            Self::process_solution(
+            Self::process_solution_with_legs(
                 &mut pairs,
                 &mut asset_builder,
                 &mut user_orders,
+                Some(&mut legs),
                 &orders_by_pool,
                 &mut top_of_block_orders,
                 &mut pool_updates,
@@ -682,16 +800,27 @@ impl AngstromBundle {
                 *t0,
                 *t1,
                 *store_index,
-                shared_gas
+                shared_gas,
+                fees
             )?;
         }
-        Ok(Self::new(
-            asset_builder.get_asset_array(),
-            pairs,
-            pool_updates,
-            top_of_block_orders,
-            user_orders
-        ))
+
+        // settle the orders of a user that are funded by the proceeds of their other
+        // orders after those
+        let mut unordered = user_orders.into_iter().map(Some).collect::<Vec<_>>();
+        let user_orders = BalanceDeltas::new(gas_details.user_balances)
+            .settlement_order(&legs)
+            .into_iter()
+            .filter_map(|i| unordered[i].take())
+            .collect();
+
+        Ok(Self::builder()
+            .assets(asset_builder.get_asset_array())?
+            .pairs(pairs)?
+            .pool_updates(pool_updates)?
+            .top_of_block_orders(top_of_block_orders)?
+            .user_orders(user_orders)?
+            .build())
     }
 }
 
@@ -701,7 +830,9 @@ pub struct BundleGasDetails {
     /// gas
     token_price_per_wei: HashMap<(Address, Address), Ray>,
     /// total gas to execute the bundle on angstrom
-    total_gas_cost_wei:  u64
+    total_gas_cost_wei:  u64,
+    /// the start of block funds of the users with pending orders
+    user_balances:       UserBalances
 }
 
 impl BundleGasDetails {
@@ -709,7 +840,18 @@ impl BundleGasDetails {
         token_price_per_wei: HashMap<(Address, Address), Ray>,
         total_gas_cost_wei: u64
     ) -> Self {
-        Self { token_price_per_wei, total_gas_cost_wei }
+        Self { token_price_per_wei, total_gas_cost_wei, user_balances: UserBalances::default() }
+    }
+
+    pub fn total_gas_cost_wei(&self) -> u64 {
+        self.total_gas_cost_wei
+    }
+
+    /// Lets the bundle order the user orders so every one is funded when it
+    /// settles.
+    pub fn with_user_balances(mut self, user_balances: UserBalances) -> Self {
+        self.user_balances = user_balances;
+        self
     }
 }
 
@@ -798,8 +940,10 @@ impl AngstromPoolConfigStore {
         self.entries.insert(key, pool);
     }
 
+    /// The key the contract stores the config of a pool under, taken from its
+    /// assets in either order.
     pub fn derive_store_key(asset0: Address, asset1: Address) -> AngstromPoolPartialKey {
-        let hash = keccak256((asset0, asset1).abi_encode());
+        let hash = keccak256(TokenPair::new(asset0, asset1).tokens().abi_encode());
         let mut store_key = [0u8; 27];
         store_key.copy_from_slice(&hash[5..32]);
         AngstromPoolPartialKey(store_key)
@@ -879,7 +1023,37 @@ impl UniswapAngstromRegistry {
 
 #[cfg(test)]
 mod test {
-    use super::AngstromBundle;
+    use std::collections::HashMap;
+
+    use alloy::primitives::{Address, PrimitiveSignature, B256, U256};
+    use pade::PadeEncode;
+
+    use super::{AngstromBundle, AngstromPoolConfigStore};
+    use crate::{
+        matching::uniswap::PoolSnapshot,
+        orders::{OrderFillState, OrderId, OrderOutcome, PoolSolution},
+        primitive::PoolId,
+        sol_bindings::{
+            grouped_orders::{GroupedVanillaOrder, OrderWithStorageData, StandingVariants},
+            rpc_orders::{ExactStandingOrder, OrderMeta}
+        }
+    };
+
+    fn user_order(pool_id: PoolId, hash: u8) -> OrderWithStorageData<GroupedVanillaOrder> {
+        let signature = PrimitiveSignature::new(U256::from(1), U256::from(1), false);
+        let order = ExactStandingOrder {
+            exact_in: true,
+            amount: 100 * hash as u128,
+            meta: OrderMeta { signature: signature.pade_encode().into(), ..Default::default() },
+            ..Default::default()
+        };
+        OrderWithStorageData {
+            order: GroupedVanillaOrder::Standing(StandingVariants::Exact(order)),
+            pool_id,
+            order_id: OrderId { pool_id, hash: B256::repeat_byte(hash), ..Default::default() },
+            ..Default::default()
+        }
+    }
 
     #[test]
     fn can_be_constructed() {
@@ -941,4 +1115,62 @@ mod test {
         let user = bundle.user_orders.remove(0);
         println!("{user:?}");
     }
+
+    #[test]
+    fn encoding_does_not_depend_on_the_order_of_the_solutions() {
+        let (a, b, c) = (Address::repeat_byte(1), Address::repeat_byte(2), Address::repeat_byte(3));
+        let (first, second) = (PoolId::repeat_byte(1), PoolId::repeat_byte(2));
+        // the second pool holds the lowest address, so assets indexed as they come
+        // wouldn't line up with the sorted asset array, and its pair goes first
+        let pools = HashMap::from([
+            (first, (b, c, PoolSnapshot::default(), 0)),
+            (second, (a, b, PoolSnapshot::default(), 1))
+        ]);
+        let orders = vec![user_order(first, 1), user_order(first, 2), user_order(second, 3)];
+        let solution = |id, filled: &[usize]| PoolSolution {
+            id,
+            limit: filled
+                .iter()
+                .map(|&i| OrderOutcome {
+                    id:      orders[i].order_id,
+                    outcome: OrderFillState::CompleteFill
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let bundle = |solutions| {
+            AngstromBundle::for_gas_finalization(orders.clone(), solutions, &pools).unwrap()
+        };
+
+        let sorted = bundle(vec![solution(first, &[0, 1]), solution(second, &[2])]);
+        let shuffled = bundle(vec![solution(second, &[2]), solution(first, &[1, 0])]);
+        assert_eq!(sorted.pade_encode(), shuffled.pade_encode());
+
+        let assets = sorted
+            .assets
+            .iter()
+            .map(|asset| asset.addr)
+            .collect::<Vec<_>>();
+        assert_eq!(assets, vec![a, b, c]);
+        let indices = sorted
+            .pairs
+            .iter()
+            .map(|pair| (pair.index0, pair.index1))
+            .collect::<Vec<_>>();
+        assert_eq!(indices, vec![(0, 1), (1, 2)]);
+        let pairs = indices
+            .iter()
+            .map(|&(index0, index1)| (assets[index0 as usize], assets[index1 as usize]))
+            .collect::<Vec<_>>();
+        assert_eq!(pairs, vec![(a, b), (b, c)]);
+    }
+
+    #[test]
+    fn store_key_ignores_the_order_of_the_assets() {
+        let (a, b) = (Address::repeat_byte(1), Address::repeat_byte(2));
+        assert_eq!(
+            AngstromPoolConfigStore::derive_store_key(a, b),
+            AngstromPoolConfigStore::derive_store_key(b, a)
+        );
+    }
 }
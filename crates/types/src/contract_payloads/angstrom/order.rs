@@ -29,6 +29,17 @@ impl OrderQuantities {
             Self::Partial { max_quantity_in, .. } => *max_quantity_in
         }
     }
+
+    /// Whether the contract settles the fill, which a partial order only does
+    /// between its minimum and maximum
+    pub fn is_settleable(&self) -> bool {
+        match self {
+            Self::Exact { .. } => true,
+            Self::Partial { min_quantity_in, max_quantity_in, filled_quantity } => {
+                (*min_quantity_in..=*max_quantity_in).contains(filled_quantity)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PadeEncode, PadeDecode)]
@@ -0,0 +1,229 @@
+use alloy::primitives::{Address, B256, U256};
+use serde::{Deserialize, Serialize};
+
+use super::{AngstromBundle, OrderQuantities};
+use crate::{
+    contract_payloads::{Asset, Pair},
+    matching::Ray
+};
+
+/// What the settled bundle of a block did, order by order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleTrace {
+    pub block_number: u64,
+    pub tx_hash:      B256,
+    /// gas the bundle transaction used on chain
+    pub gas_used:     u64,
+    pub simulation:   BundleSimulation,
+    pub orders:       Vec<OrderTrace>,
+    pub amm_swaps:    Vec<AmmSwapTrace>
+}
+
+/// The outcome of running a bundle transaction again on the state of the block
+/// before the one it was settled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSimulation {
+    pub success:  bool,
+    pub gas_used: u64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TracedOrderKind {
+    TopOfBlock,
+    User
+}
+
+/// An order as the bundle settled it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderTrace {
+    pub order_hash:     B256,
+    pub kind:           TracedOrderKind,
+    pub user:           Address,
+    pub asset_in:       Address,
+    pub asset_out:      Address,
+    /// the price of the pair in the bundle, t1 over t0 as a ray
+    pub price_1over0:   U256,
+    /// for user orders, the side of the order that isn't specified is derived
+    /// from the price of the pair, before the pool fee is taken
+    pub quantity_in:    u128,
+    pub quantity_out:   u128,
+    /// what the order paid for gas, in asset0 of its pair
+    pub gas_fee_asset0: u128
+}
+
+/// A swap of a bundle against the AMM of a pair.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AmmSwapTrace {
+    pub asset_in:    Address,
+    pub asset_out:   Address,
+    pub quantity_in: u128
+}
+
+impl AngstromBundle {
+    /// The orders of the bundle, top of block orders first, as settled in
+    /// `block_number`.
+    pub fn order_traces(&self, block_number: u64) -> Vec<OrderTrace> {
+        let tob_orders = self.top_of_block_orders.iter().map(|order| {
+            let pair = &self.pairs[order.pairs_index as usize];
+            let (asset_in, asset_out) = pair_assets(pair, &self.assets, order.zero_for_1);
+            let order_hash = order.order_hash(&self.pairs, &self.assets, block_number);
+
+            OrderTrace {
+                order_hash,
+                kind: TracedOrderKind::TopOfBlock,
+                user: order.signature.recover_signer(order_hash),
+                asset_in,
+                asset_out,
+                price_1over0: pair.price_1over0,
+                quantity_in: order.quantity_in,
+                quantity_out: order.quantity_out,
+                gas_fee_asset0: order.gas_used_asset_0
+            }
+        });
+
+        let user_orders = self.user_orders.iter().map(|order| {
+            let pair = &self.pairs[order.pair_index as usize];
+            let (asset_in, asset_out) = pair_assets(pair, &self.assets, order.zero_for_one);
+            let order_hash = order.order_hash(&self.pairs, &self.assets, block_number);
+
+            let filled = match order.order_quantities {
+                OrderQuantities::Exact { quantity } => quantity,
+                OrderQuantities::Partial { filled_quantity, .. } => filled_quantity
+            };
+            let price = Ray::from(pair.price_1over0);
+            let (quantity_in, quantity_out) = match (order.zero_for_one, order.exact_in) {
+                (true, true) => (filled, price.quantity(filled, false)),
+                (true, false) => (price.inverse_quantity(filled, true), filled),
+                (false, true) => (filled, price.inverse_quantity(filled, false)),
+                (false, false) => (price.quantity(filled, true), filled)
+            };
+
+            OrderTrace {
+                order_hash,
+                kind: TracedOrderKind::User,
+                user: order.signature.recover_signer(order_hash),
+                asset_in,
+                asset_out,
+                price_1over0: pair.price_1over0,
+                quantity_in,
+                quantity_out,
+                gas_fee_asset0: order.extra_fee_asset0
+            }
+        });
+
+        tob_orders.chain(user_orders).collect()
+    }
+
+    /// The swaps of the bundle against the AMM, one per pool it updates.
+    pub fn amm_swap_traces(&self) -> Vec<AmmSwapTrace> {
+        self.pool_updates
+            .iter()
+            .map(|update| {
+                let pair = &self.pairs[update.pair_index as usize];
+                let (asset_in, asset_out) = pair_assets(pair, &self.assets, update.zero_for_one);
+                AmmSwapTrace { asset_in, asset_out, quantity_in: update.swap_in_quantity }
+            })
+            .collect()
+    }
+}
+
+/// The assets in and out of a trade on `pair`.
+fn pair_assets(pair: &Pair, assets: &[Asset], zero_for_one: bool) -> (Address, Address) {
+    let (asset0, asset1) = (assets[pair.index0 as usize].addr, assets[pair.index1 as usize].addr);
+    if zero_for_one {
+        (asset0, asset1)
+    } else {
+        (asset1, asset0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract_payloads::{
+        angstrom::{TopOfBlockOrder, UserOrder},
+        rewards::{PoolUpdate, RewardsUpdate},
+        Signature
+    };
+
+    #[test]
+    fn user_orders_are_traced_at_the_price_of_their_pair() {
+        let (t0, t1) = (Address::with_last_byte(1), Address::with_last_byte(2));
+        let (tob_user, user) = (Address::with_last_byte(3), Address::with_last_byte(4));
+        let assets = [t0, t1]
+            .map(|addr| Asset { addr, ..Default::default() })
+            .to_vec();
+        // two t1 per t0
+        let price = U256::from(2_000_000_000_000_000_000_000_000_000_u128);
+        let pairs =
+            vec![Pair { index0: 0, index1: 1, store_index: 0, price_1over0: price }];
+        let pool_updates = vec![PoolUpdate {
+            zero_for_one:     false,
+            pair_index:       0,
+            swap_in_quantity: 500,
+            rewards_update:   RewardsUpdate::CurrentOnly { amount: 0 }
+        }];
+        let tob = TopOfBlockOrder {
+            quantity_in: 100,
+            quantity_out: 190,
+            gas_used_asset_0: 7,
+            zero_for_1: true,
+            signature: Signature::Contract { from: tob_user, signature: Default::default() },
+            ..Default::default()
+        };
+        let user_order = UserOrder {
+            ref_id:               0,
+            use_internal:         false,
+            pair_index:           0,
+            min_price:            U256::ZERO,
+            recipient:            None,
+            hook_data:            None,
+            zero_for_one:         false,
+            standing_validation:  None,
+            order_quantities:     OrderQuantities::Partial {
+                min_quantity_in: 0,
+                max_quantity_in: 1_000,
+                filled_quantity: 600
+            },
+            max_extra_fee_asset0: 10,
+            extra_fee_asset0:     3,
+            exact_in:             true,
+            signature:            Signature::Contract {
+                from:      user,
+                signature: Default::default()
+            }
+        };
+        let bundle = AngstromBundle::new(assets, pairs, pool_updates, vec![tob], vec![user_order]);
+
+        let traces = bundle.order_traces(100);
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[0].kind, TracedOrderKind::TopOfBlock);
+        assert_eq!(traces[0].user, tob_user);
+        assert_eq!((traces[0].asset_in, traces[0].asset_out), (t0, t1));
+        assert_eq!((traces[0].quantity_in, traces[0].quantity_out), (100, 190));
+        assert_eq!(traces[0].gas_fee_asset0, 7);
+
+        assert_eq!(traces[1].kind, TracedOrderKind::User);
+        assert_eq!(traces[1].user, user);
+        assert_eq!((traces[1].asset_in, traces[1].asset_out), (t1, t0));
+        assert_eq!((traces[1].quantity_in, traces[1].quantity_out), (600, 300));
+        assert_eq!(traces[1].gas_fee_asset0, 3);
+        assert_eq!(
+            traces
+                .iter()
+                .map(|trace| trace.order_hash)
+                .collect::<Vec<_>>(),
+            bundle.get_order_hashes(100).collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            bundle.amm_swap_traces(),
+            vec![AmmSwapTrace { asset_in: t1, asset_out: t0, quantity_in: 500 }]
+        );
+    }
+}
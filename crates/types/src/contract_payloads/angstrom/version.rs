@@ -0,0 +1,131 @@
+use pade::{PadeDecode, PadeEncode};
+
+use super::AngstromBundle;
+
+/// Layout versions of the [`AngstromBundle`] payload.
+///
+/// Validators have to be able to read every version that can still land on
+/// chain, while the version they write is picked with a feature flag. This lets
+/// a contract upgrade roll out in two steps: first every node learns to decode
+/// the new version, then the encoder is switched once the contract accepts it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum BundleVersion {
+    /// The original payload without a version byte. Its first byte is the high
+    /// byte of the 24 bit length of the asset list, which is always zero as
+    /// the list can never grow to 64KiB. That leaves zero free to identify it.
+    Legacy = 0,
+    /// The payload prefixed with its version byte.
+    V1     = 1
+}
+
+impl BundleVersion {
+    /// The version this node encodes bundles with.
+    #[cfg(not(feature = "bundle-v1"))]
+    pub const ENCODE: Self = Self::Legacy;
+    /// The version this node encodes bundles with.
+    #[cfg(feature = "bundle-v1")]
+    pub const ENCODE: Self = Self::V1;
+    /// The newest version this node understands.
+    pub const LATEST: Self = Self::V1;
+}
+
+impl TryFrom<u8> for BundleVersion {
+    type Error = BundleDecodeError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Legacy),
+            1 => Ok(Self::V1),
+            v => Err(BundleDecodeError::UnknownVersion(v))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BundleDecodeError {
+    #[error("empty bundle payload")]
+    Empty,
+    #[error("unknown bundle version {0}")]
+    UnknownVersion(u8),
+    #[error("failed to decode a {0:?} bundle")]
+    InvalidPayload(BundleVersion)
+}
+
+impl AngstromBundle {
+    /// Encodes the bundle with the given layout version.
+    pub fn pade_encode_versioned(&self, version: BundleVersion) -> Vec<u8> {
+        match version {
+            BundleVersion::Legacy => self.pade_encode(),
+            BundleVersion::V1 => {
+                let mut buf = vec![version as u8];
+                buf.extend(self.pade_encode());
+                buf
+            }
+        }
+    }
+
+    /// Encodes the bundle the way it is submitted to the contract, see
+    /// [`BundleVersion::ENCODE`].
+    pub fn pade_encode_for_submission(&self) -> Vec<u8> {
+        self.pade_encode_versioned(BundleVersion::ENCODE)
+    }
+
+    /// Decodes a bundle of any supported version.
+    pub fn pade_decode_versioned(
+        buf: &mut &[u8]
+    ) -> Result<(BundleVersion, Self), BundleDecodeError> {
+        let version = BundleVersion::try_from(*buf.first().ok_or(BundleDecodeError::Empty)?)?;
+        if version != BundleVersion::Legacy {
+            *buf = &buf[1..];
+        }
+
+        let bundle =
+            Self::pade_decode(buf, None).map_err(|_| BundleDecodeError::InvalidPayload(version))?;
+
+        Ok((version, bundle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_round_trip() {
+        for version in [BundleVersion::Legacy, BundleVersion::V1] {
+            let bundle = AngstromBundle::new(vec![], vec![], vec![], vec![], vec![]);
+            let encoded = bundle.pade_encode_versioned(version);
+
+            let (decoded_version, decoded) =
+                AngstromBundle::pade_decode_versioned(&mut encoded.as_slice()).unwrap();
+            assert_eq!(decoded_version, version);
+            assert_eq!(decoded.pade_encode(), bundle.pade_encode());
+        }
+    }
+
+    #[test]
+    fn legacy_payload_is_unchanged() {
+        let bundle = AngstromBundle::new(vec![], vec![], vec![], vec![], vec![]);
+        let legacy = bundle.pade_encode_versioned(BundleVersion::Legacy);
+
+        assert_eq!(legacy, bundle.pade_encode());
+        assert_eq!(legacy[0], 0);
+        assert_eq!(&bundle.pade_encode_versioned(BundleVersion::V1)[1..], legacy.as_slice());
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        assert_eq!(
+            AngstromBundle::pade_decode_versioned(&mut [].as_slice()).unwrap_err(),
+            BundleDecodeError::Empty
+        );
+        assert_eq!(
+            AngstromBundle::pade_decode_versioned(
+                &mut [BundleVersion::LATEST as u8 + 1].as_slice()
+            )
+            .unwrap_err(),
+            BundleDecodeError::UnknownVersion(BundleVersion::LATEST as u8 + 1)
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use alloy::primitives::Address;
 use itertools::Itertools;
 
@@ -16,6 +18,7 @@ pub struct AssetBuilder {
     rewards:      StageTracker,
     top_of_block: StageTracker,
     user_orders:  StageTracker,
+    saved:        HashMap<Address, u128>,
     assets:       AssetArray
 }
 
@@ -83,6 +86,13 @@ impl AssetBuilder {
         self.get_stage(stage).allocate(asset, quantity);
     }
 
+    /// Keeps `quantity` of `asset` in the contract for the protocol, see
+    /// [`Asset::save`]
+    pub fn save(&mut self, stage: AssetBuilderStage, asset: Address, quantity: u128) {
+        self.get_stage(stage).allocate(asset, quantity);
+        *self.saved.entry(asset).or_default() += quantity;
+    }
+
     pub fn add_or_get_asset(&mut self, asset: Address) -> usize {
         self.assets.add_or_get_asset_idx(asset)
     }
@@ -101,6 +111,7 @@ impl AssetBuilder {
                     asset.take = tracker.take;
                     asset.settle = tracker.settle;
                 }
+                asset.save = self.saved.get(&asset.addr).copied().unwrap_or_default();
                 asset
             })
             .sorted_by_key(|a| a.addr)
@@ -115,6 +126,7 @@ impl Default for AssetBuilder {
             rewards:      StageTracker::new(),
             top_of_block: StageTracker::new(),
             user_orders:  StageTracker::new(),
+            saved:        HashMap::new(),
             assets:       AssetArray::new()
         }
     }
@@ -0,0 +1,261 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use alloy::primitives::aliases::I24;
+
+use super::RewardsUpdate;
+use crate::{
+    contract_payloads::{angstrom::AngstromBundle, tob::ToBOutcome},
+    matching::uniswap::Tick
+};
+
+/// Donations owed to the liquidity providers of a single pool for one block.
+///
+/// Rewards are funded by the bid of the pool's searcher and by the LPs' share
+/// of the surplus of the pool's user orders, see
+/// [`SurplusDistribution`](super::SurplusDistribution). The
+/// bid is spread over the initialized ticks the searcher's swap moved the
+/// price through, so that all of the liquidity used by the swap ends up
+/// trading at the same price. The AMM volume matched against the book is
+/// settled at the uniform clearing price and doesn't earn a donation of its
+/// own.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PoolRewards {
+    /// liquidity of the pool at the start of the block
+    pub start_liquidity:  u128,
+    /// amount donated to each initialized tick the swap moved through, keyed by
+    /// the lower tick of its range. Ticks that get nothing are kept, the
+    /// contract reads an amount for every initialized tick it walks
+    pub tick_donations:   BTreeMap<Tick, u128>,
+    /// the LPs' share of the surplus of the pool's user orders, donated to the
+    /// liquidity in range at the end of the swap
+    pub surplus_donation: u128
+}
+
+impl PoolRewards {
+    pub fn from_tob_outcome(outcome: &ToBOutcome) -> Self {
+        let tick_donations = outcome
+            .tick_donations
+            .iter()
+            .map(|(tick, donation)| (*tick, donation.saturating_to()))
+            .collect();
+
+        Self { start_liquidity: outcome.start_liquidity, tick_donations, surplus_donation: 0 }
+    }
+
+    /// Sum of the donations across all ticks
+    pub fn total(&self) -> u128 {
+        self.tick_donations.values().sum::<u128>() + self.surplus_donation
+    }
+
+    /// Adds the LPs' share of the surplus of the pool's orders to the current
+    /// tick.
+    pub fn donate_surplus(&mut self, amount: u128) {
+        self.surplus_donation += amount;
+    }
+
+    /// Encodes the donations the way the contract expects them. A donation to
+    /// more than one tick is given as the amount of each tick, starting at the
+    /// lowest one, the last being the donation to the current tick. The
+    /// surplus donation goes on top of that last amount.
+    pub fn to_rewards_update(&self) -> RewardsUpdate {
+        let mut quantities = self.tick_donations.values().copied().collect::<Vec<_>>();
+        match quantities.last_mut() {
+            Some(current) => *current += self.surplus_donation,
+            None => quantities.push(self.surplus_donation)
+        }
+
+        match quantities.len() {
+            1 => RewardsUpdate::CurrentOnly { amount: quantities[0] },
+            _ => RewardsUpdate::MultiTick {
+                start_tick: I24::try_from(
+                    self.tick_donations
+                        .keys()
+                        .next()
+                        .copied()
+                        .unwrap_or_default()
+                )
+                .unwrap_or_default(),
+                start_liquidity: self.start_liquidity,
+                quantities
+            }
+        }
+    }
+}
+
+impl RewardsUpdate {
+    /// Total amount donated by this update
+    pub fn total(&self) -> u128 {
+        match self {
+            Self::MultiTick { quantities, .. } => quantities.iter().sum(),
+            Self::CurrentOnly { amount } => *amount
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RewardsError {
+    #[error("pool update references unknown pair {0}")]
+    UnknownPair(u16),
+    #[error("multi tick rewards for pair {0} have no starting liquidity")]
+    NoLiquidity(u16),
+    #[error("pair {0} pays out rewards of {1} without a searcher or user fees to fund them")]
+    Unfunded(u16, u128)
+}
+
+impl AngstromBundle {
+    /// Checks that the rewards encoded in the bundle are well formed and that
+    /// every pool paying out a reward has a searcher bid to fund it. Without
+    /// one, the rewards of a pool may be no more than the extra fees its user
+    /// orders pay.
+    pub fn verify_rewards(&self) -> Result<(), RewardsError> {
+        let funded_pairs = self
+            .top_of_block_orders
+            .iter()
+            .map(|tob| tob.pairs_index)
+            .collect::<HashSet<_>>();
+
+        let mut pair_fees = HashMap::<u16, u128>::new();
+        for order in &self.user_orders {
+            *pair_fees.entry(order.pair_index).or_default() += order.extra_fee_asset0;
+        }
+
+        self.pool_updates.iter().try_for_each(|update| {
+            let pair = update.pair_index;
+            if pair as usize >= self.pairs.len() {
+                return Err(RewardsError::UnknownPair(pair))
+            }
+
+            if let RewardsUpdate::MultiTick { start_liquidity, .. } = &update.rewards_update {
+                if *start_liquidity == 0 {
+                    return Err(RewardsError::NoLiquidity(pair))
+                }
+            }
+
+            let total = update.rewards_update.total();
+            let fees = pair_fees.get(&pair).copied().unwrap_or_default();
+            if total > fees && !funded_pairs.contains(&pair) {
+                return Err(RewardsError::Unfunded(pair, total))
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+
+    use super::*;
+    use crate::contract_payloads::{angstrom::TopOfBlockOrder, rewards::PoolUpdate, Pair};
+
+    fn rewards(donations: &[(Tick, u128)]) -> PoolRewards {
+        PoolRewards {
+            start_liquidity: 1_000,
+            tick_donations: donations.iter().copied().collect(),
+            ..Default::default()
+        }
+    }
+
+    fn bundle(rewards_update: RewardsUpdate, funded: bool) -> AngstromBundle {
+        AngstromBundle::new(
+            vec![],
+            vec![Pair::default()],
+            vec![PoolUpdate {
+                zero_for_one: false,
+                pair_index: 0,
+                swap_in_quantity: 0,
+                rewards_update
+            }],
+            if funded { vec![TopOfBlockOrder::default()] } else { vec![] },
+            vec![]
+        )
+    }
+
+    #[test]
+    fn single_tick_donates_to_current() {
+        assert_eq!(rewards(&[]).to_rewards_update(), RewardsUpdate::CurrentOnly { amount: 0 });
+        assert_eq!(
+            rewards(&[(60, 100)]).to_rewards_update(),
+            RewardsUpdate::CurrentOnly { amount: 100 }
+        );
+    }
+
+    #[test]
+    fn multi_tick_donations_are_per_tick_from_lowest_tick() {
+        let rewards = rewards(&[(120, 5), (-60, 10), (0, 0), (60, 20)]);
+        let update = rewards.to_rewards_update();
+
+        assert_eq!(
+            update,
+            RewardsUpdate::MultiTick {
+                start_tick:      I24::unchecked_from(-60),
+                start_liquidity: 1_000,
+                quantities:      vec![10, 0, 20, 5]
+            }
+        );
+        assert_eq!(update.total(), rewards.total());
+    }
+
+    #[test]
+    fn surplus_is_donated_to_the_current_tick() {
+        let mut current = rewards(&[]);
+        current.donate_surplus(3);
+        assert_eq!(current.to_rewards_update(), RewardsUpdate::CurrentOnly { amount: 3 });
+
+        let mut ticks = rewards(&[(-60, 10), (0, 0), (60, 20)]);
+        ticks.donate_surplus(5);
+        let update = ticks.to_rewards_update();
+
+        // every tick keeps its own amount, the surplus only adds to the last one
+        assert_eq!(
+            update,
+            RewardsUpdate::MultiTick {
+                start_tick:      I24::unchecked_from(-60),
+                start_liquidity: 1_000,
+                quantities:      vec![10, 0, 25]
+            }
+        );
+        assert_eq!(update.total(), ticks.total());
+    }
+
+    #[test]
+    fn matches_tob_outcome() {
+        let outcome = ToBOutcome {
+            start_liquidity: 1_000,
+            tick_donations: HashMap::from([(0, U256::from(7)), (60, U256::ZERO)]),
+            ..Default::default()
+        };
+
+        // ticks without a donation keep their place in the encoding
+        assert_eq!(PoolRewards::from_tob_outcome(&outcome), rewards(&[(0, 7), (60, 0)]));
+    }
+
+    #[test]
+    fn verifies_bundle_rewards() {
+        assert_eq!(
+            bundle(rewards(&[(0, 1), (60, 2)]).to_rewards_update(), true).verify_rewards(),
+            Ok(())
+        );
+        assert_eq!(
+            bundle(RewardsUpdate::CurrentOnly { amount: 0 }, false).verify_rewards(),
+            Ok(())
+        );
+        assert_eq!(
+            bundle(RewardsUpdate::CurrentOnly { amount: 5 }, false).verify_rewards(),
+            Err(RewardsError::Unfunded(0, 5))
+        );
+        assert_eq!(
+            bundle(
+                RewardsUpdate::MultiTick {
+                    start_tick:      I24::unchecked_from(0),
+                    start_liquidity: 0,
+                    quantities:      vec![2, 1]
+                },
+                true
+            )
+            .verify_rewards(),
+            Err(RewardsError::NoLiquidity(0))
+        );
+    }
+}
@@ -3,13 +3,18 @@ use pade_macro::{PadeDecode, PadeEncode};
 
 use super::{Asset, Pair};
 
-#[derive(Debug, PadeEncode, PadeDecode)]
+mod distribution;
+mod surplus;
+pub use distribution::*;
+pub use surplus::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, PadeEncode, PadeDecode)]
 pub enum RewardsUpdate {
     MultiTick { start_tick: I24, start_liquidity: u128, quantities: Vec<u128> },
     CurrentOnly { amount: u128 }
 }
 
-#[derive(Debug, PadeEncode, PadeDecode)]
+#[derive(Debug, Clone, PadeEncode, PadeDecode)]
 pub struct PoolUpdate {
     pub zero_for_one:     bool,
     pub pair_index:       u16,
@@ -0,0 +1,127 @@
+use alloy::primitives::U256;
+use serde::{Deserialize, Serialize};
+
+const BPS: u16 = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("surplus shares of {lps_bps} and {protocol_bps} bps add up to more than all of it")]
+pub struct InvalidSurplusDistribution {
+    pub lps_bps:      u16,
+    pub protocol_bps: u16
+}
+
+/// Who the surplus of the filled user orders over their limits goes to, in
+/// basis points of it.
+///
+/// Every order is charged the shares of the LPs and the protocol out of its own
+/// surplus, as an extra fee in T0 on top of its gas. The LPs' share is donated
+/// to the pool the order is filled in, the protocol's share is saved in the
+/// contract. What is left stays with the users, pro rata to the surplus each
+/// of them got at the UCP. By default all of it does.
+///
+/// The shares decide the fees and donations a bundle encodes, so they are a
+/// parameter of the deployment every validator reads the same.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "SurplusShares")]
+pub struct SurplusDistribution {
+    lps_bps:      u16,
+    protocol_bps: u16
+}
+
+/// The shares as read, before they are checked.
+#[derive(Deserialize)]
+struct SurplusShares {
+    lps_bps:      u16,
+    protocol_bps: u16
+}
+
+impl TryFrom<SurplusShares> for SurplusDistribution {
+    type Error = InvalidSurplusDistribution;
+
+    fn try_from(shares: SurplusShares) -> Result<Self, Self::Error> {
+        Self::new(shares.lps_bps, shares.protocol_bps)
+    }
+}
+
+impl SurplusDistribution {
+    pub fn new(lps_bps: u16, protocol_bps: u16) -> Result<Self, InvalidSurplusDistribution> {
+        if lps_bps as u32 + protocol_bps as u32 > BPS as u32 {
+            return Err(InvalidSurplusDistribution { lps_bps, protocol_bps })
+        }
+
+        Ok(Self { lps_bps, protocol_bps })
+    }
+
+    pub fn lps_bps(&self) -> u16 {
+        self.lps_bps
+    }
+
+    pub fn protocol_bps(&self) -> u16 {
+        self.protocol_bps
+    }
+
+    pub fn users_bps(&self) -> u16 {
+        BPS - self.lps_bps - self.protocol_bps
+    }
+
+    /// What an order with `surplus` is charged, at most `max_fee`. When the fee
+    /// doesn't cover the full shares, the LPs and the protocol give up the same
+    /// fraction of theirs.
+    pub fn charge(&self, surplus: u128, max_fee: u128) -> SurplusCharge {
+        let charged_bps = self.lps_bps + self.protocol_bps;
+        if charged_bps == 0 {
+            return SurplusCharge::default()
+        }
+
+        let charged = (U256::from(surplus) * U256::from(charged_bps) / U256::from(BPS))
+            .saturating_to::<u128>()
+            .min(max_fee);
+        let lps = (U256::from(charged) * U256::from(self.lps_bps) / U256::from(charged_bps))
+            .saturating_to::<u128>();
+
+        SurplusCharge { lps, protocol: charged - lps }
+    }
+}
+
+/// The shares of an order's surplus it is charged, in T0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SurplusCharge {
+    pub lps:      u128,
+    pub protocol: u128
+}
+
+impl SurplusCharge {
+    pub fn total(&self) -> u128 {
+        self.lps + self.protocol
+    }
+}
+
+impl std::ops::AddAssign for SurplusCharge {
+    fn add_assign(&mut self, rhs: Self) {
+        self.lps += rhs.lps;
+        self.protocol += rhs.protocol;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn charges_the_shares_of_lps_and_protocol() {
+        assert!(SurplusDistribution::new(6_000, 4_001).is_err());
+        assert_eq!(
+            SurplusDistribution::default().charge(1_000, u128::MAX),
+            SurplusCharge::default()
+        );
+
+        let distribution = SurplusDistribution::new(3_000, 1_000).unwrap();
+        assert_eq!(distribution.users_bps(), 6_000);
+        assert_eq!(
+            distribution.charge(1_000, u128::MAX),
+            SurplusCharge { lps: 300, protocol: 100 }
+        );
+        // capped by the fee the order allows
+        assert_eq!(distribution.charge(1_000, 200), SurplusCharge { lps: 150, protocol: 50 });
+    }
+}
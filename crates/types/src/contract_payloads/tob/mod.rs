@@ -1,9 +1,9 @@
 use std::collections::HashMap;
 
-use alloy::primitives::{aliases::I24, U256};
+use alloy::primitives::U256;
 use eyre::eyre;
 
-use super::rewards::RewardsUpdate;
+use super::rewards::{PoolRewards, RewardsUpdate};
 use crate::{
     matching::uniswap::{PoolSnapshot, Quantity, Tick},
     sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder}
@@ -59,30 +59,6 @@ impl ToBOutcome {
     }
 
     pub fn to_rewards_update(&self) -> RewardsUpdate {
-        let mut donations = self.tick_donations.iter().collect::<Vec<_>>();
-        // Will sort from lowest to highest (donations[0] will be the lowest tick
-        // number)
-        donations.sort_by_key(|f| f.0);
-        // Each reward value is the cumulative sum of the rewards before it
-        let quantities = donations
-            .iter()
-            .scan(U256::ZERO, |state, (_tick, q)| {
-                *state += **q;
-                Some(u128::try_from(*state).unwrap())
-            })
-            .collect::<Vec<_>>();
-        let start_tick = I24::try_from(donations.first().map(|(a, _)| **a).unwrap_or_default())
-            .unwrap_or_default();
-
-        match quantities.len() {
-            0 | 1 => RewardsUpdate::CurrentOnly {
-                amount: quantities.first().copied().unwrap_or_default()
-            },
-            _ => RewardsUpdate::MultiTick {
-                start_tick,
-                start_liquidity: self.start_liquidity,
-                quantities
-            }
-        }
+        PoolRewards::from_tob_outcome(self).to_rewards_update()
     }
 }
@@ -4,6 +4,7 @@
 pub mod block_sync;
 pub mod consensus;
 pub mod contract_bindings;
+pub mod contract_events;
 pub mod contract_payloads;
 pub mod matching;
 pub mod mev_boost;
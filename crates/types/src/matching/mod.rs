@@ -13,7 +13,9 @@ pub mod match_estimate_response;
 mod math;
 pub use math::max_t1_for_t0;
 mod sqrtprice;
+pub mod surplus;
 mod tokens;
+pub mod ucp;
 pub mod uniswap;
 use malachite::{
     num::{arithmetic::traits::PowerOf2, conversion::traits::FromSciString},
@@ -0,0 +1,49 @@
+//! The surplus of a filled order, how much better it trades at the UCP than
+//! at its limit price. The bundle charges part of it to the order as an extra
+//! fee in T0, which is why it is given in both tokens.
+use alloy::primitives::U256;
+
+use super::Ray;
+use crate::{
+    orders::OrderOutcome,
+    sol_bindings::grouped_orders::{GroupedVanillaOrder, OrderWithStorageData}
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OrderSurplus {
+    /// t0 the order moves at the UCP, rounded the way the bundle settles it
+    pub t0_moved: u128,
+    /// surplus in t1, rounded down
+    pub t1:       u128,
+    /// the surplus in t0 at the UCP, rounded down
+    pub t0:       u128
+}
+
+impl OrderSurplus {
+    /// The surplus of `order` filled as `outcome` at `ucp`. An order filled
+    /// outside of its limit, which [`super::ucp::check_fills`] rejects, has
+    /// none.
+    pub fn of(
+        order: &OrderWithStorageData<GroupedVanillaOrder>,
+        outcome: &OrderOutcome,
+        ucp: Ray
+    ) -> Self {
+        let quantity = outcome.fill_amount(order.max_q());
+        let t0_moved = if order.is_bid() == order.exact_in() {
+            ucp.inverse_quantity(quantity, !order.is_bid())
+        } else {
+            quantity
+        };
+
+        // a bid gains from a ucp below its limit, an ask from one above
+        let limit = order.price_for_book_side(order.is_bid);
+        let gain =
+            if order.is_bid { limit.saturating_sub(*ucp) } else { ucp.saturating_sub(*limit) };
+        let t1 = Ray::from(gain)
+            .mul_quantity(U256::from(t0_moved))
+            .saturating_to();
+        let t0 = if ucp.is_zero() { 0 } else { ucp.inverse_quantity(t1, false) };
+
+        Self { t0_moved, t1, t0 }
+    }
+}
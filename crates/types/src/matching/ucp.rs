@@ -0,0 +1,130 @@
+//! How the uniform clearing price (UCP) of a pool is determined, shared by the
+//! matcher and by proposal verification so every validator arrives at the same
+//! price for the same book.
+//!
+//! Units: all prices are [`Ray`]s of T1 per T0, scaled by 1e27. Bids are
+//! compared by their price for the book side, the inverse of the price they
+//! were signed with, so a bid and an ask cross when `ask <= bid`.
+//!
+//! The UCP is set by the last bid and ask the matcher crossed:
+//! - if both were filled completely, the UCP is their midpoint, rounded down.
+//!   An odd sum rounds towards the ask, in favor of the bids.
+//! - if the bid has quantity left, it is the bid's price.
+//! - if the ask has quantity left, it is the ask's price.
+//!
+//! While the matcher carries debt from the AMM, the UCP is the price of the
+//! debt instead.
+use std::cmp::Ordering;
+
+use alloy::primitives::{B256, U256};
+
+use super::Ray;
+use crate::{
+    orders::OrderOutcome,
+    sol_bindings::grouped_orders::{GroupedVanillaOrder, OrderWithStorageData}
+};
+
+/// Midpoint of `ask` and `bid`, rounded down. Unlike `(ask + bid) / 2` this
+/// can't overflow.
+pub fn midpoint(ask: Ray, bid: Ray) -> Ray {
+    let (ask, bid) = (*ask, *bid);
+    Ray::from((ask >> 1) + (bid >> 1) + (ask & bid & U256::from(1)))
+}
+
+/// The UCP set by the crossing of `ask` and `bid`, where `bid_vs_ask` compares
+/// the quantity the bid had left with the one the ask had left.
+pub fn clearing_price(ask: Ray, bid: Ray, bid_vs_ask: Ordering) -> Ray {
+    match bid_vs_ask {
+        Ordering::Equal => midpoint(ask, bid),
+        Ordering::Greater => bid,
+        Ordering::Less => ask
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum UcpViolation {
+    #[error("bid {0:?} was filled at a ucp above its limit")]
+    BidAboveLimit(B256),
+    #[error("ask {0:?} was filled at a ucp below its limit")]
+    AskBelowLimit(B256),
+    #[error("order {0:?} was filled but isn't in the book")]
+    UnknownOrder(B256)
+}
+
+/// Checks that none of the orders filled by `outcomes` clears at `ucp` outside
+/// of its limit. `order` looks up the orders of the book by hash.
+pub fn check_fills<'a>(
+    ucp: Ray,
+    outcomes: &[OrderOutcome],
+    order: impl Fn(&B256) -> Option<&'a OrderWithStorageData<GroupedVanillaOrder>>
+) -> Result<(), UcpViolation> {
+    outcomes
+        .iter()
+        .filter(|outcome| outcome.is_filled())
+        .try_for_each(|outcome| {
+            let hash = outcome.id.hash;
+            let order = order(&hash).ok_or(UcpViolation::UnknownOrder(hash))?;
+            let limit = order.price_for_book_side(order.is_bid);
+
+            if order.is_bid && limit < ucp {
+                return Err(UcpViolation::BidAboveLimit(hash))
+            }
+            if !order.is_bid && limit > ucp {
+                return Err(UcpViolation::AskBelowLimit(hash))
+            }
+
+            Ok(())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    /// Vectors every implementation of the rule has to reproduce.
+    const CONFORMANCE_VECTORS: &str = include_str!("ucp_vectors.json");
+
+    #[derive(Deserialize)]
+    struct Vector {
+        ask:       Ray,
+        bid:       Ray,
+        /// which side had quantity left, `none` if both were filled
+        remainder: String,
+        ucp:       Ray
+    }
+
+    #[test]
+    fn conformance_vectors() {
+        let vectors: Vec<Vector> = serde_json::from_str(CONFORMANCE_VECTORS).unwrap();
+        assert!(!vectors.is_empty());
+
+        for v in vectors {
+            let bid_vs_ask = match v.remainder.as_str() {
+                "none" => Ordering::Equal,
+                "bid" => Ordering::Greater,
+                "ask" => Ordering::Less,
+                other => panic!("unknown remainder {other}")
+            };
+            assert_eq!(
+                clearing_price(v.ask, v.bid, bid_vs_ask),
+                v.ucp,
+                "ask {:?} bid {:?} remainder {}",
+                v.ask,
+                v.bid,
+                v.remainder
+            );
+        }
+    }
+
+    #[test]
+    fn midpoint_does_not_overflow() {
+        let max = Ray::from(U256::MAX);
+        assert_eq!(midpoint(max, max), max);
+        assert_eq!(
+            midpoint(Ray::from(U256::MAX - U256::from(1)), max),
+            Ray::from(U256::MAX - U256::from(1))
+        );
+    }
+}
@@ -8,7 +8,7 @@ pub use flags::*;
 pub use liqrange::{LiqRange, LiqRangeRef};
 pub use poolprice::PoolPrice;
 pub use poolpricevec::PoolPriceVec;
-pub use poolsnapshot::PoolSnapshot;
+pub use poolsnapshot::{PoolSnapshot, QuoteRejection};
 
 pub type Tick = i32;
 
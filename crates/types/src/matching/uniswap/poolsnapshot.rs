@@ -25,7 +25,25 @@ pub struct PoolSnapshot {
     pub(crate) current_tick:   Tick,
     /// Index into the 'ranges' vector for the PoolRange that includes the tick
     /// our current price lives at/in
-    pub(crate) cur_tick_idx:   usize
+    pub(crate) cur_tick_idx:   usize,
+    /// Block whose state the snapshot was taken from, unknown for snapshots
+    /// that weren't taken from a synced pool
+    #[serde(default)]
+    pub(crate) block:          Option<u64>
+}
+
+/// Why a quote refused to price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[serde(rename_all = "camelCase", tag = "reason")]
+pub enum QuoteRejection {
+    #[error("amm snapshot of block {snapshot_block} is {age} blocks old")]
+    StaleSnapshot { snapshot_block: u64, age: u64 },
+    #[error("amm snapshot isn't from a known block")]
+    UnknownSnapshotBlock,
+    #[error("price of the pair was last updated in block {last_update}")]
+    StalePrice { last_update: u64 },
+    #[error("no price for the pair")]
+    NoPrice
 }
 
 impl PoolSnapshot {
@@ -58,7 +76,28 @@ impl PoolSnapshot {
             ));
         };
 
-        Ok(Self { ranges, sqrt_price_x96, current_tick, cur_tick_idx })
+        Ok(Self { ranges, sqrt_price_x96, current_tick, cur_tick_idx, block: None })
+    }
+
+    pub fn with_block(mut self, block: u64) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    pub fn block(&self) -> Option<u64> {
+        self.block
+    }
+
+    /// Checks that the snapshot is at most `max_age` blocks behind
+    /// `current_block`, so it can still be quoted against.
+    pub fn check_fresh(&self, current_block: u64, max_age: u64) -> Result<(), QuoteRejection> {
+        let snapshot_block = self.block.ok_or(QuoteRejection::UnknownSnapshotBlock)?;
+        let age = current_block.saturating_sub(snapshot_block);
+        if age > max_age {
+            return Err(QuoteRejection::StaleSnapshot { snapshot_block, age })
+        }
+
+        Ok(())
     }
 
     /// Find the PoolRange in this market snapshot that the provided tick lies
@@ -128,3 +167,21 @@ impl PoolSnapshot {
         self.get_range_for_tick(tick).map(|range| range.liquidity())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_go_stale() {
+        let snapshot = PoolSnapshot::default();
+        assert_eq!(snapshot.check_fresh(10, 2), Err(QuoteRejection::UnknownSnapshotBlock));
+
+        let snapshot = snapshot.with_block(8);
+        assert_eq!(snapshot.check_fresh(10, 2), Ok(()));
+        assert_eq!(
+            snapshot.check_fresh(11, 2),
+            Err(QuoteRejection::StaleSnapshot { snapshot_block: 8, age: 3 })
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use alloy::primitives::B256;
+use serde::{Deserialize, Serialize};
+
+use crate::sol_bindings::{grouped_orders::StandingVariants, RawPoolOrder};
+
+/// Replaces a pending standing order with the same order for another
+/// `max_amount_in_or_out`. The amended order is signed by the user like any
+/// order and keeps the nonce of the one it replaces, so only one of them can
+/// ever settle.
+///
+/// An amendment that lowers the amount keeps the place of the order in the
+/// book, one that raises it is queued again as if it was new.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OrderAmendment {
+    /// hash of the order that is amended
+    pub order_id: B256,
+    pub order:    StandingVariants
+}
+
+impl OrderAmendment {
+    /// Whether the amended order is `original` with nothing but its amount
+    /// and signature changed.
+    pub fn amends(&self, original: &StandingVariants) -> bool {
+        if original.order_hash() != self.order_id || original.from() != self.order.from() {
+            return false
+        }
+
+        let mut expected = original.clone();
+        match (&mut expected, &self.order) {
+            (StandingVariants::Partial(expected), StandingVariants::Partial(amended)) => {
+                expected.max_amount_in = amended.max_amount_in;
+                expected.meta = amended.meta.clone();
+            }
+            (StandingVariants::Exact(expected), StandingVariants::Exact(amended)) => {
+                expected.amount = amended.amount;
+                expected.meta = amended.meta.clone();
+            }
+            _ => return false
+        }
+
+        expected == self.order
+    }
+
+    /// Whether the amendment raises the amount of `original`, which costs the
+    /// order its place in the book.
+    pub fn is_increase(&self, original: &StandingVariants) -> bool {
+        self.order.max_q() > original.max_q()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::Address;
+
+    use super::*;
+    use crate::sol_bindings::rpc_orders::{OrderMeta, PartialStandingOrder};
+
+    fn order(max_amount_in: u128) -> StandingVariants {
+        StandingVariants::Partial(PartialStandingOrder {
+            max_amount_in,
+            nonce: 7,
+            meta: OrderMeta { from: Address::with_last_byte(1), ..Default::default() },
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn only_the_amount_can_be_amended() {
+        let original = order(100);
+        let amendment = OrderAmendment { order_id: original.order_hash(), order: order(50) };
+        assert!(amendment.amends(&original));
+        assert!(!amendment.is_increase(&original));
+
+        let amendment = OrderAmendment { order_id: original.order_hash(), order: order(150) };
+        assert!(amendment.amends(&original));
+        assert!(amendment.is_increase(&original));
+
+        let StandingVariants::Partial(mut other_nonce) = order(50) else { unreachable!() };
+        other_nonce.nonce = 8;
+        let amendment = OrderAmendment {
+            order_id: original.order_hash(),
+            order:    StandingVariants::Partial(other_nonce)
+        };
+        assert!(!amendment.amends(&original));
+
+        let amendment = OrderAmendment { order_id: B256::ZERO, order: order(50) };
+        assert!(!amendment.amends(&original));
+    }
+}
@@ -0,0 +1,63 @@
+use alloy::primitives::U256;
+use serde::{Deserialize, Serialize};
+
+use crate::primitive::PoolId;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BookSide {
+    Bid,
+    Ask
+}
+
+impl BookSide {
+    pub fn from_is_bid(is_bid: bool) -> Self {
+        if is_bid {
+            Self::Bid
+        } else {
+            Self::Ask
+        }
+    }
+}
+
+/// All pending limit orders of a pool at one price.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookLevel {
+    pub price:  U256,
+    pub volume: u128,
+    pub orders: usize
+}
+
+/// A change to a single level of a book.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum BookLevelUpdate {
+    /// a level that wasn't in the book before
+    Add { side: BookSide, level: BookLevel },
+    /// the volume or order count of an existing level changed
+    Modify { side: BookSide, level: BookLevel },
+    /// the last order of the level left the book
+    Remove { side: BookSide, price: U256 }
+}
+
+/// A book update along with its position in the updates of the pool.
+/// Sequence numbers have no gaps, so a client can tell when it missed one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookDelta {
+    pub pool_id:  PoolId,
+    pub sequence: u64,
+    pub update:   BookLevelUpdate
+}
+
+/// The full book of a pool, as of the delta with `sequence`. Bids are ordered
+/// from the highest price down, asks from the lowest up.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookSnapshot {
+    pub pool_id:  PoolId,
+    pub sequence: u64,
+    pub bids:     Vec<BookLevel>,
+    pub asks:     Vec<BookLevel>
+}
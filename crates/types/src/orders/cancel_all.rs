@@ -0,0 +1,109 @@
+use alloy::{
+    primitives::{keccak256, Address, PrimitiveSignature, B256},
+    sol_types::SolValue
+};
+use serde::{Deserialize, Serialize};
+
+use super::OrderId;
+use crate::{primitive::PoolId, sol_bindings::RespendAvoidanceMethod};
+
+/// Cancels every pending order of `user_address`, or only the ones in
+/// `pool_id`, with a single signature.
+///
+/// Standing orders are covered if their nonce is below `nonce`, usually the
+/// next valid nonce of the user. Nodes keep the request as a tombstone of the
+/// account, so covered orders that are still being gossiped aren't taken in
+/// again, while the orders the user signs afterwards aren't affected. Flash
+/// and top of block orders are covered if they are pending when the request
+/// arrives, they only live for a block anyway.
+///
+/// A request whose nonce isn't above the one of the last request of the same
+/// account and pool is stale, which keeps it from being replayed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CancelAllOrdersRequest {
+    pub user_address: Address,
+    /// only cancels the orders in this pool, the orders in all pools if None
+    pub pool_id:      Option<PoolId>,
+    pub nonce:        u64,
+    pub signature:    PrimitiveSignature
+}
+
+impl CancelAllOrdersRequest {
+    /// The hash `user_address` signs to cancel its orders. All pools are
+    /// signed as the zero pool id.
+    pub fn signing_hash(user_address: Address, pool_id: Option<PoolId>, nonce: u64) -> B256 {
+        keccak256((user_address, pool_id.unwrap_or_default(), nonce).abi_encode())
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let hash = Self::signing_hash(self.user_address, self.pool_id, self.nonce);
+        let Ok(sender) = self.signature.recover_address_from_prehash(&hash) else { return false };
+
+        sender == self.user_address
+    }
+
+    /// Whether the request cancels the pending order `id`.
+    pub fn covers(&self, id: &OrderId) -> bool {
+        id.address == self.user_address
+            && self.pool_id.map_or(true, |pool| pool == id.pool_id)
+            && match id.reuse_avoidance {
+                RespendAvoidanceMethod::Nonce(nonce) => nonce < self.nonce,
+                RespendAvoidanceMethod::Block(_) => true
+            }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+
+    use super::*;
+    use crate::sol_bindings::{
+        grouped_orders::{AllOrders, FlashVariants, StandingVariants},
+        rpc_orders::{ExactFlashOrder, OrderMeta, PartialStandingOrder}
+    };
+
+    fn request(signer: &PrivateKeySigner, pool_id: Option<PoolId>) -> CancelAllOrdersRequest {
+        let hash = CancelAllOrdersRequest::signing_hash(signer.address(), pool_id, 5);
+        CancelAllOrdersRequest {
+            user_address: signer.address(),
+            pool_id,
+            nonce: 5,
+            signature: signer.sign_hash_sync(&hash).unwrap()
+        }
+    }
+
+    #[test]
+    fn covers_older_standing_and_all_flash_orders() {
+        let signer = PrivateKeySigner::random();
+        let pool = PoolId::repeat_byte(1);
+        let meta = OrderMeta { from: signer.address(), ..Default::default() };
+        let standing = |nonce, pool_id| {
+            let order = AllOrders::Standing(StandingVariants::Partial(PartialStandingOrder {
+                nonce,
+                meta: meta.clone(),
+                ..Default::default()
+            }));
+            OrderId::from_all_orders(&order, pool_id)
+        };
+        let flash = AllOrders::Flash(FlashVariants::Exact(ExactFlashOrder {
+            valid_for_block: 100,
+            meta: meta.clone(),
+            ..Default::default()
+        }));
+
+        let all_pools = request(&signer, None);
+        assert!(all_pools.is_valid());
+        assert!(all_pools.covers(&standing(4, pool)));
+        assert!(!all_pools.covers(&standing(5, pool)));
+        assert!(all_pools.covers(&OrderId::from_all_orders(&flash, pool)));
+
+        let one_pool = request(&signer, Some(pool));
+        assert!(one_pool.covers(&standing(4, pool)));
+        assert!(!one_pool.covers(&standing(4, PoolId::repeat_byte(2))));
+
+        // signed for one pool, replayed for all of them
+        let replayed = CancelAllOrdersRequest { pool_id: None, ..one_pool };
+        assert!(!replayed.is_valid());
+    }
+}
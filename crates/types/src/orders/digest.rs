@@ -0,0 +1,21 @@
+use alloy::primitives::B256;
+use serde::{Deserialize, Serialize};
+
+/// An order in an [`OrderDigest`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DigestEntry {
+    pub order_hash: B256,
+    /// unix timestamp, in seconds, at which the order leaves the book
+    pub expires_at: u64
+}
+
+/// The orders in a node's book, which nodes periodically exchange so that
+/// their books converge. A node drops the orders none of its peers hold
+/// anymore. The expiries are informational, nodes expire orders by their own
+/// clock.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderDigest {
+    /// block the book was at
+    pub block_number: u64,
+    pub orders:       Vec<DigestEntry>
+}
@@ -1,15 +1,29 @@
+mod amendment;
+mod book;
+mod cancel_all;
+mod digest;
 mod fillstate;
 mod origin;
+mod relay;
+mod sort;
+mod source_tag;
 use alloy::{
     primitives::{keccak256, Address, FixedBytes, PrimitiveSignature, B256},
     sol_types::SolValue
 };
 pub mod orderpool;
 
+pub use amendment::*;
+pub use book::*;
+pub use cancel_all::*;
+pub use digest::*;
 pub use fillstate::*;
 pub use orderpool::*;
 pub use origin::*;
+pub use relay::*;
 use serde::{Deserialize, Serialize};
+pub use sort::*;
+pub use source_tag::*;
 
 pub type BookID = u128;
 pub type OrderID = u128;
@@ -130,6 +144,33 @@ pub struct PoolSolution {
     pub limit:        Vec<OrderOutcome>
 }
 
+impl PoolSolution {
+    /// The solution without any of its book fills, only the searcher order is
+    /// swapped against the AMM. Pools fall back to it when their book can't be
+    /// solved or settled, so their searcher order and LP rewards still are.
+    pub fn amm_only(&self) -> Self {
+        Self {
+            id:           self.id,
+            ucp:          Ray::default(),
+            searcher:     self.searcher.clone(),
+            amm_quantity: None,
+            limit:        self
+                .limit
+                .iter()
+                .map(|outcome| OrderOutcome {
+                    id:      outcome.id,
+                    outcome: OrderFillState::Unfilled
+                })
+                .collect()
+        }
+    }
+
+    /// Whether nothing but the searcher order is settled.
+    pub fn is_amm_only(&self) -> bool {
+        self.amm_quantity.is_none() && !self.limit.iter().any(OrderOutcome::is_filled)
+    }
+}
+
 impl PartialOrd for PoolSolution {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
         Some(self.cmp(other))
@@ -152,8 +193,13 @@ pub struct CancelOrderRequest {
 }
 
 impl CancelOrderRequest {
+    /// The hash `user_address` signs to cancel its order `order_id`.
+    pub fn signing_hash(user_address: Address, order_id: B256) -> B256 {
+        keccak256((user_address, order_id).abi_encode())
+    }
+
     fn signing_payload(&self) -> FixedBytes<32> {
-        keccak256((self.user_address, self.order_id).abi_encode())
+        Self::signing_hash(self.user_address, self.order_id)
     }
 
     pub fn is_valid(&self) -> bool {
@@ -16,6 +16,25 @@ pub enum OrderStatus {
     Blocked
 }
 
+/// Where a single order in the pool stands, without the order itself.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderState {
+    pub order_hash: B256,
+    pub user:       Address,
+    pub pool_id:    PoolId,
+    pub status:     OrderStatus
+}
+
+/// State of every order in the pool as of `block_number`. Sent to subscribers
+/// that fell behind on updates so they can rebuild their view of the pool.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderStatesSnapshot {
+    pub block_number: u64,
+    pub orders:       Vec<OrderState>
+}
+
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct OrderId {
     /// user address
@@ -49,12 +68,18 @@ impl OrderId {
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct OrderPriorityData {
-    pub price:     U256,
-    pub volume:    u128,
+    pub price:        U256,
+    pub volume:       u128,
     /// gas used in the pairs token0
-    pub gas:       U256,
+    pub gas:          U256,
     /// gas units used
-    pub gas_units: u64
+    pub gas_units:    u64,
+    /// when the order was validated, in unix ms. Orders at the same price are
+    /// filled in this order under [`SortStrategy::ByPriceByTime`]
+    ///
+    /// [`SortStrategy::ByPriceByTime`]: crate::orders::SortStrategy::ByPriceByTime
+    #[serde(default)]
+    pub validated_at: u64
 }
 
 impl PartialOrd for OrderPriorityData {
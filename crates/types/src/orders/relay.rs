@@ -0,0 +1,72 @@
+use alloy::{
+    dyn_abi::Eip712Domain,
+    primitives::{keccak256, Address, PrimitiveSignature, B256},
+    sol_types::SolValue
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    primitive::angstrom_domain,
+    sol_bindings::{grouped_orders::AllOrders, RawPoolOrder}
+};
+
+/// The Angstrom deployment of another chain, which orders are relayed from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub struct OriginDomain {
+    pub chain_id:         u64,
+    pub angstrom_address: Address
+}
+
+impl OriginDomain {
+    /// The domain the orders of this deployment are signed under.
+    pub fn eip712_domain(&self) -> Eip712Domain {
+        angstrom_domain(self.chain_id, self.angstrom_address)
+    }
+}
+
+/// An order signed for the deployment of another chain, relayed to us along
+/// with the attestations of the relayers that saw it there.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayedOrder {
+    pub origin:       OriginDomain,
+    /// block of the origin chain the order was seen at
+    pub source_block: u64,
+    pub order:        AllOrders,
+    /// signatures over [`Self::attestation_hash`]
+    pub attestations: Vec<PrimitiveSignature>
+}
+
+impl RelayedOrder {
+    pub fn order_hash(&self) -> B256 {
+        self.order.order_hash()
+    }
+
+    /// What the relayers sign: the order, where it was seen, and when.
+    pub fn attestation_hash(&self) -> B256 {
+        keccak256(
+            (
+                self.origin.chain_id,
+                self.origin.angstrom_address,
+                self.source_block,
+                self.order_hash()
+            )
+                .abi_encode()
+        )
+    }
+
+    /// The relayers that signed the attestations, skipping signatures that
+    /// don't recover.
+    pub fn attesters(&self) -> impl Iterator<Item = Address> + '_ {
+        let hash = self.attestation_hash();
+        self.attestations
+            .iter()
+            .filter_map(move |sig| sig.recover_address_from_prehash(&hash).ok())
+    }
+
+    /// Whether the order is signed by its user for the origin deployment.
+    pub fn is_valid_order_signature(&self) -> bool {
+        self.order.is_valid_signature(&self.origin.eip712_domain())
+    }
+}
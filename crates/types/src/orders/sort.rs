@@ -0,0 +1,138 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sol_bindings::grouped_orders::OrderWithStorageData;
+
+/// There are lots of different ways we can sort the orders we get in, so let's
+/// make this modular
+///
+/// The strategy decides which of two orders at the same price is filled
+/// first, so every validator has to build its books with the same one. It is
+/// set for a deployment, see
+/// [`DeploymentConfig`](crate::primitive::DeploymentConfig).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortStrategy {
+    Unsorted,
+    ByPriceByVolume,
+    /// Strict price-time priority: by price, then by when the order was
+    /// validated, with the order hash breaking ties so the order is total.
+    ByPriceByTime
+}
+
+impl Default for SortStrategy {
+    fn default() -> Self {
+        Self::Unsorted
+    }
+}
+
+impl SortStrategy {
+    pub fn sort_bids<O>(&self, bids: &mut [OrderWithStorageData<O>]) {
+        match self {
+            Self::Unsorted => {}
+            // Sort by price and then by volume - highest price first, highest volume first
+            // for same price
+            // Because of price inversion, we're going to reverse the order of sorting for
+            // our bid prices
+            Self::ByPriceByVolume => bids.sort_by(|a, b| a.priority_data.cmp(&b.priority_data)),
+            Self::ByPriceByTime => bids.sort_by(Self::price_time)
+        }
+    }
+
+    pub fn sort_asks<O>(&self, asks: &mut [OrderWithStorageData<O>]) {
+        match self {
+            Self::Unsorted => {}
+            // Sort by price and then by volume - lowest price first, highest volume first
+            // for same price
+            Self::ByPriceByVolume => asks.sort_by(|a, b| a.priority_data.cmp(&b.priority_data)),
+            Self::ByPriceByTime => asks.sort_by(Self::price_time)
+        }
+    }
+
+    /// How two orders of the same side of a book compare, the same way for
+    /// bids and asks. Unsorted orders are all equal.
+    pub fn compare<O>(&self, a: &OrderWithStorageData<O>, b: &OrderWithStorageData<O>) -> Ordering {
+        match self {
+            Self::Unsorted => Ordering::Equal,
+            Self::ByPriceByVolume => a.priority_data.cmp(&b.priority_data),
+            Self::ByPriceByTime => Self::price_time(a, b)
+        }
+    }
+
+    fn price_time<O>(a: &OrderWithStorageData<O>, b: &OrderWithStorageData<O>) -> Ordering {
+        a.priority_data
+            .price
+            .cmp(&b.priority_data.price)
+            .then_with(|| {
+                a.priority_data
+                    .validated_at
+                    .cmp(&b.priority_data.validated_at)
+            })
+            .then_with(|| a.order_id.hash.cmp(&b.order_id.hash))
+    }
+}
+
+/// The time an order is taken to be validated at from the stamps of the
+/// validators that vouch for it. The lower median, which a minority of the
+/// validators can't move outside the stamps of the rest, unlike the earliest
+/// one that any of them could back-date.
+pub fn median_validated_at(mut stamps: Vec<u64>) -> u64 {
+    stamps.sort_unstable();
+    stamps
+        .get(stamps.len().saturating_sub(1) / 2)
+        .copied()
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{B256, U256};
+
+    use super::*;
+    use crate::orders::{OrderId, OrderPriorityData};
+
+    fn order(price: u64, validated_at: u64, hash: u8) -> OrderWithStorageData<()> {
+        OrderWithStorageData {
+            priority_data: OrderPriorityData {
+                price: U256::from(price),
+                validated_at,
+                ..Default::default()
+            },
+            order_id: OrderId { hash: B256::repeat_byte(hash), ..Default::default() },
+            ..Default::default()
+        }
+    }
+
+    fn hashes(orders: &[OrderWithStorageData<()>]) -> Vec<u8> {
+        orders.iter().map(|o| o.order_id.hash[0]).collect()
+    }
+
+    #[test]
+    fn price_time_priority() {
+        let mut asks =
+            vec![order(10, 300, 1), order(9, 500, 2), order(10, 100, 3), order(10, 100, 0)];
+
+        SortStrategy::ByPriceByTime.sort_asks(&mut asks);
+        assert_eq!(hashes(&asks), vec![2, 0, 3, 1]);
+
+        // the result doesn't depend on the order the book was built from
+        asks.reverse();
+        SortStrategy::ByPriceByTime.sort_asks(&mut asks);
+        assert_eq!(hashes(&asks), vec![2, 0, 3, 1]);
+    }
+
+    #[test]
+    fn a_minority_cannot_back_date_an_order() {
+        assert_eq!(median_validated_at(vec![300, 0, 310]), 300);
+        assert_eq!(median_validated_at(vec![300, 0, 310, 305]), 300);
+        assert_eq!(median_validated_at(vec![300]), 300);
+        assert_eq!(median_validated_at(vec![]), 0);
+    }
+
+    #[test]
+    fn strategy_is_read_from_config() {
+        let strategy: SortStrategy = serde_json::from_str("\"by_price_by_time\"").unwrap();
+        assert_eq!(strategy, SortStrategy::ByPriceByTime);
+    }
+}
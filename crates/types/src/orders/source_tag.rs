@@ -0,0 +1,91 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Longest source tag we take.
+pub const MAX_SOURCE_TAG_LEN: usize = 64;
+
+/// Identifies the dapp or frontend an order was submitted through, so the
+/// orders of an integration can be attributed to it. Tags are only known to
+/// the node the order was submitted to, they aren't part of the order and
+/// never gossiped.
+///
+/// A tag is made of ascii letters, digits, `.`, `_` and `-`, as it ends up in
+/// metric labels and logs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SourceTag(String);
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidSourceTag {
+    #[error("source tag is empty")]
+    Empty,
+    #[error("source tag is {0} characters long, at most {MAX_SOURCE_TAG_LEN} are allowed")]
+    TooLong(usize),
+    #[error("source tag contains {0:?}, only ascii letters, digits, '.', '_' and '-' are allowed")]
+    InvalidChar(char)
+}
+
+impl SourceTag {
+    pub fn new(tag: impl Into<String>) -> Result<Self, InvalidSourceTag> {
+        let tag = tag.into();
+        if tag.is_empty() {
+            return Err(InvalidSourceTag::Empty)
+        }
+        if tag.len() > MAX_SOURCE_TAG_LEN {
+            return Err(InvalidSourceTag::TooLong(tag.len()))
+        }
+        if let Some(c) = tag
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')))
+        {
+            return Err(InvalidSourceTag::InvalidChar(c))
+        }
+
+        Ok(Self(tag))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl TryFrom<String> for SourceTag {
+    type Error = InvalidSourceTag;
+
+    fn try_from(tag: String) -> Result<Self, Self::Error> {
+        Self::new(tag)
+    }
+}
+
+impl From<SourceTag> for String {
+    fn from(tag: SourceTag) -> Self {
+        tag.0
+    }
+}
+
+impl fmt::Display for SourceTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_takes_well_formed_tags() {
+        assert_eq!(
+            SourceTag::new("uniswap-frontend.v2_1").unwrap().as_str(),
+            "uniswap-frontend.v2_1"
+        );
+        assert_eq!(SourceTag::new(""), Err(InvalidSourceTag::Empty));
+        assert_eq!(SourceTag::new("a".repeat(65)), Err(InvalidSourceTag::TooLong(65)));
+        assert_eq!(SourceTag::new("my dapp"), Err(InvalidSourceTag::InvalidChar(' ')));
+
+        let tag: SourceTag = serde_json::from_str("\"dapp\"").unwrap();
+        assert_eq!(serde_json::to_string(&tag).unwrap(), "\"dapp\"");
+        assert!(serde_json::from_str::<SourceTag>("\"dapp\\n\"").is_err());
+    }
+}
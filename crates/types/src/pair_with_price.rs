@@ -1,8 +1,7 @@
 use alloy::{consensus::Transaction, primitives::Address};
 use futures::{Stream, StreamExt};
-use pade::PadeDecode;
 use reth_primitives_traits::BlockBody;
-use reth_provider::CanonStateNotificationStream;
+use reth_provider::CanonStateNotification;
 
 use crate::{contract_payloads::angstrom::AngstromBundle, sol_bindings::Ray};
 
@@ -34,7 +33,7 @@ impl PairsWithPrice {
 
     pub fn into_price_update_stream(
         angstrom_address: Address,
-        stream: CanonStateNotificationStream
+        stream: impl Stream<Item = CanonStateNotification> + Send + Sync
     ) -> impl Stream<Item = Vec<Self>> + Send + Sync {
         stream.map(move |notification| {
             let new_cannon_chain = match notification {
@@ -50,7 +49,9 @@ impl PairsWithPrice {
                 .filter(|tx| tx.to() == Some(angstrom_address))
                 .filter_map(|transaction| {
                     let mut input: &[u8] = transaction.input();
-                    AngstromBundle::pade_decode(&mut input, None).ok()
+                    AngstromBundle::pade_decode_versioned(&mut input)
+                        .map(|(_, bundle)| bundle)
+                        .ok()
                 })
                 .take(1)
                 .flat_map(|bundle| Self::from_angstrom_bundle(block_num, &bundle))
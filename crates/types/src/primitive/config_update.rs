@@ -0,0 +1,31 @@
+use alloy::{primitives::BlockNumber, transports::http::reqwest::Url};
+use serde::{Deserialize, Serialize};
+
+use super::PoolId;
+
+/// A change to the configuration of a running node. Updates are broadcast to
+/// every manager, each of which applies the ones that concern it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigUpdate {
+    /// New size limits of the order pool
+    PoolLimits(PoolLimits),
+    /// Replaces the endpoints bundles are submitted to
+    SubmissionTargets(Vec<Url>),
+    /// Stops or resumes the intake of new orders
+    OrderIntake { paused: bool },
+    /// Resumes matching for a pool whose circuit breaker tripped
+    ResumePool(PoolId),
+    /// Allows a bundle to be submitted again for a height we already submitted
+    /// one for
+    ClearSubmission(BlockNumber)
+}
+
+/// Maximum sizes in bytes of the order sub-pools. A limit that isn't set is
+/// left as it is. Lowering a limit doesn't evict orders, it only stops new
+/// ones from coming in until the pool is back under it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolLimits {
+    pub limit_max_size:    Option<usize>,
+    pub searcher_max_size: Option<usize>
+}
@@ -16,19 +16,30 @@ ERC20,
 
 pub use ERC20::*;
 
-use crate::primitive::PoolId;
+use crate::primitive::{PoolId, TokenPair};
 
 pub const TESTNET_ANGSTROM_ADDRESS: Address =
     alloy::primitives::address!("293954613283cC7B82BfE9676D3cc0fb0A58fAa0");
 
-// The `eip712_domain` macro lets you easily define an EIP-712 domain
-// object :)
-pub const ANGSTROM_DOMAIN: Eip712Domain = eip712_domain!(
-    name: "Angstrom",
-    version: "v1",
-    chain_id: 1,
-    verifying_contract: TESTNET_ANGSTROM_ADDRESS,
-);
+/// The asset address wallets commonly use for native ETH. Angstrom only settles
+/// ERC20s: it pulls assets with `transferFrom` and has no payable entry point
+/// to wrap ETH with, so orders have to use WETH instead.
+pub const NATIVE_ETH: Address = Address::ZERO;
+
+/// The domain of the testnet deployment. Nodes build theirs from their chain
+/// and contract address with [`angstrom_domain`].
+pub const ANGSTROM_DOMAIN: Eip712Domain = angstrom_domain(1, TESTNET_ANGSTROM_ADDRESS);
+
+/// The EIP-712 domain orders are signed under for the Angstrom contract at
+/// `angstrom_address` on `chain_id`.
+pub const fn angstrom_domain(chain_id: u64, angstrom_address: Address) -> Eip712Domain {
+    eip712_domain!(
+        name: "Angstrom",
+        version: "v1",
+        chain_id: chain_id,
+        verifying_contract: angstrom_address,
+    )
+}
 
 #[derive(Default, Clone)]
 pub struct UniswapPoolRegistry {
@@ -43,12 +54,26 @@ impl UniswapPoolRegistry {
     pub fn pools(&self) -> HashMap<PoolId, PoolKey> {
         self.pools.clone()
     }
+
+    /// The pool of `pair`, if it is registered.
+    pub fn pool_for_pair(&self, pair: TokenPair) -> Option<(PoolId, &PoolKey)> {
+        self.pools
+            .iter()
+            .find(|(_, key)| TokenPair::from(*key) == pair)
+            .map(|(id, key)| (*id, key))
+    }
 }
 impl From<Vec<PoolKey>> for UniswapPoolRegistry {
     fn from(pools: Vec<PoolKey>) -> Self {
         let pubmap = pools
             .iter()
             .map(|pool_key| {
+                // the pool id is the hash of the key, a key with its currencies the other
+                // way around would be another pool
+                debug_assert!(
+                    TokenPair::is_canonical(pool_key.currency0, pool_key.currency1),
+                    "pool key currencies aren't sorted: {pool_key:?}"
+                );
                 let pool_id = PoolId::from(pool_key.clone());
                 (pool_id, pool_key.clone())
             })
@@ -66,3 +91,38 @@ impl From<Vec<PoolKey>> for UniswapPoolRegistry {
         Self { pools: pubmap, conversion_map: priv_map }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::SignerSync;
+    use pade::PadeEncode;
+
+    use super::*;
+    use crate::{
+        primitive::AngstromSigner,
+        sol_bindings::{
+            rpc_orders::{OmitOrderMeta, OrderMeta, TopOfBlockOrder},
+            RawPoolOrder
+        }
+    };
+
+    #[test]
+    fn orders_signed_for_another_chain_are_invalid() {
+        let signer = AngstromSigner::random();
+        let mainnet = angstrom_domain(1, TESTNET_ANGSTROM_ADDRESS);
+
+        let mut order = TopOfBlockOrder::default();
+        let sig = signer
+            .sign_hash_sync(&order.no_meta_eip712_signing_hash(&mainnet))
+            .unwrap();
+        order.meta = OrderMeta {
+            isEcdsa:   true,
+            from:      signer.address(),
+            signature: sig.pade_encode().into()
+        };
+
+        assert!(order.is_valid_signature(&mainnet));
+        assert!(!order.is_valid_signature(&angstrom_domain(11155111, TESTNET_ANGSTROM_ADDRESS)));
+        assert!(!order.is_valid_signature(&angstrom_domain(1, Address::repeat_byte(1))));
+    }
+}
@@ -0,0 +1,231 @@
+use std::{collections::HashMap, path::Path, time::Duration};
+
+use alloy::{dyn_abi::Eip712Domain, primitives::Address};
+use serde::{Deserialize, Serialize};
+
+use super::{angstrom_domain, PeerId, TransferTax};
+use crate::{contract_payloads::rewards::SurplusDistribution, orders::SortStrategy};
+
+/// Deployments known at build time. Nodes on any other chain, or running
+/// their own instance, pass theirs in with a config file.
+const EMBEDDED_DEPLOYMENTS: &str = include_str!("deployments.json");
+
+/// Where one instance of the Angstrom contracts lives, along with the
+/// parameters of the chain it lives on that the node can't read from the chain
+/// itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeploymentConfig {
+    pub chain_id:                   u64,
+    pub angstrom_address:           Address,
+    pub controller_address:         Address,
+    pub pool_manager_address:       Address,
+    /// How the books of the deployment are sorted. Validators have to agree on
+    /// it, or they fill different orders from the same pre-proposals.
+    #[serde(default = "default_book_sort")]
+    pub book_sort:                  SortStrategy,
+    /// Whether the pools of a wide proposal are simulated on their own before
+    /// the full bundle. A pool failing on its own is settled AMM only or left
+    /// out, so validators have to agree on it too.
+    #[serde(default)]
+    pub parallel_bundle_simulation: bool,
+    /// The fee-on-transfer tokens traded on the deployment. Orders that would
+    /// get less than their limit after the tax aren't filled, which validators
+    /// have to agree on.
+    #[serde(default)]
+    pub transfer_taxes:             Vec<TransferTax>,
+    /// The shares of the surplus of user orders charged for the LPs and the
+    /// protocol. They set the fees and donations a bundle encodes, so
+    /// validators have to agree on them.
+    #[serde(default)]
+    pub surplus_distribution:       SurplusDistribution,
+    /// Seconds between two blocks of the chain, which the consensus round is
+    /// timed against.
+    #[serde(default = "default_block_time_secs")]
+    pub block_time_secs:            u64,
+    /// The validator set of the deployment. Empty when it isn't known up
+    /// front.
+    #[serde(default)]
+    pub validators:                 Vec<ValidatorStake>
+}
+
+fn default_book_sort() -> SortStrategy {
+    SortStrategy::ByPriceByVolume
+}
+
+fn default_block_time_secs() -> u64 {
+    DEFAULT_BLOCK_TIME_SECS
+}
+
+/// The block time of mainnet and its test networks.
+pub const DEFAULT_BLOCK_TIME_SECS: u64 = 12;
+
+/// A validator of a deployment and its voting power.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorStake {
+    pub peer_id:      PeerId,
+    pub voting_power: u64
+}
+
+impl DeploymentConfig {
+    /// The domain orders for this deployment are signed under.
+    pub fn domain(&self) -> Eip712Domain {
+        angstrom_domain(self.chain_id, self.angstrom_address)
+    }
+
+    pub fn block_time(&self) -> Duration {
+        Duration::from_secs(self.block_time_secs)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum DeploymentError {
+    #[error("no angstrom deployment known for chain {0}")]
+    UnknownChain(u64),
+    #[error("chain {0} is listed more than once")]
+    DuplicateChain(u64),
+    #[error("invalid deployment list: {0}")]
+    Invalid(#[from] serde_json::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error)
+}
+
+/// Deployments by chain id.
+#[derive(Debug, Clone, Default)]
+pub struct DeploymentRegistry {
+    deployments: HashMap<u64, DeploymentConfig>
+}
+
+impl DeploymentRegistry {
+    /// The deployments shipped with the node.
+    pub fn embedded() -> Self {
+        Self::from_json(EMBEDDED_DEPLOYMENTS).expect("embedded deployments are valid")
+    }
+
+    /// Reads a JSON list of deployments.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, DeploymentError> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, DeploymentError> {
+        let mut registry = Self::default();
+        for deployment in serde_json::from_str::<Vec<DeploymentConfig>>(json)? {
+            registry.insert(deployment)?;
+        }
+
+        Ok(registry)
+    }
+
+    pub fn insert(&mut self, deployment: DeploymentConfig) -> Result<(), DeploymentError> {
+        let chain_id = deployment.chain_id;
+        if self.deployments.insert(chain_id, deployment).is_some() {
+            return Err(DeploymentError::DuplicateChain(chain_id))
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, chain_id: u64) -> Result<DeploymentConfig, DeploymentError> {
+        self.deployments
+            .get(&chain_id)
+            .cloned()
+            .ok_or(DeploymentError::UnknownChain(chain_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_by_chain_id() {
+        let registry = DeploymentRegistry::from_json(
+            r#"[{
+                "chain_id": 11155111,
+                "angstrom_address": "0x0000000000000000000000000000000000000001",
+                "controller_address": "0x0000000000000000000000000000000000000002",
+                "pool_manager_address": "0x0000000000000000000000000000000000000003"
+            }]"#
+        )
+        .unwrap();
+
+        let deployment = registry.get(11155111).unwrap();
+        assert_eq!(deployment.book_sort, SortStrategy::ByPriceByVolume);
+        assert!(!deployment.parallel_bundle_simulation);
+        assert!(deployment.transfer_taxes.is_empty());
+        assert_eq!(deployment.surplus_distribution, SurplusDistribution::default());
+        assert_eq!(deployment.block_time(), Duration::from_secs(12));
+        assert!(deployment.validators.is_empty());
+        assert_eq!(deployment.angstrom_address, Address::with_last_byte(1));
+        assert_eq!(deployment.domain().chain_id, Some(alloy::primitives::U256::from(11155111)));
+        assert!(matches!(registry.get(1), Err(DeploymentError::UnknownChain(1))));
+
+        assert!(matches!(
+            registry.clone().insert(deployment),
+            Err(DeploymentError::DuplicateChain(11155111))
+        ));
+    }
+
+    #[test]
+    fn reads_chain_parameters() {
+        let registry = DeploymentRegistry::from_json(
+            r#"[{
+                "chain_id": 17000,
+                "angstrom_address": "0x0000000000000000000000000000000000000001",
+                "controller_address": "0x0000000000000000000000000000000000000002",
+                "pool_manager_address": "0x0000000000000000000000000000000000000003",
+                "block_time_secs": 4,
+                "transfer_taxes": ["0x0000000000000000000000000000000000000004:30"],
+                "surplus_distribution": { "lps_bps": 3000, "protocol_bps": 1000 },
+                "validators": [{ "peer_id": "0x0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a", "voting_power": 100 }]
+            }]"#
+        )
+        .unwrap();
+
+        let deployment = registry.get(17000).unwrap();
+        assert_eq!(deployment.block_time(), Duration::from_secs(4));
+        assert_eq!(
+            deployment.validators,
+            vec![ValidatorStake { peer_id: PeerId::with_last_byte(10), voting_power: 100 }]
+        );
+        assert_eq!(
+            deployment.transfer_taxes,
+            vec![TransferTax { token: Address::with_last_byte(4), bps: 30 }]
+        );
+        assert_eq!(deployment.surplus_distribution, SurplusDistribution::new(3000, 1000).unwrap());
+    }
+
+    #[test]
+    fn rejects_surplus_shares_above_all_of_it() {
+        let registry = DeploymentRegistry::from_json(
+            r#"[{
+                "chain_id": 17000,
+                "angstrom_address": "0x0000000000000000000000000000000000000001",
+                "controller_address": "0x0000000000000000000000000000000000000002",
+                "pool_manager_address": "0x0000000000000000000000000000000000000003",
+                "surplus_distribution": { "lps_bps": 6000, "protocol_bps": 4001 }
+            }]"#
+        );
+        assert!(matches!(registry, Err(DeploymentError::Invalid(_))));
+    }
+
+    #[test]
+    fn rejects_invalid_transfer_taxes() {
+        let registry = DeploymentRegistry::from_json(
+            r#"[{
+                "chain_id": 17000,
+                "angstrom_address": "0x0000000000000000000000000000000000000001",
+                "controller_address": "0x0000000000000000000000000000000000000002",
+                "pool_manager_address": "0x0000000000000000000000000000000000000003",
+                "transfer_taxes": ["0x0000000000000000000000000000000000000004:10000"]
+            }]"#
+        );
+        assert!(matches!(registry, Err(DeploymentError::Invalid(_))));
+    }
+
+    #[test]
+    fn embedded_registry_has_the_testnet() {
+        let testnet = DeploymentRegistry::embedded().get(1).unwrap();
+        assert_eq!(testnet.angstrom_address, crate::primitive::TESTNET_ANGSTROM_ADDRESS);
+        assert_eq!(testnet.domain(), crate::primitive::ANGSTROM_DOMAIN);
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc
+    }
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A feature that can be switched on or off while the node runs, so that it
+/// can be shipped dark and turned on per node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Feature {
+    /// take orders with hook data, which are turned away otherwise
+    ComposableOrders,
+    /// keep the books between blocks, see `matching_engine::incremental`
+    IncrementalMatching,
+    /// take orders relayed from other chains, when an intake is configured
+    RelayIntake
+}
+
+impl Feature {
+    pub const ALL: [Feature; 3] =
+        [Feature::ComposableOrders, Feature::IncrementalMatching, Feature::RelayIntake];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Feature::ComposableOrders => "composable-orders",
+            Feature::IncrementalMatching => "incremental-matching",
+            Feature::RelayIntake => "relay-intake"
+        }
+    }
+
+    pub const fn enabled_by_default(self) -> bool {
+        match self {
+            Feature::ComposableOrders | Feature::IncrementalMatching => false,
+            Feature::RelayIntake => true
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("unknown feature {0}")]
+pub struct UnknownFeature(pub String);
+
+impl FromStr for Feature {
+    type Err = UnknownFeature;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Feature::ALL
+            .into_iter()
+            .find(|feature| feature.name() == s)
+            .ok_or_else(|| UnknownFeature(s.to_string()))
+    }
+}
+
+/// The features enabled on the node. Clones share the flags, so a feature the
+/// admin api switches is seen by every subsystem on its next check.
+#[derive(Clone)]
+pub struct FeatureFlags {
+    enabled: Arc<[AtomicBool; Feature::ALL.len()]>
+}
+
+impl Default for FeatureFlags {
+    fn default() -> Self {
+        Self::new([])
+    }
+}
+
+impl FeatureFlags {
+    /// The defaults of the features, with `overrides` applied.
+    pub fn new(overrides: impl IntoIterator<Item = (Feature, bool)>) -> Self {
+        let this = Self {
+            enabled: Arc::new(Feature::ALL.map(|f| AtomicBool::new(f.enabled_by_default())))
+        };
+        for (feature, enabled) in overrides {
+            this.set(feature, enabled);
+        }
+
+        this
+    }
+
+    pub fn is_enabled(&self, feature: Feature) -> bool {
+        self.enabled[feature as usize].load(Ordering::Relaxed)
+    }
+
+    /// Returns whether the feature was enabled before.
+    pub fn set(&self, feature: Feature, enabled: bool) -> bool {
+        self.enabled[feature as usize].swap(enabled, Ordering::Relaxed)
+    }
+
+    pub fn snapshot(&self) -> BTreeMap<Feature, bool> {
+        Feature::ALL
+            .into_iter()
+            .map(|feature| (feature, self.is_enabled(feature)))
+            .collect()
+    }
+}
+
+impl fmt::Debug for FeatureFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.snapshot()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_the_flags() {
+        let flags = FeatureFlags::new([(Feature::IncrementalMatching, true)]);
+        let shared = flags.clone();
+        assert!(shared.is_enabled(Feature::IncrementalMatching));
+        assert!(!shared.is_enabled(Feature::ComposableOrders));
+
+        assert!(!flags.set(Feature::ComposableOrders, true));
+        assert!(shared.is_enabled(Feature::ComposableOrders));
+        assert_eq!(shared.snapshot().len(), Feature::ALL.len());
+
+        for feature in Feature::ALL {
+            assert_eq!(feature.name().parse::<Feature>(), Ok(feature));
+            assert_eq!(serde_json::to_string(&feature).unwrap(), format!("\"{}\"", feature.name()));
+        }
+        assert!("multi-hop".parse::<Feature>().is_err());
+    }
+}
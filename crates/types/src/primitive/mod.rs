@@ -1,11 +1,25 @@
+mod config_update;
 mod contract;
+mod deployment;
+mod feature_flags;
 mod peers;
 mod pool_state;
+mod remote_signer;
 mod signer;
+mod token_metadata;
+mod token_pair;
+mod transfer_tax;
 mod validation;
 
+pub use config_update::*;
 pub use contract::*;
+pub use deployment::*;
+pub use feature_flags::*;
 pub use peers::*;
 pub use pool_state::*;
+pub use remote_signer::*;
 pub use signer::*;
+pub use token_metadata::*;
+pub use token_pair::*;
+pub use transfer_tax::*;
 pub use validation::*;
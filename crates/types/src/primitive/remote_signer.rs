@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use alloy::{
+    primitives::{hex, PrimitiveSignature, SignatureError, B256},
+    signers::utils::public_key_to_address,
+    transports::http::reqwest::{self, header::CONTENT_TYPE, Client, Url}
+};
+use alloy_primitives::Address;
+use k256::{ecdsa::VerifyingKey, elliptic_curve::sec1::ToEncodedPoint};
+
+/// How long a single signing request may take. Consensus rounds wait on the
+/// signature, so a signer that hangs has to fail the round rather than stall
+/// it.
+pub const REMOTE_SIGNER_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, thiserror::Error)]
+pub enum RemoteSignerError {
+    #[error("the remote signer url can't have a path appended")]
+    Url,
+    #[error("remote signer request failed: {0}")]
+    Request(#[from] reqwest::Error),
+    #[error("remote signer returned a signature that isn't hex: {0}")]
+    Hex(#[from] hex::FromHexError),
+    #[error("remote signer returned an invalid signature: {0}")]
+    Signature(#[from] SignatureError),
+    #[error("remote signer signed with {0} instead of the configured key")]
+    WrongKey(Address)
+}
+
+/// A key held by an external signer, so that it never has to live on the node
+/// host. Speaks the Web3Signer eth1 api, which ledger and HSM backed signers
+/// can be put behind as well.
+///
+/// A hash is signed with a `POST {url}/api/v1/eth1/sign/{public key}` with
+/// `{"data": "<hash>"}` as the body, answered by the hex encoded 65 byte
+/// signature.
+#[derive(Debug, Clone)]
+pub struct RemoteSigner {
+    client:     Client,
+    endpoint:   Url,
+    public_key: VerifyingKey,
+    address:    Address
+}
+
+impl RemoteSigner {
+    pub fn new(url: Url, public_key: VerifyingKey) -> Result<Self, RemoteSignerError> {
+        let client = Client::builder().timeout(REMOTE_SIGNER_TIMEOUT).build()?;
+
+        Ok(Self {
+            client,
+            endpoint: sign_endpoint(url, &public_key)?,
+            address: public_key_to_address(&public_key),
+            public_key
+        })
+    }
+
+    pub fn public_key(&self) -> &VerifyingKey {
+        &self.public_key
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub async fn sign_hash(&self, hash: &B256) -> Result<PrimitiveSignature, RemoteSignerError> {
+        let body = serde_json::json!({ "data": hash }).to_string();
+        let response = self
+            .client
+            .post(self.endpoint.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        let signature = parse_signature(&response)?;
+        // peers would reject everything signed by a signer serving another key, so
+        // this is caught here instead
+        let signer = signature.recover_address_from_prehash(hash)?;
+        if signer != self.address {
+            return Err(RemoteSignerError::WrongKey(signer))
+        }
+
+        Ok(signature)
+    }
+}
+
+fn sign_endpoint(mut url: Url, public_key: &VerifyingKey) -> Result<Url, RemoteSignerError> {
+    let public_key = hex::encode_prefixed(public_key.to_encoded_point(false).as_bytes());
+    url.path_segments_mut()
+        .map_err(|_| RemoteSignerError::Url)?
+        .pop_if_empty()
+        .extend(["api", "v1", "eth1", "sign", &public_key]);
+
+    Ok(url)
+}
+
+/// Web3Signer answers with the bare hex string, other signers quote it as json.
+fn parse_signature(response: &str) -> Result<PrimitiveSignature, RemoteSignerError> {
+    let bytes = hex::decode(response.trim().trim_matches('"'))?;
+
+    Ok(PrimitiveSignature::try_from(bytes.as_slice())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::signers::{local::PrivateKeySigner, SignerSync};
+
+    use super::*;
+
+    #[test]
+    fn signatures_are_parsed_bare_and_quoted() {
+        let key = PrivateKeySigner::random();
+        let hash = B256::repeat_byte(7);
+        let signature = key.sign_hash_sync(&hash).unwrap();
+        let encoded = hex::encode_prefixed(signature.as_bytes());
+
+        assert_eq!(parse_signature(&encoded).unwrap(), signature);
+        assert_eq!(parse_signature(&format!("\"{encoded}\"\n")).unwrap(), signature);
+        assert!(parse_signature("0x1234").is_err());
+    }
+
+    #[test]
+    fn endpoint_is_appended_to_the_url() {
+        let key = PrivateKeySigner::random();
+        let public_key = key.credential().verifying_key();
+        let encoded = hex::encode_prefixed(public_key.to_encoded_point(false).as_bytes());
+
+        for url in ["http://signer:9000", "http://signer:9000/"] {
+            let endpoint = sign_endpoint(url.parse().unwrap(), public_key).unwrap();
+            assert_eq!(endpoint.as_str(), format!("http://signer:9000/api/v1/eth1/sign/{encoded}"));
+        }
+
+        let signer = RemoteSigner::new("http://signer:9000".parse().unwrap(), *public_key).unwrap();
+        assert_eq!(signer.address(), key.address());
+    }
+}
@@ -1,20 +1,30 @@
-use std::ops::{Deref, DerefMut};
-
 use alloy::{
     consensus::{SignableTransaction, TypedTransaction},
     network::{Ethereum, NetworkWallet},
-    primitives::PrimitiveSignature,
+    primitives::{ChainId, PrimitiveSignature, B256},
     signers::{local::PrivateKeySigner, SignerSync}
 };
 use alloy_primitives::Address;
 use k256::{ecdsa::VerifyingKey, elliptic_curve::sec1::ToEncodedPoint};
 use reth_network_peers::PeerId;
+use tokio::runtime::Handle;
+
+use super::RemoteSigner;
 
-/// Wrapper around key and signing to allow for a uniform type across codebase
+/// Wrapper around key and signing to allow for a uniform type across codebase.
+/// The key is either held by the node or by a [`RemoteSigner`], both the
+/// consensus messages and the settlement transaction are signed through it.
 #[derive(Debug, Clone)]
 pub struct AngstromSigner {
-    id:     PeerId,
-    signer: PrivateKeySigner
+    id:      PeerId,
+    address: Address,
+    backend: SignerBackend
+}
+
+#[derive(Debug, Clone)]
+enum SignerBackend {
+    Local(PrivateKeySigner),
+    Remote(RemoteSigner)
 }
 
 impl AngstromSigner {
@@ -22,7 +32,13 @@ impl AngstromSigner {
         let pub_key = signer.credential().verifying_key();
         let peer_id = Self::public_key_to_peer_id(pub_key);
 
-        Self { signer, id: peer_id }
+        Self { id: peer_id, address: signer.address(), backend: SignerBackend::Local(signer) }
+    }
+
+    pub fn remote(signer: RemoteSigner) -> Self {
+        let peer_id = Self::public_key_to_peer_id(signer.public_key());
+
+        Self { id: peer_id, address: signer.address(), backend: SignerBackend::Remote(signer) }
     }
 
     pub fn random() -> Self {
@@ -30,13 +46,17 @@ impl AngstromSigner {
     }
 
     pub fn address(&self) -> Address {
-        self.signer.address()
+        self.address
     }
 
     pub fn id(&self) -> PeerId {
         self.id
     }
 
+    pub fn is_remote(&self) -> bool {
+        matches!(self.backend, SignerBackend::Remote(_))
+    }
+
     /// Taken from alloy impl
     pub fn public_key_to_peer_id(pub_key: &VerifyingKey) -> PeerId {
         let affine = pub_key.as_ref();
@@ -45,27 +65,33 @@ impl AngstromSigner {
         PeerId::from_slice(&encoded.as_bytes()[1..])
     }
 
-    fn sign_transaction_inner(
-        &self,
-        tx: &mut dyn SignableTransaction<PrimitiveSignature>
-    ) -> alloy::signers::Result<PrimitiveSignature> {
-        let hash = tx.signature_hash();
-
-        self.signer.sign_hash_sync(&hash)
+    pub async fn sign_hash(&self, hash: &B256) -> alloy::signers::Result<PrimitiveSignature> {
+        match &self.backend {
+            SignerBackend::Local(signer) => signer.sign_hash_sync(hash),
+            SignerBackend::Remote(signer) => signer
+                .sign_hash(hash)
+                .await
+                .map_err(alloy::signers::Error::other)
+        }
     }
 }
 
-impl Deref for AngstromSigner {
-    type Target = PrivateKeySigner;
-
-    fn deref(&self) -> &Self::Target {
-        &self.signer
+impl SignerSync for AngstromSigner {
+    /// With a remote signer this blocks the current worker on the request, so
+    /// it has to be called from within a multi threaded tokio runtime.
+    fn sign_hash_sync(&self, hash: &B256) -> alloy::signers::Result<PrimitiveSignature> {
+        match &self.backend {
+            SignerBackend::Local(signer) => signer.sign_hash_sync(hash),
+            SignerBackend::Remote(signer) => {
+                let handle = Handle::try_current().map_err(alloy::signers::Error::other)?;
+                tokio::task::block_in_place(|| handle.block_on(signer.sign_hash(hash)))
+                    .map_err(alloy::signers::Error::other)
+            }
+        }
     }
-}
 
-impl DerefMut for AngstromSigner {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.signer
+    fn chain_id_sync(&self) -> Option<ChainId> {
+        None
     }
 }
 
@@ -90,24 +116,24 @@ impl NetworkWallet<Ethereum> for AngstromSigner {
         tx: TypedTransaction
     ) -> alloy::signers::Result<alloy::consensus::TxEnvelope> {
         match tx {
-            TypedTransaction::Legacy(mut t) => {
-                let sig = self.sign_transaction_inner(&mut t)?;
+            TypedTransaction::Legacy(t) => {
+                let sig = self.sign_hash(&t.signature_hash()).await?;
                 Ok(t.into_signed(sig).into())
             }
-            TypedTransaction::Eip2930(mut t) => {
-                let sig = self.sign_transaction_inner(&mut t)?;
+            TypedTransaction::Eip2930(t) => {
+                let sig = self.sign_hash(&t.signature_hash()).await?;
                 Ok(t.into_signed(sig).into())
             }
-            TypedTransaction::Eip1559(mut t) => {
-                let sig = self.sign_transaction_inner(&mut t)?;
+            TypedTransaction::Eip1559(t) => {
+                let sig = self.sign_hash(&t.signature_hash()).await?;
                 Ok(t.into_signed(sig).into())
             }
-            TypedTransaction::Eip4844(mut t) => {
-                let sig = self.sign_transaction_inner(&mut t)?;
+            TypedTransaction::Eip4844(t) => {
+                let sig = self.sign_hash(&t.signature_hash()).await?;
                 Ok(t.into_signed(sig).into())
             }
-            TypedTransaction::Eip7702(mut t) => {
-                let sig = self.sign_transaction_inner(&mut t)?;
+            TypedTransaction::Eip7702(t) => {
+                let sig = self.sign_hash(&t.signature_hash()).await?;
                 Ok(t.into_signed(sig).into())
             }
         }
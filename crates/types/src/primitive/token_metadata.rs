@@ -0,0 +1,43 @@
+use alloy::primitives::U256;
+use serde::{Deserialize, Serialize};
+
+/// What is needed to show amounts of a token the way users know them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenMetadata {
+    pub decimals: u8,
+    pub symbol:   String
+}
+
+impl TokenMetadata {
+    pub fn new(decimals: u8, symbol: String) -> Self {
+        Self { decimals, symbol }
+    }
+
+    /// Converts a raw amount of the token into whole tokens.
+    pub fn to_units(&self, amount: U256) -> f64 {
+        f64::from(amount) / 10f64.powi(self.decimals as i32)
+    }
+}
+
+/// Converts a price of raw `token1` per raw `token0` into whole tokens of
+/// `token1` per whole token of `token0`.
+pub fn normalize_price(raw_price: f64, token0: &TokenMetadata, token1: &TokenMetadata) -> f64 {
+    raw_price * 10f64.powi(token0.decimals as i32 - token1.decimals as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjusts_for_decimals() {
+        let usdc = TokenMetadata::new(6, "USDC".to_string());
+        let weth = TokenMetadata::new(18, "WETH".to_string());
+
+        assert_eq!(usdc.to_units(U256::from(2_500_000)), 2.5);
+        // 1 WETH = 2000 USDC is 2000e6 raw USDC per 1e18 raw WETH
+        assert!((normalize_price(2e-9, &weth, &usdc) - 2000.0).abs() < 1e-9);
+        assert!((normalize_price(5e8, &usdc, &weth) - 0.0005).abs() < 1e-15);
+    }
+}
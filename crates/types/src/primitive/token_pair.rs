@@ -0,0 +1,123 @@
+use alloy::primitives::Address;
+use serde::{Deserialize, Serialize};
+
+use crate::contract_bindings::angstrom::Angstrom::PoolKey;
+
+/// The two tokens of a pool, in the order Uniswap keys pools with: the lower
+/// address is token0 and the higher one token1. Pools are priced in token1 per
+/// token0, so an order giving token1 for token0 is a bid and an order giving
+/// token0 for token1 an ask.
+///
+/// Anything keyed by a pair of tokens goes through this type, so a pair can't
+/// be looked up with its tokens the other way around than it was stored with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct TokenPair {
+    token0: Address,
+    token1: Address
+}
+
+impl TokenPair {
+    /// The pair of `a` and `b`, given in either order.
+    pub fn new(a: Address, b: Address) -> Self {
+        debug_assert_ne!(a, b, "a pair needs two different tokens");
+        if a < b {
+            Self { token0: a, token1: b }
+        } else {
+            Self { token0: b, token1: a }
+        }
+    }
+
+    /// Same as [`Self::new`], for tokens that aren't known to differ. None if
+    /// `a` and `b` are the same token.
+    pub fn try_new(a: Address, b: Address) -> Option<Self> {
+        (a != b).then(|| Self::new(a, b))
+    }
+
+    /// The pair an order giving `token_in` for `token_out` trades, and
+    /// whether the order is a bid on it. None for an order trading a token
+    /// for itself.
+    pub fn for_order(token_in: Address, token_out: Address) -> Option<(Self, bool)> {
+        let pair = Self::try_new(token_in, token_out)?;
+        Some((pair, pair.is_bid(token_in)))
+    }
+
+    /// Whether `token0` and `token1` are already in canonical order.
+    pub fn is_canonical(token0: Address, token1: Address) -> bool {
+        token0 < token1
+    }
+
+    pub fn token0(&self) -> Address {
+        self.token0
+    }
+
+    pub fn token1(&self) -> Address {
+        self.token1
+    }
+
+    pub fn tokens(&self) -> (Address, Address) {
+        (self.token0, self.token1)
+    }
+
+    pub fn contains(&self, token: Address) -> bool {
+        token == self.token0 || token == self.token1
+    }
+
+    /// Whether an order giving `token_in` for the other token of the pair is
+    /// a bid.
+    pub fn is_bid(&self, token_in: Address) -> bool {
+        debug_assert!(self.contains(token_in), "{token_in} isn't a token of {self:?}");
+        token_in == self.token1
+    }
+}
+
+impl From<&PoolKey> for TokenPair {
+    fn from(key: &PoolKey) -> Self {
+        debug_assert!(
+            Self::is_canonical(key.currency0, key.currency1),
+            "pool key currencies aren't sorted: {} {}",
+            key.currency0,
+            key.currency1
+        );
+        Self::new(key.currency0, key.currency1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn distinct_tokens() -> impl Strategy<Value = (Address, Address)> {
+        (any::<[u8; 20]>(), any::<[u8; 20]>())
+            .prop_filter("tokens have to differ", |(a, b)| a != b)
+            .prop_map(|(a, b)| (Address::from(a), Address::from(b)))
+    }
+
+    proptest! {
+        #[test]
+        fn pair_is_the_same_either_way_round((a, b) in distinct_tokens()) {
+            let pair = TokenPair::new(a, b);
+            prop_assert_eq!(pair, TokenPair::new(b, a));
+            prop_assert!(TokenPair::is_canonical(pair.token0(), pair.token1()));
+            prop_assert!(pair.contains(a) && pair.contains(b));
+        }
+
+        #[test]
+        fn exactly_one_side_of_a_pair_bids((a, b) in distinct_tokens()) {
+            let (pair, a_bids) = TokenPair::for_order(a, b).unwrap();
+            let (reversed, b_bids) = TokenPair::for_order(b, a).unwrap();
+            prop_assert_eq!(pair, reversed);
+            prop_assert_ne!(a_bids, b_bids);
+            // a bid gives token1, the token pools are priced in
+            prop_assert_eq!(a_bids, a == pair.token1());
+        }
+
+        #[test]
+        fn a_token_makes_no_pair_with_itself(a in any::<[u8; 20]>()) {
+            let a = Address::from(a);
+            prop_assert_eq!(TokenPair::try_new(a, a), None);
+            prop_assert_eq!(TokenPair::for_order(a, a), None);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use std::{collections::HashMap, fmt, str::FromStr, sync::Arc};
+
+use alloy::primitives::Address;
+use serde::{Deserialize, Serialize};
+
+/// Basis points of an amount, what transfer taxes are given in.
+pub const TRANSFER_TAX_BPS_SCALE: u16 = 10_000;
+
+/// A fee-on-transfer token and the share of every transfer of it that doesn't
+/// reach the recipient, in basis points. (De)serialized as `<token>:<bps>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TransferTax {
+    pub token: Address,
+    pub bps:   u16
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum InvalidTransferTax {
+    #[error("transfer tax {0:?} isn't of the form <token>:<bps>")]
+    Format(String),
+    #[error("invalid token address {0}")]
+    Token(String),
+    #[error("invalid tax {0}, has to be below {TRANSFER_TAX_BPS_SCALE} bps")]
+    Bps(String)
+}
+
+impl FromStr for TransferTax {
+    type Err = InvalidTransferTax;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (token, bps) = s
+            .split_once(':')
+            .ok_or_else(|| InvalidTransferTax::Format(s.to_string()))?;
+        let token = token
+            .parse()
+            .map_err(|_| InvalidTransferTax::Token(token.to_string()))?;
+        let bps = bps
+            .parse()
+            .ok()
+            .filter(|bps| *bps < TRANSFER_TAX_BPS_SCALE)
+            .ok_or_else(|| InvalidTransferTax::Bps(bps.to_string()))?;
+
+        Ok(Self { token, bps })
+    }
+}
+
+impl TryFrom<String> for TransferTax {
+    type Error = InvalidTransferTax;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<TransferTax> for String {
+    fn from(tax: TransferTax) -> Self {
+        tax.to_string()
+    }
+}
+
+impl fmt::Display for TransferTax {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.token, self.bps)
+    }
+}
+
+/// The tokens classified as fee-on-transfer, whose recipients get less than
+/// what is sent to them. Tokens that aren't classified are taken to arrive in
+/// full.
+#[derive(Debug, Clone, Default)]
+pub struct TransferTaxes(Arc<HashMap<Address, u16>>);
+
+impl TransferTaxes {
+    pub fn new(taxes: impl IntoIterator<Item = TransferTax>) -> Self {
+        Self(Arc::new(taxes.into_iter().map(|tax| (tax.token, tax.bps)).collect()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The tax on transfers of `token`, zero if it isn't taxed.
+    pub fn bps(&self, token: &Address) -> u16 {
+        self.0.get(token).copied().unwrap_or_default()
+    }
+
+    pub fn is_taxed(&self, token: &Address) -> bool {
+        self.bps(token) != 0
+    }
+
+    /// What the recipient of `amount` of `token` gets, rounded down.
+    pub fn after_tax(&self, token: &Address, amount: u128) -> u128 {
+        let kept = (TRANSFER_TAX_BPS_SCALE - self.bps(token)) as u128;
+        let scale = TRANSFER_TAX_BPS_SCALE as u128;
+        // (amount / scale) * kept + (amount % scale) * kept / scale, which can't
+        // overflow
+        (amount / scale) * kept + (amount % scale) * kept / scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+
+    use super::*;
+
+    #[test]
+    fn parses_token_and_bps() {
+        let token = Address::repeat_byte(1);
+        let tax: TransferTax = format!("{token}:30").parse().unwrap();
+        assert_eq!(tax, TransferTax { token, bps: 30 });
+        assert_eq!(tax.to_string().parse(), Ok(tax));
+
+        assert!(matches!("30".parse::<TransferTax>(), Err(InvalidTransferTax::Format(_))));
+        assert!(matches!("0x12:30".parse::<TransferTax>(), Err(InvalidTransferTax::Token(_))));
+        assert!(matches!(
+            format!("{token}:10000").parse::<TransferTax>(),
+            Err(InvalidTransferTax::Bps(_))
+        ));
+    }
+
+    #[test]
+    fn only_classified_tokens_are_taxed() {
+        let taxed = Address::repeat_byte(1);
+        let taxes = TransferTaxes::new([TransferTax { token: taxed, bps: 100 }]);
+
+        assert_eq!(taxes.after_tax(&taxed, 1_000), 990);
+        assert_eq!(taxes.after_tax(&taxed, 999), 989);
+        let max = U256::from(u128::MAX) * U256::from(99) / U256::from(100);
+        assert_eq!(taxes.after_tax(&taxed, u128::MAX), max.to::<u128>());
+        assert_eq!(taxes.after_tax(&Address::repeat_byte(2), 1_000), 1_000);
+        assert!(!taxes.is_taxed(&Address::repeat_byte(2)));
+    }
+}
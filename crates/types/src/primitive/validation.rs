@@ -1,9 +1,86 @@
+use alloy::primitives::Address;
 use serde::{Deserialize, Serialize};
 
+use super::Feature;
+
+/// Why an order failed validation.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, Serialize, Deserialize)]
+pub enum ValidationError {
+    #[error("failed to read state: {0}")]
+    Db(String),
+    #[error("simulation reverted: {0}")]
+    SimulationReverted(String),
+    #[error("failed to simulate order: {0}")]
+    SimulationFailed(String),
+    #[error("no price to convert gas for pair {token0:?}/{token1:?}")]
+    MissingPrice { token0: Address, token1: Address },
+    #[error("too many orders of the user are already being validated")]
+    ThreadpoolSaturated,
+    #[error("order isn't signed by its sender for this chain and contract")]
+    InvalidSignature,
+    #[error("order uses native ETH, which can't be settled. WETH is required")]
+    NativeEth,
+    #[error("no pool for the order's pair")]
+    UnknownPool,
+    #[error("nonce is already used")]
+    DuplicateNonce,
+    #[error("the signer already has an order for this block")]
+    DuplicateBlock,
+    #[error("order was cancelled")]
+    Cancelled,
+    #[error("order was already submitted")]
+    DuplicateOrder,
+    #[error("{0}")]
+    InvalidBlock(String),
+    #[error("top of block order can't be filled: {0}")]
+    TopOfBlock(String),
+    #[error("order was validated against a block that is no longer the latest")]
+    StaleBlock,
+    #[error("a new block started during validation")]
+    TransitionedToBlock,
+    #[error("node isn't accepting orders")]
+    NotAcceptingOrders,
+    #[error("amendment doesn't change only the amount of a pending standing order of its sender")]
+    InvalidAmendment,
+    #[error("order expired or was dropped from the network's books")]
+    Expired,
+    #[error("{0} is disabled on this node")]
+    FeatureDisabled(Feature)
+}
+
+impl ValidationError {
+    /// A short name of the error that doesn't change with its details, for
+    /// labelling metrics.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Self::Db(_) => "db",
+            Self::SimulationReverted(_) => "simulation_reverted",
+            Self::SimulationFailed(_) => "simulation_failed",
+            Self::MissingPrice { .. } => "missing_price",
+            Self::ThreadpoolSaturated => "threadpool_saturated",
+            Self::InvalidSignature => "invalid_signature",
+            Self::NativeEth => "native_eth",
+            Self::UnknownPool => "unknown_pool",
+            Self::DuplicateNonce => "duplicate_nonce",
+            Self::DuplicateBlock => "duplicate_block",
+            Self::Cancelled => "cancelled",
+            Self::DuplicateOrder => "duplicate_order",
+            Self::InvalidBlock(_) => "invalid_block",
+            Self::TopOfBlock(_) => "top_of_block",
+            Self::StaleBlock => "stale_block",
+            Self::TransitionedToBlock => "transitioned_to_block",
+            Self::NotAcceptingOrders => "not_accepting_orders",
+            Self::InvalidAmendment => "invalid_amendment",
+            Self::Expired => "expired",
+            Self::FeatureDisabled(_) => "feature_disabled"
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum OrderPoolNewOrderResult {
     Valid,
-    Invalid,
+    Invalid(ValidationError),
     TransitionedToBlock,
     Error(String)
 }
@@ -2,7 +2,8 @@ use std::{hash::Hash, ops::Deref};
 
 use alloy::{
     primitives::{Address, Bytes, FixedBytes, TxHash, U256},
-    signers::Signature
+    signers::Signature,
+    sol_types::Eip712Domain
 };
 use alloy_primitives::{PrimitiveSignature, B256};
 use pade::PadeDecode;
@@ -12,7 +13,7 @@ use super::{GenerateFlippedOrder, RawPoolOrder, RespendAvoidanceMethod};
 use crate::{
     matching::{Debt, Ray},
     orders::{OrderId, OrderLocation, OrderPriorityData},
-    primitive::{PoolId, ANGSTROM_DOMAIN},
+    primitive::PoolId,
     sol_bindings::rpc_orders::{
         ExactFlashOrder, ExactStandingOrder, OmitOrderMeta, PartialFlashOrder,
         PartialStandingOrder, TopOfBlockOrder
@@ -128,7 +129,7 @@ impl FlashVariants {
     pub fn min_q(&self) -> u128 {
         match self {
             Self::Exact(o) => o.amount,
-            Self::Partial(o) => o.max_amount_in
+            Self::Partial(o) => o.min_amount_in
         }
     }
 
@@ -218,6 +219,15 @@ impl AllOrders {
             Self::TOB(t) => t.eip712_hash_struct()
         }
     }
+
+    /// Whether the order carries hook data to run on settlement.
+    pub fn is_composable(&self) -> bool {
+        match self {
+            Self::Standing(p) => !p.hook_data().is_empty(),
+            Self::Flash(f) => !f.hook_data().is_empty(),
+            Self::TOB(_) => false
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -407,10 +417,10 @@ impl RawPoolOrder for StandingVariants {
         None
     }
 
-    fn is_valid_signature(&self) -> bool {
+    fn is_valid_signature(&self, domain: &Eip712Domain) -> bool {
         match self {
-            StandingVariants::Exact(e) => e.is_valid_signature(),
-            StandingVariants::Partial(p) => p.is_valid_signature()
+            StandingVariants::Exact(e) => e.is_valid_signature(domain),
+            StandingVariants::Partial(p) => p.is_valid_signature(domain)
         }
     }
 
@@ -448,10 +458,10 @@ impl RawPoolOrder for FlashVariants {
         }
     }
 
-    fn is_valid_signature(&self) -> bool {
+    fn is_valid_signature(&self, domain: &Eip712Domain) -> bool {
         match self {
-            FlashVariants::Exact(e) => e.is_valid_signature(),
-            FlashVariants::Partial(p) => p.is_valid_signature()
+            FlashVariants::Exact(e) => e.is_valid_signature(domain),
+            FlashVariants::Partial(p) => p.is_valid_signature(domain)
         }
     }
 
@@ -604,6 +614,15 @@ impl GroupedVanillaOrder {
         }
     }
 
+    /// Minimum quantity this order can be filled for, which is all of it
+    /// unless the order is partial
+    pub fn min_q(&self) -> u128 {
+        match self {
+            Self::Standing(o) => o.min_q(),
+            Self::KillOrFill(o) => o.min_q()
+        }
+    }
+
     /// Quantity filled by this order in terms of T0
     pub fn quantity_t0(&self) -> u128 {
         0
@@ -616,6 +635,8 @@ impl GroupedVanillaOrder {
         }
     }
 
+    /// Whether the contract settles the order for less than its full quantity,
+    /// which only the partial variants allow
     pub fn is_partial(&self) -> bool {
         matches!(
             self,
@@ -623,6 +644,12 @@ impl GroupedVanillaOrder {
                 | Self::KillOrFill(FlashVariants::Partial(_))
         )
     }
+
+    /// Whether the order can settle filled for `quantity` without being filled
+    /// completely, which a partial order can from its minimum on
+    pub fn accepts_partial_fill(&self, quantity: u128) -> bool {
+        self.is_partial() && (self.min_q()..=self.max_q()).contains(&quantity)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -691,9 +718,9 @@ impl RawPoolOrder for TopOfBlockOrder {
         self.asset_out
     }
 
-    fn is_valid_signature(&self) -> bool {
+    fn is_valid_signature(&self, domain: &Eip712Domain) -> bool {
         let Ok(sig) = self.order_signature() else { return false };
-        let hash = self.no_meta_eip712_signing_hash(&ANGSTROM_DOMAIN);
+        let hash = self.no_meta_eip712_signing_hash(domain);
 
         sig.recover_address_from_prehash(&hash)
             .map(|addr| addr == self.meta.from)
@@ -725,12 +752,12 @@ impl RawPoolOrder for PartialStandingOrder {
         self.max_extra_fee_asset0
     }
 
-    fn is_valid_signature(&self) -> bool {
+    fn is_valid_signature(&self, domain: &Eip712Domain) -> bool {
         let s = self.meta.signature.to_vec();
         let mut slice = s.as_slice();
 
         let Ok(sig) = Signature::pade_decode(&mut slice, None) else { return false };
-        let hash = self.no_meta_eip712_signing_hash(&ANGSTROM_DOMAIN);
+        let hash = self.no_meta_eip712_signing_hash(domain);
 
         sig.recover_address_from_prehash(&hash)
             .map(|addr| addr == self.meta.from)
@@ -798,12 +825,12 @@ impl RawPoolOrder for ExactStandingOrder {
         self.max_extra_fee_asset0
     }
 
-    fn is_valid_signature(&self) -> bool {
+    fn is_valid_signature(&self, domain: &Eip712Domain) -> bool {
         let s = self.meta.signature.to_vec();
         let mut slice = s.as_slice();
 
         let Ok(sig) = Signature::pade_decode(&mut slice, None) else { return false };
-        let hash = self.no_meta_eip712_signing_hash(&ANGSTROM_DOMAIN);
+        let hash = self.no_meta_eip712_signing_hash(domain);
 
         sig.recover_address_from_prehash(&hash)
             .map(|addr| addr == self.meta.from)
@@ -871,12 +898,12 @@ impl RawPoolOrder for PartialFlashOrder {
         self.max_extra_fee_asset0
     }
 
-    fn is_valid_signature(&self) -> bool {
+    fn is_valid_signature(&self, domain: &Eip712Domain) -> bool {
         let s = self.meta.signature.to_vec();
         let mut slice = s.as_slice();
 
         let Ok(sig) = Signature::pade_decode(&mut slice, None) else { return false };
-        let hash = self.no_meta_eip712_signing_hash(&ANGSTROM_DOMAIN);
+        let hash = self.no_meta_eip712_signing_hash(domain);
 
         sig.recover_address_from_prehash(&hash)
             .map(|addr| addr == self.meta.from)
@@ -944,12 +971,12 @@ impl RawPoolOrder for ExactFlashOrder {
         self.max_extra_fee_asset0
     }
 
-    fn is_valid_signature(&self) -> bool {
+    fn is_valid_signature(&self, domain: &Eip712Domain) -> bool {
         let s = self.meta.signature.to_vec();
         let mut slice = s.as_slice();
 
         let Ok(sig) = Signature::pade_decode(&mut slice, None) else { return false };
-        let hash = self.no_meta_eip712_signing_hash(&ANGSTROM_DOMAIN);
+        let hash = self.no_meta_eip712_signing_hash(domain);
 
         sig.recover_address_from_prehash(&hash)
             .map(|addr| addr == self.meta.from)
@@ -1025,11 +1052,11 @@ impl RawPoolOrder for AllOrders {
         }
     }
 
-    fn is_valid_signature(&self) -> bool {
+    fn is_valid_signature(&self, domain: &Eip712Domain) -> bool {
         match self {
-            AllOrders::Standing(p) => p.is_valid_signature(),
-            AllOrders::Flash(kof) => kof.is_valid_signature(),
-            AllOrders::TOB(tob) => tob.is_valid_signature()
+            AllOrders::Standing(p) => p.is_valid_signature(domain),
+            AllOrders::Flash(kof) => kof.is_valid_signature(domain),
+            AllOrders::TOB(tob) => tob.is_valid_signature(domain)
         }
     }
 
@@ -1145,10 +1172,10 @@ impl RawPoolOrder for GroupedVanillaOrder {
         }
     }
 
-    fn is_valid_signature(&self) -> bool {
+    fn is_valid_signature(&self, domain: &Eip712Domain) -> bool {
         match self {
-            GroupedVanillaOrder::Standing(p) => p.is_valid_signature(),
-            GroupedVanillaOrder::KillOrFill(kof) => kof.is_valid_signature()
+            GroupedVanillaOrder::Standing(p) => p.is_valid_signature(domain),
+            GroupedVanillaOrder::KillOrFill(kof) => kof.is_valid_signature(domain)
         }
     }
 
@@ -1315,10 +1342,10 @@ impl RawPoolOrder for GroupedComposableOrder {
         }
     }
 
-    fn is_valid_signature(&self) -> bool {
+    fn is_valid_signature(&self, domain: &Eip712Domain) -> bool {
         match self {
-            GroupedComposableOrder::Partial(p) => p.is_valid_signature(),
-            GroupedComposableOrder::KillOrFill(kof) => kof.is_valid_signature()
+            GroupedComposableOrder::Partial(p) => p.is_valid_signature(domain),
+            GroupedComposableOrder::KillOrFill(kof) => kof.is_valid_signature(domain)
         }
     }
 
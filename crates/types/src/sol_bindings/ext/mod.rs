@@ -1,7 +1,10 @@
 //! extension functionality to sol types
 use std::fmt;
 
-use alloy::primitives::{Address, TxHash, U256};
+use alloy::{
+    primitives::{Address, TxHash, U256},
+    sol_types::Eip712Domain
+};
 use alloy_primitives::PrimitiveSignature;
 use serde::{Deserialize, Serialize};
 
@@ -46,7 +49,10 @@ pub trait RawPoolOrder: fmt::Debug + Send + Sync + Clone + Unpin + 'static {
         self.token_in() > self.token_out()
     }
 
-    fn is_valid_signature(&self) -> bool;
+    /// Whether the order is signed by its `from` address under `domain`. An
+    /// order signed for another chain or deployment recovers to some other
+    /// address and fails this check.
+    fn is_valid_signature(&self, domain: &Eip712Domain) -> bool;
 
     fn order_location(&self) -> OrderLocation;
 
@@ -3,12 +3,18 @@ use std::collections::HashMap;
 use alloy::sol_types::SolValue;
 use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
 
-use crate::contract_bindings::angstrom::Angstrom::PoolKey;
+use crate::{
+    contract_bindings::angstrom::Angstrom::PoolKey,
+    orders::SortStrategy,
+    primitive::{DeploymentConfig, DEFAULT_BLOCK_TIME_SECS}
+};
 
 #[derive(Debug, Clone)]
 pub struct InitialTestnetState {
     pub angstrom_addr:     Address,
     pub pool_manager_addr: Address,
+    /// the test helper that adds liquidity to the uniswap pools
+    pub pool_gate_addr:    Address,
     pub state:             Option<Bytes>,
     pub pool_keys:         Vec<PoolKey>
 }
@@ -17,10 +23,28 @@ impl InitialTestnetState {
     pub fn new(
         angstrom_addr: Address,
         pool_manager_addr: Address,
+        pool_gate_addr: Address,
         state: Option<Bytes>,
         pool_keys: Vec<PoolKey>
     ) -> Self {
-        Self { angstrom_addr, state, pool_manager_addr, pool_keys }
+        Self { angstrom_addr, state, pool_manager_addr, pool_gate_addr, pool_keys }
+    }
+
+    /// The deployment of the testnet contracts. Pools are set up directly in
+    /// the initial state, so there is no controller.
+    pub fn deployment(&self, chain_id: u64) -> DeploymentConfig {
+        DeploymentConfig {
+            chain_id,
+            angstrom_address: self.angstrom_addr,
+            controller_address: Address::ZERO,
+            pool_manager_address: self.pool_manager_addr,
+            book_sort: SortStrategy::ByPriceByVolume,
+            parallel_bundle_simulation: false,
+            transfer_taxes: vec![],
+            surplus_distribution: Default::default(),
+            block_time_secs: DEFAULT_BLOCK_TIME_SECS,
+            validators: vec![]
+        }
     }
 }
 
@@ -13,7 +13,8 @@ use {
         },
         contract_payloads::{
             angstrom::{
-                AngstromBundle, BundleGasDetails, OrderQuantities, TopOfBlockOrder, UserOrder
+                AngstromBundle, BundleFees, BundleGasDetails, OrderQuantities, TopOfBlockOrder,
+                UserOrder
             },
             rewards::PoolUpdate,
             Asset, Pair, Signature
@@ -502,7 +503,8 @@ async fn similar_to_prev() {
             )]),
             16415544926496907170
         ),
-        &pools
+        &pools,
+        &BundleFees::default()
     )
     .unwrap();
     println!("Bundle: {:?}", bundle);
@@ -60,6 +60,8 @@ pub struct EnhancedUniswapPool<Loader: PoolDataLoader<A> = DataLoader<Address>,
     pub tick_spacing:       i32,
     pub tick_bitmap:        HashMap<i16, U256>,
     pub ticks:              HashMap<i32, TickInfo>,
+    /// last block the pool's state was synced to, `None` while it is unknown
+    pub synced_block:       Option<BlockNumber>,
     pub _phantom:           PhantomData<A>
 }
 
@@ -123,7 +125,12 @@ where
             })
             .collect::<Vec<_>>();
 
-        Ok((self.token0, self.token1, PoolSnapshot::new(liq_ranges, self.sqrt_price.into())?))
+        let mut snapshot = PoolSnapshot::new(liq_ranges, self.sqrt_price.into())?;
+        if let Some(block) = self.synced_block {
+            snapshot = snapshot.with_block(block);
+        }
+
+        Ok((self.token0, self.token1, snapshot))
     }
 
     pub async fn initialize(
@@ -136,6 +143,7 @@ where
         tracing::trace!(?block_number, "populated pool data");
         self.sync_ticks(block_number, provider.clone()).await?;
         tracing::trace!(?block_number, "synced pool ticks");
+        self.synced_block = block_number;
         Ok(())
     }
 
@@ -0,0 +1,82 @@
+//! The uniswap pools as they were at past blocks, for looking back at the
+//! state the network matched against.
+
+use std::sync::Arc;
+
+use alloy::{
+    primitives::{Address, BlockNumber},
+    providers::Provider
+};
+use angstrom_types::{matching::uniswap::PoolSnapshot, primitive::PoolId};
+use thiserror::Error;
+use tokio::sync::{
+    mpsc::{UnboundedReceiver, UnboundedSender},
+    oneshot
+};
+
+use super::pool_manager::SyncedUniswapPools;
+
+/// A pool along with its tokens, as it was at a block.
+pub type HistoricalPool = (Address, Address, PoolSnapshot);
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PoolHistoryError {
+    #[error("pool {0} isn't tracked")]
+    UnknownPool(PoolId),
+    #[error("failed to load the pool at block {block} - {reason}")]
+    Load { block: BlockNumber, reason: String },
+    #[error("the pools aren't loaded yet")]
+    Unavailable
+}
+
+/// Asks for a pool as it was at a block.
+#[derive(Debug)]
+pub struct PoolHistoryRequest {
+    pub pool_id: PoolId,
+    pub block:   BlockNumber,
+    pub tx:      oneshot::Sender<Result<HistoricalPool, PoolHistoryError>>
+}
+
+/// Requests pools as they were at past blocks from [`serve_pool_history`]. It
+/// can be handed out before the pools are loaded.
+#[derive(Debug, Clone)]
+pub struct PoolHistoryHandle {
+    tx: UnboundedSender<PoolHistoryRequest>
+}
+
+impl PoolHistoryHandle {
+    pub fn new(tx: UnboundedSender<PoolHistoryRequest>) -> Self {
+        Self { tx }
+    }
+
+    pub async fn pool_snapshot(
+        &self,
+        pool_id: PoolId,
+        block: BlockNumber
+    ) -> Result<HistoricalPool, PoolHistoryError> {
+        let (tx, rx) = oneshot::channel();
+        self.tx
+            .send(PoolHistoryRequest { pool_id, block, tx })
+            .map_err(|_| PoolHistoryError::Unavailable)?;
+
+        rx.await.map_err(|_| PoolHistoryError::Unavailable)?
+    }
+}
+
+/// Answers the requests by loading the pool again at the requested block.
+/// `provider` has to serve the state of past blocks.
+pub async fn serve_pool_history(
+    pools: SyncedUniswapPools,
+    provider: Arc<impl Provider>,
+    mut requests: UnboundedReceiver<PoolHistoryRequest>
+) {
+    while let Some(PoolHistoryRequest { pool_id, block, tx }) = requests.recv().await {
+        let snapshot = pools
+            .pool_snapshot_at(pool_id, block, provider.clone())
+            .await
+            .map_err(|e| PoolHistoryError::Load { block, reason: e.to_string() })
+            .and_then(|pool| pool.ok_or(PoolHistoryError::UnknownPool(pool_id)));
+
+        let _ = tx.send(snapshot);
+    }
+}
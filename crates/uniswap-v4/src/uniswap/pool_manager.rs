@@ -9,7 +9,8 @@ use std::{
 };
 
 use alloy::{
-    primitives::{Address, BlockNumber},
+    primitives::{Address, BlockNumber, U256},
+    providers::Provider,
     rpc::types::{eth::Filter, Block},
     transports::{RpcError, TransportErrorKind}
 };
@@ -21,13 +22,17 @@ use angstrom_types::{
     primitive::PoolId,
     sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder}
 };
+use angstrom_utils::memory_budget::MemoryConsumer;
 use arraydeque::ArrayDeque;
 use futures::FutureExt;
 use futures_util::{stream::BoxStream, StreamExt};
 use thiserror::Error;
 use tokio::sync::Notify;
 
-use super::{pool::PoolError, pool_providers::PoolMangerBlocks};
+use super::{
+    pool::{PoolError, TickInfo},
+    pool_providers::PoolMangerBlocks
+};
 use crate::uniswap::{
     pool::EnhancedUniswapPool,
     pool_data_loader::{DataLoader, PoolDataLoader},
@@ -88,6 +93,52 @@ where
         Self { pools, tx }
     }
 
+    /// The pool as it was at `block`, loaded again from the chain. Returns
+    /// `None` for a pool we don't track. The provider has to serve the state of
+    /// past blocks.
+    pub async fn pool_snapshot_at(
+        &self,
+        pool_id: A,
+        block: BlockNumber,
+        provider: Arc<impl Provider>
+    ) -> Result<Option<(Address, Address, PoolSnapshot)>, PoolError> {
+        let Some(pool) = self.pools.get(&pool_id) else { return Ok(None) };
+        let mut historical = {
+            let pool = pool.read().unwrap();
+            EnhancedUniswapPool::new(pool.data_loader(), pool.initial_ticks_per_side())
+        };
+        historical.initialize(Some(block), provider).await?;
+
+        historical.fetch_pool_snapshot().map(Some)
+    }
+
+    /// Loads every pool again from the chain at `block`, e.g. once the blocks
+    /// missed while the node was down are known. Pools that were already
+    /// synced past `block` are left as they are.
+    pub async fn reload_at(
+        &self,
+        block: BlockNumber,
+        provider: Arc<impl Provider>
+    ) -> Result<(), PoolError> {
+        for pool in self.pools.values() {
+            let mut reloaded = {
+                let pool = pool.read().unwrap();
+                let mut reloaded =
+                    EnhancedUniswapPool::new(pool.data_loader(), pool.initial_ticks_per_side());
+                reloaded.set_sim_swap_sync(pool.is_sync_swap_with_sim());
+                reloaded
+            };
+            reloaded.initialize(Some(block), provider.clone()).await?;
+
+            let mut pool = pool.write().unwrap();
+            if !matches!(pool.synced_block, Some(synced) if synced > block) {
+                *pool = reloaded;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Will calculate the tob rewards that this order specifies. More Notably,
     /// this function is async and will make sure that we always have the
     /// needed ticks loaded in order to ensure we can always properly
@@ -150,6 +201,25 @@ where
     }
 }
 
+/// Only reported to the memory budget, the ticks can't be shed: the tick
+/// bitmap says they are loaded and swaps over them would go wrong.
+impl<A, Loader> MemoryConsumer for SyncedUniswapPools<A, Loader>
+where
+    Loader: PoolDataLoader<A> + Send + Sync,
+    A: Send + Sync
+{
+    fn memory_usage(&self) -> usize {
+        self.pools
+            .values()
+            .map(|pool| {
+                let pool = pool.read().unwrap();
+                pool.ticks.len() * std::mem::size_of::<(i32, TickInfo)>()
+                    + pool.tick_bitmap.len() * std::mem::size_of::<(i16, U256)>()
+            })
+            .sum()
+    }
+}
+
 pub struct UniswapPoolManager<P, BlockSync, Loader: PoolDataLoader<A>, A = Address>
 where
     A: Debug + Copy
@@ -395,6 +465,10 @@ where
         }
 
         self.latest_synced_block = chain_head_block_number;
+        // pools without logs are just as current as the ones that had some
+        for pool in self.pools.values() {
+            pool.write().unwrap().synced_block = Some(chain_head_block_number);
+        }
 
         if is_reorg {
             self.block_sync
@@ -1,4 +1,5 @@
 pub mod macros;
+pub mod memory_budget;
 pub mod poll_ext;
 pub mod sync_pipeline;
 
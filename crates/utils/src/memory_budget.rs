@@ -0,0 +1,237 @@
+//! Memory budget shared by the subsystems that grow with load.
+//!
+//! The order pool, the validation caches and the uniswap tick maps register
+//! with a [`MemoryBudget`], each with an optional ceiling of its own, under a
+//! ceiling for all of them. When a consumer goes over its ceiling, or all of
+//! them over the total, the budget asks them to shed load, the largest first.
+//! A cache sheds by dropping entries, the order pool by refusing new orders
+//! until it is back under budget. The node then gets slower or takes fewer
+//! orders under adversarial load, instead of running out of memory.
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration
+};
+
+use serde::{Deserialize, Serialize};
+
+/// How often [`MemoryBudget::run`] checks the consumers.
+pub const MEMORY_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A consumer that shed load is restored once it is back under this share of
+/// its ceiling and of the total, so that it doesn't flap around the limit.
+const RESTORE_PCT: usize = 90;
+
+/// A subsystem whose memory grows with load.
+pub trait MemoryConsumer: Send + Sync {
+    /// Bytes held, an estimate is good enough.
+    fn memory_usage(&self) -> usize;
+
+    /// Asked to give back `excess` bytes, returns the bytes freed. Consumers
+    /// that can only stop growing, or can't shed at all, free nothing.
+    fn shed(&self, _excess: usize) -> usize {
+        0
+    }
+
+    /// Called once the consumer is back under budget after shedding.
+    fn restore(&self) {}
+}
+
+/// What a consumer uses, as of the last check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryUsage {
+    pub name:     String,
+    pub bytes:    usize,
+    pub ceiling:  Option<usize>,
+    /// the consumer was asked to shed load and wasn't restored since
+    pub shedding: bool
+}
+
+struct Registered {
+    name:     &'static str,
+    consumer: Arc<dyn MemoryConsumer>,
+    ceiling:  Option<usize>,
+    bytes:    usize,
+    shedding: bool
+}
+
+#[derive(Default)]
+struct BudgetState {
+    total:     Option<usize>,
+    consumers: Vec<Registered>
+}
+
+/// Keeps the registered consumers under their ceilings, see the
+/// [module docs](self).
+#[derive(Clone, Default)]
+pub struct MemoryBudget {
+    state: Arc<Mutex<BudgetState>>
+}
+
+impl MemoryBudget {
+    /// A budget of `total` bytes for all consumers, unlimited if `None`.
+    pub fn new(total: Option<usize>) -> Self {
+        Self { state: Arc::new(Mutex::new(BudgetState { total, consumers: vec![] })) }
+    }
+
+    /// Registers `consumer`, which may use up to `ceiling` bytes of the
+    /// budget on its own.
+    pub fn register(
+        &self,
+        name: &'static str,
+        consumer: Arc<dyn MemoryConsumer>,
+        ceiling: Option<usize>
+    ) {
+        self.state
+            .lock()
+            .expect("poisoned")
+            .consumers
+            .push(Registered { name, consumer, ceiling, bytes: 0, shedding: false });
+    }
+
+    /// The usage of every consumer as of the last [`Self::enforce`].
+    pub fn usage(&self) -> Vec<MemoryUsage> {
+        self.state
+            .lock()
+            .expect("poisoned")
+            .consumers
+            .iter()
+            .map(|c| MemoryUsage {
+                name:     c.name.to_string(),
+                bytes:    c.bytes,
+                ceiling:  c.ceiling,
+                shedding: c.shedding
+            })
+            .collect()
+    }
+
+    /// Measures the consumers, asks the ones over their ceiling to shed the
+    /// difference, and if all of them are over the total, asks the largest to
+    /// shed until they are under it. Consumers back under budget are restored.
+    pub fn enforce(&self) {
+        let mut state = self.state.lock().expect("poisoned");
+
+        for c in &mut state.consumers {
+            c.bytes = c.consumer.memory_usage();
+            if let Some(ceiling) = c.ceiling.filter(|ceiling| c.bytes > *ceiling) {
+                tracing::warn!(consumer = c.name, bytes = c.bytes, ceiling, "over memory ceiling");
+                c.bytes -= c.consumer.shed(c.bytes - ceiling).min(c.bytes);
+                c.shedding = true;
+            }
+        }
+
+        let used = state.consumers.iter().map(|c| c.bytes).sum::<usize>();
+        if let Some(total) = state.total.filter(|total| used > *total) {
+            tracing::warn!(used, total, "over memory budget");
+            let mut excess = used - total;
+            state.consumers.sort_by_key(|c| std::cmp::Reverse(c.bytes));
+            for c in state.consumers.iter_mut().filter(|c| c.bytes != 0) {
+                let freed = c.consumer.shed(excess).min(c.bytes);
+                c.bytes -= freed;
+                c.shedding = true;
+                excess = excess.saturating_sub(freed);
+                if excess == 0 {
+                    break
+                }
+            }
+        }
+
+        let used = state.consumers.iter().map(|c| c.bytes).sum::<usize>();
+        let total_ok = state
+            .total
+            .map_or(true, |total| used <= restore_level(total));
+        for c in state
+            .consumers
+            .iter_mut()
+            .filter(|c| c.shedding && total_ok)
+        {
+            if c.ceiling
+                .map_or(true, |ceiling| c.bytes <= restore_level(ceiling))
+            {
+                tracing::info!(consumer = c.name, bytes = c.bytes, "back under memory budget");
+                c.consumer.restore();
+                c.shedding = false;
+            }
+        }
+    }
+
+    /// Enforces the budget every `interval`, forever.
+    pub async fn run(self, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.enforce();
+        }
+    }
+}
+
+fn restore_level(limit: usize) -> usize {
+    limit / 100 * RESTORE_PCT
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Cache {
+        bytes:    AtomicUsize,
+        /// whether it can free memory or only stop growing
+        can_free: bool,
+        paused:   AtomicBool
+    }
+
+    impl MemoryConsumer for Cache {
+        fn memory_usage(&self) -> usize {
+            self.bytes.load(Ordering::Relaxed)
+        }
+
+        fn shed(&self, excess: usize) -> usize {
+            self.paused.store(true, Ordering::Relaxed);
+            if !self.can_free {
+                return 0
+            }
+            let freed = excess.min(self.memory_usage());
+            self.bytes.fetch_sub(freed, Ordering::Relaxed);
+            freed
+        }
+
+        fn restore(&self) {
+            self.paused.store(false, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn largest_consumers_shed_first() {
+        let budget = MemoryBudget::new(Some(1_000));
+        let pool = Arc::new(Cache { bytes: AtomicUsize::new(700), ..Default::default() });
+        let cache =
+            Arc::new(Cache { bytes: AtomicUsize::new(500), can_free: true, ..Default::default() });
+        let capped =
+            Arc::new(Cache { bytes: AtomicUsize::new(150), can_free: true, ..Default::default() });
+        budget.register("pool", pool.clone(), None);
+        budget.register("cache", cache.clone(), None);
+        budget.register("capped", capped.clone(), Some(100));
+
+        budget.enforce();
+        // the capped cache is brought under its own ceiling first
+        assert_eq!(capped.memory_usage(), 100);
+        // the pool can't free anything, so the cache frees what is left over
+        assert!(pool.paused.load(Ordering::Relaxed));
+        assert_eq!(cache.memory_usage(), 200);
+        assert!(budget.usage().iter().all(|usage| usage.shedding));
+
+        // the pool is only restored well under the budget
+        budget.enforce();
+        assert!(pool.paused.load(Ordering::Relaxed));
+        pool.bytes.store(500, Ordering::Relaxed);
+        capped.bytes.store(50, Ordering::Relaxed);
+        budget.enforce();
+        assert!(!pool.paused.load(Ordering::Relaxed));
+        assert!(budget.usage().iter().all(|usage| !usage.shedding));
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex}
+};
+
+use alloy::primitives::{BlockNumber, B256};
+use angstrom_types::contract_payloads::angstrom::BundleGasDetails;
+use angstrom_utils::memory_budget::MemoryConsumer;
+
+/// Results of the bundles simulated on top of the current block, keyed by the
+/// hash of their encoding.
+///
+/// The same bundle is simulated more than once a round: when the leader builds
+/// its proposal, and when every node rebuilds it during finalization to check
+/// the proposal it received. The result only depends on the bundle and the
+/// state it runs on, so the repeats are answered from here.
+#[derive(Debug, Default, Clone)]
+pub struct SimulationCache {
+    results: Arc<Mutex<HashMap<(B256, BlockNumber), BundleGasDetails>>>
+}
+
+impl SimulationCache {
+    pub fn get(&self, bundle_hash: B256, block: BlockNumber) -> Option<BundleGasDetails> {
+        self.results
+            .lock()
+            .expect("poisoned")
+            .get(&(bundle_hash, block))
+            .cloned()
+    }
+
+    /// Results for older blocks are dropped, their state is gone.
+    pub fn insert(&self, bundle_hash: B256, block: BlockNumber, gas_details: BundleGasDetails) {
+        let mut results = self.results.lock().expect("poisoned");
+        results.retain(|(_, cached_block), _| *cached_block >= block);
+        results.insert((bundle_hash, block), gas_details);
+    }
+}
+
+impl MemoryConsumer for SimulationCache {
+    fn memory_usage(&self) -> usize {
+        self.results.lock().expect("poisoned").len()
+            * std::mem::size_of::<((B256, BlockNumber), BundleGasDetails)>()
+    }
+
+    /// The repeats are simulated again instead.
+    fn shed(&self, _: usize) -> usize {
+        let freed = self.memory_usage();
+        self.results.lock().expect("poisoned").clear();
+        freed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_are_kept_for_the_current_block() {
+        let cache = SimulationCache::default();
+        let bundle = B256::random();
+
+        cache.insert(bundle, 10, BundleGasDetails::default());
+        assert!(cache.get(bundle, 10).is_some());
+        assert!(cache.get(bundle, 11).is_none());
+        assert!(cache.get(B256::random(), 10).is_none());
+
+        cache.insert(B256::random(), 11, BundleGasDetails::default());
+        assert!(cache.get(bundle, 10).is_none());
+    }
+}
@@ -1,14 +1,13 @@
 use std::{fmt::Debug, pin::Pin, sync::Arc};
 
 use alloy::{
-    primitives::{Address, U256},
+    primitives::{keccak256, Address},
     sol_types::SolCall
 };
-use angstrom_metrics::validation::ValidationMetrics;
-use angstrom_types::contract_payloads::angstrom::{AngstromBundle, BundleGasDetails};
+use angstrom_metrics::{track_allocations, validation::ValidationMetrics};
+use angstrom_types::contract_payloads::angstrom::{AngstromBundle, BundleGasDetails, UserBalances};
 use eyre::eyre;
 use futures::Future;
-use pade::PadeEncode;
 use revm::{
     inspector_handle_register,
     primitives::{EnvWithHandlerCfg, TxKind}
@@ -16,11 +15,18 @@ use revm::{
 use tokio::runtime::Handle;
 
 use crate::{
-    common::{key_split_threadpool::KeySplitThreadpool, TokenPriceGenerator},
+    common::{
+        key_split_threadpool::KeySplitThreadpool, NextBlockEnv, SettlementCostEstimator,
+        TokenPriceGenerator
+    },
     order::sim::console_log::CallDataInspector
 };
 
+mod cache;
+mod replay;
 pub mod validator;
+pub use cache::*;
+pub use replay::*;
 pub use validator::*;
 
 pub struct BundleValidator<DB> {
@@ -28,7 +34,10 @@ pub struct BundleValidator<DB> {
     angstrom_address: Address,
     /// the address associated with this node.
     /// this will ensure the  node has access and the simulation can pass
-    node_address:     Address
+    node_address:     Address,
+    sim_cache:        SimulationCache,
+    block_env:        NextBlockEnv,
+    settlement_costs: SettlementCostEstimator
 }
 
 impl<DB> BundleValidator<DB>
@@ -37,13 +46,41 @@ where
     <DB as revm::DatabaseRef>::Error: Send + Sync + Debug
 {
     pub fn new(db: Arc<DB>, angstrom_address: Address, node_address: Address) -> Self {
-        Self { db, angstrom_address, node_address }
+        Self {
+            db,
+            angstrom_address,
+            node_address,
+            sim_cache: SimulationCache::default(),
+            block_env: NextBlockEnv::default(),
+            settlement_costs: SettlementCostEstimator::default()
+        }
+    }
+
+    /// Shares the cache of simulation results, e.g to account for it in the
+    /// memory budget.
+    pub fn with_sim_cache(mut self, sim_cache: SimulationCache) -> Self {
+        self.sim_cache = sim_cache;
+        self
+    }
+
+    /// Simulates in the same next block environment as order validation.
+    pub fn with_block_env(mut self, block_env: NextBlockEnv) -> Self {
+        self.block_env = block_env;
+        self
+    }
+
+    /// Records the gas of the bundles that simulate successfully, to forecast
+    /// what the next settlement costs.
+    pub fn with_settlement_costs(mut self, settlement_costs: SettlementCostEstimator) -> Self {
+        self.settlement_costs = settlement_costs;
+        self
     }
 
     pub fn simulate_bundle(
         &self,
         sender: tokio::sync::oneshot::Sender<eyre::Result<BundleGasDetails>>,
         bundle: AngstromBundle,
+        user_balances: UserBalances,
         price_gen: &TokenPriceGenerator,
         thread_pool: &mut KeySplitThreadpool<
             Address,
@@ -56,12 +93,27 @@ where
         let node_address = self.node_address;
         let angstrom_address = self.angstrom_address;
         let db = self.db.clone();
+        let sim_cache = self.sim_cache.clone();
+        let block_env = self.block_env.for_block_after(number);
+        let settlement_costs = self.settlement_costs.clone();
 
         let conversion_lookup = price_gen.generate_lookup_map();
 
         thread_pool.spawn_raw(Box::pin(async move {
             metrics.simulate_bundle(|| {
-                let bundle = bundle.pade_encode();
+                if let Err(e) = bundle.verify_rewards() {
+                    let _ = sender.send(Err(eyre!("invalid bundle rewards - {e}")));
+                    return
+                }
+
+                let bundle =
+                    track_allocations("bundle_encoding", || bundle.pade_encode_for_submission());
+                let bundle_hash = keccak256(&bundle);
+                if let Some(res) = sim_cache.get(bundle_hash, number) {
+                    tracing::debug!(?bundle_hash, "bundle was already simulated");
+                    let _ = sender.send(Ok(res.with_user_balances(user_balances)));
+                    return
+                }
 
                 let mut console_log_inspector = CallDataInspector {};
 
@@ -73,10 +125,9 @@ where
                     .modify_env(|env| {
                         env.cfg.disable_balance_check = true;
                     })
-                    .modify_block_env(|env| {
-                        env.number = U256::from(number + 1);
-                    })
+                    .modify_block_env(|env| *env = block_env.clone())
                     .modify_tx_env(|tx| {
+                        tx.gas_price = block_env.basefee;
                         tx.caller = node_address;
                         tx.transact_to = TxKind::Call(angstrom_address);
                         tx.data =
@@ -108,8 +159,10 @@ where
                     return
                 }
 
+                settlement_costs.record(number, result.result.gas_used());
                 let res = BundleGasDetails::new(conversion_lookup, result.result.gas_used());
-                let _ = sender.send(Ok(res));
+                sim_cache.insert(bundle_hash, number, res.clone());
+                let _ = sender.send(Ok(res.with_user_balances(user_balances)));
             });
         }))
     }
@@ -0,0 +1,66 @@
+use alloy::{
+    consensus::Header,
+    primitives::{Address, Bytes, U256}
+};
+use angstrom_types::contract_payloads::angstrom::BundleSimulation;
+use eyre::eyre;
+use reth_provider::StateProviderBox;
+use reth_revm::database::StateProviderDatabase;
+use revm::primitives::{BlockEnv, TxKind};
+
+/// A bundle transaction as it was included in a block.
+#[derive(Debug, Clone)]
+pub struct SettledBundleTx {
+    pub sender:           Address,
+    pub angstrom_address: Address,
+    pub gas_limit:        u64,
+    pub input:            Bytes
+}
+
+/// Runs a settled bundle transaction again on `parent_state`, the state of the
+/// block before `header`, in the environment of the block it was settled in.
+///
+/// The transactions of the block that came before the bundle aren't replayed,
+/// so a bundle that didn't land at the top of its block can come out
+/// differently than it did on chain.
+pub fn replay_bundle_tx(
+    parent_state: StateProviderBox,
+    header: &Header,
+    tx: &SettledBundleTx
+) -> eyre::Result<BundleSimulation> {
+    let basefee = U256::from(header.base_fee_per_gas.unwrap_or_default());
+    let block_env = BlockEnv {
+        number: U256::from(header.number),
+        coinbase: header.beneficiary,
+        timestamp: U256::from(header.timestamp),
+        gas_limit: U256::from(header.gas_limit),
+        basefee,
+        difficulty: header.difficulty,
+        prevrandao: Some(header.mix_hash),
+        ..Default::default()
+    };
+
+    let mut evm = revm::Evm::builder()
+        .with_ref_db(StateProviderDatabase::new(parent_state))
+        .modify_env(|env| {
+            env.cfg.disable_balance_check = true;
+        })
+        .modify_block_env(|env| *env = block_env)
+        .modify_tx_env(|env| {
+            env.gas_price = basefee;
+            env.gas_limit = tx.gas_limit;
+            env.caller = tx.sender;
+            env.transact_to = TxKind::Call(tx.angstrom_address);
+            env.data = tx.input.clone();
+        })
+        .build();
+
+    let result = evm
+        .transact()
+        .map_err(|e| eyre!("failed to transact with revm - {e:?}"))?;
+
+    Ok(BundleSimulation {
+        success:  result.result.is_success(),
+        gas_used: result.result.gas_used()
+    })
+}
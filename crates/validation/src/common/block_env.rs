@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use alloy::{
+    consensus::Header,
+    eips::{
+        eip1559::BaseFeeParams,
+        eip4844::{calc_blob_gasprice, calc_excess_blob_gas}
+    },
+    primitives::U256
+};
+use parking_lot::RwLock;
+use revm::primitives::{BlobExcessGasAndPrice, BlockEnv};
+
+/// Seconds between two slots on mainnet.
+pub const SLOT_TIME: u64 = 12;
+
+/// Builds the environment of the block on top of `parent`, as close as it can
+/// be known before that block is built. The timestamp assumes the next slot
+/// isn't missed and the randomness, which isn't known yet, is carried over
+/// from the parent.
+pub fn next_block_env(parent: &Header) -> BlockEnv {
+    let basefee = parent
+        .next_block_base_fee(BaseFeeParams::ethereum())
+        .unwrap_or_default();
+
+    let blob_excess_gas_and_price =
+        parent
+            .excess_blob_gas
+            .zip(parent.blob_gas_used)
+            .map(|(excess_blob_gas, blob_gas_used)| {
+                let excess_blob_gas = calc_excess_blob_gas(excess_blob_gas, blob_gas_used);
+                BlobExcessGasAndPrice {
+                    excess_blob_gas,
+                    blob_gasprice: calc_blob_gasprice(excess_blob_gas)
+                }
+            });
+
+    BlockEnv {
+        number: U256::from(parent.number + 1),
+        timestamp: U256::from(parent.timestamp + SLOT_TIME),
+        gas_limit: U256::from(parent.gas_limit),
+        basefee: U256::from(basefee),
+        prevrandao: Some(parent.mix_hash),
+        blob_excess_gas_and_price,
+        ..Default::default()
+    }
+}
+
+/// The environment the next block is simulated in. It is shared by order and
+/// bundle simulation, so both always agree on it.
+#[derive(Debug, Clone, Default)]
+pub struct NextBlockEnv(Arc<RwLock<Option<BlockEnv>>>);
+
+impl NextBlockEnv {
+    pub fn on_new_block(&self, parent: &Header) {
+        *self.0.write() = Some(next_block_env(parent));
+    }
+
+    /// The environment of the block after `block`. If the header of `block`
+    /// hasn't been seen yet, only the number is set.
+    pub fn for_block_after(&self, block: u64) -> BlockEnv {
+        let number = U256::from(block + 1);
+
+        self.0
+            .read()
+            .as_ref()
+            .filter(|env| env.number == number)
+            .cloned()
+            .unwrap_or_else(|| BlockEnv { number, ..Default::default() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parent() -> Header {
+        Header {
+            number: 100,
+            timestamp: 1_000,
+            gas_limit: 30_000_000,
+            gas_used: 30_000_000,
+            base_fee_per_gas: Some(1_000_000_000),
+            excess_blob_gas: Some(0),
+            blob_gas_used: Some(0),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn builds_next_block_from_parent() {
+        let env = next_block_env(&parent());
+
+        assert_eq!(env.number, U256::from(101));
+        assert_eq!(env.timestamp, U256::from(1_012));
+        // a full block raises the base fee by an eighth
+        assert_eq!(env.basefee, U256::from(1_125_000_000));
+        assert_eq!(env.blob_excess_gas_and_price.unwrap().blob_gasprice, 1);
+    }
+
+    #[test]
+    fn falls_back_to_number_for_unseen_blocks() {
+        let next = NextBlockEnv::default();
+        next.on_new_block(&parent());
+
+        assert_eq!(next.for_block_after(100).basefee, U256::from(1_125_000_000));
+
+        let unseen = next.for_block_after(101);
+        assert_eq!(unseen.number, U256::from(102));
+        assert_eq!(unseen.basefee, U256::ZERO);
+    }
+}
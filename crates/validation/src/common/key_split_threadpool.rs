@@ -78,6 +78,15 @@ where
         self.waker.as_ref().inspect(|i| i.wake_by_ref());
     }
 
+    /// Number of tasks of `key` that are queued or running.
+    pub fn pending_for(&self, key: &K) -> usize {
+        // every task holds a handle to the semaphore of its key
+        self.pending
+            .get(key)
+            .map(|permit| Arc::strong_count(permit) - 1)
+            .unwrap_or_default()
+    }
+
     /// registers waker if its doesn't exist
     pub fn try_register_waker(&mut self, f: impl FnOnce() -> Waker) {
         if self.waker.is_none() {
@@ -9,9 +9,24 @@ use tokio::runtime::Handle;
 pub mod key_split_threadpool;
 use key_split_threadpool::KeySplitThreadpool;
 
+pub mod block_env;
+pub use block_env::*;
+
 pub mod db;
 pub use db::*;
 
+pub mod prefetch;
+pub use prefetch::*;
+
+pub mod remote;
+pub use remote::*;
+
+pub mod settlement_cost;
+pub use settlement_cost::*;
+
+pub mod token_metadata;
+pub use token_metadata::*;
+
 pub mod token_pricing;
 pub use token_pricing::*;
 
@@ -0,0 +1,321 @@
+use std::{
+    collections::HashSet,
+    hash::Hash,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc
+    }
+};
+
+use alloy::primitives::{Address, BlockNumber, B256, U256};
+use angstrom_utils::memory_budget::MemoryConsumer;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use reth_chainspec::ChainInfo;
+use reth_provider::{BlockHashReader, BlockNumReader, ProviderResult};
+use revm::{
+    primitives::{AccountInfo, Bytecode},
+    DatabaseRef
+};
+
+/// Most storage slots carried over from one block to the next.
+const MAX_PREFETCHED_SLOTS: usize = 100_000;
+
+/// Warms up the state simulations are going to read at the start of a block.
+pub trait StatePrefetch: Send + Sync {
+    /// Drops the state cached for the block before. Called as soon as the
+    /// canonical state changes, before anything reads the state of the new
+    /// block.
+    fn invalidate(&self);
+
+    /// Loads the state dropped by the last [`StatePrefetch::invalidate`]
+    /// again, along with `accounts`, the users and tokens of the orders
+    /// still pending. Blocks on the database.
+    fn prefetch_for_new_block(&self, accounts: HashSet<Address>);
+}
+
+/// A read through cache in front of the node's database, shared by every order
+/// and bundle simulation of the current block.
+///
+/// Most of what a block's simulations read is the same as in the block before:
+/// the angstrom and pool manager contracts, the pools and the tokens and users
+/// with pending orders. The cache is dropped as soon as the canonical state
+/// changes and that state is then loaded again in the background, so the
+/// per-order revm runs read it from memory instead of each going to the
+/// database for it cold.
+#[derive(Clone)]
+pub struct PrefetchDb<DB> {
+    db:          DB,
+    accounts:    Arc<DashMap<Address, Option<AccountInfo>>>,
+    storage:     Arc<DashMap<(Address, U256), U256>>,
+    /// code is keyed by its hash, so it stays valid across blocks
+    code:        Arc<DashMap<B256, Bytecode>>,
+    /// bumped on every invalidation, a read that started before one isn't
+    /// cached
+    generation:  Arc<AtomicU64>,
+    /// what was cached when the state was last invalidated, to be loaded again
+    invalidated: Arc<Mutex<(HashSet<Address>, HashSet<(Address, U256)>)>>
+}
+
+impl<DB> PrefetchDb<DB> {
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            accounts: Arc::default(),
+            storage: Arc::default(),
+            code: Arc::default(),
+            generation: Arc::default(),
+            invalidated: Arc::default()
+        }
+    }
+
+    /// Caches a value read from the database at `generation`, unless the
+    /// state was invalidated while it was read.
+    fn cache<K: Hash + Eq + Copy, V>(
+        &self,
+        map: &DashMap<K, V>,
+        key: K,
+        value: V,
+        generation: u64
+    ) {
+        map.insert(key, value);
+        if self.generation.load(Ordering::SeqCst) != generation {
+            map.remove(&key);
+        }
+    }
+}
+
+impl<DB> StatePrefetch for PrefetchDb<DB>
+where
+    DB: DatabaseRef + Send + Sync
+{
+    fn invalidate(&self) {
+        let mut invalidated = self.invalidated.lock();
+        self.generation.fetch_add(1, Ordering::SeqCst);
+
+        invalidated
+            .0
+            .extend(self.accounts.iter().map(|entry| *entry.key()));
+        let room = MAX_PREFETCHED_SLOTS.saturating_sub(invalidated.1.len());
+        invalidated
+            .1
+            .extend(self.storage.iter().take(room).map(|entry| *entry.key()));
+
+        self.accounts.clear();
+        self.storage.clear();
+    }
+
+    fn prefetch_for_new_block(&self, mut accounts: HashSet<Address>) {
+        let (invalidated_accounts, slots) = std::mem::take(&mut *self.invalidated.lock());
+        accounts.extend(invalidated_accounts);
+
+        // failed reads are left for the simulation that needs them to report
+        for address in accounts {
+            let _ = self.basic_ref(address);
+        }
+        for (address, slot) in slots {
+            let _ = self.storage_ref(address, slot);
+        }
+    }
+}
+
+impl<DB> PrefetchDb<DB> {
+    fn accounts_usage(&self) -> usize {
+        self.accounts.len() * std::mem::size_of::<(Address, Option<AccountInfo>)>()
+    }
+
+    fn storage_usage(&self) -> usize {
+        self.storage.len() * std::mem::size_of::<((Address, U256), U256)>()
+    }
+
+    fn code_usage(&self) -> usize {
+        self.code.iter().map(|code| code.len()).sum()
+    }
+}
+
+/// Everything in the cache can be read again from the database, so it is
+/// dropped, the storage slots first as they are the most and cheapest to read
+/// again, and the code last.
+impl<DB: Send + Sync> MemoryConsumer for PrefetchDb<DB> {
+    fn memory_usage(&self) -> usize {
+        self.accounts_usage() + self.storage_usage() + self.code_usage()
+    }
+
+    fn shed(&self, excess: usize) -> usize {
+        let mut freed = self.storage_usage();
+        self.storage.clear();
+        if freed < excess {
+            freed += self.accounts_usage();
+            self.accounts.clear();
+        }
+        if freed < excess {
+            freed += self.code_usage();
+            self.code.clear();
+        }
+
+        freed
+    }
+}
+
+impl<DB: DatabaseRef> DatabaseRef for PrefetchDb<DB> {
+    type Error = DB::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        if let Some(account) = self.accounts.get(&address) {
+            return Ok(account.clone())
+        }
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let account = self.db.basic_ref(address)?;
+        self.cache(&self.accounts, address, account.clone(), generation);
+        Ok(account)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if let Some(code) = self.code.get(&code_hash) {
+            return Ok(code.clone())
+        }
+
+        let code = self.db.code_by_hash_ref(code_hash)?;
+        self.code.insert(code_hash, code.clone());
+        Ok(code)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        if let Some(value) = self.storage.get(&(address, index)) {
+            return Ok(*value)
+        }
+
+        let generation = self.generation.load(Ordering::SeqCst);
+        let value = self.db.storage_ref(address, index)?;
+        self.cache(&self.storage, (address, index), value, generation);
+        Ok(value)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.db.block_hash_ref(number)
+    }
+}
+
+impl<DB: BlockHashReader> BlockHashReader for PrefetchDb<DB> {
+    fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        self.db.block_hash(number)
+    }
+
+    fn convert_block_hash(
+        &self,
+        hash_or_number: alloy::eips::BlockHashOrNumber
+    ) -> ProviderResult<Option<B256>> {
+        self.db.convert_block_hash(hash_or_number)
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber
+    ) -> ProviderResult<Vec<B256>> {
+        self.db.canonical_hashes_range(start, end)
+    }
+}
+
+impl<DB: BlockNumReader> BlockNumReader for PrefetchDb<DB> {
+    fn chain_info(&self) -> ProviderResult<ChainInfo> {
+        self.db.chain_info()
+    }
+
+    fn best_block_number(&self) -> ProviderResult<BlockNumber> {
+        self.db.best_block_number()
+    }
+
+    fn last_block_number(&self) -> ProviderResult<BlockNumber> {
+        self.db.last_block_number()
+    }
+
+    fn block_number(&self, hash: B256) -> ProviderResult<Option<BlockNumber>> {
+        self.db.block_number(hash)
+    }
+
+    fn convert_number(&self, id: alloy::eips::BlockHashOrNumber) -> ProviderResult<Option<B256>> {
+        self.db.convert_number(id)
+    }
+
+    fn convert_hash_or_number(
+        &self,
+        id: alloy::eips::BlockHashOrNumber
+    ) -> ProviderResult<Option<BlockNumber>> {
+        self.db.convert_hash_or_number(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    /// Counts the reads that reach the database
+    #[derive(Default)]
+    struct CountingDb {
+        value: AtomicUsize,
+        reads: AtomicUsize
+    }
+
+    impl DatabaseRef for CountingDb {
+        type Error = std::convert::Infallible;
+
+        fn basic_ref(&self, _: Address) -> Result<Option<AccountInfo>, Self::Error> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            Ok(None)
+        }
+
+        fn code_by_hash_ref(&self, _: B256) -> Result<Bytecode, Self::Error> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            Ok(Bytecode::default())
+        }
+
+        fn storage_ref(&self, _: Address, _: U256) -> Result<U256, Self::Error> {
+            self.reads.fetch_add(1, Ordering::SeqCst);
+            Ok(U256::from(self.value.load(Ordering::SeqCst)))
+        }
+
+        fn block_hash_ref(&self, _: u64) -> Result<B256, Self::Error> {
+            Ok(B256::ZERO)
+        }
+    }
+
+    #[test]
+    fn previous_block_state_is_reloaded() {
+        let db = PrefetchDb::new(Arc::new(CountingDb::default()));
+        let token = Address::repeat_byte(1);
+        let user = Address::repeat_byte(2);
+
+        assert_eq!(db.storage_ref(token, U256::from(1)).unwrap(), U256::ZERO);
+        db.storage_ref(token, U256::from(1)).unwrap();
+        assert_eq!(db.db.reads.load(Ordering::SeqCst), 1);
+
+        // the slot changes in the new block, which is never read from the cache
+        db.db.value.store(5, Ordering::SeqCst);
+        db.invalidate();
+        db.prefetch_for_new_block(HashSet::from([user]));
+        assert_eq!(db.db.reads.load(Ordering::SeqCst), 3);
+
+        // both the slot and the user are warm
+        assert_eq!(db.storage_ref(token, U256::from(1)).unwrap(), U256::from(5));
+        db.basic_ref(user).unwrap();
+        assert_eq!(db.db.reads.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn invalidated_state_is_read_again() {
+        let db = PrefetchDb::new(Arc::new(CountingDb::default()));
+        let token = Address::repeat_byte(1);
+
+        db.storage_ref(token, U256::from(1)).unwrap();
+        db.db.value.store(5, Ordering::SeqCst);
+        db.invalidate();
+
+        // read before the prefetch gets to it
+        assert_eq!(db.storage_ref(token, U256::from(1)).unwrap(), U256::from(5));
+        assert_eq!(db.db.reads.load(Ordering::SeqCst), 2);
+    }
+}
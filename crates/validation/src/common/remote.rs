@@ -0,0 +1,200 @@
+use std::{future::IntoFuture, sync::Arc};
+
+use alloy::{
+    eips::BlockNumberOrTag,
+    primitives::{Address, BlockNumber, B256, KECCAK256_EMPTY, U256},
+    providers::Provider,
+    rpc::types::BlockTransactionsKind,
+    transports::TransportError
+};
+use dashmap::DashMap;
+use futures::Future;
+use reth_chainspec::ChainInfo;
+use reth_provider::{BlockHashReader, BlockNumReader, ProviderError, ProviderResult};
+use revm::{
+    primitives::{AccountInfo, Bytecode},
+    DatabaseRef
+};
+use tokio::runtime::Handle;
+
+#[derive(Debug, thiserror::Error)]
+pub enum RemoteDbError {
+    #[error("state request failed - {0}")]
+    Transport(#[from] TransportError),
+    #[error("code {0:?} was never loaded with its account")]
+    MissingCode(B256),
+    #[error("block {0} doesn't exist")]
+    MissingBlock(BlockNumber)
+}
+
+/// Reads the state validation needs from a json rpc endpoint instead of the
+/// node's database, for running validation next to a third party archive
+/// node. Accounts are read with `eth_getProof` and storage with
+/// `eth_getStorageAt`, both at the latest block.
+///
+/// Only code is cached here, by its hash, as it never changes. The state of
+/// the current block is cached by the [`PrefetchDb`](super::PrefetchDb)
+/// validation puts in front of every database.
+#[derive(Clone)]
+pub struct RemoteDb<P> {
+    provider: Arc<P>,
+    code:     Arc<DashMap<B256, Bytecode>>,
+    /// runs the requests made from outside of a runtime
+    handle:   Handle
+}
+
+impl<P: Provider> RemoteDb<P> {
+    /// Has to be called from within a tokio runtime.
+    pub fn new(provider: Arc<P>) -> Self {
+        Self { provider, code: Arc::default(), handle: Handle::current() }
+    }
+
+    fn block_on<F: Future>(&self, f: F) -> F::Output {
+        match Handle::try_current() {
+            Ok(handle) => tokio::task::block_in_place(|| handle.block_on(f)),
+            Err(_) => self.handle.block_on(f)
+        }
+    }
+
+    fn latest_block(&self) -> Result<BlockNumber, TransportError> {
+        self.block_on(self.provider.get_block_number().into_future())
+    }
+}
+
+/// An account the endpoint knows nothing about reads as empty rather than as
+/// missing.
+fn is_empty_account(nonce: u64, balance: U256, code_hash: B256) -> bool {
+    nonce == 0 && balance.is_zero() && (code_hash == KECCAK256_EMPTY || code_hash.is_zero())
+}
+
+impl<P: Provider> DatabaseRef for RemoteDb<P> {
+    type Error = RemoteDbError;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        let proof = self.block_on(self.provider.get_proof(address, vec![]).into_future())?;
+        if is_empty_account(proof.nonce, proof.balance, proof.code_hash) {
+            return Ok(None)
+        }
+
+        let code = if proof.code_hash == KECCAK256_EMPTY {
+            Bytecode::default()
+        } else if let Some(code) = self.code.get(&proof.code_hash) {
+            code.clone()
+        } else {
+            let code = self.block_on(self.provider.get_code_at(address).into_future())?;
+            let code = Bytecode::new_raw(code);
+            self.code.insert(proof.code_hash, code.clone());
+            code
+        };
+
+        Ok(Some(AccountInfo {
+            balance:   proof.balance,
+            nonce:     proof.nonce,
+            code_hash: proof.code_hash,
+            code:      Some(code)
+        }))
+    }
+
+    /// Code comes with its account, there is no way to ask the endpoint for
+    /// code by its hash.
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        if code_hash == KECCAK256_EMPTY {
+            return Ok(Bytecode::default())
+        }
+
+        self.code
+            .get(&code_hash)
+            .map(|code| code.clone())
+            .ok_or(RemoteDbError::MissingCode(code_hash))
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        Ok(self.block_on(self.provider.get_storage_at(address, index).into_future())?)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        let block = self.block_on(
+            self.provider
+                .get_block_by_number(
+                    BlockNumberOrTag::Number(number),
+                    BlockTransactionsKind::Hashes
+                )
+                .into_future()
+        )?;
+
+        block
+            .map(|block| block.header.hash)
+            .ok_or(RemoteDbError::MissingBlock(number))
+    }
+}
+
+impl<P: Provider> BlockHashReader for RemoteDb<P> {
+    fn block_hash(&self, number: BlockNumber) -> ProviderResult<Option<B256>> {
+        match self.block_hash_ref(number) {
+            Ok(hash) => Ok(Some(hash)),
+            Err(RemoteDbError::MissingBlock(_)) => Ok(None),
+            Err(_) => Err(ProviderError::HeaderNotFound(number.into()))
+        }
+    }
+
+    fn canonical_hashes_range(
+        &self,
+        start: BlockNumber,
+        end: BlockNumber
+    ) -> ProviderResult<Vec<B256>> {
+        (start..end)
+            .map(|number| {
+                self.block_hash(number)?
+                    .ok_or(ProviderError::HeaderNotFound(number.into()))
+            })
+            .collect()
+    }
+}
+
+impl<P: Provider> BlockNumReader for RemoteDb<P> {
+    fn chain_info(&self) -> ProviderResult<ChainInfo> {
+        let best_number = self.best_block_number()?;
+        let best_hash = self
+            .block_hash(best_number)?
+            .ok_or(ProviderError::HeaderNotFound(best_number.into()))?;
+
+        Ok(ChainInfo { best_hash, best_number })
+    }
+
+    fn best_block_number(&self) -> ProviderResult<BlockNumber> {
+        self.latest_block()
+            .map_err(|_| ProviderError::BestBlockNotFound)
+    }
+
+    fn last_block_number(&self) -> ProviderResult<BlockNumber> {
+        self.best_block_number()
+    }
+
+    fn block_number(&self, hash: B256) -> ProviderResult<Option<BlockNumber>> {
+        let block = self
+            .block_on(
+                self.provider
+                    .get_block_by_hash(hash, BlockTransactionsKind::Hashes)
+                    .into_future()
+            )
+            .map_err(|_| ProviderError::BlockHashNotFound(hash))?;
+
+        Ok(block.map(|block| block.header.number))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_accounts_are_missing() {
+        assert!(is_empty_account(0, U256::ZERO, KECCAK256_EMPTY));
+        // some endpoints answer with no code hash at all
+        assert!(is_empty_account(0, U256::ZERO, B256::ZERO));
+
+        assert!(!is_empty_account(1, U256::ZERO, KECCAK256_EMPTY));
+        assert!(!is_empty_account(0, U256::from(1), KECCAK256_EMPTY));
+        assert!(!is_empty_account(0, U256::ZERO, B256::repeat_byte(1)));
+    }
+}
@@ -0,0 +1,131 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use alloy::{consensus::Header, eips::eip1559::BaseFeeParams};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+/// How many of the last blocks with a settlement the gas is forecast from.
+pub const SETTLEMENT_GAS_WINDOW: usize = 32;
+
+/// What settling the next bundle is expected to cost.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettlementCostEstimate {
+    /// the block the bundle would land in
+    pub block:    u64,
+    pub base_fee: u64,
+    /// average gas of the recent settlements
+    pub gas:      u64,
+    /// most gas any of the recent settlements took
+    pub max_gas:  u64,
+    /// `gas` at the base fee, in wei
+    pub cost:     u128,
+    /// `max_gas` at the base fee, in wei
+    pub max_cost: u128,
+    /// how many blocks the gas is forecast from
+    pub samples:  usize
+}
+
+#[derive(Debug, Default)]
+struct SettlementCosts {
+    /// the block after the last one seen and its base fee
+    next_block: Option<(u64, u64)>,
+    /// gas of the settlement of each of the last blocks with one, oldest first
+    gas:        VecDeque<(u64, u64)>
+}
+
+/// Forecasts the cost of the next bundle from the gas the bundles simulated
+/// for the last [`SETTLEMENT_GAS_WINDOW`] blocks took and the base fee of the
+/// next block. Clones share the same forecast, so it can be handed out before
+/// validation runs.
+#[derive(Debug, Clone, Default)]
+pub struct SettlementCostEstimator(Arc<RwLock<SettlementCosts>>);
+
+impl SettlementCostEstimator {
+    pub fn on_new_block(&self, parent: &Header) {
+        let base_fee = parent
+            .next_block_base_fee(BaseFeeParams::ethereum())
+            .unwrap_or_default();
+        self.0.write().next_block = Some((parent.number + 1, base_fee));
+    }
+
+    /// Records the gas of a bundle settling in the block after `number`. Of
+    /// the bundles simulated for a block, the one taking the most gas counts.
+    pub fn record(&self, number: u64, gas: u64) {
+        let block = number + 1;
+        let mut costs = self.0.write();
+        match costs.gas.iter_mut().find(|(seen, _)| *seen == block) {
+            Some((_, seen_gas)) => *seen_gas = (*seen_gas).max(gas),
+            None => {
+                if costs.gas.len() == SETTLEMENT_GAS_WINDOW {
+                    costs.gas.pop_front();
+                }
+                costs.gas.push_back((block, gas));
+            }
+        }
+    }
+
+    /// None until a block and a settlement have been seen.
+    pub fn estimate(&self) -> Option<SettlementCostEstimate> {
+        let costs = self.0.read();
+        let (block, base_fee) = costs.next_block?;
+        let max_gas = costs.gas.iter().map(|(_, gas)| *gas).max()?;
+        let samples = costs.gas.len();
+        let gas =
+            (costs.gas.iter().map(|(_, gas)| *gas as u128).sum::<u128>() / samples as u128) as u64;
+
+        Some(SettlementCostEstimate {
+            block,
+            base_fee,
+            gas,
+            max_gas,
+            cost: gas as u128 * base_fee as u128,
+            max_cost: max_gas as u128 * base_fee as u128,
+            samples
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn forecasts_from_recent_settlements() {
+        let estimator = SettlementCostEstimator::default();
+        estimator.record(99, 300_000);
+        // a block has to be seen first
+        assert_eq!(estimator.estimate(), None);
+
+        estimator.on_new_block(&Header {
+            number: 100,
+            gas_limit: 30_000_000,
+            gas_used: 15_000_000,
+            base_fee_per_gas: Some(1_000_000_000),
+            ..Default::default()
+        });
+        // the bigger of two bundles for the same block counts
+        estimator.record(100, 100_000);
+        estimator.record(100, 200_000);
+
+        let estimate = estimator.estimate().unwrap();
+        assert_eq!(estimate.block, 101);
+        assert_eq!(estimate.base_fee, 1_000_000_000);
+        assert_eq!((estimate.gas, estimate.max_gas, estimate.samples), (250_000, 300_000, 2));
+        assert_eq!(estimate.cost, 250_000 * 1_000_000_000);
+    }
+
+    #[test]
+    fn only_keeps_the_last_blocks() {
+        let estimator = SettlementCostEstimator::default();
+        estimator.on_new_block(&Header { base_fee_per_gas: Some(1), ..Default::default() });
+        estimator.record(0, 1_000_000);
+        for number in 1..=SETTLEMENT_GAS_WINDOW as u64 {
+            estimator.record(number, 100_000);
+        }
+
+        let estimate = estimator.estimate().unwrap();
+        assert_eq!(estimate.samples, SETTLEMENT_GAS_WINDOW);
+        assert_eq!(estimate.max_gas, 100_000);
+    }
+}
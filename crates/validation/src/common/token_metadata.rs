@@ -0,0 +1,154 @@
+use std::sync::Arc;
+
+use alloy::{
+    primitives::{address, Address, Bytes},
+    providers::Provider,
+    sol,
+    sol_types::SolCall
+};
+use angstrom_types::{
+    primitive::{normalize_price, TokenMetadata},
+    sol_bindings::Ray
+};
+use dashmap::DashMap;
+
+/// Multicall3 is deployed at the same address on every chain we run on.
+const MULTICALL3_ADDRESS: Address = address!("cA11bde05977b3631167028862bE2a173976CA11");
+
+sol! {
+    #[sol(rpc)]
+    interface IMulticall3 {
+        struct Call3 {
+            address target;
+            bool allowFailure;
+            bytes callData;
+        }
+
+        struct Result {
+            bool success;
+            bytes returnData;
+        }
+
+        function aggregate3(Call3[] calldata calls) external payable returns (Result[] memory returnData);
+    }
+
+    interface IERC20Metadata {
+        function decimals() external view returns (uint8);
+        function symbol() external view returns (string memory);
+    }
+}
+
+/// Decimals and symbols of the tokens we have seen, loaded the first time a
+/// token shows up. Token metadata never changes, so entries are never
+/// refreshed.
+#[derive(Debug, Clone, Default)]
+pub struct TokenMetadataRegistry {
+    tokens: Arc<DashMap<Address, TokenMetadata>>
+}
+
+impl TokenMetadataRegistry {
+    pub fn get(&self, token: &Address) -> Option<TokenMetadata> {
+        self.tokens.get(token).map(|metadata| metadata.clone())
+    }
+
+    pub fn insert(&self, token: Address, metadata: TokenMetadata) {
+        self.tokens.insert(token, metadata);
+    }
+
+    /// Loads the metadata of the tokens that aren't known yet, in a single
+    /// multicall. Tokens that don't report their decimals are left out.
+    pub async fn load_missing<P: Provider>(
+        &self,
+        provider: Arc<P>,
+        tokens: impl IntoIterator<Item = Address>
+    ) -> eyre::Result<()> {
+        let mut missing = tokens
+            .into_iter()
+            .filter(|token| !self.tokens.contains_key(token))
+            .collect::<Vec<_>>();
+        missing.sort_unstable();
+        missing.dedup();
+        if missing.is_empty() {
+            return Ok(())
+        }
+
+        let calls = missing
+            .iter()
+            .flat_map(|&target| {
+                [
+                    IERC20Metadata::decimalsCall {}.abi_encode(),
+                    IERC20Metadata::symbolCall {}.abi_encode()
+                ]
+                .map(|call| IMulticall3::Call3 {
+                    target,
+                    allowFailure: true,
+                    callData: call.into()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let results = IMulticall3::new(MULTICALL3_ADDRESS, provider)
+            .aggregate3(calls)
+            .call()
+            .await?
+            .returnData;
+
+        for (token, results) in missing.into_iter().zip(results.chunks(2)) {
+            let [decimals, symbol] = results else { continue };
+            let Some(decimals) = decimals
+                .success
+                .then(|| {
+                    IERC20Metadata::decimalsCall::abi_decode_returns(&decimals.returnData, true)
+                })
+                .and_then(Result::ok)
+            else {
+                tracing::warn!(?token, "token didn't report its decimals");
+                continue
+            };
+            let symbol = symbol
+                .success
+                .then(|| decode_symbol(&symbol.returnData))
+                .flatten()
+                .unwrap_or_default();
+
+            self.insert(token, TokenMetadata::new(decimals._0, symbol));
+        }
+
+        Ok(())
+    }
+
+    /// Converts a price of raw `token1` per raw `token0` into whole tokens.
+    pub fn normalized_price(&self, token0: Address, token1: Address, price: Ray) -> Option<f64> {
+        Some(normalize_price(price.as_f64(), &self.get(&token0)?, &self.get(&token1)?))
+    }
+}
+
+/// Some older tokens return their symbol as a `bytes32` instead of a string.
+fn decode_symbol(data: &Bytes) -> Option<String> {
+    if let Ok(symbol) = IERC20Metadata::symbolCall::abi_decode_returns(data, true) {
+        return Some(symbol._0)
+    }
+
+    (data.len() == 32).then(|| {
+        String::from_utf8_lossy(data)
+            .trim_end_matches('\0')
+            .to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{primitives::B256, sol_types::SolValue};
+
+    use super::*;
+
+    #[test]
+    fn decodes_string_and_bytes32_symbols() {
+        let symbol = Bytes::from("USDC".to_string().abi_encode());
+        assert_eq!(decode_symbol(&symbol), Some("USDC".to_string()));
+
+        let mut mkr = B256::ZERO;
+        mkr[..3].copy_from_slice(b"MKR");
+        assert_eq!(decode_symbol(&Bytes::copy_from_slice(mkr.as_slice())), Some("MKR".to_string()));
+    }
+}
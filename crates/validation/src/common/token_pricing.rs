@@ -7,13 +7,21 @@ use alloy::{
     primitives::{address, Address, U256},
     providers::Provider
 };
-use angstrom_types::{pair_with_price::PairsWithPrice, primitive::PoolId, sol_bindings::Ray};
+use angstrom_types::{
+    matching::uniswap::QuoteRejection,
+    pair_with_price::PairsWithPrice,
+    primitive::{PoolId, TokenPair},
+    sol_bindings::Ray
+};
 use futures::StreamExt;
 use tracing::warn;
 use uniswap_v4::uniswap::{pool_data_loader::PoolDataLoader, pool_manager::SyncedUniswapPools};
 
+use super::TokenMetadataRegistry;
+
 const BLOCKS_TO_AVG_PRICE: u64 = 5;
 pub const WETH_ADDRESS: Address = address!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
+const ETH_DECIMALS: u8 = 18;
 
 // crazy that this is a thing
 #[allow(clippy::too_long_first_doc_paragraph)]
@@ -26,9 +34,10 @@ pub const WETH_ADDRESS: Address = address!("c02aaa39b223fe8d0a0e5c4f27ead9083c75
 #[derive(Debug, Default, Clone)]
 pub struct TokenPriceGenerator {
     prev_prices:         HashMap<PoolId, VecDeque<PairsWithPrice>>,
-    pair_to_pool:        HashMap<(Address, Address), PoolId>,
+    pair_to_pool:        HashMap<TokenPair, PoolId>,
     cur_block:           u64,
-    blocks_to_avg_price: u64
+    blocks_to_avg_price: u64,
+    token_metadata:      TokenMetadataRegistry
 }
 
 impl TokenPriceGenerator {
@@ -46,7 +55,21 @@ impl TokenPriceGenerator {
         let mut pair_to_pool = HashMap::default();
         for (key, pool) in uni.iter() {
             let pool = pool.read().unwrap();
-            pair_to_pool.insert((pool.token0, pool.token1), *key);
+            pair_to_pool.insert(TokenPair::new(pool.token0, pool.token1), *key);
+        }
+
+        let token_metadata = TokenMetadataRegistry::default();
+        // only needed to display prices, so a failure here isn't fatal
+        if let Err(e) = token_metadata
+            .load_missing(
+                provider.clone(),
+                pair_to_pool
+                    .keys()
+                    .flat_map(|pair| [pair.token0(), pair.token1()])
+            )
+            .await
+        {
+            warn!(%e, "failed to load token metadata");
         }
 
         let blocks_to_avg_price = blocks_to_avg_price_override.unwrap_or(BLOCKS_TO_AVG_PRICE);
@@ -96,21 +119,32 @@ impl TokenPriceGenerator {
             })
             .await;
 
-        Ok(Self { prev_prices: pools, cur_block: current_block, pair_to_pool, blocks_to_avg_price })
+        Ok(Self {
+            prev_prices: pools,
+            cur_block: current_block,
+            pair_to_pool,
+            blocks_to_avg_price,
+            token_metadata
+        })
+    }
+
+    pub fn token_metadata(&self) -> &TokenMetadataRegistry {
+        &self.token_metadata
+    }
+
+    /// Same as [`Self::get_eth_conversion_price`], but in whole token0 per ETH
+    /// instead of raw units. Needs the metadata of token0 to be loaded.
+    pub fn get_eth_conversion_price_in_units(&self, pair: TokenPair) -> Option<f64> {
+        let price = self.get_eth_conversion_price(pair)?;
+        let token = self.token_metadata.get(&pair.token0())?;
+
+        Some(price.as_f64() * 10f64.powi(ETH_DECIMALS as i32 - token.decimals as i32))
     }
 
     pub fn generate_lookup_map(&self) -> HashMap<(Address, Address), Ray> {
         self.pair_to_pool
             .keys()
-            .filter_map(|(mut token0, mut token1)| {
-                if token1 < token0 {
-                    std::mem::swap(&mut token0, &mut token1)
-                };
-
-                let price = self.get_eth_conversion_price(token0, token1)?;
-
-                Some(((token0, token1), price))
-            })
+            .filter_map(|&pair| Some((pair.tokens(), self.get_eth_conversion_price(pair)?)))
             .collect()
     }
 
@@ -121,7 +155,7 @@ impl TokenPriceGenerator {
 
             let pool_key = self
                 .pair_to_pool
-                .get(&(pool_update.token0, pool_update.token1))
+                .get(&TokenPair::new(pool_update.token0, pool_update.token1))
                 .expect("got pool update that we don't have stored");
             let prev_prices = self
                 .prev_prices
@@ -133,10 +167,28 @@ impl TokenPriceGenerator {
         self.cur_block += 1;
     }
 
-    /// NOTE: assumes tokens are properly sorted.
+    /// Flags the price of a pair as stale when its pool didn't get a price
+    /// update in the last `max_age` blocks.
+    pub fn check_price_fresh(&self, pair: TokenPair, max_age: u64) -> Result<(), QuoteRejection> {
+        let last_update = self
+            .pair_to_pool
+            .get(&pair)
+            .and_then(|pool| self.prev_prices.get(pool)?.back())
+            .ok_or(QuoteRejection::NoPrice)?
+            .block_num;
+
+        if self.cur_block.saturating_sub(last_update) > max_age {
+            return Err(QuoteRejection::StalePrice { last_update })
+        }
+
+        Ok(())
+    }
+
+    /// The price of token0 of the pair in ETH.
     /// the previous prices are stored in RAY (1e27).
     /// we take this price. then
-    pub fn get_eth_conversion_price(&self, token_0: Address, token_1: Address) -> Option<Ray> {
+    pub fn get_eth_conversion_price(&self, pair: TokenPair) -> Option<Ray> {
+        let (token_0, token_1) = pair.tokens();
         if token_0 == WETH_ADDRESS {
             return Some(Ray::scale_to_ray(U256::from(1)))
         }
@@ -146,7 +198,7 @@ impl TokenPriceGenerator {
             // if so, just pull the price
             let pool_key = self
                 .pair_to_pool
-                .get(&(token_0, token_1))
+                .get(&pair)
                 .expect("got pool update that we don't have stored");
 
             let prices = self.prev_prices.get(pool_key)?;
@@ -169,20 +221,15 @@ impl TokenPriceGenerator {
         }
 
         // need to pass through a pair.
-        let (first_flip, token_0_hop1, token_1_hop1) = if token_0 < WETH_ADDRESS {
-            (false, token_0, WETH_ADDRESS)
-        } else {
-            (true, WETH_ADDRESS, token_0)
-        };
+        // the flips are set where weth is token0 of the hop
+        let hop1 = TokenPair::new(token_0, WETH_ADDRESS);
+        let first_flip = hop1.token0() == WETH_ADDRESS;
 
-        let (second_flip, token_0_hop2, token_1_hop2) = if token_1 < WETH_ADDRESS {
-            (false, token_1, WETH_ADDRESS)
-        } else {
-            (true, WETH_ADDRESS, token_1)
-        };
+        let hop2 = TokenPair::new(token_1, WETH_ADDRESS);
+        let second_flip = hop2.token0() == WETH_ADDRESS;
 
         // check token_0 first for a weth pair. otherwise, check token_1.
-        if let Some(key) = self.pair_to_pool.get(&(token_0_hop1, token_1_hop1)) {
+        if let Some(key) = self.pair_to_pool.get(&hop1) {
             // there is a hop from token_0 to weth
             let prices = self.prev_prices.get(key)?;
             let size = prices.len() as u64;
@@ -205,12 +252,12 @@ impl TokenPriceGenerator {
                     .sum::<Ray>()
                     / U256::from(size)
             )
-        } else if let Some(key) = self.pair_to_pool.get(&(token_0_hop2, token_1_hop2)) {
+        } else if let Some(key) = self.pair_to_pool.get(&hop2) {
             // because we are going through token1 here and we want token zero, we need to
             // do some extra math
             let default_pool_key = self
                 .pair_to_pool
-                .get(&(token_0, token_1))
+                .get(&pair)
                 .expect("got pool update that we don't have stored");
 
             let prices = self.prev_prices.get(default_pool_key)?;
@@ -268,10 +315,12 @@ pub mod test {
         node_bindings::WEI_IN_ETHER,
         primitives::{Address, FixedBytes, U256}
     };
-    use angstrom_types::{pair_with_price::PairsWithPrice, sol_bindings::Ray};
+    use angstrom_types::{
+        matching::uniswap::QuoteRejection, pair_with_price::PairsWithPrice, sol_bindings::Ray
+    };
     use revm::primitives::address;
 
-    use super::{TokenPriceGenerator, BLOCKS_TO_AVG_PRICE, WETH_ADDRESS};
+    use super::{TokenPair, TokenPriceGenerator, BLOCKS_TO_AVG_PRICE, WETH_ADDRESS};
 
     const TOKEN0: Address = address!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2");
     const TOKEN1: Address = address!("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc3");
@@ -286,16 +335,16 @@ pub mod test {
         // setup pair lookup
 
         // pair 1 direct NOTE: case is where weth is token1
-        pairs_to_key.insert((TOKEN2, TOKEN0), FixedBytes::<32>::with_last_byte(1));
+        pairs_to_key.insert(TokenPair::new(TOKEN2, TOKEN0), FixedBytes::<32>::with_last_byte(1));
 
         // pair 2 direct NOTE: case is where weth is token0
-        pairs_to_key.insert((TOKEN0, TOKEN1), FixedBytes::<32>::with_last_byte(2));
+        pairs_to_key.insert(TokenPair::new(TOKEN0, TOKEN1), FixedBytes::<32>::with_last_byte(2));
 
         // multi-hop where token0 matches
-        pairs_to_key.insert((TOKEN2, TOKEN3), FixedBytes::<32>::with_last_byte(3));
+        pairs_to_key.insert(TokenPair::new(TOKEN2, TOKEN3), FixedBytes::<32>::with_last_byte(3));
 
         // multi-hop where token1 matches
-        pairs_to_key.insert((TOKEN4, TOKEN1), FixedBytes::<32>::with_last_byte(4));
+        pairs_to_key.insert(TokenPair::new(TOKEN4, TOKEN1), FixedBytes::<32>::with_last_byte(4));
 
         // setup price conversions
         let mut prices = HashMap::default();
@@ -353,7 +402,8 @@ pub mod test {
             cur_block:           0,
             prev_prices:         prices,
             pair_to_pool:        pairs_to_key,
-            blocks_to_avg_price: BLOCKS_TO_AVG_PRICE
+            blocks_to_avg_price: BLOCKS_TO_AVG_PRICE,
+            token_metadata:      Default::default()
         }
     }
 
@@ -361,7 +411,7 @@ pub mod test {
     fn test_direct_conversion() {
         let token_conversion = setup();
         let rate = token_conversion
-            .get_eth_conversion_price(TOKEN2, TOKEN0)
+            .get_eth_conversion_price(TokenPair::new(TOKEN2, TOKEN0))
             .unwrap();
 
         let expected_rate = Ray::scale_to_ray(U256::from(5e18)).inv_ray();
@@ -374,7 +424,7 @@ pub mod test {
     fn test_multi_hop_where_token0_matches() {
         let token_conversion = setup();
         let rate = token_conversion
-            .get_eth_conversion_price(TOKEN2, TOKEN3)
+            .get_eth_conversion_price(TokenPair::new(TOKEN2, TOKEN3))
             .unwrap();
 
         let expected_rate = Ray::scale_to_ray(U256::from(5e18)).inv_ray();
@@ -386,7 +436,7 @@ pub mod test {
     fn test_multi_hop_where_token1_matches() {
         let token_conversion = setup();
         let rate = token_conversion
-            .get_eth_conversion_price(TOKEN4, TOKEN1)
+            .get_eth_conversion_price(TokenPair::new(TOKEN4, TOKEN1))
             .unwrap();
 
         // hop 1 rate
@@ -408,13 +458,13 @@ pub mod test {
 
         // WETH as token0 should return 1
         let rate = token_conversion
-            .get_eth_conversion_price(WETH_ADDRESS, TOKEN1)
+            .get_eth_conversion_price(TokenPair::new(WETH_ADDRESS, TOKEN1))
             .unwrap();
         assert_eq!(rate, Ray::scale_to_ray(U256::from(1)));
 
         // 5 weth .inv
         let rate = token_conversion
-            .get_eth_conversion_price(TOKEN2, WETH_ADDRESS)
+            .get_eth_conversion_price(TokenPair::new(TOKEN2, WETH_ADDRESS))
             .unwrap();
 
         assert_eq!(rate, Ray::scale_to_ray(U256::from(5) * WEI_IN_ETHER).inv_ray());
@@ -442,7 +492,7 @@ pub mod test {
 
         // Average should be (1 + 2 + 3 + 4 + 5) / 5 = 3
         let rate = token_conversion
-            .get_eth_conversion_price(TOKEN2, TOKEN0)
+            .get_eth_conversion_price(TokenPair::new(TOKEN2, TOKEN0))
             .unwrap();
 
         let mut sum = Ray::default();
@@ -454,6 +504,30 @@ pub mod test {
         assert_eq!(rate, expected);
     }
 
+    #[test]
+    fn test_stale_prices_are_flagged() {
+        let mut token_conversion = setup();
+        for block_num in 1..=3 {
+            token_conversion.apply_update(vec![PairsWithPrice {
+                token0: TOKEN2,
+                token1: TOKEN0,
+                block_num,
+                price_1_over_0: Ray::scale_to_ray(U256::from(1) * WEI_IN_ETHER)
+            }]);
+        }
+
+        assert_eq!(token_conversion.check_price_fresh(TokenPair::new(TOKEN2, TOKEN0), 0), Ok(()));
+        assert_eq!(token_conversion.check_price_fresh(TokenPair::new(TOKEN0, TOKEN1), 3), Ok(()));
+        assert_eq!(
+            token_conversion.check_price_fresh(TokenPair::new(TOKEN0, TOKEN1), 2),
+            Err(QuoteRejection::StalePrice { last_update: 0 })
+        );
+        assert_eq!(
+            token_conversion.check_price_fresh(TokenPair::new(TOKEN5, TOKEN0), 2),
+            Err(QuoteRejection::NoPrice)
+        );
+    }
+
     #[test]
     fn test_generate_lookup_map() {
         let token_conversion = setup();
@@ -487,10 +561,10 @@ pub mod test {
         let token_conversion = setup();
 
         // Try to get price for non-existent pool
-        let rate = token_conversion.get_eth_conversion_price(
+        let rate = token_conversion.get_eth_conversion_price(TokenPair::new(
             address!("1111111111111111111111111111111111111111"),
             address!("2222222222222222222222222222222222222222")
-        );
+        ));
         assert!(rate.is_none(), "Should return None for missing pool");
     }
 
@@ -502,11 +576,11 @@ pub mod test {
         let pool_id = FixedBytes::<32>::with_last_byte(6);
         token_conversion
             .pair_to_pool
-            .insert((TOKEN5, WETH_ADDRESS), pool_id);
+            .insert(TokenPair::new(TOKEN4, WETH_ADDRESS), pool_id);
 
         let mut queue = VecDeque::new();
         queue.push_back(PairsWithPrice {
-            token0:         TOKEN5,
+            token0:         TOKEN4,
             token1:         WETH_ADDRESS,
             block_num:      0,
             price_1_over_0: Ray::scale_to_ray(U256::from(1) * WEI_IN_ETHER)
@@ -514,7 +588,7 @@ pub mod test {
         token_conversion.prev_prices.insert(pool_id, queue);
 
         let rate = token_conversion
-            .get_eth_conversion_price(TOKEN5, WETH_ADDRESS)
+            .get_eth_conversion_price(TokenPair::new(TOKEN4, WETH_ADDRESS))
             .unwrap();
 
         assert_eq!(rate, Ray::scale_to_ray(U256::from(1) * WEI_IN_ETHER).inv_ray());
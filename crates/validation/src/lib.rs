@@ -8,19 +8,25 @@ use std::{
     sync::{atomic::AtomicU64, Arc}
 };
 
-use alloy::primitives::Address;
+use alloy::{primitives::Address, sol_types::Eip712Domain};
+pub use angstrom_types::primitive::ValidationError;
 use angstrom_types::{
     contract_payloads::angstrom::AngstromPoolConfigStore, pair_with_price::PairsWithPrice
 };
-use bundle::BundleValidator;
-use common::SharedTools;
+use angstrom_utils::memory_budget::MemoryBudget;
+use bundle::{BundleValidator, SimulationCache};
+use common::{SharedTools, StatePrefetch};
+use futures::StreamExt;
 use reth_provider::CanonStateNotificationStream;
 use tokio::sync::mpsc::UnboundedReceiver;
 use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
 use validator::Validator;
 
 use crate::{
-    common::{key_split_threadpool::KeySplitThreadpool, TokenPriceGenerator},
+    common::{
+        key_split_threadpool::KeySplitThreadpool, NextBlockEnv, PrefetchDb,
+        SettlementCostEstimator, TokenPriceGenerator
+    },
     order::{
         order_validator::OrderValidator,
         sim::SimValidation,
@@ -43,12 +49,20 @@ pub fn init_validation<
     uniswap_pools: SyncedUniswapPools,
     price_generator: TokenPriceGenerator,
     pool_store: Arc<AngstromPoolConfigStore>,
-    validator_rx: UnboundedReceiver<ValidationRequest>
+    domain: Eip712Domain,
+    validator_rx: UnboundedReceiver<ValidationRequest>,
+    memory_budget: MemoryBudget,
+    cache_ceiling: Option<usize>,
+    settlement_costs: SettlementCostEstimator
 ) where
     <DB as revm::DatabaseRef>::Error: Send + Sync + Debug
 {
     let current_block = Arc::new(AtomicU64::new(current_block));
-    let revm_lru = Arc::new(db);
+    let revm_lru = Arc::new(PrefetchDb::new(db));
+    let sim_cache = SimulationCache::default();
+    memory_budget.register("validation_state_cache", revm_lru.clone(), cache_ceiling);
+    memory_budget.register("bundle_simulation_cache", Arc::new(sim_cache.clone()), cache_ceiling);
+    let block_env = NextBlockEnv::default();
     let fetch = FetchUtils::new(Address::default(), revm_lru.clone());
 
     std::thread::spawn(move || {
@@ -62,21 +76,37 @@ pub fn init_validation<
         let pools = AngstromPoolsTracker::new(angstrom_address, pool_store);
         // load storage slot state + pools
         let thread_pool = KeySplitThreadpool::new(handle, MAX_VALIDATION_PER_ADDR);
-        let sim = SimValidation::new(revm_lru.clone(), angstrom_address, node_address);
+        let sim =
+            SimValidation::new(revm_lru.clone(), angstrom_address, node_address, uniswap_pools)
+                .with_block_env(block_env.clone());
 
-        // load price update stream;
+        // load price update stream, keeping the next block env in step with the chain
+        let env_updates = block_env.clone();
+        let cost_updates = settlement_costs.clone();
+        let cache = revm_lru.clone();
+        let state_notification = state_notification.inspect(move |notification| {
+            cache.invalidate();
+            env_updates.on_new_block(notification.tip().header());
+            cost_updates.on_new_block(notification.tip().header());
+        });
         let update_stream =
             PairsWithPrice::into_price_update_stream(angstrom_address, state_notification);
 
-        let order_validator =
-            rt.block_on(OrderValidator::new(sim, current_block, pools, fetch, uniswap_pools));
+        let order_validator = rt
+            .block_on(OrderValidator::new(sim, current_block, pools, fetch))
+            .with_domain(domain);
 
         let bundle_validator =
-            BundleValidator::new(revm_lru.clone(), angstrom_address, node_address);
+            BundleValidator::new(revm_lru.clone(), angstrom_address, node_address)
+                .with_block_env(block_env)
+                .with_sim_cache(sim_cache)
+                .with_settlement_costs(settlement_costs);
         let shared_utils = SharedTools::new(price_generator, Box::pin(update_stream), thread_pool);
 
         rt.block_on(async {
-            Validator::new(validator_rx, order_validator, bundle_validator, shared_utils).await
+            Validator::new(validator_rx, order_validator, bundle_validator, shared_utils)
+                .with_state_prefetch(revm_lru)
+                .await
         })
     });
 }
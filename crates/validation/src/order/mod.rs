@@ -1,13 +1,15 @@
-use std::{fmt::Debug, future::Future, pin::Pin};
+use std::{fmt::Debug, future::Future, ops::Range, pin::Pin};
 
 use alloy::primitives::{Address, B256, U256};
+use angstrom_metrics::validation::{ValidationMetrics, ValidationStage};
 use angstrom_types::{
     orders::OrderOrigin,
-    primitive::OrderPoolNewOrderResult,
+    primitive::{OrderPoolNewOrderResult, ValidationError},
     sol_bindings::{
         ext::RawPoolOrder,
         grouped_orders::{
-            AllOrders, GroupedComposableOrder, GroupedVanillaOrder, OrderWithStorageData
+            AllOrders, GroupedComposableOrder, GroupedVanillaOrder, OrderWithStorageData,
+            StandingVariants
         },
         rpc_orders::TopOfBlockOrder
     }
@@ -30,10 +32,16 @@ pub type ValidationsFuture<'a> =
     Pin<Box<dyn Future<Output = Vec<OrderValidationResults>> + Send + Sync + 'a>>;
 
 pub type GasEstimationFuture<'a> =
-    Pin<Box<dyn Future<Output = Result<(u64, U256), String>> + Send + Sync + 'a>>;
+    Pin<Box<dyn Future<Output = Result<(u64, U256), ValidationError>> + Send + Sync + 'a>>;
+
+pub type NonceFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + Sync + 'a>>;
 
 pub enum OrderValidationRequest {
-    ValidateOrder(Sender<OrderValidationResults>, AllOrders, OrderOrigin)
+    ValidateOrder(Sender<OrderValidationResults>, AllOrders, OrderOrigin),
+    /// a standing order amending the pending order with the hash
+    ValidateAmendment(Sender<OrderValidationResults>, StandingVariants, B256, OrderOrigin),
+    /// validates the order without keeping it
+    SimulateOrder(Sender<OrderValidationResults>, AllOrders)
 }
 
 /// TODO: not a fan of all the conversions. can def simplify
@@ -67,7 +75,11 @@ impl From<OrderValidationRequest> for OrderValidation {
                     // }
                 }
                 AllOrders::TOB(tob) => OrderValidation::Searcher(tx, tob, orign)
+            },
+            OrderValidationRequest::ValidateAmendment(tx, order, replaces, origin) => {
+                OrderValidation::Amendment(tx, order, replaces, origin)
             }
+            OrderValidationRequest::SimulateOrder(tx, order) => OrderValidation::Simulate(tx, order)
         }
     }
 }
@@ -80,7 +92,7 @@ pub enum ValidationMessage {
 pub enum OrderValidationResults {
     Valid(OrderWithStorageData<AllOrders>),
     // the raw hash to be removed
-    Invalid(B256),
+    Invalid(B256, ValidationError),
     TransitionedToBlock
 }
 
@@ -124,7 +136,7 @@ impl OrderValidationResults {
 
                 if let Err(e) = res {
                     tracing::info!(%e, "failed to add gas to order");
-                    *self = OrderValidationResults::Invalid(order_hash);
+                    *self = OrderValidationResults::Invalid(order_hash, e);
 
                     return
                 }
@@ -145,7 +157,7 @@ impl OrderValidationResults {
                 );
                 if let Err(e) = res {
                     tracing::info!(%e, "failed to add gas to order");
-                    *self = OrderValidationResults::Invalid(order_hash);
+                    *self = OrderValidationResults::Invalid(order_hash, e);
 
                     return
                 }
@@ -157,6 +169,74 @@ impl OrderValidationResults {
         }
     }
 
+    /// Simulates a top of block order against the start of block state and
+    /// sets the reward it pays. Has to run after the gas has been added, as the
+    /// order needs to be able to cover it.
+    pub async fn simulate_tob_or_invalidate<DB>(&mut self, sim: &SimValidation<DB>)
+    where
+        DB: Unpin
+            + Clone
+            + 'static
+            + revm::DatabaseRef
+            + reth_provider::BlockNumReader
+            + Send
+            + Sync,
+        <DB as revm::DatabaseRef>::Error: Send + Sync + std::fmt::Debug
+    {
+        let Self::Valid(order) = self else { return };
+        let order_hash = order.order_hash();
+
+        let Ok(tob) = order.clone().try_map_inner(|inner| {
+            let AllOrders::TOB(tob) = inner else { eyre::bail!("not a top of block order") };
+            Ok(tob)
+        }) else {
+            return
+        };
+
+        match sim.simulate_tob(&tob).await {
+            Ok(outcome) => order.tob_reward = outcome.total_reward,
+            Err(e) => {
+                tracing::info!(%e, "top of block order failed simulation");
+                *self = OrderValidationResults::Invalid(order_hash, e.into());
+            }
+        }
+    }
+
+    /// Adds the gas cost of a valid order and simulates it if it is a top of
+    /// block order, counting the order as rejected if either fails.
+    pub async fn simulate_or_invalidate<DB>(
+        &mut self,
+        sim: &SimValidation<DB>,
+        token_price: &TokenPriceGenerator,
+        block: u64,
+        metrics: &ValidationMetrics
+    ) where
+        DB: Unpin
+            + Clone
+            + 'static
+            + revm::DatabaseRef
+            + reth_provider::BlockNumReader
+            + Send
+            + Sync,
+        <DB as revm::DatabaseRef>::Error: Send + Sync + std::fmt::Debug
+    {
+        let Self::Valid(order) = self else { return };
+        let is_limit = !matches!(order.order, AllOrders::TOB(_));
+        let pool_id = order.pool_id;
+        let tokens = [order.order.token_in(), order.order.token_out()];
+
+        metrics
+            .stage_async(ValidationStage::Simulation, async {
+                self.add_gas_cost_or_invalidate(sim, token_price, is_limit, block);
+                self.simulate_tob_or_invalidate(sim).await;
+            })
+            .await;
+
+        if let Self::Invalid(_, error) = self {
+            metrics.rejected(error, Some(pool_id), &tokens);
+        }
+    }
+
     // hmm the structure here is probably overkill to avoid 8 extra lines of code
     fn map_and_process<Old, New, DB>(
         order: OrderWithStorageData<Old>,
@@ -170,8 +250,8 @@ impl OrderValidationResults {
             &OrderWithStorageData<New>,
             &TokenPriceGenerator,
             u64
-        ) -> eyre::Result<(u64, U256)>
-    ) -> eyre::Result<OrderWithStorageData<Old>>
+        ) -> Result<(u64, U256), ValidationError>
+    ) -> Result<OrderWithStorageData<Old>, ValidationError>
     where
         DB: Unpin + Clone + 'static + revm::DatabaseRef + Send + Sync,
         <DB as revm::DatabaseRef>::Error: Sync + Send + 'static
@@ -184,7 +264,9 @@ impl OrderValidationResults {
         order.priority_data.gas += gas_used;
         order.priority_data.gas_units = gas_units;
 
-        order.try_map_inner(move |new_order| Ok(map_old(new_order)))
+        Ok(order
+            .try_map_inner(move |new_order| Ok(map_old(new_order)))
+            .unwrap())
     }
 }
 
@@ -192,7 +274,7 @@ impl From<OrderValidationResults> for OrderPoolNewOrderResult {
     fn from(val: OrderValidationResults) -> Self {
         match val {
             OrderValidationResults::Valid(_) => OrderPoolNewOrderResult::Valid,
-            OrderValidationResults::Invalid(_) => OrderPoolNewOrderResult::Invalid,
+            OrderValidationResults::Invalid(_, e) => OrderPoolNewOrderResult::Invalid(e),
             OrderValidationResults::TransitionedToBlock => {
                 OrderPoolNewOrderResult::TransitionedToBlock
             }
@@ -203,16 +285,43 @@ impl From<OrderValidationResults> for OrderPoolNewOrderResult {
 pub enum OrderValidation {
     Limit(Sender<OrderValidationResults>, GroupedVanillaOrder, OrderOrigin),
     LimitComposable(Sender<OrderValidationResults>, GroupedComposableOrder, OrderOrigin),
-    Searcher(Sender<OrderValidationResults>, TopOfBlockOrder, OrderOrigin)
+    Searcher(Sender<OrderValidationResults>, TopOfBlockOrder, OrderOrigin),
+    Amendment(Sender<OrderValidationResults>, StandingVariants, B256, OrderOrigin),
+    Simulate(Sender<OrderValidationResults>, AllOrders)
 }
 impl OrderValidation {
     pub fn user(&self) -> Address {
         match &self {
             Self::Searcher(_, u, _) => u.from(),
             Self::LimitComposable(_, u, _) => u.from(),
-            Self::Limit(_, u, _) => u.from()
+            Self::Limit(_, u, _) => u.from(),
+            Self::Amendment(_, u, ..) => u.from(),
+            Self::Simulate(_, u) => u.from()
+        }
+    }
+
+    /// Where the order came from, simulated orders come from nowhere.
+    pub fn origin(&self) -> Option<OrderOrigin> {
+        match self {
+            Self::Searcher(.., origin)
+            | Self::LimitComposable(.., origin)
+            | Self::Limit(.., origin)
+            | Self::Amendment(.., origin) => Some(*origin),
+            Self::Simulate(..) => None
         }
     }
+
+    /// Answers the request without validating the order.
+    pub fn reject(self, error: ValidationError) {
+        let (tx, hash) = match self {
+            Self::Searcher(tx, o, _) => (tx, o.order_hash()),
+            Self::LimitComposable(tx, o, _) => (tx, o.order_hash()),
+            Self::Limit(tx, o, _) => (tx, o.order_hash()),
+            Self::Amendment(tx, o, ..) => (tx, o.order_hash()),
+            Self::Simulate(tx, o) => (tx, o.order_hash())
+        };
+        let _ = tx.send(OrderValidationResults::Invalid(hash, error));
+    }
 }
 
 /// Provides support for validating transaction at any given state of the chain
@@ -241,8 +350,28 @@ pub trait OrderValidatorHandle: Send + Sync + Clone + Debug + Unpin + 'static {
         addresses: Vec<Address>
     ) -> ValidationFuture;
 
+    /// Validates `transaction` in place of the pending order `replaces`, see
+    /// [`OrderAmendment`](angstrom_types::orders::OrderAmendment).
+    fn validate_amendment(
+        &self,
+        origin: OrderOrigin,
+        transaction: StandingVariants,
+        replaces: B256
+    ) -> ValidationFuture;
+
+    /// Validates `order` as if it was submitted, without it being kept or
+    /// changing the pending state of its user.
+    fn simulate_order(&self, order: AllOrders) -> ValidationFuture;
+
     /// estimates gas usage for order
     fn estimate_gas(&self, order: AllOrders) -> GasEstimationFuture;
+
+    /// the lowest nonce that is neither used on chain nor by a pending order
+    /// of `user`
+    fn next_valid_nonce(&self, user: Address) -> NonceFuture<u64>;
+
+    /// the nonces in `range` that `user` used or invalidated on chain
+    fn invalidated_nonces(&self, user: Address, range: Range<u64>) -> NonceFuture<Vec<u64>>;
 }
 
 impl OrderValidatorHandle for ValidationClient {
@@ -282,17 +411,71 @@ impl OrderValidatorHandle for ValidationClient {
         })
     }
 
+    fn validate_amendment(
+        &self,
+        origin: OrderOrigin,
+        transaction: StandingVariants,
+        replaces: B256
+    ) -> ValidationFuture {
+        Box::pin(async move {
+            let (tx, rx) = channel();
+            let _ =
+                self.0
+                    .send(ValidationRequest::Order(OrderValidationRequest::ValidateAmendment(
+                        tx,
+                        transaction,
+                        replaces,
+                        origin
+                    )));
+
+            rx.await.unwrap()
+        })
+    }
+
+    fn simulate_order(&self, order: AllOrders) -> ValidationFuture {
+        Box::pin(async move {
+            let (tx, rx) = channel();
+            let _ = self
+                .0
+                .send(ValidationRequest::Order(OrderValidationRequest::SimulateOrder(tx, order)));
+
+            rx.await.unwrap()
+        })
+    }
+
     fn estimate_gas(&self, order: AllOrders) -> GasEstimationFuture {
         Box::pin(async move {
-            match self.validate_order(OrderOrigin::External, order).await {
+            match self.simulate_order(order).await {
                 OrderValidationResults::Valid(o) => {
                     Ok((o.priority_data.gas_units, o.priority_data.gas))
                 }
-                OrderValidationResults::Invalid(e) => Err(format!("Invalid order: {}", e)),
+                OrderValidationResults::Invalid(_, e) => Err(e),
                 OrderValidationResults::TransitionedToBlock => {
-                    Err("Order transitioned to block".to_string())
+                    Err(ValidationError::TransitionedToBlock)
                 }
             }
         })
     }
+
+    fn next_valid_nonce(&self, user: Address) -> NonceFuture<u64> {
+        Box::pin(async move {
+            let (tx, rx) = channel();
+            let _ = self
+                .0
+                .send(ValidationRequest::NextValidNonce { sender: tx, user });
+
+            rx.await.unwrap()
+        })
+    }
+
+    fn invalidated_nonces(&self, user: Address, range: Range<u64>) -> NonceFuture<Vec<u64>> {
+        Box::pin(async move {
+            let (tx, rx) = channel();
+            let _ = self
+                .0
+                .send(ValidationRequest::InvalidatedNonces { sender: tx, user, range });
+
+            rx.await.unwrap()
+        })
+    }
 }
@@ -1,14 +1,22 @@
 use std::{
+    collections::HashSet,
     fmt::Debug,
+    ops::Range,
     pin::Pin,
     sync::{atomic::AtomicU64, Arc}
 };
 
-use alloy::primitives::{Address, BlockNumber, B256};
+use alloy::{
+    primitives::{Address, BlockNumber, B256},
+    sol_types::Eip712Domain
+};
 use angstrom_metrics::validation::ValidationMetrics;
+use angstrom_types::{
+    contract_payloads::angstrom::UserBalances, primitive::ValidationError,
+    sol_bindings::grouped_orders::GroupedVanillaOrder
+};
 use futures::Future;
 use tokio::runtime::Handle;
-use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
 
 use super::{
     sim::SimValidation,
@@ -23,6 +31,10 @@ use crate::{
     order::{state::account::UserAccountProcessor, OrderValidation}
 };
 
+/// Validations of a single user that can be waiting on the threadpool before
+/// new orders of the user are turned away.
+const MAX_QUEUED_VALIDATIONS_PER_ADDR: usize = 64;
+
 pub struct OrderValidator<DB, Pools, Fetch> {
     sim:                     SimValidation<DB>,
     state:                   StateValidation<Pools, Fetch>,
@@ -40,10 +52,9 @@ where
         sim: SimValidation<DB>,
         block_number: Arc<AtomicU64>,
         pools: Pools,
-        fetch: Fetch,
-        uniswap_pools: SyncedUniswapPools
+        fetch: Fetch
     ) -> Self {
-        let state = StateValidation::new(UserAccountProcessor::new(fetch), pools, uniswap_pools);
+        let state = StateValidation::new(UserAccountProcessor::new(fetch), pools);
 
         Self { state, sim, block_number }
     }
@@ -56,7 +67,30 @@ where
     ) {
         self.block_number
             .store(block_number, std::sync::atomic::Ordering::SeqCst);
-        self.state.new_block(completed_orders, address_changes);
+        self.state
+            .new_block(block_number, completed_orders, address_changes);
+    }
+
+    /// Checks order signatures against `domain` instead of the testnet one.
+    pub fn with_domain(mut self, domain: Eip712Domain) -> Self {
+        self.state = self.state.with_domain(domain);
+        self
+    }
+
+    pub fn pending_accounts(&self) -> HashSet<Address> {
+        self.state.pending_accounts()
+    }
+
+    pub fn start_balances(&self) -> UserBalances {
+        self.state.start_balances()
+    }
+
+    pub fn next_valid_nonce(&self, user: Address) -> u64 {
+        self.state.next_valid_nonce(user)
+    }
+
+    pub fn invalidated_nonces(&self, user: Address, range: Range<u64>) -> Vec<u64> {
+        self.state.invalidated_nonces(user, range)
     }
 
     /// only checks state
@@ -73,7 +107,17 @@ where
     ) {
         let block_number = self.block_number.load(std::sync::atomic::Ordering::SeqCst);
         let order_validation: OrderValidation = order.into();
+        let metrics = match order_validation.origin() {
+            Some(origin) => metrics.with_origin(origin),
+            None => metrics.simulating()
+        };
         let user = order_validation.user();
+        if thread_pool.pending_for(&user) >= MAX_QUEUED_VALIDATIONS_PER_ADDR {
+            metrics.rejected(&ValidationError::ThreadpoolSaturated, None, &[]);
+            order_validation.reject(ValidationError::ThreadpoolSaturated);
+            return
+        }
+
         let cloned_state = self.state.clone();
         let cloned_sim = self.sim.clone();
 
@@ -89,12 +133,14 @@ where
                                     block_number,
                                     metrics.clone()
                                 );
-                                results.add_gas_cost_or_invalidate(
-                                    &cloned_sim,
-                                    &token_conversion,
-                                    true,
-                                    block_number
-                                );
+                                results
+                                    .simulate_or_invalidate(
+                                        &cloned_sim,
+                                        &token_conversion,
+                                        block_number,
+                                        &metrics
+                                    )
+                                    .await;
 
                                 let _ = tx.send(results);
                             })
@@ -103,21 +149,60 @@ where
                     OrderValidation::Searcher(tx, order, _) => {
                         metrics
                             .new_order(true, || async {
-                                let mut results = cloned_state
-                                    .handle_tob_order(order, block_number, metrics.clone())
+                                let mut results = cloned_state.handle_regular_order(
+                                    order,
+                                    block_number,
+                                    metrics.clone()
+                                );
+                                results
+                                    .simulate_or_invalidate(
+                                        &cloned_sim,
+                                        &token_conversion,
+                                        block_number,
+                                        &metrics
+                                    )
                                     .await;
 
-                                results.add_gas_cost_or_invalidate(
-                                    &cloned_sim,
-                                    &token_conversion,
-                                    false,
-                                    block_number
+                                let _ = tx.send(results);
+                            })
+                            .await;
+                    }
+                    OrderValidation::Amendment(tx, order, replaces, _) => {
+                        metrics
+                            .new_order(false, || async {
+                                let mut results = cloned_state.handle_amended_order(
+                                    GroupedVanillaOrder::Standing(order),
+                                    replaces,
+                                    block_number,
+                                    metrics.clone()
                                 );
+                                results
+                                    .simulate_or_invalidate(
+                                        &cloned_sim,
+                                        &token_conversion,
+                                        block_number,
+                                        &metrics
+                                    )
+                                    .await;
 
                                 let _ = tx.send(results);
                             })
                             .await;
                     }
+                    OrderValidation::Simulate(tx, order) => {
+                        let mut results =
+                            cloned_state.simulate_order(order, block_number, metrics.clone());
+                        results
+                            .simulate_or_invalidate(
+                                &cloned_sim,
+                                &token_conversion,
+                                block_number,
+                                &metrics
+                            )
+                            .await;
+
+                        let _ = tx.send(results);
+                    }
                     _ => unreachable!()
                 }
             })
@@ -8,6 +8,7 @@ use angstrom_types::{
     contract_bindings::mintable_mock_erc_20::MintableMockERC20::{allowanceCall, balanceOfCall},
     contract_payloads::angstrom::AngstromBundle,
     matching::uniswap::UniswapFlags,
+    primitive::ValidationError,
     sol_bindings::{
         grouped_orders::{GroupedVanillaOrder, OrderWithStorageData},
         rpc_orders::TopOfBlockOrder,
@@ -15,18 +16,20 @@ use angstrom_types::{
     }
 };
 use eyre::eyre;
-use pade::PadeEncode;
 use reth_provider::BlockNumReader;
 use revm::{
     db::CacheDB,
     inspector_handle_register,
-    primitives::{EnvWithHandlerCfg, ResultAndState, TxEnv},
+    primitives::{EVMError, EnvWithHandlerCfg, ExecutionResult, ResultAndState, TxEnv},
     DatabaseRef
 };
 
 use super::gas_inspector::{GasSimulationInspector, GasUsed};
-use crate::order::state::db_state_utils::finders::{
-    find_slot_offset_for_approval, find_slot_offset_for_balance
+use crate::{
+    common::NextBlockEnv,
+    order::state::db_state_utils::finders::{
+        find_slot_offset_for_approval, find_slot_offset_for_balance
+    }
 };
 
 /// A address we can use to deploy contracts
@@ -50,7 +53,8 @@ pub struct OrderGasCalculations<DB> {
     // the deployed addresses in cache_db
     angstrom_address: Address,
     /// the address(pubkey) of this node.
-    node_address:     Option<Address>
+    node_address:     Option<Address>,
+    block_env:        NextBlockEnv
 }
 
 impl<DB> OrderGasCalculations<DB>
@@ -71,20 +75,35 @@ where
         // );
 
         if let Some(angstrom_address) = angstrom_address {
-            Ok(Self { db: CacheDB::new(db), angstrom_address, node_address: Some(node_address) })
+            Ok(Self {
+                db: CacheDB::new(db),
+                angstrom_address,
+                node_address: Some(node_address),
+                block_env: NextBlockEnv::default()
+            })
         } else {
             let ConfiguredRevm { db, angstrom } =
                 Self::setup_revm_cache_database_for_simulation(db)?;
 
-            Ok(Self { db, angstrom_address: angstrom, node_address: None })
+            Ok(Self {
+                db,
+                angstrom_address: angstrom,
+                node_address: None,
+                block_env: NextBlockEnv::default()
+            })
         }
     }
 
+    pub fn with_block_env(mut self, block_env: NextBlockEnv) -> Self {
+        self.block_env = block_env;
+        self
+    }
+
     pub fn gas_of_tob_order(
         &self,
         tob: &OrderWithStorageData<TopOfBlockOrder>,
         block: u64
-    ) -> eyre::Result<GasUsed> {
+    ) -> Result<GasUsed, ValidationError> {
         // need to grab the order hash
         self.execute_on_revm(
             &HashMap::default(),
@@ -99,11 +118,12 @@ where
             |execution_env| {
                 let bundle = AngstromBundle::build_dummy_for_tob_gas(tob).unwrap();
 
-                let bundle = bundle.pade_encode();
+                let bundle = bundle.pade_encode_for_submission();
                 let bundle_bytes: Bytes = bundle.into();
-                execution_env.block.number = U256::from(block + 1);
+                execution_env.block = self.block_env.for_block_after(block);
 
                 let tx = &mut execution_env.tx;
+                tx.gas_price = execution_env.block.basefee;
                 tx.caller = self.node_address.unwrap_or(DEFAULT_FROM);
                 tx.transact_to = TxKind::Call(self.angstrom_address);
                 tx.data = angstrom_types::contract_bindings::angstrom::Angstrom::executeCall::new(
@@ -113,18 +133,17 @@ where
                 .into();
             }
         )
-        .map_err(|e| eyre!("tob order err={} {:?}", e, tob.from()))
     }
 
     pub fn gas_of_book_order(
         &self,
         order: &OrderWithStorageData<GroupedVanillaOrder>,
         block: u64
-    ) -> eyre::Result<GasUsed> {
+    ) -> Result<GasUsed, ValidationError> {
         let exact_in = order.exact_in();
         let bundle = AngstromBundle::build_dummy_for_user_gas(order).unwrap();
 
-        let bundle = bundle.pade_encode();
+        let bundle = bundle.pade_encode_for_submission();
 
         let (amount_in, amount_out) = if exact_in {
             (U256::from(order.amount_in()), {
@@ -152,9 +171,10 @@ where
                 flipped_order: Address::default()
             },
             |execution_env| {
-                execution_env.block.number = U256::from(block + 1);
+                execution_env.block = self.block_env.for_block_after(block);
 
                 let tx = &mut execution_env.tx;
+                tx.gas_price = execution_env.block.basefee;
                 tx.caller = self.node_address.unwrap_or(DEFAULT_FROM);
                 tx.transact_to = TxKind::Call(self.angstrom_address);
                 tx.data = angstrom_types::contract_bindings::angstrom::Angstrom::executeCall::new(
@@ -164,7 +184,6 @@ where
                 .into();
             }
         )
-        .map_err(|e| eyre!("user order err={} {:?}", e, order.from()))
     }
 
     fn execute_with_db<D: DatabaseRef, F>(db: D, f: F) -> eyre::Result<(ResultAndState, D)>
@@ -268,7 +287,7 @@ where
         offsets: &HashMap<usize, usize>,
         overrides: OverridesForTestAngstrom,
         f: F
-    ) -> eyre::Result<GasUsed>
+    ) -> Result<GasUsed, ValidationError>
     where
         F: FnOnce(&mut EnvWithHandlerCfg)
     {
@@ -302,20 +321,26 @@ where
                 })
                 .build();
 
-            let result = evm
-                .transact()
-                .map_err(|e| eyre!("failed to transact with revm: {e:?}"))?;
-
-            if !result.result.is_success() {
-                let output = result.result.output().unwrap().to_vec();
-                let allowed_revert = alloy::primitives::hex!("cc67af53");
-
-                if output[0..4] != allowed_revert {
-                    return Err(eyre::eyre!(
-                        "gas simulation had a revert. cannot guarantee the proper gas was \
-                         estimated err={:?}",
-                        result.result
-                    ))
+            let result = evm.transact().map_err(|e| match e {
+                EVMError::Database(e) => ValidationError::Db(format!("{e:?}")),
+                e => ValidationError::SimulationFailed(format!("{e:?}"))
+            })?;
+
+            // the gas can't be trusted if the simulation reverted anywhere but at the
+            // end of the bundle
+            match result.result {
+                ExecutionResult::Success { .. } => {}
+                ExecutionResult::Revert { output, .. } => {
+                    let allowed_revert = alloy::primitives::hex!("cc67af53");
+                    if !output.starts_with(&allowed_revert) {
+                        return Err(ValidationError::SimulationReverted(
+                            alloy::sol_types::decode_revert_reason(&output)
+                                .unwrap_or_else(|| output.to_string())
+                        ))
+                    }
+                }
+                ExecutionResult::Halt { reason, .. } => {
+                    return Err(ValidationError::SimulationReverted(format!("{reason:?}")))
                 }
             }
         }
@@ -508,6 +533,7 @@ pub mod test {
         }
     };
     use eyre::eyre;
+    use pade::PadeEncode;
     use reth_provider::BlockNumReader;
     use reth_revm::primitives::Bytecode;
     use revm::primitives::AccountInfo;
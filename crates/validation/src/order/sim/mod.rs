@@ -2,26 +2,37 @@ use std::{fmt::Debug, sync::Arc};
 
 use alloy::primitives::Address;
 use angstrom_metrics::validation::ValidationMetrics;
-use angstrom_types::sol_bindings::{
-    grouped_orders::{GroupedVanillaOrder, OrderWithStorageData},
-    rpc_orders::TopOfBlockOrder,
-    RawPoolOrder
+use angstrom_types::{
+    primitive::{TokenPair, ValidationError},
+    sol_bindings::{
+        grouped_orders::{GroupedVanillaOrder, OrderWithStorageData},
+        rpc_orders::TopOfBlockOrder,
+        RawPoolOrder
+    }
 };
 use gas::OrderGasCalculations;
 use revm::primitives::ruint::aliases::U256;
 use tracing::error_span;
+use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
 
-use crate::{common::TokenPriceGenerator, order::sim::gas_inspector::GasUsed};
+use crate::{
+    common::{NextBlockEnv, TokenPriceGenerator},
+    order::sim::gas_inspector::GasUsed
+};
 
 pub mod console_log;
 mod gas;
 mod gas_inspector;
+mod tob;
+pub use tob::*;
 
 pub type GasInToken0 = U256;
 /// validation relating to simulations.
 #[derive(Clone)]
 pub struct SimValidation<DB> {
     gas_calculator: OrderGasCalculations<DB>,
+    /// start of block state of the pools, used to simulate top of block orders
+    uniswap_pools:  SyncedUniswapPools,
     metrics:        ValidationMetrics
 }
 
@@ -30,11 +41,22 @@ where
     DB: Unpin + Clone + 'static + revm::DatabaseRef + reth_provider::BlockNumReader + Send + Sync,
     <DB as revm::DatabaseRef>::Error: Send + Sync + Debug
 {
-    pub fn new(db: Arc<DB>, angstrom_address: Address, node_address: Address) -> Self {
+    pub fn new(
+        db: Arc<DB>,
+        angstrom_address: Address,
+        node_address: Address,
+        uniswap_pools: SyncedUniswapPools
+    ) -> Self {
         let gas_calculator =
             OrderGasCalculations::new(db.clone(), Some(angstrom_address), node_address)
                 .expect("failed to deploy baseline angstrom for gas calculations");
-        Self { gas_calculator, metrics: ValidationMetrics::new() }
+        Self { gas_calculator, uniswap_pools, metrics: ValidationMetrics::new() }
+    }
+
+    /// Simulates in the same next block environment as bundle validation.
+    pub fn with_block_env(mut self, block_env: NextBlockEnv) -> Self {
+        self.gas_calculator = self.gas_calculator.with_block_env(block_env);
+        self
     }
 
     pub fn calculate_tob_gas(
@@ -42,23 +64,19 @@ where
         order: &OrderWithStorageData<TopOfBlockOrder>,
         conversion: &TokenPriceGenerator,
         block: u64
-    ) -> eyre::Result<(GasUsed, GasInToken0)> {
+    ) -> Result<(GasUsed, GasInToken0), ValidationError> {
         let hash = order.order_hash();
         let user = order.from();
         let span = error_span!("tob", ?hash, ?user);
         span.in_scope(|| {
             self.metrics.fetch_gas_for_user(true, || {
                 let gas_in_wei = self.gas_calculator.gas_of_tob_order(order, block)?;
-                // grab order tokens;
-                let (token0, token1) = if order.asset_in < order.asset_out {
-                    (order.asset_in, order.asset_out)
-                } else {
-                    (order.asset_out, order.asset_in)
-                };
+                let pair = TokenPair::new(order.asset_in, order.asset_out);
 
                 // grab price conversion
-                let conversion_factor =
-                    conversion.get_eth_conversion_price(token0, token1).unwrap();
+                let conversion_factor = conversion.get_eth_conversion_price(pair).ok_or(
+                    ValidationError::MissingPrice { token0: pair.token0(), token1: pair.token1() }
+                )?;
 
                 Ok((gas_in_wei, (conversion_factor * U256::from(gas_in_wei)).scale_out_of_ray()))
             })
@@ -70,23 +88,19 @@ where
         order: &OrderWithStorageData<GroupedVanillaOrder>,
         conversion: &TokenPriceGenerator,
         block: u64
-    ) -> eyre::Result<(GasUsed, GasInToken0)> {
+    ) -> Result<(GasUsed, GasInToken0), ValidationError> {
         let hash = order.order_hash();
         let user = order.from();
         let span = error_span!("user", ?hash, ?user);
         span.in_scope(|| {
             self.metrics.fetch_gas_for_user(false, || {
                 let gas_in_wei = self.gas_calculator.gas_of_book_order(order, block)?;
-                // grab order tokens;
-                let (token0, token1) = if order.token_in() < order.token_out() {
-                    (order.token_in(), order.token_out())
-                } else {
-                    (order.token_out(), order.token_in())
-                };
+                let pair = TokenPair::new(order.token_in(), order.token_out());
 
                 // grab price conversion
-                let conversion_factor =
-                    conversion.get_eth_conversion_price(token0, token1).unwrap();
+                let conversion_factor = conversion.get_eth_conversion_price(pair).ok_or(
+                    ValidationError::MissingPrice { token0: pair.token0(), token1: pair.token1() }
+                )?;
 
                 Ok((gas_in_wei, (conversion_factor * U256::from(gas_in_wei)).scale_out_of_ray()))
             })
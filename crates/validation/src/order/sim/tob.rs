@@ -0,0 +1,113 @@
+use std::fmt::Debug;
+
+use alloy::primitives::U256;
+use angstrom_types::{
+    contract_payloads::tob::ToBOutcome,
+    primitive::ValidationError,
+    sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder}
+};
+use thiserror::Error;
+
+use super::SimValidation;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TobSimulationError {
+    #[error("no uniswap pool synced for the order")]
+    UnknownPool,
+    #[error("swap can't be filled against the start of block state: {0}")]
+    Unfillable(String),
+    #[error("order leaves nothing to bid")]
+    NoBid,
+    #[error("gas cost of {gas} token0 is above the order's limit of {max_gas}")]
+    GasAboveLimit { gas: U256, max_gas: u128 }
+}
+
+impl From<TobSimulationError> for ValidationError {
+    fn from(e: TobSimulationError) -> Self {
+        match e {
+            TobSimulationError::UnknownPool => ValidationError::UnknownPool,
+            e => ValidationError::TopOfBlock(e.to_string())
+        }
+    }
+}
+
+impl<DB> SimValidation<DB>
+where
+    DB: Unpin + Clone + 'static + revm::DatabaseRef + reth_provider::BlockNumReader + Send + Sync,
+    <DB as revm::DatabaseRef>::Error: Send + Sync + Debug
+{
+    /// Simulates the swap of a top of block order against the start of block
+    /// state of its pool and checks that the bid it claims is payable. Only
+    /// orders that pass are eligible for the searcher auction. Expects the gas
+    /// of the order to already be set in its priority data.
+    pub async fn simulate_tob(
+        &self,
+        order: &OrderWithStorageData<TopOfBlockOrder>
+    ) -> Result<ToBOutcome, TobSimulationError> {
+        if !self.uniswap_pools.contains_key(&order.pool_id) {
+            return Err(TobSimulationError::UnknownPool)
+        }
+
+        let outcome = self
+            .uniswap_pools
+            .calculate_rewards(order.pool_id, order)
+            .await
+            .map_err(|e| TobSimulationError::Unfillable(e.to_string()))?;
+
+        check_tob_outcome(order, &outcome)?;
+
+        Ok(outcome)
+    }
+}
+
+/// The swap itself filling is checked while building the outcome. What is left
+/// is that there is something to bid and that the order covers its own gas.
+fn check_tob_outcome(
+    order: &OrderWithStorageData<TopOfBlockOrder>,
+    outcome: &ToBOutcome
+) -> Result<(), TobSimulationError> {
+    if outcome.total_reward.is_zero() {
+        return Err(TobSimulationError::NoBid)
+    }
+
+    let gas = order.priority_data.gas;
+    if gas > U256::from(order.max_gas_asset0) {
+        return Err(TobSimulationError::GasAboveLimit { gas, max_gas: order.max_gas_asset0 })
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(gas: u64, max_gas: u128) -> OrderWithStorageData<TopOfBlockOrder> {
+        let mut order = OrderWithStorageData::<TopOfBlockOrder>::default();
+        order.priority_data.gas = U256::from(gas);
+        order.order.max_gas_asset0 = max_gas;
+        order
+    }
+
+    fn outcome(total_reward: u64) -> ToBOutcome {
+        ToBOutcome { total_reward: U256::from(total_reward), ..Default::default() }
+    }
+
+    #[test]
+    fn payable_bid_passes() {
+        assert_eq!(check_tob_outcome(&order(10, 10), &outcome(1)), Ok(()));
+    }
+
+    #[test]
+    fn empty_bid_is_rejected() {
+        assert_eq!(check_tob_outcome(&order(10, 10), &outcome(0)), Err(TobSimulationError::NoBid));
+    }
+
+    #[test]
+    fn gas_above_limit_is_rejected() {
+        assert_eq!(
+            check_tob_outcome(&order(11, 10), &outcome(100)),
+            Err(TobSimulationError::GasAboveLimit { gas: U256::from(11), max_gas: 10 })
+        );
+    }
+}
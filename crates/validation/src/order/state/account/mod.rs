@@ -1,9 +1,19 @@
 //! keeps track of account state for orders
 
+use std::{
+    collections::HashSet,
+    ops::Range,
+    time::{SystemTime, UNIX_EPOCH}
+};
+
 use alloy::primitives::{Address, B256, U256};
 use angstrom_types::{
+    contract_payloads::angstrom::UserBalances,
     orders::OrderId,
-    sol_bindings::{ext::RawPoolOrder, grouped_orders::OrderWithStorageData}
+    primitive::ValidationError,
+    sol_bindings::{
+        ext::RawPoolOrder, grouped_orders::OrderWithStorageData, RespendAvoidanceMethod
+    }
 };
 use thiserror::Error;
 use user::UserAccounts;
@@ -28,8 +38,43 @@ impl<S: StateFetchUtils> UserAccountProcessor<S> {
         Self { fetch_utils, user_accounts }
     }
 
-    pub fn prepare_for_new_block(&self, users: Vec<Address>, orders: Vec<B256>) {
-        self.user_accounts.new_block(users, orders);
+    pub fn prepare_for_new_block(&self, block: u64, users: Vec<Address>, orders: Vec<B256>) {
+        self.user_accounts.new_block(block, users, orders);
+    }
+
+    pub fn pending_accounts(&self) -> HashSet<Address> {
+        self.user_accounts.pending_accounts()
+    }
+
+    pub fn start_balances(&self) -> UserBalances {
+        self.user_accounts.start_balances()
+    }
+
+    /// The nonces in `range` the user used or invalidated on chain.
+    pub fn invalidated_nonces(&self, user: Address, range: Range<u64>) -> Vec<u64> {
+        self.fetch_utils.invalidated_nonces(user, range)
+    }
+
+    /// The lowest nonce that is neither used on chain nor taken by one of the
+    /// user's pending orders.
+    pub fn next_valid_nonce(&self, user: Address) -> u64 {
+        let pending = self.user_accounts.pending_nonces(user);
+        let mut start = 0u64;
+        loop {
+            let words = start..start.saturating_add(256);
+            let invalidated = self
+                .fetch_utils
+                .invalidated_nonces(user, words.clone())
+                .into_iter()
+                .collect::<HashSet<_>>();
+            if let Some(nonce) = words
+                .clone()
+                .find(|nonce| !invalidated.contains(nonce) && !pending.contains(nonce))
+            {
+                return nonce
+            }
+            start = words.end;
+        }
     }
 
     pub fn verify_order<O: RawPoolOrder>(
@@ -37,6 +82,32 @@ impl<S: StateFetchUtils> UserAccountProcessor<S> {
         order: O,
         pool_info: UserOrderPoolInfo,
         block: u64
+    ) -> Result<OrderWithStorageData<O>, UserAccountVerificationError<O>> {
+        self.verify(order, None, pool_info, block, true)
+    }
+
+    /// Verifies `order` in place of the pending order `replaces`, which
+    /// shares its nonce, no matter which of the two hashes is lower.
+    pub fn verify_amendment<O: RawPoolOrder>(
+        &self,
+        order: O,
+        replaces: B256,
+        pool_info: UserOrderPoolInfo,
+        block: u64
+    ) -> Result<OrderWithStorageData<O>, UserAccountVerificationError<O>> {
+        self.verify(order, Some(replaces), pool_info, block, true)
+    }
+
+    /// Verifies `order` against the live state of its user. Unless `commit`
+    /// is set, the state is left as is, the order isn't added to the pending
+    /// orders of the user and the orders it conflicts with aren't cancelled.
+    pub(crate) fn verify<O: RawPoolOrder>(
+        &self,
+        order: O,
+        replaces: Option<B256>,
+        pool_info: UserOrderPoolInfo,
+        block: u64,
+        commit: bool
     ) -> Result<OrderWithStorageData<O>, UserAccountVerificationError<O>> {
         let user = order.from();
         let order_hash = order.order_hash();
@@ -45,12 +116,16 @@ impl<S: StateFetchUtils> UserAccountProcessor<S> {
         //
         let respend = order.respend_avoidance_strategy();
         match respend {
-            angstrom_types::sol_bindings::RespendAvoidanceMethod::Nonce(nonce) => {
+            RespendAvoidanceMethod::Nonce(nonce) => {
                 if !self.fetch_utils.is_valid_nonce(user, nonce) {
                     return Err(UserAccountVerificationError::DuplicateNonce(order_hash))
                 }
             }
-            angstrom_types::sol_bindings::RespendAvoidanceMethod::Block(order_block) => {
+            RespendAvoidanceMethod::Block(order_block) => {
+                // a block that passed can't be settled in anymore
+                if order_block <= block {
+                    return Err(UserAccountVerificationError::PastBlock(block + 1, order_block))
+                }
                 // order should be for block + 1
                 if block + 1 != order_block {
                     return Err(UserAccountVerificationError::BadBlock(block + 1, order_block))
@@ -62,16 +137,25 @@ impl<S: StateFetchUtils> UserAccountProcessor<S> {
         let conflicting_orders = self.user_accounts.respend_conflicts(user, respend);
         if conflicting_orders
             .iter()
-            .any(|o| o.order_hash <= order_hash)
+            .any(|o| o.order_hash <= order_hash && Some(o.order_hash) != replaces)
         {
-            return Err(UserAccountVerificationError::DuplicateNonce(order_hash))
+            return Err(match respend {
+                RespendAvoidanceMethod::Nonce(_) => {
+                    UserAccountVerificationError::DuplicateNonce(order_hash)
+                }
+                RespendAvoidanceMethod::Block(_) => {
+                    UserAccountVerificationError::DuplicateBlock(order_hash)
+                }
+            })
         }
         tracing::trace!(?conflicting_orders);
 
         // if new order has lower hash cancel all orders with the same nonce
-        conflicting_orders.iter().for_each(|order| {
-            self.user_accounts.cancel_order(&user, &order.order_hash);
-        });
+        if commit {
+            conflicting_orders.iter().for_each(|order| {
+                self.user_accounts.cancel_order(&user, &order.order_hash);
+            });
+        }
 
         // get the live state sorted up to the nonce, level, doesn't check orders above
         // that
@@ -86,11 +170,13 @@ impl<S: StateFetchUtils> UserAccountProcessor<S> {
         let (is_cur_valid, mut invalid_orders) = live_state
             .can_support_order(&order, &pool_info)
             .map(|pending_user_action| {
-                (
-                    true,
+                let invalidated = if commit {
                     self.user_accounts
                         .insert_pending_user_action(order.from(), pending_user_action)
-                )
+                } else {
+                    vec![]
+                };
+                (true, invalidated)
             })
             .unwrap_or_default();
 
@@ -114,10 +200,14 @@ pub trait StorageWithData: RawPoolOrder {
     ) -> OrderWithStorageData<Self> {
         OrderWithStorageData {
             priority_data: angstrom_types::orders::OrderPriorityData {
-                price:     self.limit_price(),
-                volume:    self.amount_in(),
-                gas:       U256::ZERO,
-                gas_units: 0
+                price:        self.limit_price(),
+                volume:       self.amount_in(),
+                gas:          U256::ZERO,
+                gas_units:    0,
+                validated_at: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as u64
             },
             pool_id: pool_info.pool_id,
             is_currently_valid: is_cur_valid,
@@ -140,8 +230,24 @@ pub enum UserAccountVerificationError<O: RawPoolOrder> {
     OrderIsCancelled(B256),
     #[error("Nonce exists for a current order hash: {0:?}")]
     DuplicateNonce(B256),
+    #[error("signer already has an order for the block: {0:?}")]
+    DuplicateBlock(B256),
     #[error("block for flash order is not for next block. next_block: {0}, requested_block: {1}.")]
-    BadBlock(u64, u64)
+    BadBlock(u64, u64),
+    #[error("block for flash order already passed. next_block: {0}, requested_block: {1}.")]
+    PastBlock(u64, u64)
+}
+
+impl<O: RawPoolOrder> From<UserAccountVerificationError<O>> for ValidationError {
+    fn from(e: UserAccountVerificationError<O>) -> Self {
+        match e {
+            UserAccountVerificationError::OrderIsCancelled(_) => ValidationError::Cancelled,
+            UserAccountVerificationError::DuplicateNonce(_) => ValidationError::DuplicateNonce,
+            UserAccountVerificationError::DuplicateBlock(_) => ValidationError::DuplicateBlock,
+            UserAccountVerificationError::PastBlock(..) => ValidationError::Expired,
+            e => ValidationError::InvalidBlock(e.to_string())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -411,6 +517,13 @@ pub mod tests {
         else {
             panic!("should fail for wrong block");
         };
+
+        // Should fail once the order's block passed
+        let Err(UserAccountVerificationError::PastBlock(..)) =
+            processor.verify_order(order.clone(), pool_info.clone(), 421)
+        else {
+            panic!("should fail for a past block");
+        };
     }
 
     #[test]
@@ -592,7 +705,7 @@ pub mod tests {
             .expect("order should be valid");
 
         // Prepare for new block
-        processor.prepare_for_new_block(vec![user], vec![order.hash()]);
+        processor.prepare_for_new_block(421, vec![user], vec![order.hash()]);
 
         // Try to add same order again - should succeed because state was cleared
         let result = processor
@@ -894,4 +1007,48 @@ pub mod tests {
             result
         );
     }
+
+    #[test]
+    fn next_valid_nonce_skips_used_and_pending_nonces() {
+        let processor = setup_test_account_processor();
+
+        let sk = AngstromSigner::random();
+        let user = sk.address();
+        let token0 = Address::random();
+        let token1 = Address::random();
+
+        let mock_pool = MockPoolTracker::default();
+        mock_pool.add_pool(token0, token1, PoolId::default());
+
+        processor
+            .fetch_utils
+            .set_used_nonces(user, HashSet::from([0, 1, 3]));
+        assert_eq!(processor.next_valid_nonce(user), 2);
+        assert_eq!(processor.invalidated_nonces(user, 1..4), vec![1, 3]);
+
+        let order: GroupedVanillaOrder = UserOrderBuilder::new()
+            .standing()
+            .asset_in(token0)
+            .asset_out(token1)
+            .nonce(2)
+            .signing_key(Some(sk.clone()))
+            .recipient(user)
+            .build();
+        let pool_info = mock_pool
+            .fetch_pool_info_for_order(&order)
+            .expect("pool tracker should have valid state");
+        processor
+            .fetch_utils
+            .set_balance_for_user(user, token0, U256::from(order.amount_in()));
+        processor
+            .fetch_utils
+            .set_approval_for_user(user, token0, U256::from(order.amount_in()));
+        processor
+            .verify_order(order, pool_info, 420)
+            .expect("order should be valid");
+
+        // a pending order holds nonce 2, which isn't invalidated on chain yet
+        assert_eq!(processor.next_valid_nonce(user), 4);
+        assert_eq!(processor.invalidated_nonces(user, 1..4), vec![1, 3]);
+    }
 }
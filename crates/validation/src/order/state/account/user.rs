@@ -1,7 +1,13 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc
+};
 
 use alloy::primitives::{Address, B256, U256};
-use angstrom_types::sol_bindings::{ext::RawPoolOrder, RespendAvoidanceMethod};
+use angstrom_types::{
+    contract_payloads::angstrom::{UserBalance, UserBalances},
+    sol_bindings::{ext::RawPoolOrder, RespendAvoidanceMethod}
+};
 use dashmap::DashMap;
 
 use crate::order::state::{db_state_utils::StateFetchUtils, pools::UserOrderPoolInfo};
@@ -77,6 +83,56 @@ pub struct PendingUserAction {
     pub pool_info: UserOrderPoolInfo
 }
 
+impl PendingUserAction {
+    fn fits_within(&self, approval: Amount, balance: Amount, angstrom_balance: Amount) -> bool {
+        self.token_approval <= approval
+            && self.token_delta <= balance
+            && self.angstrom_delta <= angstrom_balance
+    }
+}
+
+/// What the live orders of a user that spend a token commit of its balances.
+///
+/// Orders are counted in the order they are settled in, an order that doesn't
+/// fit in what the ones before it leave over is parked and doesn't count
+/// towards the exposure, so the committed amounts never exceed the balances.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Exposure {
+    pub approval:         Amount,
+    pub balance:          Amount,
+    pub angstrom_balance: Amount,
+    /// the orders past the cap
+    pub parked:           Vec<B256>
+}
+
+impl Exposure {
+    fn of<'a>(
+        baseline: &BaselineState,
+        token: TokenAddress,
+        actions: impl Iterator<Item = &'a PendingUserAction>
+    ) -> Option<Self> {
+        let approval = *baseline.token_approval.get(&token)?;
+        let balance = *baseline.token_balance.get(&token)?;
+        let angstrom_balance = *baseline.angstrom_balance.get(&token)?;
+
+        Some(actions.fold(Self::default(), |mut exposure, action| {
+            if action.fits_within(
+                approval - exposure.approval,
+                balance - exposure.balance,
+                angstrom_balance - exposure.angstrom_balance
+            ) {
+                exposure.approval += action.token_approval;
+                exposure.balance += action.token_delta;
+                exposure.angstrom_balance += action.angstrom_delta;
+            } else {
+                exposure.parked.push(action.order_hash);
+            }
+
+            exposure
+        }))
+    }
+}
+
 pub struct UserAccounts {
     /// all of a user addresses pending orders.
     pending_actions: Arc<DashMap<UserAddress, Vec<PendingUserAction>>>,
@@ -99,20 +155,68 @@ impl UserAccounts {
         }
     }
 
-    pub fn new_block(&self, users: Vec<Address>, orders: Vec<B256>) {
+    pub fn new_block(&self, block: u64, users: Vec<Address>, orders: Vec<B256>) {
         // remove all user specific orders
         users.iter().for_each(|user| {
             self.pending_actions.remove(user);
             self.last_known_state.remove(user);
         });
 
-        // remove all singular orders
+        // remove all singular orders, and the orders for a block that passed, which
+        // can't be settled anymore
         self.pending_actions.retain(|_, pending_orders| {
-            pending_orders.retain(|p| !orders.contains(&p.order_hash));
+            pending_orders.retain(|p| {
+                !orders.contains(&p.order_hash)
+                    && !matches!(p.respend, RespendAvoidanceMethod::Block(b) if b <= block)
+            });
             !pending_orders.is_empty()
         });
     }
 
+    /// The users with pending orders along with the tokens those orders
+    /// spend.
+    pub fn pending_accounts(&self) -> HashSet<Address> {
+        self.pending_actions
+            .iter()
+            .flat_map(|entry| {
+                std::iter::once(*entry.key())
+                    .chain(entry.value().iter().map(|action| action.token_address))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// The start of block funds of every user whose balances were loaded.
+    pub fn start_balances(&self) -> UserBalances {
+        self.last_known_state
+            .iter()
+            .flat_map(|entry| {
+                let user = *entry.key();
+                let baseline = entry.value();
+                baseline
+                    .token_balance
+                    .iter()
+                    .map(|(token, balance)| {
+                        let funds = UserBalance {
+                            balance:  *balance,
+                            approval: baseline
+                                .token_approval
+                                .get(token)
+                                .copied()
+                                .unwrap_or_default(),
+                            angstrom: baseline
+                                .angstrom_balance
+                                .get(token)
+                                .copied()
+                                .unwrap_or_default()
+                        };
+                        ((user, *token), funds)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
     /// returns true if the order cancel has been processed successfully
     pub fn cancel_order(&self, user: &UserAddress, order_hash: &B256) -> bool {
         let Some(mut inner_orders) = self.pending_actions.get_mut(user) else { return false };
@@ -127,25 +231,41 @@ impl UserAccounts {
         res
     }
 
+    /// The nonces of the user's pending orders.
+    pub fn pending_nonces(&self, user: UserAddress) -> HashSet<u64> {
+        self.pending_actions
+            .get(&user)
+            .map(|actions| {
+                actions
+                    .value()
+                    .iter()
+                    .filter_map(|action| match action.respend {
+                        RespendAvoidanceMethod::Nonce(nonce) => Some(nonce),
+                        RespendAvoidanceMethod::Block(_) => None
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The pending orders of the user that use the same nonce, or are for the
+    /// same block, as `avoidance`. A signer gets one order per block, the same
+    /// way it gets one per nonce.
     pub fn respend_conflicts(
         &self,
         user: UserAddress,
         avoidance: RespendAvoidanceMethod
     ) -> Vec<PendingUserAction> {
-        match avoidance {
-            nonce @ RespendAvoidanceMethod::Nonce(_) => self
-                .pending_actions
-                .get(&user)
-                .map(|v| {
-                    v.value()
-                        .iter()
-                        .filter(|pending_order| pending_order.respend == nonce)
-                        .cloned()
-                        .collect()
-                })
-                .unwrap_or_default(),
-            RespendAvoidanceMethod::Block(_) => Vec::new()
-        }
+        self.pending_actions
+            .get(&user)
+            .map(|v| {
+                v.value()
+                    .iter()
+                    .filter(|pending_order| pending_order.respend == avoidance)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
     }
 
     pub fn get_live_state_for_order<S: StateFetchUtils>(
@@ -181,7 +301,9 @@ impl UserAccounts {
         // override as fresh query
         entry.token_balance.insert(token, balances);
         entry.token_approval.insert(token, approvals);
-        entry.angstrom_balance.insert(token, balances);
+        entry
+            .angstrom_balance
+            .insert(token, utils.fetch_token_balance_in_angstrom(user, token));
     }
 
     /// inserts the user action and returns all pending user action hashes that
@@ -205,42 +327,22 @@ impl UserAccounts {
     }
 
     fn fetch_all_invalidated_orders(&self, user: UserAddress, token: TokenAddress) -> Vec<B256> {
-        let Some(baseline) = self.last_known_state.get(&user) else { return vec![] };
-
-        let mut baseline_approval = *baseline.token_approval.get(&token).unwrap();
-        let mut baseline_balance = *baseline.token_balance.get(&token).unwrap();
-        let mut baseline_angstrom_balance = *baseline.angstrom_balance.get(&token).unwrap();
-        let mut has_overflowed = false;
+        self.exposure(user, token)
+            .map(|exposure| exposure.parked)
+            .unwrap_or_default()
+    }
 
-        let mut bad = vec![];
-        for pending_state in self
-            .pending_actions
-            .get(&user)
-            .unwrap()
+    /// The aggregate exposure of all of the user's live orders spending
+    /// `token`, none if the user's balances haven't been loaded.
+    pub fn exposure(&self, user: UserAddress, token: TokenAddress) -> Option<Exposure> {
+        let baseline = self.last_known_state.get(&user)?;
+        let pending = self.pending_actions.get(&user);
+        let actions = pending
             .iter()
-            .filter(|state| state.token_address == token)
-        {
-            let (baseline, overflowed) =
-                baseline_approval.overflowing_sub(pending_state.token_approval);
-            has_overflowed |= overflowed;
-            baseline_approval = baseline;
-
-            let (baseline, overflowed) =
-                baseline_balance.overflowing_sub(pending_state.token_delta);
-            has_overflowed |= overflowed;
-            baseline_balance = baseline;
-
-            let (baseline, overflowed) =
-                baseline_angstrom_balance.overflowing_sub(pending_state.angstrom_delta);
-            has_overflowed |= overflowed;
-            baseline_angstrom_balance = baseline;
-
-            // mark for removal
-            if has_overflowed {
-                bad.push(pending_state.order_hash);
-            }
-        }
-        bad
+            .flat_map(|actions| actions.iter())
+            .filter(|action| action.token_address == token);
+
+        Exposure::of(&baseline, token, actions)
     }
 
     /// for the given user and token_in, and nonce, will return none
@@ -257,33 +359,22 @@ impl UserAccounts {
         let baseline_balance = *baseline.token_balance.get(&token)?;
         let baseline_angstrom_balance = *baseline.angstrom_balance.get(&token)?;
 
-        // the values returned here are the negative delta compaired to baseline.
-        let (pending_approvals_spend, pending_balance_spend, pending_angstrom_balance_spend) = self
-            .pending_actions
-            .get(&user)
-            .map(|val| {
-                val.iter()
-                    .filter(|state| state.token_address == token)
-                    .take_while(|state| {
-                        state.respend.get_ord_for_pending_orders()
-                            <= respend.get_ord_for_pending_orders()
-                    })
-                    .fold(
-                        (Amount::default(), Amount::default(), Amount::default()),
-                        |(mut approvals_spend, mut balance_spend, mut angstrom_spend), x| {
-                            approvals_spend += x.token_approval;
-                            balance_spend += x.token_delta;
-                            angstrom_spend += x.angstrom_delta;
-                            (approvals_spend, balance_spend, angstrom_spend)
-                        }
-                    )
-            })
-            .unwrap_or_default();
+        // what the orders settled before this one commit, the orders parked past the
+        // cap don't take away from it.
+        let pending = self.pending_actions.get(&user);
+        let actions = pending
+            .iter()
+            .flat_map(|actions| actions.iter())
+            .filter(|state| state.token_address == token)
+            .take_while(|state| {
+                state.respend.get_ord_for_pending_orders() <= respend.get_ord_for_pending_orders()
+            });
+        let exposure = Exposure::of(&baseline, token, actions)?;
 
-        let live_approval = baseline_approval.saturating_sub(pending_approvals_spend);
-        let live_balance = baseline_balance.saturating_sub(pending_balance_spend);
+        let live_approval = baseline_approval.saturating_sub(exposure.approval);
+        let live_balance = baseline_balance.saturating_sub(exposure.balance);
         let live_angstrom_balance =
-            baseline_angstrom_balance.saturating_sub(pending_angstrom_balance_spend);
+            baseline_angstrom_balance.saturating_sub(exposure.angstrom_balance);
 
         Some(LiveState {
             token,
@@ -343,7 +434,7 @@ mod tests {
         assert!(accounts.pending_actions.contains_key(&user2));
 
         // Call new_block to clear specific users and orders
-        accounts.new_block(vec![user1], vec![action2.order_hash]);
+        accounts.new_block(1, vec![user1], vec![action2.order_hash]);
 
         // Verify user1's actions are cleared
         assert!(!accounts.pending_actions.contains_key(&user1));
@@ -483,10 +574,49 @@ mod tests {
         assert!(invalidated.contains(&action2.order_hash));
     }
 
+    #[test]
+    fn test_exposure_skips_orders_past_the_cap() {
+        let accounts = setup_test_accounts();
+        let user = address!("1234567890123456789012345678901234567890");
+        let token = address!("deadbeefdeadbeefdeadbeefdeadbeefdeadbeef");
+        assert!(accounts.exposure(user, token).is_none());
+
+        let mut baseline = BaselineState::default();
+        baseline.token_approval.insert(token, U256::from(1000));
+        baseline.token_balance.insert(token, U256::from(1000));
+        baseline.angstrom_balance.insert(token, U256::from(1000));
+        accounts.last_known_state.insert(user, baseline);
+
+        let action1 =
+            create_test_pending_action(token, U256::from(600), U256::from(0), U256::from(600), 1);
+        let action2 =
+            create_test_pending_action(token, U256::from(500), U256::from(0), U256::from(500), 2);
+        let action3 =
+            create_test_pending_action(token, U256::from(300), U256::from(0), U256::from(300), 3);
+
+        accounts.insert_pending_user_action(user, action1);
+        let invalidated = accounts.insert_pending_user_action(user, action2.clone());
+        assert_eq!(invalidated, vec![action2.order_hash]);
+
+        // the parked order doesn't count, so a smaller one still fits
+        let invalidated = accounts.insert_pending_user_action(user, action3);
+        assert_eq!(invalidated, vec![action2.order_hash]);
+
+        let exposure = accounts.exposure(user, token).unwrap();
+        assert_eq!(exposure.balance, U256::from(900));
+        assert_eq!(exposure.approval, U256::from(900));
+        assert_eq!(exposure.parked, vec![action2.order_hash]);
+
+        let live_state = accounts
+            .try_fetch_live_pending_state(user, token, RespendAvoidanceMethod::Nonce(4))
+            .unwrap();
+        assert_eq!(live_state.balance, U256::from(100));
+    }
+
     #[test]
     fn test_new_block_with_empty_state() {
         let accounts = setup_test_accounts();
-        accounts.new_block(vec![], vec![]);
+        accounts.new_block(1, vec![], vec![]);
         assert!(accounts.pending_actions.is_empty());
         assert!(accounts.last_known_state.is_empty());
     }
@@ -512,6 +642,31 @@ mod tests {
         assert!(conflicts.is_empty());
     }
 
+    #[test]
+    fn test_block_orders_conflict_until_their_block_passes() {
+        let accounts = setup_test_accounts();
+        let user = address!("1234567890123456789012345678901234567890");
+        let token = address!("deadbeefdeadbeefdeadbeefdeadbeefdeadbeef");
+
+        let flash = PendingUserAction {
+            respend: RespendAvoidanceMethod::Block(11),
+            ..create_test_pending_action(token, U256::from(100), U256::ZERO, U256::from(100), 0)
+        };
+        accounts.insert_pending_user_action(user, flash.clone());
+
+        let conflicts = accounts.respend_conflicts(user, RespendAvoidanceMethod::Block(11));
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].order_hash, flash.order_hash);
+        assert!(accounts
+            .respend_conflicts(user, RespendAvoidanceMethod::Block(12))
+            .is_empty());
+
+        accounts.new_block(10, vec![], vec![]);
+        assert!(accounts.pending_actions.contains_key(&user));
+        accounts.new_block(11, vec![], vec![]);
+        assert!(!accounts.pending_actions.contains_key(&user));
+    }
+
     #[test]
     fn test_live_state_with_multiple_tokens() {
         let accounts = setup_test_accounts();
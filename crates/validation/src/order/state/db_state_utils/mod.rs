@@ -4,7 +4,7 @@ pub mod nonces;
 
 pub mod finders;
 
-use std::{collections::HashMap, fmt::Debug, sync::Arc};
+use std::{collections::HashMap, fmt::Debug, ops::Range, sync::Arc};
 
 use alloy::primitives::{Address, U256};
 use angstrom_metrics::validation::ValidationMetrics;
@@ -14,6 +14,13 @@ use self::{approvals::Approvals, balances::Balances, nonces::Nonces};
 pub trait StateFetchUtils: Clone + Send + Unpin {
     fn is_valid_nonce(&self, user: Address, nonce: u64) -> bool;
 
+    /// The nonces in `range` that can't be used anymore.
+    fn invalidated_nonces(&self, user: Address, range: Range<u64>) -> Vec<u64> {
+        range
+            .filter(|nonce| !self.is_valid_nonce(user, *nonce))
+            .collect()
+    }
+
     fn fetch_approval_balance_for_token_overrides(
         &self,
         user: Address,
@@ -65,6 +72,10 @@ where
         self.nonces.is_valid_nonce(user, nonce, db)
     }
 
+    fn invalidated_nonces(&self, user: Address, range: Range<u64>) -> Vec<u64> {
+        self.nonces.invalidated_nonces(user, range, self.db.clone())
+    }
+
     fn fetch_approval_balance_for_token_overrides(
         &self,
         user: Address,
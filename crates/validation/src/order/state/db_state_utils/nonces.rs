@@ -1,4 +1,4 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, ops::Range, sync::Arc};
 
 use alloy::primitives::{hex, keccak256, Address, B256, U256};
 use reth_revm::DatabaseRef;
@@ -42,4 +42,35 @@ impl Nonces {
         tracing::debug!(?word, %out);
         out
     }
+
+    /// The nonces in `range` that were used or invalidated on chain. Reads one
+    /// bitmap word per 256 nonces.
+    pub fn invalidated_nonces<DB: revm::DatabaseRef>(
+        &self,
+        user: Address,
+        range: Range<u64>,
+        db: Arc<DB>
+    ) -> Vec<u64>
+    where
+        <DB as DatabaseRef>::Error: Sync + Send + 'static + Debug
+    {
+        let mut invalidated = Vec::new();
+        let mut word_start = range.start & !0xff;
+        while word_start < range.end {
+            let slot = self.get_nonce_word_slot(user, word_start);
+            let word = db.storage_ref(self.0, slot.into()).unwrap();
+            if !word.is_zero() {
+                invalidated.extend(
+                    (word_start.max(range.start)..(word_start + 256).min(range.end))
+                        .filter(|nonce| word.bit((nonce & 0xff) as usize))
+                );
+            }
+            word_start = match word_start.checked_add(256) {
+                Some(next) => next,
+                None => break
+            };
+        }
+
+        invalidated
+    }
 }
@@ -1,15 +1,19 @@
-use std::sync::Arc;
+use std::{collections::HashSet, ops::Range, sync::Arc};
 
 use account::UserAccountProcessor;
-use alloy::primitives::{Address, B256};
-use angstrom_metrics::validation::ValidationMetrics;
-use angstrom_types::sol_bindings::{
-    ext::RawPoolOrder, grouped_orders::AllOrders, rpc_orders::TopOfBlockOrder
+use alloy::{
+    primitives::{Address, B256},
+    sol_types::Eip712Domain
+};
+use angstrom_metrics::validation::{ValidationMetrics, ValidationStage};
+use angstrom_types::{
+    contract_payloads::angstrom::UserBalances,
+    primitive::{ValidationError, ANGSTROM_DOMAIN, NATIVE_ETH},
+    sol_bindings::{ext::RawPoolOrder, grouped_orders::AllOrders}
 };
 use db_state_utils::StateFetchUtils;
 use parking_lot::RwLock;
 use pools::PoolsTracker;
-use uniswap_v4::uniswap::pool_manager::SyncedUniswapPools;
 
 use super::OrderValidationResults;
 
@@ -29,8 +33,8 @@ pub struct StateValidation<Pools, Fetch> {
     user_account_tracker: Arc<UserAccountProcessor<Fetch>>,
     /// tracks all info about the current angstrom pool state.
     pool_tacker:          Arc<RwLock<Pools>>,
-    /// keeps up-to-date with the on-chain pool
-    uniswap_pools:        SyncedUniswapPools
+    /// the domain orders have to be signed under
+    domain:               Eip712Domain
 }
 
 impl<Pools, Fetch> Clone for StateValidation<Pools, Fetch> {
@@ -38,27 +42,51 @@ impl<Pools, Fetch> Clone for StateValidation<Pools, Fetch> {
         Self {
             user_account_tracker: Arc::clone(&self.user_account_tracker),
             pool_tacker:          Arc::clone(&self.pool_tacker),
-            uniswap_pools:        self.uniswap_pools.clone()
+            domain:               self.domain.clone()
         }
     }
 }
 
 impl<Pools: PoolsTracker, Fetch: StateFetchUtils> StateValidation<Pools, Fetch> {
-    pub fn new(
-        user_account_tracker: UserAccountProcessor<Fetch>,
-        pools: Pools,
-        uniswap_pools: SyncedUniswapPools
-    ) -> Self {
+    pub fn new(user_account_tracker: UserAccountProcessor<Fetch>, pools: Pools) -> Self {
         Self {
-            pool_tacker: Arc::new(RwLock::new(pools)),
+            pool_tacker:          Arc::new(RwLock::new(pools)),
             user_account_tracker: Arc::new(user_account_tracker),
-            uniswap_pools
+            domain:               ANGSTROM_DOMAIN
         }
     }
 
-    pub fn new_block(&self, completed_orders: Vec<B256>, address_changes: Vec<Address>) {
+    pub fn with_domain(mut self, domain: Eip712Domain) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    pub fn new_block(
+        &self,
+        block: u64,
+        completed_orders: Vec<B256>,
+        address_changes: Vec<Address>
+    ) {
         self.user_account_tracker
-            .prepare_for_new_block(address_changes, completed_orders)
+            .prepare_for_new_block(block, address_changes, completed_orders)
+    }
+
+    /// The accounts read by the orders that are still pending.
+    pub fn pending_accounts(&self) -> HashSet<Address> {
+        self.user_account_tracker.pending_accounts()
+    }
+
+    /// The start of block funds of the users with pending orders.
+    pub fn start_balances(&self) -> UserBalances {
+        self.user_account_tracker.start_balances()
+    }
+
+    pub fn next_valid_nonce(&self, user: Address) -> u64 {
+        self.user_account_tracker.next_valid_nonce(user)
+    }
+
+    pub fn invalidated_nonces(&self, user: Address, range: Range<u64>) -> Vec<u64> {
+        self.user_account_tracker.invalidated_nonces(user, range)
     }
 
     pub fn handle_regular_order<O: RawPoolOrder + Into<AllOrders>>(
@@ -66,21 +94,76 @@ impl<Pools: PoolsTracker, Fetch: StateFetchUtils> StateValidation<Pools, Fetch>
         order: O,
         block: u64,
         metrics: ValidationMetrics
+    ) -> OrderValidationResults {
+        self.handle_order(order, None, block, metrics, true)
+    }
+
+    /// Validates `order` in place of the pending order `replaces`, which it
+    /// amends.
+    pub fn handle_amended_order<O: RawPoolOrder + Into<AllOrders>>(
+        &self,
+        order: O,
+        replaces: B256,
+        block: u64,
+        metrics: ValidationMetrics
+    ) -> OrderValidationResults {
+        self.handle_order(order, Some(replaces), block, metrics, true)
+    }
+
+    /// Validates `order` like a new order without keeping it, the pending
+    /// state of its user is left as is.
+    pub fn simulate_order<O: RawPoolOrder + Into<AllOrders>>(
+        &self,
+        order: O,
+        block: u64,
+        metrics: ValidationMetrics
+    ) -> OrderValidationResults {
+        self.handle_order(order, None, block, metrics, false)
+    }
+
+    fn handle_order<O: RawPoolOrder + Into<AllOrders>>(
+        &self,
+        order: O,
+        replaces: Option<B256>,
+        block: u64,
+        metrics: ValidationMetrics,
+        commit: bool
     ) -> OrderValidationResults {
         metrics.applying_state_transitions(|| {
             let order_hash = order.order_hash();
-            if !order.is_valid_signature() {
-                tracing::debug!("order had invalid hash");
-                return OrderValidationResults::Invalid(order_hash)
+            let tokens = [order.token_in(), order.token_out()];
+            let reject = |error: ValidationError, pool_id| {
+                metrics.rejected(&error, pool_id, &tokens);
+                OrderValidationResults::Invalid(order_hash, error)
+            };
+
+            if !metrics.stage(ValidationStage::Signature, || order.is_valid_signature(&self.domain))
+            {
+                tracing::debug!(
+                    chain_id = ?self.domain.chain_id,
+                    verifying_contract = ?self.domain.verifying_contract,
+                    "order isn't signed by its sender for this chain and contract, it may have \
+                     been signed for another chain"
+                );
+                return reject(ValidationError::InvalidSignature, None)
+            }
+
+            if order.token_in() == NATIVE_ETH || order.token_out() == NATIVE_ETH {
+                tracing::debug!("order uses native ETH, which can't be settled. WETH is required");
+                return reject(ValidationError::NativeEth, None)
             }
 
             let Some(pool_info) = self.pool_tacker.read().fetch_pool_info_for_order(&order) else {
                 tracing::debug!("order requested a invalid pool");
-                return OrderValidationResults::Invalid(order_hash);
+                return reject(ValidationError::UnknownPool, None);
             };
 
-            self.user_account_tracker
-                .verify_order::<O>(order, pool_info, block)
+            let pool_id = pool_info.pool_id;
+            metrics
+                .stage(ValidationStage::State, || {
+                    self.user_account_tracker
+                        .verify::<O>(order, replaces, pool_info, block, commit)
+                })
                 .map(|o: _| {
                     OrderValidationResults::Valid(
                         o.try_map_inner(|inner| Ok(inner.into())).unwrap()
@@ -88,37 +171,8 @@ impl<Pools: PoolsTracker, Fetch: StateFetchUtils> StateValidation<Pools, Fetch>
                 })
                 .unwrap_or_else(|e| {
                     tracing::debug!(%e,"user acount tracker failed to validate order");
-                    OrderValidationResults::Invalid(order_hash)
+                    reject(e.into(), Some(pool_id))
                 })
         })
     }
-
-    pub async fn handle_tob_order(
-        &self,
-        order: TopOfBlockOrder,
-        block: u64,
-        metrics: ValidationMetrics
-    ) -> OrderValidationResults {
-        let mut results = self.handle_regular_order(order, block, metrics);
-
-        if let OrderValidationResults::Valid(ref mut order_with_storage) = results {
-            let tob_order = order_with_storage
-                .clone()
-                .try_map_inner(|inner| {
-                    let AllOrders::TOB(order) = inner else { eyre::bail!("unreachable") };
-                    Ok(order)
-                })
-                .expect("should be unreachable");
-            let pool_address = order_with_storage.pool_id;
-            let rewards = self
-                .uniswap_pools
-                .calculate_rewards(pool_address, &tob_order)
-                .await
-                .unwrap();
-
-            order_with_storage.tob_reward = rewards.total_reward;
-        }
-
-        results
-    }
 }
@@ -6,7 +6,8 @@ use alloy::primitives::{
 };
 use angstrom_types::{
     contract_bindings::angstrom::Angstrom::PoolKey,
-    contract_payloads::angstrom::AngstromPoolConfigStore, primitive::PoolId,
+    contract_payloads::angstrom::AngstromPoolConfigStore,
+    primitive::{PoolId, TokenPair},
     sol_bindings::ext::RawPoolOrder
 };
 
@@ -35,15 +36,14 @@ impl AngstromPoolsTracker {
         Self { angstrom_address, pool_store }
     }
 
-    pub fn get_poolid(&self, mut addr1: Address, mut addr2: Address) -> Option<PoolId> {
-        let store = self.pool_store.get_entry(addr1, addr2)?;
-        if addr2 < addr1 {
-            std::mem::swap(&mut addr1, &mut addr2)
-        };
+    /// The pool of the two tokens, given in either order.
+    pub fn get_poolid(&self, addr1: Address, addr2: Address) -> Option<PoolId> {
+        let pair = TokenPair::try_new(addr1, addr2)?;
+        let store = self.pool_store.get_entry(pair.token0(), pair.token1())?;
 
         Some(PoolId::from(PoolKey {
-            currency0:   addr1,
-            currency1:   addr2,
+            currency0:   pair.token0(),
+            currency1:   pair.token1(),
             tickSpacing: I24::from_limbs([store.tick_spacing as u64]),
             hooks:       self.angstrom_address,
             fee:         U24::from_limbs([store.fee_in_e6 as u64])
@@ -52,20 +52,13 @@ impl AngstromPoolsTracker {
 
     pub fn order_info(
         &self,
-        mut currency_in: Address,
-        mut currency_out: Address
+        currency_in: Address,
+        currency_out: Address
     ) -> Option<(bool, PoolId)> {
         // Uniswap pools are priced as t1/t0 - the order is a bid if it's offering t1 to
-        // get t0.   Uniswap standard has the token addresses sorted and t0 is the
-        // lower of the two, therefore if the currency_in is the higher of the two we
-        // know it's t1 and therefore this order is a bid.
-        let is_bid = currency_in > currency_out;
-
-        if currency_in > currency_out {
-            std::mem::swap(&mut currency_in, &mut currency_out)
-        };
-
-        let key = self.get_poolid(currency_in, currency_out)?;
+        // get t0, see `TokenPair`
+        let (pair, is_bid) = TokenPair::for_order(currency_in, currency_out)?;
+        let key = self.get_poolid(pair.token0(), pair.token1())?;
 
         Some((is_bid, key))
     }
@@ -109,11 +102,10 @@ pub mod pool_tracker_mock {
         ) -> Option<UserOrderPoolInfo> {
             let pool_id = self.pools.get(&(order.token_in(), order.token_out()))?;
 
-            let user_info = UserOrderPoolInfo {
-                pool_id: *pool_id,
-                is_bid:  order.token_in() > order.token_out(),
-                token:   order.token_in()
-            };
+            let is_bid = TokenPair::for_order(order.token_in(), order.token_out())
+                .is_some_and(|(_, is_bid)| is_bid);
+            let user_info =
+                UserOrderPoolInfo { pool_id: *pool_id, is_bid, token: order.token_in() };
 
             Some(user_info)
         }
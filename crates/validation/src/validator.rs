@@ -1,4 +1,4 @@
-use std::{fmt::Debug, task::Poll};
+use std::{fmt::Debug, ops::Range, sync::Arc, task::Poll};
 
 use alloy::primitives::{Address, B256};
 use angstrom_types::contract_payloads::angstrom::{AngstromBundle, BundleGasDetails};
@@ -7,7 +7,7 @@ use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 use crate::{
     bundle::BundleValidator,
-    common::SharedTools,
+    common::{SharedTools, StatePrefetch},
     order::{
         order_validator::OrderValidator,
         state::{db_state_utils::StateFetchUtils, pools::PoolsTracker},
@@ -29,6 +29,17 @@ pub enum ValidationRequest {
         block_number: u64,
         orders:       Vec<B256>,
         addresses:    Vec<Address>
+    },
+    /// the lowest nonce a new order of `user` can use
+    NextValidNonce {
+        sender: tokio::sync::oneshot::Sender<u64>,
+        user:   Address
+    },
+    /// the nonces in `range` that `user` used or invalidated on chain
+    InvalidatedNonces {
+        sender: tokio::sync::oneshot::Sender<Vec<u64>>,
+        user:   Address,
+        range:  Range<u64>
     }
 }
 
@@ -39,7 +50,8 @@ pub struct Validator<DB, Pools, Fetch> {
     rx:               UnboundedReceiver<ValidationRequest>,
     order_validator:  OrderValidator<DB, Pools, Fetch>,
     bundle_validator: BundleValidator<DB>,
-    utils:            SharedTools
+    utils:            SharedTools,
+    prefetch:         Option<Arc<dyn StatePrefetch>>
 }
 
 impl<DB, Pools, Fetch> Validator<DB, Pools, Fetch>
@@ -55,7 +67,15 @@ where
         bundle_validator: BundleValidator<DB>,
         utils: SharedTools
     ) -> Self {
-        Self { order_validator, rx, utils, bundle_validator }
+        Self { order_validator, rx, utils, bundle_validator, prefetch: None }
+    }
+
+    /// Warms up the state of the pending orders on every new block, off the
+    /// validation task. The cache has to be invalidated on canonical state
+    /// changes by the caller, see [`StatePrefetch::invalidate`].
+    pub fn with_state_prefetch(mut self, prefetch: Arc<dyn StatePrefetch>) -> Self {
+        self.prefetch = Some(prefetch);
+        self
     }
 
     fn on_new_validation_request(&mut self, req: ValidationRequest) {
@@ -75,6 +95,7 @@ where
                 self.bundle_validator.simulate_bundle(
                     sender,
                     bundle,
+                    self.order_validator.start_balances(),
                     &self.utils.token_pricing,
                     &mut self.utils.thread_pool,
                     self.utils.metrics.clone(),
@@ -87,10 +108,20 @@ where
                     self.order_validator
                         .on_new_block(block_number, orders, addresses);
                 });
+                if let Some(prefetch) = self.prefetch.clone() {
+                    let accounts = self.order_validator.pending_accounts();
+                    tokio::task::spawn_blocking(move || prefetch.prefetch_for_new_block(accounts));
+                }
                 sender
                     .send(OrderValidationResults::TransitionedToBlock)
                     .unwrap();
             }
+            ValidationRequest::NextValidNonce { sender, user } => {
+                let _ = sender.send(self.order_validator.next_valid_nonce(user));
+            }
+            ValidationRequest::InvalidatedNonces { sender, user, range } => {
+                let _ = sender.send(self.order_validator.invalidated_nonces(user, range));
+            }
         }
     }
 }
@@ -108,6 +139,9 @@ where
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>
     ) -> std::task::Poll<Self::Output> {
+        // canonical state changes are taken in before the requests, so none of
+        // them reads the state cached for the block before
+        let _ = self.utils.poll_unpin(cx);
         while let Poll::Ready(Some(req)) = self.rx.poll_recv(cx) {
             self.on_new_validation_request(req);
         }
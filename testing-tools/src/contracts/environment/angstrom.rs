@@ -194,7 +194,7 @@ mod tests {
             mintable_mock_erc_20::MintableMockERC20,
             pool_gate::PoolGate::PoolGateInstance
         },
-        contract_payloads::angstrom::{AngstromBundle, BundleGasDetails, UserOrder},
+        contract_payloads::angstrom::{AngstromBundle, BundleFees, BundleGasDetails, UserOrder},
         matching::{uniswap::LiqRange, SqrtPriceX96},
         orders::{OrderFillState, OrderOutcome},
         primitive::{AngstromSigner, ANGSTROM_DOMAIN},
@@ -375,10 +375,15 @@ mod tests {
             .build();
         println!("Proposal solutions:\n{:?}", proposal.solutions);
         let pools = HashMap::from([(pool.id(), (pool.token0(), pool.token1(), amm, 0))]);
-        let bundle =
-            AngstromBundle::from_proposal(&proposal, BundleGasDetails::default(), &pools).unwrap();
+        let bundle = AngstromBundle::from_proposal(
+            &proposal,
+            BundleGasDetails::default(),
+            &pools,
+            &BundleFees::default()
+        )
+        .unwrap();
         println!("Bundle: {:?}", bundle);
-        let encoded = bundle.pade_encode();
+        let encoded = bundle.pade_encode_for_submission();
 
         angstrom.toggleNodes(nodes).run_safe().await.unwrap();
         angstrom
@@ -21,6 +21,7 @@ use futures::Future;
 pub mod anvil;
 pub mod deploy;
 pub mod environment;
+pub mod revm_env;
 
 /// This trait is used to provide safe run and potentially debug capabilities
 /// for our local contract runs.
@@ -0,0 +1,470 @@
+//! An Angstrom deployment in an in memory revm database. Unlike the anvil
+//! environments this needs no external process, so bundles can be simulated
+//! against the compiled contracts in any test.
+use std::{pin::Pin, sync::Arc};
+
+use alloy::{
+    hex,
+    primitives::{
+        address,
+        aliases::{I24, U24},
+        keccak256, Address, Bytes, FixedBytes, Log, TxKind, B256, U160, U256
+    },
+    sol_types::{SolCall, SolValue}
+};
+use angstrom_metrics::validation::ValidationMetrics;
+use angstrom_types::{
+    contract_bindings::{
+        angstrom::Angstrom, mintable_mock_erc_20::MintableMockERC20, pool_gate::PoolGate,
+        pool_manager::PoolManager
+    },
+    contract_payloads::angstrom::{AngstromBundle, BundleFees, BundleGasDetails, UserBalances},
+    matching::{uniswap::UniswapFlags, SqrtPriceX96},
+    primitive::TESTNET_ANGSTROM_ADDRESS
+};
+use eyre::{bail, eyre};
+use futures::{Future, StreamExt};
+use reth_chainspec::ChainInfo;
+use reth_provider::{BlockHashReader, BlockNumReader, ProviderResult};
+use revm::{
+    db::{CacheDB, EmptyDB},
+    primitives::{AccountInfo, Bytecode, ExecutionResult},
+    DatabaseRef, Evm
+};
+use tokio::runtime::Handle;
+use validation::{
+    bundle::BundleValidator,
+    common::{key_split_threadpool::KeySplitThreadpool, TokenPriceGenerator}
+};
+
+/// Deploys the contracts and controls them.
+pub const DEPLOYER: Address = address!("aa250d5630b4cf539739df2c5dacb4c659f2488d");
+/// The deterministic deployment proxy, Angstrom's hook address is mined for
+/// it.
+const CREATE2_FACTORY: Address = address!("4e59b44847b379578588920cA78FbF26c0B4956C");
+const CREATE2_FACTORY_CODE: Bytes = Bytes::from_static(&hex!(
+    "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe03601600081602082378035828234f58015156039578182fd5b8082525050506014600cf3"
+));
+
+/// A revm database at a fixed chain head, which is all the bundle validator
+/// needs on top of the state.
+#[derive(Debug, Clone, Default)]
+pub struct RevmDb {
+    state: CacheDB<EmptyDB>,
+    block: u64
+}
+
+impl RevmDb {
+    /// Executes a transaction and commits its state changes. Fails if it
+    /// reverts.
+    fn transact(
+        &mut self,
+        caller: Address,
+        to: TxKind,
+        data: impl Into<Bytes>
+    ) -> eyre::Result<ExecutionResult> {
+        let block = U256::from(self.block);
+        let data = data.into();
+        let mut evm = Evm::builder()
+            .with_db(&mut self.state)
+            .modify_env(|env| {
+                env.cfg.disable_balance_check = true;
+                env.cfg.disable_block_gas_limit = true;
+                env.cfg.limit_contract_code_size = Some(usize::MAX - 1);
+            })
+            .modify_block_env(|env| env.number = block)
+            .modify_tx_env(|tx| {
+                tx.caller = caller;
+                tx.transact_to = to;
+                tx.data = data;
+            })
+            .build();
+
+        let result = evm
+            .transact_commit()
+            .map_err(|e| eyre!("failed to transact with revm - {e:?}"))?;
+        if !result.is_success() {
+            bail!("transaction reverted - {result:?}")
+        }
+
+        Ok(result)
+    }
+
+    fn call(&mut self, caller: Address, to: Address, call: impl SolCall) -> eyre::Result<()> {
+        self.transact(caller, TxKind::Call(to), call.abi_encode())
+            .map(|_| ())
+    }
+
+    fn deploy(&mut self, initcode: Vec<u8>) -> eyre::Result<Address> {
+        self.transact(DEPLOYER, TxKind::Create, initcode)?
+            .output()
+            .and_then(|output| output.address().copied())
+            .ok_or_else(|| eyre!("contract creation returned no address"))
+    }
+
+    /// Moves the code and storage of the contract at `from` to `to`.
+    fn relocate(&mut self, from: Address, to: Address) -> eyre::Result<()> {
+        let account = self
+            .state
+            .accounts
+            .remove(&from)
+            .ok_or_else(|| eyre!("no contract at {from}"))?;
+
+        self.state.insert_account_info(to, account.info);
+        for (slot, value) in account.storage {
+            self.state.insert_account_storage(to, slot, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl DatabaseRef for RevmDb {
+    type Error = <CacheDB<EmptyDB> as DatabaseRef>::Error;
+
+    fn basic_ref(&self, address: Address) -> Result<Option<AccountInfo>, Self::Error> {
+        self.state.basic_ref(address)
+    }
+
+    fn code_by_hash_ref(&self, code_hash: B256) -> Result<Bytecode, Self::Error> {
+        self.state.code_by_hash_ref(code_hash)
+    }
+
+    fn storage_ref(&self, address: Address, index: U256) -> Result<U256, Self::Error> {
+        self.state.storage_ref(address, index)
+    }
+
+    fn block_hash_ref(&self, number: u64) -> Result<B256, Self::Error> {
+        self.state.block_hash_ref(number)
+    }
+}
+
+impl BlockHashReader for RevmDb {
+    fn block_hash(&self, number: u64) -> ProviderResult<Option<B256>> {
+        Ok(self.state.block_hash_ref(number).ok())
+    }
+
+    fn canonical_hashes_range(&self, start: u64, end: u64) -> ProviderResult<Vec<B256>> {
+        Ok((start..end)
+            .filter_map(|number| self.state.block_hash_ref(number).ok())
+            .collect())
+    }
+}
+
+impl BlockNumReader for RevmDb {
+    fn chain_info(&self) -> ProviderResult<ChainInfo> {
+        Ok(ChainInfo {
+            best_hash:   self.block_hash(self.block)?.unwrap_or_default(),
+            best_number: self.block
+        })
+    }
+
+    fn best_block_number(&self) -> ProviderResult<u64> {
+        Ok(self.block)
+    }
+
+    fn last_block_number(&self) -> ProviderResult<u64> {
+        Ok(self.block)
+    }
+
+    fn block_number(&self, hash: B256) -> ProviderResult<Option<u64>> {
+        Ok((self.block_hash(self.block)? == Some(hash)).then_some(self.block))
+    }
+}
+
+/// Uniswap's pool manager and Angstrom, deployed into a [`RevmDb`].
+///
+/// Angstrom is placed at [`TESTNET_ANGSTROM_ADDRESS`], the address orders are
+/// signed for.
+#[derive(Debug, Clone)]
+pub struct RevmAngstromEnv {
+    db:           RevmDb,
+    pool_manager: Address,
+    pool_gate:    Address
+}
+
+impl RevmAngstromEnv {
+    /// Deploys the contracts at `block`, with `nodes` allowed to submit
+    /// bundles.
+    pub fn new(nodes: Vec<Address>, block: u64) -> eyre::Result<Self> {
+        let mut db = RevmDb { block, ..Default::default() };
+        db.state.insert_account_info(
+            CREATE2_FACTORY,
+            AccountInfo {
+                code: Some(Bytecode::new_raw(CREATE2_FACTORY_CODE)),
+                ..Default::default()
+            }
+        );
+
+        let pool_manager =
+            db.deploy([&PoolManager::BYTECODE[..], &DEPLOYER.abi_encode()[..]].concat())?;
+        let pool_gate =
+            db.deploy([&PoolGate::BYTECODE[..], &pool_manager.abi_encode()[..]].concat())?;
+
+        // the constructor checks the hook flags of the address it is deployed to
+        let initcode: Bytes =
+            [&Angstrom::BYTECODE[..], &(pool_manager, DEPLOYER).abi_encode_params()[..]]
+                .concat()
+                .into();
+        let (mined, salt) = mine_hook_address(&initcode);
+        db.transact(
+            DEPLOYER,
+            TxKind::Call(CREATE2_FACTORY),
+            [salt.abi_encode(), initcode.to_vec()].concat()
+        )?;
+        db.relocate(mined, TESTNET_ANGSTROM_ADDRESS)?;
+
+        db.call(DEPLOYER, TESTNET_ANGSTROM_ADDRESS, Angstrom::toggleNodesCall::new((nodes,)))?;
+        db.call(DEPLOYER, pool_gate, PoolGate::setHookCall::new((TESTNET_ANGSTROM_ADDRESS,)))?;
+
+        Ok(Self { db, pool_manager, pool_gate })
+    }
+
+    pub fn angstrom(&self) -> Address {
+        TESTNET_ANGSTROM_ADDRESS
+    }
+
+    pub fn pool_manager(&self) -> Address {
+        self.pool_manager
+    }
+
+    pub fn controller(&self) -> Address {
+        DEPLOYER
+    }
+
+    pub fn db(&self) -> &RevmDb {
+        &self.db
+    }
+
+    /// Deploys two mock tokens, returned sorted.
+    pub fn deploy_token_pair(&mut self) -> eyre::Result<(Address, Address)> {
+        let token_a = self.db.deploy(MintableMockERC20::BYTECODE.to_vec())?;
+        let token_b = self.db.deploy(MintableMockERC20::BYTECODE.to_vec())?;
+
+        Ok(if token_a < token_b { (token_a, token_b) } else { (token_b, token_a) })
+    }
+
+    /// Configures and initializes the pool of `token0` and `token1` at `price`,
+    /// with `liquidity` in between `lower_tick` and `upper_tick`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_pool(
+        &mut self,
+        token0: Address,
+        token1: Address,
+        tick_spacing: u16,
+        price: SqrtPriceX96,
+        lower_tick: i32,
+        upper_tick: i32,
+        liquidity: u128
+    ) -> eyre::Result<()> {
+        let angstrom = self.angstrom();
+        self.db.call(
+            DEPLOYER,
+            angstrom,
+            Angstrom::configurePoolCall::new((token0, token1, tick_spacing, U24::ZERO, U24::ZERO))
+        )?;
+        self.db.call(
+            DEPLOYER,
+            angstrom,
+            Angstrom::initializePoolCall::new((token0, token1, U256::ZERO, *price))
+        )?;
+
+        self.db.call(
+            DEPLOYER,
+            self.pool_gate,
+            PoolGate::tickSpacingCall::new((I24::unchecked_from(tick_spacing),))
+        )?;
+        self.db.call(
+            DEPLOYER,
+            self.pool_gate,
+            PoolGate::addLiquidityCall::new((
+                token0,
+                token1,
+                I24::unchecked_from(lower_tick),
+                I24::unchecked_from(upper_tick),
+                U256::from(liquidity),
+                FixedBytes::default()
+            ))
+        )
+    }
+
+    /// Mints `user` plenty of `token` and approves Angstrom to pull it.
+    pub fn fund(&mut self, user: Address, token: Address) -> eyre::Result<()> {
+        self.db.call(
+            DEPLOYER,
+            token,
+            MintableMockERC20::mintCall::new((user, U256::from(u128::MAX)))
+        )?;
+        self.db
+            .call(user, token, MintableMockERC20::approveCall::new((self.angstrom(), U256::MAX)))
+    }
+
+    /// Runs `bundle` through [`BundleValidator::simulate_bundle`] as `node`,
+    /// for the block after the one the environment is at.
+    pub async fn simulate_bundle(
+        &self,
+        node: Address,
+        bundle: AngstromBundle
+    ) -> eyre::Result<BundleGasDetails> {
+        let validator = BundleValidator::new(Arc::new(self.db.clone()), self.angstrom(), node);
+        let mut thread_pool: KeySplitThreadpool<
+            Address,
+            Pin<Box<dyn Future<Output = ()> + Send + Sync>>,
+            Handle
+        > = KeySplitThreadpool::new(Handle::current(), 1);
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        validator.simulate_bundle(
+            tx,
+            bundle,
+            UserBalances::default(),
+            &TokenPriceGenerator::default(),
+            &mut thread_pool,
+            ValidationMetrics::new(),
+            self.db.block
+        );
+        // the simulation only runs while the pool is polled
+        thread_pool.next().await;
+
+        rx.await?
+    }
+
+    /// Executes `bundle` as `node` on a copy of the state and returns the logs
+    /// it emitted.
+    pub fn execute_bundle(&self, node: Address, bundle: &AngstromBundle) -> eyre::Result<Vec<Log>> {
+        let mut db = self.db.clone();
+        db.block += 1;
+        let call =
+            Angstrom::executeCall::new((bundle.pade_encode_for_submission().into(),)).abi_encode();
+
+        Ok(db
+            .transact(node, TxKind::Call(self.angstrom()), call)?
+            .into_logs())
+    }
+}
+
+/// Finds a salt that has the deployment proxy deploy `initcode` to an address
+/// with exactly Angstrom's hook flags.
+fn mine_hook_address(initcode: &Bytes) -> (Address, U256) {
+    let flags = UniswapFlags::BeforeInitialize
+        | UniswapFlags::AfterInitialize
+        | UniswapFlags::BeforeAddLiquidity
+        | UniswapFlags::BeforeRemoveLiquidity
+        | UniswapFlags::BeforeSwap;
+    let init_code_hash = keccak256(initcode);
+
+    (0u64..)
+        .map(U256::from)
+        .map(|salt| (CREATE2_FACTORY.create2(B256::from(salt), init_code_hash), salt))
+        .find(|(address, _)| U160::from_be_bytes(address.0 .0) & UniswapFlags::mask() == flags)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use alloy::sol_types::SolEvent;
+    use angstrom_types::{
+        contract_bindings::angstrom::Angstrom::PoolKey,
+        matching::{uniswap::LiqRange, Ray},
+        primitive::AngstromSigner,
+        sol_bindings::RawPoolOrder
+    };
+
+    use super::*;
+    use crate::type_generator::{
+        amm::AMMSnapshotBuilder,
+        consensus::{pool::Pool, proposal::ProposalBuilder}
+    };
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn matcher_bundles_settle_on_the_contract() {
+        let node = AngstromSigner::random();
+        let block = 10;
+        let mut env = RevmAngstromEnv::new(vec![node.address()], block).unwrap();
+
+        let (token0, token1) = env.deploy_token_pair().unwrap();
+        let price = SqrtPriceX96::at_tick(100000).unwrap();
+        let liquidity = 1_000_000_000_000_000_u128;
+        env.add_pool(token0, token1, 10, price, 99000, 101000, liquidity)
+            .unwrap();
+
+        let amm = AMMSnapshotBuilder::new(price)
+            .with_positions(vec![LiqRange::new(99000, 101000, liquidity).unwrap()])
+            .build();
+        let key = PoolKey {
+            currency0:   token0,
+            currency1:   token1,
+            fee:         U24::ZERO,
+            tickSpacing: I24::unchecked_from(10),
+            hooks:       env.angstrom()
+        };
+        let pool = Pool::new(key, amm.clone(), env.controller());
+        let proposal = ProposalBuilder::new()
+            .for_pools(vec![pool.clone()])
+            .order_count(10)
+            .preproposal_count(1)
+            .with_secret_key(node.clone())
+            .for_block(block + 1)
+            .build();
+
+        let pre_proposals = proposal.flattened_pre_proposals();
+        let limit = pre_proposals
+            .iter()
+            .flat_map(|pre| &pre.limit)
+            .map(|order| (order.order_id.hash, order))
+            .collect::<HashMap<_, _>>();
+        let searchers = pre_proposals
+            .iter()
+            .flat_map(|pre| &pre.searcher)
+            .collect::<Vec<_>>();
+        for order in limit.values() {
+            env.fund(order.from(), order.token_in()).unwrap();
+        }
+        for order in &searchers {
+            env.fund(order.from(), order.token_in()).unwrap();
+        }
+
+        let pools = HashMap::from([(pool.id(), (token0, token1, amm, 0))]);
+        let bundle = || {
+            let gas = BundleGasDetails::new(
+                HashMap::from([(
+                    (token0, token1),
+                    Ray::from(SqrtPriceX96::at_tick(-100000).unwrap())
+                )]),
+                16415544926496907170
+            );
+            AngstromBundle::from_proposal(&proposal, gas, &pools, &BundleFees::default()).unwrap()
+        };
+
+        env.simulate_bundle(node.address(), bundle()).await.unwrap();
+
+        // everyone the solutions fill, and nobody else, pays angstrom for their order
+        let paid = env
+            .execute_bundle(node.address(), &bundle())
+            .unwrap()
+            .iter()
+            .filter_map(|log| MintableMockERC20::Transfer::decode_log_data(&log.data, true).ok())
+            .filter(|transfer| transfer.to == env.angstrom() && transfer.from != env.pool_manager())
+            .map(|transfer| transfer.from)
+            .collect::<HashSet<_>>();
+        let filled = proposal
+            .solutions
+            .iter()
+            .flat_map(|solution| {
+                let limit = solution
+                    .limit
+                    .iter()
+                    .filter(|outcome| outcome.is_filled())
+                    .map(|outcome| limit[&outcome.id.hash].from());
+                let searcher = solution.searcher.as_ref().map(|order| order.from());
+
+                limit.chain(searcher).collect::<Vec<_>>()
+            })
+            .collect::<HashSet<_>>();
+
+        assert!(!filled.is_empty());
+        assert_eq!(paid, filled);
+    }
+}
@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use alloy::providers::{ext::AnvilApi, Provider};
+use alloy_primitives::BlockNumber;
+use consensus::{
+    rounds::ConsensusPhase, ConsensusHandle, ConsensusQueryHandle, ConsensusRoundInfo
+};
+
+use crate::contracts::anvil::WalletProviderRpc;
+
+/// How the testnet anvil produces blocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MiningSchedule {
+    /// anvil mines a block on its own every interval
+    Interval(Duration),
+    /// blocks are only mined by the [`BlockTimeController`]
+    Manual
+}
+
+/// Drives block production of the testnet anvil from the consensus rounds of
+/// the nodes, so a test can mine the next block as soon as every node got to
+/// a phase instead of sleeping for a block time and hoping they did.
+#[derive(Clone)]
+pub struct BlockTimeController {
+    provider:      WalletProviderRpc,
+    nodes:         Vec<ConsensusQueryHandle>,
+    poll_interval: Duration,
+    /// how long to wait for the nodes to reach a phase
+    timeout:       Duration
+}
+
+impl BlockTimeController {
+    pub fn new(provider: WalletProviderRpc, nodes: Vec<ConsensusQueryHandle>) -> Self {
+        Self {
+            provider,
+            nodes,
+            poll_interval: Duration::from_millis(50),
+            timeout: Duration::from_secs(30)
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub async fn set_schedule(&self, schedule: MiningSchedule) -> eyre::Result<()> {
+        match schedule {
+            MiningSchedule::Interval(interval) => {
+                self.provider
+                    .anvil_set_interval_mining(interval.as_secs().max(1))
+                    .await?
+            }
+            MiningSchedule::Manual => {
+                // an interval of zero turns interval mining off
+                self.provider.anvil_set_interval_mining(0).await?;
+                self.provider.anvil_set_auto_mine(false).await?;
+            }
+        }
+        tracing::debug!(?schedule, "set testnet mining schedule");
+
+        Ok(())
+    }
+
+    /// Mines `blocks` blocks right away, returns the new tip.
+    pub async fn mine(&self, blocks: u64) -> eyre::Result<BlockNumber> {
+        self.provider.anvil_mine(Some(blocks), None).await?;
+        Ok(self.provider.get_block_number().await?)
+    }
+
+    /// Waits until every node is at `phase`, or past it, in the round of the
+    /// current tip.
+    pub async fn wait_for_phase(&self, phase: ConsensusPhase) -> eyre::Result<()> {
+        let wait = async {
+            loop {
+                let tip = self.provider.get_block_number().await?;
+                let rounds =
+                    futures::future::join_all(self.nodes.iter().map(|node| node.round_info()))
+                        .await;
+                if all_reached(&rounds, tip, phase) {
+                    return eyre::Ok(())
+                }
+
+                tokio::time::sleep(self.poll_interval).await;
+            }
+        };
+
+        tokio::time::timeout(self.timeout, wait)
+            .await
+            .map_err(|_| eyre::eyre!("nodes didn't reach {phase:?} within {:?}", self.timeout))?
+    }
+
+    /// Mines a block once every node reached `phase`, returns the new tip.
+    pub async fn mine_at_phase(&self, phase: ConsensusPhase) -> eyre::Result<BlockNumber> {
+        self.wait_for_phase(phase).await?;
+        self.mine(1).await
+    }
+
+    /// Mines `blocks` blocks, each one once every node reached `phase` of the
+    /// round before it.
+    pub async fn run_rounds(&self, phase: ConsensusPhase, blocks: u64) -> eyre::Result<()> {
+        for _ in 0..blocks {
+            let tip = self.mine_at_phase(phase).await?;
+            tracing::debug!(tip, ?phase, "mined testnet block at phase");
+        }
+
+        Ok(())
+    }
+}
+
+/// Nodes that aren't running consensus never reach a phase.
+fn all_reached(
+    rounds: &[Option<ConsensusRoundInfo>],
+    tip: BlockNumber,
+    phase: ConsensusPhase
+) -> bool {
+    rounds.iter().all(|round| {
+        round
+            .as_ref()
+            .is_some_and(|r| r.height == tip && r.phase >= phase)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::primitive::PeerId;
+
+    use super::*;
+
+    fn round(height: BlockNumber, phase: ConsensusPhase) -> Option<ConsensusRoundInfo> {
+        Some(ConsensusRoundInfo {
+            height,
+            phase,
+            leader: PeerId::default(),
+            is_leader: false,
+            pre_proposals: 0,
+            pre_proposal_aggregations: 0,
+            last_proposal: None
+        })
+    }
+
+    #[test]
+    fn waits_for_every_node_in_the_round_of_the_tip() {
+        let rounds = [
+            round(10, ConsensusPhase::Proposal),
+            round(10, ConsensusPhase::PreProposalAggregation)
+        ];
+        assert!(all_reached(&rounds, 10, ConsensusPhase::PreProposalAggregation));
+        assert!(!all_reached(&rounds, 10, ConsensusPhase::Proposal));
+
+        // a node still in the round of the previous block
+        let behind = [round(10, ConsensusPhase::Proposal), round(9, ConsensusPhase::Finalization)];
+        assert!(!all_reached(&behind, 10, ConsensusPhase::BidAggregation));
+
+        let stopped = [round(10, ConsensusPhase::Proposal), None];
+        assert!(!all_reached(&stopped, 10, ConsensusPhase::BidAggregation));
+    }
+}
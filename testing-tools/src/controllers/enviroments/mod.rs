@@ -216,6 +216,32 @@ where
         Ok(out == self.peers.len() - 1)
     }
 
+    /// passes every consensus message a peer receives to `tap` before handing
+    /// it on to the peer's consensus manager.
+    pub fn tap_consensus_events(
+        &self,
+        tap: impl Fn(u64, &StromConsensusEvent) + Clone + Send + 'static
+    ) {
+        self.peers.iter().for_each(|(id, peer)| {
+            let (tap_tx, mut tap_rx) = metered_unbounded_channel("consensus tap");
+            let Some(consensus_tx) =
+                peer.strom_network_manager_mut(|net| net.swap_consensus_manager(tap_tx))
+            else {
+                return
+            };
+
+            let (id, tap) = (*id, tap.clone());
+            tokio::spawn(async move {
+                while let Some(event) = tap_rx.next().await {
+                    tap(id, &event);
+                    if consensus_tx.send(event).is_err() {
+                        break
+                    }
+                }
+            });
+        });
+    }
+
     /// if id is None, then a random id is used
     async fn run_event<'a, F, O>(&'a self, id: Option<u64>, f: F) -> O::Output
     where
@@ -9,11 +9,12 @@ use futures::{Future, StreamExt};
 use reth_chainspec::Hardforks;
 use reth_provider::{BlockReader, ChainSpecProvider, HeaderProvider, ReceiptProvider};
 use reth_tasks::TaskExecutor;
+use tokio_util::sync::CancellationToken;
 
 use super::AngstromTestnet;
 use crate::{
     agents::AgentConfig,
-    controllers::strom::TestnetNode,
+    controllers::{strom::TestnetNode, BlockTimeController},
     providers::{AnvilInitializer, AnvilProvider, TestnetBlockProvider, WalletProvider},
     types::{
         config::{TestingNodeConfig, TestnetConfig},
@@ -57,6 +58,18 @@ where
         Ok(this)
     }
 
+    /// Drives the blocks of the testnet from the consensus rounds of its
+    /// nodes. Has to be taken before the testnet is run.
+    pub fn block_time_controller(&self) -> BlockTimeController {
+        let nodes = self
+            .peers
+            .values()
+            .map(|peer| peer.consensus_query())
+            .collect();
+
+        BlockTimeController::new(self.get_peer(0).state_provider().rpc_provider(), nodes)
+    }
+
     pub async fn run_to_completion(mut self, executor: TaskExecutor) {
         let all_peers = std::mem::take(&mut self.peers).into_values().map(|peer| {
             executor.spawn_critical_blocking(
@@ -68,6 +81,40 @@ where
         let _ = futures::future::select_all(all_peers).await;
     }
 
+    /// runs the testnet until `stop` resolves, then shuts down every node.
+    /// Errors if a node exits before that.
+    pub async fn run_until<R>(
+        mut self,
+        stop: impl Future<Output = eyre::Result<R>>
+    ) -> eyre::Result<R> {
+        let shutdown = CancellationToken::new();
+        let runtime = tokio::runtime::Handle::current();
+        let all_peers = std::mem::take(&mut self.peers)
+            .into_values()
+            .map(|peer| {
+                let (shutdown, runtime) = (shutdown.clone(), runtime.clone());
+                tokio::task::spawn_blocking(move || {
+                    runtime.block_on(async move {
+                        tokio::select! {
+                            _ = peer.testnet_future() => {}
+                            _ = shutdown.cancelled() => {}
+                        }
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let out = tokio::select! {
+            out = stop => out,
+            _ = futures::future::select_all(all_peers) => {
+                Err(eyre::eyre!("a testnet node exited before the run finished"))
+            }
+        };
+        shutdown.cancel();
+
+        out
+    }
+
     async fn spawn_new_testnet_nodes<F>(&mut self, c: C, agents: Vec<F>) -> eyre::Result<()>
     where
         F: for<'a> Fn(
@@ -1,3 +1,5 @@
+mod block_time;
+pub use block_time::*;
 pub mod enviroments;
 pub mod strom;
 
@@ -1,6 +1,7 @@
 use std::{pin::Pin, sync::Arc};
 
-use alloy_rpc_types::{BlockId, Transaction};
+use alloy::providers::Provider;
+use alloy_rpc_types::{BlockId, BlockNumberOrTag, BlockTransactionsKind};
 use angstrom::components::StromHandles;
 use angstrom_eth::handle::Eth;
 use angstrom_network::{pool_manager::PoolHandle, PoolManagerBuilder, StromNetworkHandle};
@@ -14,13 +15,14 @@ use angstrom_types::{
     sol_bindings::testnet::TestnetHub,
     testnet::InitialTestnetState
 };
-use consensus::{AngstromValidator, ConsensusManager, ManagerNetworkDeps};
+use consensus::{AngstromValidator, ConsensusManager, ConsensusQueryHandle, ManagerNetworkDeps};
 use futures::{Future, Stream, StreamExt, TryStreamExt};
 use jsonrpsee::server::ServerBuilder;
 use matching_engine::{configure_uniswap_manager, manager::MatcherHandle, MatchingManager};
 use order_pool::{order_storage::OrderStorage, PoolConfig};
 use reth_provider::{BlockNumReader, CanonStateSubscriptions};
 use reth_tasks::TokioTaskExecutor;
+use tokio::sync::mpsc::unbounded_channel;
 use tokio_stream::wrappers::BroadcastStream;
 use tracing::{span, Instrument};
 use validation::{
@@ -28,12 +30,13 @@ use validation::{
     validator::ValidationClient
 };
 
+use super::{TestnetApiServer, TestnetRpc};
 use crate::{
     agents::AgentConfig,
     contracts::anvil::WalletProviderRpc,
     providers::{
         utils::StromContractInstance, AnvilEthDataCleanser, AnvilProvider, AnvilStateProvider,
-        AnvilSubmissionProvider, WalletProvider
+        AnvilSubmissionProvider, TestnetBlock, TestnetFaucet, WalletProvider
     },
     types::{
         config::TestingNodeConfig, GlobalTestingConfig, SendingStromHandles, WithWalletProvider
@@ -47,7 +50,8 @@ pub struct AngstromDevnetNodeInternals<P> {
     pub order_storage:    Arc<OrderStorage>,
     pub pool_handle:      PoolHandle,
     pub tx_strom_handles: SendingStromHandles,
-    pub testnet_hub:      StromContractInstance
+    pub testnet_hub:      StromContractInstance,
+    pub consensus_query:  ConsensusQueryHandle
 }
 
 impl<P: WithWalletProvider> AngstromDevnetNodeInternals<P> {
@@ -57,7 +61,7 @@ impl<P: WithWalletProvider> AngstromDevnetNodeInternals<P> {
         strom_handles: StromHandles,
         strom_network_handle: StromNetworkHandle,
         initial_validators: Vec<AngstromValidator>,
-        block_rx: BroadcastStream<(u64, Vec<Transaction>)>,
+        block_rx: BroadcastStream<TestnetBlock>,
         inital_angstrom_state: InitialTestnetState,
         agents: Vec<F>,
         block_sync: GlobalBlockSync
@@ -80,15 +84,16 @@ impl<P: WithWalletProvider> AngstromDevnetNodeInternals<P> {
         let validation_client = ValidationClient(strom_handles.validator_tx);
         let matching_handle = MatchingManager::spawn(executor.clone(), validation_client.clone());
 
-        let order_api = OrderApi::new(pool.clone(), executor.clone(), validation_client.clone());
+        let (consensus_query_tx, consensus_query_rx) = unbounded_channel();
+        let consensus_query = ConsensusQueryHandle::new(consensus_query_tx);
+        let order_api = OrderApi::new(pool.clone(), executor.clone(), consensus_query.clone());
 
-        let block_subscription: Pin<
-            Box<dyn Stream<Item = (u64, Vec<Transaction>)> + Unpin + Send>
-        > = if node_config.is_devnet() {
-            Box::pin(block_rx.into_stream().map(|v| v.unwrap()))
-        } else {
-            Box::pin(state_provider.subscribe_blocks().await?)
-        };
+        let block_subscription: Pin<Box<dyn Stream<Item = TestnetBlock> + Unpin + Send>> =
+            if node_config.is_devnet() {
+                Box::pin(block_rx.into_stream().map(|v| v.unwrap()))
+            } else {
+                Box::pin(state_provider.subscribe_blocks().await?)
+            };
 
         let block_number = BlockNumReader::best_block_number(&state_provider.state_provider())?;
         block_sync.set_block(block_number);
@@ -97,6 +102,11 @@ impl<P: WithWalletProvider> AngstromDevnetNodeInternals<P> {
             node_config.node_id,
             executor.clone(),
             inital_angstrom_state.angstrom_addr,
+            inital_angstrom_state
+                .pool_keys
+                .iter()
+                .flat_map(|key| [key.currency0, key.currency1])
+                .collect(),
             strom_handles.eth_tx,
             strom_handles.eth_rx,
             block_subscription,
@@ -188,7 +198,8 @@ impl<P: WithWalletProvider> AngstromDevnetNodeInternals<P> {
             ids: uniswap_registry.pools().keys().cloned().collect::<Vec<_>>(),
             ..Default::default()
         };
-        let order_storage = Arc::new(OrderStorage::new(&pool_config));
+        let order_storage =
+            Arc::new(OrderStorage::new(&pool_config).with_view(strom_handles.order_view.clone()));
 
         let pool_handle = PoolManagerBuilder::new(
             validator.client.clone(),
@@ -214,8 +225,17 @@ impl<P: WithWalletProvider> AngstromDevnetNodeInternals<P> {
 
         let addr = server.local_addr()?;
 
+        let mut rpc_module = order_api.into_rpc();
+        // a devnet node runs its own anvil, tokens minted there would never
+        // reach the other nodes
+        if !node_config.is_devnet() {
+            let faucet =
+                TestnetFaucet::new(state_provider.wallet_provider(), &inital_angstrom_state);
+            rpc_module.merge(TestnetRpc::new(Arc::new(faucet)).into_rpc())?;
+        }
+
         tokio::spawn(async move {
-            let server_handle = server.start(order_api.into_rpc());
+            let server_handle = server.start(rpc_module);
             tracing::info!("rpc server started on: {}", addr);
             let _ = server_handle.stopped().await;
         });
@@ -237,6 +257,13 @@ impl<P: WithWalletProvider> AngstromDevnetNodeInternals<P> {
 
         tracing::debug!("created mev boost provider");
 
+        let leader_seed = state_provider
+            .rpc_provider()
+            .get_block_by_number(BlockNumberOrTag::Earliest, BlockTransactionsKind::Hashes)
+            .await?
+            .map(|block| block.header.hash)
+            .unwrap_or_default();
+
         let consensus = ConsensusManager::new(
             ManagerNetworkDeps::new(
                 strom_network_handle.clone(),
@@ -247,6 +274,7 @@ impl<P: WithWalletProvider> AngstromDevnetNodeInternals<P> {
             ),
             node_config.angstrom_signer(),
             initial_validators,
+            leader_seed,
             order_storage.clone(),
             block_number,
             inital_angstrom_state.angstrom_addr,
@@ -255,7 +283,8 @@ impl<P: WithWalletProvider> AngstromDevnetNodeInternals<P> {
             mev_boost_provider,
             matching_handle,
             block_sync.clone()
-        );
+        )
+        .with_query_channel(consensus_query_rx);
 
         // init agents
         let agent_config = AgentConfig {
@@ -284,7 +313,8 @@ impl<P: WithWalletProvider> AngstromDevnetNodeInternals<P> {
                 order_storage,
                 pool_handle,
                 tx_strom_handles,
-                testnet_hub
+                testnet_hub,
+                consensus_query
             },
             consensus,
             validator
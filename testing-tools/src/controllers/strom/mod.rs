@@ -2,3 +2,5 @@ mod node;
 pub use node::*;
 mod internals;
 pub use internals::*;
+mod testnet_rpc;
+pub use testnet_rpc::*;
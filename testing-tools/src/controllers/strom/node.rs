@@ -17,7 +17,7 @@ use angstrom_types::{
     sol_bindings::{grouped_orders::AllOrders, testnet::random::RandomValues},
     testnet::InitialTestnetState
 };
-use consensus::{AngstromValidator, ConsensusManager};
+use consensus::{AngstromValidator, ConsensusManager, ConsensusQueryHandle};
 use futures::Future;
 use matching_engine::manager::MatcherHandle;
 use parking_lot::RwLock;
@@ -37,7 +37,7 @@ use crate::{
     contracts::anvil::WalletProviderRpc,
     controllers::TestnetStateFutureLock,
     network::{EthPeerPool, TestnetNodeNetwork},
-    providers::AnvilProvider,
+    providers::{AnvilProvider, TestnetBlock},
     types::{config::TestingNodeConfig, GlobalTestingConfig, WithWalletProvider}
 };
 
@@ -67,7 +67,7 @@ where
         state_provider: AnvilProvider<P>,
         initial_validators: Vec<AngstromValidator>,
         inital_angstrom_state: InitialTestnetState,
-        block_provider: BroadcastStream<(u64, Vec<alloy_rpc_types::Transaction>)>,
+        block_provider: BroadcastStream<TestnetBlock>,
         agents: Vec<F>,
         block_sync: GlobalBlockSync
     ) -> eyre::Result<Self>
@@ -271,6 +271,12 @@ where
         !self.state_lock.consensus_state()
     }
 
+    /// Read access into the consensus round of the node, usable once the
+    /// node is running.
+    pub fn consensus_query(&self) -> ConsensusQueryHandle {
+        self.strom.consensus_query.clone()
+    }
+
     /// Testing Utils
     /// -------------------------------------
 
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+use alloy_primitives::{Address, U256};
+use angstrom_rpc::{invalid_params_rpc_err, rpc_err};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+
+use crate::providers::{LiquidityPosition, TestnetFaucet};
+
+/// Testnet only methods, served next to the order api of a testnet node.
+#[rpc(server, namespace = "testnet")]
+#[async_trait::async_trait]
+pub trait TestnetApi {
+    /// Mints `amount` of every testnet token to `account` and approves Angstrom
+    /// to pull them. Returns the tokens.
+    #[method(name = "fundAccount")]
+    async fn fund_account(&self, account: Address, amount: U256) -> RpcResult<Vec<Address>>;
+
+    /// Adds liquidity at the given ticks to the pool of the two tokens.
+    #[method(name = "seedLiquidity")]
+    async fn seed_liquidity(
+        &self,
+        currency0: Address,
+        currency1: Address,
+        positions: Vec<LiquidityPosition>
+    ) -> RpcResult<()>;
+}
+
+pub struct TestnetRpc {
+    faucet: Arc<TestnetFaucet>
+}
+
+impl TestnetRpc {
+    pub fn new(faucet: Arc<TestnetFaucet>) -> Self {
+        Self { faucet }
+    }
+}
+
+#[async_trait::async_trait]
+impl TestnetApiServer for TestnetRpc {
+    async fn fund_account(&self, account: Address, amount: U256) -> RpcResult<Vec<Address>> {
+        self.faucet
+            .fund(account, amount)
+            .await
+            .map_err(internal_err)
+    }
+
+    async fn seed_liquidity(
+        &self,
+        currency0: Address,
+        currency1: Address,
+        positions: Vec<LiquidityPosition>
+    ) -> RpcResult<()> {
+        let Some(pool_key) = self.faucet.pool_key(currency0, currency1).cloned() else {
+            return Err(invalid_params_rpc_err(format!(
+                "no testnet pool for {currency0} and {currency1}"
+            )))
+        };
+        if let Some(position) = positions
+            .iter()
+            .find(|position| position.lower_tick >= position.upper_tick)
+        {
+            return Err(invalid_params_rpc_err(format!(
+                "lower tick {} isn't below upper tick {}",
+                position.lower_tick, position.upper_tick
+            )))
+        }
+
+        self.faucet
+            .seed_liquidity(&pool_key, &positions)
+            .await
+            .map_err(internal_err)
+    }
+}
+
+fn internal_err(error: eyre::Report) -> jsonrpsee::types::ErrorObjectOwned {
+    rpc_err(jsonrpsee::types::error::INTERNAL_ERROR_CODE, error.to_string(), None)
+}
@@ -0,0 +1,115 @@
+//! End to end latency benchmarks. Orders are submitted to a testnet node over
+//! rpc and timed until they show up in a pre-proposal received by any node.
+//!
+//! Only the first node submits orders. As a node's own pre-proposal never
+//! reaches its network manager, a scenario needs at least two nodes to observe
+//! anything.
+
+mod recorder;
+mod report;
+
+use std::{future::Future, ops::Range, pin::Pin, time::Instant};
+
+use angstrom_client::AngstromClient;
+use angstrom_eth::manager::ChainExt;
+use angstrom_types::{sol_bindings::grouped_orders::AllOrders, testnet::InitialTestnetState};
+use futures::StreamExt;
+use jsonrpsee::http_client::HttpClient;
+pub use recorder::*;
+pub use report::*;
+use reth_provider::{CanonStateNotification, CanonStateSubscriptions};
+use tracing::{span, Instrument, Level};
+
+use crate::{
+    agents::AgentConfig,
+    order_generator::{GeneratedPoolOrders, OrderGenerator}
+};
+
+const PARTIAL_PCT_RANGE: Range<f64> = 0.1..0.6;
+
+/// Agent that submits `book_size` book orders and a searcher order per pool on
+/// each of the next `blocks` blocks, recording them in `recorder`.
+pub fn latency_agent(
+    recorder: LatencyRecorder,
+    book_size: usize,
+    blocks: u64
+) -> impl for<'a> Fn(
+    &'a InitialTestnetState,
+    AgentConfig
+) -> Pin<Box<dyn Future<Output = eyre::Result<()>> + Send + 'a>>
+       + Clone {
+    move |_, agent_config| {
+        let recorder = recorder.clone();
+        Box::pin(async move {
+            if agent_config.agent_id != 0 {
+                return Ok(())
+            }
+
+            let agent_id = agent_config.agent_id;
+            let client = AngstromClient::http(format!("http://{}", agent_config.rpc_address))?;
+            tokio::spawn(
+                submit_orders(client, agent_config, recorder, book_size, blocks).instrument(span!(
+                    Level::ERROR,
+                    "latency agent",
+                    ?agent_id
+                ))
+            );
+
+            Ok(())
+        })
+    }
+}
+
+async fn submit_orders(
+    client: AngstromClient<HttpClient>,
+    agent_config: AgentConfig,
+    recorder: LatencyRecorder,
+    book_size: usize,
+    blocks: u64
+) {
+    let mut generator = OrderGenerator::new(
+        agent_config.uniswap_pools.clone(),
+        agent_config.current_block,
+        book_size..book_size + 1,
+        PARTIAL_PCT_RANGE
+    );
+    let mut new_blocks = agent_config
+        .state_provider
+        .canonical_state_stream()
+        .map(|notification| match notification {
+            CanonStateNotification::Commit { new } | CanonStateNotification::Reorg { new, .. } => {
+                new.tip_number()
+            }
+        })
+        .take(blocks as usize);
+
+    while let Some(block_number) = new_blocks.next().await {
+        generator.new_block(block_number);
+        let orders = generator
+            .generate_orders()
+            .into_iter()
+            .flat_map(|GeneratedPoolOrders { tob, book, .. }| {
+                book.into_iter().map(Into::into).chain(Some(tob.into()))
+            })
+            .collect::<Vec<AllOrders>>();
+        let hashes = orders.iter().map(AllOrders::order_hash).collect::<Vec<_>>();
+
+        tracing::info!(block_number, orders = orders.len(), "submitting orders");
+        recorder.record_submission(Instant::now(), hashes.iter().copied());
+        match client.send_orders(orders).await {
+            Ok(results) => recorder.record_rejection(
+                hashes
+                    .into_iter()
+                    .zip(results)
+                    .filter(|(_, result)| !result.is_valid())
+                    .map(|(hash, _)| hash)
+            ),
+            Err(e) => {
+                tracing::warn!(%e, "failed to submit orders");
+                recorder.record_rejection(hashes);
+            }
+        }
+    }
+
+    recorder.finish_submitting();
+}
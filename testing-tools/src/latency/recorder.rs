@@ -0,0 +1,135 @@
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant}
+};
+
+use alloy_primitives::B256;
+use angstrom_types::consensus::PreProposal;
+use parking_lot::Mutex;
+
+use super::{LatencyScenario, LatencyStats, ScenarioReport};
+
+/// Timestamps orders when they are submitted over rpc and when they first
+/// show up in a pre-proposal received by any node of the testnet.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyRecorder {
+    inner: Arc<Mutex<RecorderInner>>
+}
+
+#[derive(Debug, Default)]
+struct RecorderInner {
+    submitted:       HashMap<B256, Instant>,
+    included:        HashMap<B256, Duration>,
+    done_submitting: bool
+}
+
+impl LatencyRecorder {
+    /// Records orders right before they are sent to the rpc. Resubmissions
+    /// keep their first timestamp.
+    pub fn record_submission(&self, sent_at: Instant, hashes: impl IntoIterator<Item = B256>) {
+        let mut inner = self.inner.lock();
+        hashes.into_iter().for_each(|hash| {
+            inner.submitted.entry(hash).or_insert(sent_at);
+        });
+    }
+
+    /// Forgets orders the rpc didn't accept. They can't be included, so they
+    /// shouldn't count as missed either.
+    pub fn record_rejection(&self, hashes: impl IntoIterator<Item = B256>) {
+        let mut inner = self.inner.lock();
+        hashes.into_iter().for_each(|hash| {
+            inner.submitted.remove(&hash);
+            inner.included.remove(&hash);
+        });
+    }
+
+    /// Marks every submitted order in the pre-proposal as included.
+    pub fn record_pre_proposal(&self, pre_proposal: &PreProposal) {
+        let now = Instant::now();
+        let mut inner = self.inner.lock();
+        let RecorderInner { submitted, included, .. } = &mut *inner;
+
+        pre_proposal
+            .limit
+            .iter()
+            .map(|order| order.order_id.hash)
+            .chain(
+                pre_proposal
+                    .searcher
+                    .iter()
+                    .map(|order| order.order_id.hash)
+            )
+            .for_each(|hash| {
+                if let Some(sent_at) = submitted.get(&hash) {
+                    included
+                        .entry(hash)
+                        .or_insert_with(|| now.saturating_duration_since(*sent_at));
+                }
+            });
+    }
+
+    /// Called by the submitter once it won't send any more orders.
+    pub fn finish_submitting(&self) {
+        self.inner.lock().done_submitting = true;
+    }
+
+    /// True once all orders have been submitted and each of them was seen in
+    /// a pre-proposal.
+    pub fn is_complete(&self) -> bool {
+        let inner = self.inner.lock();
+        inner.done_submitting && inner.included.len() == inner.submitted.len()
+    }
+
+    pub fn report(&self, scenario: LatencyScenario) -> ScenarioReport {
+        let inner = self.inner.lock();
+        let samples = inner.included.values().copied().collect::<Vec<_>>();
+        let missed = inner.submitted.len() - samples.len();
+
+        ScenarioReport { scenario, stats: LatencyStats::from_samples(samples, missed) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use angstrom_types::sol_bindings::grouped_orders::OrderWithStorageData;
+
+    use super::*;
+
+    fn pre_proposal(hashes: &[B256]) -> PreProposal {
+        let limit = hashes
+            .iter()
+            .map(|hash| {
+                let mut order = OrderWithStorageData::default();
+                order.order_id.hash = *hash;
+                order
+            })
+            .collect();
+
+        PreProposal { limit, ..Default::default() }
+    }
+
+    #[test]
+    fn only_first_inclusion_of_submitted_orders_counts() {
+        let recorder = LatencyRecorder::default();
+        let (a, b, unknown) = (B256::repeat_byte(1), B256::repeat_byte(2), B256::repeat_byte(3));
+
+        recorder.record_submission(Instant::now(), [a, b, unknown]);
+        recorder.record_rejection([unknown]);
+        recorder.finish_submitting();
+        recorder.record_pre_proposal(&pre_proposal(&[a, unknown]));
+        assert!(!recorder.is_complete());
+
+        let first = recorder.report(LatencyScenario::default()).stats;
+        assert_eq!((first.samples, first.missed), (1, 1));
+
+        std::thread::sleep(Duration::from_millis(5));
+        recorder.record_pre_proposal(&pre_proposal(&[a, b]));
+        assert!(recorder.is_complete());
+
+        let second = recorder.report(LatencyScenario::default()).stats;
+        assert_eq!((second.samples, second.missed), (2, 0));
+        assert!(second.max_ms >= 5.0);
+        assert!(second.min_ms <= first.max_ms);
+    }
+}
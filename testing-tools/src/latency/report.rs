@@ -0,0 +1,195 @@
+use std::{fmt, path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+/// The shape of the testnet a latency run was taken on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LatencyScenario {
+    pub node_count: u64,
+    /// book orders submitted per pool each block
+    pub book_size:  usize
+}
+
+impl fmt::Display for LatencyScenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} nodes, {} orders per pool", self.node_count, self.book_size)
+    }
+}
+
+/// Distribution of the time from rpc submission to pre-proposal inclusion, in
+/// milliseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub samples: usize,
+    /// orders accepted by the rpc that never made it into a pre-proposal
+    pub missed:  usize,
+    pub min_ms:  f64,
+    pub mean_ms: f64,
+    pub p50_ms:  f64,
+    pub p90_ms:  f64,
+    pub p99_ms:  f64,
+    pub max_ms:  f64
+}
+
+impl LatencyStats {
+    pub fn from_samples(mut samples: Vec<Duration>, missed: usize) -> Self {
+        if samples.is_empty() {
+            return Self { missed, ..Default::default() }
+        }
+        samples.sort_unstable();
+
+        let ms = |d: &Duration| d.as_nanos() as f64 / 1_000_000.0;
+        // nearest rank
+        let percentile = |p: f64| {
+            let rank = ((p * samples.len() as f64).ceil() as usize).clamp(1, samples.len());
+            ms(&samples[rank - 1])
+        };
+
+        Self {
+            samples: samples.len(),
+            missed,
+            min_ms: ms(&samples[0]),
+            mean_ms: samples.iter().map(ms).sum::<f64>() / samples.len() as f64,
+            p50_ms: percentile(0.5),
+            p90_ms: percentile(0.9),
+            p99_ms: percentile(0.99),
+            max_ms: ms(&samples[samples.len() - 1])
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioReport {
+    pub scenario: LatencyScenario,
+    pub stats:    LatencyStats
+}
+
+/// Machine readable output of a latency benchmark run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyReport {
+    pub scenarios: Vec<ScenarioReport>
+}
+
+impl LatencyReport {
+    pub fn load(path: impl AsRef<Path>) -> eyre::Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> eyre::Result<()> {
+        Ok(std::fs::write(path, serde_json::to_vec_pretty(self)?)?)
+    }
+
+    /// Scenarios whose p50 or p90 latency grew by more than `tolerance_pct`
+    /// percent over the baseline, or that missed orders the baseline didn't.
+    /// Scenarios missing from the baseline are skipped.
+    pub fn regressions(&self, baseline: &Self, tolerance_pct: f64) -> Vec<LatencyRegression> {
+        let factor = 1.0 + tolerance_pct / 100.0;
+        let mut regressions = vec![];
+
+        for current in &self.scenarios {
+            let Some(base) = baseline
+                .scenarios
+                .iter()
+                .find(|base| base.scenario == current.scenario)
+            else {
+                continue
+            };
+            let (new, old) = (&current.stats, &base.stats);
+
+            let checks = [
+                ("p50_ms", new.p50_ms, old.p50_ms, new.p50_ms > old.p50_ms * factor),
+                ("p90_ms", new.p90_ms, old.p90_ms, new.p90_ms > old.p90_ms * factor),
+                ("missed", new.missed as f64, old.missed as f64, new.missed > old.missed)
+            ];
+            regressions.extend(checks.into_iter().filter(|check| check.3).map(
+                |(metric, current_value, baseline_value, _)| LatencyRegression {
+                    scenario: current.scenario,
+                    metric,
+                    baseline: baseline_value,
+                    current: current_value
+                }
+            ));
+        }
+
+        regressions
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatencyRegression {
+    pub scenario: LatencyScenario,
+    pub metric:   &'static str,
+    pub baseline: f64,
+    pub current:  f64
+}
+
+impl fmt::Display for LatencyRegression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} went from {:.2} to {:.2}",
+            self.scenario, self.metric, self.baseline, self.current
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(p50_ms: f64, p90_ms: f64, missed: usize) -> LatencyReport {
+        LatencyReport {
+            scenarios: vec![ScenarioReport {
+                scenario: LatencyScenario { node_count: 3, book_size: 10 },
+                stats:    LatencyStats { p50_ms, p90_ms, missed, ..Default::default() }
+            }]
+        }
+    }
+
+    #[test]
+    fn percentiles_use_nearest_rank() {
+        let samples = (1..=100).rev().map(Duration::from_millis).collect();
+        let stats = LatencyStats::from_samples(samples, 2);
+
+        assert_eq!(stats.samples, 100);
+        assert_eq!(stats.missed, 2);
+        assert_eq!(stats.min_ms, 1.0);
+        assert_eq!(stats.p50_ms, 50.0);
+        assert_eq!(stats.p90_ms, 90.0);
+        assert_eq!(stats.p99_ms, 99.0);
+        assert_eq!(stats.max_ms, 100.0);
+        assert_eq!(stats.mean_ms, 50.5);
+        assert_eq!(LatencyStats::from_samples(vec![], 1).samples, 0);
+    }
+
+    #[test]
+    fn flags_regressions_over_tolerance() {
+        let baseline = report(100.0, 200.0, 0);
+
+        assert!(report(105.0, 209.0, 0)
+            .regressions(&baseline, 10.0)
+            .is_empty());
+
+        let regressions = report(120.0, 200.0, 1).regressions(&baseline, 10.0);
+        assert_eq!(
+            regressions
+                .iter()
+                .map(|regression| regression.metric)
+                .collect::<Vec<_>>(),
+            vec!["p50_ms", "missed"]
+        );
+
+        // unknown scenarios have nothing to regress against
+        assert!(report(1_000.0, 1_000.0, 5)
+            .regressions(&LatencyReport::default(), 10.0)
+            .is_empty());
+    }
+
+    #[test]
+    fn report_round_trips_through_json() {
+        let report = report(1.5, 2.5, 0);
+        let json = serde_json::to_string(&report).unwrap();
+
+        assert_eq!(serde_json::from_str::<LatencyReport>(&json).unwrap(), report);
+    }
+}
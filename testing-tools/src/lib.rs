@@ -6,6 +6,8 @@
 /// for example a order generator that pushes orders to the nodes rpc
 /// and then checks for fills
 pub mod agents;
+/// Order intake to pre-proposal latency benchmarks run on a testnet
+pub mod latency;
 /// mocks utils for different modules
 pub mod mocks;
 /// Tools for testing network setup
@@ -23,14 +23,12 @@ impl AnvilConsensusCanonStateNotification {
     pub fn new_block(&self, block: &Block) -> Arc<Chain> {
         let mut chain = self.chain.write();
 
-        // the consensus only uses the block number so we can use default values for the
-        // rest of the block
-        let recovered = reth_primitives::Block {
-            header: reth_primitives::Header { number: block.header.number, ..Default::default() },
-            ..Default::default()
-        }
-        .try_into_recovered()
-        .unwrap();
+        // the header is kept whole as validation prices the next block from its base
+        // fee and timestamp, the body isn't needed
+        let recovered =
+            reth_primitives::Block { header: block.header.inner.clone(), ..Default::default() }
+                .try_into_recovered()
+                .unwrap();
 
         chain.append_block(recovered, ExecutionOutcome::default());
 
@@ -1,18 +1,22 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, ops::Range, sync::Arc};
 
 use alloy_primitives::{keccak256, Address, FixedBytes};
 use angstrom_types::{
     self,
     contract_payloads::angstrom::{AngstromBundle, BundleGasDetails},
     orders::OrderOrigin,
-    sol_bindings::{ext::RawPoolOrder, grouped_orders::AllOrders}
+    sol_bindings::{
+        ext::RawPoolOrder,
+        grouped_orders::{AllOrders, StandingVariants}
+    }
 };
 use eyre::OptionExt;
 use pade::PadeEncode;
 use parking_lot::Mutex;
 use validation::{
     bundle::BundleValidatorHandle,
-    order::{GasEstimationFuture, OrderValidationResults, OrderValidatorHandle}
+    order::{GasEstimationFuture, NonceFuture, OrderValidationResults, OrderValidatorHandle},
+    ValidationError
 };
 
 // all keys are the signer of the order
@@ -65,19 +69,48 @@ impl OrderValidatorHandle for MockValidator {
         Box::pin(async move { res })
     }
 
+    fn validate_amendment(
+        &self,
+        origin: angstrom_types::orders::OrderOrigin,
+        transaction: StandingVariants,
+        _: alloy_primitives::B256
+    ) -> validation::order::ValidationFuture {
+        self.validate_order(origin, AllOrders::Standing(transaction))
+    }
+
+    // unlike validation, the order stays in the mock
+    fn simulate_order(&self, order: AllOrders) -> validation::order::ValidationFuture {
+        let address = order.from();
+        let res = self
+            .limit_orders
+            .lock()
+            .get(&address)
+            .cloned()
+            .expect("not in mock");
+        Box::pin(async move { res })
+    }
+
     fn estimate_gas(&self, order: AllOrders) -> GasEstimationFuture {
         Box::pin(async move {
             match self.validate_order(OrderOrigin::External, order).await {
                 OrderValidationResults::Valid(o) => {
                     Ok((o.priority_data.gas_units, o.priority_data.gas))
                 }
-                OrderValidationResults::Invalid(e) => Err(format!("Invalid order: {}", e)),
+                OrderValidationResults::Invalid(_, e) => Err(e),
                 OrderValidationResults::TransitionedToBlock => {
-                    Err("Order transitioned to block".to_string())
+                    Err(ValidationError::TransitionedToBlock)
                 }
             }
         })
     }
+
+    fn next_valid_nonce(&self, _: Address) -> NonceFuture<u64> {
+        Box::pin(async move { 0 })
+    }
+
+    fn invalidated_nonces(&self, _: Address, _: Range<u64>) -> NonceFuture<Vec<u64>> {
+        Box::pin(async move { vec![] })
+    }
 }
 
 impl BundleValidatorHandle for MockValidator {
@@ -5,8 +5,9 @@ use std::{collections::HashSet, sync::Arc};
 use alloy_chains::Chain;
 use angstrom_eth::manager::EthEvent;
 use angstrom_network::{
-    manager::StromConsensusEvent, state::StromState, NetworkOrderEvent, StatusState,
-    StromNetworkManager, StromProtocolHandler, StromSessionManager, Swarm, VerificationSidecar
+    manager::StromConsensusEvent, state::StromState, NetworkOrderEvent, ResumptionCache,
+    StatusState, StromNetworkManager, StromProtocolHandler, StromSessionManager, Swarm,
+    VerificationSidecar
 };
 pub use eth_peer::*;
 use parking_lot::RwLock;
@@ -68,7 +69,8 @@ impl TestnetNodeNetwork {
             status:       state,
             has_sent:     false,
             has_received: false,
-            secret_key:   node_config.angstrom_signer()
+            secret_key:   node_config.angstrom_signer(),
+            resumption:   ResumptionCache::default()
         };
 
         let validators = Arc::new(RwLock::new(HashSet::default()));
@@ -1,10 +1,18 @@
-use std::task::{Context, Poll};
+use std::{
+    collections::HashSet,
+    task::{Context, Poll}
+};
 
-use alloy::{primitives::Address, rpc::types::Transaction, sol_types::SolCall};
+use alloy::{
+    primitives::{Address, B256},
+    rpc::types::Transaction,
+    sol_types::SolCall
+};
 use alloy_rpc_types::TransactionTrait;
 use angstrom_eth::{
     handle::{EthCommand, EthHandle},
-    manager::EthEvent
+    manager::EthEvent,
+    settlement::{address_changeset, is_settlement}
 };
 use angstrom_types::{
     block_sync::{BlockSyncProducer, GlobalBlockSync},
@@ -12,15 +20,22 @@ use angstrom_types::{
     sol_bindings::testnet::TestnetHub
 };
 use futures::{Future, Stream, StreamExt};
-use pade::PadeDecode;
 use reth_tasks::TaskSpawner;
 use tokio::sync::mpsc::{Receiver, Sender, UnboundedSender};
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::{span, Instrument, Level};
 
-pub struct AnvilEthDataCleanser<S: Stream<Item = (u64, Vec<Transaction>)>> {
+use super::TestnetBlock;
+
+/// Turns the blocks anvil mines into the eth events of a node, reading the
+/// orders filled and the accounts changed from the receipts of the block like
+/// the `EthDataCleanser` does from the execution outcome.
+pub struct AnvilEthDataCleanser<S: Stream<Item = TestnetBlock>> {
     testnet_node_id:             u64,
     angstrom_contract:           Address,
+    /// the tokens of the angstrom pools, whose transfers and approvals change
+    /// the accounts of users
+    angstrom_tokens:             HashSet<Address>,
     /// our command receiver
     commander:                   ReceiverStream<EthCommand>,
     /// people listening to events
@@ -30,11 +45,13 @@ pub struct AnvilEthDataCleanser<S: Stream<Item = (u64, Vec<Transaction>)>> {
     block_sync:                  GlobalBlockSync
 }
 
-impl<S: Stream<Item = (u64, Vec<Transaction>)> + Unpin + Send + 'static> AnvilEthDataCleanser<S> {
+impl<S: Stream<Item = TestnetBlock> + Unpin + Send + 'static> AnvilEthDataCleanser<S> {
+    #[allow(clippy::too_many_arguments)]
     pub async fn spawn<TP: TaskSpawner>(
         testnet_node_id: u64,
         tp: TP,
         angstrom_contract: Address,
+        angstrom_tokens: HashSet<Address>,
         tx: Sender<EthCommand>,
         rx: Receiver<EthCommand>,
         block_subscription: S,
@@ -48,6 +65,7 @@ impl<S: Stream<Item = (u64, Vec<Transaction>)> + Unpin + Send + 'static> AnvilEt
             event_listeners: Vec::new(),
             block_subscription,
             angstrom_contract,
+            angstrom_tokens,
             block_finalization_lookback,
             block_sync
         };
@@ -78,8 +96,9 @@ impl<S: Stream<Item = (u64, Vec<Transaction>)> + Unpin + Send + 'static> AnvilEt
         }
     }
 
-    fn on_new_block(&mut self, block: (u64, Vec<Transaction>)) {
-        let (bn, txes) = block;
+    fn on_new_block(&mut self, block: TestnetBlock) {
+        let TestnetBlock { number: bn, timestamp, base_fee, transactions, receipts } = block;
+        tracing::debug!(block_number = bn, timestamp, ?base_fee, "new block");
         self.block_sync.new_block(bn);
 
         self.send_events(EthEvent::NewBlock(bn));
@@ -88,50 +107,50 @@ impl<S: Stream<Item = (u64, Vec<Transaction>)> + Unpin + Send + 'static> AnvilEt
             self.send_events(EthEvent::FinalizedBlock(bn - self.block_finalization_lookback));
         }
 
-        // find angstrom tx
-        let Some(angstrom_tx) = txes
-            .into_iter()
-            .find(|tx| tx.to() == Some(self.angstrom_contract))
-        else {
-            tracing::info!("No angstrom txs found");
-            self.send_events(EthEvent::NewBlockTransitions {
-                block_number:      block.0,
-                filled_orders:     vec![],
-                address_changeset: vec![]
-            });
-
-            return
-        };
-        let input = angstrom_tx.input();
-
-        let Ok(bytes) = TestnetHub::executeCall::abi_decode(input, false) else {
-            tracing::warn!("found angstrom contract call thats not a bundle");
-            return
-        };
-        let bytes = bytes.data.to_vec();
-        let mut slice = bytes.as_slice();
+        let receipts = receipts.iter().collect::<Vec<_>>();
+        let filled_orders = self.filled_orders(bn, &transactions, &receipts);
+        let address_changeset = address_changeset(&receipts, &self.angstrom_tokens);
+        tracing::debug!(?filled_orders, ?address_changeset, "block transitions");
 
-        // decode call input to grab orders. Drop function sig
-        let Ok(bundle) = AngstromBundle::pade_decode(&mut slice, None) else {
-            tracing::error!("failed to decode bundle");
-            return
-        };
-
-        let hashes = bundle.get_order_hashes(bn).collect::<Vec<_>>();
-
-        let addresses = vec![];
-        tracing::debug!("found angstrom tx with orders filled {:#?}", hashes);
         self.send_events(EthEvent::NewBlockTransitions {
-            block_number:      block.0,
-            filled_orders:     hashes,
-            address_changeset: addresses
+            block_number: bn,
+            filled_orders,
+            address_changeset
         });
     }
+
+    /// The orders of the bundles settled in the block. Only a transaction that
+    /// emitted the settlement log fills its orders, the testnet hub takes the
+    /// bundle wrapped in a call to `execute`.
+    fn filled_orders(
+        &self,
+        block_number: u64,
+        transactions: &[Transaction],
+        receipts: &[&reth_primitives::Receipt]
+    ) -> Vec<B256> {
+        transactions
+            .iter()
+            .zip(receipts)
+            .filter(|(tx, receipt)| {
+                tx.to() == Some(self.angstrom_contract)
+                    && is_settlement(self.angstrom_contract, receipt)
+            })
+            .filter_map(|(tx, _)| {
+                let Ok(call) = TestnetHub::executeCall::abi_decode(tx.input(), false) else {
+                    tracing::warn!("found angstrom contract call thats not a bundle");
+                    return None
+                };
+                let mut slice = call.data.as_ref();
+                AngstromBundle::pade_decode_versioned(&mut slice)
+                    .inspect_err(|e| tracing::error!(%e, "failed to decode bundle"))
+                    .ok()
+            })
+            .flat_map(|(_, bundle)| bundle.get_order_hashes(block_number).collect::<Vec<_>>())
+            .collect()
+    }
 }
 
-impl<S: Stream<Item = (u64, Vec<Transaction>)> + Unpin + Send + 'static> Future
-    for AnvilEthDataCleanser<S>
-{
+impl<S: Stream<Item = TestnetBlock> + Unpin + Send + 'static> Future for AnvilEthDataCleanser<S> {
     type Output = ();
 
     fn poll(mut self: std::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
@@ -139,7 +158,7 @@ impl<S: Stream<Item = (u64, Vec<Transaction>)> + Unpin + Send + 'static> Future
         let e = span.enter();
 
         while let Poll::Ready(Some(block)) = self.block_subscription.poll_next_unpin(cx) {
-            tracing::trace!(block_number = block.0, "received new block from anvil");
+            tracing::trace!(block_number = block.number, "received new block from anvil");
             self.on_new_block(block);
         }
         while let Poll::Ready(Some(cmd)) = self.commander.poll_next_unpin(cx) {
@@ -8,11 +8,11 @@ use alloy::{
     signers::local::PrivateKeySigner
 };
 use alloy_primitives::Bytes;
-use alloy_rpc_types::{BlockTransactionsKind, Header, Transaction};
+use alloy_rpc_types::{BlockTransactionsKind, Header};
 use angstrom_types::block_sync::GlobalBlockSync;
 use futures::{stream::FuturesUnordered, Stream, StreamExt};
 
-use super::{AnvilStateProvider, WalletProvider};
+use super::{AnvilStateProvider, TestnetBlock, WalletProvider};
 use crate::{contracts::anvil::WalletProviderRpc, types::WithWalletProvider};
 
 #[derive(Debug)]
@@ -68,8 +68,13 @@ where
         &mut self.provider
     }
 
-    pub async fn execute_and_return_state(&self) -> eyre::Result<(Bytes, Block)> {
+    pub async fn execute_and_return_state(&self) -> eyre::Result<(Bytes, TestnetBlock)> {
         let block = self.mine_block().await?;
+        let receipts = self
+            .rpc_provider()
+            .get_block_receipts(block.header.number.into())
+            .await?
+            .unwrap_or_default();
 
         Ok((
             self.provider
@@ -77,7 +82,7 @@ where
                 .rpc_provider()
                 .anvil_dump_state()
                 .await?,
-            block
+            TestnetBlock::new(block, receipts)
         ))
     }
 
@@ -118,7 +123,7 @@ where
 
     pub async fn subscribe_blocks(
         &self
-    ) -> eyre::Result<impl Stream<Item = (u64, Vec<Transaction>)> + Unpin + Send> {
+    ) -> eyre::Result<impl Stream<Item = TestnetBlock> + Unpin + Send> {
         let stream = self.rpc_provider().subscribe_blocks().await?.into_stream();
 
         Ok(StreamBlockProvider::new(self.rpc_provider(), stream))
@@ -161,7 +166,7 @@ impl AnvilProvider<WalletProvider> {
 struct StreamBlockProvider {
     provider:      WalletProviderRpc,
     header_stream: Pin<Box<dyn Stream<Item = Header> + Send>>,
-    futs:          FuturesUnordered<Pin<Box<dyn Future<Output = (u64, Vec<Transaction>)> + Send>>>
+    futs:          FuturesUnordered<Pin<Box<dyn Future<Output = TestnetBlock> + Send>>>
 }
 
 impl StreamBlockProvider {
@@ -177,19 +182,24 @@ impl StreamBlockProvider {
             .push(Box::pin(Self::make_block(self.provider.clone(), header.number)));
     }
 
-    async fn make_block(provider: WalletProviderRpc, number: u64) -> (u64, Vec<Transaction>) {
+    async fn make_block(provider: WalletProviderRpc, number: u64) -> TestnetBlock {
         let block = provider
             .get_block(number.into(), BlockTransactionsKind::Full)
             .await
             .unwrap_or_else(|_| panic!("could not get block number {number}"))
             .unwrap_or_else(|| panic!("no block found - number {number}"));
+        let receipts = provider
+            .get_block_receipts(number.into())
+            .await
+            .unwrap_or_else(|_| panic!("could not get the receipts of block number {number}"))
+            .unwrap_or_default();
 
-        (number, block.transactions.into_transactions().collect())
+        TestnetBlock::new(block, receipts)
     }
 }
 
 impl Stream for StreamBlockProvider {
-    type Item = (u64, Vec<Transaction>);
+    type Item = TestnetBlock;
 
     fn poll_next(
         self: std::pin::Pin<&mut Self>,
@@ -1,9 +1,10 @@
 use std::pin::Pin;
 
 use alloy::{
-    eips::eip2718::Encodable2718,
+    consensus::{Transaction, TxEnvelope},
+    eips::eip2718::{Decodable2718, Encodable2718},
     network::TransactionBuilder,
-    primitives::TxHash,
+    primitives::{Address, TxHash},
     providers::{ext::AnvilApi, Provider}
 };
 use alloy_rpc_types::TransactionRequest;
@@ -13,7 +14,6 @@ use angstrom_types::{
     mev_boost::SubmitTx, primitive::AngstromSigner
 };
 use futures::{Future, FutureExt, StreamExt};
-use pade::PadeDecode;
 
 use crate::contracts::anvil::WalletProviderRpc;
 
@@ -21,6 +21,32 @@ pub struct AnvilSubmissionProvider {
     pub provider: WalletProviderRpc
 }
 
+impl AnvilSubmissionProvider {
+    /// Applies all mock approvals + balances the bundle in `input` needs.
+    async fn apply_overrides(&self, angstrom_address: Address, input: &[u8]) {
+        // problem is we have abi enocded as bytes so we need to unabi incode
+        let bytes = Angstrom::executeCall::abi_decode(input, true)
+            .unwrap()
+            .encoded;
+        let vecd = bytes.to_vec();
+        let mut slice = vecd.as_slice();
+
+        let (_, bundle) = AngstromBundle::pade_decode_versioned(&mut slice).unwrap();
+        let block = self.provider.get_block_number().await.unwrap() + 1;
+        let order_overrides = bundle.fetch_needed_overrides(block);
+
+        let _ = futures::stream::iter(order_overrides.into_slots_with_overrides(angstrom_address))
+            .then(|(token, slot, value)| async move {
+                self.provider
+                    .anvil_set_storage_at(token, slot.into(), value.into())
+                    .await
+                    .expect("failed to use anvil_set_storage_at");
+            })
+            .collect::<Vec<_>>()
+            .await;
+    }
+}
+
 impl SubmitTx for AnvilSubmissionProvider {
     fn submit_transaction<'a>(
         &'a self,
@@ -33,29 +59,8 @@ impl SubmitTx for AnvilSubmissionProvider {
             // given token
 
             let data_vec = tx.input.input.clone().unwrap().to_vec();
-            let slice = data_vec.as_slice();
-            // problem is we have abi enocded as bytes so we need to unabi incode
-            let bytes = Angstrom::executeCall::abi_decode(slice, true)
-                .unwrap()
-                .encoded;
-            let vecd = bytes.to_vec();
-            let mut slice = vecd.as_slice();
-
-            let bundle = AngstromBundle::pade_decode(&mut slice, None).unwrap();
-            let block = self.provider.get_block_number().await.unwrap() + 1;
-            let order_overrides = bundle.fetch_needed_overrides(block);
             let angstrom_address = *tx.to.as_ref().unwrap().to().unwrap();
-
-            let _ =
-                futures::stream::iter(order_overrides.into_slots_with_overrides(angstrom_address))
-                    .then(|(token, slot, value)| async move {
-                        self.provider
-                            .anvil_set_storage_at(token, slot.into(), value.into())
-                            .await
-                            .expect("failed to use anvil_set_storage_at");
-                    })
-                    .collect::<Vec<_>>()
-                    .await;
+            self.apply_overrides(angstrom_address, &data_vec).await;
 
             let tx = tx.build(&signer).await.unwrap();
 
@@ -67,4 +72,17 @@ impl SubmitTx for AnvilSubmissionProvider {
         }
         .boxed()
     }
+
+    fn submit_raw_transaction<'a>(
+        &'a self,
+        encoded: &'a [u8]
+    ) -> Pin<Box<dyn Future<Output = bool> + Send + 'a>> {
+        async move {
+            let tx = TxEnvelope::decode_2718(&mut &encoded[..]).unwrap();
+            self.apply_overrides(tx.to().unwrap(), tx.input()).await;
+
+            self.provider.send_raw_transaction(encoded).await.is_ok()
+        }
+        .boxed()
+    }
 }
@@ -1,9 +1,47 @@
-use alloy_rpc_types::{Block, Transaction};
+use alloy_rpc_types::{Block, Transaction, TransactionReceipt};
 use tokio::sync::broadcast;
 use tokio_stream::wrappers::BroadcastStream;
 
+/// A block mined by anvil, with the receipts of its transactions so the logs
+/// can be read the same way they are from the execution outcome of a node.
+#[derive(Debug, Clone)]
+pub struct TestnetBlock {
+    pub number:       u64,
+    pub timestamp:    u64,
+    pub base_fee:     Option<u64>,
+    pub transactions: Vec<Transaction>,
+    /// the receipts of `transactions`, in the same order
+    pub receipts:     Vec<reth_primitives::Receipt>
+}
+
+impl TestnetBlock {
+    pub fn new(block: Block, receipts: Vec<TransactionReceipt>) -> Self {
+        let receipts = receipts
+            .into_iter()
+            .map(|receipt| reth_primitives::Receipt {
+                success: receipt.status(),
+                logs: receipt
+                    .inner
+                    .logs()
+                    .iter()
+                    .map(|log| log.inner.clone())
+                    .collect(),
+                ..Default::default()
+            })
+            .collect();
+
+        Self {
+            number: block.header.number,
+            timestamp: block.header.timestamp,
+            base_fee: block.header.base_fee_per_gas,
+            transactions: block.transactions.into_transactions().collect(),
+            receipts
+        }
+    }
+}
+
 pub struct TestnetBlockProvider {
-    tx: broadcast::Sender<(u64, Vec<Transaction>)>
+    tx: broadcast::Sender<TestnetBlock>
 }
 
 impl Default for TestnetBlockProvider {
@@ -18,14 +56,11 @@ impl TestnetBlockProvider {
         Self { tx }
     }
 
-    pub fn subscribe_to_new_blocks(&self) -> BroadcastStream<(u64, Vec<Transaction>)> {
+    pub fn subscribe_to_new_blocks(&self) -> BroadcastStream<TestnetBlock> {
         BroadcastStream::new(self.tx.subscribe())
     }
 
-    pub fn broadcast_block(&self, block: Block) {
-        let block_num = block.header.number;
-        let txs = block.transactions.as_transactions().unwrap().to_vec();
-
-        let _ = self.tx.send((block_num, txs));
+    pub fn broadcast_block(&self, block: TestnetBlock) {
+        let _ = self.tx.send(block);
     }
 }
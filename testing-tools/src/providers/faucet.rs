@@ -0,0 +1,196 @@
+use std::collections::HashSet;
+
+use alloy::{
+    primitives::aliases::I24,
+    providers::{ext::AnvilApi, PendingTransaction, PendingTransactionConfig, Provider},
+    rpc::types::TransactionRequest,
+    sol_types::SolCall
+};
+use alloy_primitives::{Address, Bytes, FixedBytes, U256};
+use angstrom_types::{
+    contract_bindings::{
+        angstrom::Angstrom::PoolKey,
+        mintable_mock_erc_20::MintableMockERC20::{self, MintableMockERC20Instance},
+        pool_gate::PoolGate::PoolGateInstance
+    },
+    testnet::InitialTestnetState
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use super::WalletProvider;
+use crate::contracts::{
+    anvil::{SafeDeployPending, WalletProviderRpc},
+    environment::TestAnvilEnvironment
+};
+
+/// Liquidity to add to a pool between two ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiquidityPosition {
+    pub lower_tick: i32,
+    pub upper_tick: i32,
+    pub liquidity:  u128
+}
+
+/// Hands out the testnet tokens and seeds the testnet pools. Tokens are minted
+/// and liquidity is added by the controller, approvals are sent as the funded
+/// account by impersonating it, so this only works against anvil.
+pub struct TestnetFaucet {
+    provider:  WalletProvider,
+    angstrom:  Address,
+    pool_gate: PoolGateInstance<(), WalletProviderRpc>,
+    pool_keys: Vec<PoolKey>,
+    /// the pool gate adds liquidity to the pool of the tick spacing it was
+    /// last given, so seeding is done one pool at a time
+    seeding:   Mutex<()>
+}
+
+impl TestnetFaucet {
+    pub fn new(provider: WalletProvider, state: &InitialTestnetState) -> Self {
+        let pool_gate = PoolGateInstance::new(state.pool_gate_addr, provider.provider());
+
+        Self {
+            provider,
+            angstrom: state.angstrom_addr,
+            pool_gate,
+            pool_keys: state.pool_keys.clone(),
+            seeding: Mutex::new(())
+        }
+    }
+
+    /// The tokens of the testnet pools, every one of them is handed out.
+    pub fn tokens(&self) -> Vec<Address> {
+        let mut seen = HashSet::new();
+        self.pool_keys
+            .iter()
+            .flat_map(|key| [key.currency0, key.currency1])
+            .filter(|token| seen.insert(*token))
+            .collect()
+    }
+
+    pub fn pool_key(&self, currency0: Address, currency1: Address) -> Option<&PoolKey> {
+        self.pool_keys
+            .iter()
+            .find(|key| key.currency0 == currency0 && key.currency1 == currency1)
+    }
+
+    /// Mints `amount` of every token to `account` and lets Angstrom pull all of
+    /// it.
+    pub async fn fund(&self, account: Address, amount: U256) -> eyre::Result<Vec<Address>> {
+        let tokens = self.tokens();
+        self.mint(account, &tokens, amount).await?;
+        self.approve_angstrom(account, &tokens, amount).await?;
+
+        Ok(tokens)
+    }
+
+    pub async fn mint(
+        &self,
+        account: Address,
+        tokens: &[Address],
+        amount: U256
+    ) -> eyre::Result<()> {
+        let mut pending = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let tx = MintableMockERC20Instance::new(*token, self.provider.provider())
+                .mint(account, amount)
+                .from(self.provider.controller())
+                .deploy_pending()
+                .await?;
+            pending.push(tx);
+        }
+
+        futures::future::try_join_all(pending).await?;
+        tracing::debug!(?account, ?tokens, ?amount, "minted testnet tokens");
+
+        Ok(())
+    }
+
+    /// Approves Angstrom to pull `amount` of `tokens` from `owner`.
+    pub async fn approve_angstrom(
+        &self,
+        owner: Address,
+        tokens: &[Address],
+        amount: U256
+    ) -> eyre::Result<()> {
+        let provider = self.provider.provider_ref();
+        provider.anvil_impersonate_account(owner).await?;
+
+        let approvals = self.send_approvals(owner, tokens, amount).await;
+        provider.anvil_stop_impersonating_account(owner).await?;
+
+        futures::future::try_join_all(approvals?).await?;
+        tracing::debug!(?owner, ?tokens, ?amount, "approved angstrom");
+
+        Ok(())
+    }
+
+    async fn send_approvals(
+        &self,
+        owner: Address,
+        tokens: &[Address],
+        amount: U256
+    ) -> eyre::Result<Vec<PendingTransaction>> {
+        let provider = self.provider.provider_ref();
+        let calldata: Bytes = MintableMockERC20::approveCall::new((self.angstrom, amount))
+            .abi_encode()
+            .into();
+
+        let mut pending = Vec::with_capacity(tokens.len());
+        for token in tokens {
+            let tx = TransactionRequest::default()
+                .from(owner)
+                .to(*token)
+                .input(calldata.clone().into());
+            let hash = provider.anvil_send_impersonated_transaction(tx).await?;
+            pending.push(
+                provider
+                    .watch_pending_transaction(PendingTransactionConfig::new(hash))
+                    .await?
+            );
+        }
+
+        Ok(pending)
+    }
+
+    /// Adds `positions` to the uniswap pool of `pool_key`.
+    pub async fn seed_liquidity(
+        &self,
+        pool_key: &PoolKey,
+        positions: &[LiquidityPosition]
+    ) -> eyre::Result<()> {
+        let _seeding = self.seeding.lock().await;
+        let controller = self.provider.controller();
+
+        self.pool_gate
+            .tickSpacing(pool_key.tickSpacing)
+            .from(controller)
+            .deploy_pending()
+            .await?
+            .await?;
+
+        let mut pending = Vec::with_capacity(positions.len());
+        for position in positions {
+            let tx = self
+                .pool_gate
+                .addLiquidity(
+                    pool_key.currency0,
+                    pool_key.currency1,
+                    I24::try_from(position.lower_tick)?,
+                    I24::try_from(position.upper_tick)?,
+                    U256::from(position.liquidity),
+                    FixedBytes::<32>::default()
+                )
+                .from(controller)
+                .deploy_pending()
+                .await?;
+            pending.push(tx);
+        }
+
+        futures::future::try_join_all(pending).await?;
+        tracing::debug!(?pool_key, positions = positions.len(), "seeded testnet pool");
+
+        Ok(())
+    }
+}
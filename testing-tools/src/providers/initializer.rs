@@ -285,6 +285,7 @@ impl AnvilInitializer {
         let state = InitialTestnetState::new(
             self.angstrom_env.angstrom(),
             self.angstrom_env.pool_manager(),
+            self.angstrom_env.pool_gate(),
             Some(state_bytes),
             pool_keys.clone()
         );
@@ -313,6 +314,7 @@ impl AnvilInitializer {
         let state = InitialTestnetState::new(
             self.angstrom_env.angstrom(),
             self.angstrom_env.pool_manager(),
+            self.angstrom_env.pool_gate(),
             None,
             pool_keys.clone()
         );
@@ -12,8 +12,10 @@ pub use state_provider::*;
 mod block_provider;
 pub mod utils;
 pub use block_provider::*;
+mod faucet;
 mod initializer;
 use alloy::{node_bindings::AnvilInstance, signers::local::PrivateKeySigner};
+pub use faucet::*;
 pub use initializer::*;
 
 use crate::{
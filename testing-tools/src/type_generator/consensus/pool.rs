@@ -8,7 +8,7 @@ use angstrom_types::{
         uniswap::{LiqRange, PoolPrice, PoolSnapshot},
         SqrtPriceX96
     },
-    primitive::PoolId,
+    primitive::{PoolId, TokenPair},
     sol_bindings::grouped_orders::{GroupedVanillaOrder, OrderWithStorageData}
 };
 
@@ -106,7 +106,7 @@ impl PoolBuilder {
 }
 
 pub fn create_key(token0: Address, token1: Address, tick_spacing: i32) -> PoolKey {
-    let (currency0, currency1) = if token0 < token1 { (token0, token1) } else { (token1, token0) };
+    let (currency0, currency1) = TokenPair::new(token0, token1).tokens();
     PoolKey {
         currency0,
         currency1,
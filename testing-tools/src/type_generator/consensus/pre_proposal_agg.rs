@@ -1,6 +1,8 @@
+use std::collections::BTreeSet;
+
 use alloy_primitives::U256;
 use angstrom_types::{
-    consensus::{PreProposal, PreProposalAggregation},
+    consensus::{OrderReceipt, PreProposal, PreProposalAggregation},
     orders::OrderPriorityData,
     primitive::AngstromSigner,
     sol_bindings::{
@@ -107,10 +109,11 @@ impl PreProposalAggregationBuilder {
                     .build();
                 let price: u128 = Rng::gen(&mut rng);
                 let priority_data = OrderPriorityData {
-                    price:     U256::from(price),
-                    volume:    1,
-                    gas:       Randomizer::gen(&mut rng),
-                    gas_units: Randomizer::gen(&mut rng)
+                    price:        U256::from(price),
+                    volume:       1,
+                    gas:          Randomizer::gen(&mut rng),
+                    gas_units:    Randomizer::gen(&mut rng),
+                    validated_at: 0
                 };
                 OrderWithStorageData {
                     invalidates: vec![],
@@ -127,7 +130,15 @@ impl PreProposalAggregationBuilder {
             })
             .collect();
 
-        let pre_proposal = PreProposal::generate_pre_proposal(block, &sk, limit, searcher);
-        PreProposalAggregation::new(block, &sk, vec![pre_proposal])
+        let receipts = limit
+            .iter()
+            .map(|order| order.order_id.hash)
+            .chain(searcher.iter().map(|order| order.order_id.hash))
+            .map(OrderReceipt::now)
+            .collect();
+
+        let pre_proposal =
+            PreProposal::generate_pre_proposal(block, &sk, limit, searcher, receipts);
+        PreProposalAggregation::new(block, &sk, vec![pre_proposal], &BTreeSet::from([sk.id()]))
     }
 }
@@ -1,6 +1,6 @@
 use alloy_primitives::U256;
 use angstrom_types::{
-    consensus::PreProposal,
+    consensus::{OrderReceipt, PreProposal},
     orders::OrderPriorityData,
     primitive::AngstromSigner,
     sol_bindings::{
@@ -111,10 +111,11 @@ impl PreproposalBuilder {
                     .build();
                 let price: u128 = Rng::gen(&mut rng);
                 let priority_data = OrderPriorityData {
-                    price:     U256::from(price),
-                    volume:    1,
-                    gas:       Randomizer::gen(&mut rng),
-                    gas_units: Randomizer::gen(&mut rng)
+                    price:        U256::from(price),
+                    volume:       1,
+                    gas:          Randomizer::gen(&mut rng),
+                    gas_units:    Randomizer::gen(&mut rng),
+                    validated_at: 0
                 };
                 OrderWithStorageData {
                     invalidates: vec![],
@@ -131,7 +132,14 @@ impl PreproposalBuilder {
             })
             .collect();
 
-        PreProposal::generate_pre_proposal(block, &sk, limit, searcher)
+        let receipts = limit
+            .iter()
+            .map(|order| order.order_id.hash)
+            .chain(searcher.iter().map(|order| order.order_id.hash))
+            .map(OrderReceipt::now)
+            .collect();
+
+        PreProposal::generate_pre_proposal(block, &sk, limit, searcher, receipts)
     }
 }
 
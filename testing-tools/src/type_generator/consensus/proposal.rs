@@ -5,9 +5,10 @@ use alloy_primitives::{
     Address
 };
 use angstrom_types::{
-    consensus::{PreProposalAggregation, Proposal},
+    consensus::{BookCommitment, PreProposalAggregation, Proposal},
     contract_bindings::angstrom::Angstrom::PoolKey,
     matching::{uniswap::LiqRange, SqrtPriceX96},
+    orders::SortStrategy,
     primitive::{AngstromSigner, PoolId},
     sol_bindings::{grouped_orders::OrderWithStorageData, rpc_orders::TopOfBlockOrder}
 };
@@ -108,7 +109,8 @@ impl ProposalBuilder {
 
         let books = MatchingManager::<TokioTaskExecutor, MockValidator>::build_books(
             &preproposals[0].pre_proposals,
-            &HashMap::default()
+            &HashMap::default(),
+            SortStrategy::ByPriceByVolume
         );
         let searcher_orders: HashMap<PoolId, OrderWithStorageData<TopOfBlockOrder>> = preproposals
             .iter()
@@ -127,7 +129,22 @@ impl ProposalBuilder {
                     .unwrap()
             })
             .collect::<Vec<_>>();
-        Proposal::generate_proposal(ethereum_height, &sk, preproposals, solutions)
+        let book_commitment = BookCommitment::new(
+            preproposals
+                .iter()
+                .flat_map(|p| p.pre_proposals.iter())
+                .flat_map(|p| {
+                    p.limit
+                        .iter()
+                        .map(|order| (order.pool_id, order.order_id.hash))
+                        .chain(
+                            p.searcher
+                                .iter()
+                                .map(|order| (order.pool_id, order.order_id.hash))
+                        )
+                })
+        );
+        Proposal::generate_proposal(ethereum_height, &sk, preproposals, solutions, book_commitment)
     }
 }
 
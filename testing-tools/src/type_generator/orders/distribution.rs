@@ -5,6 +5,7 @@ use angstrom_types::{
     sol_bindings::grouped_orders::{GroupedVanillaOrder, OrderWithStorageData}
 };
 use eyre::eyre;
+use rand::{rngs::StdRng, SeedableRng};
 use rand_distr::{num_traits::ToPrimitive, Distribution, SkewNormal};
 
 use super::{DistributionParameters, UserOrderBuilder};
@@ -17,7 +18,9 @@ pub struct OrderDistributionBuilder {
     volumeparams: Option<DistributionParameters>,
     pool_id:      Option<PoolId>,
     valid_block:  Option<u64>,
-    signing_key:  Option<AngstromSigner>
+    signing_key:  Option<AngstromSigner>,
+    /// a random one is picked and logged if not set
+    seed:         Option<u64>
 }
 
 impl OrderDistributionBuilder {
@@ -61,6 +64,11 @@ impl OrderDistributionBuilder {
         Self { signing_key, ..self }
     }
 
+    /// Builds the same orders every time for the same seed and parameters.
+    pub fn seed(self, seed: u64) -> Self {
+        Self { seed: Some(seed), ..self }
+    }
+
     pub fn build(self) -> eyre::Result<Vec<OrderWithStorageData<GroupedVanillaOrder>>> {
         let order_count = self.order_count.unwrap_or_default();
         let pool_id = self.pool_id.unwrap_or_default();
@@ -73,30 +81,40 @@ impl OrderDistributionBuilder {
         let DistributionParameters { location: v_location, scale: v_scale, shape: v_shape } =
             self.volumeparams.unwrap_or_default();
 
-        // We need two RNG handles because we hand them out as a mutable
-        let mut rng = rand::thread_rng();
-        let mut rng2 = rand::thread_rng();
+        let seed = self.seed.unwrap_or_else(rand::random);
+        tracing::debug!(seed, order_count, "generating order distribution");
+        let mut rng = StdRng::seed_from_u64(seed);
 
         let price_gen = SkewNormal::new(price_location, price_scale, price_shape)
             .map_err(|e| eyre!("Error creating price distribution: {}", e))?;
         let volume_gen = SkewNormal::new(v_location, v_scale, v_shape)
             .map_err(|e| eyre!("Error creating price distribution: {}", e))?;
-        Ok(price_gen
-            .sample_iter(&mut rng)
-            .zip(volume_gen.sample_iter(&mut rng2))
-            .map(|(p, v)| {
-                UserOrderBuilder::new()
-                    .is_standing(false)
-                    .block(valid_block)
-                    .amount(v.to_u128().unwrap_or_default())
-                    .min_price(Ray::from(Uint::from(p.to_u128().unwrap_or(1_u128))))
-                    .signing_key(self.signing_key.clone())
-                    .with_storage()
-                    .pool_id(pool_id)
-                    .is_bid(self.is_bid)
-                    .build()
+        Ok((0..order_count)
+            .map(|_| {
+                let (p, v) = (price_gen.sample(&mut rng), volume_gen.sample(&mut rng));
+                flash_order(self.is_bid, pool_id, valid_block, p, v, self.signing_key.clone())
             })
-            .take(order_count)
             .collect())
     }
 }
+
+/// A flash order for `valid_block` from a sampled price and volume.
+pub(super) fn flash_order(
+    is_bid: bool,
+    pool_id: PoolId,
+    valid_block: u64,
+    price: f64,
+    volume: f64,
+    signing_key: Option<AngstromSigner>
+) -> OrderWithStorageData<GroupedVanillaOrder> {
+    UserOrderBuilder::new()
+        .is_standing(false)
+        .block(valid_block)
+        .amount(volume.to_u128().unwrap_or_default())
+        .min_price(Ray::from(Uint::from(price.to_u128().unwrap_or(1_u128))))
+        .signing_key(signing_key)
+        .with_storage()
+        .pool_id(pool_id)
+        .is_bid(is_bid)
+        .build()
+}
@@ -19,9 +19,11 @@ use rand::{rngs::ThreadRng, Rng};
 
 // mod stored;
 mod distribution;
+mod stream;
 mod tob;
 mod user;
 pub use distribution::OrderDistributionBuilder;
+pub use stream::{OrderStreamBuilder, OrderStreamEvent, TimestampedOrderEvent};
 pub use tob::ToBOrderBuilder;
 pub use user::UserOrderBuilder;
 
@@ -98,10 +100,11 @@ impl StoredOrderBuilder {
             .or(self.order.flash_block())
             .unwrap_or_default();
         let priority_data = OrderPriorityData {
-            price:     self.order.price_for_book_side(is_bid).into(),
-            volume:    self.order.max_q(),
-            gas:       U256::ZERO,
-            gas_units: 0
+            price:        self.order.price_for_book_side(is_bid).into(),
+            volume:       self.order.max_q(),
+            gas:          U256::ZERO,
+            gas_units:    0,
+            validated_at: 0
         };
         let tob_reward = self.tob_reward.unwrap_or_default();
         OrderWithStorageData {
@@ -179,7 +182,8 @@ pub fn generate_top_of_block_order(
         .quantity_out(quantity_out.unwrap_or_default())
         .build();
 
-    let priority_data = OrderPriorityData { price: U256::from(price), volume, gas, gas_units };
+    let priority_data =
+        OrderPriorityData { price: U256::from(price), volume, gas, gas_units, validated_at: 0 };
     let order_id = OrderIdBuilder::new()
         .pool_id(pool_id)
         .order_hash(order.order_hash())
@@ -201,7 +205,7 @@ pub fn generate_top_of_block_order(
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy)]
 pub struct DistributionParameters {
     pub location: f64,
     pub scale:    f64,
@@ -0,0 +1,249 @@
+use alloy::primitives::B256;
+use angstrom_types::{
+    primitive::{AngstromSigner, PoolId},
+    sol_bindings::grouped_orders::{GroupedVanillaOrder, OrderWithStorageData}
+};
+use eyre::eyre;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand_distr::{Distribution, Exp, SkewNormal};
+
+use super::{distribution::flash_order, DistributionParameters};
+
+/// A change to the book in a generated order stream.
+#[derive(Debug, Clone)]
+pub enum OrderStreamEvent {
+    New(OrderWithStorageData<GroupedVanillaOrder>),
+    Cancel {
+        order_hash: B256
+    },
+    /// the order `replaces` is cancelled and `order` takes its place, on the
+    /// same side at a new price and volume
+    Amend {
+        replaces: B256,
+        order:    OrderWithStorageData<GroupedVanillaOrder>
+    }
+}
+
+impl OrderStreamEvent {
+    /// the hash of the order the event adds or removes
+    pub fn order_hash(&self) -> B256 {
+        match self {
+            Self::New(order) | Self::Amend { order, .. } => order.order_id.hash,
+            Self::Cancel { order_hash } => *order_hash
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TimestampedOrderEvent {
+    /// milliseconds since the start of the stream
+    pub at_ms: u64,
+    pub event: OrderStreamEvent
+}
+
+/// Generates a stream of orders, cancellations and amendments for a single
+/// pool. The stream only depends on the seed and the parameters, so a failing
+/// run can be replayed from the seed it logged.
+#[derive(Debug, Clone)]
+pub struct OrderStreamBuilder {
+    seed:             Option<u64>,
+    event_count:      usize,
+    pool_id:          PoolId,
+    valid_block:      u64,
+    bid_prices:       DistributionParameters,
+    ask_prices:       DistributionParameters,
+    volumes:          DistributionParameters,
+    /// mean time between two events
+    mean_interval_ms: f64,
+    cancel_ratio:     f64,
+    amend_ratio:      f64,
+    signing_key:      Option<AngstromSigner>
+}
+
+impl Default for OrderStreamBuilder {
+    fn default() -> Self {
+        let (bid_prices, ask_prices) = DistributionParameters::crossed_at(100_000_000.0);
+        Self {
+            seed: None,
+            event_count: 0,
+            pool_id: PoolId::default(),
+            valid_block: 0,
+            bid_prices,
+            ask_prices,
+            volumes: DistributionParameters {
+                location: 1_000_000.0,
+                scale:    100_000.0,
+                shape:    0.0
+            },
+            mean_interval_ms: 100.0,
+            cancel_ratio: 0.1,
+            amend_ratio: 0.1,
+            signing_key: None
+        }
+    }
+}
+
+impl OrderStreamBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn seed(self, seed: u64) -> Self {
+        Self { seed: Some(seed), ..self }
+    }
+
+    pub fn event_count(self, event_count: usize) -> Self {
+        Self { event_count, ..self }
+    }
+
+    pub fn pool_id(self, pool_id: PoolId) -> Self {
+        Self { pool_id, ..self }
+    }
+
+    pub fn valid_block(self, valid_block: u64) -> Self {
+        Self { valid_block, ..self }
+    }
+
+    pub fn price_params(self, bids: DistributionParameters, asks: DistributionParameters) -> Self {
+        Self { bid_prices: bids, ask_prices: asks, ..self }
+    }
+
+    pub fn volume_params(self, volumes: DistributionParameters) -> Self {
+        Self { volumes, ..self }
+    }
+
+    pub fn mean_interval_ms(self, mean_interval_ms: f64) -> Self {
+        Self { mean_interval_ms, ..self }
+    }
+
+    /// The share of events that cancel or amend a resting order, as long as
+    /// there is one.
+    pub fn churn(self, cancel_ratio: f64, amend_ratio: f64) -> Self {
+        Self { cancel_ratio, amend_ratio, ..self }
+    }
+
+    pub fn signing_key(self, signing_key: Option<AngstromSigner>) -> Self {
+        Self { signing_key, ..self }
+    }
+
+    pub fn build(self) -> eyre::Result<Vec<TimestampedOrderEvent>> {
+        if self.cancel_ratio + self.amend_ratio > 1.0 {
+            return Err(eyre!("cancellations and amendments can't be more than every event"))
+        }
+        let seed = self.seed.unwrap_or_else(rand::random);
+        tracing::info!(seed, events = self.event_count, "generating order stream");
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let skew_normal = |params: DistributionParameters| {
+            SkewNormal::new(params.location, params.scale, params.shape)
+                .map_err(|e| eyre!("Error creating distribution: {}", e))
+        };
+        let bid_prices = skew_normal(self.bid_prices)?;
+        let ask_prices = skew_normal(self.ask_prices)?;
+        let volumes = skew_normal(self.volumes)?;
+        let intervals = Exp::new(1.0 / self.mean_interval_ms)
+            .map_err(|e| eyre!("Error creating interval distribution: {}", e))?;
+
+        let new_order = |rng: &mut StdRng, is_bid: bool| {
+            let prices = if is_bid { &bid_prices } else { &ask_prices };
+            let (price, volume) = (prices.sample(rng), volumes.sample(rng));
+            flash_order(
+                is_bid,
+                self.pool_id,
+                self.valid_block,
+                price,
+                volume,
+                self.signing_key.clone()
+            )
+        };
+
+        // (hash, is_bid) of the orders resting in the book
+        let mut resting: Vec<(B256, bool)> = Vec::new();
+        let mut at_ms = 0.0;
+        let mut events = Vec::with_capacity(self.event_count);
+        for _ in 0..self.event_count {
+            at_ms += intervals.sample(&mut rng);
+
+            let roll: f64 = rng.gen();
+            let event = if resting.is_empty() || roll >= self.cancel_ratio + self.amend_ratio {
+                let is_bid = rng.gen();
+                let order = new_order(&mut rng, is_bid);
+                resting.push((order.order_id.hash, is_bid));
+                OrderStreamEvent::New(order)
+            } else {
+                let (replaces, is_bid) = resting.swap_remove(rng.gen_range(0..resting.len()));
+                if roll < self.cancel_ratio {
+                    OrderStreamEvent::Cancel { order_hash: replaces }
+                } else {
+                    let order = new_order(&mut rng, is_bid);
+                    resting.push((order.order_id.hash, is_bid));
+                    OrderStreamEvent::Amend { replaces, order }
+                }
+            };
+
+            events.push(TimestampedOrderEvent { at_ms: at_ms as u64, event });
+        }
+
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    fn hashes(events: &[TimestampedOrderEvent]) -> Vec<(u64, B256)> {
+        events
+            .iter()
+            .map(|event| (event.at_ms, event.event.order_hash()))
+            .collect()
+    }
+
+    #[test]
+    fn the_same_seed_replays_the_same_stream() {
+        let stream = |seed| {
+            OrderStreamBuilder::new()
+                .seed(seed)
+                .event_count(200)
+                .churn(0.2, 0.2)
+                .build()
+                .unwrap()
+        };
+
+        assert_eq!(hashes(&stream(7)), hashes(&stream(7)));
+        assert_ne!(hashes(&stream(7)), hashes(&stream(8)));
+    }
+
+    #[test]
+    fn only_resting_orders_are_cancelled_or_amended() {
+        let events = OrderStreamBuilder::new()
+            .seed(1)
+            .event_count(500)
+            .churn(0.3, 0.3)
+            .build()
+            .unwrap();
+
+        let mut resting = HashSet::new();
+        let (mut cancels, mut amends) = (0, 0);
+        for TimestampedOrderEvent { event, .. } in &events {
+            match event {
+                OrderStreamEvent::New(order) => {
+                    resting.insert(order.order_id.hash);
+                }
+                OrderStreamEvent::Cancel { order_hash } => {
+                    assert!(resting.remove(order_hash));
+                    cancels += 1;
+                }
+                OrderStreamEvent::Amend { replaces, order } => {
+                    assert!(resting.remove(replaces));
+                    resting.insert(order.order_id.hash);
+                    amends += 1;
+                }
+            }
+        }
+        assert!(cancels > 0 && amends > 0);
+        assert!(events.windows(2).all(|w| w[0].at_ms <= w[1].at_ms));
+    }
+}
@@ -1,4 +1,4 @@
-use alloy::{primitives::Address, signers::SignerSync};
+use alloy::{primitives::Address, signers::SignerSync, sol_types::Eip712Domain};
 use angstrom_types::{
     primitive::{AngstromSigner, ANGSTROM_DOMAIN},
     sol_bindings::rpc_orders::{OmitOrderMeta, OrderMeta, TopOfBlockOrder}
@@ -13,7 +13,8 @@ pub struct ToBOrderBuilder {
     quantity_in:  Option<u128>,
     quantity_out: Option<u128>,
     valid_block:  Option<u64>,
-    signing_key:  Option<AngstromSigner>
+    signing_key:  Option<AngstromSigner>,
+    domain:       Option<Eip712Domain>
 }
 
 impl ToBOrderBuilder {
@@ -49,6 +50,11 @@ impl ToBOrderBuilder {
         Self { signing_key, ..self }
     }
 
+    /// The domain to sign under, defaults to the testnet one.
+    pub fn domain(self, domain: Eip712Domain) -> Self {
+        Self { domain: Some(domain), ..self }
+    }
+
     pub fn build(self) -> TopOfBlockOrder {
         let mut order = TopOfBlockOrder {
             asset_in: self.asset_in.unwrap_or_default(),
@@ -61,7 +67,8 @@ impl ToBOrderBuilder {
             ..Default::default()
         };
         if let Some(signer) = self.signing_key {
-            let hash = order.no_meta_eip712_signing_hash(&ANGSTROM_DOMAIN);
+            let hash =
+                order.no_meta_eip712_signing_hash(self.domain.as_ref().unwrap_or(&ANGSTROM_DOMAIN));
             let sig = signer.sign_hash_sync(&hash).unwrap();
             order.meta = OrderMeta {
                 isEcdsa:   true,
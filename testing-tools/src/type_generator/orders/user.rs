@@ -1,6 +1,7 @@
 use alloy::{
     primitives::{Address, U256},
-    signers::SignerSync
+    signers::SignerSync,
+    sol_types::Eip712Domain
 };
 use alloy_primitives::aliases::U40;
 use angstrom_types::{
@@ -31,9 +32,12 @@ pub struct UserOrderBuilder {
     asset_in:    Address,
     asset_out:   Address,
     amount:      u128,
+    /// only used by partial orders
+    min_amount:  u128,
     min_price:   Ray,
     deadline:    U256,
-    signing_key: Option<AngstromSigner>
+    signing_key: Option<AngstromSigner>,
+    domain:      Option<Eip712Domain>
 }
 
 impl UserOrderBuilder {
@@ -115,6 +119,11 @@ impl UserOrderBuilder {
         Self { amount, ..self }
     }
 
+    /// The least a partial order can be filled for
+    pub fn min_amount(self, min_amount: u128) -> Self {
+        Self { min_amount, ..self }
+    }
+
     pub fn exact_in(self, exact_in: bool) -> Self {
         Self { exact_in, ..self }
     }
@@ -131,7 +140,13 @@ impl UserOrderBuilder {
         Self { signing_key, ..self }
     }
 
+    /// The domain to sign under, defaults to the testnet one.
+    pub fn domain(self, domain: Eip712Domain) -> Self {
+        Self { domain: Some(domain), ..self }
+    }
+
     pub fn build(self) -> GroupedVanillaOrder {
+        let domain = self.domain.clone().unwrap_or(ANGSTROM_DOMAIN);
         match (self.is_standing, self.is_exact) {
             (true, true) => {
                 let mut order = ExactStandingOrder {
@@ -153,7 +168,7 @@ impl UserOrderBuilder {
                     ..Default::default()
                 };
                 if let Some(signer) = self.signing_key {
-                    let hash = order.no_meta_eip712_signing_hash(&ANGSTROM_DOMAIN);
+                    let hash = order.no_meta_eip712_signing_hash(&domain);
                     let sig = signer.sign_hash_sync(&hash).unwrap();
                     order.meta = OrderMeta {
                         isEcdsa:   true,
@@ -167,6 +182,7 @@ impl UserOrderBuilder {
                 let mut order = PartialStandingOrder {
                     asset_in: self.asset_in,
                     asset_out: self.asset_out,
+                    min_amount_in: self.min_amount,
                     max_amount_in: self.amount,
                     max_extra_fee_asset0: self.amount,
                     nonce: self.nonce,
@@ -176,7 +192,7 @@ impl UserOrderBuilder {
                     ..Default::default()
                 };
                 if let Some(signer) = self.signing_key {
-                    let hash = order.no_meta_eip712_signing_hash(&ANGSTROM_DOMAIN);
+                    let hash = order.no_meta_eip712_signing_hash(&domain);
                     let sig = signer.sign_hash_sync(&hash).unwrap();
                     order.meta = OrderMeta {
                         isEcdsa:   true,
@@ -206,7 +222,7 @@ impl UserOrderBuilder {
                     ..Default::default()
                 };
                 if let Some(signer) = self.signing_key {
-                    let hash = order.no_meta_eip712_signing_hash(&ANGSTROM_DOMAIN);
+                    let hash = order.no_meta_eip712_signing_hash(&domain);
                     let sig = signer.sign_hash_sync(&hash).unwrap();
                     order.meta = OrderMeta {
                         isEcdsa:   true,
@@ -222,13 +238,14 @@ impl UserOrderBuilder {
                     asset_in: self.asset_in,
                     asset_out: self.asset_out,
                     max_extra_fee_asset0: self.amount,
+                    min_amount_in: self.min_amount,
                     max_amount_in: self.amount,
                     min_price: *self.min_price,
                     recipient: self.recipient,
                     ..Default::default()
                 };
                 if let Some(signer) = self.signing_key {
-                    let hash = order.no_meta_eip712_signing_hash(&ANGSTROM_DOMAIN);
+                    let hash = order.no_meta_eip712_signing_hash(&domain);
                     let sig = signer.sign_hash_sync(&hash).unwrap();
                     order.meta = OrderMeta {
                         isEcdsa:   true,
@@ -5,16 +5,17 @@ use angstrom_network::{
     pool_manager::{OrderCommand, PoolHandle},
     NetworkOrderEvent
 };
-use order_pool::PoolManagerUpdate;
+use order_pool::{OrderStorageView, PoolManagerUpdate};
 use reth_metrics::common::mpsc::UnboundedMeteredSender;
-use tokio::sync::mpsc::{Sender, UnboundedSender};
+use tokio::sync::mpsc::Sender;
 
 #[derive(Clone)]
 pub struct SendingStromHandles {
     pub eth_tx:          Sender<EthCommand>,
     pub network_tx:      UnboundedMeteredSender<NetworkOrderEvent>,
-    pub orderpool_tx:    UnboundedSender<OrderCommand>,
+    pub orderpool_tx:    Sender<OrderCommand>,
     pub pool_manager_tx: tokio::sync::broadcast::Sender<PoolManagerUpdate>,
+    pub order_view:      OrderStorageView,
     // pub consensus_tx:    Sender<ConsensusMessage>,
     pub consensus_tx_op: UnboundedMeteredSender<StromConsensusEvent>
 }
@@ -23,7 +24,8 @@ impl SendingStromHandles {
     pub fn get_pool_handle(&self) -> DefaultPoolHandle {
         PoolHandle {
             manager_tx:      self.orderpool_tx.clone(),
-            pool_manager_tx: self.pool_manager_tx.clone()
+            pool_manager_tx: self.pool_manager_tx.clone(),
+            view:            self.order_view.clone()
         }
     }
 }
@@ -35,6 +37,7 @@ impl From<&StromHandles> for SendingStromHandles {
             network_tx:      value.pool_tx.clone(),
             orderpool_tx:    value.orderpool_tx.clone(),
             pool_manager_tx: value.pool_manager_tx.clone(),
+            order_view:      value.order_view.clone(),
             // consensus_tx:    value.consensus_tx.clone(),
             consensus_tx_op: value.consensus_tx_op.clone()
         }
@@ -70,10 +70,9 @@ where
 
         let handle = tokio::runtime::Handle::current();
         let thread_pool = KeySplitThreadpool::new(handle, 3);
-        let sim = SimValidation::new(db.clone(), angstrom_address, node_address);
+        let sim = SimValidation::new(db.clone(), angstrom_address, node_address, uniswap_pools);
 
-        let order_validator =
-            OrderValidator::new(sim, current_block, pool_storage, fetch, uniswap_pools).await;
+        let order_validator = OrderValidator::new(sim, current_block, pool_storage, fetch).await;
 
         let bundle_validator = BundleValidator::new(db.clone(), angstrom_address, node_address);
         let shared_utils = SharedTools::new(token_conversion, token_updates, thread_pool);